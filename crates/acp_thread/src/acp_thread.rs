@@ -2104,6 +2104,28 @@ impl AcpThread {
         })
     }
 
+    /// Restores the git working tree to the state at the given message's checkpoint (if one
+    /// exists), without rewinding the thread.
+    pub fn restore_git_checkpoint(
+        &mut self,
+        id: &UserMessageId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some((_, message)) = self.user_message_mut(id) else {
+            return Task::ready(Err(anyhow!("message not found")));
+        };
+        let Some(checkpoint) = message
+            .checkpoint
+            .as_ref()
+            .map(|c| c.git_checkpoint.clone())
+        else {
+            return Task::ready(Ok(()));
+        };
+
+        let git_store = self.project.read(cx).git_store().clone();
+        git_store.update(cx, |git, cx| git.restore_checkpoint(checkpoint, cx))
+    }
+
     /// Rewinds this thread to before the entry at `index`, removing it and all
     /// subsequent entries while rejecting any action_log changes made from that point.
     /// Unlike `restore_checkpoint`, this method does not restore from git.
@@ -2819,6 +2841,7 @@ mod tests {
                     cx,
                     vec![],
                     PathStyle::local(),
+                    None,
                 )
             })
             .await
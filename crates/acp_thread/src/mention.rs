@@ -57,6 +57,11 @@ pub enum MentionUri {
     TerminalSelection {
         line_count: u32,
     },
+    ContextServerResource {
+        server_id: String,
+        uri: Url,
+        name: String,
+    },
 }
 
 impl MentionUri {
@@ -208,6 +213,23 @@ impl MentionUri {
                         .parse::<u32>()
                         .unwrap_or(0);
                     Ok(Self::TerminalSelection { line_count })
+                } else if let Some(server_id) = path.strip_prefix("/agent/context-server-resource/")
+                {
+                    let mut uri = None;
+                    let mut name = None;
+                    for (key, value) in url.query_pairs() {
+                        match key.as_ref() {
+                            "uri" => uri = Some(Url::parse(&value)?),
+                            "name" => name = Some(value.into_owned()),
+                            _ => bail!("invalid query parameter"),
+                        }
+                    }
+                    let server_id = decode(server_id).unwrap_or(Cow::Borrowed(server_id));
+                    Ok(Self::ContextServerResource {
+                        server_id: server_id.into_owned(),
+                        uri: uri.context("Missing resource uri")?,
+                        name: name.context("Missing resource name")?,
+                    })
                 } else {
                     bail!("invalid zed url: {:?}", input);
                 }
@@ -243,6 +265,7 @@ impl MentionUri {
                 ..
             } => selection_name(path.as_deref(), line_range),
             MentionUri::Fetch { url } => url.to_string(),
+            MentionUri::ContextServerResource { name, .. } => name.clone(),
         }
     }
 
@@ -262,6 +285,7 @@ impl MentionUri {
             MentionUri::TerminalSelection { .. } => IconName::Terminal.path().into(),
             MentionUri::Selection { .. } => IconName::Reader.path().into(),
             MentionUri::Fetch { .. } => IconName::ToolWeb.path().into(),
+            MentionUri::ContextServerResource { .. } => IconName::Server.path().into(),
         }
     }
 
@@ -360,6 +384,22 @@ impl MentionUri {
                     .append_pair("lines", &line_count.to_string());
                 url
             }
+            MentionUri::ContextServerResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                let mut url = Url::parse("zed:///").unwrap();
+                url.path_segments_mut().unwrap().pop_if_empty().extend([
+                    "agent",
+                    "context-server-resource",
+                    server_id,
+                ]);
+                url.query_pairs_mut()
+                    .append_pair("uri", uri.as_str())
+                    .append_pair("name", name);
+                url
+            }
         }
     }
 }
@@ -542,6 +582,25 @@ mod tests {
         assert_eq!(parsed.to_uri().to_string(), rule_uri);
     }
 
+    #[test]
+    fn test_parse_context_server_resource_uri() {
+        let resource_uri = "zed:///agent/context-server-resource/github?uri=repo%3A%2F%2Fzed%2FREADME.md&name=README";
+        let parsed = MentionUri::parse(resource_uri, PathStyle::local()).unwrap();
+        match &parsed {
+            MentionUri::ContextServerResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                assert_eq!(server_id, "github");
+                assert_eq!(uri.as_str(), "repo://zed/README.md");
+                assert_eq!(name, "README");
+            }
+            _ => panic!("Expected ContextServerResource variant"),
+        }
+        assert_eq!(parsed.to_uri().to_string(), resource_uri);
+    }
+
     #[test]
     fn test_parse_fetch_http_uri() {
         let http_uri = "http://example.com/path?query=value#fragment";
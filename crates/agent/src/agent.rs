@@ -1,17 +1,21 @@
 mod db;
 mod edit_agent;
+mod hooks;
 mod legacy_thread;
 mod native_agent_server;
 pub mod outline;
 mod pattern_extraction;
 mod shell_parser;
 mod templates;
+mod terminal_sandbox;
 #[cfg(test)]
 mod tests;
 mod thread;
+mod thread_export;
 mod thread_store;
 mod tool_permissions;
 mod tools;
+mod usage;
 
 use context_server::ContextServerId;
 pub use db::*;
@@ -19,9 +23,11 @@ pub use native_agent_server::NativeAgentServer;
 pub use pattern_extraction::*;
 pub use templates::*;
 pub use thread::*;
+pub use thread_export::ThreadExport;
 pub use thread_store::*;
 pub use tool_permissions::*;
 pub use tools::*;
+pub use usage::{UsageCost, UsageLimitExceeded, UsageLimitScope};
 
 use acp_thread::{
     AcpThread, AgentModelSelector, AgentSessionInfo, AgentSessionList, AgentSessionListRequest,
@@ -666,10 +672,12 @@ impl NativeAgent {
     fn handle_context_server_store_updated(
         &mut self,
         _store: Entity<project::context_server_store::ContextServerStore>,
-        _event: &project::context_server_store::Event,
+        event: &project::context_server_store::Event,
         cx: &mut Context<Self>,
     ) {
-        self.update_available_commands(cx);
+        if let project::context_server_store::Event::ServerStatusChanged { .. } = event {
+            self.update_available_commands(cx);
+        }
     }
 
     fn handle_context_server_registry_event(
@@ -716,7 +724,7 @@ impl NativeAgent {
 
         registry
             .prompts()
-            .flat_map(|context_server_prompt| {
+            .map(|context_server_prompt| {
                 let prompt = &context_server_prompt.prompt;
 
                 let should_prefix = prompt_name_counts
@@ -736,22 +744,27 @@ impl NativeAgent {
                     prompt.description.clone().unwrap_or_default(),
                 );
 
-                match prompt.arguments.as_deref() {
-                    Some([arg]) => {
-                        let hint = format!("<{}>", arg.name);
+                if let Some(arguments) = prompt.arguments.as_deref()
+                    && !arguments.is_empty()
+                {
+                    let hint = arguments
+                        .iter()
+                        .map(|arg| {
+                            if arg.required.unwrap_or(false) {
+                                format!("<{}>", arg.name)
+                            } else {
+                                format!("[{}]", arg.name)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ");
 
-                        command = command.input(acp::AvailableCommandInput::Unstructured(
-                            acp::UnstructuredCommandInput::new(hint),
-                        ));
-                    }
-                    Some([]) | None => {}
-                    Some(_) => {
-                        // skip >1 argument commands since we don't support them yet
-                        return None;
-                    }
+                    command = command.input(acp::AvailableCommandInput::Unstructured(
+                        acp::UnstructuredCommandInput::new(hint),
+                    ));
                 }
 
-                Some(command)
+                command
             })
             .collect()
     }
@@ -833,6 +846,57 @@ impl NativeAgent {
         })
     }
 
+    /// Saves a new thread with the messages of a thread that precede one of its user messages,
+    /// and returns the new thread's ID. The new thread is a fork of the original one.
+    pub fn fork_thread(
+        &mut self,
+        id: acp::SessionId,
+        message_id: UserMessageId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<acp::SessionId>> {
+        let Some(session) = self.sessions.get(&id) else {
+            return Task::ready(Err(anyhow!("Session not found")));
+        };
+        if !session.thread.read(cx).is_turn_complete() {
+            return Task::ready(Err(anyhow!("Can't fork a thread while it's generating")));
+        }
+        let db_thread = session.thread.read(cx).to_db(cx);
+        let thread_store = self.thread_store.clone();
+        cx.spawn(async move |_, cx| {
+            let mut db_thread = db_thread.await;
+            let ix = db_thread
+                .messages
+                .iter()
+                .position(
+                    |message| matches!(message, Message::User(message) if message.id == message_id),
+                )
+                .context("Message not found")?;
+            db_thread.messages.truncate(ix);
+            let remaining_message_ids = db_thread
+                .messages
+                .iter()
+                .filter_map(|message| match message {
+                    Message::User(message) => Some(message.id.clone()),
+                    _ => None,
+                })
+                .collect::<HashSet<_>>();
+            db_thread
+                .request_token_usage
+                .retain(|id, _| remaining_message_ids.contains(id));
+            db_thread.updated_at = Utc::now();
+            db_thread.imported = false;
+            db_thread.parent_id = Some(id);
+
+            let fork_id = acp::SessionId::new(uuid::Uuid::new_v4().to_string());
+            thread_store
+                .update(cx, |store, cx| {
+                    store.save_thread(fork_id.clone(), db_thread, cx)
+                })
+                .await?;
+            Ok(fork_id)
+        })
+    }
+
     fn save_thread(&mut self, thread: Entity<Thread>, cx: &mut Context<Self>) {
         if thread.read(cx).is_empty() {
             return;
@@ -965,6 +1029,16 @@ impl NativeAgentConnection {
         self.0.update(cx, |this, cx| this.load_thread(id, cx))
     }
 
+    pub fn fork_thread(
+        &self,
+        id: acp::SessionId,
+        message_id: UserMessageId,
+        cx: &mut App,
+    ) -> Task<Result<acp::SessionId>> {
+        self.0
+            .update(cx, |this, cx| this.fork_thread(id, message_id, cx))
+    }
+
     fn run_turn(
         &self,
         session_id: acp::SessionId,
@@ -1112,6 +1186,31 @@ impl<'a> Command<'a> {
             })
         }
     }
+
+    /// Assigns the whitespace-separated words after the command to the prompt's arguments, in
+    /// order. The last argument receives the rest of the text, so that it can contain spaces.
+    fn arguments<'b>(
+        &self,
+        argument_names: impl ExactSizeIterator<Item = &'b str>,
+    ) -> HashMap<String, String> {
+        let argument_count = argument_names.len();
+        let mut arguments = HashMap::default();
+        let mut rest = self.arg_value.trim();
+        for (ix, name) in argument_names.enumerate() {
+            if rest.is_empty() {
+                break;
+            }
+            let value = if ix + 1 == argument_count {
+                std::mem::take(&mut rest)
+            } else {
+                let (value, remainder) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                rest = remainder.trim_start();
+                value
+            };
+            arguments.insert(name.to_string(), value.to_string());
+        }
+        arguments
+    }
 }
 
 struct NativeAgentModelSelector {
@@ -1285,18 +1384,15 @@ impl acp_thread::AgentConnection for NativeAgentConnection {
             if let Some(prompt) =
                 registry.find_prompt(explicit_server_id.as_ref(), parsed_command.prompt_name)
             {
-                let arguments = if !parsed_command.arg_value.is_empty()
-                    && let Some(arg_name) = prompt
+                let arguments = parsed_command.arguments(
+                    prompt
                         .prompt
                         .arguments
-                        .as_ref()
-                        .and_then(|args| args.first())
-                        .map(|arg| arg.name.clone())
-                {
-                    HashMap::from_iter([(arg_name, parsed_command.arg_value.to_string())])
-                } else {
-                    Default::default()
-                };
+                        .as_deref()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|arg| arg.name.as_str()),
+                );
 
                 let prompt_name = prompt.prompt.name.clone();
                 let server_id = prompt.server_id.clone();
@@ -1706,6 +1802,35 @@ mod internal_tests {
         });
     }
 
+    #[test]
+    fn test_command_arguments() {
+        let prompt = [acp::ContentBlock::Text(acp::TextContent::new(
+            "/github.issue zed-industries 1234 needs triage",
+        ))];
+        let command = Command::parse(&prompt).unwrap();
+        assert_eq!(command.explicit_server_id, Some("github"));
+        assert_eq!(command.prompt_name, "issue");
+        assert_eq!(
+            command.arguments(["owner", "number", "notes"].into_iter()),
+            HashMap::from_iter([
+                ("owner".to_string(), "zed-industries".to_string()),
+                ("number".to_string(), "1234".to_string()),
+                ("notes".to_string(), "needs triage".to_string()),
+            ])
+        );
+        assert_eq!(
+            command.arguments(["owner"].into_iter()),
+            HashMap::from_iter([(
+                "owner".to_string(),
+                "zed-industries 1234 needs triage".to_string()
+            )])
+        );
+
+        let prompt = [acp::ContentBlock::Text(acp::TextContent::new("/summarize"))];
+        let command = Command::parse(&prompt).unwrap();
+        assert_eq!(command.arguments(["owner"].into_iter()), HashMap::default());
+    }
+
     #[gpui::test]
     async fn test_listing_models(cx: &mut TestAppContext) {
         init_test(cx);
@@ -1983,6 +2108,109 @@ mod internal_tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_fork_thread(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/", json!({ "a": {} })).await;
+        let project = Project::test(fs.clone(), [path!("/a").as_ref()], cx).await;
+        let thread_store = cx.new(|cx| ThreadStore::new(cx));
+        let agent = NativeAgent::new(
+            project.clone(),
+            thread_store.clone(),
+            Templates::new(),
+            None,
+            fs.clone(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        let connection = Rc::new(NativeAgentConnection(agent.clone()));
+        let acp_thread = cx
+            .update(|cx| {
+                connection
+                    .clone()
+                    .new_thread(project.clone(), Path::new(""), cx)
+            })
+            .await
+            .unwrap();
+        let session_id = acp_thread.read_with(cx, |thread, _| thread.session_id().clone());
+        let thread = agent.read_with(cx, |agent, _| {
+            agent.sessions.get(&session_id).unwrap().thread.clone()
+        });
+        let model = Arc::new(FakeLanguageModel::default());
+        thread.update(cx, |thread, cx| thread.set_model(model.clone(), cx));
+
+        let mut message_ids = Vec::new();
+        for (ix, text) in ["One", "Two"].into_iter().enumerate() {
+            let message_id = UserMessageId::new();
+            thread
+                .update(cx, |thread, cx| thread.send(message_id.clone(), [text], cx))
+                .unwrap();
+            cx.run_until_parked();
+            model.send_last_completion_stream_text_chunk("Done.");
+            model.send_last_completion_stream_event(
+                language_model::LanguageModelCompletionEvent::UsageUpdate(
+                    language_model::TokenUsage {
+                        input_tokens: 100 * (ix as u64 + 1),
+                        output_tokens: 10,
+                        cache_creation_input_tokens: 0,
+                        cache_read_input_tokens: 0,
+                    },
+                ),
+            );
+            model.end_last_completion_stream();
+            cx.run_until_parked();
+            message_ids.push(message_id);
+        }
+
+        // Forking at the second message keeps only what precedes it.
+        let fork_id = agent
+            .update(cx, |agent, cx| {
+                agent.fork_thread(session_id.clone(), message_ids[1].clone(), cx)
+            })
+            .await
+            .unwrap();
+        assert_ne!(fork_id, session_id);
+        let fork = thread_store
+            .update(cx, |store, cx| store.load_thread(fork_id.clone(), cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fork.parent_id, Some(session_id.clone()));
+        assert!(!fork.imported);
+        assert!(matches!(
+            fork.messages.as_slice(),
+            [Message::User(message), Message::Agent(_)] if message.id == message_ids[0]
+        ));
+        assert_eq!(
+            fork.request_token_usage.keys().collect::<Vec<_>>(),
+            vec![&message_ids[0]]
+        );
+        assert_eq!(fork.request_token_usage[&message_ids[0]].input_tokens, 100);
+
+        // The source thread is left as it was.
+        thread.read_with(cx, |thread, _| {
+            assert!(thread.to_markdown().contains("## User\n\nTwo"));
+        });
+
+        // A thread can't be forked while it's generating.
+        thread
+            .update(cx, |thread, cx| {
+                thread.send(UserMessageId::new(), ["Three"], cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert!(
+            agent
+                .update(cx, |agent, cx| {
+                    agent.fork_thread(session_id.clone(), message_ids[1].clone(), cx)
+                })
+                .await
+                .is_err()
+        );
+    }
+
     fn thread_entries(
         thread_store: &Entity<ThreadStore>,
         cx: &mut TestAppContext,
@@ -29,6 +29,9 @@ pub struct DbThreadMetadata {
     #[serde(alias = "summary")]
     pub title: SharedString,
     pub updated_at: DateTime<Utc>,
+    /// The thread this thread was forked from.
+    #[serde(default)]
+    pub parent_id: Option<acp::SessionId>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -50,6 +53,9 @@ pub struct DbThread {
     pub profile: Option<AgentProfileId>,
     #[serde(default)]
     pub imported: bool,
+    /// The thread this thread was forked from.
+    #[serde(default)]
+    pub parent_id: Option<acp::SessionId>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +93,7 @@ impl SharedThread {
             model: self.model,
             profile: None,
             imported: true,
+            parent_id: None,
         }
     }
 
@@ -260,10 +267,21 @@ impl DbThread {
             model: thread.model,
             profile: thread.profile,
             imported: false,
+            parent_id: None,
         })
     }
 }
 
+/// The tokens used by one of the agent's completion requests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbTokenUsage {
+    pub thread_id: acp::SessionId,
+    pub provider: Arc<str>,
+    pub model: Arc<str>,
+    pub created_at: DateTime<Utc>,
+    pub usage: language_model::TokenUsage,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataType {
     #[serde(rename = "json")]
@@ -357,6 +375,36 @@ impl ThreadsDatabase {
         "})?()
         .map_err(|e| anyhow!("Failed to create threads table: {}", e))?;
 
+        let has_parent_id = connection.select_row::<bool>(indoc! {"
+            SELECT COUNT(*) > 0 FROM pragma_table_info('threads') WHERE name = 'parent_id'
+        "})?()?
+        .unwrap_or(false);
+        if !has_parent_id {
+            connection.exec(indoc! {"
+                ALTER TABLE threads ADD COLUMN parent_id TEXT
+            "})?()
+            .map_err(|e| anyhow!("Failed to add parent_id to threads table: {}", e))?;
+        }
+
+        // Usage is kept when threads are deleted, since their requests still count toward
+        // daily totals.
+        connection.exec(indoc! {"
+            CREATE TABLE IF NOT EXISTS token_usage (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                thread_id TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cache_creation_input_tokens INTEGER NOT NULL,
+                cache_read_input_tokens INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS token_usage_thread_id ON token_usage (thread_id);
+            CREATE INDEX IF NOT EXISTS token_usage_created_at ON token_usage (created_at);
+        "})?()
+        .map_err(|e| anyhow!("Failed to create token_usage table: {}", e))?;
+
         let db = Self {
             executor,
             connection: Arc::new(Mutex::new(connection)),
@@ -381,6 +429,7 @@ impl ThreadsDatabase {
 
         let title = thread.title.to_string();
         let updated_at = thread.updated_at.to_rfc3339();
+        let parent_id = thread.parent_id.as_ref().map(|id| id.0.clone());
         let json_data = serde_json::to_string(&SerializedThread {
             thread,
             version: DbThread::VERSION,
@@ -392,11 +441,11 @@ impl ThreadsDatabase {
         let data_type = DataType::Zstd;
         let data = compressed;
 
-        let mut insert = connection.exec_bound::<(Arc<str>, String, String, DataType, Vec<u8>)>(indoc! {"
-            INSERT OR REPLACE INTO threads (id, summary, updated_at, data_type, data) VALUES (?, ?, ?, ?, ?)
+        let mut insert = connection.exec_bound::<(Arc<str>, String, String, DataType, Vec<u8>, Option<Arc<str>>)>(indoc! {"
+            INSERT OR REPLACE INTO threads (id, summary, updated_at, data_type, data, parent_id) VALUES (?, ?, ?, ?, ?, ?)
         "})?;
 
-        insert((id.0, title, updated_at, data_type, data))?;
+        insert((id.0, title, updated_at, data_type, data, parent_id))?;
 
         Ok(())
     }
@@ -407,19 +456,20 @@ impl ThreadsDatabase {
         self.executor.spawn(async move {
             let connection = connection.lock();
 
-            let mut select =
-                connection.select_bound::<(), (Arc<str>, String, String)>(indoc! {"
-                SELECT id, summary, updated_at FROM threads ORDER BY updated_at DESC
+            let mut select = connection
+                .select_bound::<(), (Arc<str>, String, String, Option<Arc<str>>)>(indoc! {"
+                SELECT id, summary, updated_at, parent_id FROM threads ORDER BY updated_at DESC
             "})?;
 
             let rows = select(())?;
             let mut threads = Vec::new();
 
-            for (id, summary, updated_at) in rows {
+            for (id, summary, updated_at, parent_id) in rows {
                 threads.push(DbThreadMetadata {
                     id: acp::SessionId::new(id),
                     title: summary.into(),
                     updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+                    parent_id: parent_id.map(acp::SessionId::new),
                 });
             }
 
@@ -476,6 +526,114 @@ impl ThreadsDatabase {
         })
     }
 
+    pub fn record_token_usage(&self, record: DbTokenUsage) -> Task<Result<()>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+
+            let mut insert =
+                connection
+                    .exec_bound::<(Arc<str>, Arc<str>, Arc<str>, String, i64, i64, i64, i64)>(
+                        indoc! {"
+                    INSERT INTO token_usage (
+                        thread_id, provider, model, created_at, input_tokens, output_tokens,
+                        cache_creation_input_tokens, cache_read_input_tokens
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "},
+                    )?;
+
+            insert((
+                record.thread_id.0,
+                record.provider,
+                record.model,
+                record.created_at.to_rfc3339(),
+                record.usage.input_tokens as i64,
+                record.usage.output_tokens as i64,
+                record.usage.cache_creation_input_tokens as i64,
+                record.usage.cache_read_input_tokens as i64,
+            ))?;
+
+            Ok(())
+        })
+    }
+
+    /// Returns the token usage of a thread's requests.
+    pub fn thread_token_usage(&self, id: acp::SessionId) -> Task<Result<Vec<DbTokenUsage>>> {
+        self.select_token_usage(
+            indoc! {"
+                SELECT thread_id, provider, model, created_at, input_tokens, output_tokens,
+                    cache_creation_input_tokens, cache_read_input_tokens
+                FROM token_usage WHERE thread_id = ? ORDER BY created_at
+            "},
+            id.0.to_string(),
+        )
+    }
+
+    /// Returns the token usage of all requests made since the given time.
+    pub fn token_usage_since(&self, since: DateTime<Utc>) -> Task<Result<Vec<DbTokenUsage>>> {
+        self.select_token_usage(
+            indoc! {"
+                SELECT thread_id, provider, model, created_at, input_tokens, output_tokens,
+                    cache_creation_input_tokens, cache_read_input_tokens
+                FROM token_usage WHERE created_at >= ? ORDER BY created_at
+            "},
+            since.to_rfc3339(),
+        )
+    }
+
+    fn select_token_usage(
+        &self,
+        query: &'static str,
+        argument: String,
+    ) -> Task<Result<Vec<DbTokenUsage>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+            let mut select = connection.select_bound::<String, (
+                Arc<str>,
+                Arc<str>,
+                Arc<str>,
+                String,
+                i64,
+                i64,
+                i64,
+                i64,
+            )>(query)?;
+
+            select(argument)?
+                .into_iter()
+                .map(
+                    |(
+                        thread_id,
+                        provider,
+                        model,
+                        created_at,
+                        input_tokens,
+                        output_tokens,
+                        cache_creation_input_tokens,
+                        cache_read_input_tokens,
+                    )| {
+                        Ok(DbTokenUsage {
+                            thread_id: acp::SessionId::new(thread_id),
+                            provider,
+                            model,
+                            created_at: DateTime::parse_from_rfc3339(&created_at)?
+                                .with_timezone(&Utc),
+                            usage: language_model::TokenUsage {
+                                input_tokens: input_tokens as u64,
+                                output_tokens: output_tokens as u64,
+                                cache_creation_input_tokens: cache_creation_input_tokens as u64,
+                                cache_read_input_tokens: cache_read_input_tokens as u64,
+                            },
+                        })
+                    },
+                )
+                .collect()
+        })
+    }
+
     pub fn delete_threads(&self) -> Task<Result<()>> {
         let connection = self.connection.clone();
 
@@ -552,6 +710,7 @@ mod tests {
             model: None,
             profile: None,
             imported: false,
+            parent_id: None,
         }
     }
 
@@ -618,4 +777,92 @@ mod tests {
             Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()
         );
     }
+
+    #[gpui::test]
+    async fn test_forked_threads_keep_their_parent(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let parent_id = session_id("thread-a");
+        let fork_id = session_id("thread-b");
+        let mut fork = make_thread(
+            "Thread A",
+            Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+        );
+        fork.parent_id = Some(parent_id.clone());
+
+        database
+            .save_thread(
+                parent_id.clone(),
+                make_thread(
+                    "Thread A",
+                    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                ),
+            )
+            .await
+            .unwrap();
+        database.save_thread(fork_id.clone(), fork).await.unwrap();
+
+        let entries = database.list_threads().await.unwrap();
+        assert_eq!(entries[0].id, fork_id);
+        assert_eq!(entries[0].parent_id, Some(parent_id.clone()));
+        assert_eq!(entries[1].parent_id, None);
+
+        let loaded = database.load_thread(fork_id).await.unwrap().unwrap();
+        assert_eq!(loaded.parent_id, Some(parent_id));
+    }
+
+    #[gpui::test]
+    async fn test_token_usage(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let record = |thread_id: &str, day: u32, input_tokens: u64| DbTokenUsage {
+            thread_id: session_id(thread_id),
+            provider: "anthropic".into(),
+            model: "claude-sonnet-4".into(),
+            created_at: Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap(),
+            usage: language_model::TokenUsage {
+                input_tokens,
+                output_tokens: 10,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 5,
+            },
+        };
+        let records = [
+            record("thread-a", 1, 100),
+            record("thread-b", 2, 200),
+            record("thread-a", 3, 300),
+        ];
+        for record in &records {
+            database.record_token_usage(record.clone()).await.unwrap();
+        }
+
+        assert_eq!(
+            database
+                .thread_token_usage(session_id("thread-a"))
+                .await
+                .unwrap(),
+            vec![records[0].clone(), records[2].clone()]
+        );
+        assert_eq!(
+            database
+                .token_usage_since(Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap())
+                .await
+                .unwrap(),
+            vec![records[1].clone(), records[2].clone()]
+        );
+
+        // Usage outlives the threads it belongs to.
+        database
+            .delete_thread(session_id("thread-a"))
+            .await
+            .unwrap();
+        assert_eq!(
+            database
+                .thread_token_usage(session_id("thread-a"))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
 }
@@ -0,0 +1,426 @@
+//! Lifecycle hooks, configured with the `agent.hooks` setting.
+//!
+//! A hook is a shell command that runs at a point of a thread's lifecycle. It receives the
+//! event as JSON on stdin, and can steer what happens next by printing a JSON [`HookResponse`]
+//! on stdout. Any other output on stdout is added to the model's context. Exiting with status 2
+//! blocks the action, with stderr as the reason.
+//!
+//! Hooks that can block an action are policies, so when one fails, times out or prints a
+//! malformed response, the action is blocked rather than let through. Failures of other hooks
+//! are logged and ignored. `stop` hooks only notify that a turn ended: what they print is
+//! ignored, as the turn is over by then.
+
+use agent_settings::AgentHook;
+use anyhow::{Context as _, Result, anyhow};
+use futures::{AsyncWriteExt as _, FutureExt as _};
+use gpui::{App, BackgroundExecutor, Task};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, process::Stdio};
+use util::shell::{ShellKind, get_system_shell};
+
+/// The exit status with which a hook blocks the action it ran for.
+const BLOCK_EXIT_CODE: i32 = 2;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HookEvent {
+    PreToolUse {
+        tool_name: String,
+        tool_input: serde_json::Value,
+    },
+    PostToolUse {
+        tool_name: String,
+        tool_input: serde_json::Value,
+        tool_output: String,
+        is_error: bool,
+    },
+    UserPromptSubmit {
+        prompt: String,
+    },
+    /// The end of a turn, which hooks can't respond to.
+    Stop,
+}
+
+impl HookEvent {
+    fn tool_name(&self) -> Option<&str> {
+        match self {
+            HookEvent::PreToolUse { tool_name, .. } | HookEvent::PostToolUse { tool_name, .. } => {
+                Some(tool_name)
+            }
+            HookEvent::UserPromptSubmit { .. } | HookEvent::Stop => None,
+        }
+    }
+
+    fn is_notification(&self) -> bool {
+        matches!(self, HookEvent::Stop)
+    }
+
+    fn can_block(&self) -> bool {
+        matches!(
+            self,
+            HookEvent::PreToolUse { .. } | HookEvent::UserPromptSubmit { .. }
+        )
+    }
+}
+
+/// What a hook receives on stdin.
+#[derive(Debug, Serialize)]
+struct HookInput<'a> {
+    session_id: &'a str,
+    cwd: Option<&'a PathBuf>,
+    #[serde(flatten)]
+    event: &'a HookEvent,
+}
+
+/// What a hook can print on stdout. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HookResponse {
+    /// Whether to let the action happen. Only tool calls and prompts can be blocked.
+    decision: Option<HookDecision>,
+    /// Why the action was blocked, which is shown to the model.
+    reason: Option<String>,
+    /// For `pre_tool_use` hooks, the input the tool runs with instead.
+    updated_input: Option<serde_json::Value>,
+    /// Text added to the model's context, after the tool result or the prompt.
+    additional_context: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum HookDecision {
+    Allow,
+    Block,
+}
+
+/// The combined effect of the hooks that ran for an event.
+#[derive(Debug, Default, PartialEq)]
+pub struct HookOutcome {
+    /// The reason a hook gave for blocking the action, if one did.
+    pub blocked: Option<String>,
+    /// The rewritten input of a tool call.
+    pub updated_input: Option<serde_json::Value>,
+    pub additional_context: Vec<String>,
+}
+
+impl HookOutcome {
+    pub fn additional_context(&self) -> Option<String> {
+        (!self.additional_context.is_empty()).then(|| self.additional_context.join("\n\n"))
+    }
+}
+
+/// Where hooks run, and the thread they run for.
+#[derive(Clone, Debug)]
+pub struct HookContext {
+    pub session_id: String,
+    /// The project's root directory, for local projects.
+    pub cwd: Option<PathBuf>,
+}
+
+/// Returns the hooks that run for the given event.
+pub fn hooks_for_event(hooks: &[AgentHook], event: &HookEvent) -> Vec<AgentHook> {
+    hooks
+        .iter()
+        .filter(|hook| {
+            event
+                .tool_name()
+                .is_none_or(|tool_name| hook.applies_to_tool(tool_name))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Runs the given hooks for an event, one after the other, until one blocks the action.
+///
+/// A hook that rewrites the input of a tool call passes the new input on to the next hooks.
+pub fn run_hooks(
+    hooks: Vec<AgentHook>,
+    mut event: HookEvent,
+    context: HookContext,
+    cx: &App,
+) -> Task<HookOutcome> {
+    if hooks.is_empty() {
+        return Task::ready(HookOutcome::default());
+    }
+
+    let executor = cx.background_executor().clone();
+    cx.background_spawn(async move {
+        let mut outcome = HookOutcome::default();
+        for hook in hooks {
+            let response = match run_hook(&hook, &event, &context, &executor).await {
+                Ok(response) => response,
+                Err(error) => {
+                    log::warn!("Agent hook `{}` failed: {error:#}", hook.command);
+                    if event.can_block() {
+                        outcome.blocked =
+                            Some(format!("Hook `{}` failed: {error:#}", hook.command));
+                        break;
+                    }
+                    continue;
+                }
+            };
+            if event.is_notification() {
+                continue;
+            }
+
+            if let Some(context) = response.additional_context {
+                outcome.additional_context.push(context);
+            }
+            if let Some(updated_input) = response.updated_input
+                && let HookEvent::PreToolUse { tool_input, .. } = &mut event
+            {
+                *tool_input = updated_input.clone();
+                outcome.updated_input = Some(updated_input);
+            }
+            if response.decision == Some(HookDecision::Block) {
+                let reason = response
+                    .reason
+                    .unwrap_or_else(|| format!("Blocked by hook `{}`", hook.command));
+                if event.can_block() {
+                    outcome.blocked = Some(reason);
+                    break;
+                } else {
+                    outcome.additional_context.push(reason);
+                }
+            }
+        }
+        outcome
+    })
+}
+
+async fn run_hook(
+    hook: &AgentHook,
+    event: &HookEvent,
+    context: &HookContext,
+    executor: &BackgroundExecutor,
+) -> Result<HookResponse> {
+    let input = serde_json::to_vec(&HookInput {
+        session_id: &context.session_id,
+        cwd: context.cwd.as_ref(),
+        event,
+    })?;
+
+    let mut command = util::command::new_smol_command(get_system_shell());
+    command
+        .args(ShellKind::system().args_for_shell(false, hook.command.clone()))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(cwd) = &context.cwd {
+        command.current_dir(cwd);
+    }
+    let mut child = command.spawn().context("spawning the hook")?;
+    let stdin = child.stdin.take();
+    let write_input = async move {
+        // Hooks don't have to read their input, so a closed stdin isn't an error.
+        if let Some(mut stdin) = stdin {
+            stdin.write_all(&input).await.ok();
+            stdin.close().await.ok();
+        }
+    };
+
+    // Write the input while reading the output, so that neither side blocks on a full pipe.
+    let output = futures::select! {
+        (_, output) = futures::future::join(write_input, child.output()).fuse() => output?,
+        _ = executor.timer(hook.timeout).fuse() => {
+            return Err(anyhow!("timed out after {:?}", hook.timeout));
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = stdout.trim();
+    if output.status.code() == Some(BLOCK_EXIT_CODE) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.trim();
+        return Ok(HookResponse {
+            decision: Some(HookDecision::Block),
+            reason: (!reason.is_empty()).then(|| reason.to_string()),
+            ..Default::default()
+        });
+    }
+    if !output.status.success() {
+        return Err(anyhow!(
+            "exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    if stdout.is_empty() {
+        Ok(HookResponse::default())
+    } else if stdout.starts_with('{') {
+        serde_json::from_str(stdout).context("parsing the hook's response")
+    } else {
+        Ok(HookResponse {
+            additional_context: Some(stdout.to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn hook(command: &str) -> AgentHook {
+        AgentHook {
+            command: command.to_string(),
+            tools: None,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    fn context() -> HookContext {
+        HookContext {
+            session_id: "session-1".to_string(),
+            cwd: None,
+        }
+    }
+
+    fn pre_tool_use() -> HookEvent {
+        HookEvent::PreToolUse {
+            tool_name: "terminal".to_string(),
+            tool_input: json!({ "command": "rm -rf build" }),
+        }
+    }
+
+    fn post_tool_use() -> HookEvent {
+        HookEvent::PostToolUse {
+            tool_name: "terminal".to_string(),
+            tool_input: json!({ "command": "cargo build" }),
+            tool_output: "Finished".to_string(),
+            is_error: false,
+        }
+    }
+
+    #[gpui::test]
+    async fn test_hook_receives_event_on_stdin(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let outcome = cx
+            .update(|cx| {
+                run_hooks(
+                    vec![hook("printf 'Input: '; cat")],
+                    pre_tool_use(),
+                    context(),
+                    cx,
+                )
+            })
+            .await;
+        let input: serde_json::Value = serde_json::from_str(
+            outcome
+                .additional_context()
+                .unwrap()
+                .strip_prefix("Input: ")
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            input,
+            json!({
+                "session_id": "session-1",
+                "cwd": null,
+                "event": "pre_tool_use",
+                "tool_name": "terminal",
+                "tool_input": { "command": "rm -rf build" },
+            })
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hooks_block_and_rewrite_input(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let rewrite = hook(
+            r#"echo '{"updated_input": {"command": "rm -rf ./build"}, "additional_context": "Rewrote the path"}'"#,
+        );
+        let block = hook("grep -q 'rm -rf ./build' && echo 'No deleting builds' >&2 && exit 2");
+        let never_runs = hook("echo 'should not run'");
+        let outcome = cx
+            .update(|cx| {
+                run_hooks(
+                    vec![rewrite, block, never_runs],
+                    pre_tool_use(),
+                    context(),
+                    cx,
+                )
+            })
+            .await;
+        assert_eq!(
+            outcome,
+            HookOutcome {
+                blocked: Some("No deleting builds".to_string()),
+                updated_input: Some(json!({ "command": "rm -rf ./build" })),
+                additional_context: vec!["Rewrote the path".to_string()],
+            }
+        );
+
+        // Only tool calls and prompts can be blocked. Otherwise the reason becomes context.
+        let block = hook(r#"echo '{"decision": "block", "reason": "Run the tests"}'"#);
+        let outcome = cx
+            .update(|cx| run_hooks(vec![block.clone()], post_tool_use(), context(), cx))
+            .await;
+        assert_eq!(outcome.blocked, None);
+        assert_eq!(
+            outcome.additional_context,
+            vec!["Run the tests".to_string()]
+        );
+
+        // Stop hooks only notify that the turn ended.
+        let outcome = cx
+            .update(|cx| run_hooks(vec![block], HookEvent::Stop, context(), cx))
+            .await;
+        assert_eq!(outcome, HookOutcome::default());
+    }
+
+    #[gpui::test]
+    async fn test_failing_hooks(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        // Hooks that fail can't let prompts or tool calls through.
+        for failing_hook in ["exit 1", "echo '{not json'"] {
+            let outcome = cx
+                .update(|cx| {
+                    run_hooks(
+                        vec![hook("echo ok"), hook(failing_hook), hook("echo never runs")],
+                        HookEvent::UserPromptSubmit {
+                            prompt: "Hi".to_string(),
+                        },
+                        context(),
+                        cx,
+                    )
+                })
+                .await;
+            assert!(
+                outcome.blocked.as_ref().is_some_and(
+                    |reason| reason.starts_with(&format!("Hook `{failing_hook}` failed: "))
+                ),
+                "{outcome:?}"
+            );
+            assert_eq!(outcome.additional_context, vec!["ok".to_string()]);
+        }
+
+        // Other hooks that fail are ignored.
+        let outcome = cx
+            .update(|cx| {
+                run_hooks(
+                    vec![hook("exit 1"), hook("echo '{not json'"), hook("echo ok")],
+                    post_tool_use(),
+                    context(),
+                    cx,
+                )
+            })
+            .await;
+        assert_eq!(
+            outcome,
+            HookOutcome {
+                additional_context: vec!["ok".to_string()],
+                ..Default::default()
+            }
+        );
+    }
+}
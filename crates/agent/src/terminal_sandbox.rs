@@ -0,0 +1,390 @@
+//! Runs the commands of the terminal tool in an OS-level sandbox, configured per agent profile.
+//!
+//! On Linux, commands run under [bubblewrap](https://github.com/containers/bubblewrap), which
+//! uses namespaces to mount the whole file system read-only, except for the project's worktrees,
+//! a private temporary directory and the profile's `writable_paths`, and optionally to cut off
+//! the network. The host's runtime directories are replaced with empty ones, so that commands
+//! can't reach the sockets of services like D-Bus, systemd or Docker, which would let them act
+//! outside of the sandbox. Bubblewrap is required: there is no fallback to Landlock, which can't cut off
+//! the network on most kernels. Without `bwrap`, on other platforms and in remote projects,
+//! commands fail instead of running unsandboxed.
+
+use agent_settings::TerminalSandbox;
+use anyhow::{Result, anyhow};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+use util::shell::ShellKind;
+
+/// Where a sandboxed command can write to.
+#[derive(Clone, Debug)]
+pub struct SandboxPolicy {
+    pub writable_paths: Vec<PathBuf>,
+    pub allow_network: bool,
+    /// The user's runtime directory, `$XDG_RUNTIME_DIR`, which is hidden like `/run`.
+    pub runtime_dir: Option<PathBuf>,
+    /// Sockets outside of the runtime directories that are hidden from commands.
+    pub hidden_sockets: Vec<PathBuf>,
+}
+
+impl SandboxPolicy {
+    pub fn new(sandbox: &TerminalSandbox, worktree_paths: Vec<PathBuf>) -> Self {
+        let mut writable_paths = worktree_paths;
+        writable_paths.extend(sandbox.writable_paths.iter().cloned());
+        Self {
+            writable_paths,
+            allow_network: sandbox.allow_network,
+            runtime_dir: std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from),
+            hidden_sockets: vec![paths::terminal_sessions_socket().clone()],
+        }
+    }
+
+    /// Returns the command that runs `command` with `shell` in the sandbox, given the path of
+    /// the `bwrap` executable.
+    pub fn wrap_command(&self, bwrap: &str, shell: &str, command: &str) -> Result<String> {
+        let mut args = vec![
+            bwrap.to_string(),
+            "--die-with-parent".into(),
+            // Without a new session, commands could inject input into the terminal with TIOCSTI.
+            "--new-session".into(),
+            "--unshare-ipc".into(),
+            "--unshare-pid".into(),
+            "--unsetenv".into(),
+            "DBUS_SESSION_BUS_ADDRESS".into(),
+            "--ro-bind".into(),
+            "/".into(),
+            "/".into(),
+            // Keep the terminal's pty, and devices like `/dev/null`, available.
+            "--dev-bind".into(),
+            "/dev".into(),
+            "/dev".into(),
+            "--proc".into(),
+            "/proc".into(),
+            "--tmpfs".into(),
+            "/tmp".into(),
+            "--tmpfs".into(),
+            "/run".into(),
+        ];
+        if let Some(runtime_dir) = &self.runtime_dir {
+            args.extend(["--tmpfs".into(), runtime_dir.to_string_lossy().to_string()]);
+        }
+        if self.allow_network {
+            // `/etc/resolv.conf` is often a link to a file of systemd-resolved in `/run`.
+            if let Ok(resolv_conf) = std::fs::canonicalize("/etc/resolv.conf")
+                && resolv_conf.starts_with("/run")
+            {
+                let path = resolv_conf.to_string_lossy();
+                args.extend(["--ro-bind".into(), path.to_string(), path.to_string()]);
+            }
+        } else {
+            args.push("--unshare-net".into());
+        }
+        for path in &self.writable_paths {
+            let path = path.to_string_lossy();
+            args.extend(["--bind-try".into(), path.to_string(), path.to_string()]);
+        }
+        for socket in self.hidden_sockets.iter().filter(|socket| socket.exists()) {
+            args.extend([
+                "--ro-bind-try".into(),
+                "/dev/null".into(),
+                socket.to_string_lossy().to_string(),
+            ]);
+        }
+        args.extend([
+            "--".into(),
+            shell.to_string(),
+            "-c".into(),
+            command.to_string(),
+        ]);
+
+        let args = args
+            .iter()
+            .map(|arg| {
+                ShellKind::Posix
+                    .try_quote(arg)
+                    .ok_or_else(|| anyhow!("Couldn't quote {arg:?} for the sandbox"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(args.join(" "))
+    }
+
+    /// Guesses from a failed command's output whether it failed because of the sandbox. Only
+    /// errors for writes that the sandbox denies, `EROFS` or `EACCES` on a path that isn't
+    /// writable in it, count as write violations.
+    pub fn violation(&self, output: &str) -> Option<SandboxViolation> {
+        const WRITE_ERRORS: &[&str] = &["Read-only file system", "Permission denied"];
+        const NETWORK_ERRORS: &[&str] = &[
+            "Network is unreachable",
+            "Could not resolve host",
+            "Temporary failure in name resolution",
+            "Name or service not known",
+        ];
+
+        let denied_path = output
+            .lines()
+            .filter(|line| WRITE_ERRORS.iter().any(|error| line.contains(error)))
+            .flat_map(absolute_paths)
+            .find(|path| !self.is_writable(path));
+        if let Some(path) = denied_path {
+            Some(SandboxViolation::Write {
+                path: path.to_path_buf(),
+                writable_paths: self.writable_paths.clone(),
+            })
+        } else if !self.allow_network && NETWORK_ERRORS.iter().any(|error| output.contains(error)) {
+            Some(SandboxViolation::Network)
+        } else {
+            None
+        }
+    }
+
+    fn is_writable(&self, path: &Path) -> bool {
+        // Besides the writable paths, the sandbox has its own `/tmp` and runtime directories, and
+        // binds `/dev` and `/proc` as they are.
+        ["/tmp", "/run", "/dev", "/proc"]
+            .iter()
+            .map(Path::new)
+            .chain(self.runtime_dir.as_deref())
+            .chain(self.writable_paths.iter().map(PathBuf::as_path))
+            .any(|writable_path| path.starts_with(writable_path))
+    }
+}
+
+/// Returns the absolute paths that an error message mentions, for messages that quote paths,
+/// like `touch: cannot touch '/etc/x': Read-only file system`, or end them with a colon, like
+/// `sh: /etc/x: Read-only file system`.
+fn absolute_paths(line: &str) -> impl Iterator<Item = &Path> {
+    const QUOTES: &[char] = &['\'', '"', '`', '‘', '’', '“', '”'];
+    line.match_indices('/').filter_map(move |(start, _)| {
+        let preceding = line[..start].chars().next_back();
+        if preceding.is_some_and(|c| !c.is_whitespace() && !QUOTES.contains(&c) && c != '(') {
+            return None;
+        }
+        let len = line[start..]
+            .find(|c: char| c == ':' || c == ',' || c == ')' || QUOTES.contains(&c))
+            .unwrap_or(line.len() - start);
+        Some(Path::new(line[start..start + len].trim_end()))
+    })
+}
+
+/// An action of a command that the sandbox prevented.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SandboxViolation {
+    Write {
+        path: PathBuf,
+        writable_paths: Vec<PathBuf>,
+    },
+    Network,
+}
+
+impl fmt::Display for SandboxViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SandboxViolation::Write {
+                path,
+                writable_paths,
+            } => {
+                writeln!(
+                    f,
+                    "Sandbox violation: the command tried to write to {}, outside of the paths it is allowed to write to:",
+                    path.display()
+                )?;
+                for path in writable_paths {
+                    writeln!(f, "- {}", path.display())?;
+                }
+                write!(
+                    f,
+                    "- /tmp, which is private to the command.\n\n\
+                    Don't try to work around the sandbox. Change the command to only write to these paths, \
+                    or ask the user to allow writing to other paths with the `terminal_sandbox.writable_paths` setting of their agent profile."
+                )
+            }
+            SandboxViolation::Network => write!(
+                f,
+                "Sandbox violation: the command tried to access the network, which the sandbox doesn't allow.\n\n\
+                Don't try to work around the sandbox. Run the command without network access, \
+                or ask the user to allow it with the `terminal_sandbox.allow_network` setting of their agent profile."
+            ),
+        }
+    }
+}
+
+/// Returns the path of the `bwrap` executable, or an error explaining why commands can't be
+/// sandboxed.
+pub fn find_bwrap() -> Result<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return Err(anyhow!(
+            "The terminal sandbox is only supported on Linux. Disable `terminal_sandbox` in the agent profile to run commands."
+        ));
+    }
+    which::which("bwrap").map_err(|_| {
+        anyhow!(
+            "The terminal sandbox requires bubblewrap (`bwrap`), which wasn't found. Install it, or disable `terminal_sandbox` in the agent profile to run commands."
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow_network: bool) -> SandboxPolicy {
+        SandboxPolicy {
+            runtime_dir: Some(PathBuf::from("/run/user/1000")),
+            hidden_sockets: Vec::new(),
+            ..SandboxPolicy::new(
+                &TerminalSandbox {
+                    allow_network,
+                    writable_paths: vec![PathBuf::from("/home/user/.cargo")],
+                },
+                vec![PathBuf::from("/home/user/my project")],
+            )
+        }
+    }
+
+    #[test]
+    fn test_wrap_command() {
+        let command = policy(true)
+            .wrap_command("/usr/bin/bwrap", "/bin/bash", "cargo test")
+            .unwrap();
+        assert!(command.starts_with(
+            "/usr/bin/bwrap --die-with-parent --new-session --unshare-ipc --unshare-pid \
+             --unsetenv DBUS_SESSION_BUS_ADDRESS --ro-bind / / --dev-bind /dev /dev --proc /proc \
+             --tmpfs /tmp --tmpfs /run --tmpfs /run/user/1000 "
+        ));
+        assert!(
+            command.contains(" --bind-try /home/user/.cargo /home/user/.cargo -- /bin/bash -c ")
+        );
+        assert!(!command.contains("--unshare-net"));
+
+        let command = policy(false)
+            .wrap_command("bwrap", "sh", "curl example.com")
+            .unwrap();
+        assert!(command.contains(" --unshare-net -- sh -c "));
+    }
+
+    #[test]
+    fn test_violation() {
+        let write_violation = |path: &str| {
+            Some(SandboxViolation::Write {
+                path: PathBuf::from(path),
+                writable_paths: vec![
+                    PathBuf::from("/home/user/my project"),
+                    PathBuf::from("/home/user/.cargo"),
+                ],
+            })
+        };
+        assert_eq!(
+            policy(true).violation("touch: cannot touch '/etc/x': Read-only file system"),
+            write_violation("/etc/x")
+        );
+        assert_eq!(
+            policy(true).violation("sh: 1: cannot create /usr/lib/x: Permission denied"),
+            write_violation("/usr/lib/x")
+        );
+        assert_eq!(
+            policy(true)
+                .violation("error: failed to open `/opt/x`: Permission denied (os error 13)"),
+            write_violation("/opt/x")
+        );
+        // Errors for paths the sandbox lets the command write to aren't caused by it.
+        assert_eq!(
+            policy(true).violation(
+                "touch: cannot touch '/home/user/my project/x': Permission denied\n\
+                 mkdir: cannot create directory ‘/tmp/x’: Read-only file system"
+            ),
+            None
+        );
+        assert_eq!(
+            policy(true).violation("touch: cannot touch 'x': Read-only file system"),
+            None
+        );
+        assert_eq!(
+            policy(true).violation("mounting /etc/x failed: No such file or directory"),
+            None
+        );
+        assert_eq!(
+            policy(false).violation("curl: (6) Could not resolve host: example.com"),
+            Some(SandboxViolation::Network)
+        );
+        assert_eq!(
+            policy(true).violation("curl: (6) Could not resolve host: example.com"),
+            None
+        );
+        assert_eq!(policy(false).violation("error: test failed"), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_bwrap_sandbox() {
+        // Bubblewrap isn't always installed, or allowed to create namespaces, where tests run.
+        let Ok(bwrap) = find_bwrap() else {
+            return;
+        };
+        let usable = std::process::Command::new(&bwrap)
+            .args(["--ro-bind", "/", "/", "true"])
+            .status()
+            .is_ok_and(|status| status.success());
+        if !usable {
+            return;
+        }
+
+        // Outside of `/tmp`, which the sandbox replaces with its own.
+        let writable = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let read_only = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        // A stand-in for the user's runtime directory, with a session bus socket.
+        let runtime_dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let bus = runtime_dir.path().join("bus");
+        let _bus_listener = std::os::unix::net::UnixListener::bind(&bus).unwrap();
+        let policy = SandboxPolicy {
+            runtime_dir: Some(runtime_dir.path().to_path_buf()),
+            ..SandboxPolicy::new(
+                &TerminalSandbox {
+                    allow_network: true,
+                    writable_paths: Vec::new(),
+                },
+                vec![writable.path().to_path_buf()],
+            )
+        };
+        let run = |command: &str| {
+            let command = policy
+                .wrap_command(&bwrap.to_string_lossy(), "sh", command)
+                .unwrap();
+            std::process::Command::new("sh")
+                .args(["-c", &command])
+                .env(
+                    "DBUS_SESSION_BUS_ADDRESS",
+                    format!("unix:path={}", bus.display()),
+                )
+                .output()
+                .unwrap()
+        };
+
+        // The session bus can't be reached, neither through its socket nor its address.
+        assert!(bus.exists());
+        let output = run(&format!(
+            "test ! -e '{}' && test -z \"$DBUS_SESSION_BUS_ADDRESS\"",
+            bus.display()
+        ));
+        assert!(output.status.success(), "{output:?}");
+
+        let output = run(&format!(
+            "touch '{}/allowed' /tmp/allowed",
+            writable.path().display()
+        ));
+        assert!(output.status.success(), "{output:?}");
+        assert!(writable.path().join("allowed").exists());
+
+        let blocked = read_only.path().join("blocked");
+        let output = run(&format!("touch '{}'", blocked.display()));
+        assert!(!output.status.success());
+        assert!(!blocked.exists());
+        assert_eq!(
+            policy.violation(&String::from_utf8_lossy(&output.stderr)),
+            Some(SandboxViolation::Write {
+                path: blocked,
+                writable_paths: vec![writable.path().to_path_buf()],
+            })
+        );
+    }
+}
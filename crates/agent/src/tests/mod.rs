@@ -20,7 +20,7 @@ use futures::{
     future::{Fuse, Shared},
 };
 use gpui::{
-    App, AppContext, AsyncApp, Entity, Task, TestAppContext, UpdateGlobal,
+    App, AppContext, AsyncApp, Entity, Task, TestAppContext, UpdateGlobal, WeakEntity,
     http_client::FakeHttpClient,
 };
 use indoc::indoc;
@@ -254,7 +254,11 @@ async fn test_terminal_tool_timeout_kills_handle(cx: &mut TestAppContext) {
     });
 
     #[allow(clippy::arc_with_non_send_sync)]
-    let tool = Arc::new(crate::TerminalTool::new(project, environment));
+    let tool = Arc::new(crate::TerminalTool::new(
+        project,
+        WeakEntity::new_invalid(),
+        environment,
+    ));
     let (event_stream, mut rx) = crate::ToolCallEventStream::test();
 
     let task = cx.update(|cx| {
@@ -321,7 +325,11 @@ async fn test_terminal_tool_without_timeout_does_not_kill_handle(cx: &mut TestAp
     });
 
     #[allow(clippy::arc_with_non_send_sync)]
-    let tool = Arc::new(crate::TerminalTool::new(project, environment));
+    let tool = Arc::new(crate::TerminalTool::new(
+        project,
+        WeakEntity::new_invalid(),
+        environment,
+    ));
     let (event_stream, mut rx) = crate::ToolCallEventStream::test();
 
     let _task = cx.update(|cx| {
@@ -1637,6 +1645,7 @@ async fn test_terminal_tool_cancellation_captures_output(cx: &mut TestAppContext
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
                 thread.project().clone(),
+                cx.weak_entity(),
                 environment,
             ));
             thread.send(UserMessageId::new(), ["run a command"], cx)
@@ -1920,6 +1929,7 @@ async fn test_truncate_while_terminal_tool_running(cx: &mut TestAppContext) {
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
                 thread.project().clone(),
+                cx.weak_entity(),
                 environment,
             ));
             thread.send(message_id.clone(), ["run a command"], cx)
@@ -1984,6 +1994,7 @@ async fn test_cancel_multiple_concurrent_terminal_tools(cx: &mut TestAppContext)
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
                 thread.project().clone(),
+                cx.weak_entity(),
                 environment.clone(),
             ));
             thread.send(UserMessageId::new(), ["run multiple commands"], cx)
@@ -2097,6 +2108,7 @@ async fn test_terminal_tool_stopped_via_terminal_card_button(cx: &mut TestAppCon
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
                 thread.project().clone(),
+                cx.weak_entity(),
                 environment,
             ));
             thread.send(UserMessageId::new(), ["run a command"], cx)
@@ -2191,6 +2203,7 @@ async fn test_terminal_tool_timeout_expires(cx: &mut TestAppContext) {
         .update(cx, |thread, cx| {
             thread.add_tool(crate::TerminalTool::new(
                 thread.project().clone(),
+                cx.weak_entity(),
                 environment,
             ));
             thread.send(UserMessageId::new(), ["run a command with timeout"], cx)
@@ -3675,7 +3688,11 @@ async fn test_terminal_tool_permission_rules(cx: &mut TestAppContext) {
         });
 
         #[allow(clippy::arc_with_non_send_sync)]
-        let tool = Arc::new(crate::TerminalTool::new(project.clone(), environment));
+        let tool = Arc::new(crate::TerminalTool::new(
+            project.clone(),
+            WeakEntity::new_invalid(),
+            environment,
+        ));
         let (event_stream, _rx) = crate::ToolCallEventStream::test();
 
         let task = cx.update(|cx| {
@@ -3728,7 +3745,11 @@ async fn test_terminal_tool_permission_rules(cx: &mut TestAppContext) {
         });
 
         #[allow(clippy::arc_with_non_send_sync)]
-        let tool = Arc::new(crate::TerminalTool::new(project.clone(), environment));
+        let tool = Arc::new(crate::TerminalTool::new(
+            project.clone(),
+            WeakEntity::new_invalid(),
+            environment,
+        ));
         let (event_stream, mut rx) = crate::ToolCallEventStream::test();
 
         let task = cx.update(|cx| {
@@ -3786,7 +3807,11 @@ async fn test_terminal_tool_permission_rules(cx: &mut TestAppContext) {
         });
 
         #[allow(clippy::arc_with_non_send_sync)]
-        let tool = Arc::new(crate::TerminalTool::new(project.clone(), environment));
+        let tool = Arc::new(crate::TerminalTool::new(
+            project.clone(),
+            WeakEntity::new_invalid(),
+            environment,
+        ));
         let (event_stream, _rx) = crate::ToolCallEventStream::test();
 
         let task = cx.update(|cx| {
@@ -3830,7 +3855,11 @@ async fn test_terminal_tool_permission_rules(cx: &mut TestAppContext) {
         });
 
         #[allow(clippy::arc_with_non_send_sync)]
-        let tool = Arc::new(crate::TerminalTool::new(project.clone(), environment));
+        let tool = Arc::new(crate::TerminalTool::new(
+            project.clone(),
+            WeakEntity::new_invalid(),
+            environment,
+        ));
         let (event_stream, _rx) = crate::ToolCallEventStream::test();
 
         let task = cx.update(|cx| {
@@ -1,10 +1,13 @@
 use crate::{
     ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
-    DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool,
-    ListDirectoryTool, MovePathTool, NowTool, OpenTool, ProjectSnapshot, ReadFileTool,
-    RestoreFileFromDiskTool, SaveFileTool, StreamingEditFileTool, SubagentTool,
-    SystemPromptTemplate, Template, Templates, TerminalTool, ThinkingTool, ToolPermissionDecision,
-    WebSearchTool, decide_permission_from_settings,
+    DbTokenUsage, DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool,
+    FindReferencesTool, GoToDefinitionTool, GrepTool, HoverTool, ListDirectoryTool, MovePathTool,
+    NowTool, OpenTool, ProjectSnapshot, ReadFileTool, RenameSymbolTool, RestoreFileFromDiskTool,
+    SaveFileTool, StreamingEditFileTool, SubagentTool, SystemPromptTemplate, Template, Templates,
+    TerminalTool, ThinkingTool, ThreadsDatabase, ToolPermissionDecision, UsageCost, WebSearchTool,
+    WorkspaceSymbolsTool, decide_permission_from_settings,
+    hooks::{HookContext, HookEvent, hooks_for_event, run_hooks},
+    usage::check_usage_limits,
 };
 use acp_thread::{MentionUri, UserMessageId};
 use action_log::ActionLog;
@@ -13,7 +16,7 @@ use feature_flags::{FeatureFlagAppExt as _, SubagentsFeatureFlag};
 use agent_client_protocol as acp;
 use agent_settings::{
     AgentProfileId, AgentProfileSettings, AgentSettings, SUMMARIZE_THREAD_DETAILED_PROMPT,
-    SUMMARIZE_THREAD_PROMPT,
+    SUMMARIZE_THREAD_PROMPT, UsageLimits,
 };
 use anyhow::{Context as _, Result, anyhow};
 use chrono::{DateTime, Utc};
@@ -224,6 +227,7 @@ impl UserMessage {
         const OPEN_RULES_TAG: &str =
             "<rules>\nThe user has specified the following rules that should be applied:\n";
         const OPEN_DIAGNOSTICS_TAG: &str = "<diagnostics>";
+        const OPEN_RESOURCES_TAG: &str = "<context_server_resources>";
 
         let mut file_context = OPEN_FILES_TAG.to_string();
         let mut directory_context = OPEN_DIRECTORIES_TAG.to_string();
@@ -233,6 +237,7 @@ impl UserMessage {
         let mut fetch_context = OPEN_FETCH_TAG.to_string();
         let mut rules_context = OPEN_RULES_TAG.to_string();
         let mut diagnostics_context = OPEN_DIAGNOSTICS_TAG.to_string();
+        let mut resources_context = OPEN_RESOURCES_TAG.to_string();
 
         for chunk in &self.content {
             let chunk = match chunk {
@@ -317,6 +322,10 @@ impl UserMessage {
                         MentionUri::Diagnostics { .. } => {
                             write!(&mut diagnostics_context, "\n{}\n", content).ok();
                         }
+                        MentionUri::ContextServerResource { uri, .. } => {
+                            write!(&mut resources_context, "\nResource: {}\n\n{}", uri, content)
+                                .ok();
+                        }
                         MentionUri::TerminalSelection { .. } => {
                             write!(
                                 &mut selection_context,
@@ -395,6 +404,13 @@ impl UserMessage {
                 .push(language_model::MessageContent::Text(diagnostics_context));
         }
 
+        if resources_context.len() > OPEN_RESOURCES_TAG.len() {
+            resources_context.push_str("</context_server_resources>\n");
+            message
+                .content
+                .push(language_model::MessageContent::Text(resources_context));
+        }
+
         if message.content.len() > len_before_context {
             message.content.insert(
                 len_before_context,
@@ -680,6 +696,7 @@ impl ToolPermissionContext {
             || tool_name == MovePathTool::NAME
             || tool_name == CreateDirectoryTool::NAME
             || tool_name == SaveFileTool::NAME
+            || tool_name == RenameSymbolTool::NAME
         {
             (
                 extract_path_pattern(input_value),
@@ -788,6 +805,12 @@ pub struct Thread {
     request_token_usage: HashMap<UserMessageId, language_model::TokenUsage>,
     #[allow(unused)]
     cumulative_token_usage: TokenUsage,
+    /// The usage of the completion request that's streaming, recorded when it ends.
+    completion_token_usage: Option<TokenUsage>,
+    /// The estimated cost of the thread's requests, and of all of today's requests.
+    usage_cost: Option<UsageCost>,
+    /// Whether the user continued after the agent paused at a soft usage limit.
+    soft_usage_limits_acknowledged: bool,
     #[allow(unused)]
     initial_project_snapshot: Shared<Task<Option<Arc<ProjectSnapshot>>>>,
     pub(crate) context_server_registry: Entity<ContextServerRegistry>,
@@ -805,6 +828,8 @@ pub struct Thread {
     pub(crate) file_read_times: HashMap<PathBuf, fs::MTime>,
     /// True if this thread was imported from a shared thread and can be synced.
     imported: bool,
+    /// The thread this thread was forked from.
+    parent_id: Option<acp::SessionId>,
     /// If this is a subagent thread, contains context about the parent
     subagent_context: Option<SubagentContext>,
     /// Weak references to running subagent threads for cancellation propagation
@@ -852,6 +877,9 @@ impl Thread {
             tools: BTreeMap::default(),
             request_token_usage: HashMap::default(),
             cumulative_token_usage: TokenUsage::default(),
+            completion_token_usage: None,
+            usage_cost: None,
+            soft_usage_limits_acknowledged: false,
             initial_project_snapshot: {
                 let project_snapshot = Self::project_snapshot(project.clone(), cx);
                 cx.foreground_executor()
@@ -871,6 +899,7 @@ impl Thread {
             action_log,
             file_read_times: HashMap::default(),
             imported: false,
+            parent_id: None,
             subagent_context: None,
             running_subagents: Vec::new(),
         }
@@ -924,6 +953,9 @@ impl Thread {
             tools,
             request_token_usage: HashMap::default(),
             cumulative_token_usage: TokenUsage::default(),
+            completion_token_usage: None,
+            usage_cost: None,
+            soft_usage_limits_acknowledged: false,
             initial_project_snapshot: Task::ready(None).shared(),
             context_server_registry,
             profile_id,
@@ -938,6 +970,7 @@ impl Thread {
             action_log,
             file_read_times: HashMap::default(),
             imported: false,
+            parent_id: None,
             subagent_context: Some(subagent_context),
             running_subagents: Vec::new(),
         }
@@ -952,6 +985,11 @@ impl Thread {
         self.imported
     }
 
+    /// Returns the thread this thread was forked from, if any.
+    pub fn parent_id(&self) -> Option<&acp::SessionId> {
+        self.parent_id.as_ref()
+    }
+
     pub fn replay(
         &mut self,
         cx: &mut Context<Self>,
@@ -1108,7 +1146,7 @@ impl Thread {
 
         let action_log = cx.new(|_| ActionLog::new(project.clone()));
 
-        Self {
+        let mut this = Self {
             id,
             prompt_id: PromptId::new(),
             title: if db_thread.title.is_empty() {
@@ -1127,6 +1165,9 @@ impl Thread {
             tools: BTreeMap::default(),
             request_token_usage: db_thread.request_token_usage.clone(),
             cumulative_token_usage: db_thread.cumulative_token_usage,
+            completion_token_usage: None,
+            usage_cost: None,
+            soft_usage_limits_acknowledged: false,
             initial_project_snapshot: Task::ready(db_thread.initial_project_snapshot).shared(),
             context_server_registry,
             profile_id,
@@ -1143,9 +1184,12 @@ impl Thread {
             prompt_capabilities_rx,
             file_read_times: HashMap::default(),
             imported: db_thread.imported,
+            parent_id: db_thread.parent_id.clone(),
             subagent_context: None,
             running_subagents: Vec::new(),
-        }
+        };
+        this.refresh_usage_cost(cx);
+        this
     }
 
     pub fn to_db(&self, cx: &App) -> Task<DbThread> {
@@ -1164,6 +1208,7 @@ impl Thread {
             }),
             profile: Some(self.profile_id.clone()),
             imported: self.imported,
+            parent_id: self.parent_id.clone(),
         };
 
         cx.background_spawn(async move {
@@ -1278,7 +1323,10 @@ impl Thread {
         ));
         self.add_tool(FetchTool::new(self.project.read(cx).client().http_client()));
         self.add_tool(FindPathTool::new(self.project.clone()));
+        self.add_tool(FindReferencesTool::new(self.project.clone()));
+        self.add_tool(GoToDefinitionTool::new(self.project.clone()));
         self.add_tool(GrepTool::new(self.project.clone()));
+        self.add_tool(HoverTool::new(self.project.clone()));
         self.add_tool(ListDirectoryTool::new(self.project.clone()));
         self.add_tool(MovePathTool::new(self.project.clone()));
         self.add_tool(NowTool);
@@ -1288,11 +1336,21 @@ impl Thread {
             self.project.clone(),
             self.action_log.clone(),
         ));
+        self.add_tool(RenameSymbolTool::new(
+            cx.weak_entity(),
+            self.project.clone(),
+            self.action_log.clone(),
+        ));
         self.add_tool(SaveFileTool::new(self.project.clone()));
         self.add_tool(RestoreFileFromDiskTool::new(self.project.clone()));
-        self.add_tool(TerminalTool::new(self.project.clone(), environment));
+        self.add_tool(TerminalTool::new(
+            self.project.clone(),
+            cx.weak_entity(),
+            environment,
+        ));
         self.add_tool(ThinkingTool);
         self.add_tool(WebSearchTool);
+        self.add_tool(WorkspaceSymbolsTool::new(self.project.clone()));
 
         if cx.has_flag::<SubagentsFeatureFlag>() && self.depth() < MAX_SUBAGENT_DEPTH {
             self.add_tool(SubagentTool::new(cx.weak_entity(), self.depth()));
@@ -1399,6 +1457,99 @@ impl Thread {
         Ok(())
     }
 
+    /// The estimated cost of the thread's requests and of all of today's requests, if language
+    /// models have prices in the `agent.token_prices` setting.
+    pub fn usage_cost(&self) -> Option<UsageCost> {
+        self.usage_cost
+    }
+
+    fn refresh_usage_cost(&mut self, cx: &mut Context<Self>) {
+        let prices = AgentSettings::get_global(cx).token_prices.clone();
+        if prices.0.is_empty() {
+            self.usage_cost = None;
+            return;
+        }
+
+        let id = self.id.clone();
+        cx.spawn(async move |this, cx| {
+            let cost = UsageCost::load(id, prices, cx).await?;
+            this.update(cx, |this, cx| {
+                this.usage_cost = Some(cost);
+                cx.emit(TokenUsageUpdated(this.latest_token_usage()));
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn record_completion_token_usage(
+        &mut self,
+        model: &Arc<dyn LanguageModel>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(usage) = self.completion_token_usage.take() else {
+            return;
+        };
+
+        let record = DbTokenUsage {
+            thread_id: self.id.clone(),
+            provider: model.provider_id().0.as_ref().into(),
+            model: model.id().0.as_ref().into(),
+            created_at: Utc::now(),
+            usage,
+        };
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.spawn(async move |this, cx| {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.record_token_usage(record).await?;
+            this.update(cx, |this, cx| this.refresh_usage_cost(cx))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Fails when the next request would exceed one of the `agent.usage_limits`.
+    async fn ensure_within_usage_limits(
+        this: &WeakEntity<Self>,
+        model: &Arc<dyn LanguageModel>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let (id, prices, limits, next_request, soft_limits_acknowledged) =
+            this.read_with(cx, |this, cx| {
+                let settings = AgentSettings::get_global(cx);
+                // Assume that the next request costs as much as the previous one.
+                let next_request = this
+                    .latest_request_token_usage()
+                    .and_then(|usage| {
+                        settings
+                            .token_prices
+                            .cost(&model.provider_id().0, &model.id().0, &usage)
+                    })
+                    .unwrap_or_default();
+                (
+                    this.id.clone(),
+                    settings.token_prices.clone(),
+                    settings.usage_limits,
+                    next_request,
+                    this.soft_usage_limits_acknowledged,
+                )
+            })?;
+        if limits == UsageLimits::default() {
+            return Ok(());
+        }
+
+        let cost = UsageCost::load(id, prices, cx).await?;
+        if let Err(exceeded) =
+            check_usage_limits(cost, next_request, &limits, soft_limits_acknowledged)
+        {
+            // Pause only once at soft limits, so that sending a message continues the thread.
+            if !exceeded.hard {
+                this.update(cx, |this, _| this.soft_usage_limits_acknowledged = true)?;
+            }
+            return Err(exceeded.into());
+        }
+        Ok(())
+    }
+
     pub fn latest_request_token_usage(&self) -> Option<language_model::TokenUsage> {
         let last_user_message = self.last_user_message()?;
         let tokens = self.request_token_usage.get(&last_user_message.id)?;
@@ -1578,13 +1729,53 @@ impl Thread {
         let message_ix = self.messages.len().saturating_sub(1);
         self.clear_summary();
         let (cancellation_tx, mut cancellation_rx) = watch::channel(false);
+        let tools = self.enabled_tools(profile, &model, cx);
+        let user_prompt_submit = if let Some(Message::User(message)) = self.messages.last() {
+            let event = HookEvent::UserPromptSubmit {
+                prompt: message
+                    .content
+                    .iter()
+                    .filter_map(|content| match content {
+                        UserMessageContent::Text(text) => Some(text.as_str()),
+                        UserMessageContent::Mention { .. } | UserMessageContent::Image(_) => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+            let hooks = hooks_for_event(
+                &AgentSettings::get_global(cx).hooks.user_prompt_submit,
+                &event,
+            );
+            Some(run_hooks(hooks, event, self.hook_context(cx), cx))
+        } else {
+            None
+        };
         self.running_turn = Some(RunningTurn {
             event_stream: event_stream.clone(),
-            tools: self.enabled_tools(profile, &model, cx),
+            tools,
             cancellation_tx,
             _task: cx.spawn(async move |this, cx| {
                 log::debug!("Starting agent turn execution");
 
+                if let Some(user_prompt_submit) = user_prompt_submit {
+                    let outcome = user_prompt_submit.await;
+                    if let Some(reason) = outcome.blocked {
+                        _ = this.update(cx, |this, _| {
+                            this.messages.truncate(message_ix);
+                            this.running_turn.take();
+                        });
+                        event_stream.send_error(anyhow!("Prompt blocked by hook: {reason}"));
+                        return;
+                    }
+                    if let Some(context) = outcome.additional_context() {
+                        _ = this.update(cx, |this, _| {
+                            if let Some(Message::User(message)) = this.messages.last_mut() {
+                                message.content.push(UserMessageContent::Text(context));
+                            }
+                        });
+                    }
+                }
+
                 let turn_result = Self::run_turn_internal(
                     &this,
                     model,
@@ -1608,6 +1799,13 @@ impl Thread {
                     Ok(()) => {
                         log::debug!("Turn execution completed");
                         event_stream.send_stop(acp::StopReason::EndTurn);
+                        // Stop hooks are notifications, so the turn doesn't wait for them.
+                        if let Ok(stop) = this.update(cx, |this, cx| {
+                            let hooks = AgentSettings::get_global(cx).hooks.stop.clone();
+                            run_hooks(hooks, HookEvent::Stop, this.hook_context(cx), cx)
+                        }) {
+                            stop.detach();
+                        }
                     }
                     Err(error) => {
                         log::error!("Turn execution failed: {:?}", error);
@@ -1642,6 +1840,7 @@ impl Thread {
         let mut attempt = 0;
         let mut intent = CompletionIntent::UserPrompt;
         loop {
+            Self::ensure_within_usage_limits(this, &model, cx).await?;
             let request =
                 this.update(cx, |this, cx| this.build_completion_request(intent, cx))??;
 
@@ -1731,6 +1930,9 @@ impl Thread {
             // tool execution, which could cause deadlocks when tools spawn subagents
             // that need their own permits.
             drop(events);
+            this.update(cx, |this, cx| {
+                this.record_completion_token_usage(&model, cx)
+            })?;
 
             let end_turn = tool_results.is_empty();
             while let Some(tool_result) = tool_results.next().await {
@@ -1912,6 +2114,7 @@ impl Thread {
                     cache_creation_input_tokens = usage.cache_creation_input_tokens,
                     cache_read_input_tokens = usage.cache_read_input_tokens,
                 );
+                self.completion_token_usage = Some(usage);
                 self.update_token_usage(usage, cx);
             }
             Stop(StopReason::Refusal) => return Err(CompletionError::Refusal.into()),
@@ -2044,21 +2247,89 @@ impl Thread {
             acp::ToolCallUpdateFields::new().status(acp::ToolCallStatus::InProgress),
         );
         let supports_images = self.model().is_some_and(|model| model.supports_images());
-        let tool_result = tool.run(tool_use.input, tool_event_stream, cx);
+        let hooks = &AgentSettings::get_global(cx).hooks;
+        let pre_tool_use = HookEvent::PreToolUse {
+            tool_name: tool_use.name.to_string(),
+            tool_input: tool_use.input.clone(),
+        };
+        let pre_tool_use_hooks = hooks_for_event(&hooks.pre_tool_use, &pre_tool_use);
+        let post_tool_use_hooks = hooks
+            .post_tool_use
+            .iter()
+            .filter(|hook| hook.applies_to_tool(&tool_use.name))
+            .cloned()
+            .collect::<Vec<_>>();
+        let hook_context = self.hook_context(cx);
+
+        // Start the tool right away, unless hooks have to see its input first.
+        let mut tool_result = None;
+        let mut pre_tool_use_outcome = None;
+        if pre_tool_use_hooks.is_empty() {
+            tool_result = Some(tool.clone().run(
+                tool_use.input.clone(),
+                tool_event_stream.clone(),
+                cx,
+            ));
+        } else {
+            pre_tool_use_outcome = Some(run_hooks(
+                pre_tool_use_hooks,
+                pre_tool_use,
+                hook_context.clone(),
+                cx,
+            ));
+        }
         log::debug!("Running tool {}", tool_use.name);
-        Some(cx.foreground_executor().spawn(async move {
-            let tool_result = tool_result.await.and_then(|output| {
-                if let LanguageModelToolResultContent::Image(_) = &output.llm_output
-                    && !supports_images
-                {
-                    return Err(anyhow!(
-                        "Attempted to read an image, but this model doesn't support it.",
-                    ));
+        Some(cx.spawn(async move |this, cx| {
+            let mut input = tool_use.input.clone();
+            let mut hook_context_text = Vec::new();
+            if let Some(outcome) = pre_tool_use_outcome {
+                let outcome = outcome.await;
+                hook_context_text.extend(outcome.additional_context());
+                if let Some(reason) = outcome.blocked {
+                    let content = format!("The tool call was blocked by a hook: {reason}");
+                    return LanguageModelToolResult {
+                        tool_use_id: tool_use.id,
+                        tool_name: tool_use.name,
+                        is_error: true,
+                        content: LanguageModelToolResultContent::Text(Arc::from(content.as_str())),
+                        output: Some(content.into()),
+                    };
                 }
-                Ok(output)
-            });
+                if let Some(updated_input) = outcome.updated_input {
+                    input = updated_input;
+                    tool_event_stream
+                        .update_fields(acp::ToolCallUpdateFields::new().raw_input(input.clone()));
+                    this.update(cx, |this, _| {
+                        for content in &mut this.pending_message().content {
+                            if let AgentMessageContent::ToolUse(pending_tool_use) = content
+                                && pending_tool_use.id == tool_use.id
+                            {
+                                pending_tool_use.raw_input = input.to_string();
+                                pending_tool_use.input = input.clone();
+                            }
+                        }
+                    })
+                    .ok();
+                }
+                tool_result =
+                    Some(cx.update(|cx| tool.run(input.clone(), tool_event_stream.clone(), cx)));
+            }
+
+            let tool_result = tool_result
+                .expect("the tool runs either right away or after its hooks")
+                .await
+                .and_then(|output| {
+                    if let LanguageModelToolResultContent::Image(_) = &output.llm_output
+                        && !supports_images
+                    {
+                        return Err(anyhow!(
+                            "Attempted to read an image, but this model doesn't support it.",
+                        ));
+                    }
+                    Ok(output)
+                });
 
-            match tool_result {
+            let mut result = match tool_result {
                 Ok(output) => LanguageModelToolResult {
                     tool_use_id: tool_use.id,
                     tool_name: tool_use.name,
@@ -2073,7 +2344,32 @@ impl Thread {
                     content: LanguageModelToolResultContent::Text(Arc::from(error.to_string())),
                     output: Some(error.to_string().into()),
                 },
+            };
+
+            if !post_tool_use_hooks.is_empty() {
+                let post_tool_use = HookEvent::PostToolUse {
+                    tool_name: result.tool_name.to_string(),
+                    tool_input: input,
+                    tool_output: match &result.content {
+                        LanguageModelToolResultContent::Text(text) => text.to_string(),
+                        LanguageModelToolResultContent::Image(_) => "[image]".to_string(),
+                    },
+                    is_error: result.is_error,
+                };
+                let outcome = cx
+                    .update(|cx| run_hooks(post_tool_use_hooks, post_tool_use, hook_context, cx))
+                    .await;
+                hook_context_text.extend(outcome.additional_context());
             }
+            if !hook_context_text.is_empty()
+                && let LanguageModelToolResultContent::Text(text) = &result.content
+            {
+                result.content = LanguageModelToolResultContent::Text(Arc::from(format!(
+                    "{text}\n\n{}",
+                    hook_context_text.join("\n\n")
+                )));
+            }
+            result
         }))
     }
 
@@ -2246,6 +2542,22 @@ impl Thread {
             })
     }
 
+    fn hook_context(&self, cx: &App) -> HookContext {
+        let project = self.project.read(cx);
+        let cwd = if project.is_local() {
+            project
+                .visible_worktrees(cx)
+                .next()
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+        } else {
+            None
+        };
+        HookContext {
+            session_id: self.id.to_string(),
+            cwd,
+        }
+    }
+
     fn pending_message(&mut self) -> &mut AgentMessage {
         self.pending_message.get_or_insert_default()
     }
@@ -0,0 +1,671 @@
+//! Export of threads to Markdown, for reading and sharing, and to a versioned JSON format that
+//! can be imported back into the thread history.
+//!
+//! The JSON format has types of its own rather than reusing the database's, so that changing how
+//! threads are stored doesn't change what can be imported.
+
+use crate::{
+    AgentMessage, AgentMessageContent, DbLanguageModel, DbThread, Message, UserMessage,
+    UserMessageContent,
+};
+use acp_thread::{MentionUri, UserMessageId};
+use agent_settings::AgentProfileId;
+use anyhow::{Context as _, Result, anyhow};
+use chrono::{DateTime, Utc};
+use collections::{HashMap, IndexMap};
+use gpui::{DevicePixels, size};
+use language_model::{
+    LanguageModelImage, LanguageModelToolResult, LanguageModelToolResultContent,
+    LanguageModelToolUse, LanguageModelToolUseId, TokenUsage,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write as _, sync::Arc};
+use util::{markdown::MarkdownCodeBlock, paths::PathStyle};
+
+/// A thread in the exported JSON format.
+///
+/// Unlike the database's format, which can change between releases, this format is versioned:
+/// a thread exported by one version of Zed can be imported by any later version.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThreadExport {
+    pub version: String,
+    pub title: String,
+    pub updated_at: DateTime<Utc>,
+    pub messages: Vec<ExportedMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detailed_summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ExportedModel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// The tokens used by the whole thread.
+    #[serde(default)]
+    pub token_usage: ExportedTokenUsage,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "snake_case")]
+pub enum ExportedMessage {
+    User {
+        content: Vec<ExportedUserContent>,
+        /// The tokens used by the request that the message was sent with.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token_usage: Option<ExportedTokenUsage>,
+    },
+    Assistant {
+        content: Vec<ExportedAssistantContent>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reasoning_details: Option<serde_json::Value>,
+    },
+    /// The user asked the assistant to continue after it stopped.
+    Resume,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportedUserContent {
+    Text {
+        text: String,
+    },
+    /// Context that the user mentioned, with its content at the time the message was sent.
+    Mention {
+        uri: String,
+        content: String,
+    },
+    Image(ExportedImage),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportedAssistantContent {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+    },
+    RedactedThinking {
+        data: String,
+    },
+    ToolCall(ExportedToolCall),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportedToolCall {
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thought_signature: Option<String>,
+    /// The result of the tool call, or `None` if it was canceled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ExportedToolResult>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportedToolResult {
+    pub is_error: bool,
+    pub content: ExportedToolResultContent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<serde_json::Value>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportedToolResultContent {
+    Text { text: String },
+    Image(ExportedImage),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportedImage {
+    /// A base64-encoded PNG image.
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportedModel {
+    pub provider: String,
+    pub model: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTokenUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+impl ThreadExport {
+    /// The version of the format. Its major version changes when threads exported with it can't
+    /// be imported by older versions of Zed.
+    pub const VERSION: &'static str = "1.0.0";
+
+    pub fn from_db_thread(thread: &DbThread) -> Self {
+        let messages = thread
+            .messages
+            .iter()
+            .map(|message| match message {
+                Message::User(message) => ExportedMessage::User {
+                    content: message
+                        .content
+                        .iter()
+                        .map(ExportedUserContent::from_db)
+                        .collect(),
+                    token_usage: thread
+                        .request_token_usage
+                        .get(&message.id)
+                        .copied()
+                        .map(Into::into),
+                },
+                Message::Agent(message) => ExportedMessage::Assistant {
+                    content: message
+                        .content
+                        .iter()
+                        .map(|content| ExportedAssistantContent::from_db(content, message))
+                        .collect(),
+                    reasoning_details: message.reasoning_details.clone(),
+                },
+                Message::Resume => ExportedMessage::Resume,
+            })
+            .collect();
+        Self {
+            version: Self::VERSION.to_string(),
+            title: thread.title.to_string(),
+            updated_at: thread.updated_at,
+            messages,
+            detailed_summary: thread.detailed_summary.as_ref().map(ToString::to_string),
+            model: thread.model.as_ref().map(|model| ExportedModel {
+                provider: model.provider.clone(),
+                model: model.model.clone(),
+            }),
+            profile: thread
+                .profile
+                .as_ref()
+                .map(|profile| profile.as_str().to_string()),
+            token_usage: thread.cumulative_token_usage.into(),
+        }
+    }
+
+    /// Returns a thread that can be saved to the database and resumed.
+    pub fn to_db_thread(self) -> Result<DbThread> {
+        let mut request_token_usage = HashMap::default();
+        let mut messages = Vec::with_capacity(self.messages.len());
+        for message in self.messages {
+            messages.push(match message {
+                ExportedMessage::User {
+                    content,
+                    token_usage,
+                } => {
+                    let id = UserMessageId::new();
+                    if let Some(token_usage) = token_usage {
+                        request_token_usage.insert(id.clone(), token_usage.into());
+                    }
+                    let content = content
+                        .into_iter()
+                        .map(ExportedUserContent::into_db)
+                        .collect::<Result<_>>()?;
+                    Message::User(UserMessage { id, content })
+                }
+                ExportedMessage::Assistant {
+                    content,
+                    reasoning_details,
+                } => {
+                    let mut tool_results = IndexMap::default();
+                    let content = content
+                        .into_iter()
+                        .map(|content| content.into_db(&mut tool_results))
+                        .collect();
+                    Message::Agent(AgentMessage {
+                        content,
+                        tool_results,
+                        reasoning_details,
+                    })
+                }
+                ExportedMessage::Resume => Message::Resume,
+            });
+        }
+
+        Ok(DbThread {
+            title: self.title.into(),
+            messages,
+            updated_at: self.updated_at,
+            detailed_summary: self.detailed_summary.map(Into::into),
+            initial_project_snapshot: None,
+            cumulative_token_usage: self.token_usage.into(),
+            request_token_usage,
+            model: self.model.map(|model| DbLanguageModel {
+                provider: model.provider,
+                model: model.model,
+            }),
+            profile: self.profile.map(|profile| AgentProfileId(profile.into())),
+            imported: true,
+            parent_id: None,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &[u8]) -> Result<Self> {
+        let value = serde_json::from_slice::<serde_json::Value>(json)
+            .context("The file isn't a valid JSON document")?;
+        let version = value
+            .get("version")
+            .and_then(|version| version.as_str())
+            .ok_or_else(|| anyhow!("The file isn't an exported thread"))?;
+        let major_version = |version: &str| version.split('.').next().map(str::to_string);
+        if major_version(version) != major_version(Self::VERSION) {
+            return Err(anyhow!(
+                "The thread was exported with version {version} of the format, which this version of Zed can't import"
+            ));
+        }
+        serde_json::from_value(value).context("The exported thread is invalid")
+    }
+
+    /// Renders the thread as a Markdown document. Tool calls, and the diffs of edits, are
+    /// collapsed.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.title);
+        for message in &self.messages {
+            match message {
+                ExportedMessage::User { content, .. } => {
+                    user_message_to_markdown(content, &mut markdown)
+                }
+                ExportedMessage::Assistant { content, .. } => {
+                    assistant_message_to_markdown(content, &mut markdown)
+                }
+                ExportedMessage::Resume => {}
+            }
+        }
+        markdown
+    }
+}
+
+impl ExportedUserContent {
+    fn from_db(content: &UserMessageContent) -> Self {
+        match content {
+            UserMessageContent::Text(text) => Self::Text { text: text.clone() },
+            UserMessageContent::Mention { uri, content } => Self::Mention {
+                uri: uri.to_uri().to_string(),
+                content: content.clone(),
+            },
+            UserMessageContent::Image(image) => Self::Image(image.into()),
+        }
+    }
+
+    fn into_db(self) -> Result<UserMessageContent> {
+        Ok(match self {
+            Self::Text { text } => UserMessageContent::Text(text),
+            Self::Mention { uri, content } => UserMessageContent::Mention {
+                uri: MentionUri::parse(&uri, PathStyle::local())
+                    .with_context(|| format!("The thread mentions an invalid URI: {uri}"))?,
+                content,
+            },
+            Self::Image(image) => UserMessageContent::Image(image.into()),
+        })
+    }
+}
+
+impl ExportedAssistantContent {
+    fn from_db(content: &AgentMessageContent, message: &AgentMessage) -> Self {
+        match content {
+            AgentMessageContent::Text(text) => Self::Text { text: text.clone() },
+            AgentMessageContent::Thinking { text, signature } => Self::Thinking {
+                text: text.clone(),
+                signature: signature.clone(),
+            },
+            AgentMessageContent::RedactedThinking(data) => {
+                Self::RedactedThinking { data: data.clone() }
+            }
+            AgentMessageContent::ToolUse(tool_use) => Self::ToolCall(ExportedToolCall {
+                id: tool_use.id.to_string(),
+                name: tool_use.name.to_string(),
+                input: tool_use.input.clone(),
+                thought_signature: tool_use.thought_signature.clone(),
+                result: message
+                    .tool_results
+                    .get(&tool_use.id)
+                    .map(|result| ExportedToolResult {
+                        is_error: result.is_error,
+                        content: match &result.content {
+                            LanguageModelToolResultContent::Text(text) => {
+                                ExportedToolResultContent::Text {
+                                    text: text.to_string(),
+                                }
+                            }
+                            LanguageModelToolResultContent::Image(image) => {
+                                ExportedToolResultContent::Image(image.into())
+                            }
+                        },
+                        output: result.output.clone(),
+                    }),
+            }),
+        }
+    }
+
+    fn into_db(
+        self,
+        tool_results: &mut IndexMap<LanguageModelToolUseId, LanguageModelToolResult>,
+    ) -> AgentMessageContent {
+        match self {
+            Self::Text { text } => AgentMessageContent::Text(text),
+            Self::Thinking { text, signature } => AgentMessageContent::Thinking { text, signature },
+            Self::RedactedThinking { data } => AgentMessageContent::RedactedThinking(data),
+            Self::ToolCall(tool_call) => {
+                let id = LanguageModelToolUseId::from(tool_call.id);
+                let name = Arc::<str>::from(tool_call.name);
+                if let Some(result) = tool_call.result {
+                    tool_results.insert(
+                        id.clone(),
+                        LanguageModelToolResult {
+                            tool_use_id: id.clone(),
+                            tool_name: name.clone(),
+                            is_error: result.is_error,
+                            content: match result.content {
+                                ExportedToolResultContent::Text { text } => {
+                                    LanguageModelToolResultContent::Text(text.into())
+                                }
+                                ExportedToolResultContent::Image(image) => {
+                                    LanguageModelToolResultContent::Image(image.into())
+                                }
+                            },
+                            output: result.output,
+                        },
+                    );
+                }
+                AgentMessageContent::ToolUse(LanguageModelToolUse {
+                    id,
+                    name,
+                    raw_input: tool_call.input.to_string(),
+                    input: tool_call.input,
+                    is_input_complete: true,
+                    thought_signature: tool_call.thought_signature,
+                })
+            }
+        }
+    }
+}
+
+impl From<&LanguageModelImage> for ExportedImage {
+    fn from(image: &LanguageModelImage) -> Self {
+        Self {
+            source: image.source.to_string(),
+            width: image.size.map(|size| size.width.0),
+            height: image.size.map(|size| size.height.0),
+        }
+    }
+}
+
+impl From<ExportedImage> for LanguageModelImage {
+    fn from(image: ExportedImage) -> Self {
+        Self {
+            source: image.source.into(),
+            size: image
+                .width
+                .zip(image.height)
+                .map(|(width, height)| size(DevicePixels(width), DevicePixels(height))),
+        }
+    }
+}
+
+impl From<TokenUsage> for ExportedTokenUsage {
+    fn from(usage: TokenUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+        }
+    }
+}
+
+impl From<ExportedTokenUsage> for TokenUsage {
+    fn from(usage: ExportedTokenUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+        }
+    }
+}
+
+fn user_message_to_markdown(content: &[ExportedUserContent], markdown: &mut String) {
+    markdown.push_str("\n## User\n\n");
+    for content in content {
+        let line = match content {
+            ExportedUserContent::Text { text } => text.trim_end().to_string(),
+            ExportedUserContent::Mention { uri, .. } => MentionUri::parse(uri, PathStyle::local())
+                .map(|mention| mention.as_link().to_string())
+                .unwrap_or_else(|_| format!("<{uri}>")),
+            ExportedUserContent::Image(_) => "_[image]_".to_string(),
+        };
+        writeln!(markdown, "{line}").ok();
+    }
+}
+
+fn assistant_message_to_markdown(content: &[ExportedAssistantContent], markdown: &mut String) {
+    markdown.push_str("\n## Assistant\n\n");
+    for content in content {
+        match content {
+            ExportedAssistantContent::Text { text } => {
+                writeln!(markdown, "{}\n", text.trim_end()).ok();
+            }
+            ExportedAssistantContent::Thinking { text, .. } => {
+                writeln!(
+                    markdown,
+                    "<details>\n<summary>Thinking</summary>\n\n{}\n\n</details>\n",
+                    text.trim_end()
+                )
+                .ok();
+            }
+            ExportedAssistantContent::RedactedThinking { .. } => {}
+            ExportedAssistantContent::ToolCall(tool_call) => {
+                tool_call_to_markdown(tool_call, markdown);
+            }
+        }
+    }
+}
+
+fn tool_call_to_markdown(tool_call: &ExportedToolCall, markdown: &mut String) {
+    let status = match &tool_call.result {
+        Some(result) if result.is_error => " (failed)",
+        Some(_) => "",
+        None => " (canceled)",
+    };
+    writeln!(
+        markdown,
+        "<details>\n<summary>Tool call: <code>{}</code>{status}</summary>\n",
+        tool_call.name
+    )
+    .ok();
+    writeln!(
+        markdown,
+        "{}",
+        MarkdownCodeBlock {
+            tag: "json",
+            text: &format!("{:#}", tool_call.input),
+        }
+    )
+    .ok();
+
+    if let Some(result) = &tool_call.result {
+        // The result of an edit already contains its diff as a code block.
+        match &result.content {
+            ExportedToolResultContent::Text { text } => {
+                writeln!(markdown, "{}\n", text.trim_end()).ok();
+            }
+            ExportedToolResultContent::Image(_) => {
+                writeln!(markdown, "_[image]_\n").ok();
+            }
+        }
+    }
+    markdown.push_str("</details>\n\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone as _;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn thread() -> ThreadExport {
+        let token_usage = ExportedTokenUsage {
+            input_tokens: 100,
+            output_tokens: 20,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        };
+        ThreadExport {
+            version: ThreadExport::VERSION.to_string(),
+            title: "Fix the bug".into(),
+            updated_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            messages: vec![
+                ExportedMessage::User {
+                    content: vec![ExportedUserContent::Text {
+                        text: "Fix the bug".into(),
+                    }],
+                    token_usage: Some(token_usage),
+                },
+                ExportedMessage::Assistant {
+                    content: vec![
+                        ExportedAssistantContent::Text {
+                            text: "Fixing it.".into(),
+                        },
+                        ExportedAssistantContent::ToolCall(ExportedToolCall {
+                            id: "tool-1".into(),
+                            name: "edit_file".into(),
+                            input: json!({ "path": "src/main.rs" }),
+                            thought_signature: None,
+                            result: Some(ExportedToolResult {
+                                is_error: false,
+                                content: ExportedToolResultContent::Text {
+                                    text: "Edited src/main.rs:\n\n```diff\n-old\n+new\n```".into(),
+                                },
+                                output: None,
+                            }),
+                        }),
+                    ],
+                    reasoning_details: None,
+                },
+            ],
+            detailed_summary: None,
+            model: None,
+            profile: None,
+            token_usage,
+        }
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(
+            thread().to_markdown(),
+            indoc::indoc! {r#"
+                # Fix the bug
+
+                ## User
+
+                Fix the bug
+
+                ## Assistant
+
+                Fixing it.
+
+                <details>
+                <summary>Tool call: <code>edit_file</code></summary>
+
+                ```json
+                {
+                  "path": "src/main.rs"
+                }
+                ```
+
+                Edited src/main.rs:
+
+                ```diff
+                -old
+                +new
+                ```
+
+                </details>
+
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_json_format() {
+        // The format is versioned, so it must only change along with `ThreadExport::VERSION`.
+        let golden = include_str!("../test_data/thread_export_v1.json");
+        assert_eq!(thread().to_json().unwrap(), golden.trim_end());
+        assert_eq!(
+            ThreadExport::from_json(golden.as_bytes()).unwrap(),
+            thread()
+        );
+
+        let mut future_version = serde_json::from_str::<serde_json::Value>(golden).unwrap();
+        future_version["version"] = json!("2.0.0");
+        assert!(ThreadExport::from_json(future_version.to_string().as_bytes()).is_err());
+        assert!(ThreadExport::from_json(b"{\"title\": \"Not an export\"}").is_err());
+    }
+
+    #[test]
+    fn test_db_thread_roundtrip() {
+        let mut export = thread();
+        let ExportedMessage::User { content, .. } = &mut export.messages[0] else {
+            unreachable!()
+        };
+        content.push(ExportedUserContent::Mention {
+            uri: "file:///project/src/main.rs".into(),
+            content: "fn main() {}".into(),
+        });
+
+        let db_thread = ThreadExport::from_json(export.to_json().unwrap().as_bytes())
+            .unwrap()
+            .to_db_thread()
+            .unwrap();
+        assert!(db_thread.imported);
+        assert_eq!(db_thread.cumulative_token_usage.input_tokens, 100);
+        let [Message::User(user_message), Message::Agent(agent_message)] =
+            db_thread.messages.as_slice()
+        else {
+            panic!("unexpected messages: {:?}", db_thread.messages);
+        };
+        assert_eq!(
+            db_thread.request_token_usage[&user_message.id].output_tokens,
+            20
+        );
+        assert!(matches!(
+            &user_message.content[1],
+            UserMessageContent::Mention {
+                uri: MentionUri::File { .. },
+                ..
+            }
+        ));
+        let tool_use_id = LanguageModelToolUseId::from("tool-1");
+        assert!(matches!(
+            &agent_message.content[1],
+            AgentMessageContent::ToolUse(tool_use) if tool_use.id == tool_use_id
+        ));
+        assert_eq!(
+            agent_message.tool_results[&tool_use_id].tool_name.as_ref(),
+            "edit_file"
+        );
+
+        assert_eq!(ThreadExport::from_db_thread(&db_thread), export);
+    }
+}
@@ -156,6 +156,7 @@ mod tests {
             model: None,
             profile: None,
             imported: false,
+            parent_id: None,
         }
     }
 
@@ -1,3 +1,4 @@
+mod code_navigation;
 mod context_server_registry;
 mod copy_path_tool;
 mod create_directory_tool;
@@ -6,12 +7,16 @@ mod diagnostics_tool;
 mod edit_file_tool;
 mod fetch_tool;
 mod find_path_tool;
+mod find_references_tool;
+mod go_to_definition_tool;
 mod grep_tool;
+mod hover_tool;
 mod list_directory_tool;
 mod move_path_tool;
 mod now_tool;
 mod open_tool;
 mod read_file_tool;
+mod rename_symbol_tool;
 mod restore_file_from_disk_tool;
 mod save_file_tool;
 mod streaming_edit_file_tool;
@@ -19,6 +24,7 @@ mod subagent_tool;
 mod terminal_tool;
 mod thinking_tool;
 mod web_search_tool;
+mod workspace_symbols_tool;
 
 use crate::AgentTool;
 use language_model::{LanguageModelRequestTool, LanguageModelToolSchemaFormat};
@@ -31,12 +37,16 @@ pub use diagnostics_tool::*;
 pub use edit_file_tool::*;
 pub use fetch_tool::*;
 pub use find_path_tool::*;
+pub use find_references_tool::*;
+pub use go_to_definition_tool::*;
 pub use grep_tool::*;
+pub use hover_tool::*;
 pub use list_directory_tool::*;
 pub use move_path_tool::*;
 pub use now_tool::*;
 pub use open_tool::*;
 pub use read_file_tool::*;
+pub use rename_symbol_tool::*;
 pub use restore_file_from_disk_tool::*;
 pub use save_file_tool::*;
 pub use streaming_edit_file_tool::*;
@@ -44,6 +54,7 @@ pub use subagent_tool::*;
 pub use terminal_tool::*;
 pub use thinking_tool::*;
 pub use web_search_tool::*;
+pub use workspace_symbols_tool::*;
 
 macro_rules! tools {
     ($($tool:ty),* $(,)?) => {
@@ -120,16 +131,21 @@ tools! {
     EditFileTool,
     FetchTool,
     FindPathTool,
+    FindReferencesTool,
+    GoToDefinitionTool,
     GrepTool,
+    HoverTool,
     ListDirectoryTool,
     MovePathTool,
     NowTool,
     OpenTool,
     ReadFileTool,
+    RenameSymbolTool,
     RestoreFileFromDiskTool,
     SaveFileTool,
     SubagentTool,
     TerminalTool,
     ThinkingTool,
     WebSearchTool,
+    WorkspaceSymbolsTool,
 }
@@ -0,0 +1,501 @@
+//! Helpers shared by the tools that navigate code through the project's language servers.
+
+use crate::{ToolPermissionDecision, decide_permission_from_settings};
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::{Result, anyhow};
+use collections::HashSet;
+use gpui::{App, AsyncApp, Entity, Task};
+use language::{Buffer, Location, Point, ToPoint as _};
+use project::{Project, ProjectPath, lsp_store::OpenLspBufferHandle};
+use settings::Settings as _;
+use std::{fmt::Write as _, path::PathBuf};
+use util::markdown::MarkdownInlineCode;
+
+/// How many locations a tool lists before summarizing the rest.
+pub(crate) const MAX_LOCATIONS: usize = 50;
+
+/// How many characters of a line a snippet shows.
+const MAX_SNIPPET_LEN: usize = 120;
+
+/// Decides whether a read-only navigation tool may run.
+///
+/// These tools only read code, so unlike tools with side effects they run without asking,
+/// unless the user configured `tool_permissions` rules for them.
+pub(crate) fn read_only_permission(
+    tool_name: &str,
+    input: &str,
+    cx: &App,
+) -> ToolPermissionDecision {
+    let settings = AgentSettings::get_global(cx);
+    if !settings.tool_permissions.tools.contains_key(tool_name) {
+        return ToolPermissionDecision::Allow;
+    }
+    decide_permission_from_settings(tool_name, input, settings)
+}
+
+/// A symbol at a known position of a buffer, which stays registered with its language
+/// servers for as long as this is alive.
+pub(crate) struct SymbolPosition {
+    pub buffer: Entity<Buffer>,
+    pub point: Point,
+    pub abs_path: Option<PathBuf>,
+    _lsp_handle: OpenLspBufferHandle,
+}
+
+/// Opens the buffer at `path` and finds the first whole-word occurrence of `symbol` on its
+/// 1-based `line`.
+pub(crate) fn resolve_symbol(
+    project: &Entity<Project>,
+    path: &str,
+    line: u32,
+    symbol: &str,
+    cx: &mut App,
+) -> Task<Result<SymbolPosition>> {
+    let Some(project_path) = project.read(cx).find_project_path(path, cx) else {
+        return Task::ready(Err(anyhow!("Path {path} not found in project")));
+    };
+    if line == 0 {
+        return Task::ready(Err(anyhow!("Line numbers start at 1")));
+    }
+    let abs_path = project.read(cx).absolute_path(&project_path, cx);
+    let open_buffer = project.update(cx, |project, cx| {
+        project.open_buffer_with_lsp(project_path, cx)
+    });
+    let path = path.to_string();
+    let symbol = symbol.to_string();
+    cx.spawn(async move |cx| {
+        let (buffer, lsp_handle) = open_buffer.await?;
+        let point = buffer.read_with(cx, |buffer, _| {
+            let row = line - 1;
+            if row > buffer.max_point().row {
+                return Err(anyhow!(
+                    "{path} has only {} lines",
+                    buffer.max_point().row + 1
+                ));
+            }
+            let line_text = buffer
+                .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+                .collect::<String>();
+            let column = find_word(&line_text, &symbol).ok_or_else(|| {
+                anyhow!(
+                    "{} not found on line {line} of {path}",
+                    MarkdownInlineCode(&symbol)
+                )
+            })?;
+            Ok(Point::new(row, column as u32))
+        })?;
+        Ok(SymbolPosition {
+            buffer,
+            point,
+            abs_path,
+            _lsp_handle: lsp_handle,
+        })
+    })
+}
+
+/// Returns the byte offset of the first whole-word occurrence of `word` in `text`.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    if word.is_empty() {
+        return None;
+    }
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).map(|(ix, _)| ix).find(|&ix| {
+        let end = ix + word.len();
+        !text[..ix].chars().next_back().is_some_and(is_word_char)
+            && !text[end..].chars().next().is_some_and(is_word_char)
+    })
+}
+
+/// Lists locations as `path:line: snippet`, one per line, sorted and without duplicate lines.
+pub(crate) fn format_locations(locations: &[Location], cx: &App) -> String {
+    let mut seen = HashSet::default();
+    let mut entries = locations
+        .iter()
+        .filter_map(|location| {
+            let buffer = location.buffer.read(cx);
+            let row = location.range.start.to_point(buffer).row;
+            let file = buffer.file()?;
+            let path = file.full_path(cx);
+            if !seen.insert((path.clone(), row)) {
+                return None;
+            }
+            let snippet = if file.is_private() {
+                None
+            } else {
+                let line = buffer
+                    .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+                    .collect::<String>();
+                Some(truncate_snippet(line.trim()))
+            };
+            Some((path, row, snippet))
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let mut output = String::new();
+    for (path, row, snippet) in entries.iter().take(MAX_LOCATIONS) {
+        match snippet {
+            Some(snippet) => writeln!(output, "{}:{}: {snippet}", path.display(), row + 1),
+            None => writeln!(output, "{}:{}", path.display(), row + 1),
+        }
+        .ok();
+    }
+    if entries.len() > MAX_LOCATIONS {
+        writeln!(output, "…and {} more", entries.len() - MAX_LOCATIONS).ok();
+    }
+    output
+}
+
+fn truncate_snippet(line: &str) -> String {
+    if line.chars().count() <= MAX_SNIPPET_LEN {
+        line.to_string()
+    } else {
+        let mut snippet = line.chars().take(MAX_SNIPPET_LEN).collect::<String>();
+        snippet.push('…');
+        snippet
+    }
+}
+
+/// Returns the locations to show in the tool call, for the files that exist on disk.
+pub(crate) fn tool_call_locations(
+    project: &Entity<Project>,
+    locations: &[Location],
+    cx: &AsyncApp,
+) -> Vec<acp::ToolCallLocation> {
+    cx.update(|cx| {
+        let project = project.read(cx);
+        locations
+            .iter()
+            .take(MAX_LOCATIONS)
+            .filter_map(|location| {
+                let buffer = location.buffer.read(cx);
+                let file = buffer.file()?;
+                let abs_path =
+                    project.absolute_path(&ProjectPath::from_file(file.as_ref(), cx), cx)?;
+                let row = location.range.start.to_point(buffer).row;
+                Some(acp::ToolCallLocation::new(abs_path).line(Some(row)))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AgentTool, EditFileTool, FindReferencesTool, FindReferencesToolInput, GoToDefinitionTool,
+        GoToDefinitionToolInput, HoverTool, HoverToolInput, RenameSymbolTool,
+        RenameSymbolToolInput, ToolCallEventStream, WorkspaceSymbolsTool,
+        WorkspaceSymbolsToolInput,
+    };
+    use crate::{ContextServerRegistry, Templates, Thread};
+    use action_log::ActionLog;
+    use agent_settings::AgentSettings;
+    use edit_prediction_context::fake_definition_lsp::register_fake_definition_server;
+    use futures::StreamExt as _;
+    use gpui::{AppContext as _, TestAppContext};
+    use indoc::indoc;
+    use language_model::fake_provider::FakeLanguageModel;
+    use project::{FakeFs, Project};
+    use prompt_store::ProjectContext;
+    use serde_json::json;
+    use settings::{Settings as _, SettingsStore};
+    use std::sync::Arc;
+    use util::path;
+
+    const LIB_RS: &str = indoc! {"
+        pub struct Config {
+            pub verbose: bool,
+        }
+
+        pub fn load_config() -> Config {
+            Config { verbose: false }
+        }
+    "};
+
+    const MAIN_RS: &str = indoc! {"
+        use crate::lib::{Config, load_config};
+
+        fn main() {
+            let config: Config = load_config();
+            run(config);
+        }
+
+        fn run(config: Config) {}
+    "};
+
+    #[test]
+    fn test_find_word() {
+        assert_eq!(
+            find_word("let config: Config = load();", "Config"),
+            Some(12)
+        );
+        assert_eq!(find_word("let config: Config = load();", "config"), Some(4));
+        assert_eq!(find_word("ConfigBuilder::new()", "Config"), None);
+        assert_eq!(find_word("anything", ""), None);
+    }
+
+    #[gpui::test]
+    async fn test_go_to_definition(cx: &mut TestAppContext) {
+        let (project, _lsp_handle) = init_test(cx).await;
+        let tool = Arc::new(GoToDefinitionTool::new(project));
+        let output = cx
+            .update(|cx| {
+                tool.run(
+                    GoToDefinitionToolInput {
+                        path: "root/src/main.rs".into(),
+                        line: 4,
+                        symbol: "load_config".into(),
+                    },
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "root/src/lib.rs:5: pub fn load_config() -> Config {\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_find_references(cx: &mut TestAppContext) {
+        let (project, _lsp_handle) = init_test(cx).await;
+        let tool = Arc::new(FindReferencesTool::new(project));
+        let output = cx
+            .update(|cx| {
+                tool.run(
+                    FindReferencesToolInput {
+                        path: "root/src/lib.rs".into(),
+                        line: 1,
+                        symbol: "Config".into(),
+                    },
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            indoc! {"
+                root/src/lib.rs:1: pub struct Config {
+                root/src/lib.rs:5: pub fn load_config() -> Config {
+                root/src/lib.rs:6: Config { verbose: false }
+                root/src/main.rs:1: use crate::lib::{Config, load_config};
+                root/src/main.rs:4: let config: Config = load_config();
+                root/src/main.rs:8: fn run(config: Config) {}
+            "}
+        );
+
+        let error = cx
+            .update(|cx| {
+                tool.run(
+                    FindReferencesToolInput {
+                        path: "root/src/lib.rs".into(),
+                        line: 2,
+                        symbol: "Config".into(),
+                    },
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`Config` not found on line 2 of root/src/lib.rs"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_workspace_symbols(cx: &mut TestAppContext) {
+        let (project, _lsp_handle) = init_test(cx).await;
+        let tool = Arc::new(WorkspaceSymbolsTool::new(project));
+        let output = cx
+            .update(|cx| {
+                tool.run(
+                    WorkspaceSymbolsToolInput {
+                        query: "config".into(),
+                    },
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            indoc! {"
+                struct Config — root/src/lib.rs:1
+                function load_config — root/src/lib.rs:5
+            "}
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hover(cx: &mut TestAppContext) {
+        let (project, _lsp_handle) = init_test(cx).await;
+        let tool = Arc::new(HoverTool::new(project));
+        let output = cx
+            .update(|cx| {
+                tool.run(
+                    HoverToolInput {
+                        path: "root/src/main.rs".into(),
+                        line: 5,
+                        symbol: "run".into(),
+                    },
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert_eq!(output, "```\nfn run(config: Config) {}\n```");
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol(cx: &mut TestAppContext) {
+        let (project, _lsp_handle) = init_test(cx).await;
+        let (tool, action_log, _thread) = rename_symbol_tool(&project, cx);
+        let output = cx
+            .update(|cx| tool.run(rename_load_config(), ToolCallEventStream::test().0, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            indoc! {"
+                Renamed `load_config` to `read_config` in 2 files:
+                root/src/lib.rs
+                root/src/main.rs
+            "}
+        );
+
+        let main_buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/src/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        main_buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), MAIN_RS.replace("load_config", "read_config"));
+            assert!(!buffer.is_dirty());
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            action_log.read_with(cx, |log, cx| log.changed_buffers(cx).len()),
+            2
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_respects_edit_file_permissions(cx: &mut TestAppContext) {
+        let (project, _lsp_handle) = init_test(cx).await;
+        cx.update(|cx| {
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.tools.insert(
+                EditFileTool::NAME.into(),
+                agent_settings::ToolRules {
+                    default_mode: settings::ToolPermissionMode::Confirm,
+                    always_allow: vec![],
+                    always_deny: vec![
+                        agent_settings::CompiledRegex::new(r"main\.rs$", false).unwrap(),
+                    ],
+                    always_confirm: vec![],
+                    invalid_patterns: vec![],
+                },
+            );
+            AgentSettings::override_global(settings, cx);
+        });
+
+        // The symbol is declared in lib.rs, but the rename would also change main.rs.
+        let (tool, action_log, _thread) = rename_symbol_tool(&project, cx);
+        let result = cx
+            .update(|cx| tool.run(rename_load_config(), ToolCallEventStream::test().0, cx))
+            .await;
+        assert!(result.is_err());
+
+        let main_buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/src/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        main_buffer.read_with(cx, |buffer, _| assert_eq!(buffer.text(), MAIN_RS));
+        cx.run_until_parked();
+        assert_eq!(
+            action_log.read_with(cx, |log, cx| log.changed_buffers(cx).len()),
+            0
+        );
+    }
+
+    fn rename_symbol_tool(
+        project: &Entity<Project>,
+        cx: &mut TestAppContext,
+    ) -> (Arc<RenameSymbolTool>, Entity<ActionLog>, Entity<Thread>) {
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let context_server_registry =
+            cx.new(|cx| ContextServerRegistry::new(project.read(cx).context_server_store(), cx));
+        let thread = cx.new(|cx| {
+            Thread::new(
+                project.clone(),
+                cx.new(|_cx| ProjectContext::default()),
+                context_server_registry,
+                Templates::new(),
+                Some(Arc::new(FakeLanguageModel::default())),
+                cx,
+            )
+        });
+        let tool = Arc::new(RenameSymbolTool::new(
+            thread.downgrade(),
+            project.clone(),
+            action_log.clone(),
+        ));
+        (tool, action_log, thread)
+    }
+
+    fn rename_load_config() -> RenameSymbolToolInput {
+        RenameSymbolToolInput {
+            path: "root/src/lib.rs".into(),
+            line: 5,
+            symbol: "load_config".into(),
+            new_name: "read_config".into(),
+        }
+    }
+
+    async fn init_test(cx: &mut TestAppContext) -> (Entity<Project>, OpenLspBufferHandle) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "src": {
+                    "lib.rs": LIB_RS,
+                    "main.rs": MAIN_RS,
+                }
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        let rust = language::rust_lang();
+        language_registry.add(rust.clone());
+        let mut fake_servers = register_fake_definition_server(&language_registry, rust, fs);
+
+        let (_buffer, lsp_handle) = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer_with_lsp(path!("/root/src/lib.rs"), cx)
+            })
+            .await
+            .unwrap();
+        fake_servers.next().await.unwrap();
+        cx.run_until_parked();
+        (project, lsp_handle)
+    }
+}
@@ -0,0 +1,115 @@
+use super::code_navigation::{
+    format_locations, read_only_permission, resolve_symbol, tool_call_locations,
+};
+use crate::{AgentTool, ToolCallEventStream, ToolPermissionDecision};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+/// Finds all references to a symbol across the project, using the project's language servers.
+///
+/// Prefer this over grepping to find the callers of a function or the uses of a type, since it only lists real references to that symbol and not unrelated symbols with the same name.
+///
+/// Returns one `path:line: snippet` entry per line that references the symbol, including its declaration.
+///
+/// <example>
+/// To find all uses of the `Config` struct declared on line 10 of `src/config.rs` in the `app` root directory:
+/// {
+///     "path": "app/src/config.rs",
+///     "line": 10,
+///     "symbol": "Config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindReferencesToolInput {
+    /// The relative path of the file that contains the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based number of the line that contains the symbol.
+    pub line: u32,
+    /// The symbol, exactly as it appears on that line. Its first occurrence on the line is used.
+    pub symbol: String,
+}
+
+pub struct FindReferencesTool {
+    project: Entity<Project>,
+}
+
+impl FindReferencesTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for FindReferencesTool {
+    type Input = FindReferencesToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "find_references";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Find references to {}", MarkdownInlineCode(&input.symbol)).into(),
+            Err(_) => "Find references".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        if let ToolPermissionDecision::Deny(reason) =
+            read_only_permission(Self::NAME, &input.path, cx)
+        {
+            return Task::ready(Err(anyhow!("{}", reason)));
+        }
+
+        let symbol = resolve_symbol(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbol = symbol.await?;
+            let references = project.update(cx, |project, cx| {
+                project.references(&symbol.buffer, symbol.point, cx)
+            });
+            let references = futures::select! {
+                result = references.fuse() => result?.unwrap_or_default(),
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    anyhow::bail!("Find references cancelled by user");
+                }
+            };
+            if references.is_empty() {
+                return Ok(format!(
+                    "No references found for {}",
+                    MarkdownInlineCode(&input.symbol)
+                ));
+            }
+
+            event_stream.update_fields(
+                acp::ToolCallUpdateFields::new().locations(tool_call_locations(
+                    &project,
+                    &references,
+                    cx,
+                )),
+            );
+            Ok(cx.update(|cx| format_locations(&references, cx)))
+        })
+    }
+}
@@ -0,0 +1,118 @@
+use super::code_navigation::{
+    format_locations, read_only_permission, resolve_symbol, tool_call_locations,
+};
+use crate::{AgentTool, ToolCallEventStream, ToolPermissionDecision};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+/// Finds where a symbol is defined, using the project's language servers.
+///
+/// Prefer this over grepping when you need the definition of a function, type, or variable that is used in code you're reading: it follows imports, re-exports and methods the way the compiler does.
+///
+/// Returns one `path:line: snippet` entry per definition.
+///
+/// <example>
+/// To find the definition of `parse_config`, called on line 42 of `src/main.rs` in the `app` root directory:
+/// {
+///     "path": "app/src/main.rs",
+///     "line": 42,
+///     "symbol": "parse_config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GoToDefinitionToolInput {
+    /// The relative path of the file that contains the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based number of the line that contains the symbol.
+    pub line: u32,
+    /// The symbol, exactly as it appears on that line. Its first occurrence on the line is used.
+    pub symbol: String,
+}
+
+pub struct GoToDefinitionTool {
+    project: Entity<Project>,
+}
+
+impl GoToDefinitionTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GoToDefinitionTool {
+    type Input = GoToDefinitionToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "go_to_definition";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => {
+                format!("Go to definition of {}", MarkdownInlineCode(&input.symbol)).into()
+            }
+            Err(_) => "Go to definition".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        if let ToolPermissionDecision::Deny(reason) =
+            read_only_permission(Self::NAME, &input.path, cx)
+        {
+            return Task::ready(Err(anyhow!("{}", reason)));
+        }
+
+        let symbol = resolve_symbol(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbol = symbol.await?;
+            let definitions = project.update(cx, |project, cx| {
+                project.definitions(&symbol.buffer, symbol.point, cx)
+            });
+            let definitions = futures::select! {
+                result = definitions.fuse() => result?.unwrap_or_default(),
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    anyhow::bail!("Go to definition cancelled by user");
+                }
+            };
+            if definitions.is_empty() {
+                return Ok(format!(
+                    "No definition found for {}",
+                    MarkdownInlineCode(&input.symbol)
+                ));
+            }
+
+            let locations = definitions
+                .into_iter()
+                .map(|link| link.target)
+                .collect::<Vec<_>>();
+            event_stream.update_fields(
+                acp::ToolCallUpdateFields::new()
+                    .locations(tool_call_locations(&project, &locations, cx)),
+            );
+            Ok(cx.update(|cx| format_locations(&locations, cx)))
+        })
+    }
+}
@@ -0,0 +1,126 @@
+use super::code_navigation::{read_only_permission, resolve_symbol};
+use crate::{AgentTool, ToolCallEventStream, ToolPermissionDecision};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::{HoverBlockKind, Project};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::{MarkdownCodeBlock, MarkdownInlineCode};
+
+/// Shows the type and documentation of a symbol, as the project's language servers report them when hovering it.
+///
+/// Use this to learn the inferred type of a variable, the signature of a function, or the documentation of a type without reading its definition.
+///
+/// <example>
+/// To see the type of the `settings` variable on line 12 of `src/main.rs` in the `app` root directory:
+/// {
+///     "path": "app/src/main.rs",
+///     "line": 12,
+///     "symbol": "settings"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HoverToolInput {
+    /// The relative path of the file that contains the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based number of the line that contains the symbol.
+    pub line: u32,
+    /// The symbol, exactly as it appears on that line. Its first occurrence on the line is used.
+    pub symbol: String,
+}
+
+pub struct HoverTool {
+    project: Entity<Project>,
+}
+
+impl HoverTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for HoverTool {
+    type Input = HoverToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "hover";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Inspect {}", MarkdownInlineCode(&input.symbol)).into(),
+            Err(_) => "Inspect symbol".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        if let ToolPermissionDecision::Deny(reason) =
+            read_only_permission(Self::NAME, &input.path, cx)
+        {
+            return Task::ready(Err(anyhow!("{}", reason)));
+        }
+
+        let symbol = resolve_symbol(&self.project, &input.path, input.line, &input.symbol, cx);
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbol = symbol.await?;
+            if let Some(abs_path) = symbol.abs_path.clone() {
+                event_stream.update_fields(acp::ToolCallUpdateFields::new().locations(vec![
+                    acp::ToolCallLocation::new(abs_path).line(Some(symbol.point.row)),
+                ]));
+            }
+
+            let hovers = project.update(cx, |project, cx| {
+                project.hover(&symbol.buffer, symbol.point, cx)
+            });
+            let hovers = futures::select! {
+                hovers = hovers.fuse() => hovers.unwrap_or_default(),
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    anyhow::bail!("Hover cancelled by user");
+                }
+            };
+
+            let blocks = hovers
+                .iter()
+                .flat_map(|hover| &hover.contents)
+                .filter(|block| !block.text.trim().is_empty())
+                .map(|block| match &block.kind {
+                    HoverBlockKind::Code { language } => MarkdownCodeBlock {
+                        tag: language,
+                        text: &block.text,
+                    }
+                    .to_string(),
+                    HoverBlockKind::PlainText | HoverBlockKind::Markdown => {
+                        block.text.trim().to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+            if blocks.is_empty() {
+                Ok(format!(
+                    "No hover information for {}",
+                    MarkdownInlineCode(&input.symbol)
+                ))
+            } else {
+                Ok(blocks.join("\n\n"))
+            }
+        })
+    }
+}
@@ -0,0 +1,400 @@
+use super::code_navigation::resolve_symbol;
+use crate::{
+    AgentTool, EditFileTool, Thread, ToolCallEventStream, ToolPermissionDecision,
+    decide_permission_from_settings,
+};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::{Result, anyhow};
+use collections::HashSet;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task, WeakEntity};
+use language::Buffer;
+use project::{Project, ProjectPath};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{fmt::Write as _, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// Renames a symbol and updates every reference to it across the project, using the project's language servers.
+///
+/// Prefer this over editing files one by one when renaming a function, type, variable or field: the language server finds every reference, including ones a text search would miss, and leaves unrelated symbols with the same name alone.
+///
+/// The changed files are saved, except for ones outside the project, which are left for the user to review. Read them again before editing them further.
+///
+/// <example>
+/// To rename the `parse` function declared on line 7 of `src/config.rs` in the `app` root directory to `parse_config`:
+/// {
+///     "path": "app/src/config.rs",
+///     "line": 7,
+///     "symbol": "parse",
+///     "new_name": "parse_config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenameSymbolToolInput {
+    /// The relative path of a file that contains the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based number of the line that contains the symbol.
+    pub line: u32,
+    /// The symbol, exactly as it appears on that line. Its first occurrence on the line is used.
+    pub symbol: String,
+    /// The new name of the symbol.
+    pub new_name: String,
+}
+
+pub struct RenameSymbolTool {
+    thread: WeakEntity<Thread>,
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+}
+
+impl RenameSymbolTool {
+    pub fn new(
+        thread: WeakEntity<Thread>,
+        project: Entity<Project>,
+        action_log: Entity<ActionLog>,
+    ) -> Self {
+        Self {
+            thread,
+            project,
+            action_log,
+        }
+    }
+
+    pub fn with_thread(&self, new_thread: WeakEntity<Thread>) -> Self {
+        Self {
+            thread: new_thread,
+            project: self.project.clone(),
+            action_log: self.action_log.clone(),
+        }
+    }
+
+    /// Checks every file the rename changes against the permissions of this tool, and against
+    /// the ones of the edit file tool, since the rename edits those files too. Renames that
+    /// change settings files or files outside the project must be confirmed.
+    fn authorize(
+        &self,
+        input: &RenameSymbolToolInput,
+        buffers: &HashSet<Entity<Buffer>>,
+        event_stream: &ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        let settings = AgentSettings::get_global(cx);
+        let project = self.project.read(cx);
+        let mut confirm = false;
+        let mut reasons = Vec::new();
+        for file in buffers
+            .iter()
+            .filter_map(|buffer| RenamedFile::new(buffer, project, cx))
+        {
+            for tool_name in [Self::NAME, EditFileTool::NAME] {
+                match decide_permission_from_settings(tool_name, &file.path, settings) {
+                    ToolPermissionDecision::Deny(reason) => {
+                        return Task::ready(Err(anyhow!("{}", reason)));
+                    }
+                    ToolPermissionDecision::Confirm if tool_name == Self::NAME => confirm = true,
+                    ToolPermissionDecision::Allow | ToolPermissionDecision::Confirm => {}
+                }
+            }
+            if let Some(reason) = file.confirmation_reason()
+                && !settings.always_allow_tool_actions
+                && !reasons.contains(&reason)
+            {
+                reasons.push(reason);
+            }
+        }
+        if !confirm && reasons.is_empty() {
+            return Task::ready(Ok(()));
+        }
+
+        let mut title = format!(
+            "Rename {} to {}",
+            MarkdownInlineCode(&input.symbol),
+            MarkdownInlineCode(&input.new_name)
+        );
+        if !reasons.is_empty() {
+            reasons.sort();
+            write!(title, " ({})", reasons.join(", ")).ok();
+        }
+        let context = crate::ToolPermissionContext {
+            tool_name: Self::NAME.to_string(),
+            input_value: input.path.clone(),
+        };
+        event_stream.authorize(title, context, cx)
+    }
+}
+
+/// A file that a rename changes.
+struct RenamedFile {
+    /// The path of the file, starting with the name of its worktree.
+    path: String,
+    /// Whether the file is in one of the project's worktrees, rather than only opened by it.
+    in_project: bool,
+    is_local_settings: bool,
+    is_global_settings: bool,
+}
+
+impl RenamedFile {
+    fn new(buffer: &Entity<Buffer>, project: &Project, cx: &App) -> Option<Self> {
+        let file = buffer.read(cx).file()?;
+        let in_project = project
+            .worktree_for_id(file.worktree_id(cx), cx)
+            .is_some_and(|worktree| worktree.read(cx).is_visible());
+        let is_local_settings = file
+            .path()
+            .components()
+            .any(|component| component == paths::local_settings_folder_name());
+        let is_global_settings = project
+            .absolute_path(&ProjectPath::from_file(file.as_ref(), cx), cx)
+            .is_some_and(|abs_path| abs_path.starts_with(paths::config_dir()));
+        Some(Self {
+            path: file.full_path(cx).display().to_string(),
+            in_project,
+            is_local_settings,
+            is_global_settings,
+        })
+    }
+
+    fn confirmation_reason(&self) -> Option<&'static str> {
+        if self.is_local_settings {
+            Some("local settings")
+        } else if self.is_global_settings {
+            Some("global settings")
+        } else if !self.in_project {
+            Some("outside the project")
+        } else {
+            None
+        }
+    }
+}
+
+impl AgentTool for RenameSymbolTool {
+    type Input = RenameSymbolToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "rename_symbol";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!(
+                "Rename {} to {}",
+                MarkdownInlineCode(&input.symbol),
+                MarkdownInlineCode(&input.new_name)
+            )
+            .into(),
+            Err(_) => "Rename symbol".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let symbol = resolve_symbol(&self.project, &input.path, input.line, &input.symbol, cx);
+        cx.spawn(async move |cx| {
+            let symbol = symbol.await?;
+
+            // Check every file that the rename is going to change, and track it before it
+            // changes, so that the user can review and reject the edits.
+            let references = self.project.update(cx, |project, cx| {
+                project.references(&symbol.buffer, symbol.point, cx)
+            });
+            let references = futures::select! {
+                result = references.fuse() => result?.unwrap_or_default(),
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    anyhow::bail!("Rename cancelled by user");
+                }
+            };
+            let mut buffers = HashSet::default();
+            buffers.insert(symbol.buffer.clone());
+            buffers.extend(references.into_iter().map(|location| location.buffer));
+            let dirty_paths = cx.update(|cx| {
+                let mut dirty_paths = buffers
+                    .iter()
+                    .filter(|buffer| buffer.read(cx).is_dirty())
+                    .filter_map(|buffer| Some(buffer.read(cx).file()?.full_path(cx)))
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>();
+                dirty_paths.sort();
+                dirty_paths
+            });
+            if !dirty_paths.is_empty() {
+                anyhow::bail!(
+                    "These files have unsaved changes: {}. Ask the user to save or discard \
+                     those changes, then retry the rename.",
+                    dirty_paths.join(", ")
+                );
+            }
+            let authorize = cx.update(|cx| self.authorize(&input, &buffers, &event_stream, cx));
+            authorize.await?;
+            self.action_log.update(cx, |log, cx| {
+                for buffer in &buffers {
+                    log.buffer_read(buffer.clone(), cx);
+                }
+            });
+
+            let rename = self.project.update(cx, |project, cx| {
+                project.perform_rename(
+                    symbol.buffer.clone(),
+                    symbol.point,
+                    input.new_name.clone(),
+                    cx,
+                )
+            });
+            let transaction = rename.await?;
+            if transaction.0.is_empty() {
+                anyhow::bail!(
+                    "The language server didn't rename {}",
+                    MarkdownInlineCode(&input.symbol)
+                );
+            }
+
+            // The language server's edit can change files that the references didn't include,
+            // which weren't checked against the edit permissions, so undo the rename then.
+            let unauthorized_paths = cx.update(|cx| {
+                let mut paths = transaction
+                    .0
+                    .keys()
+                    .filter(|buffer| !buffers.contains(*buffer))
+                    .map(|buffer| match buffer.read(cx).file() {
+                        Some(file) => file.full_path(cx).display().to_string(),
+                        None => "an untitled buffer".to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                if !paths.is_empty() {
+                    for (buffer, transaction) in &transaction.0 {
+                        buffer.update(cx, |buffer, cx| {
+                            buffer.undo_transaction(transaction.id, cx);
+                        });
+                    }
+                }
+                paths.sort();
+                paths
+            });
+            if !unauthorized_paths.is_empty() {
+                anyhow::bail!(
+                    "The rename was undone, because the language server also changed files that \
+                     don't reference {}: {}. Edit the files directly instead.",
+                    MarkdownInlineCode(&input.symbol),
+                    unauthorized_paths.join(", ")
+                );
+            }
+
+            // Files outside the project's worktrees are left for the user to review and save.
+            let changed_buffers = transaction.0.into_keys().collect::<HashSet<_>>();
+            let (saved_buffers, unsaved_buffers): (HashSet<_>, HashSet<_>) = cx.update(|cx| {
+                changed_buffers.into_iter().partition(|buffer| {
+                    RenamedFile::new(buffer, self.project.read(cx), cx)
+                        .is_some_and(|file| file.in_project)
+                })
+            });
+            self.project
+                .update(cx, |project, cx| {
+                    project.save_buffers(saved_buffers.clone(), cx)
+                })
+                .await?;
+            self.action_log.update(cx, |log, cx| {
+                for buffer in saved_buffers.iter().chain(&unsaved_buffers) {
+                    log.buffer_edited(buffer.clone(), cx);
+                }
+            });
+
+            let file_info = |buffer: &Entity<Buffer>, cx: &App| {
+                let file = buffer.read(cx).file()?;
+                let abs_path = self
+                    .project
+                    .read(cx)
+                    .absolute_path(&ProjectPath::from_file(file.as_ref(), cx), cx);
+                Some((
+                    file.full_path(cx).display().to_string(),
+                    abs_path,
+                    file.disk_state().mtime(),
+                ))
+            };
+            let (changed_files, unsaved_files) = cx.update(|cx| {
+                (
+                    saved_buffers
+                        .iter()
+                        .filter_map(|buffer| file_info(buffer, cx))
+                        .collect::<Vec<_>>(),
+                    unsaved_buffers
+                        .iter()
+                        .filter_map(|buffer| file_info(buffer, cx))
+                        .collect::<Vec<_>>(),
+                )
+            });
+            // The files changed on disk, but in a way the agent knows about, so it can keep
+            // editing the ones it read before.
+            self.thread
+                .update(cx, |thread, _| {
+                    for (_, abs_path, mtime) in &changed_files {
+                        if let (Some(abs_path), Some(mtime)) = (abs_path, mtime)
+                            && let Some(read_time) = thread.file_read_times.get_mut(abs_path)
+                        {
+                            *read_time = *mtime;
+                        }
+                    }
+                })
+                .ok();
+            event_stream.update_fields(
+                acp::ToolCallUpdateFields::new().locations(
+                    changed_files
+                        .iter()
+                        .chain(&unsaved_files)
+                        .filter_map(|(_, abs_path, _)| abs_path.clone())
+                        .map(acp::ToolCallLocation::new)
+                        .collect::<Vec<_>>(),
+                ),
+            );
+            let mut changed_files = changed_files
+                .into_iter()
+                .map(|(path, _, _)| (path, true))
+                .chain(unsaved_files.into_iter().map(|(path, _, _)| (path, false)))
+                .collect::<Vec<_>>();
+            changed_files.sort();
+
+            let mut output = format!(
+                "Renamed {} to {} in {} {}:\n",
+                MarkdownInlineCode(&input.symbol),
+                MarkdownInlineCode(&input.new_name),
+                changed_files.len(),
+                if changed_files.len() == 1 {
+                    "file"
+                } else {
+                    "files"
+                }
+            );
+            for (path, saved) in changed_files {
+                if saved {
+                    writeln!(output, "{path}").ok();
+                } else {
+                    writeln!(output, "{path} (outside the project, not saved)").ok();
+                }
+            }
+            Ok(output)
+        })
+    }
+
+    fn rebind_thread(
+        &self,
+        new_thread: WeakEntity<Thread>,
+    ) -> Option<std::sync::Arc<dyn crate::AnyAgentTool>> {
+        Some(self.with_thread(new_thread).erase())
+    }
+}
@@ -1,8 +1,8 @@
 use agent_client_protocol as acp;
 use agent_settings::AgentSettings;
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use futures::FutureExt as _;
-use gpui::{App, Entity, SharedString, Task};
+use gpui::{App, Entity, SharedString, Task, WeakEntity};
 use project::Project;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -13,10 +13,12 @@ use std::{
     sync::Arc,
     time::Duration,
 };
+use util::shell::get_default_system_shell_preferring_bash;
 
 use crate::{
-    AgentTool, ThreadEnvironment, ToolCallEventStream, ToolPermissionDecision,
+    AgentTool, Thread, ThreadEnvironment, ToolCallEventStream, ToolPermissionDecision,
     decide_permission_from_settings,
+    terminal_sandbox::{SandboxPolicy, find_bwrap},
 };
 
 const COMMAND_OUTPUT_LIMIT: u64 = 16 * 1024;
@@ -49,16 +51,58 @@ pub struct TerminalToolInput {
 
 pub struct TerminalTool {
     project: Entity<Project>,
+    thread: WeakEntity<Thread>,
     environment: Rc<dyn ThreadEnvironment>,
 }
 
 impl TerminalTool {
-    pub fn new(project: Entity<Project>, environment: Rc<dyn ThreadEnvironment>) -> Self {
+    pub fn new(
+        project: Entity<Project>,
+        thread: WeakEntity<Thread>,
+        environment: Rc<dyn ThreadEnvironment>,
+    ) -> Self {
         Self {
             project,
+            thread,
             environment,
         }
     }
+
+    pub fn with_thread(&self, new_thread: WeakEntity<Thread>) -> Self {
+        Self {
+            project: self.project.clone(),
+            thread: new_thread,
+            environment: self.environment.clone(),
+        }
+    }
+
+    /// Returns the sandbox that the thread's profile runs commands in, if any.
+    fn sandbox_policy(&self, cx: &App) -> Result<Option<SandboxPolicy>> {
+        let Some(sandbox) = self
+            .thread
+            .read_with(cx, |thread, cx| {
+                AgentSettings::get_global(cx)
+                    .profiles
+                    .get(thread.profile())
+                    .and_then(|profile| profile.terminal_sandbox.clone())
+            })
+            .ok()
+            .flatten()
+        else {
+            return Ok(None);
+        };
+
+        let project = self.project.read(cx);
+        anyhow::ensure!(
+            project.is_local(),
+            "The terminal sandbox is only supported for local projects. Disable `terminal_sandbox` in the agent profile to run commands."
+        );
+        let worktree_paths = project
+            .worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .collect();
+        Ok(Some(SandboxPolicy::new(&sandbox, worktree_paths)))
+    }
 }
 
 impl AgentTool for TerminalTool {
@@ -115,14 +159,22 @@ impl AgentTool for TerminalTool {
                 authorize.await?;
             }
 
+            // The thread is being updated while it runs tools, so read its profile afterwards.
+            let sandbox_policy = cx.update(|cx| self.sandbox_policy(cx))?;
+            let command = match &sandbox_policy {
+                Some(policy) => {
+                    let bwrap = cx.background_spawn(async { find_bwrap() }).await?;
+                    policy.wrap_command(
+                        &bwrap.to_string_lossy(),
+                        &get_default_system_shell_preferring_bash(),
+                        &input.command,
+                    )?
+                }
+                None => input.command.clone(),
+            };
             let terminal = self
                 .environment
-                .create_terminal(
-                    input.command.clone(),
-                    working_dir,
-                    Some(COMMAND_OUTPUT_LIMIT),
-                    cx,
-                )
+                .create_terminal(command, working_dir, Some(COMMAND_OUTPUT_LIMIT), cx)
                 .await?;
 
             let terminal_id = terminal.id(cx)?;
@@ -178,6 +230,21 @@ impl AgentTool for TerminalTool {
 
             let output = terminal.current_output(cx)?;
 
+            if let Some(policy) = &sandbox_policy
+                && !timed_out
+                && !user_stopped
+                && output
+                    .exit_status
+                    .as_ref()
+                    .is_some_and(|status| status.exit_code != Some(0))
+                && let Some(violation) = policy.violation(&output.output)
+            {
+                return Err(anyhow!(
+                    "{violation}\n\n{}",
+                    process_content(output, &input.command, timed_out, user_stopped)
+                ));
+            }
+
             Ok(process_content(
                 output,
                 &input.command,
@@ -186,6 +253,13 @@ impl AgentTool for TerminalTool {
             ))
         })
     }
+
+    fn rebind_thread(
+        &self,
+        new_thread: WeakEntity<Thread>,
+    ) -> Option<std::sync::Arc<dyn crate::AnyAgentTool>> {
+        Some(self.with_thread(new_thread).erase())
+    }
 }
 
 fn process_content(
@@ -0,0 +1,150 @@
+use super::code_navigation::{MAX_LOCATIONS, read_only_permission};
+use crate::{AgentTool, ToolCallEventStream, ToolPermissionDecision};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::{Project, Symbol, lsp_store::SymbolLocation};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write as _, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// Searches the symbols declared across the project, such as types, functions and constants, using the project's language servers.
+///
+/// Use this to locate a symbol by name when you don't know which file declares it. The query is matched fuzzily against symbol names.
+///
+/// Returns one `kind name — path:line` entry per symbol.
+///
+/// <example>
+/// To find the declaration of a `RequestHandler` type:
+/// {
+///     "query": "RequestHandler"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSymbolsToolInput {
+    /// The name, or part of the name, of the symbols to search for.
+    pub query: String,
+}
+
+pub struct WorkspaceSymbolsTool {
+    project: Entity<Project>,
+}
+
+impl WorkspaceSymbolsTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for WorkspaceSymbolsTool {
+    type Input = WorkspaceSymbolsToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "workspace_symbols";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Search symbols for {}", MarkdownInlineCode(&input.query)).into(),
+            Err(_) => "Search symbols".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        if let ToolPermissionDecision::Deny(reason) =
+            read_only_permission(Self::NAME, &input.query, cx)
+        {
+            return Task::ready(Err(anyhow!("{}", reason)));
+        }
+
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.symbols(&input.query, cx));
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbols = futures::select! {
+                result = symbols.fuse() => result?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    anyhow::bail!("Symbol search cancelled by user");
+                }
+            };
+            if symbols.is_empty() {
+                return Ok(format!(
+                    "No symbols found for {}",
+                    MarkdownInlineCode(&input.query)
+                ));
+            }
+
+            let mut output = String::new();
+            cx.update(|cx| {
+                for symbol in symbols.iter().take(MAX_LOCATIONS) {
+                    write!(output, "{} {}", symbol_kind_name(symbol), symbol.name).ok();
+                    if let Some(container_name) = &symbol.container_name {
+                        write!(output, " (in {container_name})").ok();
+                    }
+                    let path = match &symbol.path {
+                        SymbolLocation::InProject(project_path) => project
+                            .read(cx)
+                            .worktree_for_id(project_path.worktree_id, cx)
+                            .map(|worktree| {
+                                let worktree = worktree.read(cx);
+                                worktree
+                                    .root_name()
+                                    .join(&project_path.path)
+                                    .display(worktree.path_style())
+                                    .into_owned()
+                            }),
+                        SymbolLocation::OutsideProject { abs_path, .. } => {
+                            Some(abs_path.to_string_lossy().into_owned())
+                        }
+                    };
+                    if let Some(path) = path {
+                        write!(output, " — {path}:{}", symbol.range.start.0.row + 1).ok();
+                    }
+                    output.push('\n');
+                }
+            });
+            if symbols.len() > MAX_LOCATIONS {
+                writeln!(output, "…and {} more", symbols.len() - MAX_LOCATIONS).ok();
+            }
+            Ok(output)
+        })
+    }
+}
+
+fn symbol_kind_name(symbol: &Symbol) -> &'static str {
+    match symbol.kind {
+        lsp::SymbolKind::FILE => "file",
+        lsp::SymbolKind::MODULE => "module",
+        lsp::SymbolKind::NAMESPACE => "namespace",
+        lsp::SymbolKind::PACKAGE => "package",
+        lsp::SymbolKind::CLASS => "class",
+        lsp::SymbolKind::METHOD => "method",
+        lsp::SymbolKind::PROPERTY => "property",
+        lsp::SymbolKind::FIELD => "field",
+        lsp::SymbolKind::CONSTRUCTOR => "constructor",
+        lsp::SymbolKind::ENUM => "enum",
+        lsp::SymbolKind::INTERFACE => "interface",
+        lsp::SymbolKind::FUNCTION => "function",
+        lsp::SymbolKind::VARIABLE => "variable",
+        lsp::SymbolKind::CONSTANT => "constant",
+        lsp::SymbolKind::STRUCT => "struct",
+        lsp::SymbolKind::ENUM_MEMBER => "variant",
+        lsp::SymbolKind::TYPE_PARAMETER => "type parameter",
+        _ => "symbol",
+    }
+}
@@ -0,0 +1,223 @@
+//! Accounting of the tokens used by the agent's requests, and of their estimated cost.
+//!
+//! Every completion request is recorded in the `token_usage` table of the threads database.
+//! Costs are estimated from the `agent.token_prices` setting whenever they're needed, so that
+//! changing a price applies to past requests too.
+
+use crate::{DbTokenUsage, ThreadsDatabase};
+use agent_client_protocol as acp;
+use agent_settings::{TokenPrices, UsageLimit, UsageLimits};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use gpui::AsyncApp;
+use std::fmt;
+
+/// The estimated cost of the agent's requests, in US dollars.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UsageCost {
+    /// The cost of the thread's requests.
+    pub thread: f64,
+    /// The cost of the requests of all threads, since midnight.
+    pub today: f64,
+}
+
+impl UsageCost {
+    /// Loads the usage of a thread and of today's requests, and estimates their cost.
+    pub async fn load(
+        thread_id: acp::SessionId,
+        prices: TokenPrices,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        let database_future = cx.update(|cx| ThreadsDatabase::connect(cx));
+        let database = database_future.await.map_err(|err| anyhow!(err))?;
+        let thread_usage = database.thread_token_usage(thread_id).await?;
+        let today_usage = database.token_usage_since(start_of_today()).await?;
+        Ok(Self {
+            thread: cost(&thread_usage, &prices),
+            today: cost(&today_usage, &prices),
+        })
+    }
+}
+
+/// Returns the estimated cost of the given requests. Requests of models without a price are
+/// free.
+pub fn cost(records: &[DbTokenUsage], prices: &TokenPrices) -> f64 {
+    records
+        .iter()
+        .filter_map(|record| prices.cost(&record.provider, &record.model, &record.usage))
+        .sum()
+}
+
+fn start_of_today() -> DateTime<Utc> {
+    let midnight = Local::now().date_naive().and_time(NaiveTime::MIN);
+    midnight
+        .and_local_timezone(Local)
+        .earliest()
+        .map_or_else(Utc::now, |midnight| midnight.with_timezone(&Utc))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UsageLimitScope {
+    Thread,
+    Daily,
+}
+
+/// A usage limit that the agent's next request would exceed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UsageLimitExceeded {
+    pub scope: UsageLimitScope,
+    pub limit: f64,
+    pub hard: bool,
+}
+
+impl std::error::Error for UsageLimitExceeded {}
+
+impl fmt::Display for UsageLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scope = match self.scope {
+            UsageLimitScope::Thread => "this thread",
+            UsageLimitScope::Daily => "today",
+        };
+        if self.hard {
+            write!(
+                f,
+                "The agent stopped because its next request would exceed the hard usage limit of ${:.2} for {scope}.",
+                self.limit
+            )
+        } else {
+            write!(
+                f,
+                "The agent paused because its next request would exceed the soft usage limit of ${:.2} for {scope}. Send a message to continue.",
+                self.limit
+            )
+        }
+    }
+}
+
+/// Checks whether the agent can make a request estimated to cost `next_request`, after
+/// already spending `cost`. Soft limits are skipped once the user continued past them.
+pub fn check_usage_limits(
+    cost: UsageCost,
+    next_request: f64,
+    limits: &UsageLimits,
+    soft_limits_acknowledged: bool,
+) -> Result<(), UsageLimitExceeded> {
+    let check = |scope, spent: f64, limit: &UsageLimit| {
+        let projected = spent + next_request;
+        if let Some(hard) = limit.hard
+            && projected > hard
+        {
+            return Err(UsageLimitExceeded {
+                scope,
+                limit: hard,
+                hard: true,
+            });
+        }
+        if let Some(soft) = limit.soft
+            && projected > soft
+            && !soft_limits_acknowledged
+        {
+            return Err(UsageLimitExceeded {
+                scope,
+                limit: soft,
+                hard: false,
+            });
+        }
+        Ok(())
+    };
+
+    check(UsageLimitScope::Thread, cost.thread, &limits.thread)?;
+    check(UsageLimitScope::Daily, cost.today, &limits.daily)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_settings::TokenPrice;
+    use language_model::TokenUsage;
+
+    #[test]
+    fn test_cost() {
+        let mut prices = TokenPrices::default();
+        prices.0.entry("anthropic".into()).or_default().insert(
+            "claude-sonnet-4".into(),
+            TokenPrice {
+                input: 3.,
+                output: 15.,
+                cache_write: 3.75,
+                cache_read: 0.25,
+            },
+        );
+        let record = |model: &str, input_tokens, output_tokens| DbTokenUsage {
+            thread_id: acp::SessionId::new("thread"),
+            provider: "anthropic".into(),
+            model: model.into(),
+            created_at: Utc::now(),
+            usage: TokenUsage {
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 0,
+            },
+        };
+
+        let records = [
+            record("claude-sonnet-4", 1_000_000, 0),
+            record("claude-sonnet-4", 0, 100_000),
+            record("unpriced-model", 1_000_000, 1_000_000),
+        ];
+        assert_eq!(cost(&records, &prices), 4.5);
+    }
+
+    #[test]
+    fn test_check_usage_limits() {
+        let limits = UsageLimits {
+            thread: UsageLimit {
+                soft: Some(1.),
+                hard: Some(2.),
+            },
+            daily: UsageLimit {
+                soft: None,
+                hard: Some(10.),
+            },
+        };
+        let cost = |thread, today| UsageCost { thread, today };
+
+        assert_eq!(
+            check_usage_limits(cost(0.5, 5.), 0.25, &limits, false),
+            Ok(())
+        );
+        assert_eq!(
+            check_usage_limits(cost(0.9, 5.), 0.25, &limits, false),
+            Err(UsageLimitExceeded {
+                scope: UsageLimitScope::Thread,
+                limit: 1.,
+                hard: false,
+            })
+        );
+        assert_eq!(
+            check_usage_limits(cost(0.9, 5.), 0.25, &limits, true),
+            Ok(())
+        );
+        assert_eq!(
+            check_usage_limits(cost(1.9, 5.), 0.25, &limits, true),
+            Err(UsageLimitExceeded {
+                scope: UsageLimitScope::Thread,
+                limit: 2.,
+                hard: true,
+            })
+        );
+        assert_eq!(
+            check_usage_limits(cost(0., 9.9), 0.25, &limits, true),
+            Err(UsageLimitExceeded {
+                scope: UsageLimitScope::Daily,
+                limit: 10.,
+                hard: true,
+            })
+        );
+        assert_eq!(
+            check_usage_limits(cost(0., 0.), 0., &UsageLimits::default(), false),
+            Ok(())
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{Result, bail};
 use collections::IndexMap;
@@ -7,9 +7,9 @@ use fs::Fs;
 use gpui::{App, SharedString};
 use settings::{
     AgentProfileContent, ContextServerPresetContent, LanguageModelSelection, Settings as _,
-    SettingsContent, update_settings_file,
+    SettingsContent, TerminalSandboxContent, update_settings_file,
 };
-use util::ResultExt as _;
+use util::{ResultExt as _, paths::home_dir};
 
 use crate::{AgentProfileId, AgentSettings};
 
@@ -70,6 +70,9 @@ impl AgentProfile {
         let default_model = base_profile
             .as_ref()
             .and_then(|profile| profile.default_model.clone());
+        let terminal_sandbox = base_profile
+            .as_ref()
+            .and_then(|profile| profile.terminal_sandbox.clone());
 
         let profile_settings = AgentProfileSettings {
             name: name.into(),
@@ -77,6 +80,7 @@ impl AgentProfile {
             enable_all_context_servers,
             context_servers,
             default_model,
+            terminal_sandbox,
         };
 
         update_settings_file(fs, cx, {
@@ -109,6 +113,8 @@ pub struct AgentProfileSettings {
     pub context_servers: IndexMap<Arc<str>, ContextServerPreset>,
     /// Default language model to apply when this profile becomes active.
     pub default_model: Option<LanguageModelSelection>,
+    /// The sandbox in which the terminal tool runs commands, if it's enabled.
+    pub terminal_sandbox: Option<TerminalSandbox>,
 }
 
 impl AgentProfileSettings {
@@ -158,6 +164,7 @@ impl AgentProfileSettings {
                     })
                     .collect(),
                 default_model: self.default_model.clone(),
+                terminal_sandbox: self.terminal_sandbox.as_ref().map(Into::into),
             },
         );
 
@@ -173,6 +180,7 @@ impl From<AgentProfileContent> for AgentProfileSettings {
             enable_all_context_servers,
             context_servers,
             default_model,
+            terminal_sandbox,
         } = content;
 
         Self {
@@ -184,6 +192,36 @@ impl From<AgentProfileContent> for AgentProfileSettings {
                 .map(|(server_id, preset)| (server_id, preset.into()))
                 .collect(),
             default_model,
+            terminal_sandbox: terminal_sandbox
+                .filter(|sandbox| sandbox.enabled.unwrap_or(false))
+                .map(TerminalSandbox::from),
+        }
+    }
+}
+
+/// Where the commands of the terminal tool can write to, and whether they can access the
+/// network. Commands can always write to the project's worktrees and a temporary directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalSandbox {
+    pub allow_network: bool,
+    pub writable_paths: Vec<PathBuf>,
+}
+
+impl From<TerminalSandboxContent> for TerminalSandbox {
+    fn from(content: TerminalSandboxContent) -> Self {
+        Self {
+            allow_network: content.allow_network.unwrap_or(true),
+            writable_paths: content
+                .writable_paths
+                .unwrap_or_default()
+                .into_iter()
+                .map(|path| match path.strip_prefix('~') {
+                    Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                        home_dir().join(rest.trim_start_matches('/'))
+                    }
+                    _ => PathBuf::from(path),
+                })
+                .collect(),
         }
     }
 }
@@ -200,3 +238,19 @@ impl From<settings::ContextServerPresetContent> for ContextServerPreset {
         }
     }
 }
+
+impl From<&TerminalSandbox> for TerminalSandboxContent {
+    fn from(sandbox: &TerminalSandbox) -> Self {
+        Self {
+            enabled: Some(true),
+            allow_network: Some(sandbox.allow_network),
+            writable_paths: Some(
+                sandbox
+                    .writable_paths
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect(),
+            ),
+        }
+    }
+}
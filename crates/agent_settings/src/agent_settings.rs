@@ -1,11 +1,11 @@
 mod agent_profile;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use agent_client_protocol::ModelId;
-use collections::{HashSet, IndexMap};
+use collections::{HashMap, HashSet, IndexMap};
 use gpui::{App, Pixels, px};
-use language_model::LanguageModel;
+use language_model::{LanguageModel, TokenUsage};
 use project::DisableAiSettings;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -51,6 +51,9 @@ pub struct AgentSettings {
     pub message_editor_min_lines: usize,
     pub show_turn_stats: bool,
     pub tool_permissions: ToolPermissions,
+    pub hooks: AgentHooks,
+    pub token_prices: TokenPrices,
+    pub usage_limits: UsageLimits,
 }
 
 impl AgentSettings {
@@ -164,6 +167,89 @@ impl Default for ToolRules {
     }
 }
 
+/// Shell commands that run at points of a thread's lifecycle.
+#[derive(Clone, Debug, Default)]
+pub struct AgentHooks {
+    pub pre_tool_use: Vec<AgentHook>,
+    pub post_tool_use: Vec<AgentHook>,
+    pub user_prompt_submit: Vec<AgentHook>,
+    pub stop: Vec<AgentHook>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AgentHook {
+    pub command: String,
+    /// The tools a tool hook applies to, or `None` when it applies to all of them.
+    pub tools: Option<CompiledRegex>,
+    pub timeout: Duration,
+}
+
+impl AgentHook {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+    pub fn applies_to_tool(&self, tool_name: &str) -> bool {
+        self.tools
+            .as_ref()
+            .is_none_or(|tools| tools.is_match(tool_name))
+    }
+}
+
+/// Prices of language models, keyed by provider ID, then by model ID.
+#[derive(Clone, Debug, Default)]
+pub struct TokenPrices(pub HashMap<Arc<str>, HashMap<Arc<str>, TokenPrice>>);
+
+impl TokenPrices {
+    pub fn get(&self, provider_id: &str, model_id: &str) -> Option<&TokenPrice> {
+        self.0.get(provider_id)?.get(model_id)
+    }
+
+    /// Returns the estimated cost of the given usage, or `None` if the model has no price.
+    pub fn cost(&self, provider_id: &str, model_id: &str, usage: &TokenUsage) -> Option<f64> {
+        Some(self.get(provider_id, model_id)?.cost(usage))
+    }
+}
+
+/// The price of a language model's tokens, in US dollars per million tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TokenPrice {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl TokenPrice {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.
+    }
+}
+
+/// Limits on the estimated cost of the agent's requests, in US dollars.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UsageLimits {
+    pub thread: UsageLimit,
+    pub daily: UsageLimit,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UsageLimit {
+    pub soft: Option<f64>,
+    pub hard: Option<f64>,
+}
+
+impl From<settings::UsageLimitContent> for UsageLimit {
+    fn from(content: settings::UsageLimitContent) -> Self {
+        Self {
+            soft: content.soft,
+            hard: content.hard,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct CompiledRegex {
     pub pattern: String,
@@ -241,10 +327,94 @@ impl Settings for AgentSettings {
             message_editor_min_lines: agent.message_editor_min_lines.unwrap(),
             show_turn_stats: agent.show_turn_stats.unwrap(),
             tool_permissions: compile_tool_permissions(agent.tool_permissions),
+            hooks: compile_hooks(agent.hooks),
+            token_prices: token_prices(agent.token_prices),
+            usage_limits: agent
+                .usage_limits
+                .map(|limits| UsageLimits {
+                    thread: limits.thread.map(Into::into).unwrap_or_default(),
+                    daily: limits.daily.map(Into::into).unwrap_or_default(),
+                })
+                .unwrap_or_default(),
         }
     }
 }
 
+fn token_prices(
+    content: Option<HashMap<Arc<str>, HashMap<Arc<str>, settings::TokenPriceContent>>>,
+) -> TokenPrices {
+    TokenPrices(
+        content
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(provider_id, models)| {
+                let models = models
+                    .into_iter()
+                    .map(|(model_id, price)| {
+                        let input = price.input.unwrap_or_default();
+                        let price = TokenPrice {
+                            input,
+                            output: price.output.unwrap_or_default(),
+                            cache_write: price.cache_write.unwrap_or(input),
+                            cache_read: price.cache_read.unwrap_or(input),
+                        };
+                        (model_id, price)
+                    })
+                    .collect();
+                (provider_id, models)
+            })
+            .collect(),
+    )
+}
+
+fn compile_hooks(content: Option<settings::AgentHooksContent>) -> AgentHooks {
+    let Some(content) = content else {
+        return AgentHooks::default();
+    };
+
+    let compile = |hooks: Option<Vec<settings::AgentHookContent>>| {
+        hooks
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|hook| !hook.command.trim().is_empty())
+            .filter_map(|hook| {
+                let tools = match hook.tools {
+                    Some(pattern) => {
+                        // The pattern must match the whole tool name, so that `edit_file`
+                        // doesn't also match `streaming_edit_file`.
+                        match CompiledRegex::try_new(&format!("^(?:{pattern})$"), true) {
+                            Ok(regex) => Some(regex),
+                            Err(error) => {
+                                log::error!(
+                                    "Skipping agent hook `{}` with invalid `tools` pattern '{pattern}': {error}",
+                                    hook.command
+                                );
+                                return None;
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                Some(AgentHook {
+                    command: hook.command,
+                    tools,
+                    timeout: hook
+                        .timeout
+                        .map(Duration::from_secs)
+                        .unwrap_or(AgentHook::DEFAULT_TIMEOUT),
+                })
+            })
+            .collect()
+    };
+
+    AgentHooks {
+        pre_tool_use: compile(content.pre_tool_use),
+        post_tool_use: compile(content.post_tool_use),
+        user_prompt_submit: compile(content.user_prompt_submit),
+        stop: compile(content.stop),
+    }
+}
+
 fn compile_tool_permissions(content: Option<settings::ToolPermissionsContent>) -> ToolPermissions {
     let Some(content) = content else {
         return ToolPermissions::default();
@@ -729,4 +899,71 @@ mod tests {
             "default_mode should be Confirm when not specified"
         );
     }
+
+    #[test]
+    fn test_hooks_parsing() {
+        let json = json!({
+            "pre_tool_use": [
+                { "command": "./check-policy.sh", "tools": "edit_file|terminal" },
+                { "command": "./broken.sh", "tools": "[invalid(regex" },
+            ],
+            "post_tool_use": [
+                { "command": "cargo fmt", "tools": "edit_file", "timeout": 5 },
+            ],
+            "stop": [
+                { "command": "" },
+                { "command": "notify-send done" },
+            ],
+        });
+
+        let content: settings::AgentHooksContent = serde_json::from_value(json).unwrap();
+        let hooks = compile_hooks(Some(content));
+
+        assert_eq!(hooks.pre_tool_use.len(), 1);
+        let policy = &hooks.pre_tool_use[0];
+        assert!(policy.applies_to_tool("edit_file"));
+        assert!(policy.applies_to_tool("terminal"));
+        assert!(!policy.applies_to_tool("streaming_edit_file"));
+        assert!(!policy.applies_to_tool("read_file"));
+        assert_eq!(policy.timeout, AgentHook::DEFAULT_TIMEOUT);
+
+        assert_eq!(hooks.post_tool_use.len(), 1);
+        assert_eq!(hooks.post_tool_use[0].timeout, Duration::from_secs(5));
+
+        assert!(hooks.user_prompt_submit.is_empty());
+        assert_eq!(hooks.stop.len(), 1);
+        assert!(hooks.stop[0].applies_to_tool("anything"));
+    }
+
+    #[test]
+    fn test_token_prices() {
+        let json = json!({
+            "anthropic": {
+                "claude-sonnet-4": { "input": 3.0, "output": 15.0, "cache_read": 0.3 },
+            },
+        });
+
+        let content = serde_json::from_value(json).unwrap();
+        let prices = token_prices(Some(content));
+        assert_eq!(
+            prices.get("anthropic", "claude-sonnet-4"),
+            Some(&TokenPrice {
+                input: 3.0,
+                output: 15.0,
+                cache_write: 3.0,
+                cache_read: 0.3,
+            })
+        );
+        assert_eq!(prices.get("anthropic", "claude-opus-4"), None);
+
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 2_000_000,
+        };
+        let cost = prices.cost("anthropic", "claude-sonnet-4", &usage).unwrap();
+        assert!((cost - (3.0 + 1.5 + 0.6)).abs() < 1e-9, "{cost}");
+        assert_eq!(prices.cost("openai", "gpt-5", &usage), None);
+    }
 }
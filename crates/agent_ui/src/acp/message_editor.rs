@@ -79,6 +79,7 @@ impl PromptCompletionProviderDelegate for Entity<MessageEditor> {
                 PromptContextType::Diagnostics,
                 PromptContextType::Fetch,
                 PromptContextType::Rules,
+                PromptContextType::Resource,
             ]);
         }
         supported
@@ -191,8 +192,9 @@ impl MessageEditor {
                 {
                     editor.update(cx, |editor, cx| {
                         let snapshot = editor.snapshot(window, cx);
-                        this.mention_set
-                            .update(cx, |mention_set, _cx| mention_set.remove_invalid(&snapshot));
+                        this.mention_set.update(cx, |mention_set, cx| {
+                            mention_set.remove_invalid(&snapshot, cx)
+                        });
 
                         let new_hints = this
                             .command_hint(snapshot.buffer())
@@ -499,9 +501,9 @@ impl MessageEditor {
         self.editor.update(cx, |editor, cx| {
             editor.clear(window, cx);
             editor.remove_creases(
-                self.mention_set.update(cx, |mention_set, _cx| {
+                self.mention_set.update(cx, |mention_set, cx| {
                     mention_set
-                        .clear()
+                        .clear(cx)
                         .map(|(crease_id, _)| crease_id)
                         .collect::<Vec<_>>()
                 }),
@@ -729,8 +731,8 @@ impl MessageEditor {
                         })
                         .shared();
 
-                    self.mention_set.update(cx, |mention_set, _cx| {
-                        mention_set.insert_mention(crease_id, mention_uri.clone(), mention_task)
+                    self.mention_set.update(cx, |mention_set, cx| {
+                        mention_set.insert_mention(crease_id, mention_uri.clone(), mention_task, cx)
                     });
                 }
             }
@@ -803,8 +805,8 @@ impl MessageEditor {
                         .spawn(async move |_, _| task.await.map_err(|e| e.to_string()))
                         .shared();
 
-                    self.mention_set.update(cx, |mention_set, _cx| {
-                        mention_set.insert_mention(crease_id, mention_uri.clone(), task.clone())
+                    self.mention_set.update(cx, |mention_set, cx| {
+                        mention_set.insert_mention(crease_id, mention_uri.clone(), task.clone(), cx)
                     });
 
                     // Drop the tx after inserting to signal the crease is ready
@@ -970,8 +972,8 @@ impl MessageEditor {
         }))
         .shared();
 
-        self.mention_set.update(cx, |mention_set, _| {
-            mention_set.insert_mention(crease_id, mention_uri, mention_task);
+        self.mention_set.update(cx, |mention_set, cx| {
+            mention_set.insert_mention(crease_id, mention_uri, mention_task, cx);
         });
     }
 
@@ -1261,11 +1263,12 @@ impl MessageEditor {
             };
             drop(tx);
 
-            self.mention_set.update(cx, |mention_set, _cx| {
+            self.mention_set.update(cx, |mention_set, cx| {
                 mention_set.insert_mention(
                     crease_id,
                     mention_uri.clone(),
                     Task::ready(Ok(mention)).shared(),
+                    cx,
                 )
             });
         }
@@ -3062,6 +3065,231 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_context_server_resource_mentions(cx: &mut TestAppContext) {
+        use context_server::{
+            ContextServer, ContextServerId,
+            types::{
+                CompletionCompleteResponse, CompletionResult, Implementation, InitializeResponse,
+                ListResourceTemplatesResponse, ProtocolVersion, ResourceContentsType,
+                ResourceTemplate, ResourcesCapabilities, ResourcesListResponse,
+                ResourcesReadResponse, ServerCapabilities, TextResourceContents, requests,
+            },
+        };
+
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/project", json!({})).await;
+        let project = Project::test(fs, [Path::new(path!("/project"))], cx).await;
+        cx.run_until_parked();
+
+        let entity_docs = Arc::new(parking_lot::Mutex::new("pub struct Entity<T>".to_string()));
+        let subscribed_uris = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let transport = context_server::test::create_fake_transport("docs", cx.executor())
+            .on_request::<requests::Initialize, _>(|_| async {
+                InitializeResponse {
+                    protocol_version: ProtocolVersion(
+                        context_server::types::LATEST_PROTOCOL_VERSION.to_string(),
+                    ),
+                    server_info: Implementation {
+                        name: "docs".into(),
+                        version: "1.0.0".into(),
+                    },
+                    capabilities: ServerCapabilities {
+                        completions: Some(json!({})),
+                        resources: Some(ResourcesCapabilities {
+                            subscribe: Some(true),
+                            list_changed: None,
+                        }),
+                        ..Default::default()
+                    },
+                    meta: None,
+                }
+            })
+            .on_request::<requests::ResourcesList, _>(|_| async {
+                ResourcesListResponse {
+                    resources: Vec::new(),
+                    next_cursor: None,
+                    meta: None,
+                }
+            })
+            .on_request::<requests::ListResourceTemplates, _>(|_| async {
+                ListResourceTemplatesResponse {
+                    resource_templates: vec![ResourceTemplate {
+                        uri_template: "docs://{crate}/{item}".into(),
+                        name: "Docs".into(),
+                        description: None,
+                        mime_type: None,
+                    }],
+                    next_cursor: None,
+                    meta: None,
+                }
+            })
+            .on_request::<requests::CompletionComplete, _>(|params| async move {
+                let candidates = match params.argument.name.as_str() {
+                    "crate" => ["gpui", "serde"],
+                    _ => ["App", "Entity"],
+                };
+                CompletionCompleteResponse {
+                    completion: CompletionResult {
+                        values: candidates
+                            .into_iter()
+                            .filter(|candidate| candidate.starts_with(&params.argument.value))
+                            .map(ToString::to_string)
+                            .collect(),
+                        total: None,
+                        has_more: None,
+                        meta: None,
+                    },
+                    meta: None,
+                }
+            })
+            .on_request::<requests::ResourcesRead, _>({
+                let entity_docs = entity_docs.clone();
+                move |params| {
+                    let text = entity_docs.lock().clone();
+                    async move {
+                        ResourcesReadResponse {
+                            contents: vec![ResourceContentsType::Text(TextResourceContents {
+                                uri: params.uri,
+                                mime_type: None,
+                                text,
+                            })],
+                            meta: None,
+                        }
+                    }
+                }
+            })
+            .on_request::<requests::ResourcesSubscribe, _>({
+                let subscribed_uris = subscribed_uris.clone();
+                move |params| {
+                    subscribed_uris.lock().push(params.uri.to_string());
+                    async {}
+                }
+            });
+        let server_messages = transport.sender();
+        let context_server_store =
+            project.read_with(cx, |project, _| project.context_server_store());
+        context_server_store.update(cx, |store, cx| {
+            store.test_start_server(
+                Arc::new(ContextServer::new(
+                    ContextServerId("docs".into()),
+                    Arc::new(transport),
+                )),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let thread_store = Some(cx.new(|cx| ThreadStore::new(cx)));
+        let history = cx
+            .update(|window, cx| cx.new(|cx| crate::acp::AcpThreadHistory::new(None, window, cx)));
+
+        let (message_editor, editor) = workspace.update_in(cx, |workspace, window, cx| {
+            let workspace_handle = cx.weak_entity();
+            let message_editor = cx.new(|cx| {
+                let editor = MessageEditor::new(
+                    workspace_handle,
+                    project.downgrade(),
+                    thread_store.clone(),
+                    history.downgrade(),
+                    None,
+                    Default::default(),
+                    Default::default(),
+                    "Test Agent".into(),
+                    "Test",
+                    EditorMode::AutoHeight {
+                        max_lines: None,
+                        min_lines: 1,
+                    },
+                    window,
+                    cx,
+                );
+                editor
+                    .prompt_capabilities
+                    .replace(acp::PromptCapabilities::new().embedded_context(true));
+                editor
+            });
+            workspace.active_pane().update(cx, |pane, cx| {
+                pane.add_item(
+                    Box::new(cx.new(|_| MessageEditorItem(message_editor.clone()))),
+                    true,
+                    true,
+                    None,
+                    window,
+                    cx,
+                );
+            });
+            message_editor.read(cx).focus_handle(cx).focus(window, cx);
+            let editor = message_editor.read(cx).editor().clone();
+            (message_editor, editor)
+        });
+
+        cx.simulate_input("@resource docs:gpui,En");
+        cx.run_until_parked();
+
+        editor.update_in(cx, |editor, window, cx| {
+            assert_eq!(
+                current_completion_labels(editor),
+                &["Docs (gpui, En)", "Docs (gpui, Entity)"]
+            );
+            editor.context_menu_next(&editor::actions::ContextMenuNext, window, cx);
+            editor.confirm_completion(&editor::actions::ConfirmCompletion::default(), window, cx);
+        });
+        cx.run_until_parked();
+
+        let entity_uri = MentionUri::ContextServerResource {
+            server_id: "docs".into(),
+            uri: "docs://gpui/Entity".parse().unwrap(),
+            name: "Docs (gpui, Entity)".into(),
+        };
+        let mention_contents = |cx: &mut VisualTestContext| {
+            message_editor.update(cx, |message_editor, cx| {
+                message_editor
+                    .mention_set()
+                    .update(cx, |mention_set, cx| mention_set.contents(false, cx))
+            })
+        };
+        let contents = mention_contents(cx)
+            .await
+            .unwrap()
+            .into_values()
+            .collect::<Vec<_>>();
+        let [(uri, Mention::Text { content, .. })] = contents.as_slice() else {
+            panic!("Unexpected mentions");
+        };
+        assert_eq!(uri, &entity_uri);
+        assert_eq!(content, "pub struct Entity<T>");
+        assert_eq!(*subscribed_uris.lock(), ["docs://gpui/Entity"]);
+
+        *entity_docs.lock() = "pub struct Entity<T: 'static>".to_string();
+        server_messages
+            .unbounded_send(
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/resources/updated",
+                    "params": { "uri": "docs://gpui/Entity" },
+                })
+                .to_string(),
+            )
+            .unwrap();
+        cx.run_until_parked();
+
+        let contents = mention_contents(cx)
+            .await
+            .unwrap()
+            .into_values()
+            .collect::<Vec<_>>();
+        let [(uri, Mention::Text { content, .. })] = contents.as_slice() else {
+            panic!("Unexpected mentions");
+        };
+        assert_eq!(uri, &entity_uri);
+        assert_eq!(content, "pub struct Entity<T: 'static>");
+    }
+
     #[gpui::test]
     async fn test_autoscroll_after_insert_selections(cx: &mut TestAppContext) {
         init_test(cx);
@@ -1,12 +1,18 @@
 use crate::acp::AcpServerView;
-use crate::{AgentPanel, RemoveHistory, RemoveSelectedThread};
+use crate::{
+    AgentPanel, ExportSelectedThreadAsJson, ExportSelectedThreadAsMarkdown, ImportThread,
+    RemoveHistory, RemoveSelectedThread,
+};
 use acp_thread::{AgentSessionInfo, AgentSessionList, AgentSessionListRequest, SessionListUpdate};
+use agent::{NativeAgentSessionList, ThreadExport, ThreadStore};
 use agent_client_protocol as acp;
+use anyhow::Context as _;
 use chrono::{Datelike as _, Local, NaiveDate, TimeDelta, Utc};
+use collections::{HashMap, HashSet};
 use editor::{Editor, EditorEvent};
 use fuzzy::StringMatchCandidate;
 use gpui::{
-    App, Entity, EventEmitter, FocusHandle, Focusable, ScrollStrategy, Task,
+    App, Entity, EventEmitter, FocusHandle, Focusable, PathPromptOptions, ScrollStrategy, Task,
     UniformListScrollHandle, WeakEntity, Window, uniform_list,
 };
 use std::{fmt::Display, ops::Range, rc::Rc};
@@ -16,6 +22,7 @@ use ui::{
     ElementId, HighlightedLabel, IconButtonShape, ListItem, ListItemSpacing, Tab, Tooltip,
     WithScrollbar, prelude::*,
 };
+use workspace::notifications::DetachAndPromptErr as _;
 
 const DEFAULT_TITLE: &SharedString = &SharedString::new_static("New Thread");
 
@@ -48,6 +55,8 @@ enum ListItemType {
     Entry {
         entry: AgentSessionInfo,
         format: EntryTimeFormat,
+        /// How many forks away from a thread that isn't a fork the entry is.
+        depth: usize,
     },
     SearchResult {
         entry: AgentSessionInfo,
@@ -69,6 +78,21 @@ pub enum ThreadHistoryEvent {
     Open(AgentSessionInfo),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
 impl EventEmitter<ThreadHistoryEvent> for AcpThreadHistory {}
 
 impl AcpThreadHistory {
@@ -121,7 +145,17 @@ impl AcpThreadHistory {
     fn update_visible_items(&mut self, preserve_selected_item: bool, cx: &mut Context<Self>) {
         let entries = self.sessions.clone();
         let new_list_items = if self.search_query.is_empty() {
-            self.add_list_separators(entries, cx)
+            let parents = self
+                .thread_store()
+                .map(|thread_store| {
+                    thread_store
+                        .read(cx)
+                        .entries()
+                        .filter_map(|thread| Some((thread.id, thread.parent_id?)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            self.add_list_separators(entries, parents, cx)
         } else {
             self.filter_search_results(entries, cx)
         };
@@ -347,6 +381,20 @@ impl AcpThreadHistory {
             .unwrap_or(false)
     }
 
+    /// Returns the store of the threads in the history, if they're threads of the native agent,
+    /// which are the only ones that can be exported and imported.
+    fn thread_store(&self) -> Option<Entity<ThreadStore>> {
+        let session_list = self
+            .session_list
+            .clone()?
+            .downcast::<NativeAgentSessionList>()?;
+        Some(session_list.thread_store().clone())
+    }
+
+    pub fn supports_export(&self) -> bool {
+        self.thread_store().is_some()
+    }
+
     pub(crate) fn delete_session(
         &self,
         session_id: &acp::SessionId,
@@ -359,9 +407,11 @@ impl AcpThreadHistory {
         }
     }
 
+    /// Groups the entries by time, with forks listed under the thread they were forked from.
     fn add_list_separators(
         &self,
         entries: Vec<AgentSessionInfo>,
+        parents: HashMap<acp::SessionId, acp::SessionId>,
         cx: &App,
     ) -> Task<Vec<ListItemType>> {
         cx.background_spawn(async move {
@@ -369,7 +419,7 @@ impl AcpThreadHistory {
             let mut bucket = None;
             let today = Local::now().naive_local().date();
 
-            for entry in entries.into_iter() {
+            for (entry, depth) in sort_as_tree(entries, &parents) {
                 let entry_bucket = entry
                     .updated_at
                     .map(|timestamp| {
@@ -378,7 +428,7 @@ impl AcpThreadHistory {
                     })
                     .unwrap_or(TimeBucket::All);
 
-                if Some(entry_bucket) != bucket {
+                if depth == 0 && Some(entry_bucket) != bucket {
                     bucket = Some(entry_bucket);
                     items.push(ListItemType::BucketSeparator(entry_bucket));
                 }
@@ -386,6 +436,7 @@ impl AcpThreadHistory {
                 items.push(ListItemType::Entry {
                     entry,
                     format: entry_bucket.into(),
+                    depth,
                 });
             }
             items
@@ -548,6 +599,106 @@ impl AcpThreadHistory {
         task.detach_and_log_err(cx);
     }
 
+    fn export_selected_thread_as_markdown(
+        &mut self,
+        _: &ExportSelectedThreadAsMarkdown,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.export_thread(self.selected_index, ExportFormat::Markdown, window, cx)
+    }
+
+    fn export_selected_thread_as_json(
+        &mut self,
+        _: &ExportSelectedThreadAsJson,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.export_thread(self.selected_index, ExportFormat::Json, window, cx)
+    }
+
+    fn export_thread(
+        &mut self,
+        visible_item_ix: usize,
+        format: ExportFormat,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entry) = self.get_history_entry(visible_item_ix) else {
+            return;
+        };
+        let Some(thread_store) = self.thread_store() else {
+            return;
+        };
+
+        let file_name = thread_title(entry)
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        let file_name = format!("{}.{}", file_name.trim(), format.extension());
+        let save_path = cx.prompt_for_new_path(paths::home_dir(), Some(&file_name));
+        let load_thread = thread_store.update(cx, |thread_store, cx| {
+            thread_store.load_thread(entry.session_id.clone(), cx)
+        });
+
+        cx.spawn(async move |_, _| {
+            let Some(path) = save_path.await?? else {
+                return Ok(());
+            };
+            let thread = load_thread.await?.context("The thread no longer exists")?;
+            let export = ThreadExport::from_db_thread(&thread);
+            let contents = match format {
+                ExportFormat::Markdown => export.to_markdown(),
+                ExportFormat::Json => export.to_json()?,
+            };
+            async_fs::write(&path, contents).await?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to export the thread", window, cx, |_, _, _| None);
+    }
+
+    fn import_thread(&mut self, _: &ImportThread, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(thread_store) = self.thread_store() else {
+            return;
+        };
+
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Import Thread".into()),
+        });
+        cx.spawn(async move |this, cx| {
+            let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+                return Ok(());
+            };
+            let json = async_fs::read(&path).await?;
+            let thread = ThreadExport::from_json(&json)?.to_db_thread()?;
+
+            let session_id = acp::SessionId::new(uuid::Uuid::new_v4().to_string());
+            let session_info = AgentSessionInfo {
+                session_id: session_id.clone(),
+                cwd: None,
+                title: Some(thread.title.clone()),
+                updated_at: Some(thread.updated_at),
+                meta: None,
+            };
+            thread_store
+                .update(cx, |thread_store, cx| {
+                    thread_store.save_thread(session_id, thread, cx)
+                })
+                .await?;
+            this.update(cx, |_, cx| cx.emit(ThreadHistoryEvent::Open(session_info)))
+        })
+        .detach_and_prompt_err("Failed to import the thread", window, cx, |_, _, _| None);
+    }
+
     fn remove_history(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
         let Some(session_list) = self.session_list.as_ref() else {
             return;
@@ -587,12 +738,17 @@ impl AcpThreadHistory {
 
     fn render_list_item(&self, item: &ListItemType, ix: usize, cx: &Context<Self>) -> AnyElement {
         match item {
-            ListItemType::Entry { entry, format } => self
-                .render_history_entry(entry, *format, ix, Vec::default(), cx)
+            ListItemType::Entry {
+                entry,
+                format,
+                depth,
+            } => self
+                .render_history_entry(entry, *format, *depth, ix, Vec::default(), cx)
                 .into_any(),
             ListItemType::SearchResult { entry, positions } => self.render_history_entry(
                 entry,
                 EntryTimeFormat::DateAndTime,
+                0,
                 ix,
                 positions.clone(),
                 cx,
@@ -614,6 +770,7 @@ impl AcpThreadHistory {
         &self,
         entry: &AgentSessionInfo,
         format: EntryTimeFormat,
+        depth: usize,
         ix: usize,
         highlight_positions: Vec<usize>,
         cx: &Context<Self>,
@@ -650,15 +807,31 @@ impl AcpThreadHistory {
                     .rounded()
                     .toggle_state(selected)
                     .spacing(ListItemSpacing::Sparse)
+                    .indent_level(depth)
                     .start_slot(
                         h_flex()
                             .w_full()
                             .gap_2()
                             .justify_between()
                             .child(
-                                HighlightedLabel::new(thread_title(entry), highlight_positions)
-                                    .size(LabelSize::Small)
-                                    .truncate(),
+                                h_flex()
+                                    .gap_1()
+                                    .min_w_0()
+                                    .when(depth > 0, |this| {
+                                        this.child(
+                                            Icon::new(IconName::GitBranch)
+                                                .size(IconSize::XSmall)
+                                                .color(Color::Muted),
+                                        )
+                                    })
+                                    .child(
+                                        HighlightedLabel::new(
+                                            thread_title(entry),
+                                            highlight_positions,
+                                        )
+                                        .size(LabelSize::Small)
+                                        .truncate(),
+                                    ),
                             )
                             .child(
                                 Label::new(display_text)
@@ -678,23 +851,86 @@ impl AcpThreadHistory {
 
                         cx.notify();
                     }))
-                    .end_slot::<IconButton>(if hovered && self.supports_delete() {
-                        Some(
-                            IconButton::new("delete", IconName::Trash)
-                                .shape(IconButtonShape::Square)
-                                .icon_size(IconSize::XSmall)
-                                .icon_color(Color::Muted)
-                                .tooltip(move |_window, cx| {
-                                    Tooltip::for_action("Delete", &RemoveSelectedThread, cx)
-                                })
-                                .on_click(cx.listener(move |this, _, _, cx| {
-                                    this.remove_thread(ix, cx);
-                                    cx.stop_propagation()
-                                })),
-                        )
-                    } else {
-                        None
-                    })
+                    .end_slot::<Div>(
+                        (hovered && (self.supports_export() || self.supports_delete())).then(
+                            || {
+                                h_flex()
+                                    .gap_0p5()
+                                    .when(self.supports_export(), |this| {
+                                        this.child(
+                                            IconButton::new(
+                                                "export-markdown",
+                                                IconName::FileMarkdown,
+                                            )
+                                            .shape(IconButtonShape::Square)
+                                            .icon_size(IconSize::XSmall)
+                                            .icon_color(Color::Muted)
+                                            .tooltip(move |_window, cx| {
+                                                Tooltip::for_action(
+                                                    "Export as Markdown",
+                                                    &ExportSelectedThreadAsMarkdown,
+                                                    cx,
+                                                )
+                                            })
+                                            .on_click(
+                                                cx.listener(move |this, _, window, cx| {
+                                                    this.export_thread(
+                                                        ix,
+                                                        ExportFormat::Markdown,
+                                                        window,
+                                                        cx,
+                                                    );
+                                                    cx.stop_propagation()
+                                                }),
+                                            ),
+                                        )
+                                        .child(
+                                            IconButton::new("export-json", IconName::Json)
+                                                .shape(IconButtonShape::Square)
+                                                .icon_size(IconSize::XSmall)
+                                                .icon_color(Color::Muted)
+                                                .tooltip(move |_window, cx| {
+                                                    Tooltip::for_action(
+                                                        "Export as JSON",
+                                                        &ExportSelectedThreadAsJson,
+                                                        cx,
+                                                    )
+                                                })
+                                                .on_click(cx.listener(
+                                                    move |this, _, window, cx| {
+                                                        this.export_thread(
+                                                            ix,
+                                                            ExportFormat::Json,
+                                                            window,
+                                                            cx,
+                                                        );
+                                                        cx.stop_propagation()
+                                                    },
+                                                )),
+                                        )
+                                    })
+                                    .when(self.supports_delete(), |this| {
+                                        this.child(
+                                            IconButton::new("delete", IconName::Trash)
+                                                .shape(IconButtonShape::Square)
+                                                .icon_size(IconSize::XSmall)
+                                                .icon_color(Color::Muted)
+                                                .tooltip(move |_window, cx| {
+                                                    Tooltip::for_action(
+                                                        "Delete",
+                                                        &RemoveSelectedThread,
+                                                        cx,
+                                                    )
+                                                })
+                                                .on_click(cx.listener(move |this, _, _, cx| {
+                                                    this.remove_thread(ix, cx);
+                                                    cx.stop_propagation()
+                                                })),
+                                        )
+                                    })
+                            },
+                        ),
+                    )
                     .on_click(cx.listener(move |this, _, _, cx| this.confirm_entry(ix, cx))),
             )
             .into_any_element()
@@ -710,6 +946,8 @@ impl Focusable for AcpThreadHistory {
 impl Render for AcpThreadHistory {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let has_no_history = self.is_empty();
+        let can_delete_history = !has_no_history && self.supports_delete();
+        let supports_export = self.supports_export();
 
         v_flex()
             .key_context("ThreadHistory")
@@ -721,6 +959,9 @@ impl Render for AcpThreadHistory {
             .on_action(cx.listener(Self::select_last))
             .on_action(cx.listener(Self::confirm))
             .on_action(cx.listener(Self::remove_selected_thread))
+            .on_action(cx.listener(Self::export_selected_thread_as_markdown))
+            .on_action(cx.listener(Self::export_selected_thread_as_json))
+            .on_action(cx.listener(Self::import_thread))
             .on_action(cx.listener(|this, _: &RemoveHistory, window, cx| {
                 this.remove_history(window, cx);
             }))
@@ -775,22 +1016,36 @@ impl Render for AcpThreadHistory {
                     .vertical_scrollbar_for(&self.scroll_handle, window, cx)
                 }
             })
-            .when(!has_no_history && self.supports_delete(), |this| {
+            .when(can_delete_history || supports_export, |this| {
                 this.child(
                     h_flex()
                         .p_2()
                         .border_t_1()
                         .border_color(cx.theme().colors().border_variant)
                         .when(!self.confirming_delete_history, |this| {
-                            this.child(
-                                Button::new("delete_history", "Delete All History")
-                                    .full_width()
-                                    .style(ButtonStyle::Outlined)
-                                    .label_size(LabelSize::Small)
-                                    .on_click(cx.listener(|this, _, window, cx| {
-                                        this.prompt_delete_history(window, cx);
-                                    })),
-                            )
+                            this.gap_2()
+                                .when(supports_export, |this| {
+                                    this.child(
+                                        Button::new("import_thread", "Import Thread")
+                                            .full_width()
+                                            .style(ButtonStyle::Outlined)
+                                            .label_size(LabelSize::Small)
+                                            .on_click(cx.listener(|this, _, window, cx| {
+                                                this.import_thread(&ImportThread, window, cx);
+                                            })),
+                                    )
+                                })
+                                .when(can_delete_history, |this| {
+                                    this.child(
+                                        Button::new("delete_history", "Delete All History")
+                                            .full_width()
+                                            .style(ButtonStyle::Outlined)
+                                            .label_size(LabelSize::Small)
+                                            .on_click(cx.listener(|this, _, window, cx| {
+                                                this.prompt_delete_history(window, cx);
+                                            })),
+                                    )
+                                })
                         })
                         .when(self.confirming_delete_history, |this| {
                             this.w_full()
@@ -840,6 +1095,45 @@ impl Render for AcpThreadHistory {
     }
 }
 
+/// Orders the entries so that forks follow the thread they were forked from, depth-first, and
+/// returns each entry with its depth in the tree. Entries whose parent isn't listed are roots.
+fn sort_as_tree(
+    entries: Vec<AgentSessionInfo>,
+    parents: &HashMap<acp::SessionId, acp::SessionId>,
+) -> Vec<(AgentSessionInfo, usize)> {
+    let ids = entries
+        .iter()
+        .map(|entry| entry.session_id.clone())
+        .collect::<HashSet<_>>();
+    let mut roots = Vec::new();
+    let mut children = HashMap::<acp::SessionId, Vec<AgentSessionInfo>>::default();
+    for entry in entries {
+        match parents
+            .get(&entry.session_id)
+            .filter(|parent| ids.contains(*parent))
+        {
+            Some(parent) => children.entry(parent.clone()).or_default().push(entry),
+            None => roots.push(entry),
+        }
+    }
+
+    let mut items = Vec::with_capacity(ids.len());
+    let mut stack = roots
+        .into_iter()
+        .rev()
+        .map(|entry| (entry, 0))
+        .collect::<Vec<_>>();
+    while let Some((entry, depth)) = stack.pop() {
+        if let Some(children) = children.remove(&entry.session_id) {
+            stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+        }
+        items.push((entry, depth));
+    }
+    // Entries that are forks of each other can't be reached from a root, but must still be listed.
+    items.extend(children.into_values().flatten().map(|entry| (entry, 0)));
+    items
+}
+
 #[derive(IntoElement)]
 pub struct AcpHistoryEntryElement {
     entry: AgentSessionInfo,
@@ -1322,6 +1616,37 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_sort_as_tree() {
+        let entries = ["fork-of-fork", "b", "fork-of-a", "a", "fork-of-deleted"]
+            .into_iter()
+            .map(|id| AgentSessionInfo::new(acp::SessionId::new(id)))
+            .collect();
+        let parents = [
+            ("fork-of-fork", "fork-of-a"),
+            ("fork-of-a", "a"),
+            ("fork-of-deleted", "deleted"),
+        ]
+        .into_iter()
+        .map(|(id, parent)| (acp::SessionId::new(id), acp::SessionId::new(parent)))
+        .collect();
+
+        let tree = sort_as_tree(entries, &parents)
+            .into_iter()
+            .map(|(entry, depth)| (entry.session_id.to_string(), depth))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tree,
+            vec![
+                ("b".to_string(), 0),
+                ("a".to_string(), 0),
+                ("fork-of-a".to_string(), 1),
+                ("fork-of-fork".to_string(), 2),
+                ("fork-of-deleted".to_string(), 0),
+            ]
+        );
+    }
+
     #[test]
     fn test_time_bucket_from_dates() {
         let today = NaiveDate::from_ymd_opt(2023, 1, 15).unwrap();
@@ -311,6 +311,9 @@ impl AcpThreadView {
                             cx,
                         );
                     }
+                    ExternalAgentInitialContent::Message(content) => {
+                        editor.set_message(content, window, cx);
+                    }
                 }
             }
             editor
@@ -1512,6 +1515,90 @@ impl AcpThreadView {
             .detach_and_log_err(cx);
     }
 
+    /// Forks the thread at a user message: the fork shares the history that precedes the
+    /// message and opens with the message in its editor. If the message has a checkpoint, the
+    /// user chooses whether the project is also restored to it, once the fork has been saved.
+    pub fn fork_thread(
+        &mut self,
+        message_id: &UserMessageId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(connection) = self.as_native_connection(cx) else {
+            return;
+        };
+        let thread = self.thread.read(cx);
+        if thread.status() == ThreadStatus::Generating {
+            return;
+        }
+        let Some(message) = thread.entries().iter().find_map(|entry| match entry {
+            AgentThreadEntry::UserMessage(message) if message.id.as_ref() == Some(message_id) => {
+                Some(message)
+            }
+            _ => None,
+        }) else {
+            return;
+        };
+        let content = message.chunks.clone();
+        let has_checkpoint = message.checkpoint.is_some();
+        let title = thread.title();
+        let session_id = thread.session_id().clone();
+        let message_id = message_id.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let restore_checkpoint = if has_checkpoint {
+                let answer = cx
+                    .prompt(
+                        gpui::PromptLevel::Info,
+                        "Restore the project to this message?",
+                        Some(
+                            "Restoring discards the changes made to the project since the message was sent.",
+                        ),
+                        &["Fork and Restore", "Fork Only", "Cancel"],
+                    )
+                    .await?;
+                match answer {
+                    0 => true,
+                    1 => false,
+                    _ => return Ok(()),
+                }
+            } else {
+                false
+            };
+
+            let fork_id = cx
+                .update(|_, cx| connection.fork_thread(session_id, message_id.clone(), cx))?
+                .await?;
+            if restore_checkpoint {
+                this.update(cx, |this, cx| {
+                    this.thread.update(cx, |thread, cx| {
+                        thread.restore_git_checkpoint(&message_id, cx)
+                    })
+                })?
+                .await?;
+            }
+            workspace.update_in(cx, |workspace, window, cx| {
+                if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                    panel.update(cx, |panel, cx| {
+                        panel.open_forked_thread(
+                            AgentSessionInfo {
+                                session_id: fork_id,
+                                cwd: None,
+                                title: Some(title),
+                                updated_at: Some(chrono::Utc::now()),
+                                meta: None,
+                            },
+                            content,
+                            window,
+                            cx,
+                        )
+                    });
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub fn clear_thread_error(&mut self, cx: &mut Context<Self>) {
         self.thread_error = None;
         self.thread_error_markdown = None;
@@ -2355,6 +2442,7 @@ impl AcpThreadView {
                         h_flex()
                             .gap_1()
                             .children(self.render_token_usage(cx))
+                            .children(self.render_usage_cost(cx))
                             .children(self.profile_selector.clone())
                             .map(|this| {
                                 // Either config_options_view OR (mode_selector + model_selector)
@@ -2693,6 +2781,26 @@ impl AcpThreadView {
         }
     }
 
+    fn render_usage_cost(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let cost = self.as_native_thread(cx)?.read(cx).usage_cost()?;
+        let tooltip = format!(
+            "Estimated cost of this thread: ${:.2}\nEstimated cost of today's requests: ${:.2}",
+            cost.thread, cost.today
+        );
+
+        Some(
+            div()
+                .id("usage-cost")
+                .mr_1p5()
+                .child(
+                    Label::new(format!("${:.2}", cost.thread))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .tooltip(Tooltip::text(tooltip)),
+        )
+    }
+
     fn render_thinking_toggle(&self, cx: &mut Context<Self>) -> Option<IconButton> {
         if !cx.has_flag::<CloudThinkingToggleFeatureFlag>() {
             return None;
@@ -3161,9 +3269,28 @@ impl AcpThreadView {
                                     .overflow_hidden();
 
                                 let is_loading_contents = self.is_loading_contents;
-                                if message.id.is_some() {
+                                let can_fork = self.as_native_connection(cx).is_some();
+                                let is_generating =
+                                    self.thread.read(cx).status() == ThreadStatus::Generating;
+                                if let Some(message_id) = message.id.clone() {
                                     this.child(
                                         base_container
+                                            .when(can_fork, |this| {
+                                                this.child(
+                                                    IconButton::new("fork", IconName::GitBranch)
+                                                        .disabled(is_loading_contents || is_generating)
+                                                        .icon_color(Color::Muted)
+                                                        .icon_size(IconSize::XSmall)
+                                                        .tooltip(Tooltip::text(if is_generating {
+                                                            "Stop the thread to fork it."
+                                                        } else {
+                                                            "Fork the thread from this message."
+                                                        }))
+                                                        .on_click(cx.listener(move |this, _, window, cx| {
+                                                            this.fork_thread(&message_id, window, cx);
+                                                        }))
+                                                )
+                                            })
                                             .child(
                                                 IconButton::new("cancel", IconName::Close)
                                                     .disabled(is_loading_contents)
@@ -7071,6 +7198,7 @@ pub(crate) fn open_link(
             }
             MentionUri::Diagnostics { .. } => {}
             MentionUri::TerminalSelection { .. } => {}
+            MentionUri::ContextServerResource { .. } => {}
         })
     } else {
         cx.open_url(&url);
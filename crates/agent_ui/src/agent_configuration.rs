@@ -650,6 +650,13 @@ impl AgentConfiguration {
             .configuration_for_server(&context_server_id);
 
         let is_running = matches!(server_status, ContextServerStatus::Running);
+        let requires_authorization =
+            matches!(server_status, ContextServerStatus::AuthorizationRequired);
+        let uses_oauth = self
+            .context_server_store
+            .read(cx)
+            .get_server(&context_server_id)
+            .is_some_and(|server| server.uses_oauth());
         let item_id = SharedString::from(context_server_id.0.clone());
         // Servers without a configuration can only be provided by extensions.
         let provided_by_extension = server_configuration.as_ref().is_none_or(|config| {
@@ -707,7 +714,28 @@ impl AgentConfiguration {
                 Indicator::dot().color(Color::Muted).into_any_element(),
                 "Server is stopped.",
             ),
+            ContextServerStatus::AuthorizationRequired => (
+                Indicator::dot().color(Color::Warning).into_any_element(),
+                "Server requires authorization.",
+            ),
         };
+        let authenticate_button = requires_authorization.then(|| {
+            Button::new(
+                SharedString::from(format!("authenticate-{}", context_server_id.0)),
+                "Authenticate",
+            )
+            .style(ButtonStyle::Outlined)
+            .label_size(LabelSize::Small)
+            .on_click({
+                let context_server_store = self.context_server_store.clone();
+                let context_server_id = context_server_id.clone();
+                move |_, _window, cx| {
+                    context_server_store.update(cx, |store, cx| {
+                        store.authenticate_server(&context_server_id, cx).log_err();
+                    });
+                }
+            })
+        });
         let is_remote = server_configuration
             .as_ref()
             .map(|config| matches!(config.as_ref(), ContextServerConfiguration::Http { .. }))
@@ -726,6 +754,7 @@ impl AgentConfiguration {
                 let language_registry = self.language_registry.clone();
                 let workspace = self.workspace.clone();
                 let context_server_registry = self.context_server_registry.clone();
+                let context_server_store = self.context_server_store.clone();
 
                 move |window, cx| {
                     Some(ContextMenu::build(window, cx, |menu, _window, _cx| {
@@ -772,6 +801,15 @@ impl AgentConfiguration {
                                 .ok();
                             }
                         }))
+                        .when(uses_oauth && is_running, |this| this.entry("Sign Out", None, {
+                            let context_server_id = context_server_id.clone();
+                            let context_server_store = context_server_store.clone();
+                            move |_, cx| {
+                                context_server_store.update(cx, |store, cx| {
+                                    store.sign_out_server(&context_server_id, cx).log_err();
+                                });
+                            }
+                        }))
                         .separator()
                         .entry("Uninstall", None, {
                             let fs = fs.clone();
@@ -879,7 +917,10 @@ impl AgentConfiguration {
                             .flex_none()
                             .child(context_server_configuration_menu)
                             .child(
-                            Switch::new("context-server-switch", is_running.into())
+                            Switch::new(
+                                "context-server-switch",
+                                (is_running || requires_authorization).into(),
+                            )
                                 .on_click({
                                     let context_server_manager = self.context_server_store.clone();
                                     let fs = self.fs.clone();
@@ -928,6 +969,20 @@ impl AgentConfiguration {
                         ),
                     ),
             )
+            .when_some(authenticate_button, |parent, authenticate_button| {
+                parent.child(
+                    h_flex()
+                        .gap_2()
+                        .pr_4()
+                        .justify_between()
+                        .child(
+                            Label::new("Authorize Zed in your browser to use this server.")
+                                .color(Color::Muted)
+                                .size(LabelSize::Small),
+                        )
+                        .child(authenticate_button),
+                )
+            })
             .map(|parent| {
                 if let Some(error) = error {
                     return parent.child(
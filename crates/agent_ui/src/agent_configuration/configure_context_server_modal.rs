@@ -883,7 +883,9 @@ fn wait_for_context_server(
     let subscription = cx.subscribe(context_server_store, move |_, event, _cx| match event {
         project::context_server_store::Event::ServerStatusChanged { server_id, status } => {
             match status {
-                ContextServerStatus::Running => {
+                // A server that requires authorization is configured correctly, and the user
+                // authenticates from its entry in the agent settings.
+                ContextServerStatus::Running | ContextServerStatus::AuthorizationRequired => {
                     if server_id == &context_server_id
                         && let Some(tx) = tx.lock().unwrap().take()
                     {
@@ -907,6 +909,7 @@ fn wait_for_context_server(
                 _ => {}
             }
         }
+        project::context_server_store::Event::ResourceUpdated { .. } => {}
     });
 
     cx.spawn(async move |_cx| {
@@ -710,6 +710,24 @@ impl AgentPanel {
         );
     }
 
+    /// Opens a thread forked from another one, with the message it was forked at in the
+    /// message editor, ready to be edited and sent.
+    pub fn open_forked_thread(
+        &mut self,
+        thread: AgentSessionInfo,
+        message: Vec<acp::ContentBlock>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.external_thread(
+            Some(crate::ExternalAgent::NativeAgent),
+            Some(thread),
+            Some(ExternalAgentInitialContent::Message(message)),
+            window,
+            cx,
+        );
+    }
+
     pub(crate) fn context_server_registry(&self) -> &Entity<ContextServerRegistry> {
         &self.context_server_registry
     }
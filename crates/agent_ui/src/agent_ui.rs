@@ -8,6 +8,7 @@ mod buffer_codegen;
 mod completion_provider;
 mod context;
 mod context_server_configuration;
+mod context_server_requests;
 mod favorite_models;
 mod inline_assistant;
 mod inline_prompt_editor;
@@ -83,6 +84,12 @@ actions!(
         AddContextServer,
         /// Removes the currently selected thread.
         RemoveSelectedThread,
+        /// Exports the selected thread of the history as a Markdown file.
+        ExportSelectedThreadAsMarkdown,
+        /// Exports the selected thread of the history as a JSON file that can be imported back.
+        ExportSelectedThreadAsJson,
+        /// Imports a thread from a JSON file into the thread history.
+        ImportThread,
         /// Starts a chat conversation with follow-up enabled.
         ChatWithFollow,
         /// Cycles to the next inline assist suggestion.
@@ -225,6 +232,7 @@ impl ExternalAgent {
 pub enum ExternalAgentInitialContent {
     ThreadSummary(acp_thread::AgentSessionInfo),
     Text(String),
+    Message(Vec<agent_client_protocol::ContentBlock>),
 }
 
 /// Opens the profile management interface for configuring agent tools and settings.
@@ -279,6 +287,7 @@ pub fn init(
     assistant_slash_command::init(cx);
     agent_panel::init(cx);
     context_server_configuration::init(language_registry.clone(), fs.clone(), cx);
+    context_server_requests::init(cx);
     TextThreadEditor::init(cx);
 
     register_slash_commands(cx);
@@ -561,6 +570,9 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions: Default::default(),
             show_turn_stats: false,
+            hooks: Default::default(),
+            token_prices: Default::default(),
+            usage_limits: Default::default(),
         };
 
         cx.update(|cx| {
@@ -7,6 +7,16 @@ use std::sync::atomic::AtomicBool;
 use crate::acp::AcpThreadHistory;
 use acp_thread::{AgentSessionInfo, MentionUri};
 use anyhow::Result;
+use collections::HashMap;
+use context_server::{
+    ContextServerId,
+    protocol::{InitializedContextServerProtocol, ServerCapability},
+    types::{
+        CompletionArgument, CompletionCompleteParams, CompletionReference, PromptReferenceType,
+        Resource, ResourceReference, ResourceTemplate, requests,
+    },
+    uri_template,
+};
 use editor::{
     CompletionProvider, Editor, ExcerptId, code_context_menus::COMPLETION_MENU_MAX_WIDTH,
 };
@@ -30,6 +40,7 @@ use terminal_view::terminal_panel::TerminalPanel;
 use text::{Anchor, ToOffset as _, ToPoint as _};
 use ui::IconName;
 use ui::prelude::*;
+use url::Url;
 use util::ResultExt as _;
 use util::paths::PathStyle;
 use util::rel_path::RelPath;
@@ -63,6 +74,7 @@ pub(crate) enum PromptContextType {
     Thread,
     Rules,
     Diagnostics,
+    Resource,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -101,6 +113,7 @@ impl TryFrom<&str> for PromptContextType {
             "thread" => Ok(Self::Thread),
             "rule" => Ok(Self::Rules),
             "diagnostics" => Ok(Self::Diagnostics),
+            "resource" => Ok(Self::Resource),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -115,6 +128,7 @@ impl PromptContextType {
             Self::Thread => "thread",
             Self::Rules => "rule",
             Self::Diagnostics => "diagnostics",
+            Self::Resource => "resource",
         }
     }
 
@@ -126,6 +140,7 @@ impl PromptContextType {
             Self::Thread => "Threads",
             Self::Rules => "Rules",
             Self::Diagnostics => "Diagnostics",
+            Self::Resource => "MCP Resources",
         }
     }
 
@@ -137,6 +152,7 @@ impl PromptContextType {
             Self::Thread => IconName::Thread,
             Self::Rules => IconName::Reader,
             Self::Diagnostics => IconName::Warning,
+            Self::Resource => IconName::Server,
         }
     }
 }
@@ -148,6 +164,7 @@ pub(crate) enum Match {
     RecentThread(AgentSessionInfo),
     Fetch(SharedString),
     Rules(RulesContextEntry),
+    Resource(ResourceMatch),
     Entry(EntryMatch),
 }
 
@@ -160,6 +177,7 @@ impl Match {
             Match::RecentThread(_) => 1.,
             Match::Symbol(_) => 1.,
             Match::Rules(_) => 1.,
+            Match::Resource(_) => 1.,
             Match::Fetch(_) => 1.,
         }
     }
@@ -184,6 +202,33 @@ pub struct RulesContextEntry {
     pub title: SharedString,
 }
 
+#[derive(Debug, Clone)]
+pub(crate) enum ResourceMatch {
+    /// A resource, or a resource template whose arguments have all been filled in.
+    Resource {
+        server_id: ContextServerId,
+        uri: Url,
+        name: SharedString,
+        description: Option<SharedString>,
+    },
+    /// A resource template whose arguments are yet to be filled in, in the order its variables
+    /// appear in its URI template.
+    Template {
+        key: String,
+        arguments: Vec<String>,
+        name: SharedString,
+        description: Option<SharedString>,
+    },
+}
+
+impl ResourceMatch {
+    fn name(&self) -> &SharedString {
+        match self {
+            Self::Resource { name, .. } | Self::Template { name, .. } => name,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AvailableCommand {
     pub name: Arc<str>,
@@ -352,6 +397,83 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
         }
     }
 
+    fn completion_for_resource(
+        resource: ResourceMatch,
+        source_range: Range<Anchor>,
+        source: Arc<T>,
+        editor: WeakEntity<Editor>,
+        mention_set: WeakEntity<MentionSet>,
+        workspace: Entity<Workspace>,
+        cx: &mut App,
+    ) -> Completion {
+        match resource {
+            ResourceMatch::Resource {
+                server_id,
+                uri,
+                name,
+                description,
+            } => {
+                let documentation = description.unwrap_or_else(|| uri.to_string().into());
+                let uri = MentionUri::ContextServerResource {
+                    server_id: server_id.0.to_string(),
+                    uri,
+                    name: name.to_string(),
+                };
+                let new_text = format!("{} ", uri.as_link());
+                let new_text_len = new_text.len();
+                let icon_path = uri.icon_path(cx);
+                Completion {
+                    replace_range: source_range.clone(),
+                    new_text,
+                    label: CodeLabel::plain(name.to_string(), None),
+                    documentation: Some(CompletionDocumentation::SingleLine(documentation)),
+                    insert_text_mode: None,
+                    source: project::CompletionSource::Custom,
+                    match_start: None,
+                    snippet_deduplication_key: None,
+                    icon_path: Some(icon_path),
+                    confirm: Some(confirm_completion_callback(
+                        name,
+                        source_range.start,
+                        new_text_len - 1,
+                        uri,
+                        source,
+                        editor,
+                        mention_set,
+                        workspace,
+                    )),
+                }
+            }
+            ResourceMatch::Template {
+                key,
+                arguments,
+                name,
+                description,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| format!("{argument},"))
+                    .collect::<String>();
+                Completion {
+                    replace_range: source_range,
+                    new_text: format!(
+                        "@{} {key}:{arguments}",
+                        PromptContextType::Resource.keyword()
+                    ),
+                    label: CodeLabel::plain(name.to_string(), None),
+                    documentation: description.map(CompletionDocumentation::SingleLine),
+                    insert_text_mode: None,
+                    source: project::CompletionSource::Custom,
+                    match_start: None,
+                    snippet_deduplication_key: None,
+                    icon_path: Some(PromptContextType::Resource.icon().path().into()),
+                    // Keep the menu open so that the next argument can be completed.
+                    confirm: Some(Arc::new(|_, _, _| true)),
+                }
+            }
+        }
+    }
+
     pub(crate) fn completion_for_path(
         project_path: ProjectPath,
         path_prefix: &RelPath,
@@ -629,7 +751,7 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
                                     });
 
                                     mention_set
-                                        .update(cx, |mention_set, _| {
+                                        .update(cx, |mention_set, cx| {
                                             mention_set.insert_mention(
                                                 crease_id,
                                                 mention_uri.clone(),
@@ -640,6 +762,7 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
                                                     },
                                                 ))
                                                 .shared(),
+                                                cx,
                                             );
                                         })
                                         .ok();
@@ -877,6 +1000,18 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
 
             Some(PromptContextType::Diagnostics) => Task::ready(Vec::new()),
 
+            Some(PromptContextType::Resource) => {
+                let search_resources_task =
+                    search_context_server_resources(query, cancellation_flag, &workspace, cx);
+                cx.background_spawn(async move {
+                    search_resources_task
+                        .await
+                        .into_iter()
+                        .map(Match::Resource)
+                        .collect()
+                })
+            }
+
             None if query.is_empty() => {
                 let recent_task = self.recent_context_picker_entries(&workspace, cx);
                 let entries = self
@@ -1075,6 +1210,14 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
             entries.push(PromptContextEntry::Mode(PromptContextType::Fetch));
         }
 
+        if self
+            .source
+            .supports_context(PromptContextType::Resource, cx)
+            && !context_server_clients_with_resources(workspace, cx).is_empty()
+        {
+            entries.push(PromptContextEntry::Mode(PromptContextType::Resource));
+        }
+
         if self
             .source
             .supports_context(PromptContextType::Diagnostics, cx)
@@ -1325,6 +1468,15 @@ impl<T: PromptCompletionProviderDelegate> CompletionProvider for PromptCompletio
                                     workspace.clone(),
                                     cx,
                                 )),
+                                Match::Resource(resource) => Some(Self::completion_for_resource(
+                                    resource,
+                                    source_range.clone(),
+                                    source.clone(),
+                                    editor.clone(),
+                                    mention_set.clone(),
+                                    workspace.clone(),
+                                    cx,
+                                )),
                                 Match::Fetch(url) => Self::completion_for_fetch(
                                     source_range.clone(),
                                     url,
@@ -1944,6 +2096,227 @@ pub(crate) fn search_rules(
     })
 }
 
+struct ContextServerResources {
+    server_id: ContextServerId,
+    client: Arc<InitializedContextServerProtocol>,
+    resources: Vec<Resource>,
+    templates: Vec<ResourceTemplate>,
+}
+
+fn context_server_clients_with_resources(
+    workspace: &Entity<Workspace>,
+    cx: &App,
+) -> Vec<(ContextServerId, Arc<InitializedContextServerProtocol>)> {
+    let server_store = workspace.read(cx).project().read(cx).context_server_store();
+    server_store
+        .read(cx)
+        .running_servers()
+        .into_iter()
+        .filter_map(|server| {
+            let client = server.client()?;
+            client
+                .capable(ServerCapability::Resources)
+                .then(|| (server.id(), client))
+        })
+        .collect()
+}
+
+/// The word that refers to a resource template in a mention, e.g. `@resource issue:1234`.
+fn resource_template_key(template: &ResourceTemplate) -> String {
+    template
+        .name
+        .chars()
+        .map(|char| {
+            if char.is_alphanumeric() {
+                char.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Searches the resources and resource templates of the running MCP servers. When the query
+/// refers to a template (`<template>:<argument>,<argument>,…`), completes the argument being
+/// typed instead.
+pub(crate) fn search_context_server_resources(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    workspace: &Entity<Workspace>,
+    cx: &mut App,
+) -> Task<Vec<ResourceMatch>> {
+    let clients = context_server_clients_with_resources(workspace, cx);
+    let executor = cx.background_executor().clone();
+    cx.background_spawn(async move {
+        let servers =
+            futures::future::join_all(clients.into_iter().map(|(server_id, client)| async move {
+                let resources = client
+                    .request::<requests::ResourcesList>(())
+                    .await
+                    .log_err()
+                    .map(|response| response.resources)
+                    .unwrap_or_default();
+                let templates = client
+                    .request::<requests::ListResourceTemplates>(())
+                    .await
+                    .log_err()
+                    .map(|response| response.resource_templates)
+                    .unwrap_or_default();
+                ContextServerResources {
+                    server_id,
+                    client,
+                    resources,
+                    templates,
+                }
+            }))
+            .await;
+
+        if let Some((key, arguments)) = query.split_once(':')
+            && let Some((server, template)) = servers.iter().find_map(|server| {
+                server
+                    .templates
+                    .iter()
+                    .find(|template| resource_template_key(template) == key)
+                    .map(|template| (server, template))
+            })
+        {
+            return complete_resource_template_arguments(server, template, arguments).await;
+        }
+
+        let mut entries = Vec::new();
+        for server in &servers {
+            entries.extend(
+                server
+                    .resources
+                    .iter()
+                    .map(|resource| ResourceMatch::Resource {
+                        server_id: server.server_id.clone(),
+                        uri: resource.uri.clone(),
+                        name: resource.name.clone().into(),
+                        description: resource.description.clone().map(Into::into),
+                    }),
+            );
+            entries.extend(server.templates.iter().map(|template| {
+                let description = template.description.clone().map(Into::into);
+                if uri_template::variables(&template.uri_template).is_empty()
+                    && let Ok(uri) = Url::parse(&template.uri_template)
+                {
+                    ResourceMatch::Resource {
+                        server_id: server.server_id.clone(),
+                        uri,
+                        name: template.name.clone().into(),
+                        description,
+                    }
+                } else {
+                    ResourceMatch::Template {
+                        key: resource_template_key(template),
+                        arguments: Vec::new(),
+                        name: template.name.clone().into(),
+                        description,
+                    }
+                }
+            }));
+        }
+
+        if query.is_empty() {
+            return entries;
+        }
+
+        let candidates = entries
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| StringMatchCandidate::new(ix, entry.name().as_ref()))
+            .collect::<Vec<_>>();
+        let matches = fuzzy::match_strings(
+            &candidates,
+            &query,
+            false,
+            true,
+            100,
+            &cancellation_flag,
+            executor,
+        )
+        .await;
+        matches
+            .into_iter()
+            .map(|mat| entries[mat.candidate_id].clone())
+            .collect()
+    })
+}
+
+async fn complete_resource_template_arguments(
+    server: &ContextServerResources,
+    template: &ResourceTemplate,
+    arguments: &str,
+) -> Vec<ResourceMatch> {
+    let variables = uri_template::variables(&template.uri_template);
+    let mut arguments = arguments
+        .split(',')
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let value = arguments.pop().unwrap_or_default();
+    let Some(variable) = variables.get(arguments.len()) else {
+        return Vec::new();
+    };
+
+    let mut values = Vec::new();
+    if server.client.initialize.capabilities.completions.is_some() {
+        let response = server
+            .client
+            .request::<requests::CompletionComplete>(CompletionCompleteParams {
+                reference: CompletionReference::Resource(ResourceReference {
+                    ty: PromptReferenceType::Resource,
+                    uri: template.uri_template.clone(),
+                }),
+                argument: CompletionArgument {
+                    name: variable.to_string(),
+                    value: value.clone(),
+                },
+                meta: None,
+            })
+            .await;
+        if let Some(response) = response.log_err() {
+            values = response.completion.values;
+        }
+    }
+    if !value.is_empty() && !values.contains(&value) {
+        values.insert(0, value);
+    }
+
+    let is_last_argument = arguments.len() + 1 == variables.len();
+    let description: Option<SharedString> = template.description.clone().map(Into::into);
+    values
+        .into_iter()
+        .filter_map(|value| {
+            let mut arguments = arguments.clone();
+            arguments.push(value);
+            let name = format!("{} ({})", template.name, arguments.join(", ")).into();
+            if is_last_argument {
+                let values = variables
+                    .iter()
+                    .map(ToString::to_string)
+                    .zip(arguments)
+                    .collect::<HashMap<_, _>>();
+                let uri =
+                    Url::parse(&uri_template::expand(&template.uri_template, &values)).ok()?;
+                Some(ResourceMatch::Resource {
+                    server_id: server.server_id.clone(),
+                    uri,
+                    name,
+                    description: description.clone(),
+                })
+            } else {
+                Some(ResourceMatch::Template {
+                    key: resource_template_key(template),
+                    arguments,
+                    name,
+                    description: description.clone(),
+                })
+            }
+        })
+        .collect()
+}
+
 pub struct SymbolMatch {
     pub symbol: Symbol,
 }
@@ -2425,4 +2798,104 @@ mod tests {
             "dir1/a.txt should be second"
         );
     }
+    #[gpui::test]
+    async fn test_complete_resource_template_arguments(cx: &mut TestAppContext) {
+        use context_server::{
+            ContextServer,
+            types::{
+                CompletionCompleteResponse, CompletionResult, Implementation, InitializeResponse,
+                ProtocolVersion, ServerCapabilities,
+            },
+        };
+
+        let transport = context_server::test::create_fake_transport("docs", cx.executor())
+            .on_request::<requests::Initialize, _>(|_| async {
+                InitializeResponse {
+                    protocol_version: ProtocolVersion(
+                        context_server::types::LATEST_PROTOCOL_VERSION.to_string(),
+                    ),
+                    server_info: Implementation {
+                        name: "docs".into(),
+                        version: "1.0.0".into(),
+                    },
+                    capabilities: ServerCapabilities {
+                        completions: Some(serde_json::json!({})),
+                        ..Default::default()
+                    },
+                    meta: None,
+                }
+            })
+            .on_request::<requests::CompletionComplete, _>(|params| async move {
+                let candidates = match params.argument.name.as_str() {
+                    "crate" => ["gpui", "serde"],
+                    _ => ["App", "Entity"],
+                };
+                CompletionCompleteResponse {
+                    completion: CompletionResult {
+                        values: candidates
+                            .into_iter()
+                            .filter(|candidate| candidate.starts_with(&params.argument.value))
+                            .map(ToString::to_string)
+                            .collect(),
+                        total: None,
+                        has_more: None,
+                        meta: None,
+                    },
+                    meta: None,
+                }
+            });
+        let server = ContextServer::new(ContextServerId("docs".into()), Arc::new(transport));
+        server.start(&cx.to_async()).await.unwrap();
+        let template = ResourceTemplate {
+            uri_template: "docs://{crate}/{item}".into(),
+            name: "Docs".into(),
+            description: None,
+            mime_type: None,
+        };
+        let server = ContextServerResources {
+            server_id: server.id(),
+            client: server.client().unwrap(),
+            resources: Vec::new(),
+            templates: Vec::new(),
+        };
+
+        let complete = async |arguments: &str| {
+            complete_resource_template_arguments(&server, &template, arguments)
+                .await
+                .into_iter()
+                .map(|resource| match resource {
+                    ResourceMatch::Resource { uri, name, .. } => format!("{name}: {uri}"),
+                    ResourceMatch::Template {
+                        key,
+                        arguments,
+                        name,
+                        ..
+                    } => format!("{name}: {key}:{}", arguments.join(",")),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The typed value comes first, followed by the server's completions for it.
+        assert_eq!(
+            complete("").await,
+            ["Docs (gpui): docs:gpui", "Docs (serde): docs:serde"]
+        );
+        assert_eq!(
+            complete("g").await,
+            ["Docs (g): docs:g", "Docs (gpui): docs:gpui"]
+        );
+        // Filling in the last variable yields resources.
+        assert_eq!(
+            complete("gpui,En").await,
+            [
+                "Docs (gpui, En): docs://gpui/En",
+                "Docs (gpui, Entity): docs://gpui/Entity",
+            ]
+        );
+        assert_eq!(
+            complete("gpui,Entity").await,
+            ["Docs (gpui, Entity): docs://gpui/Entity"]
+        );
+        assert!(complete("gpui,Entity,").await.is_empty());
+    }
 }
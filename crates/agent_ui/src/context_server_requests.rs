@@ -0,0 +1,849 @@
+//! Answers the requests that MCP servers send to Zed: sampling a language model, which the user
+//! has to allow first, and elicitation, which asks the user to fill in a small form.
+
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use context_server::{
+    ContextServerId, ContextServerRequestHandler,
+    types::{
+        self, CreateMessageRequest, CreateMessageResult, ElicitAction, ElicitRequest, ElicitResult,
+        PrimitiveSchemaDefinition,
+    },
+};
+use futures::{StreamExt as _, channel::oneshot};
+use gpui::{
+    AsyncApp, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle, Task,
+};
+use language_model::{
+    LanguageModel, LanguageModelImage, LanguageModelRegistry, LanguageModelRequest,
+    LanguageModelRequestMessage, MessageContent, Role,
+};
+use serde_json::{Map, Value};
+use ui::{
+    Banner, Checkbox, ContextMenu, DropdownMenu, DropdownStyle, KeyBinding, Modal, ModalFooter,
+    ModalHeader, Section, ToggleState, WithScrollbar, prelude::*,
+};
+use ui_input::InputField;
+use url::Url;
+use workspace::{ModalView, Workspace};
+
+pub(crate) fn init(cx: &mut App) {
+    context_server::set_request_handler(Arc::new(ModalRequestHandler), cx);
+}
+
+/// Shows the requests in the active workspace, as modals.
+struct ModalRequestHandler;
+
+impl ContextServerRequestHandler for ModalRequestHandler {
+    fn create_message(
+        &self,
+        server_id: ContextServerId,
+        request: CreateMessageRequest,
+        cx: AsyncApp,
+    ) -> Task<Result<CreateMessageResult>> {
+        cx.spawn(async move |cx| {
+            let (tx, rx) = oneshot::channel();
+            show_modal(cx, |window, cx| {
+                SamplingModal::new(server_id, &request, tx, window, cx)
+            })?;
+            let model = rx
+                .await
+                .map_err(|_| anyhow!("User rejected the sampling request"))?;
+            sample(model, request, cx).await
+        })
+    }
+
+    fn elicit(
+        &self,
+        server_id: ContextServerId,
+        request: ElicitRequest,
+        cx: AsyncApp,
+    ) -> Task<Result<ElicitResult>> {
+        cx.spawn(async move |cx| {
+            let (tx, rx) = oneshot::channel();
+            show_modal(cx, |window, cx| {
+                ElicitationModal::new(server_id, request, tx, window, cx)
+            })?;
+            Ok(rx.await.unwrap_or(ElicitResult {
+                action: ElicitAction::Cancel,
+                content: None,
+            }))
+        })
+    }
+}
+
+fn show_modal<V: ModalView>(
+    cx: &mut AsyncApp,
+    build: impl FnOnce(&mut Window, &mut Context<V>) -> V,
+) -> Result<()> {
+    let workspace = cx
+        .update(|cx| {
+            cx.active_window()
+                .and_then(|window| window.downcast::<Workspace>())
+                .or_else(|| workspace::local_workspace_windows(cx).into_iter().next())
+        })
+        .context("no workspace to show the MCP server request in")?;
+    workspace.update(cx, |workspace, window, cx| {
+        // Toggling would dismiss the modal that is awaiting an answer to an earlier request,
+        // whichever kind of request it was.
+        anyhow::ensure!(
+            workspace.active_modal::<SamplingModal>(cx).is_none()
+                && workspace.active_modal::<ElicitationModal>(cx).is_none(),
+            "another request from an MCP server is awaiting an answer"
+        );
+        workspace.toggle_modal(window, cx, build);
+        Ok(())
+    })?
+}
+
+/// Sends the sampling request to the model the user picked. The request's token limit is not
+/// enforced, since language model requests don't have one.
+async fn sample(
+    model: Arc<dyn LanguageModel>,
+    request: CreateMessageRequest,
+    cx: &AsyncApp,
+) -> Result<CreateMessageResult> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = request.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![system_prompt.into()],
+            cache: false,
+            reasoning_details: None,
+        });
+    }
+    for message in request.messages {
+        let content = match message.content {
+            types::MessageContent::Text { text, .. } => text.into(),
+            types::MessageContent::Image {
+                data, mime_type, ..
+            } if mime_type == "image/png" => MessageContent::Image(LanguageModelImage {
+                source: data.into(),
+                size: None,
+            }),
+            types::MessageContent::Image { mime_type, .. }
+            | types::MessageContent::Audio { mime_type, .. } => {
+                bail!("unsupported content in sampling request: {mime_type}")
+            }
+            types::MessageContent::Resource { .. } => {
+                bail!("unsupported resource content in sampling request")
+            }
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                types::Role::User => Role::User,
+                types::Role::Assistant => Role::Assistant,
+            },
+            content: vec![content],
+            cache: false,
+            reasoning_details: None,
+        });
+    }
+
+    let model_request = LanguageModelRequest {
+        messages,
+        stop: request.stop_sequences.unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+        ..Default::default()
+    };
+    let mut stream = model
+        .stream_completion_text(model_request, cx)
+        .await?
+        .stream;
+    let mut text = String::new();
+    while let Some(chunk) = stream.next().await {
+        text.push_str(&chunk?);
+    }
+
+    Ok(CreateMessageResult {
+        role: types::Role::Assistant,
+        content: types::MessageContent::Text {
+            text,
+            annotations: None,
+        },
+        model: model.id().0.to_string(),
+        stop_reason: Some("endTurn".to_string()),
+    })
+}
+
+struct SamplingModal {
+    server_id: ContextServerId,
+    system_prompt: Option<SharedString>,
+    messages: Vec<(&'static str, SharedString)>,
+    models: Vec<Arc<dyn LanguageModel>>,
+    selected_model: Option<Arc<dyn LanguageModel>>,
+    tx: Option<oneshot::Sender<Arc<dyn LanguageModel>>>,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+}
+
+impl SamplingModal {
+    fn new(
+        server_id: ContextServerId,
+        request: &CreateMessageRequest,
+        tx: oneshot::Sender<Arc<dyn LanguageModel>>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let registry = LanguageModelRegistry::read_global(cx);
+        let models = registry.available_models(cx).collect::<Vec<_>>();
+
+        // Servers name the models they prefer by substrings of their names, in order of preference.
+        let preferred_model = request
+            .model_preferences
+            .iter()
+            .flat_map(|preferences| preferences.hints.iter().flatten())
+            .filter_map(|hint| hint.name.as_deref())
+            .find_map(|hint| {
+                let hint = hint.to_lowercase();
+                models
+                    .iter()
+                    .find(|model| model.name().0.to_lowercase().contains(&hint))
+                    .cloned()
+            });
+        let selected_model =
+            preferred_model.or_else(|| registry.default_model().map(|model| model.model));
+
+        let messages = request
+            .messages
+            .iter()
+            .map(|message| {
+                let role = match message.role {
+                    types::Role::User => "User",
+                    types::Role::Assistant => "Assistant",
+                };
+                let content = match &message.content {
+                    types::MessageContent::Text { text, .. } => text.clone().into(),
+                    types::MessageContent::Image { mime_type, .. } => {
+                        format!("[Image ({mime_type})]").into()
+                    }
+                    types::MessageContent::Audio { mime_type, .. } => {
+                        format!("[Audio ({mime_type})]").into()
+                    }
+                    types::MessageContent::Resource { resource, .. } => {
+                        format!("[Resource ({})]", resource.uri).into()
+                    }
+                };
+                (role, content)
+            })
+            .collect();
+
+        Self {
+            server_id,
+            system_prompt: request.system_prompt.clone().map(Into::into),
+            messages,
+            models,
+            selected_model,
+            tx: Some(tx),
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(model) = self.selected_model.clone() else {
+            return;
+        };
+        if let Some(tx) = self.tx.take() {
+            tx.send(model).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_model_picker(&self, window: &mut Window, cx: &mut Context<Self>) -> DropdownMenu {
+        let label: SharedString = self
+            .selected_model
+            .as_ref()
+            .map(|model| model.name().0)
+            .unwrap_or_else(|| "Select a Model".into());
+        let models = self.models.clone();
+        let this = cx.weak_entity();
+        DropdownMenu::new(
+            "sampling-model-picker",
+            label,
+            ContextMenu::build(window, cx, move |mut menu, _, _| {
+                for model in models {
+                    let this = this.clone();
+                    menu = menu.entry(model.name().0, None, move |_, cx| {
+                        this.update(cx, |this, cx| {
+                            this.selected_model = Some(model.clone());
+                            cx.notify();
+                        })
+                        .ok();
+                    });
+                }
+                menu
+            }),
+        )
+        .style(DropdownStyle::Outlined)
+    }
+}
+
+impl ModalView for SamplingModal {}
+
+impl Focusable for SamplingModal {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<DismissEvent> for SamplingModal {}
+
+impl Render for SamplingModal {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        v_flex()
+            .key_context("SamplingModal")
+            .occlude()
+            .elevation_3(cx)
+            .w(rems(34.))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .track_focus(&self.focus_handle)
+            .child(
+                Modal::new("mcp-sampling", None::<ScrollHandle>)
+                    .header(
+                        ModalHeader::new()
+                            .headline(format!("{} wants to use a language model", self.server_id))
+                            .description("Review the messages it would send before allowing it."),
+                    )
+                    .child(
+                        div()
+                            .size_full()
+                            .vertical_scrollbar_for(&self.scroll_handle, window, cx)
+                            .child(
+                                v_flex()
+                                    .id("sampling-messages")
+                                    .px_2()
+                                    .gap_2()
+                                    .max_h_96()
+                                    .overflow_y_scroll()
+                                    .track_scroll(&self.scroll_handle)
+                                    .when_some(self.system_prompt.clone(), |this, prompt| {
+                                        this.child(render_message("System", prompt))
+                                    })
+                                    .children(self.messages.iter().map(|(role, content)| {
+                                        render_message(role, content.clone())
+                                    })),
+                            ),
+                    )
+                    .section(
+                        Section::new().child(
+                            h_flex()
+                                .px_2()
+                                .justify_between()
+                                .child(Label::new("Model"))
+                                .child(self.render_model_picker(window, cx)),
+                        ),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Button::new("deny", "Deny")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Cancel,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _event, window, cx| {
+                                            this.cancel(&menu::Cancel, window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("allow", "Allow")
+                                        .disabled(self.selected_model.is_none())
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Confirm,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _event, window, cx| {
+                                            this.confirm(&menu::Confirm, window, cx)
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+fn render_message(role: &'static str, content: SharedString) -> impl IntoElement {
+    v_flex()
+        .gap_0p5()
+        .child(Label::new(role).size(LabelSize::Small).color(Color::Muted))
+        .child(Label::new(content).size(LabelSize::Small))
+}
+
+enum ElicitationInput {
+    Text(Entity<InputField>),
+    Choice {
+        options: Vec<(String, SharedString)>,
+        selected: Option<usize>,
+    },
+    Boolean(bool),
+}
+
+struct ElicitationField {
+    name: String,
+    label: SharedString,
+    description: Option<SharedString>,
+    required: bool,
+    schema: PrimitiveSchemaDefinition,
+    input: ElicitationInput,
+}
+
+struct ElicitationModal {
+    server_id: ContextServerId,
+    message: SharedString,
+    fields: Vec<ElicitationField>,
+    tx: Option<oneshot::Sender<ElicitResult>>,
+    last_error: Option<SharedString>,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+}
+
+impl ElicitationModal {
+    fn new(
+        server_id: ContextServerId,
+        request: ElicitRequest,
+        tx: oneshot::Sender<ElicitResult>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let required = request.requested_schema.required;
+        let fields = request
+            .requested_schema
+            .properties
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (name, schema))| {
+                let input = match &schema {
+                    PrimitiveSchemaDefinition::String {
+                        enum_values: Some(values),
+                        enum_names,
+                        ..
+                    } => ElicitationInput::Choice {
+                        options: values
+                            .iter()
+                            .enumerate()
+                            .map(|(value_ix, value)| {
+                                let label = enum_names
+                                    .as_ref()
+                                    .and_then(|names| names.get(value_ix))
+                                    .unwrap_or(value);
+                                (value.clone(), label.clone().into())
+                            })
+                            .collect(),
+                        selected: None,
+                    },
+                    PrimitiveSchemaDefinition::Boolean { default, .. } => {
+                        ElicitationInput::Boolean(default.unwrap_or(false))
+                    }
+                    _ => ElicitationInput::Text(cx.new(|cx| {
+                        InputField::new(window, cx, "")
+                            .tab_index(ix as isize)
+                            .tab_stop(true)
+                    })),
+                };
+                ElicitationField {
+                    label: schema.title().unwrap_or(&name).to_string().into(),
+                    description: schema
+                        .description()
+                        .map(|description| description.to_string().into()),
+                    required: required.contains(&name),
+                    name,
+                    schema,
+                    input,
+                }
+            })
+            .collect();
+
+        Self {
+            server_id,
+            message: request.message.into(),
+            fields,
+            tx: Some(tx),
+            last_error: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+        }
+    }
+
+    fn respond(&mut self, result: ElicitResult, cx: &mut Context<Self>) {
+        if let Some(tx) = self.tx.take() {
+            tx.send(result).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        match self.content(cx) {
+            Ok(content) => self.respond(
+                ElicitResult {
+                    action: ElicitAction::Accept,
+                    content: Some(content),
+                },
+                cx,
+            ),
+            Err(error) => {
+                self.last_error = Some(error);
+                cx.notify();
+            }
+        }
+    }
+
+    fn decline(&mut self, cx: &mut Context<Self>) {
+        self.respond(
+            ElicitResult {
+                action: ElicitAction::Decline,
+                content: None,
+            },
+            cx,
+        );
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn on_tab(&mut self, _: &menu::SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        window.focus_next(cx);
+    }
+
+    fn on_tab_prev(
+        &mut self,
+        _: &menu::SelectPrevious,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus_prev(cx);
+    }
+
+    fn content(&self, cx: &App) -> Result<Map<String, Value>, SharedString> {
+        let mut content = Map::new();
+        for field in &self.fields {
+            let value = match &field.input {
+                ElicitationInput::Text(input) => {
+                    let text = input.read(cx).text(cx);
+                    if text.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            parse_value(&field.schema, &text)
+                                .map_err(|error| format!("{}: {error}", field.label))?,
+                        )
+                    }
+                }
+                ElicitationInput::Choice { options, selected } => selected
+                    .and_then(|ix| options.get(ix))
+                    .map(|(value, _)| Value::String(value.clone())),
+                ElicitationInput::Boolean(checked) => Some(Value::Bool(*checked)),
+            };
+            match value {
+                Some(value) => {
+                    content.insert(field.name.clone(), value);
+                }
+                None if field.required => {
+                    return Err(format!("{} is required", field.label).into());
+                }
+                None => {}
+            }
+        }
+        Ok(content)
+    }
+
+    fn render_field(
+        &self,
+        ix: usize,
+        field: &ElicitationField,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let label = if field.required {
+            format!("{} *", field.label).into()
+        } else {
+            field.label.clone()
+        };
+        let input = match &field.input {
+            ElicitationInput::Text(input) => input.clone().into_any_element(),
+            ElicitationInput::Choice { options, selected } => {
+                let selected_label = selected
+                    .and_then(|ix| options.get(ix))
+                    .map(|(_, label)| label.clone())
+                    .unwrap_or_else(|| "Select an Option".into());
+                let options = options.clone();
+                let this = cx.weak_entity();
+                DropdownMenu::new(
+                    ("elicitation-choice", ix),
+                    selected_label,
+                    ContextMenu::build(window, cx, move |mut menu, _, _| {
+                        for (option_ix, (_, label)) in options.into_iter().enumerate() {
+                            let this = this.clone();
+                            menu = menu.entry(label, None, move |_, cx| {
+                                this.update(cx, |this, cx| {
+                                    if let Some(ElicitationInput::Choice { selected, .. }) =
+                                        this.fields.get_mut(ix).map(|field| &mut field.input)
+                                    {
+                                        *selected = Some(option_ix);
+                                    }
+                                    cx.notify();
+                                })
+                                .ok();
+                            });
+                        }
+                        menu
+                    }),
+                )
+                .style(DropdownStyle::Outlined)
+                .full_width(true)
+                .into_any_element()
+            }
+            ElicitationInput::Boolean(checked) => {
+                Checkbox::new(("elicitation-checkbox", ix), (*checked).into())
+                    .label(label.clone())
+                    .on_click(
+                        cx.listener(move |this, checked: &ToggleState, _window, cx| {
+                            if let Some(ElicitationInput::Boolean(value)) =
+                                this.fields.get_mut(ix).map(|field| &mut field.input)
+                            {
+                                *value = checked.selected();
+                            }
+                            cx.notify();
+                        }),
+                    )
+                    .into_any_element()
+            }
+        };
+
+        v_flex()
+            .gap_1()
+            .when(
+                !matches!(field.input, ElicitationInput::Boolean(_)),
+                |this| this.child(Label::new(label).size(LabelSize::Small)),
+            )
+            .child(input)
+            .when_some(field.description.clone(), |this, description| {
+                this.child(
+                    Label::new(description)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+    }
+}
+
+/// Converts the text entered for a property into the JSON value its schema asks for.
+fn parse_value(schema: &PrimitiveSchemaDefinition, text: &str) -> Result<Value, String> {
+    match schema {
+        PrimitiveSchemaDefinition::String {
+            min_length,
+            max_length,
+            format,
+            ..
+        } => {
+            let length = text.chars().count();
+            if let Some(min_length) = min_length
+                && length < *min_length
+            {
+                return Err(format!("must be at least {min_length} characters long"));
+            }
+            if let Some(max_length) = max_length
+                && length > *max_length
+            {
+                return Err(format!("must be at most {max_length} characters long"));
+            }
+            match format.as_deref() {
+                Some("email") if !text.contains('@') => {
+                    return Err("must be an email address".to_string());
+                }
+                Some("uri") if Url::parse(text).is_err() => {
+                    return Err("must be a URI".to_string());
+                }
+                _ => {}
+            }
+            Ok(Value::String(text.to_string()))
+        }
+        PrimitiveSchemaDefinition::Number {
+            minimum, maximum, ..
+        } => {
+            let number = text
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| "must be a number".to_string())?;
+            check_range(number, *minimum, *maximum)?;
+            serde_json::Number::from_f64(number)
+                .map(Value::Number)
+                .ok_or_else(|| "must be a finite number".to_string())
+        }
+        PrimitiveSchemaDefinition::Integer {
+            minimum, maximum, ..
+        } => {
+            let integer = text
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| "must be a whole number".to_string())?;
+            check_range(integer, *minimum, *maximum)?;
+            Ok(Value::from(integer))
+        }
+        PrimitiveSchemaDefinition::Boolean { .. } => match text.trim() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => Err("must be true or false".to_string()),
+        },
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    value: T,
+    minimum: Option<T>,
+    maximum: Option<T>,
+) -> Result<(), String> {
+    if let Some(minimum) = minimum
+        && value < minimum
+    {
+        return Err(format!("must be at least {minimum}"));
+    }
+    if let Some(maximum) = maximum
+        && value > maximum
+    {
+        return Err(format!("must be at most {maximum}"));
+    }
+    Ok(())
+}
+
+impl ModalView for ElicitationModal {}
+
+impl Focusable for ElicitationModal {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<DismissEvent> for ElicitationModal {}
+
+impl Render for ElicitationModal {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        let fields = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(ix, field)| self.render_field(ix, field, window, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .key_context("ElicitationModal")
+            .occlude()
+            .elevation_3(cx)
+            .w(rems(34.))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::on_tab))
+            .on_action(cx.listener(Self::on_tab_prev))
+            .track_focus(&self.focus_handle)
+            .capture_any_mouse_down(cx.listener(|this, _, window, cx| {
+                this.focus_handle(cx).focus(window, cx);
+            }))
+            .child(
+                Modal::new("mcp-elicitation", None::<ScrollHandle>)
+                    .header(
+                        ModalHeader::new()
+                            .headline(format!("{} needs more information", self.server_id))
+                            .description(self.message.clone()),
+                    )
+                    .when_some(self.last_error.clone(), |this, error| {
+                        this.section(
+                            Section::new().child(
+                                Banner::new()
+                                    .severity(Severity::Warning)
+                                    .child(div().text_xs().child(error)),
+                            ),
+                        )
+                    })
+                    .child(
+                        div()
+                            .size_full()
+                            .vertical_scrollbar_for(&self.scroll_handle, window, cx)
+                            .child(
+                                v_flex()
+                                    .id("elicitation-fields")
+                                    .tab_group()
+                                    .px_2()
+                                    .gap_3()
+                                    .max_h_96()
+                                    .overflow_y_scroll()
+                                    .track_scroll(&self.scroll_handle)
+                                    .children(fields),
+                            ),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_1()
+                                .child(Button::new("decline", "Decline").on_click(
+                                    cx.listener(|this, _event, _window, cx| this.decline(cx)),
+                                ))
+                                .child(
+                                    Button::new("submit", "Submit")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Confirm,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _event, window, cx| {
+                                            this.confirm(&menu::Confirm, window, cx)
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(value: Value) -> PrimitiveSchemaDefinition {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_value() {
+        let name = schema(json!({"type": "string", "minLength": 2, "maxLength": 4}));
+        assert_eq!(parse_value(&name, "Zed"), Ok(json!("Zed")));
+        assert!(parse_value(&name, "Z").is_err());
+        assert!(parse_value(&name, "Zed Industries").is_err());
+
+        let email = schema(json!({"type": "string", "format": "email"}));
+        assert_eq!(parse_value(&email, "hi@zed.dev"), Ok(json!("hi@zed.dev")));
+        assert!(parse_value(&email, "zed.dev").is_err());
+
+        let count = schema(json!({"type": "integer", "minimum": 1, "maximum": 10}));
+        assert_eq!(parse_value(&count, " 3 "), Ok(json!(3)));
+        assert!(parse_value(&count, "3.5").is_err());
+        assert!(parse_value(&count, "11").is_err());
+
+        let ratio = schema(json!({"type": "number", "maximum": 1.0}));
+        assert_eq!(parse_value(&ratio, "0.5"), Ok(json!(0.5)));
+        assert!(parse_value(&ratio, "1.5").is_err());
+        assert!(parse_value(&ratio, "half").is_err());
+    }
+}
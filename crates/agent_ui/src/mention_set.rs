@@ -5,6 +5,11 @@ use agent_servers::{AgentServer, AgentServerDelegate};
 use anyhow::{Context as _, Result, anyhow};
 use assistant_slash_commands::{codeblock_fence_for_path, collect_diagnostics_output};
 use collections::{HashMap, HashSet};
+use context_server::{
+    ContextServerId,
+    protocol::InitializedContextServerProtocol,
+    types::{ResourceContentsType, requests},
+};
 use editor::{
     Anchor, Editor, EditorSnapshot, ExcerptId, FoldPlaceholder, ToOffset,
     display_map::{Crease, CreaseId, CreaseMetadata, FoldId},
@@ -13,7 +18,7 @@ use editor::{
 use futures::{AsyncReadExt as _, FutureExt as _, future::Shared};
 use gpui::{
     AppContext, ClipboardEntry, Context, Empty, Entity, EntityId, Image, ImageFormat, Img,
-    SharedString, Task, WeakEntity,
+    SharedString, Subscription, Task, WeakEntity,
 };
 use http_client::{AsyncBody, HttpClientWithUrl};
 use itertools::Either;
@@ -35,6 +40,7 @@ use std::{
 };
 use text::OffsetRangeExt;
 use ui::{Disclosure, Toggleable, prelude::*};
+use url::Url;
 use util::{ResultExt, debug_panic, rel_path::RelPath};
 use workspace::{Workspace, notifications::NotifyResultExt as _};
 
@@ -63,6 +69,10 @@ pub struct MentionSet {
     thread_store: Option<Entity<ThreadStore>>,
     prompt_store: Option<Entity<PromptStore>>,
     mentions: HashMap<CreaseId, (MentionUri, MentionTask)>,
+    /// The mentioned context server resources that are subscribed to in the
+    /// [`ContextServerStore`](project::context_server_store::ContextServerStore).
+    watched_resources: HashSet<(ContextServerId, Url)>,
+    context_server_subscriptions: Vec<Subscription>,
 }
 
 impl MentionSet {
@@ -76,6 +86,8 @@ impl MentionSet {
             thread_store,
             prompt_store,
             mentions: HashMap::default(),
+            watched_resources: HashSet::default(),
+            context_server_subscriptions: Vec::new(),
         }
     }
 
@@ -106,16 +118,30 @@ impl MentionSet {
         })
     }
 
-    pub fn remove_invalid(&mut self, snapshot: &EditorSnapshot) {
+    pub fn remove_invalid(&mut self, snapshot: &EditorSnapshot, cx: &mut Context<Self>) {
+        let mut removed = false;
         for (crease_id, crease) in snapshot.crease_snapshot.creases() {
             if !crease.range().start.is_valid(snapshot.buffer_snapshot()) {
-                self.mentions.remove(&crease_id);
+                removed |= self.mentions.remove(&crease_id).is_some();
             }
         }
+        if removed {
+            self.watch_context_server_resources(cx);
+        }
     }
 
-    pub fn insert_mention(&mut self, crease_id: CreaseId, uri: MentionUri, task: MentionTask) {
+    pub fn insert_mention(
+        &mut self,
+        crease_id: CreaseId,
+        uri: MentionUri,
+        task: MentionTask,
+        cx: &mut Context<Self>,
+    ) {
+        let is_context_server_resource = matches!(uri, MentionUri::ContextServerResource { .. });
         self.mentions.insert(crease_id, (uri, task));
+        if is_context_server_resource {
+            self.watch_context_server_resources(cx);
+        }
     }
 
     /// Creates the appropriate confirmation task for a mention based on its URI type.
@@ -147,6 +173,9 @@ impl MentionSet {
                 include_errors,
                 include_warnings,
             } => self.confirm_mention_for_diagnostics(include_errors, include_warnings, cx),
+            MentionUri::ContextServerResource { server_id, uri, .. } => {
+                self.confirm_mention_for_context_server_resource(server_id, uri, cx)
+            }
             MentionUri::PastedImage
             | MentionUri::Selection { .. }
             | MentionUri::TerminalSelection { .. } => {
@@ -155,8 +184,9 @@ impl MentionSet {
         }
     }
 
-    pub fn remove_mention(&mut self, crease_id: &CreaseId) {
+    pub fn remove_mention(&mut self, crease_id: &CreaseId, cx: &mut Context<Self>) {
         self.mentions.remove(crease_id);
+        self.watch_context_server_resources(cx);
     }
 
     pub fn creases(&self) -> HashSet<CreaseId> {
@@ -167,11 +197,20 @@ impl MentionSet {
         self.mentions.values().map(|(uri, _)| uri.clone()).collect()
     }
 
-    pub fn set_mentions(&mut self, mentions: HashMap<CreaseId, (MentionUri, MentionTask)>) {
+    pub fn set_mentions(
+        &mut self,
+        mentions: HashMap<CreaseId, (MentionUri, MentionTask)>,
+        cx: &mut Context<Self>,
+    ) {
         self.mentions = mentions;
+        self.watch_context_server_resources(cx);
     }
 
-    pub fn clear(&mut self) -> impl Iterator<Item = (CreaseId, (MentionUri, MentionTask))> {
+    pub fn clear(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) -> impl Iterator<Item = (CreaseId, (MentionUri, MentionTask))> {
+        self.watch_context_server_resources(cx);
         self.mentions.drain()
     }
 
@@ -276,6 +315,9 @@ impl MentionSet {
                 include_errors,
                 include_warnings,
             } => self.confirm_mention_for_diagnostics(include_errors, include_warnings, cx),
+            MentionUri::ContextServerResource { server_id, uri, .. } => {
+                self.confirm_mention_for_context_server_resource(server_id, uri, cx)
+            }
             MentionUri::PastedImage => {
                 debug_panic!("pasted image URI should not be included in completions");
                 Task::ready(Err(anyhow!(
@@ -294,7 +336,7 @@ impl MentionSet {
         let task = cx
             .spawn(async move |_, _| task.await.map_err(|e| e.to_string()))
             .shared();
-        self.mentions.insert(crease_id, (mention_uri, task.clone()));
+        self.insert_mention(crease_id, mention_uri, task.clone(), cx);
 
         // Notify the user if we failed to load the mentioned context
         cx.spawn_in(window, async move |this, cx| {
@@ -306,7 +348,7 @@ impl MentionSet {
                         // Remove mention
                         editor.edit([(start_anchor..end_anchor, "")], cx);
                     });
-                    this.mentions.remove(&crease_id);
+                    this.remove_mention(&crease_id, cx);
                 })
                 .ok();
             }
@@ -556,6 +598,149 @@ impl MentionSet {
         })
     }
 
+    fn confirm_mention_for_context_server_resource(
+        &self,
+        server_id: String,
+        uri: Url,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Mention>> {
+        let Some(client) = self.context_server_client(&server_id, cx) else {
+            return Task::ready(Err(anyhow!("MCP server {server_id} is not running")));
+        };
+        cx.background_spawn(async move {
+            let content = read_context_server_resource(&client, uri).await?;
+            Ok(Mention::Text {
+                content,
+                tracked_buffers: Vec::new(),
+            })
+        })
+    }
+
+    fn context_server_client(
+        &self,
+        server_id: &str,
+        cx: &App,
+    ) -> Option<Arc<InitializedContextServerProtocol>> {
+        let project = self.project.upgrade()?;
+        let server_store = project.read(cx).context_server_store();
+        let server = server_store
+            .read(cx)
+            .get_running_server(&ContextServerId(server_id.into()))?;
+        server.client()
+    }
+
+    /// Keeps the mentioned resources of context servers up to date, so that messages are sent
+    /// with their latest contents.
+    ///
+    /// The mentioned resources are subscribed to in the context server store, which refreshes
+    /// their mentions when it reports them as updated, and are unsubscribed from once they are
+    /// no longer mentioned or the mention set is dropped. This runs once the current update is
+    /// finished, so that replacing the mentions within an update doesn't resubscribe to them.
+    fn watch_context_server_resources(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+        cx.defer(move |cx| {
+            this.update(cx, |this, cx| this.sync_context_server_resources(cx))
+                .ok();
+        });
+    }
+
+    fn sync_context_server_resources(&mut self, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let server_store = project.read(cx).context_server_store();
+
+        let mentioned_resources = self
+            .mentions
+            .values()
+            .filter_map(|(mention_uri, _)| match mention_uri {
+                MentionUri::ContextServerResource { server_id, uri, .. } => {
+                    Some((ContextServerId(server_id.as_str().into()), uri.clone()))
+                }
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        if mentioned_resources == self.watched_resources {
+            return;
+        }
+
+        if self.context_server_subscriptions.is_empty() {
+            self.context_server_subscriptions = vec![
+                cx.subscribe(&server_store, |this, _, event, cx| {
+                    if let project::context_server_store::Event::ResourceUpdated {
+                        server_id,
+                        uri,
+                    } = event
+                    {
+                        this.refresh_context_server_resource(&server_id.0, uri, cx);
+                    }
+                }),
+                cx.on_release({
+                    let server_store = server_store.downgrade();
+                    move |this, cx| {
+                        server_store
+                            .update(cx, |server_store, cx| {
+                                for (server_id, uri) in this.watched_resources.drain() {
+                                    server_store.unsubscribe_from_resource(&server_id, &uri, cx);
+                                }
+                            })
+                            .ok();
+                    }
+                }),
+            ];
+        }
+
+        let old_resources = std::mem::replace(&mut self.watched_resources, mentioned_resources);
+        server_store.update(cx, |server_store, cx| {
+            for (server_id, uri) in old_resources.difference(&self.watched_resources) {
+                server_store.unsubscribe_from_resource(server_id, uri, cx);
+            }
+            for (server_id, uri) in self.watched_resources.difference(&old_resources) {
+                server_store.subscribe_to_resource(server_id.clone(), uri.clone(), cx);
+            }
+        });
+    }
+
+    fn refresh_context_server_resource(
+        &mut self,
+        server_id: &str,
+        uri: &str,
+        cx: &mut Context<Self>,
+    ) {
+        let mut resource_uri = None;
+        let mut crease_ids = Vec::new();
+        for (crease_id, (mention_uri, _)) in &self.mentions {
+            if let MentionUri::ContextServerResource {
+                server_id: mention_server_id,
+                uri: mention_uri,
+                ..
+            } = mention_uri
+                && mention_server_id == server_id
+                && mention_uri.as_str() == uri
+            {
+                resource_uri = Some(mention_uri.clone());
+                crease_ids.push(*crease_id);
+            }
+        }
+        let Some(resource_uri) = resource_uri else {
+            return;
+        };
+
+        let task = self.confirm_mention_for_context_server_resource(
+            server_id.to_string(),
+            resource_uri,
+            cx,
+        );
+        let task = cx
+            .spawn(async move |_, _| task.await.map_err(|e| e.to_string()))
+            .shared();
+        for crease_id in crease_ids {
+            if let Some((_, mention_task)) = self.mentions.get_mut(&crease_id) {
+                *mention_task = task.clone();
+            }
+        }
+    }
+
     fn confirm_mention_for_diagnostics(
         &self,
         include_errors: bool,
@@ -719,16 +904,16 @@ pub(crate) async fn insert_images_as_context(
             })
             .shared();
 
-        mention_set.update(cx, |mention_set, _cx| {
-            mention_set.insert_mention(crease_id, MentionUri::PastedImage, task.clone())
+        mention_set.update(cx, |mention_set, cx| {
+            mention_set.insert_mention(crease_id, MentionUri::PastedImage, task.clone(), cx)
         });
 
         if task.await.notify_async_err(cx).is_none() {
             editor.update(cx, |editor, cx| {
                 editor.edit([(start_anchor..end_anchor, "")], cx);
             });
-            mention_set.update(cx, |mention_set, _cx| {
-                mention_set.remove_mention(&crease_id)
+            mention_set.update(cx, |mention_set, cx| {
+                mention_set.remove_mention(&crease_id, cx)
             });
         }
     }
@@ -1103,6 +1288,37 @@ impl Render for ImageHover {
     }
 }
 
+async fn read_context_server_resource(
+    client: &InitializedContextServerProtocol,
+    uri: Url,
+) -> Result<String> {
+    let response = client
+        .request::<requests::ResourcesRead>(context_server::types::ResourcesReadParams {
+            uri,
+            meta: None,
+        })
+        .await?;
+
+    let mut content = String::new();
+    for contents in response.contents {
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        match contents {
+            ResourceContentsType::Text(text) => content.push_str(&text.text),
+            ResourceContentsType::Blob(blob) => {
+                write!(
+                    content,
+                    "[Binary content ({})]",
+                    blob.mime_type.as_deref().unwrap_or("unknown type")
+                )
+                .ok();
+            }
+        }
+    }
+    Ok(content)
+}
+
 async fn fetch_url_content(http_client: Arc<HttpClientWithUrl>, url: String) -> Result<String> {
     #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
     enum ContentType {
@@ -0,0 +1,243 @@
+use agent_ui::AgentDiffPane;
+use gpui::{Entity, EventEmitter, Focusable, Subscription, WeakEntity, prelude::*};
+use menu::Confirm;
+use ui::{Tooltip, prelude::*};
+use ui_input::InputField;
+use workspace::{Workspace, notifications::DetachAndPromptErr as _};
+
+use crate::agent_task_queue::{AgentTask, AgentTaskId, AgentTaskQueue, AgentTaskStatus};
+
+pub enum AgentTaskBoardEvent {
+    OpenTask(AgentTaskId),
+}
+
+/// Lists the tasks of an [`AgentTaskQueue`] with their status and changes, and lets the user
+/// enqueue new ones.
+pub struct AgentTaskBoard {
+    queue: Entity<AgentTaskQueue>,
+    prompt_editor: Entity<InputField>,
+    workspace: WeakEntity<Workspace>,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<AgentTaskBoardEvent> for AgentTaskBoard {}
+
+impl AgentTaskBoard {
+    pub fn new(
+        queue: Entity<AgentTaskQueue>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let prompt_editor =
+            cx.new(|cx| InputField::new(window, cx, "Queue a task to run in its own worktree…"));
+        let subscription = cx.observe(&queue, |_, _, cx| cx.notify());
+        Self {
+            queue,
+            prompt_editor,
+            workspace,
+            _subscription: subscription,
+        }
+    }
+
+    pub fn queue(&self) -> &Entity<AgentTaskQueue> {
+        &self.queue
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let prompt = self.prompt_editor.read(cx).text(cx);
+        let prompt = prompt.trim();
+        if prompt.is_empty() {
+            return;
+        }
+        let prompt = prompt.to_string();
+        self.queue.update(cx, |queue, cx| queue.enqueue(prompt, cx));
+        self.prompt_editor
+            .update(cx, |editor, cx| editor.clear(window, cx));
+    }
+
+    fn review_changes(&mut self, id: AgentTaskId, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(thread) = self
+            .queue
+            .read(cx)
+            .task(id)
+            .and_then(|task| task.thread().cloned())
+        else {
+            return;
+        };
+        AgentDiffPane::deploy(thread, self.workspace.clone(), window, cx).ok();
+    }
+
+    fn merge_back(&mut self, id: AgentTaskId, window: &mut Window, cx: &mut Context<Self>) {
+        let merge = self.queue.update(cx, |queue, cx| queue.merge_back(id, cx));
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            merge.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                git_ui::project_diff::ProjectDiff::deploy_at(workspace, None, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to merge the task", window, cx, |_, _, _| None);
+    }
+
+    fn remove(&mut self, id: AgentTaskId, window: &mut Window, cx: &mut Context<Self>) {
+        self.queue
+            .update(cx, |queue, cx| queue.remove(id, cx))
+            .detach_and_prompt_err("Failed to remove the task", window, cx, |_, _, _| None);
+    }
+
+    fn render_status(&self, task: &AgentTask, cx: &App) -> impl IntoElement {
+        let (icon, color, label): (_, _, SharedString) = match &task.status {
+            AgentTaskStatus::Queued => (IconName::Circle, Color::Muted, "Queued".into()),
+            AgentTaskStatus::Starting => (IconName::ArrowCircle, Color::Muted, "Starting".into()),
+            AgentTaskStatus::Running if task.is_waiting_for_confirmation(cx) => (
+                IconName::Warning,
+                Color::Warning,
+                "Waiting for approval".into(),
+            ),
+            AgentTaskStatus::Running => (IconName::ArrowCircle, Color::Accent, "Running".into()),
+            AgentTaskStatus::Finished => (IconName::Check, Color::Success, "Finished".into()),
+            AgentTaskStatus::Failed(error) => (IconName::XCircle, Color::Error, error.clone()),
+            AgentTaskStatus::Merged => (IconName::Check, Color::Muted, "Merged".into()),
+        };
+        let spinning = matches!(
+            task.status,
+            AgentTaskStatus::Starting | AgentTaskStatus::Running
+        ) && color != Color::Warning;
+
+        h_flex()
+            .gap_1()
+            .min_w_0()
+            .child(
+                Icon::new(icon)
+                    .size(IconSize::XSmall)
+                    .color(color)
+                    .when(spinning, |icon| icon.with_rotate_animation(2))
+                    .into_any_element(),
+            )
+            .child(
+                Label::new(label)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .truncate(),
+            )
+    }
+
+    fn render_task(&self, id: AgentTaskId, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let task = self.queue.read(cx).task(id)?;
+        let title = task.title(cx);
+        let status = self.render_status(task, cx).into_any_element();
+        let branch = task.branch.clone();
+        let changed_files = task.changed_files(cx);
+        let has_thread = task.thread().is_some();
+        let can_merge = task.status == AgentTaskStatus::Finished;
+        let element_id = |name: &str| SharedString::from(format!("{name}-{branch}"));
+
+        let element = v_flex()
+            .p_2()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(title).truncate())
+            .child(
+                h_flex().gap_2().justify_between().child(status).child(
+                    h_flex()
+                        .gap_1()
+                        .flex_none()
+                        .child(
+                            Icon::new(IconName::GitBranch)
+                                .size(IconSize::XSmall)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            Label::new(branch.clone())
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted),
+                        ),
+                ),
+            )
+            .when(!changed_files.is_empty(), |this| {
+                this.child(
+                    v_flex()
+                        .pl_4()
+                        .children(changed_files.into_iter().map(|path| {
+                            Label::new(path)
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted)
+                                .truncate()
+                        })),
+                )
+            })
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new(element_id("open"), "Open")
+                            .label_size(LabelSize::Small)
+                            .disabled(!has_thread)
+                            .on_click(cx.listener(move |_, _, _, cx| {
+                                cx.emit(AgentTaskBoardEvent::OpenTask(id));
+                            })),
+                    )
+                    .child(
+                        Button::new(element_id("review"), "Review Changes")
+                            .label_size(LabelSize::Small)
+                            .disabled(!has_thread)
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.review_changes(id, window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(element_id("merge"), "Merge Back")
+                            .label_size(LabelSize::Small)
+                            .disabled(!can_merge)
+                            .tooltip(Tooltip::text(
+                                "Merge the task's branch into the project's checkout",
+                            ))
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.merge_back(id, window, cx);
+                            })),
+                    )
+                    .child(
+                        IconButton::new(element_id("remove"), IconName::Trash)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Remove Task and Worktree"))
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.remove(id, window, cx);
+                            })),
+                    ),
+            );
+        Some(element)
+    }
+}
+
+impl Focusable for AgentTaskBoard {
+    fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
+        self.prompt_editor.focus_handle(cx)
+    }
+}
+
+impl Render for AgentTaskBoard {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let task_ids = self
+            .queue
+            .read(cx)
+            .tasks()
+            .iter()
+            .map(|task| task.id)
+            .collect::<Vec<_>>();
+        let task_elements = task_ids
+            .into_iter()
+            .filter_map(|id| self.render_task(id, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .key_context("AgentTaskBoard")
+            .on_action(cx.listener(Self::confirm))
+            .w_full()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(div().p_2().child(self.prompt_editor.clone()))
+            .children(task_elements)
+    }
+}
@@ -0,0 +1,825 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use acp_thread::AcpThread;
+use agent::ThreadStore;
+use agent_servers::AgentServer;
+use agent_ui::acp::{AcpServerView, AcpThreadHistory};
+use anyhow::{Context as _, Result};
+use askpass::AskPassDelegate;
+use collections::HashSet;
+use fs::Fs;
+use futures::future;
+use git::repository::CommitOptions;
+use gpui::{
+    AnyWindowHandle, App, AppContext as _, Context, Entity, Subscription, Task, WeakEntity,
+};
+use project::git_store::Repository;
+use project::trusted_worktrees::{PathTrust, TrustedWorktrees};
+use project::{LocalProjectFlags, Project};
+use prompt_store::PromptStore;
+use ui::SharedString;
+use util::{ResultExt as _, post_inc};
+use workspace::Workspace;
+
+/// How many tasks can run at the same time; the others wait in the queue.
+const MAX_RUNNING_TASKS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AgentTaskId(usize);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AgentTaskStatus {
+    Queued,
+    Starting,
+    Running,
+    Finished,
+    Failed(SharedString),
+    Merged,
+}
+
+impl AgentTaskStatus {
+    fn is_active(&self) -> bool {
+        matches!(self, Self::Starting | Self::Running)
+    }
+}
+
+/// A prompt that runs in its own thread, in a git worktree on its own branch.
+pub struct AgentTask {
+    pub id: AgentTaskId,
+    pub prompt: SharedString,
+    pub branch: String,
+    pub status: AgentTaskStatus,
+    repository: Option<Entity<Repository>>,
+    worktree_path: Option<PathBuf>,
+    project: Option<Entity<Project>>,
+    thread_view: Option<Entity<AcpServerView>>,
+    thread: Option<Entity<AcpThread>>,
+    _task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl AgentTask {
+    pub fn thread_view(&self) -> Option<&Entity<AcpServerView>> {
+        self.thread_view.as_ref()
+    }
+
+    pub fn thread(&self) -> Option<&Entity<AcpThread>> {
+        self.thread.as_ref()
+    }
+
+    pub fn title(&self, cx: &App) -> SharedString {
+        self.thread
+            .as_ref()
+            .map(|thread| thread.read(cx).title())
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| self.prompt.clone())
+    }
+
+    pub fn is_waiting_for_confirmation(&self, cx: &App) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| thread.read(cx).first_tool_awaiting_confirmation().is_some())
+    }
+
+    /// The paths of the files the agent changed, relative to the worktree.
+    pub fn changed_files(&self, cx: &App) -> Vec<SharedString> {
+        let Some(thread) = &self.thread else {
+            return Vec::new();
+        };
+        thread
+            .read(cx)
+            .action_log()
+            .read(cx)
+            .changed_buffers(cx)
+            .into_keys()
+            .filter_map(|buffer| {
+                let file = buffer.read(cx).file()?;
+                Some(file.path().display(file.path_style(cx)).to_string().into())
+            })
+            .collect()
+    }
+
+    /// Removes the task's git worktree, discarding the changes that weren't merged back.
+    fn remove_worktree(&mut self, cx: &mut App) -> Task<Result<()>> {
+        let (Some(repository), Some(worktree_path)) =
+            (self.repository.take(), self.worktree_path.take())
+        else {
+            return Task::ready(Ok(()));
+        };
+        let remove = repository.update(cx, |repository, _| {
+            repository.remove_worktree(worktree_path)
+        });
+        cx.background_spawn(async move { remove.await? })
+    }
+}
+
+/// Runs agent tasks in parallel, each in a git worktree of the project's repository so that they
+/// don't edit the same checkout.
+///
+/// Tasks aren't persisted, so their worktrees are removed when the queue is released or Zed quits,
+/// and as soon as a task fails.
+pub struct AgentTaskQueue {
+    tasks: Vec<AgentTask>,
+    next_task_id: usize,
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    agent: Rc<dyn AgentServer>,
+    fs: Arc<dyn Fs>,
+    thread_store: Entity<ThreadStore>,
+    prompt_store: Option<Entity<PromptStore>>,
+    history: Entity<AcpThreadHistory>,
+    window: AnyWindowHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl AgentTaskQueue {
+    pub fn new(
+        workspace: WeakEntity<Workspace>,
+        project: Entity<Project>,
+        agent: Rc<dyn AgentServer>,
+        fs: Arc<dyn Fs>,
+        thread_store: Entity<ThreadStore>,
+        prompt_store: Option<Entity<PromptStore>>,
+        history: Entity<AcpThreadHistory>,
+        window: AnyWindowHandle,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscriptions = vec![
+            cx.on_release(|this, cx| {
+                let remove = this.remove_worktrees(cx);
+                cx.background_spawn(remove).detach();
+            }),
+            cx.on_app_quit(|this, cx| this.remove_worktrees(cx)),
+        ];
+        Self {
+            tasks: Vec::new(),
+            next_task_id: 0,
+            workspace,
+            project,
+            agent,
+            fs,
+            thread_store,
+            prompt_store,
+            history,
+            window,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn remove_worktrees(&mut self, cx: &mut App) -> impl Future<Output = ()> + use<> {
+        let removals = self
+            .tasks
+            .iter_mut()
+            .map(|task| task.remove_worktree(cx))
+            .collect::<Vec<_>>();
+        async move {
+            for result in future::join_all(removals).await {
+                result.log_err();
+            }
+        }
+    }
+
+    pub fn tasks(&self) -> &[AgentTask] {
+        &self.tasks
+    }
+
+    pub fn task(&self, id: AgentTaskId) -> Option<&AgentTask> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    fn task_mut(&mut self, id: AgentTaskId) -> Option<&mut AgentTask> {
+        self.tasks.iter_mut().find(|task| task.id == id)
+    }
+
+    pub fn enqueue(&mut self, prompt: String, cx: &mut Context<Self>) {
+        let id = AgentTaskId(post_inc(&mut self.next_task_id));
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis());
+        self.tasks.push(AgentTask {
+            id,
+            branch: branch_name(&prompt, &format!("{timestamp:x}{}", id.0)),
+            prompt: prompt.into(),
+            status: AgentTaskStatus::Queued,
+            repository: None,
+            worktree_path: None,
+            project: None,
+            thread_view: None,
+            thread: None,
+            _task: Task::ready(()),
+            _subscriptions: Vec::new(),
+        });
+        self.start_queued_tasks(cx);
+        cx.notify();
+    }
+
+    fn start_queued_tasks(&mut self, cx: &mut Context<Self>) {
+        let mut running = self
+            .tasks
+            .iter()
+            .filter(|task| task.status.is_active())
+            .count();
+        while running < MAX_RUNNING_TASKS {
+            let Some(task) = self
+                .tasks
+                .iter_mut()
+                .find(|task| task.status == AgentTaskStatus::Queued)
+            else {
+                break;
+            };
+            task.status = AgentTaskStatus::Starting;
+            let id = task.id;
+            let start = self.start_task(id, cx);
+            if let Some(task) = self.task_mut(id) {
+                task._task = start;
+            }
+            running += 1;
+        }
+    }
+
+    fn start_task(&mut self, id: AgentTaskId, cx: &mut Context<Self>) -> Task<()> {
+        let Some(task) = self.task(id) else {
+            return Task::ready(());
+        };
+        let branch = task.branch.clone();
+        let source_project = self.project.clone();
+        let repository = self.project.read(cx).active_repository(cx);
+        let workspace = self.workspace.clone();
+        let agent = self.agent.clone();
+        let fs = self.fs.clone();
+        let thread_store = self.thread_store.clone();
+        let prompt_store = self.prompt_store.clone();
+        let history = self.history.clone();
+        let window = self.window;
+
+        cx.spawn(async move |this, cx| {
+            let result = async {
+                let repository = repository.context("The project is not in a git repository")?;
+                let app_state =
+                    workspace.read_with(cx, |workspace, _| workspace.app_state().clone())?;
+
+                let repository_path = repository.read_with(cx, |repository, _| {
+                    repository.snapshot().work_directory_abs_path
+                });
+                let directory = paths::agent_worktrees_dir().join(
+                    repository_path
+                        .file_name()
+                        .context("The repository has no name")?,
+                );
+                fs.create_dir(&directory).await?;
+                let worktree_path = directory.join(&branch);
+                // Record the worktree before creating it, so that removing it from the queue
+                // meanwhile is ordered after its creation in the repository's jobs.
+                let create_worktree = this.update(cx, |this, cx| {
+                    if let Some(task) = this.task_mut(id) {
+                        task.repository = Some(repository.clone());
+                        task.worktree_path = Some(worktree_path.clone());
+                    }
+                    repository.update(cx, |repository, _| {
+                        repository.create_worktree(
+                            branch.clone(),
+                            directory.clone(),
+                            Some("HEAD".into()),
+                        )
+                    })
+                })?;
+                if let Err(error) = create_worktree.await? {
+                    this.update(cx, |this, _| {
+                        if let Some(task) = this.task_mut(id) {
+                            task.repository = None;
+                            task.worktree_path = None;
+                        }
+                    })?;
+                    return Err(error);
+                }
+
+                let project = cx.update(|cx| {
+                    Project::local(
+                        app_state.client.clone(),
+                        app_state.node_runtime.clone(),
+                        app_state.user_store.clone(),
+                        app_state.languages.clone(),
+                        app_state.fs.clone(),
+                        None,
+                        LocalProjectFlags::default(),
+                        cx,
+                    )
+                });
+                // The worktree is a checkout of the user's repository, so it's only trusted when
+                // the repository's worktree in the user's project is.
+                cx.update(|cx| {
+                    let Some(trusted_worktrees) = TrustedWorktrees::try_get_global(cx) else {
+                        return;
+                    };
+                    let Some((source_worktree, _)) =
+                        source_project.read(cx).find_worktree(&repository_path, cx)
+                    else {
+                        return;
+                    };
+                    let source_worktree_store = source_project.read(cx).worktree_store();
+                    let worktree_store = project.read(cx).worktree_store();
+                    trusted_worktrees.update(cx, |trusted_worktrees, cx| {
+                        if trusted_worktrees.can_trust(
+                            &source_worktree_store,
+                            source_worktree.read(cx).id(),
+                            cx,
+                        ) {
+                            trusted_worktrees.trust(
+                                &worktree_store,
+                                HashSet::from_iter([PathTrust::AbsPath(worktree_path.clone())]),
+                                cx,
+                            );
+                        }
+                    });
+                });
+                project
+                    .update(cx, |project, cx| {
+                        project.find_or_create_worktree(&worktree_path, true, cx)
+                    })
+                    .await?;
+
+                let thread_view = cx.update_window(window, |_, window, cx| {
+                    cx.new(|cx| {
+                        AcpServerView::new(
+                            agent,
+                            None,
+                            None,
+                            workspace,
+                            project.clone(),
+                            Some(thread_store),
+                            prompt_store,
+                            history,
+                            window,
+                            cx,
+                        )
+                    })
+                })?;
+
+                this.update(cx, |this, cx| {
+                    let subscription = cx.observe(&thread_view, move |this, _, cx| {
+                        this.thread_view_changed(id, cx)
+                    });
+                    if let Some(task) = this.task_mut(id) {
+                        task.project = Some(project);
+                        task.thread_view = Some(thread_view);
+                        task._subscriptions.push(subscription);
+                    }
+                    this.thread_view_changed(id, cx);
+                })
+            }
+            .await;
+
+            if let Err(error) = result {
+                this.update(cx, |this, cx| this.finish_task(id, Err(error), cx))
+                    .ok();
+            }
+        })
+    }
+
+    /// Sends the prompt once the agent is connected.
+    fn thread_view_changed(&mut self, id: AgentTaskId, cx: &mut Context<Self>) {
+        let Some(task) = self.task(id) else {
+            return;
+        };
+        if task.thread.is_some() || task.status != AgentTaskStatus::Starting {
+            cx.notify();
+            return;
+        }
+        let Some(thread_view) = task.thread_view.clone() else {
+            return;
+        };
+
+        let thread_view = thread_view.read(cx);
+        let Some(active_thread) = thread_view.as_active_thread() else {
+            if !thread_view.is_loading() {
+                self.finish_task(id, Err(anyhow::anyhow!("The agent failed to start")), cx);
+            }
+            return;
+        };
+
+        let thread = active_thread.read(cx).thread.clone();
+        let prompt = task.prompt.to_string();
+        let send = thread.update(cx, |thread, cx| {
+            thread.send(vec![prompt.as_str().into()], cx)
+        });
+        let subscription = cx.observe(&thread, |_, _, cx| cx.notify());
+        if let Some(task) = self.task_mut(id) {
+            task.status = AgentTaskStatus::Running;
+            task.thread = Some(thread);
+            task._subscriptions.push(subscription);
+            task._task = cx.spawn(async move |this, cx| {
+                let result = send.await;
+                this.update(cx, |this, cx| this.finish_task(id, result, cx))
+                    .ok();
+            });
+        }
+        cx.notify();
+    }
+
+    fn finish_task(&mut self, id: AgentTaskId, result: Result<()>, cx: &mut Context<Self>) {
+        if let Some(task) = self.task_mut(id) {
+            task.status = match result {
+                Ok(()) => AgentTaskStatus::Finished,
+                Err(error) => {
+                    log::error!("agent task failed: {error:#}");
+                    task.remove_worktree(cx).detach_and_log_err(cx);
+                    AgentTaskStatus::Failed(error.to_string().into())
+                }
+            };
+        }
+        self.start_queued_tasks(cx);
+        cx.notify();
+    }
+
+    /// Commits the task's changes to its branch, and merges the branch into the project's
+    /// checkout without committing, so that the changes can be reviewed in the project diff.
+    pub fn merge_back(&mut self, id: AgentTaskId, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(task) = self.task(id) else {
+            return Task::ready(Ok(()));
+        };
+        let Some(task_repository) = task
+            .project
+            .as_ref()
+            .and_then(|project| project.read(cx).active_repository(cx))
+        else {
+            return Task::ready(Err(anyhow::anyhow!("The task has no worktree")));
+        };
+        let Some(repository) = self.project.read(cx).active_repository(cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "The project is not in a git repository"
+            )));
+        };
+        let scans_complete = task
+            .project
+            .as_ref()
+            .map(|project| {
+                project
+                    .read(cx)
+                    .worktrees(cx)
+                    .filter_map(|worktree| Some(worktree.read(cx).as_local()?.scan_complete()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let branch = task.branch.clone();
+        let message = task.title(cx);
+
+        cx.spawn(async move |this, cx| {
+            // The agent's last edits may not have been picked up by the git status yet.
+            future::join_all(scans_complete).await;
+            task_repository
+                .update(cx, |repository, _| repository.barrier())
+                .await?;
+            let has_changes = task_repository
+                .read_with(cx, |repository, _| repository.status_summary().count > 0);
+            if has_changes {
+                task_repository
+                    .update(cx, |repository, cx| repository.stage_all(cx))
+                    .await?;
+                let askpass = AskPassDelegate::new(cx, |_, _, _| {});
+                task_repository
+                    .update(cx, |repository, cx| {
+                        repository.commit(message, None, CommitOptions::default(), askpass, cx)
+                    })
+                    .await??;
+            }
+
+            repository
+                .update(cx, |repository, _| repository.squash_merge(branch))
+                .await??;
+
+            this.update(cx, |this, cx| {
+                if let Some(task) = this.task_mut(id) {
+                    task.status = AgentTaskStatus::Merged;
+                }
+                cx.notify();
+            })
+        })
+    }
+
+    /// Removes the task from the queue, along with its worktree. Its branch is kept.
+    pub fn remove(&mut self, id: AgentTaskId, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(ix) = self.tasks.iter().position(|task| task.id == id) else {
+            return Task::ready(Ok(()));
+        };
+        let mut task = self.tasks.remove(ix);
+        self.start_queued_tasks(cx);
+        cx.notify();
+        task.remove_worktree(cx)
+    }
+}
+
+/// Returns a branch name made of the first words of the prompt, followed by `suffix` to make it
+/// unique.
+fn branch_name(prompt: &str, suffix: &str) -> String {
+    let mut slug = String::new();
+    for word in prompt
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(5)
+    {
+        if slug.len() + word.len() > 40 {
+            break;
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+        slug.push('-');
+    }
+    format!("agent-task-{slug}{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use acp_thread::{AgentConnection, StubAgentConnection};
+    use agent_client_protocol as acp;
+    use agent_servers::AgentServerDelegate;
+    use collections::HashMap;
+    use fs::FakeFs;
+    use git::repository::repo_path;
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::any::Any;
+    use std::cell::Cell;
+    use std::path::Path;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_task_queue(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "src": { "main.rs": "fn main() {}\n" },
+            }),
+        )
+        .await;
+        fs.set_head_and_index_for_repo(
+            path!("/project/.git").as_ref(),
+            &[("src/main.rs", "fn main() {}\n".into())],
+        );
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let connection = StubAgentConnection::new();
+        let fail_to_connect = Rc::new(Cell::new(false));
+        let agent = Rc::new(StubAgentServer {
+            connection: connection.clone(),
+            fail_to_connect: fail_to_connect.clone(),
+        });
+        let queue = cx.update(|window, cx| {
+            let thread_store = cx.new(|cx| ThreadStore::new(cx));
+            let history = cx.new(|cx| AcpThreadHistory::new(None, window, cx));
+            cx.new(|cx| {
+                AgentTaskQueue::new(
+                    workspace.downgrade(),
+                    project.clone(),
+                    agent,
+                    fs.clone(),
+                    thread_store,
+                    None,
+                    history,
+                    window.window_handle(),
+                    cx,
+                )
+            })
+        });
+
+        for prompt in ["First", "Second", "Third", "Fourth"] {
+            queue.update(cx, |queue, cx| queue.enqueue(prompt.into(), cx));
+        }
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&queue, cx),
+            [
+                AgentTaskStatus::Running,
+                AgentTaskStatus::Running,
+                AgentTaskStatus::Running,
+                AgentTaskStatus::Queued,
+            ]
+        );
+
+        // The agent edits the worktree, and finishing its turn starts the queued task.
+        let (first, worktree_path) = queue.read_with(cx, |queue, _| {
+            let task = &queue.tasks()[0];
+            (task.id, task.worktree_path.clone().unwrap())
+        });
+        assert!(fs.is_dir(&worktree_path).await);
+        // The user's worktree isn't trusted, so neither is the task's checkout.
+        assert!(is_restricted(&queue, first, cx));
+        fs.write(
+            &worktree_path.join("src/main.rs"),
+            b"fn main() {\n    println!(\"Hello\");\n}\n",
+        )
+        .await
+        .unwrap();
+        end_turn(&queue, &connection, first, cx);
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&queue, cx),
+            [
+                AgentTaskStatus::Finished,
+                AgentTaskStatus::Running,
+                AgentTaskStatus::Running,
+                AgentTaskStatus::Running,
+            ]
+        );
+
+        queue
+            .update(cx, |queue, cx| queue.merge_back(first, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            fs.load(path!("/project/src/main.rs").as_ref())
+                .await
+                .unwrap(),
+            "fn main() {\n    println!(\"Hello\");\n}\n"
+        );
+        let (head, index) = fs
+            .with_git_state(path!("/project/.git").as_ref(), false, |state| {
+                let path = repo_path("src/main.rs");
+                (
+                    state.head_contents.get(&path).cloned(),
+                    state.index_contents.get(&path).cloned(),
+                )
+            })
+            .unwrap();
+        assert_eq!(head.as_deref(), Some("fn main() {}\n"));
+        assert_eq!(
+            index.as_deref(),
+            Some("fn main() {\n    println!(\"Hello\");\n}\n")
+        );
+        assert_eq!(statuses(&queue, cx)[0], AgentTaskStatus::Merged);
+
+        queue
+            .update(cx, |queue, cx| queue.remove(first, cx))
+            .await
+            .unwrap();
+        assert!(!fs.is_dir(&worktree_path).await);
+        assert_eq!(statuses(&queue, cx).len(), 3);
+
+        // A task whose agent doesn't connect fails, without holding up the queue.
+        cx.update(|_, cx| {
+            let worktree_store = project.read(cx).worktree_store();
+            TrustedWorktrees::try_get_global(cx)
+                .unwrap()
+                .update(cx, |trusted_worktrees, cx| {
+                    trusted_worktrees.trust(
+                        &worktree_store,
+                        HashSet::from_iter([PathTrust::AbsPath(path!("/project").into())]),
+                        cx,
+                    )
+                });
+        });
+        fail_to_connect.set(true);
+        queue.update(cx, |queue, cx| queue.enqueue("Fifth".into(), cx));
+        cx.run_until_parked();
+        assert_eq!(statuses(&queue, cx)[3], AgentTaskStatus::Queued);
+
+        let second = queue.read_with(cx, |queue, _| queue.tasks()[0].id);
+        end_turn(&queue, &connection, second, cx);
+        cx.run_until_parked();
+        assert_eq!(
+            statuses(&queue, cx),
+            [
+                AgentTaskStatus::Finished,
+                AgentTaskStatus::Running,
+                AgentTaskStatus::Running,
+                AgentTaskStatus::Failed("The agent failed to start".into()),
+            ]
+        );
+        let (fifth, fifth_branch) = queue.read_with(cx, |queue, _| {
+            let task = &queue.tasks()[3];
+            (task.id, task.branch.clone())
+        });
+        assert!(!is_restricted(&queue, fifth, cx));
+        // The failed task's worktree is removed right away.
+        let directory = worktree_path.parent().unwrap();
+        assert!(!fs.is_dir(&directory.join(fifth_branch)).await);
+
+        // Releasing the queue removes the worktrees of the remaining tasks.
+        let worktree_paths = queue.read_with(cx, |queue, _| {
+            queue
+                .tasks()
+                .iter()
+                .filter_map(|task| task.worktree_path.clone())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(worktree_paths.len(), 3);
+        drop(queue);
+        cx.update(|_, _| {});
+        cx.run_until_parked();
+        for worktree_path in worktree_paths {
+            assert!(!fs.is_dir(&worktree_path).await);
+        }
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+            release_channel::init(semver::Version::new(0, 0, 0), cx);
+            prompt_store::init(cx);
+            project::trusted_worktrees::init(HashMap::default(), cx);
+        });
+    }
+
+    fn is_restricted(
+        queue: &Entity<AgentTaskQueue>,
+        id: AgentTaskId,
+        cx: &VisualTestContext,
+    ) -> bool {
+        queue.read_with(cx, |queue, cx| {
+            let worktree_store = queue
+                .task(id)
+                .unwrap()
+                .project
+                .as_ref()
+                .unwrap()
+                .read(cx)
+                .worktree_store();
+            TrustedWorktrees::try_get_global(cx)
+                .unwrap()
+                .read(cx)
+                .has_restricted_worktrees(&worktree_store, cx)
+        })
+    }
+
+    fn statuses(queue: &Entity<AgentTaskQueue>, cx: &VisualTestContext) -> Vec<AgentTaskStatus> {
+        queue.read_with(cx, |queue, _| {
+            queue
+                .tasks()
+                .iter()
+                .map(|task| task.status.clone())
+                .collect()
+        })
+    }
+
+    fn end_turn(
+        queue: &Entity<AgentTaskQueue>,
+        connection: &StubAgentConnection,
+        id: AgentTaskId,
+        cx: &VisualTestContext,
+    ) {
+        let session_id = queue.read_with(cx, |queue, cx| {
+            let thread = queue.task(id).unwrap().thread().unwrap();
+            thread.read(cx).session_id().clone()
+        });
+        connection.end_turn(session_id, acp::StopReason::EndTurn);
+    }
+
+    struct StubAgentServer {
+        connection: StubAgentConnection,
+        fail_to_connect: Rc<Cell<bool>>,
+    }
+
+    impl AgentServer for StubAgentServer {
+        fn logo(&self) -> ui::IconName {
+            ui::IconName::Ai
+        }
+
+        fn name(&self) -> SharedString {
+            "Test".into()
+        }
+
+        fn connect(
+            &self,
+            _root_dir: Option<&Path>,
+            _delegate: AgentServerDelegate,
+            _cx: &mut App,
+        ) -> Task<Result<(Rc<dyn AgentConnection>, Option<task::SpawnInTerminal>)>> {
+            if self.fail_to_connect.get() {
+                return Task::ready(Err(anyhow::anyhow!("Failed to connect")));
+            }
+            Task::ready(Ok((Rc::new(self.connection.clone()), None)))
+        }
+
+        fn into_any(self: Rc<Self>) -> Rc<dyn Any> {
+            self
+        }
+    }
+
+    #[test]
+    fn test_branch_name() {
+        assert_eq!(
+            branch_name("Fix the login bug in `auth.rs`, please!", "1a"),
+            "agent-task-fix-the-login-bug-in-1a"
+        );
+        assert_eq!(branch_name("日本語", "2"), "agent-task-2");
+        assert_eq!(
+            branch_name(
+                "Refactorize extraordinarily complicated implementations everywhere",
+                "3"
+            ),
+            "agent-task-refactorize-extraordinarily-3"
+        );
+    }
+}
@@ -126,6 +126,15 @@ impl AgentThreadPane {
             )
         });
 
+        self.show_thread_view(thread_view, thread_id, cx);
+    }
+
+    pub fn show_thread_view(
+        &mut self,
+        thread_view: Entity<AcpServerView>,
+        thread_id: acp::SessionId,
+        cx: &mut Context<Self>,
+    ) {
         let notify = cx.observe(&thread_view, |_, _, cx| {
             cx.notify();
         });
@@ -1,3 +1,5 @@
+mod agent_task_board;
+mod agent_task_queue;
 mod agent_thread_pane;
 
 pub mod agents_panel;
@@ -15,6 +15,7 @@ use project::Project;
 use prompt_store::PromptStore;
 use serde::{Deserialize, Serialize};
 use settings::{Settings as _, update_settings_file};
+use std::rc::Rc;
 use std::sync::Arc;
 use ui::{App, Context, IconName, IntoElement, ParentElement, Render, Styled, Window};
 use util::ResultExt;
@@ -24,6 +25,8 @@ use workspace::{
     utility_pane::{UtilityPaneSlot, utility_slot_for_dock_position},
 };
 
+use crate::agent_task_board::{AgentTaskBoard, AgentTaskBoardEvent};
+use crate::agent_task_queue::{AgentTaskId, AgentTaskQueue};
 use crate::agent_thread_pane::{
     AgentThreadPane, AgentsUtilityPaneEvent, SerializedAgentThreadPane, SerializedHistoryEntryId,
 };
@@ -59,6 +62,7 @@ pub struct AgentsPanel {
     workspace: WeakEntity<Workspace>,
     project: Entity<Project>,
     agent_thread_pane: Option<Entity<AgentThreadPane>>,
+    task_board: Entity<AgentTaskBoard>,
     history: Entity<AcpThreadHistory>,
     thread_store: Entity<ThreadStore>,
     prompt_store: Option<Entity<PromptStore>>,
@@ -162,9 +166,26 @@ impl AgentsPanel {
         })
         .detach();
 
+        let task_queue = cx.new(|cx| {
+            AgentTaskQueue::new(
+                workspace.clone(),
+                project.clone(),
+                Rc::new(NativeAgentServer::new(fs.clone(), thread_store.clone())),
+                fs.clone(),
+                thread_store.clone(),
+                prompt_store.clone(),
+                history.clone(),
+                window.window_handle(),
+                cx,
+            )
+        });
+        let task_board =
+            cx.new(|cx| AgentTaskBoard::new(task_queue, workspace.clone(), window, cx));
+
         let this = cx.weak_entity();
         let subscriptions = vec![
             cx.subscribe_in(&history, window, Self::handle_history_event),
+            cx.subscribe_in(&task_board, window, Self::handle_task_board_event),
             cx.observe_in(&history, window, Self::handle_history_updated),
             cx.on_flags_ready(move |_, cx| {
                 this.update(cx, |_, cx| {
@@ -179,6 +200,7 @@ impl AgentsPanel {
             workspace,
             project,
             agent_thread_pane: None,
+            task_board,
             history,
             thread_store,
             prompt_store,
@@ -263,6 +285,18 @@ impl AgentsPanel {
         }
     }
 
+    fn handle_task_board_event(
+        &mut self,
+        _task_board: &Entity<AgentTaskBoard>,
+        event: &AgentTaskBoardEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            AgentTaskBoardEvent::OpenTask(id) => self.open_task(*id, window, cx),
+        }
+    }
+
     fn maybe_restore_pending(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if self.agent_thread_pane.is_some() {
             self.pending_restore = None;
@@ -333,6 +367,44 @@ impl AgentsPanel {
             pane
         });
 
+        self.register_thread_pane(agent_thread_pane, window, cx);
+    }
+
+    fn open_task(&mut self, id: AgentTaskId, window: &mut Window, cx: &mut Context<Self>) {
+        let queue = self.task_board.read(cx).queue().read(cx);
+        let Some(task) = queue.task(id) else {
+            return;
+        };
+        let (Some(thread_view), Some(thread)) = (task.thread_view().cloned(), task.thread()) else {
+            return;
+        };
+        let thread_id = thread.read(cx).session_id().clone();
+        self.pending_restore = None;
+
+        if let Some(existing_pane) = &self.agent_thread_pane {
+            existing_pane.update(cx, |pane, cx| {
+                pane.show_thread_view(thread_view, thread_id, cx);
+                pane.set_expanded(true, cx);
+            });
+            self.serialize(cx);
+            return;
+        }
+
+        let agent_thread_pane = cx.new(|cx| {
+            let mut pane = AgentThreadPane::new(self.workspace.clone(), self.history.clone(), cx);
+            pane.show_thread_view(thread_view, thread_id, cx);
+            pane.set_expanded(true, cx);
+            pane
+        });
+        self.register_thread_pane(agent_thread_pane, window, cx);
+    }
+
+    fn register_thread_pane(
+        &mut self,
+        agent_thread_pane: Entity<AgentThreadPane>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         let state_subscription = cx.subscribe(&agent_thread_pane, Self::handle_utility_pane_event);
         let close_subscription = cx.subscribe(&agent_thread_pane, Self::handle_close_pane_event);
 
@@ -476,6 +548,9 @@ impl Panel for AgentsPanel {
 
 impl Render for AgentsPanel {
     fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
-        gpui::div().size_full().child(self.history.clone())
+        ui::v_flex()
+            .size_full()
+            .child(self.task_board.clone())
+            .child(gpui::div().flex_1().min_h_0().child(self.history.clone()))
     }
 }
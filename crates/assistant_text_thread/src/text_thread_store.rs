@@ -901,7 +901,9 @@ impl TextThreadStore {
                             cx,
                         );
                     }
-                    ContextServerStatus::Stopped | ContextServerStatus::Error(_) => {
+                    ContextServerStatus::Stopped
+                    | ContextServerStatus::Error(_)
+                    | ContextServerStatus::AuthorizationRequired => {
                         if let Some(slash_command_ids) =
                             self.context_server_slash_command_ids.remove(server_id)
                         {
@@ -911,6 +913,7 @@ impl TextThreadStore {
                     _ => {}
                 }
             }
+            project::context_server_store::Event::ResourceUpdated { .. } => {}
         }
     }
 
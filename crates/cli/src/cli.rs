@@ -21,6 +21,7 @@ pub enum CliRequest {
         reuse: bool,
         env: Option<HashMap<String, String>>,
         user_data_dir: Option<String>,
+        render_to_image: Option<String>,
     },
 }
 
@@ -129,6 +129,9 @@ struct Args {
     /// When directories are provided, recurses into them and shows all changed files in a single multi-diff view.
     #[arg(long, action = clap::ArgAction::Append, num_args = 2, value_names = ["OLD_PATH", "NEW_PATH"])]
     diff: Vec<String>,
+    /// Render the first of the given paths to a PNG or SVG image at PATH, then close it.
+    #[arg(long, value_name = "PATH")]
+    render_to_image: Option<String>,
     /// Uninstall Zublime from user system
     #[cfg(all(
         any(target_os = "linux", target_os = "macos"),
@@ -640,6 +643,19 @@ fn main() -> Result<()> {
         }
     }
 
+    let render_to_image = args
+        .render_to_image
+        .as_deref()
+        .map(|path| {
+            anyhow::Ok(
+                env::current_dir()?
+                    .join(path)
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        })
+        .transpose()?;
+
     anyhow::ensure!(
         args.dev_server_token.is_none(),
         "Dev servers were removed in v0.157.x please upgrade to SSH remoting: https://github.com/lydakis/zublime"
@@ -677,6 +693,7 @@ fn main() -> Result<()> {
                     reuse: args.reuse,
                     env,
                     user_data_dir: user_data_dir_for_thread,
+                    render_to_image,
                 })?;
 
                 while let Ok(response) = rx.recv() {
@@ -53,6 +53,7 @@ pub(crate) struct Client {
     name: Arc<str>,
     subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
     io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
@@ -201,6 +202,7 @@ impl Client {
             let subscription_set = subscription_set.clone();
             let response_handlers = response_handlers.clone();
             let request_handlers = request_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
             let transport = transport.clone();
             async move |cx| {
                 Self::handle_input(
@@ -208,6 +210,7 @@ impl Client {
                     subscription_set,
                     request_handlers,
                     response_handlers,
+                    outbound_tx,
                     cx,
                 )
                 .log_err()
@@ -238,6 +241,7 @@ impl Client {
             server_id,
             subscription_set,
             response_handlers,
+            request_handlers,
             name: server_name,
             next_id: Default::default(),
             outbound_tx,
@@ -254,12 +258,14 @@ impl Client {
     /// This function continuously reads lines from the provided stdout stream,
     /// parses them as JSON-RPC responses or notifications, and dispatches them
     /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// to pending requests), requests (which are answered by registered handlers) and
+    /// notifications (which trigger registered handlers).
     async fn handle_input(
         transport: Arc<dyn Transport>,
         subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
         request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: &mut AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
@@ -274,6 +280,16 @@ impl Client {
                         request.params.unwrap_or(RawValue::NULL),
                         cx.clone(),
                     );
+                } else {
+                    log::debug!("Unhandled request from context_server: {}", request.method);
+                    send_response::<()>(
+                        &outbound_tx,
+                        request.id,
+                        Err(Error {
+                            message: format!("Method not found: {}", request.method),
+                            code: METHOD_NOT_FOUND,
+                        }),
+                    );
                 }
             } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut()
@@ -452,6 +468,49 @@ impl Client {
         Ok(())
     }
 
+    /// Registers a handler for the requests the context server sends with the given method.
+    /// The result of the task returned by the handler is sent back as the response, and requests
+    /// whose parameters can't be deserialized are answered with an invalid params error.
+    pub fn on_request<P, R>(
+        &self,
+        method: &'static str,
+        mut f: impl 'static + Send + FnMut(P, AsyncApp) -> Task<Result<R>>,
+    ) where
+        P: DeserializeOwned,
+        R: 'static + Send + Serialize,
+    {
+        let outbound_tx = self.outbound_tx.clone();
+        self.request_handlers.lock().insert(
+            method,
+            Box::new(move |id, params: &RawValue, cx: AsyncApp| {
+                let params = match serde_json::from_str::<P>(params.get()) {
+                    Ok(params) => params,
+                    Err(error) => {
+                        send_response::<()>(
+                            &outbound_tx,
+                            id,
+                            Err(Error {
+                                message: error.to_string(),
+                                code: INVALID_PARAMS,
+                            }),
+                        );
+                        return;
+                    }
+                };
+                let task = f(params, cx.clone());
+                let outbound_tx = outbound_tx.clone();
+                cx.background_spawn(async move {
+                    let result = task.await.map_err(|error| Error {
+                        message: error.to_string(),
+                        code: INTERNAL_ERROR,
+                    });
+                    send_response(&outbound_tx, id, result);
+                })
+                .detach();
+            }),
+        );
+    }
+
     #[must_use]
     pub fn on_notification(
         &self,
@@ -467,6 +526,23 @@ impl Client {
     }
 }
 
+fn send_response<T: Serialize>(
+    outbound_tx: &channel::Sender<String>,
+    id: RequestId,
+    result: Result<T, Error>,
+) {
+    let response = serde_json::to_string(&Response {
+        jsonrpc: JSON_RPC_VERSION,
+        id,
+        value: match result {
+            Ok(result) => CspResult::Ok(Some(result)),
+            Err(error) => CspResult::Error(Some(error)),
+        },
+    })
+    .unwrap();
+    outbound_tx.try_send(response).log_err();
+}
+
 #[derive(Debug)]
 pub struct RequestCanceled;
 
@@ -562,3 +638,111 @@ impl Drop for NotificationSubscription {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test::create_fake_transport,
+        types::{
+            CreateMessageRequest, CreateMessageResult, ElicitAction, ElicitRequest, ElicitResult,
+            MessageContent, Request as _, Role,
+            requests::{CreateMessage, Elicit},
+        },
+    };
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_requests_from_server(cx: &mut TestAppContext) {
+        let (response_tx, mut response_rx) = futures::channel::mpsc::unbounded();
+        let transport = create_fake_transport("test", cx.executor()).on_response(response_tx);
+        let server_tx = transport.sender();
+        let client = Client::new(
+            ContextServerId("test".into()),
+            "test".into(),
+            Arc::new(transport),
+            None,
+            cx.to_async(),
+        )
+        .unwrap();
+        client.on_request::<CreateMessageRequest, CreateMessageResult>(
+            CreateMessage::METHOD,
+            |request, _| {
+                Task::ready(Ok(CreateMessageResult {
+                    role: Role::Assistant,
+                    content: MessageContent::Text {
+                        text: format!("Replying to {} messages", request.messages.len()),
+                        annotations: None,
+                    },
+                    model: "fake".into(),
+                    stop_reason: Some("endTurn".into()),
+                }))
+            },
+        );
+        client.on_request::<ElicitRequest, ElicitResult>(Elicit::METHOD, |request, _| {
+            Task::ready(if request.message.is_empty() {
+                Err(anyhow!("Nothing to ask"))
+            } else {
+                Ok(ElicitResult {
+                    action: ElicitAction::Decline,
+                    content: None,
+                })
+            })
+        });
+
+        let mut request = |id: Value, method: &str, params: Value| {
+            let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+            server_tx.unbounded_send(request.to_string()).unwrap();
+            cx.run_until_parked();
+            let response = response_rx.try_next().unwrap().unwrap();
+            serde_json::from_str::<Value>(&response).unwrap()
+        };
+
+        assert_eq!(
+            request(
+                json!(1),
+                CreateMessage::METHOD,
+                json!({
+                    "messages": [{ "role": "user", "content": { "type": "text", "text": "Hi" } }],
+                    "maxTokens": 100
+                }),
+            ),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "role": "assistant",
+                    "content": { "type": "text", "text": "Replying to 1 messages" },
+                    "model": "fake",
+                    "stopReason": "endTurn"
+                }
+            })
+        );
+        assert_eq!(
+            request(
+                json!("elicit-1"),
+                Elicit::METHOD,
+                json!({ "message": "Which branch?", "requestedSchema": { "properties": {} } }),
+            ),
+            json!({ "jsonrpc": "2.0", "id": "elicit-1", "result": { "action": "decline" } })
+        );
+
+        // Requests are answered with errors when their parameters are invalid, when no handler is
+        // registered for them, or when their handler fails.
+        let response = request(json!(2), CreateMessage::METHOD, json!({ "messages": [] }));
+        assert_eq!(response["id"], json!(2));
+        assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+        let response = request(json!(3), "roots/list", Value::Null);
+        assert_eq!(response["id"], json!(3));
+        assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+        let response = request(
+            json!(4),
+            Elicit::METHOD,
+            json!({ "message": "", "requestedSchema": { "properties": {} } }),
+        );
+        assert_eq!(response["id"], json!(4));
+        assert_eq!(response["error"]["code"], json!(INTERNAL_ERROR));
+        assert_eq!(response["error"]["message"], json!("Nothing to ask"));
+    }
+}
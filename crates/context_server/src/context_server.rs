@@ -1,10 +1,12 @@
 pub mod client;
 pub mod listener;
+pub mod oauth;
 pub mod protocol;
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
 pub mod transport;
 pub mod types;
+pub mod uri_template;
 
 use collections::HashMap;
 use http_client::HttpClient;
@@ -15,10 +17,11 @@ use std::{fmt::Display, path::PathBuf};
 
 use anyhow::Result;
 use client::Client;
-use gpui::AsyncApp;
-use parking_lot::RwLock;
+use gpui::{App, AsyncApp, Global, Task};
+use parking_lot::{Mutex, RwLock};
 pub use settings::ContextServerCommand;
 use url::Url;
+use util::ResultExt as _;
 
 use crate::transport::HttpTransport;
 
@@ -31,8 +34,55 @@ impl Display for ContextServerId {
     }
 }
 
+/// Answers the requests that context servers send to the client on behalf of the user.
+pub trait ContextServerRequestHandler: 'static + Send + Sync {
+    /// Samples a language model for the server (`sampling/createMessage`).
+    fn create_message(
+        &self,
+        server_id: ContextServerId,
+        request: types::CreateMessageRequest,
+        cx: AsyncApp,
+    ) -> Task<Result<types::CreateMessageResult>>;
+
+    /// Asks the user for the information the server needs (`elicitation/create`).
+    fn elicit(
+        &self,
+        server_id: ContextServerId,
+        request: types::ElicitRequest,
+        cx: AsyncApp,
+    ) -> Task<Result<types::ElicitResult>>;
+}
+
+struct GlobalContextServerRequestHandler(Arc<dyn ContextServerRequestHandler>);
+
+impl Global for GlobalContextServerRequestHandler {}
+
+/// Sets the handler for the requests of the context servers started from now on. Sampling and
+/// elicitation are only advertised to servers when a handler is set.
+pub fn set_request_handler(handler: Arc<dyn ContextServerRequestHandler>, cx: &mut App) {
+    cx.set_global(GlobalContextServerRequestHandler(handler));
+}
+
+/// The error [`ContextServer::start`] fails with when the server requires the user to authorize
+/// Zed, which they do through [`ContextServer::authenticate`].
+#[derive(Debug)]
+pub struct AuthorizationRequired;
+
+impl Display for AuthorizationRequired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "authorization required")
+    }
+}
+
+impl std::error::Error for AuthorizationRequired {}
+
 enum ContextServerTransport {
     Stdio(ContextServerCommand, Option<PathBuf>),
+    Http {
+        transport: Arc<HttpTransport>,
+        endpoint: Url,
+        http_client: Arc<dyn HttpClient>,
+    },
     Custom(Arc<dyn crate::transport::Transport>),
 }
 
@@ -41,6 +91,8 @@ pub struct ContextServer {
     client: RwLock<Option<Arc<crate::protocol::InitializedContextServerProtocol>>>,
     configuration: ContextServerTransport,
     request_timeout: Option<Duration>,
+    persist_credentials: Mutex<Option<Task<()>>>,
+    authorization_challenge: Mutex<Option<oauth::AuthorizationChallenge>>,
 }
 
 impl ContextServer {
@@ -57,6 +109,8 @@ impl ContextServer {
                 working_directory.map(|directory| directory.to_path_buf()),
             ),
             request_timeout: None,
+            persist_credentials: Mutex::new(None),
+            authorization_challenge: Mutex::new(None),
         }
     }
 
@@ -71,13 +125,35 @@ impl ContextServer {
         let transport = match endpoint.scheme() {
             "http" | "https" => {
                 log::info!("Using HTTP transport for {}", endpoint);
-                let transport =
-                    HttpTransport::new(http_client, endpoint.to_string(), headers, executor);
-                Arc::new(transport) as _
+                // Servers configured with an explicit authorization header don't use OAuth.
+                let uses_oauth = !headers
+                    .keys()
+                    .any(|key| key.eq_ignore_ascii_case("authorization"));
+                let transport = Arc::new(HttpTransport::new(
+                    http_client.clone(),
+                    endpoint.to_string(),
+                    headers,
+                    executor,
+                ));
+                if !uses_oauth {
+                    return Ok(Self::new_with_timeout(id, transport, request_timeout));
+                }
+                transport
             }
             _ => anyhow::bail!("unsupported MCP url scheme {}", endpoint.scheme()),
         };
-        Ok(Self::new_with_timeout(id, transport, request_timeout))
+        Ok(Self {
+            id,
+            client: RwLock::new(None),
+            configuration: ContextServerTransport::Http {
+                transport,
+                endpoint: endpoint.clone(),
+                http_client,
+            },
+            request_timeout,
+            persist_credentials: Mutex::new(None),
+            authorization_challenge: Mutex::new(None),
+        })
     }
 
     pub fn new(id: ContextServerId, transport: Arc<dyn crate::transport::Transport>) -> Self {
@@ -94,6 +170,8 @@ impl ContextServer {
             client: RwLock::new(None),
             configuration: ContextServerTransport::Custom(transport),
             request_timeout,
+            persist_credentials: Mutex::new(None),
+            authorization_challenge: Mutex::new(None),
         }
     }
 
@@ -105,8 +183,91 @@ impl ContextServer {
         self.client.read().clone()
     }
 
+    /// Whether the server authorizes Zed through OAuth, which [`Self::authenticate`] and
+    /// [`Self::sign_out`] manage.
+    pub fn uses_oauth(&self) -> bool {
+        matches!(self.configuration, ContextServerTransport::Http { .. })
+    }
+
+    /// Starts the server with the stored credentials, failing with [`AuthorizationRequired`] when
+    /// there are none or the server rejects them.
     pub async fn start(&self, cx: &AsyncApp) -> Result<()> {
-        self.initialize(self.new_client(cx)?).await
+        let ContextServerTransport::Http {
+            transport,
+            endpoint,
+            ..
+        } = &self.configuration
+        else {
+            return self.initialize(self.new_client(cx)?, cx).await;
+        };
+
+        match oauth::read_credentials(endpoint, cx).await {
+            Ok(credentials) => transport.set_credentials(credentials),
+            Err(error) => log::error!(
+                "failed to read the credentials of context server {}: {error:#}",
+                self.id
+            ),
+        }
+        *self.persist_credentials.lock() = Some(cx.spawn({
+            let refreshed_credentials = transport.refreshed_credentials();
+            let endpoint = endpoint.clone();
+            async move |cx| {
+                while let Ok(credentials) = refreshed_credentials.recv().await {
+                    oauth::write_credentials(&endpoint, &credentials, cx)
+                        .await
+                        .log_err();
+                }
+            }
+        }));
+
+        let result = self.initialize(self.new_client(cx)?, cx).await;
+        let Some(challenge) = transport.take_authorization_challenge() else {
+            return result;
+        };
+        log::info!("context server {} requires authorization", self.id);
+        *self.authorization_challenge.lock() = Some(challenge);
+        Err(AuthorizationRequired.into())
+    }
+
+    /// Authorizes Zed in the browser and starts the server with the new credentials.
+    pub async fn authenticate(&self, cx: &AsyncApp) -> Result<()> {
+        let ContextServerTransport::Http {
+            transport,
+            endpoint,
+            http_client,
+        } = &self.configuration
+        else {
+            anyhow::bail!("context server {} doesn't use OAuth", self.id);
+        };
+
+        let challenge = self
+            .authorization_challenge
+            .lock()
+            .take()
+            .unwrap_or_default();
+        let credentials = oauth::authorize(http_client, endpoint, &challenge, |url| {
+            cx.update(|cx| cx.open_url(url.as_str()))
+        })
+        .await?;
+        oauth::write_credentials(endpoint, &credentials, cx)
+            .await
+            .log_err();
+        transport.set_credentials(Some(credentials));
+        self.initialize(self.new_client(cx)?, cx).await
+    }
+
+    /// Forgets the credentials of the server, which then has to be authorized again.
+    pub async fn sign_out(&self, cx: &AsyncApp) -> Result<()> {
+        let ContextServerTransport::Http {
+            transport,
+            endpoint,
+            ..
+        } = &self.configuration
+        else {
+            anyhow::bail!("context server {} doesn't use OAuth", self.id);
+        };
+        transport.set_credentials(None);
+        oauth::delete_credentials(endpoint, cx).await
     }
 
     fn new_client(&self, cx: &AsyncApp) -> Result<Client> {
@@ -122,6 +283,13 @@ impl ContextServer {
                 working_directory,
                 cx.clone(),
             )?,
+            ContextServerTransport::Http { transport, .. } => Client::new(
+                client::ContextServerId(self.id.0.clone()),
+                self.id().0,
+                transport.clone(),
+                self.request_timeout,
+                cx.clone(),
+            )?,
             ContextServerTransport::Custom(transport) => Client::new(
                 client::ContextServerId(self.id.0.clone()),
                 self.id().0,
@@ -132,14 +300,39 @@ impl ContextServer {
         })
     }
 
-    async fn initialize(&self, client: Client) -> Result<()> {
+    async fn initialize(&self, client: Client, cx: &AsyncApp) -> Result<()> {
         log::debug!("starting context server {}", self.id);
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
             name: "Zed".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
-        let initialized_protocol = protocol.initialize(client_info).await?;
+
+        let mut capabilities = types::ClientCapabilities {
+            experimental: None,
+            sampling: None,
+            elicitation: None,
+            roots: None,
+        };
+        if let Some(handler) = cx.try_read_global(
+            |GlobalContextServerRequestHandler(handler): &GlobalContextServerRequestHandler, _| {
+                handler.clone()
+            },
+        ) {
+            protocol.on_request::<types::requests::CreateMessage>({
+                let handler = handler.clone();
+                let server_id = self.id();
+                move |request, cx| handler.create_message(server_id.clone(), request, cx)
+            });
+            protocol.on_request::<types::requests::Elicit>({
+                let server_id = self.id();
+                move |request, cx| handler.elicit(server_id.clone(), request, cx)
+            });
+            capabilities.sampling = Some(serde_json::json!({}));
+            capabilities.elicitation = Some(serde_json::json!({}));
+        }
+
+        let initialized_protocol = protocol.initialize(client_info, capabilities).await?;
 
         log::debug!(
             "context server {} initialized: {:?}",
@@ -0,0 +1,955 @@
+//! OAuth authorization for remote context servers.
+//!
+//! Implements the authorization flow of the Model Context Protocol: discovering the
+//! authorization server through the protected resource metadata (RFC 9728) and the
+//! authorization server metadata (RFC 8414), registering Zed as a client (RFC 7591),
+//! authorizing with PKCE through a loopback redirect, and refreshing the tokens.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context as _, Result, anyhow};
+use base64::Engine as _;
+use credentials_provider::CredentialsProvider;
+use gpui::AsyncApp;
+use http_client::{AsyncBody, HttpClient, Request, Response, http::Method};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest as _, Sha256};
+use url::Url;
+
+const CLIENT_NAME: &str = "Zed";
+const REDIRECT_PATH: &str = "/callback";
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long before their expiration tokens are considered expired, to account for latency.
+const EXPIRATION_MARGIN: Duration = Duration::from_secs(60);
+
+/// The parameters of the `WWW-Authenticate` header of an unauthorized response.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AuthorizationChallenge {
+    pub resource_metadata: Option<Url>,
+    pub scope: Option<String>,
+}
+
+impl AuthorizationChallenge {
+    pub fn from_response(response: &Response<AsyncBody>) -> Self {
+        response
+            .headers()
+            .get_all("www-authenticate")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(Self::parse)
+            .unwrap_or_default()
+    }
+
+    /// Parses a `Bearer` challenge, such as
+    /// `Bearer resource_metadata="https://example.com/.well-known/oauth-protected-resource"`.
+    pub fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(' ').unwrap_or((header.trim(), ""));
+        if !scheme.eq_ignore_ascii_case("bearer") {
+            return None;
+        }
+
+        let mut challenge = Self::default();
+        let mut rest = params.trim();
+        while !rest.is_empty() {
+            let Some((name, after_name)) = rest.split_once('=') else {
+                break;
+            };
+            let after_name = after_name.trim_start();
+            let (value, after_value) = if let Some(quoted) = after_name.strip_prefix('"') {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or(""))
+            } else {
+                after_name.split_once(',').unwrap_or((after_name, ""))
+            };
+
+            match name.trim() {
+                "resource_metadata" => challenge.resource_metadata = Url::parse(value).ok(),
+                "scope" => challenge.scope = Some(value.to_string()),
+                _ => {}
+            }
+            rest = after_value.trim_start_matches([',', ' ']);
+        }
+        Some(challenge)
+    }
+}
+
+/// See https://datatracker.ietf.org/doc/html/rfc9728#section-2
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProtectedResourceMetadata {
+    pub resource: Option<String>,
+    #[serde(default)]
+    pub authorization_servers: Vec<Url>,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+}
+
+/// See https://datatracker.ietf.org/doc/html/rfc8414#section-2
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthorizationServerMetadata {
+    pub issuer: Url,
+    pub authorization_endpoint: Url,
+    pub token_endpoint: Url,
+    pub registration_endpoint: Option<Url>,
+    #[serde(default)]
+    pub code_challenge_methods_supported: Vec<String>,
+}
+
+/// A client registered with an authorization server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OAuthClient {
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// When the access token expires, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl OAuthTokens {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| unix_time() + EXPIRATION_MARGIN.as_secs() >= expires_at)
+    }
+}
+
+/// Everything needed to authorize the requests to a context server and to refresh the tokens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OAuthCredentials {
+    pub token_endpoint: Url,
+    pub resource: String,
+    pub client: OAuthClient,
+    pub tokens: OAuthTokens,
+}
+
+impl OAuthCredentials {
+    pub fn can_refresh(&self) -> bool {
+        self.tokens.refresh_token.is_some()
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+}
+
+impl TokenResponse {
+    fn into_tokens(self, previous_refresh_token: Option<String>) -> OAuthTokens {
+        OAuthTokens {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token.or(previous_refresh_token),
+            expires_at: self.expires_in.map(|expires_in| unix_time() + expires_in),
+        }
+    }
+}
+
+/// A PKCE code verifier and its S256 challenge.
+///
+/// See https://datatracker.ietf.org/doc/html/rfc7636
+struct Pkce {
+    verifier: String,
+    challenge: String,
+}
+
+impl Pkce {
+    fn new() -> Self {
+        let verifier = random_token();
+        let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+}
+
+/// Runs the whole authorization flow for the context server at `endpoint`: discovers the
+/// authorization server, registers a client, asks `open_url` to show the authorization page
+/// to the user, and exchanges the code the browser is redirected with for tokens.
+pub async fn authorize(
+    http_client: &Arc<dyn HttpClient>,
+    endpoint: &Url,
+    challenge: &AuthorizationChallenge,
+    open_url: impl FnOnce(Url),
+) -> Result<OAuthCredentials> {
+    let resource_metadata = discover_protected_resource(http_client, endpoint, challenge).await?;
+    let issuer = match resource_metadata
+        .as_ref()
+        .and_then(|metadata| metadata.authorization_servers.first())
+    {
+        Some(issuer) => issuer.clone(),
+        // Servers implementing earlier revisions of the protocol act as their own
+        // authorization server.
+        None => {
+            let mut origin = endpoint.clone();
+            origin.set_path("");
+            origin.set_query(None);
+            origin
+        }
+    };
+    let metadata = discover_authorization_server(http_client, &issuer).await?;
+    anyhow::ensure!(
+        metadata.code_challenge_methods_supported.is_empty()
+            || metadata
+                .code_challenge_methods_supported
+                .iter()
+                .any(|method| method == "S256"),
+        "authorization server {issuer} does not support PKCE"
+    );
+
+    let resource = resource_metadata
+        .as_ref()
+        .and_then(|metadata| metadata.resource.clone())
+        .unwrap_or_else(|| endpoint.to_string());
+    let scope = challenge.scope.clone().or_else(|| {
+        resource_metadata
+            .as_ref()
+            .filter(|metadata| !metadata.scopes_supported.is_empty())
+            .map(|metadata| metadata.scopes_supported.join(" "))
+    });
+
+    let server = tiny_http::Server::http("127.0.0.1:0")
+        .map_err(|error| anyhow!("failed to start the OAuth redirect server: {error}"))?;
+    let redirect_uri = format!(
+        "http://127.0.0.1:{}{REDIRECT_PATH}",
+        server.server_addr().port()
+    );
+
+    let client = register_client(http_client, &metadata, &redirect_uri).await?;
+    let pkce = Pkce::new();
+    let state = random_token();
+
+    let mut authorization_url = metadata.authorization_endpoint.clone();
+    {
+        let mut query = authorization_url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &client.client_id)
+            .append_pair("redirect_uri", &redirect_uri)
+            .append_pair("code_challenge", &pkce.challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", &state)
+            .append_pair("resource", &resource);
+        if let Some(scope) = &scope {
+            query.append_pair("scope", scope);
+        }
+    }
+    open_url(authorization_url);
+
+    let code = smol::unblock(move || receive_authorization_code(server, &state)).await?;
+
+    let response: TokenResponse = post_form(
+        http_client,
+        &metadata.token_endpoint,
+        &client,
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", &redirect_uri),
+            ("code_verifier", &pkce.verifier),
+            ("resource", &resource),
+        ],
+    )
+    .await
+    .context("failed to exchange the authorization code")?;
+
+    Ok(OAuthCredentials {
+        token_endpoint: metadata.token_endpoint,
+        resource,
+        client,
+        tokens: response.into_tokens(None),
+    })
+}
+
+/// Exchanges the refresh token for new tokens.
+pub async fn refresh(
+    http_client: &Arc<dyn HttpClient>,
+    credentials: &OAuthCredentials,
+) -> Result<OAuthTokens> {
+    let refresh_token = credentials
+        .tokens
+        .refresh_token
+        .clone()
+        .context("no refresh token")?;
+    let response: TokenResponse = post_form(
+        http_client,
+        &credentials.token_endpoint,
+        &credentials.client,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &refresh_token),
+            ("resource", &credentials.resource),
+        ],
+    )
+    .await
+    .context("failed to refresh the access token")?;
+    Ok(response.into_tokens(Some(refresh_token)))
+}
+
+/// Returns the protected resource metadata of `endpoint`, or `None` if the server doesn't
+/// publish any.
+pub async fn discover_protected_resource(
+    http_client: &Arc<dyn HttpClient>,
+    endpoint: &Url,
+    challenge: &AuthorizationChallenge,
+) -> Result<Option<ProtectedResourceMetadata>> {
+    let candidates = match &challenge.resource_metadata {
+        Some(url) => vec![url.clone()],
+        None => well_known_urls(endpoint, &["oauth-protected-resource"]),
+    };
+    let (url, metadata) =
+        match get_first_json::<ProtectedResourceMetadata>(http_client, candidates).await {
+            Ok(found) => found,
+            Err(error) => {
+                log::debug!(
+                    "failed to discover the protected resource metadata of {endpoint}: {error:#}"
+                );
+                return Ok(None);
+            }
+        };
+
+    // The metadata must be about the resource it was looked up for (RFC 9728 §3.3), otherwise
+    // tokens could be requested for another resource. Metadata at a well-known URL can also be
+    // about the resource that URL was derived from.
+    let mut identifiers = vec![endpoint.clone()];
+    if challenge.resource_metadata.is_none()
+        && let Some(path) = url
+            .path()
+            .strip_prefix("/.well-known/oauth-protected-resource")
+    {
+        let mut identifier = endpoint.clone();
+        identifier.set_path(path);
+        identifier.set_query(None);
+        identifiers.push(identifier);
+    }
+    let resource = metadata
+        .resource
+        .as_deref()
+        .and_then(|resource| Url::parse(resource).ok());
+    anyhow::ensure!(
+        resource.is_some_and(|resource| identifiers.contains(&resource)),
+        "the protected resource metadata at {url} is for {}, not {endpoint}",
+        metadata
+            .resource
+            .as_deref()
+            .unwrap_or("an unspecified resource")
+    );
+    Ok(Some(metadata))
+}
+
+pub async fn discover_authorization_server(
+    http_client: &Arc<dyn HttpClient>,
+    issuer: &Url,
+) -> Result<AuthorizationServerMetadata> {
+    let candidates = well_known_urls(
+        issuer,
+        &["oauth-authorization-server", "openid-configuration"],
+    );
+    let (url, metadata) = get_first_json::<AuthorizationServerMetadata>(http_client, candidates)
+        .await
+        .with_context(|| {
+            format!("failed to discover the authorization server metadata of {issuer}")
+        })?;
+    // See https://datatracker.ietf.org/doc/html/rfc8414#section-3.3
+    anyhow::ensure!(
+        metadata.issuer == *issuer,
+        "the authorization server metadata at {url} is for {}, not {issuer}",
+        metadata.issuer
+    );
+    Ok(metadata)
+}
+
+pub async fn register_client(
+    http_client: &Arc<dyn HttpClient>,
+    metadata: &AuthorizationServerMetadata,
+    redirect_uri: &str,
+) -> Result<OAuthClient> {
+    let registration_endpoint = metadata
+        .registration_endpoint
+        .as_ref()
+        .context("the authorization server does not support dynamic client registration")?;
+    let body = serde_json::json!({
+        "client_name": CLIENT_NAME,
+        "redirect_uris": [redirect_uri],
+        "grant_types": ["authorization_code", "refresh_token"],
+        "response_types": ["code"],
+        "token_endpoint_auth_method": "none",
+    });
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(registration_endpoint.as_str())
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .body(AsyncBody::from(serde_json::to_vec(&body)?))?;
+    let response = http_client.send(request).await?;
+    read_json(response)
+        .await
+        .context("failed to register with the authorization server")
+}
+
+pub async fn read_credentials(endpoint: &Url, cx: &AsyncApp) -> Result<Option<OAuthCredentials>> {
+    let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx));
+    let Some((_, credentials)) = credentials_provider
+        .read_credentials(endpoint.as_str(), cx)
+        .await?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(serde_json::from_slice(&credentials)?))
+}
+
+pub async fn write_credentials(
+    endpoint: &Url,
+    credentials: &OAuthCredentials,
+    cx: &AsyncApp,
+) -> Result<()> {
+    let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx));
+    credentials_provider
+        .write_credentials(
+            endpoint.as_str(),
+            "Bearer",
+            &serde_json::to_vec(credentials)?,
+            cx,
+        )
+        .await
+}
+
+pub async fn delete_credentials(endpoint: &Url, cx: &AsyncApp) -> Result<()> {
+    let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx));
+    credentials_provider
+        .delete_credentials(endpoint.as_str(), cx)
+        .await
+}
+
+/// Returns the well-known URLs to look for metadata at, in order of preference, inserting the
+/// well-known path between the host and the path of `url` as RFC 8414 describes.
+fn well_known_urls(url: &Url, suffixes: &[&str]) -> Vec<Url> {
+    let path = url.path().trim_end_matches('/');
+    let mut urls = Vec::new();
+    for suffix in suffixes {
+        let mut well_known = url.clone();
+        well_known.set_query(None);
+        well_known.set_fragment(None);
+        well_known.set_path(&format!("/.well-known/{suffix}{path}"));
+        urls.push(well_known.clone());
+        if !path.is_empty() {
+            well_known.set_path(&format!("/.well-known/{suffix}"));
+            urls.push(well_known);
+        }
+    }
+    urls
+}
+
+/// Returns the first of the `candidates` that serves JSON metadata, along with the metadata.
+async fn get_first_json<T: DeserializeOwned>(
+    http_client: &Arc<dyn HttpClient>,
+    candidates: Vec<Url>,
+) -> Result<(Url, T)> {
+    let mut last_error = anyhow!("no metadata URL to try");
+    for url in candidates {
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(url.as_str())
+            .header("Accept", "application/json")
+            .body(AsyncBody::empty())?;
+        let result = match http_client.send(request).await {
+            Ok(response) => read_json(response).await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(metadata) => return Ok((url, metadata)),
+            Err(error) => last_error = error.context(format!("GET {url}")),
+        }
+    }
+    Err(last_error)
+}
+
+async fn post_form<T: DeserializeOwned>(
+    http_client: &Arc<dyn HttpClient>,
+    url: &Url,
+    client: &OAuthClient,
+    params: &[(&str, &str)],
+) -> Result<T> {
+    let mut form = url::form_urlencoded::Serializer::new(String::new());
+    form.extend_pairs(params);
+    form.append_pair("client_id", &client.client_id);
+    if let Some(client_secret) = &client.client_secret {
+        form.append_pair("client_secret", client_secret);
+    }
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(url.as_str())
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body(AsyncBody::from(form.finish()))?;
+    let response = http_client.send(request).await?;
+    read_json(response).await
+}
+
+async fn read_json<T: DeserializeOwned>(mut response: Response<AsyncBody>) -> Result<T> {
+    let mut body = String::new();
+    futures::AsyncReadExt::read_to_string(response.body_mut(), &mut body).await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "HTTP {}: {}",
+        response.status(),
+        body
+    );
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Waits for the browser to be redirected to the loopback server, and returns the authorization
+/// code it was redirected with.
+fn receive_authorization_code(server: tiny_http::Server, state: &str) -> Result<String> {
+    let deadline = std::time::Instant::now() + REDIRECT_TIMEOUT;
+    while let Some(timeout) = deadline.checked_duration_since(std::time::Instant::now()) {
+        let Some(request) = server.recv_timeout(timeout)? else {
+            break;
+        };
+        let url = Url::parse(&format!("http://127.0.0.1{}", request.url()))
+            .context("failed to parse the OAuth redirect url")?;
+        if url.path() != REDIRECT_PATH {
+            request.respond(tiny_http::Response::empty(404)).ok();
+            continue;
+        }
+
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        // Anything running locally can make requests to the loopback server, so only the
+        // redirect of this flow ends it.
+        if param("state").as_deref() != Some(state) {
+            request
+                .respond(
+                    tiny_http::Response::from_string("Unexpected OAuth state")
+                        .with_status_code(400),
+                )
+                .ok();
+            continue;
+        }
+
+        let result = if let Some(error) = param("error") {
+            let description = param("error_description").unwrap_or_default();
+            Err(anyhow!("authorization failed: {error} {description}"))
+        } else {
+            param("code").context("the OAuth redirect has no authorization code")
+        };
+
+        let message = match &result {
+            Ok(_) => "Zed has been authorized. You can close this page.".to_string(),
+            Err(error) => format!("Zed could not be authorized: {error}"),
+        };
+        let header = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/plain"[..])
+            .expect("valid header");
+        request
+            .respond(tiny_http::Response::from_string(message).with_header(header))
+            .ok();
+        return result;
+    }
+    anyhow::bail!("timed out waiting for the authorization in the browser")
+}
+
+fn random_token() -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpTransport, Transport as _};
+    use collections::HashMap;
+    use futures::{AsyncReadExt as _, StreamExt as _};
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+    use parking_lot::Mutex;
+    use std::io::{Read as _, Write as _};
+
+    #[test]
+    fn test_parse_challenge() {
+        assert_eq!(
+            AuthorizationChallenge::parse(
+                r#"Bearer resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource", scope="read write""#
+            ),
+            Some(AuthorizationChallenge {
+                resource_metadata: Some(
+                    Url::parse("https://mcp.example.com/.well-known/oauth-protected-resource")
+                        .unwrap()
+                ),
+                scope: Some("read write".into()),
+            })
+        );
+        assert_eq!(
+            AuthorizationChallenge::parse(r#"Bearer error="invalid_token""#),
+            Some(AuthorizationChallenge::default())
+        );
+        assert_eq!(AuthorizationChallenge::parse("Basic realm=\"mcp\""), None);
+    }
+
+    #[test]
+    fn test_well_known_urls() {
+        let urls = |url: &str, suffixes: &[&str]| {
+            well_known_urls(&Url::parse(url).unwrap(), suffixes)
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            urls(
+                "https://mcp.example.com/v1/mcp",
+                &["oauth-protected-resource"]
+            ),
+            [
+                "https://mcp.example.com/.well-known/oauth-protected-resource/v1/mcp",
+                "https://mcp.example.com/.well-known/oauth-protected-resource",
+            ]
+        );
+        assert_eq!(
+            urls(
+                "https://auth.example.com",
+                &["oauth-authorization-server", "openid-configuration"]
+            ),
+            [
+                "https://auth.example.com/.well-known/oauth-authorization-server",
+                "https://auth.example.com/.well-known/openid-configuration",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_authorize() {
+        let server = FakeAuthorizationServer::default();
+        let http_client = server.http_client();
+        let endpoint = Url::parse("https://mcp.example.com/mcp").unwrap();
+
+        let credentials = smol::block_on(authorize(
+            &http_client,
+            &endpoint,
+            &AuthorizationChallenge::default(),
+            |url| {
+                let server = server.clone();
+                std::thread::spawn(move || open_in_fake_browser(&server, url));
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(
+            credentials.client,
+            OAuthClient {
+                client_id: "client-1".into(),
+                client_secret: None,
+            }
+        );
+        assert_eq!(credentials.resource, "https://mcp.example.com/mcp");
+        assert_eq!(credentials.tokens.access_token, "access-1");
+        assert_eq!(
+            credentials.tokens.refresh_token.as_deref(),
+            Some("refresh-1")
+        );
+        assert!(!credentials.tokens.is_expired());
+
+        let tokens = smol::block_on(refresh(&http_client, &credentials)).unwrap();
+        assert_eq!(tokens.access_token, "access-2");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh-2"));
+    }
+
+    #[test]
+    fn test_authorize_rejects_metadata_of_other_servers() {
+        let endpoint = Url::parse("https://mcp.example.com/mcp").unwrap();
+        let authorize_with = |url: &'static str, field: &'static str, value: &'static str| {
+            let server = FakeAuthorizationServer::default();
+            server
+                .metadata_overrides
+                .lock()
+                .insert(url, serde_json::json!({ field: value }));
+            let error = smol::block_on(authorize(
+                &server.http_client(),
+                &endpoint,
+                &AuthorizationChallenge::default(),
+                |_| panic!("the authorization page was opened"),
+            ))
+            .unwrap_err();
+            format!("{error:#}")
+        };
+
+        let error = authorize_with(
+            "https://mcp.example.com/.well-known/oauth-protected-resource/mcp",
+            "resource",
+            "https://other.example.com/mcp",
+        );
+        assert!(
+            error.contains("is for https://other.example.com/mcp, not https://mcp.example.com/mcp"),
+            "{error}"
+        );
+
+        let error = authorize_with(
+            "https://auth.example.com/.well-known/oauth-authorization-server",
+            "issuer",
+            "https://other.example.com/",
+        );
+        assert!(
+            error.contains("is for https://other.example.com/, not https://auth.example.com/"),
+            "{error}"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_transport_refreshes_expired_tokens(cx: &mut TestAppContext) {
+        let server = FakeAuthorizationServer::default();
+        let transport = HttpTransport::new(
+            server.http_client(),
+            "https://mcp.example.com/mcp".into(),
+            HashMap::default(),
+            cx.executor(),
+        );
+        let message = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#.to_string();
+
+        transport.send(message.clone()).await.unwrap();
+        let response = transport.receive().next().await.unwrap();
+        let response = serde_json::from_str::<serde_json::Value>(&response).unwrap();
+        assert_eq!(response["error"]["code"], crate::transport::UNAUTHORIZED);
+        let challenge = transport.take_authorization_challenge().unwrap();
+        assert_eq!(
+            challenge.resource_metadata.unwrap().as_str(),
+            "https://mcp.example.com/.well-known/oauth-protected-resource/mcp"
+        );
+
+        // The server rejects the expired access token, so the transport refreshes it and retries.
+        transport.set_credentials(Some(OAuthCredentials {
+            token_endpoint: Url::parse("https://auth.example.com/token").unwrap(),
+            resource: "https://mcp.example.com/mcp".into(),
+            client: OAuthClient {
+                client_id: "client-1".into(),
+                client_secret: None,
+            },
+            tokens: OAuthTokens {
+                access_token: "expired".into(),
+                refresh_token: Some("refresh-1".into()),
+                expires_at: None,
+            },
+        }));
+        transport.send(message).await.unwrap();
+        let response = transport.receive().next().await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&response).unwrap(),
+            serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": {}})
+        );
+        assert_eq!(transport.take_authorization_challenge(), None);
+
+        let refreshed = transport.refreshed_credentials().recv().await.unwrap();
+        assert_eq!(refreshed.tokens.access_token, "access-2");
+        assert_eq!(refreshed.tokens.refresh_token.as_deref(), Some("refresh-2"));
+    }
+
+    #[gpui::test]
+    async fn test_start_requires_authorization(cx: &mut TestAppContext) {
+        let server = FakeAuthorizationServer::default();
+        let context_server = crate::ContextServer::http(
+            crate::ContextServerId("mcp".into()),
+            &Url::parse("https://mcp.example.com/mcp").unwrap(),
+            HashMap::default(),
+            server.http_client(),
+            cx.executor(),
+            None,
+        )
+        .unwrap();
+        assert!(context_server.uses_oauth());
+
+        // The authorization flow only starts when the user asks for it.
+        let error = context_server.start(&cx.to_async()).await.unwrap_err();
+        assert!(error.is::<crate::AuthorizationRequired>(), "{error:#}");
+        assert_eq!(cx.opened_url(), None);
+    }
+
+    /// A stand-in for a context server that requires authorization and its authorization server.
+    #[derive(Clone, Default)]
+    struct FakeAuthorizationServer {
+        code_challenge: Arc<Mutex<Option<String>>>,
+        /// Fields to replace in the metadata served at each URL.
+        metadata_overrides: Arc<Mutex<HashMap<&'static str, serde_json::Value>>>,
+    }
+
+    impl FakeAuthorizationServer {
+        fn http_client(&self) -> Arc<dyn HttpClient> {
+            let this = self.clone();
+            FakeHttpClient::create(move |request| {
+                let this = this.clone();
+                async move { Ok(this.handle(request).await) }
+            })
+        }
+
+        async fn handle(&self, mut request: Request<AsyncBody>) -> Response<AsyncBody> {
+            let mut body = String::new();
+            request.body_mut().read_to_string(&mut body).await.unwrap();
+            let form = url::form_urlencoded::parse(body.as_bytes())
+                .into_owned()
+                .collect::<HashMap<String, String>>();
+
+            let uri = request.uri().to_string();
+            let json = |value: serde_json::Value| {
+                Response::builder()
+                    .status(200)
+                    .header("Content-Type", "application/json")
+                    .body(AsyncBody::from(value.to_string()))
+                    .unwrap()
+            };
+            match (request.method().as_str(), uri.as_str()) {
+                ("POST", "https://mcp.example.com/mcp") => {
+                    let authorization = request
+                        .headers()
+                        .get("Authorization")
+                        .and_then(|value| value.to_str().ok());
+                    if authorization == Some("Bearer access-2") {
+                        json(serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": {}}))
+                    } else {
+                        Response::builder()
+                            .status(401)
+                            .header(
+                                "WWW-Authenticate",
+                                r#"Bearer resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource/mcp""#,
+                            )
+                            .body(AsyncBody::empty())
+                            .unwrap()
+                    }
+                }
+                ("GET", "https://mcp.example.com/.well-known/oauth-protected-resource/mcp") => {
+                    json(self.metadata(
+                        &uri,
+                        serde_json::json!({
+                            "resource": "https://mcp.example.com/mcp",
+                            "authorization_servers": ["https://auth.example.com/"],
+                        }),
+                    ))
+                }
+                ("GET", "https://auth.example.com/.well-known/oauth-authorization-server") => {
+                    json(self.metadata(
+                        &uri,
+                        serde_json::json!({
+                            "issuer": "https://auth.example.com/",
+                            "authorization_endpoint": "https://auth.example.com/authorize",
+                            "token_endpoint": "https://auth.example.com/token",
+                            "registration_endpoint": "https://auth.example.com/register",
+                            "code_challenge_methods_supported": ["S256"],
+                        }),
+                    ))
+                }
+                ("POST", "https://auth.example.com/register") => {
+                    let registration: serde_json::Value = serde_json::from_str(&body).unwrap();
+                    assert_eq!(registration["token_endpoint_auth_method"], "none");
+                    assert!(
+                        registration["redirect_uris"][0]
+                            .as_str()
+                            .unwrap()
+                            .starts_with("http://127.0.0.1:")
+                    );
+                    json(serde_json::json!({"client_id": "client-1"}))
+                }
+                ("POST", "https://auth.example.com/token") => {
+                    assert_eq!(form["client_id"], "client-1");
+                    assert_eq!(form["resource"], "https://mcp.example.com/mcp");
+                    match form["grant_type"].as_str() {
+                        "authorization_code" => {
+                            assert_eq!(form["code"], "code-1");
+                            let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                                .encode(Sha256::digest(form["code_verifier"].as_bytes()));
+                            assert_eq!(Some(challenge), self.code_challenge.lock().clone());
+                            json(serde_json::json!({
+                                "access_token": "access-1",
+                                "token_type": "Bearer",
+                                "expires_in": 3600,
+                                "refresh_token": "refresh-1",
+                            }))
+                        }
+                        "refresh_token" => {
+                            assert_eq!(form["refresh_token"], "refresh-1");
+                            json(serde_json::json!({
+                                "access_token": "access-2",
+                                "token_type": "Bearer",
+                                "refresh_token": "refresh-2",
+                            }))
+                        }
+                        grant_type => panic!("unexpected grant type {grant_type}"),
+                    }
+                }
+                _ => Response::builder()
+                    .status(404)
+                    .body(AsyncBody::empty())
+                    .unwrap(),
+            }
+        }
+
+        fn metadata(&self, url: &str, mut metadata: serde_json::Value) -> serde_json::Value {
+            if let Some(serde_json::Value::Object(overrides)) =
+                self.metadata_overrides.lock().get(url)
+            {
+                for (field, value) in overrides {
+                    metadata[field] = value.clone();
+                }
+            }
+            metadata
+        }
+    }
+
+    /// Plays the part of the browser: the user approves the authorization, and the authorization
+    /// server redirects to the loopback server.
+    fn open_in_fake_browser(server: &FakeAuthorizationServer, authorization_url: Url) {
+        let params = authorization_url
+            .query_pairs()
+            .into_owned()
+            .collect::<HashMap<String, String>>();
+        assert_eq!(params["client_id"], "client-1");
+        assert_eq!(params["resource"], "https://mcp.example.com/mcp");
+
+        assert_eq!(params["code_challenge_method"], "S256");
+        *server.code_challenge.lock() = Some(params["code_challenge"].clone());
+
+        // Redirects with another state, like the ones of an earlier attempt, are ignored.
+        let redirect_uri = Url::parse(&params["redirect_uri"]).unwrap();
+        let response = redirect(&redirect_uri, &[("code", "code-0"), ("state", "stale")]);
+        assert!(response.starts_with("HTTP/1.1 400"), "{response}");
+
+        let response = redirect(
+            &redirect_uri,
+            &[("code", "code-1"), ("state", &params["state"])],
+        );
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    }
+
+    fn redirect(redirect_uri: &Url, params: &[(&str, &str)]) -> String {
+        let mut redirect_url = redirect_uri.clone();
+        redirect_url.query_pairs_mut().extend_pairs(params);
+
+        let address = format!(
+            "{}:{}",
+            redirect_url.host_str().unwrap(),
+            redirect_url.port().unwrap()
+        );
+        let mut stream = std::net::TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "GET {}?{} HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n",
+            redirect_url.path(),
+            redirect_url.query().unwrap()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+}
@@ -39,6 +39,7 @@ pub struct FakeTransport {
         &'static str,
         Arc<dyn Send + Sync + Fn(serde_json::Value) -> BoxFuture<'static, serde_json::Value>>,
     >,
+    response_tx: Option<futures::channel::mpsc::UnboundedSender<String>>,
     tx: futures::channel::mpsc::UnboundedSender<String>,
     rx: Arc<Mutex<futures::channel::mpsc::UnboundedReceiver<String>>>,
     executor: BackgroundExecutor,
@@ -49,6 +50,7 @@ impl FakeTransport {
         let (tx, rx) = futures::channel::mpsc::unbounded();
         Self {
             request_handlers: Default::default(),
+            response_tx: None,
             tx,
             rx: Arc::new(Mutex::new(rx)),
             executor,
@@ -78,6 +80,17 @@ impl FakeTransport {
         );
         self
     }
+
+    /// Forwards the responses of the client to the requests of the server to `tx`.
+    pub fn on_response(mut self, tx: futures::channel::mpsc::UnboundedSender<String>) -> Self {
+        self.response_tx = Some(tx);
+        self
+    }
+
+    /// Returns a sender of messages to the client, such as requests from the server.
+    pub fn sender(&self) -> futures::channel::mpsc::UnboundedSender<String> {
+        self.tx.clone()
+    }
 }
 
 #[async_trait::async_trait]
@@ -101,6 +114,10 @@ impl Transport for FakeTransport {
                 } else {
                     log::debug!("No handler registered for MCP request '{method}'");
                 }
+            } else if let Some(response_tx) = &self.response_tx {
+                response_tx
+                    .unbounded_send(message)
+                    .context("sending a response")?;
             }
         }
         Ok(())
@@ -8,12 +8,15 @@ use parking_lot::Mutex as SyncMutex;
 use smol::channel;
 use std::{pin::Pin, sync::Arc};
 
+use crate::oauth::{self, AuthorizationChallenge, OAuthCredentials};
 use crate::transport::Transport;
 
 // Constants from MCP spec
 const HEADER_SESSION_ID: &str = "Mcp-Session-Id";
 const EVENT_STREAM_MIME_TYPE: &str = "text/event-stream";
 const JSON_MIME_TYPE: &str = "application/json";
+/// The JSON-RPC error code of the requests rejected because the server requires authorization.
+pub const UNAUTHORIZED: i32 = -32001;
 
 /// HTTP Transport with session management and SSE support
 pub struct HttpTransport {
@@ -27,6 +30,10 @@ pub struct HttpTransport {
     error_rx: channel::Receiver<String>,
     // Authentication headers to include in requests
     headers: HashMap<String, String>,
+    credentials: Arc<SyncMutex<Option<OAuthCredentials>>>,
+    challenge: Arc<SyncMutex<Option<AuthorizationChallenge>>>,
+    refreshed_credentials_tx: channel::Sender<OAuthCredentials>,
+    refreshed_credentials_rx: channel::Receiver<OAuthCredentials>,
 }
 
 impl HttpTransport {
@@ -38,6 +45,7 @@ impl HttpTransport {
     ) -> Self {
         let (response_tx, response_rx) = channel::unbounded();
         let (error_tx, error_rx) = channel::unbounded();
+        let (refreshed_credentials_tx, refreshed_credentials_rx) = channel::unbounded();
 
         Self {
             http_client,
@@ -49,14 +57,52 @@ impl HttpTransport {
             error_tx,
             error_rx,
             headers,
+            credentials: Arc::new(SyncMutex::new(None)),
+            challenge: Arc::new(SyncMutex::new(None)),
+            refreshed_credentials_tx,
+            refreshed_credentials_rx,
         }
     }
 
-    /// Send a message and handle the response based on content type
-    async fn send_message(&self, message: String) -> Result<()> {
-        let is_notification =
-            !message.contains("\"id\":") || message.contains("notifications/initialized");
+    /// Sets the OAuth credentials to authorize the requests with.
+    pub fn set_credentials(&self, credentials: Option<OAuthCredentials>) {
+        *self.credentials.lock() = credentials;
+    }
+
+    /// Returns the challenge of the last response that required authorization, if any.
+    pub fn take_authorization_challenge(&self) -> Option<AuthorizationChallenge> {
+        self.challenge.lock().take()
+    }
+
+    /// The credentials obtained by refreshing the tokens, which should be persisted.
+    pub fn refreshed_credentials(&self) -> channel::Receiver<OAuthCredentials> {
+        self.refreshed_credentials_rx.clone()
+    }
 
+    /// Refreshes the access token, returning whether the credentials could be refreshed.
+    async fn refresh_credentials(&self) -> bool {
+        let Some(mut credentials) = self.credentials.lock().clone() else {
+            return false;
+        };
+        if !credentials.can_refresh() {
+            return false;
+        }
+
+        match oauth::refresh(&self.http_client, &credentials).await {
+            Ok(tokens) => {
+                credentials.tokens = tokens;
+                *self.credentials.lock() = Some(credentials.clone());
+                self.refreshed_credentials_tx.send(credentials).await.ok();
+                true
+            }
+            Err(error) => {
+                log::warn!("{error:#}");
+                false
+            }
+        }
+    }
+
+    async fn post(&self, message: &str) -> Result<Response<AsyncBody>> {
         let mut request_builder = Request::builder()
             .method(Method::POST)
             .uri(&self.endpoint)
@@ -70,13 +116,77 @@ impl HttpTransport {
             request_builder = request_builder.header(key.as_str(), value.as_str());
         }
 
+        if let Some(credentials) = self.credentials.lock().as_ref() {
+            request_builder = request_builder.header(
+                "Authorization",
+                format!("Bearer {}", credentials.tokens.access_token),
+            );
+        }
+
         // Add session ID if we have one (except for initialize)
         if let Some(ref session_id) = *self.session_id.lock() {
             request_builder = request_builder.header(HEADER_SESSION_ID, session_id.as_str());
         }
 
-        let request = request_builder.body(AsyncBody::from(message.into_bytes()))?;
-        let mut response = self.http_client.send(request).await?;
+        let request = request_builder.body(AsyncBody::from(message.to_string()))?;
+        self.http_client.send(request).await
+    }
+
+    /// Fails the request in `message` right away rather than letting it time out, so that
+    /// the client can authorize and retry.
+    async fn reject_unauthorized(&self, message: &str) -> Result<()> {
+        let id = serde_json::from_str::<serde_json::Value>(message)
+            .ok()
+            .and_then(|mut message| message.get_mut("id").map(serde_json::Value::take));
+        match id {
+            Some(id) => self
+                .response_tx
+                .send(
+                    serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": UNAUTHORIZED,
+                            "message": "The context server requires authorization",
+                        },
+                    })
+                    .to_string(),
+                )
+                .await
+                .map_err(|_| anyhow!("Failed to send unauthorized response")),
+            None => self
+                .error_tx
+                .send("HTTP 401: the context server requires authorization".to_string())
+                .await
+                .map_err(|_| anyhow!("Failed to send error")),
+        }
+    }
+
+    /// Send a message and handle the response based on content type
+    async fn send_message(&self, message: String) -> Result<()> {
+        let is_notification =
+            !message.contains("\"id\":") || message.contains("notifications/initialized");
+
+        let expired = self
+            .credentials
+            .lock()
+            .as_ref()
+            .is_some_and(|credentials| credentials.tokens.is_expired());
+        if expired {
+            self.refresh_credentials().await;
+        }
+
+        let mut response = self.post(&message).await?;
+        if response.status().as_u16() == 401 {
+            let challenge = AuthorizationChallenge::from_response(&response);
+            if self.refresh_credentials().await {
+                response = self.post(&message).await?;
+            }
+            if response.status().as_u16() == 401 {
+                *self.challenge.lock() = Some(challenge);
+                return self.reject_unauthorized(&message).await;
+            }
+        }
 
         // Handle different response types based on status and content-type
         match response.status() {
@@ -233,6 +343,11 @@ impl Drop for HttpTransport {
         let endpoint = self.endpoint.clone();
         let session_id = self.session_id.lock().clone();
         let headers = self.headers.clone();
+        let access_token = self
+            .credentials
+            .lock()
+            .as_ref()
+            .map(|credentials| credentials.tokens.access_token.clone());
 
         if let Some(session_id) = session_id {
             self.executor
@@ -246,6 +361,10 @@ impl Drop for HttpTransport {
                     for (key, value) in headers {
                         request_builder = request_builder.header(key.as_str(), value.as_str());
                     }
+                    if let Some(access_token) = access_token {
+                        request_builder = request_builder
+                            .header("Authorization", format!("Bearer {access_token}"));
+                    }
 
                     let request = request_builder.body(AsyncBody::empty());
 
@@ -0,0 +1,170 @@
+//! Expansion of the URI templates ([RFC 6570](https://datatracker.ietf.org/doc/html/rfc6570))
+//! that servers use to describe parameterized resources.
+//!
+//! Only level 3 templates are supported, which covers the templates servers use in practice.
+
+use collections::HashMap;
+use std::fmt::Write as _;
+
+struct Operator {
+    prefix: &'static str,
+    separator: &'static str,
+    named: bool,
+    if_empty: &'static str,
+    allow_reserved: bool,
+}
+
+impl Operator {
+    fn parse(expression: &str) -> (Self, &str) {
+        let operator = |prefix, separator, named, if_empty, allow_reserved| Operator {
+            prefix,
+            separator,
+            named,
+            if_empty,
+            allow_reserved,
+        };
+        match expression.chars().next() {
+            Some('+') => (operator("", ",", false, "", true), &expression[1..]),
+            Some('#') => (operator("#", ",", false, "", true), &expression[1..]),
+            Some('.') => (operator(".", ".", false, "", false), &expression[1..]),
+            Some('/') => (operator("/", "/", false, "", false), &expression[1..]),
+            Some(';') => (operator(";", ";", true, "", false), &expression[1..]),
+            Some('?') => (operator("?", "&", true, "=", false), &expression[1..]),
+            Some('&') => (operator("&", "&", true, "=", false), &expression[1..]),
+            _ => (operator("", ",", false, "", false), expression),
+        }
+    }
+}
+
+/// Returns the names of the variables in the template, in the order they first appear.
+pub fn variables(template: &str) -> Vec<&str> {
+    let mut variables = Vec::new();
+    for expression in expressions(template) {
+        let (_, names) = Operator::parse(expression);
+        for name in names.split(',').map(variable_name) {
+            if !name.is_empty() && !variables.contains(&name) {
+                variables.push(name);
+            }
+        }
+    }
+    variables
+}
+
+/// Expands the template, leaving out the variables that have no value.
+pub fn expand(template: &str, values: &HashMap<String, String>) -> String {
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+
+        let (operator, names) = Operator::parse(&rest[start + 1..end]);
+        let mut first = true;
+        for name in names.split(',').map(variable_name) {
+            let Some(value) = values.get(name) else {
+                continue;
+            };
+            expanded.push_str(if first {
+                operator.prefix
+            } else {
+                operator.separator
+            });
+            first = false;
+            if operator.named {
+                expanded.push_str(name);
+                if value.is_empty() {
+                    expanded.push_str(operator.if_empty);
+                    continue;
+                }
+                expanded.push('=');
+            }
+            encode(value, operator.allow_reserved, &mut expanded);
+        }
+
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+fn expressions(template: &str) -> impl Iterator<Item = &str> {
+    template.split('{').skip(1).filter_map(|part| {
+        let (expression, _) = part.split_once('}')?;
+        Some(expression)
+    })
+}
+
+/// Strips the prefix and explode modifiers, which aren't supported, from a variable.
+fn variable_name(variable: &str) -> &str {
+    let variable = variable.trim();
+    let variable = variable.strip_suffix('*').unwrap_or(variable);
+    variable
+        .split_once(':')
+        .map_or(variable, |(name, _max_length)| name)
+}
+
+fn encode(value: &str, allow_reserved: bool, output: &mut String) {
+    const RESERVED: &str = ":/?#[]@!$&'()*+,;=";
+    for char in value.chars() {
+        if char.is_ascii_alphanumeric()
+            || matches!(char, '-' | '.' | '_' | '~')
+            || (allow_reserved && (RESERVED.contains(char) || char == '%'))
+        {
+            output.push(char);
+        } else {
+            let mut bytes = [0; 4];
+            for byte in char.encode_utf8(&mut bytes).bytes() {
+                write!(output, "%{byte:02X}").ok();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_variables() {
+        assert_eq!(
+            variables("repo://{owner}/{repo}/blob/{+path}{?ref,owner}"),
+            vec!["owner", "repo", "path", "ref"]
+        );
+        assert_eq!(variables("file:///logs/today"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_expand() {
+        let values = values(&[
+            ("owner", "zed-industries"),
+            ("repo", "zed"),
+            ("path", "crates/gpui/README.md"),
+            ("query", "hello world"),
+        ]);
+        assert_eq!(
+            expand("repo://{owner}/{repo}/blob/{+path}", &values),
+            "repo://zed-industries/zed/blob/crates/gpui/README.md"
+        );
+        assert_eq!(
+            expand("repo://{owner}/blob/{path}", &values),
+            "repo://zed-industries/blob/crates%2Fgpui%2FREADME.md"
+        );
+        assert_eq!(
+            expand("search://{owner}{?query,ref}", &values),
+            "search://zed-industries?query=hello%20world"
+        );
+        assert_eq!(
+            expand("repo://{/owner,repo}", &values),
+            "repo:///zed-industries/zed"
+        );
+    }
+}
@@ -20,8 +20,8 @@ use util::{RangeExt as _, ResultExt};
 mod assemble_excerpts;
 #[cfg(test)]
 mod edit_prediction_context_tests;
-#[cfg(test)]
-mod fake_definition_lsp;
+#[cfg(any(test, feature = "test-support"))]
+pub mod fake_definition_lsp;
 
 pub use zeta_prompt::{RelatedExcerpt, RelatedFile};
 
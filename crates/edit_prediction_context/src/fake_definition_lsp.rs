@@ -12,6 +12,9 @@ use tree_sitter::{Parser, QueryCursor, StreamingIterator, Tree};
 /// Registers a fake language server that implements go-to-definition using tree-sitter,
 /// making the assumption that all names are unique, and all variables' types are
 /// explicitly declared.
+///
+/// It also answers find-references, workspace-symbol, hover and rename requests, treating
+/// every whole-word occurrence of a declared name as a reference to it.
 pub fn register_fake_definition_server(
     language_registry: &Arc<LanguageRegistry>,
     language: Arc<Language>,
@@ -34,6 +37,10 @@ pub fn register_fake_definition_server(
             },
             capabilities: lsp::ServerCapabilities {
                 definition_provider: Some(lsp::OneOf::Left(true)),
+                references_provider: Some(lsp::OneOf::Left(true)),
+                workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                rename_provider: Some(lsp::OneOf::Left(true)),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
                     TextDocumentSyncKind::FULL,
                 )),
@@ -153,6 +160,48 @@ pub fn register_fake_definition_server(
                             async move { Ok(result) }
                         }
                     });
+
+                    server.set_request_handler::<lsp::request::References, _, _>({
+                        let index = index.clone();
+                        move |params, _cx| {
+                            let result = index.lock().get_references(
+                                params.text_document_position.text_document.uri,
+                                params.text_document_position.position,
+                            );
+                            async move { Ok(result) }
+                        }
+                    });
+
+                    server.set_request_handler::<lsp::request::WorkspaceSymbolRequest, _, _>({
+                        let index = index.clone();
+                        move |params, _cx| {
+                            let result = index.lock().get_workspace_symbols(&params.query);
+                            async move { Ok(Some(result)) }
+                        }
+                    });
+
+                    server.set_request_handler::<lsp::request::HoverRequest, _, _>({
+                        let index = index.clone();
+                        move |params, _cx| {
+                            let result = index.lock().get_hover(
+                                params.text_document_position_params.text_document.uri,
+                                params.text_document_position_params.position,
+                            );
+                            async move { Ok(result) }
+                        }
+                    });
+
+                    server.set_request_handler::<lsp::request::Rename, _, _>({
+                        let index = index.clone();
+                        move |params, _cx| {
+                            let result = index.lock().get_rename_edit(
+                                params.text_document_position.text_document.uri,
+                                params.text_document_position.position,
+                                &params.new_name,
+                            );
+                            async move { Ok(result) }
+                        }
+                    });
                 }
             })),
         },
@@ -249,6 +298,130 @@ impl DefinitionIndex {
         let locations = self.definitions.get(name).cloned()?;
         Some(lsp::GotoDefinitionResponse::Array(locations))
     }
+
+    fn get_references(&self, uri: Uri, position: lsp::Position) -> Option<Vec<lsp::Location>> {
+        let entry = self.files.get(&uri)?;
+        let name = word_at_position(&entry.contents, position)?;
+        self.definitions.get(name)?;
+        Some(self.occurrences(name))
+    }
+
+    fn get_workspace_symbols(&self, query: &str) -> lsp::WorkspaceSymbolResponse {
+        let query = query.to_lowercase();
+        let mut symbols = Vec::new();
+        for (name, locations) in &self.definitions {
+            if !name.to_lowercase().contains(&query) {
+                continue;
+            }
+            for location in locations {
+                let kind = self
+                    .declaration_line(location)
+                    .map(symbol_kind_for_declaration)
+                    .unwrap_or(lsp::SymbolKind::VARIABLE);
+                #[allow(deprecated)]
+                symbols.push(lsp::SymbolInformation {
+                    name: name.clone(),
+                    kind,
+                    tags: None,
+                    deprecated: None,
+                    location: location.clone(),
+                    container_name: None,
+                });
+            }
+        }
+        symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        lsp::WorkspaceSymbolResponse::Flat(symbols)
+    }
+
+    fn get_hover(&self, uri: Uri, position: lsp::Position) -> Option<lsp::Hover> {
+        let entry = self.files.get(&uri)?;
+        let name = word_at_position(&entry.contents, position)?;
+        let location = self.definitions.get(name)?.first()?;
+        let declaration = self.declaration_line(location)?;
+        Some(lsp::Hover {
+            contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                kind: lsp::MarkupKind::Markdown,
+                value: format!("```\n{}\n```", declaration.trim()),
+            }),
+            range: None,
+        })
+    }
+
+    fn get_rename_edit(
+        &self,
+        uri: Uri,
+        position: lsp::Position,
+        new_name: &str,
+    ) -> Option<lsp::WorkspaceEdit> {
+        let references = self.get_references(uri, position)?;
+        let mut changes = std::collections::HashMap::<Uri, Vec<lsp::TextEdit>>::default();
+        for location in references {
+            changes
+                .entry(location.uri)
+                .or_default()
+                .push(lsp::TextEdit::new(location.range, new_name.to_string()));
+        }
+        Some(lsp::WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        })
+    }
+
+    /// Returns every whole-word occurrence of the given name across the indexed files.
+    fn occurrences(&self, name: &str) -> Vec<lsp::Location> {
+        let mut locations = Vec::new();
+        for (uri, entry) in &self.files {
+            let contents = &entry.contents;
+            for (start, _) in contents.match_indices(name) {
+                let end = start + name.len();
+                let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+                if contents[..start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(is_word_char)
+                    || contents[end..].chars().next().is_some_and(is_word_char)
+                {
+                    continue;
+                }
+                locations.push(lsp::Location {
+                    uri: uri.clone(),
+                    range: byte_range_to_lsp_range(contents, start..end),
+                });
+            }
+        }
+        locations
+            .sort_by(|a, b| (a.uri.as_str(), a.range.start).cmp(&(b.uri.as_str(), b.range.start)));
+        locations
+    }
+
+    fn declaration_line(&self, location: &lsp::Location) -> Option<&str> {
+        let entry = self.files.get(&location.uri)?;
+        entry
+            .contents
+            .lines()
+            .nth(location.range.start.line as usize)
+    }
+}
+
+fn symbol_kind_for_declaration(line: &str) -> lsp::SymbolKind {
+    let words = line
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .collect::<Vec<_>>();
+    if words.contains(&"fn") {
+        lsp::SymbolKind::FUNCTION
+    } else if words.contains(&"struct") {
+        lsp::SymbolKind::STRUCT
+    } else if words.contains(&"enum") {
+        lsp::SymbolKind::ENUM
+    } else if words.contains(&"trait") {
+        lsp::SymbolKind::INTERFACE
+    } else if words.contains(&"mod") {
+        lsp::SymbolKind::MODULE
+    } else if words.contains(&"const") || words.contains(&"static") {
+        lsp::SymbolKind::CONSTANT
+    } else {
+        lsp::SymbolKind::VARIABLE
+    }
 }
 
 fn extract_declarations_from_tree(
@@ -317,6 +317,45 @@ pub struct SplitSelectionIntoLines {
     pub keep_selections: bool,
 }
 
+/// Aligns the selected lines on a delimiter such as `=`, `:`, `,` or `=>`.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor)]
+#[serde(deny_unknown_fields)]
+pub struct AlignOnDelimiter {
+    /// The delimiter to align on. When omitted, the most common of `=>`, `=`, `:` and `,` is used.
+    #[serde(default)]
+    pub delimiter: Option<String>,
+    /// Whether `delimiter` is a regular expression.
+    #[serde(default)]
+    pub regex: bool,
+    /// Whether to align every occurrence of the delimiter rather than only the first on each line.
+    #[serde(default)]
+    pub all_occurrences: bool,
+}
+
+/// Pipes each selection, or the whole buffer when nothing is selected, through a shell command.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor)]
+#[serde(deny_unknown_fields)]
+pub struct FilterSelectionsThroughCommand {
+    /// The command to run in the project's shell. When omitted, a prompt asks for one.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Whether the command output replaces the selections or is inserted at each cursor.
+    #[serde(default)]
+    pub mode: FilterCommandMode,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterCommandMode {
+    /// Feed the selected text to the command and replace it with the output.
+    #[default]
+    Replace,
+    /// Run the command without input and insert its output at each cursor.
+    Insert,
+}
+
 /// Goes to the next diagnostic in the file.
 #[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
 #[action(namespace = editor)]
@@ -398,6 +437,8 @@ actions!(
         #[action(deprecated_aliases = ["editor::AcceptPartialCopilotSuggestion"])]
         AcceptNextWordEditPrediction,
         AcceptNextLineEditPrediction,
+        /// Pads multiple cursors with spaces so that they line up on the same column.
+        AlignCursors,
         /// Applies all diff hunks in the editor.
         ApplyAllDiffHunks,
         /// Applies the diff hunk at the current position.
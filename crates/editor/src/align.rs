@@ -0,0 +1,424 @@
+use std::ops::Range;
+
+use collections::BTreeMap;
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot};
+use regex::Regex;
+use text::Point;
+use ui::{App, Context, Window};
+use unicode_width::UnicodeWidthChar as _;
+
+use crate::{
+    AlignCursors, AlignOnDelimiter, Editor, HideMouseCursorOrigin, Selection, SelectionGoal,
+    scroll::Autoscroll,
+};
+
+/// Delimiters tried, in order, when [`AlignOnDelimiter`] does not name one.
+const DEFAULT_DELIMITERS: &[&str] = &["=>", "=", ":", ","];
+
+impl Editor {
+    pub fn align_cursors(&mut self, _: &AlignCursors, window: &mut Window, cx: &mut Context<Self>) {
+        self.hide_mouse_cursor(HideMouseCursorOrigin::TypingAction, cx);
+        let display_snapshot = self.display_snapshot(cx);
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<Point>(&display_snapshot);
+        if selections.len() < 2 {
+            return;
+        }
+
+        // Selections spanning several rows aren't aligned, but move along with the text.
+        let mut selections_by_row = BTreeMap::<u32, Vec<Selection<Point>>>::default();
+        let mut other_selections = Vec::new();
+        for selection in &selections {
+            if selection.start.row == selection.end.row {
+                selections_by_row
+                    .entry(selection.start.row)
+                    .or_default()
+                    .push(selection.clone());
+            } else {
+                other_selections.push(selection);
+            }
+        }
+        if selections_by_row.len() < 2 {
+            return;
+        }
+        let tab_size = tab_size_at(&buffer, selections[0].start, cx);
+
+        let lines = selections_by_row
+            .iter()
+            .map(|(&row, selections)| {
+                let text = line_text(&buffer, row);
+                let split_points = selections
+                    .iter()
+                    .map(|selection| selection.start.column as usize)
+                    .collect::<Vec<_>>();
+                (text, split_points)
+            })
+            .collect::<Vec<_>>();
+        let aligned = pad_to_columns(&lines, tab_size);
+
+        let mut edits = Vec::new();
+        let mut new_selections = Vec::new();
+        let mut splits_by_row = BTreeMap::<u32, Vec<(usize, usize)>>::default();
+        for (((&row, selections), (old_text, old_columns)), (new_text, new_columns)) in
+            selections_by_row.iter().zip(&lines).zip(aligned)
+        {
+            splits_by_row.insert(
+                row,
+                old_columns
+                    .iter()
+                    .copied()
+                    .zip(new_columns.clone())
+                    .collect(),
+            );
+            for (selection, column) in selections.iter().zip(new_columns) {
+                let start = Point::new(row, column as u32);
+                let end = Point::new(
+                    row,
+                    start.column + selection.end.column - selection.start.column,
+                );
+                new_selections.push(Selection {
+                    id: selection.id,
+                    start,
+                    end,
+                    reversed: selection.reversed,
+                    goal: SelectionGoal::None,
+                });
+            }
+            if *old_text != new_text {
+                edits.push((
+                    Point::new(row, 0)..Point::new(row, buffer.line_len(MultiBufferRow(row))),
+                    new_text,
+                ));
+            }
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        // Padding is inserted before each split point, so a point moves by the padding inserted
+        // before it, which includes a split point at its column when it starts a selection.
+        let shift = |point: Point, is_start: bool| {
+            let column = point.column as usize;
+            let shifted_column = splits_by_row
+                .get(&point.row)
+                .and_then(|splits| {
+                    splits
+                        .iter()
+                        .take_while(|(old, _)| *old < column || (is_start && *old == column))
+                        .last()
+                })
+                .map_or(column, |(old, new)| column + new - old);
+            Point::new(point.row, shifted_column as u32)
+        };
+        for selection in other_selections {
+            new_selections.push(Selection {
+                id: selection.id,
+                start: shift(selection.start, true),
+                end: shift(selection.end, false),
+                reversed: selection.reversed,
+                goal: SelectionGoal::None,
+            });
+        }
+
+        self.transact(window, cx, |this, window, cx| {
+            this.buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+            this.change_selections(Default::default(), window, cx, |s| {
+                s.select(new_selections);
+            });
+            this.request_autoscroll(Autoscroll::fit(), cx);
+        });
+    }
+
+    pub fn align_on_delimiter(
+        &mut self,
+        action: &AlignOnDelimiter,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.hide_mouse_cursor(HideMouseCursorOrigin::TypingAction, cx);
+        let display_snapshot = self.display_snapshot(cx);
+        let buffer = self.buffer.read(cx).snapshot(cx);
+
+        let explicit_delimiter = match action.delimiter.as_deref().filter(|d| !d.is_empty()) {
+            Some(delimiter) if action.regex => match Regex::new(delimiter) {
+                Ok(regex) => Some(regex),
+                Err(error) => {
+                    log::error!("invalid alignment pattern {delimiter:?}: {error}");
+                    return;
+                }
+            },
+            Some(delimiter) => Regex::new(&regex::escape(delimiter)).ok(),
+            None => None,
+        };
+
+        let mut blocks: Vec<Range<u32>> = Vec::new();
+        for selection in self.selections.all::<Point>(&display_snapshot) {
+            let mut rows = selection.start.row..selection.end.row + 1;
+            if selection.end.column == 0 && selection.end.row > selection.start.row {
+                rows.end -= 1;
+            }
+            match blocks.last_mut() {
+                Some(last) if last.end >= rows.start => last.end = last.end.max(rows.end),
+                _ => blocks.push(rows),
+            }
+        }
+
+        let mut edits = Vec::new();
+        let mut aligned_blocks = Vec::new();
+        for mut rows in blocks {
+            let delimiter = match &explicit_delimiter {
+                Some(regex) => Some((regex.clone(), false)),
+                None => detect_delimiter(&buffer, rows.clone()),
+            };
+            let Some((regex, standalone_only)) = delimiter else {
+                continue;
+            };
+            let occurrences = |row: u32| {
+                let mut occurrences = delimiter_occurrences(&buffer, row, &regex, standalone_only);
+                if !action.all_occurrences {
+                    occurrences.truncate(1);
+                }
+                occurrences
+            };
+
+            // With a bare cursor, align the surrounding lines that share its
+            // indentation and contain the delimiter.
+            if rows.len() == 1 {
+                let indent = buffer.indent_size_for_line(MultiBufferRow(rows.start));
+                let belongs = |row: u32| {
+                    buffer.indent_size_for_line(MultiBufferRow(row)) == indent
+                        && !occurrences(row).is_empty()
+                };
+                while rows.start > 0 && belongs(rows.start - 1) {
+                    rows.start -= 1;
+                }
+                while rows.end <= buffer.max_point().row && belongs(rows.end) {
+                    rows.end += 1;
+                }
+            }
+
+            let lines = rows
+                .clone()
+                .map(|row| (line_text(&buffer, row), occurrences(row)))
+                .collect::<Vec<_>>();
+            let tab_size = tab_size_at(&buffer, Point::new(rows.start, 0), cx);
+            for (row, ((old_text, _), new_text)) in rows
+                .clone()
+                .zip(lines.iter().zip(align_on_delimiters(&lines, tab_size)))
+            {
+                if *old_text != new_text {
+                    edits.push((
+                        Point::new(row, 0)..Point::new(row, buffer.line_len(MultiBufferRow(row))),
+                        new_text,
+                    ));
+                }
+            }
+            aligned_blocks.push(rows);
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        self.transact(window, cx, |this, window, cx| {
+            let buffer = this.buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+                buffer.snapshot(cx)
+            });
+            let new_selections = aligned_blocks
+                .into_iter()
+                .map(|rows| {
+                    let last_row = MultiBufferRow(rows.end - 1);
+                    Point::new(rows.start, 0)..Point::new(last_row.0, buffer.line_len(last_row))
+                })
+                .collect::<Vec<_>>();
+            this.change_selections(Default::default(), window, cx, |s| {
+                s.select_ranges(new_selections);
+            });
+            this.request_autoscroll(Autoscroll::fit(), cx);
+        });
+    }
+}
+
+fn tab_size_at(buffer: &MultiBufferSnapshot, point: Point, cx: &App) -> usize {
+    buffer.language_settings_at(point, cx).tab_size.get() as usize
+}
+
+fn line_text(buffer: &MultiBufferSnapshot, row: u32) -> String {
+    buffer
+        .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(MultiBufferRow(row))))
+        .collect()
+}
+
+/// Picks the default delimiter that occurs on the most lines in `rows`,
+/// preferring earlier entries of [`DEFAULT_DELIMITERS`] on ties.
+fn detect_delimiter(buffer: &MultiBufferSnapshot, rows: Range<u32>) -> Option<(Regex, bool)> {
+    DEFAULT_DELIMITERS
+        .iter()
+        .filter_map(|delimiter| {
+            let regex = Regex::new(&regex::escape(delimiter)).ok()?;
+            let count = rows
+                .clone()
+                .filter(|&row| !delimiter_occurrences(buffer, row, &regex, true).is_empty())
+                .count();
+            (count > 0).then_some((regex, count))
+        })
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(regex, _)| (regex, true))
+}
+
+/// Returns the byte ranges within `row` that match `delimiter`, skipping
+/// matches that the language marks as being inside a string or a comment.
+fn delimiter_occurrences(
+    buffer: &MultiBufferSnapshot,
+    row: u32,
+    delimiter: &Regex,
+    standalone_only: bool,
+) -> Vec<Range<usize>> {
+    let text = line_text(buffer, row);
+    delimiter
+        .find_iter(&text)
+        .filter(|found| !found.is_empty())
+        .map(|found| found.range())
+        .filter(|range| !standalone_only || is_standalone_operator(&text, range.clone()))
+        .filter(|range| {
+            !buffer
+                .language_scope_at(Point::new(row, range.start as u32))
+                .is_some_and(|scope| matches!(scope.override_name(), Some("string" | "comment")))
+        })
+        .collect()
+}
+
+/// Whether the match is an operator of its own rather than part of a longer
+/// one, e.g. the `=` in `==`, `<=` or `=>`, or the `:` in `::`.
+fn is_standalone_operator(text: &str, range: Range<usize>) -> bool {
+    const OPERATOR_CHARS: &[char] = &['=', '!', '<', '>', ':'];
+    let before = text[..range.start].chars().next_back();
+    let after = text[range.end..].chars().next();
+    !before.is_some_and(|c| OPERATOR_CHARS.contains(&c))
+        && !after.is_some_and(|c| OPERATOR_CHARS.contains(&c))
+}
+
+/// Inserts spaces before each split point so that the `n`th split point of
+/// every line lands on the same column. Returns the padded lines along with
+/// the new byte offsets of their split points.
+fn pad_to_columns(lines: &[(String, Vec<usize>)], tab_size: usize) -> Vec<(String, Vec<usize>)> {
+    let column_count = lines
+        .iter()
+        .map(|(_, splits)| splits.len())
+        .max()
+        .unwrap_or(0);
+    let mut output = vec![(String::new(), Vec::new()); lines.len()];
+    let mut consumed = vec![0; lines.len()];
+    for column in 0..column_count {
+        for (ix, (text, splits)) in lines.iter().enumerate() {
+            if let Some(&split) = splits.get(column) {
+                output[ix].0.push_str(&text[consumed[ix]..split]);
+                consumed[ix] = split;
+            }
+        }
+        let width = output
+            .iter()
+            .zip(lines)
+            .filter(|(_, (_, splits))| column < splits.len())
+            .map(|((line, _), _)| display_width(line, tab_size))
+            .max()
+            .unwrap_or(0);
+        for (ix, (_, splits)) in lines.iter().enumerate() {
+            if column < splits.len() {
+                pad(&mut output[ix].0, width, tab_size);
+                let offset = output[ix].0.len();
+                output[ix].1.push(offset);
+            }
+        }
+    }
+    for (ix, (text, _)) in lines.iter().enumerate() {
+        output[ix].0.push_str(&text[consumed[ix]..]);
+    }
+    output
+}
+
+/// Aligns the delimiter occurrences of every line column by column.
+///
+/// When the delimiter is attached to the preceding text (as in `key: value`),
+/// the text following it is aligned instead of the delimiter itself.
+fn align_on_delimiters(lines: &[(String, Vec<Range<usize>>)], tab_size: usize) -> Vec<String> {
+    let column_count = lines
+        .iter()
+        .map(|(_, ranges)| ranges.len())
+        .max()
+        .unwrap_or(0);
+    let mut output = vec![String::new(); lines.len()];
+    let mut consumed = vec![0; lines.len()];
+    for column in 0..column_count {
+        let mut attached = None;
+        for (ix, (text, ranges)) in lines.iter().enumerate() {
+            let Some(range) = ranges.get(column) else {
+                continue;
+            };
+            let segment = &text[consumed[ix]..range.start];
+            let attached = *attached.get_or_insert_with(|| {
+                !segment.trim().is_empty() && !segment.ends_with(char::is_whitespace)
+            });
+            if segment.trim().is_empty() {
+                output[ix].push_str(segment);
+            } else {
+                output[ix].push_str(segment.trim_end());
+            }
+            if attached {
+                output[ix].push_str(&text[range.clone()]);
+            }
+        }
+        let Some(attached) = attached else {
+            break;
+        };
+        let width = output
+            .iter()
+            .zip(lines)
+            .filter(|(_, (_, ranges))| column < ranges.len())
+            .map(|(line, _)| display_width(line, tab_size))
+            .max()
+            .unwrap_or(0);
+        for (ix, (text, ranges)) in lines.iter().enumerate() {
+            let Some(range) = ranges.get(column) else {
+                continue;
+            };
+            pad(&mut output[ix], width, tab_size);
+            if !attached {
+                if !output[ix].trim().is_empty() {
+                    output[ix].push(' ');
+                }
+                output[ix].push_str(&text[range.clone()]);
+            }
+            let rest = &text[range.end..];
+            consumed[ix] = text.len() - rest.trim_start().len();
+            output[ix].push(' ');
+        }
+    }
+    for (ix, (text, _)) in lines.iter().enumerate() {
+        if consumed[ix] == text.len() {
+            let trimmed_len = output[ix].trim_end().len();
+            output[ix].truncate(trimmed_len);
+        } else {
+            output[ix].push_str(&text[consumed[ix]..]);
+        }
+    }
+    output
+}
+
+/// Pads `text` with spaces until it is displayed `width` columns wide.
+fn pad(text: &mut String, width: usize, tab_size: usize) {
+    let len = display_width(text, tab_size);
+    text.extend(std::iter::repeat_n(' ', width.saturating_sub(len)));
+}
+
+/// The number of columns `text` takes up when displayed from the start of a line, with tabs
+/// expanded to the next tab stop and wide characters taking up two columns.
+fn display_width(text: &str, tab_size: usize) -> usize {
+    text.chars().fold(0, |width, c| match c {
+        '\t' => width + tab_size - width % tab_size,
+        c => width + c.width().unwrap_or(0),
+    })
+}
@@ -165,6 +165,7 @@ pub enum HighlightKey {
     Rename,
     SearchWithinRange,
     SelectedTextHighlight,
+    SpellCheck,
     SyntaxTreeView(usize),
     VimExchange,
 }
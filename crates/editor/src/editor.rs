@@ -12,6 +12,7 @@
 //!
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides its behavior.
 pub mod actions;
+mod align;
 pub mod blink_manager;
 mod bracket_colorization;
 mod clangd_ext;
@@ -19,6 +20,7 @@ pub mod code_context_menus;
 pub mod display_map;
 mod editor_settings;
 mod element;
+mod filter_command;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
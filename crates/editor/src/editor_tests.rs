@@ -5985,6 +5985,256 @@ async fn test_rotate_selections(cx: &mut TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_align(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    // Pad cursors to the rightmost cursor column
+    cx.set_state(indoc! {"
+        aˇ = 1
+        bbbˇ = 2
+        ccˇ = 3
+    "});
+    cx.update_editor(|e, window, cx| e.align_cursors(&AlignCursors, window, cx));
+    cx.assert_editor_state(indoc! {"
+        a  ˇ = 1
+        bbbˇ = 2
+        cc ˇ = 3
+    "});
+
+    // Keep selections spanning several rows, moving them along with the padding
+    cx.set_state(indoc! {"
+        «zero
+        oneˇ» aˇ = 1
+        bbbˇ = 2
+        ccˇ = «3
+        dddd = 4ˇ»
+    "});
+    cx.update_editor(|e, window, cx| e.align_cursors(&AlignCursors, window, cx));
+    cx.assert_editor_state(indoc! {"
+        «zero
+        oneˇ» aˇ = 1
+        bbb  ˇ = 2
+        cc   ˇ = «3
+        dddd = 4ˇ»
+    "});
+
+    // Align the selected lines on the detected delimiter
+    cx.set_state(indoc! {"
+        «let a = 1;
+        let bbb = 2;
+        let cc = 3;ˇ»
+    "});
+    cx.update_editor(|e, window, cx| {
+        e.align_on_delimiter(&AlignOnDelimiter::default(), window, cx)
+    });
+    cx.assert_editor_state(indoc! {"
+        «let a   = 1;
+        let bbb = 2;
+        let cc  = 3;ˇ»
+    "});
+
+    // Align the values following an attached delimiter around a bare cursor
+    cx.set_state(indoc! {"
+        name: x
+        iˇd: 1
+        description: y
+
+        other: z
+    "});
+    cx.update_editor(|e, window, cx| {
+        e.align_on_delimiter(&AlignOnDelimiter::default(), window, cx)
+    });
+    cx.assert_editor_state(indoc! {"
+        «name:        x
+        id:          1
+        description: yˇ»
+
+        other: z
+    "});
+
+    // Align every occurrence of an explicit delimiter
+    cx.set_state(indoc! {"
+        «1,22,3
+        333,4,55ˇ»
+    "});
+    cx.update_editor(|e, window, cx| {
+        e.align_on_delimiter(
+            &AlignOnDelimiter {
+                delimiter: Some(",".into()),
+                all_occurrences: true,
+                ..Default::default()
+            },
+            window,
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        «1,   22, 3
+        333, 4,  55ˇ»
+    "});
+
+    // Pad to the displayed column, expanding tabs and counting wide characters twice
+    cx.set_state("\taˇ = 1\nbbbbbbˇ = 2\n日本ˇ = 3\n");
+    cx.update_editor(|e, window, cx| e.align_cursors(&AlignCursors, window, cx));
+    cx.assert_editor_state("\ta ˇ = 1\nbbbbbbˇ = 2\n日本  ˇ = 3\n");
+
+    // Skip delimiters inside strings and comments
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(rust_lang()), cx));
+    cx.set_state(indoc! {r#"
+        «let a = "k=v";
+        let bbb = 2; // c = d
+        let cc = 3;ˇ»
+    "#});
+    cx.run_until_parked();
+    cx.update_editor(|e, window, cx| {
+        e.align_on_delimiter(
+            &AlignOnDelimiter {
+                delimiter: Some("=".into()),
+                all_occurrences: true,
+                ..Default::default()
+            },
+            window,
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {r#"
+        «let a   = "k=v";
+        let bbb = 2; // c = d
+        let cc  = 3;ˇ»
+    "#});
+}
+
+#[cfg(unix)]
+async fn filter_command_test_editor(
+    text: &str,
+    cx: &mut TestAppContext,
+) -> (gpui::WindowHandle<Workspace>, Entity<Editor>) {
+    init_test(cx, |_| {});
+    // The filter command runs a real process.
+    cx.executor().allow_parking();
+
+    let fs = FakeFs::new(cx.executor());
+    let project = Project::test(fs, [], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(text, cx));
+    let editor = workspace
+        .update(cx, |workspace, window, cx| {
+            let editor = cx.new(|cx| {
+                Editor::new(
+                    EditorMode::full(),
+                    buffer,
+                    Some(project.clone()),
+                    window,
+                    cx,
+                )
+            });
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, window, cx);
+            editor
+        })
+        .unwrap();
+    (workspace, editor)
+}
+
+#[cfg(unix)]
+#[gpui::test]
+async fn test_filter_selections_through_command(cx: &mut TestAppContext) {
+    let (workspace, editor) = filter_command_test_editor("one\ntwo\nthree", cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+
+    // Each selection is piped through the command separately, in a single transaction.
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([
+                MultiBufferOffset(0)..MultiBufferOffset(3),
+                MultiBufferOffset(8)..MultiBufferOffset(13),
+            ])
+        });
+    });
+    editor
+        .update_in(cx, |editor, window, cx| {
+            editor.run_filter_command("tr a-z A-Z".into(), FilterCommandMode::Replace, window, cx)
+        })
+        .await;
+    editor.update(cx, |editor, cx| {
+        assert_eq!(editor.text(cx), "ONE\ntwo\nTHREE");
+    });
+    editor.update_in(cx, |editor, window, cx| {
+        editor.undo(&Undo, window, cx);
+        assert_eq!(editor.text(cx), "one\ntwo\nthree");
+    });
+
+    // In insert mode, the output is inserted at each cursor and nothing is replaced.
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([
+                MultiBufferOffset(0)..MultiBufferOffset(3),
+                MultiBufferOffset(7)..MultiBufferOffset(7),
+            ])
+        });
+    });
+    editor
+        .update_in(cx, |editor, window, cx| {
+            editor.run_filter_command("printf x".into(), FilterCommandMode::Insert, window, cx)
+        })
+        .await;
+    editor.update(cx, |editor, cx| {
+        assert_eq!(editor.text(cx), "onex\ntwox\nthree");
+    });
+}
+
+#[cfg(unix)]
+#[gpui::test]
+async fn test_filter_command_with_large_input(cx: &mut TestAppContext) {
+    // Larger than a pipe's buffer, so that `cat` blocks on its output while reading its input.
+    let text = "0123456789abcdef\n".repeat(16 * 1024);
+    let (workspace, editor) = filter_command_test_editor(&text, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+
+    // With no selection, the whole buffer is filtered.
+    editor
+        .update_in(cx, |editor, window, cx| {
+            editor.run_filter_command(
+                "cat | tr a-f A-F".into(),
+                FilterCommandMode::Replace,
+                window,
+                cx,
+            )
+        })
+        .await;
+    editor.update(cx, |editor, cx| {
+        assert_eq!(editor.text(cx), text.to_uppercase());
+    });
+}
+
+#[cfg(unix)]
+#[gpui::test]
+async fn test_filter_command_failure(cx: &mut TestAppContext) {
+    let (workspace, editor) = filter_command_test_editor("one\ntwo", cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+
+    editor
+        .update_in(cx, |editor, window, cx| {
+            editor.run_filter_command(
+                "echo oops >&2; exit 3".into(),
+                FilterCommandMode::Replace,
+                window,
+                cx,
+            )
+        })
+        .await;
+    editor.update(cx, |editor, cx| {
+        assert_eq!(editor.text(cx), "one\ntwo");
+    });
+    workspace
+        .update(cx, |workspace, _, _| {
+            assert_eq!(workspace.notification_ids().len(), 1);
+        })
+        .unwrap();
+}
+
 #[gpui::test]
 fn test_move_line_up_down(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -32603,3 +32853,34 @@ comment */ˇ»;"#},
         assert_text_with_selections(editor, indoc! {r#"let arr = [«1, 2, 3]ˇ»;"#}, cx);
     });
 }
+
+#[gpui::test]
+async fn test_accessibility_text_and_selection(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state("one\ntw«oˇ» three\n");
+    cx.run_until_parked();
+    assert!(cx.accessibility_tree().is_none());
+
+    cx.activate_accessibility();
+    let tree = cx.accessibility_tree().unwrap();
+    let node = tree
+        .find(|node| node.properties.role == gpui::Role::MultilineTextInput)
+        .unwrap();
+    assert_eq!(tree.focus().id, node.id);
+    let text = node.properties.text.clone().unwrap();
+    assert_eq!(text.content.as_ref(), "one\ntwo three\n");
+    assert_eq!(text.selection, 6..7);
+    assert!(!text.reversed);
+
+    cx.set_state("one\n«ˇtwo» three\n");
+    cx.run_until_parked();
+    let tree = cx.accessibility_tree().unwrap();
+    let text = tree
+        .find(|node| node.properties.role == gpui::Role::MultilineTextInput)
+        .and_then(|node| node.properties.text.clone())
+        .unwrap();
+    assert_eq!(text.selection, 4..7);
+    assert!(text.reversed);
+    assert_eq!(text.cursor(), 4);
+}
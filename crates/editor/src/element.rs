@@ -39,13 +39,14 @@ use feature_flags::{DiffReviewFeatureFlag, FeatureFlagAppExt as _};
 use file_icons::FileIcons;
 use git::{Oid, blame::BlameEntry, commit::ParsedCommitMessage, status::FileStatus};
 use gpui::{
-    Action, Along, AnyElement, App, AppContext, AvailableSpace, Axis as ScrollbarAxis, BorderStyle,
-    Bounds, ClickEvent, ClipboardItem, ContentMask, Context, Corner, Corners, CursorStyle,
-    DispatchPhase, Edges, Element, ElementInputHandler, Entity, Focusable as _, FontId, FontWeight,
-    GlobalElementId, Hitbox, HitboxBehavior, Hsla, InteractiveElement, IntoElement, IsZero,
-    KeybindingKeystroke, Length, Modifiers, ModifiersChangedEvent, MouseButton, MouseClickEvent,
-    MouseDownEvent, MouseMoveEvent, MousePressureEvent, MouseUpEvent, PaintQuad, ParentElement,
-    Pixels, PressureStage, ScrollDelta, ScrollHandle, ScrollWheelEvent, ShapedLine, SharedString,
+    AccessibilityNodeId, AccessibilityProperties, AccessibleText, Action, Along, AnyElement, App,
+    AppContext, AvailableSpace, Axis as ScrollbarAxis, BorderStyle, Bounds, ClickEvent,
+    ClipboardItem, ContentMask, Context, Corner, Corners, CursorStyle, DispatchPhase, Edges,
+    Element, ElementInputHandler, Entity, Focusable as _, FontId, FontWeight, GlobalElementId,
+    Hitbox, HitboxBehavior, Hsla, InteractiveElement, IntoElement, IsZero, KeybindingKeystroke,
+    Length, Modifiers, ModifiersChangedEvent, MouseButton, MouseClickEvent, MouseDownEvent,
+    MouseMoveEvent, MousePressureEvent, MouseUpEvent, PaintQuad, ParentElement, Pixels,
+    PressureStage, Role, ScrollDelta, ScrollHandle, ScrollWheelEvent, ShapedLine, SharedString,
     Size, StatefulInteractiveElement, Style, Styled, StyledText, TextAlign, TextRun,
     TextStyleRefinement, WeakEntity, Window, anchored, checkerboard, deferred, div, fill,
     linear_color_stop, linear_gradient, outline, point, px, quad, relative, size, solid_background,
@@ -56,7 +57,7 @@ use language::{IndentGuideSettings, language_settings::ShowWhitespaceSetting};
 use markdown::Markdown;
 use multi_buffer::{
     Anchor, ExcerptId, ExcerptInfo, ExpandExcerptDirection, ExpandInfo, MultiBufferPoint,
-    MultiBufferRow, RowInfo,
+    MultiBufferRow, RowInfo, ToOffset as _,
 };
 
 use edit_prediction_types::EditPredictionGranularity;
@@ -266,6 +267,9 @@ impl EditorElement {
         register_action(editor, window, Editor::sort_lines_case_insensitive);
         register_action(editor, window, Editor::reverse_lines);
         register_action(editor, window, Editor::shuffle_lines);
+        register_action(editor, window, Editor::filter_selections_through_command);
+        register_action(editor, window, Editor::align_cursors);
+        register_action(editor, window, Editor::align_on_delimiter);
         register_action(editor, window, Editor::rotate_selections_forward);
         register_action(editor, window, Editor::rotate_selections_backward);
         register_action(editor, window, Editor::convert_indentation_to_spaces);
@@ -653,6 +657,52 @@ impl EditorElement {
         }
     }
 
+    /// Describes the editor to assistive technologies, exposing the visible lines and the
+    /// newest selection so that screen readers can follow the cursor.
+    fn accessibility_properties(&self, layout: &EditorLayout, cx: &App) -> AccessibilityProperties {
+        let snapshot = &layout.position_map.snapshot;
+        let buffer = snapshot.buffer_snapshot();
+        let max_row = snapshot.max_point().row();
+        let visible_start = snapshot.display_point_to_point(
+            DisplayPoint::new(layout.visible_display_row_range.start.min(max_row), 0),
+            Bias::Left,
+        );
+        let visible_end = snapshot.display_point_to_point(
+            DisplayPoint::new(layout.visible_display_row_range.end.min(max_row), 0),
+            Bias::Right,
+        );
+        let visible_range = buffer.point_to_offset(Point::new(visible_start.row, 0))
+            ..buffer.point_to_offset(Point::new(
+                visible_end.row,
+                buffer.line_len(MultiBufferRow(visible_end.row)),
+            ));
+
+        let selection = self.editor.read(cx).selections.newest_anchor();
+        let to_visible_offset = |anchor: &Anchor| {
+            let offset = anchor.to_offset(buffer);
+            offset.clamp(visible_range.start, visible_range.end).0 - visible_range.start.0
+        };
+        let text = AccessibleText {
+            content: buffer
+                .text_for_range(visible_range.clone())
+                .collect::<String>()
+                .into(),
+            selection: to_visible_offset(&selection.start)..to_visible_offset(&selection.end),
+            reversed: selection.reversed,
+        };
+
+        let role = if layout.mode.is_single_line() {
+            Role::TextInput
+        } else {
+            Role::MultilineTextInput
+        };
+        let mut properties = AccessibilityProperties::new(role).text(text);
+        if let Some(placeholder) = snapshot.placeholder_text() {
+            properties = properties.description(placeholder);
+        }
+        properties
+    }
+
     fn register_key_listeners(&self, window: &mut Window, _: &mut App, layout: &EditorLayout) {
         let position_map = layout.position_map.clone();
         window.on_key_event({
@@ -10831,7 +10881,7 @@ impl Element for EditorElement {
 
     fn paint(
         &mut self,
-        _: Option<&GlobalElementId>,
+        global_id: Option<&GlobalElementId>,
         _inspector_id: Option<&gpui::InspectorElementId>,
         bounds: Bounds<gpui::Pixels>,
         _: &mut Self::RequestLayoutState,
@@ -10860,6 +10910,16 @@ impl Element for EditorElement {
             line_height: Some(self.style.text.line_height),
             ..Default::default()
         };
+        let exposed = !layout.mode.is_minimap() && window.is_accessibility_active();
+        if exposed {
+            window.push_accessibility_node(
+                global_id.map(AccessibilityNodeId::from),
+                self.accessibility_properties(layout, cx),
+                bounds,
+                Some(self.editor.focus_handle(cx)),
+            );
+        }
+
         let rem_size = self.rem_size(cx);
         window.with_rem_size(rem_size, |window| {
             window.with_text_style(Some(text_style), |window| {
@@ -10899,7 +10959,11 @@ impl Element for EditorElement {
                     self.paint_mouse_context_menu(layout, window, cx);
                 });
             })
-        })
+        });
+
+        if exposed {
+            window.pop_accessibility_node();
+        }
     }
 }
 
@@ -0,0 +1,348 @@
+use std::{ops::Range, process::Stdio};
+
+use futures::{AsyncWriteExt as _, future::join_all};
+use gpui::{
+    AsyncWindowContext, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task,
+    WeakEntity,
+};
+use multi_buffer::Anchor;
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::{
+    ModalView, Workspace,
+    notifications::{NotificationId, simple_message_notification::MessageNotification},
+};
+
+use crate::{Editor, FilterCommandMode, FilterSelectionsThroughCommand, MultiBufferOffset};
+
+/// The outcome of running the filter command over a single selection.
+struct FilterOutput {
+    range: Range<Anchor>,
+    stdout: String,
+    stderr: String,
+    exit_code: Option<i32>,
+    success: bool,
+}
+
+impl Editor {
+    pub fn filter_selections_through_command(
+        &mut self,
+        action: &FilterSelectionsThroughCommand,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only(cx) {
+            return;
+        }
+        let mode = action.mode;
+        match action
+            .command
+            .as_deref()
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+        {
+            Some(command) => {
+                self.run_filter_command(command.to_string(), mode, window, cx)
+                    .detach();
+            }
+            None => {
+                let Some(workspace) = self.workspace() else {
+                    return;
+                };
+                let editor = cx.entity().downgrade();
+                workspace.update(cx, |workspace, cx| {
+                    workspace.toggle_modal(window, cx, move |window, cx| {
+                        FilterCommandPrompt::new(editor, mode, window, cx)
+                    });
+                });
+            }
+        }
+    }
+
+    /// Runs `command` through the project's shell once per selection.
+    ///
+    /// In [`FilterCommandMode::Replace`] the selected text (or the whole buffer, when every
+    /// selection is empty) is piped into the command and replaced by its output. In
+    /// [`FilterCommandMode::Insert`] the command receives no input and its output is inserted
+    /// at each cursor. All edits are applied as a single transaction.
+    pub fn run_filter_command(
+        &mut self,
+        command: String,
+        mode: FilterCommandMode,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<()> {
+        let Some(project) = self.project().cloned() else {
+            return Task::ready(());
+        };
+        let workspace = self.workspace();
+
+        let display_snapshot = self.display_snapshot(cx);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<MultiBufferOffset>(&display_snapshot);
+        let ranges: Vec<Range<Anchor>> = match mode {
+            FilterCommandMode::Replace if selections.iter().all(|s| s.is_empty()) => {
+                vec![
+                    snapshot.anchor_before(MultiBufferOffset(0))
+                        ..snapshot.anchor_after(snapshot.len()),
+                ]
+            }
+            FilterCommandMode::Replace => selections
+                .iter()
+                .filter(|selection| !selection.is_empty())
+                .map(|selection| {
+                    snapshot.anchor_before(selection.start)..snapshot.anchor_after(selection.end)
+                })
+                .collect(),
+            FilterCommandMode::Insert => selections
+                .iter()
+                .map(|selection| {
+                    let head = snapshot.anchor_after(selection.end);
+                    head..head
+                })
+                .collect(),
+        };
+
+        let process_task =
+            project.update(cx, |project, cx| project.exec_in_shell(command.clone(), cx));
+
+        cx.spawn_in(window, async move |editor, cx| {
+            let mut process = match process_task.await {
+                Ok(process) => process,
+                Err(error) => {
+                    report_filter_failure(&workspace, &command, error.to_string(), cx);
+                    return;
+                }
+            };
+            process.stdout(Stdio::piped());
+            process.stderr(Stdio::piped());
+
+            let mut runs = Vec::with_capacity(ranges.len());
+            for range in ranges {
+                let input = match mode {
+                    FilterCommandMode::Replace => {
+                        Some(snapshot.text_for_range(range.clone()).collect::<String>())
+                    }
+                    FilterCommandMode::Insert => None,
+                };
+                process.stdin(if input.is_some() {
+                    Stdio::piped()
+                } else {
+                    Stdio::null()
+                });
+                let mut child = match process.spawn() {
+                    Ok(child) => child,
+                    Err(error) => {
+                        report_filter_failure(&workspace, &command, error.to_string(), cx);
+                        return;
+                    }
+                };
+                let stdin = child.stdin.take();
+                let input_ends_with_newline =
+                    input.as_ref().is_some_and(|input| input.ends_with('\n'));
+                runs.push(cx.background_spawn(async move {
+                    // The input is written while the output is read, as a streaming command
+                    // blocks on a full stdout pipe until it is drained.
+                    let write_input = async move {
+                        if let Some((mut stdin, input)) = stdin.zip(input) {
+                            stdin.write_all(input.as_bytes()).await.log_err();
+                            stdin.flush().await.log_err();
+                        }
+                    };
+                    let ((), output) = futures::join!(write_input, child.output());
+                    let output = output?;
+                    let mut stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+                    // Most commands terminate their output with a newline, which would otherwise
+                    // grow every filtered selection by one line.
+                    if mode == FilterCommandMode::Replace
+                        && !input_ends_with_newline
+                        && stdout.ends_with('\n')
+                    {
+                        stdout.pop();
+                        if stdout.ends_with('\r') {
+                            stdout.pop();
+                        }
+                    }
+                    anyhow::Ok(FilterOutput {
+                        range,
+                        stdout,
+                        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                        exit_code: output.status.code(),
+                        success: output.status.success(),
+                    })
+                }));
+            }
+
+            let mut outputs = Vec::new();
+            for output in join_all(runs).await {
+                match output {
+                    Ok(output) => outputs.push(output),
+                    Err(error) => {
+                        report_filter_failure(&workspace, &command, error.to_string(), cx);
+                        return;
+                    }
+                }
+            }
+
+            if let Some(failed) = outputs.iter().find(|output| !output.success) {
+                let status = match failed.exit_code {
+                    Some(code) => format!("exited with status {code}"),
+                    None => "was terminated".to_string(),
+                };
+                let details = if failed.stderr.trim().is_empty() {
+                    format!("`{command}` {status}")
+                } else {
+                    format!("`{command}` {status}:\n{}", failed.stderr.trim_end())
+                };
+                report_filter_failure(&workspace, &command, details, cx);
+                return;
+            }
+
+            let stderr = outputs
+                .iter()
+                .map(|output| output.stderr.trim_end())
+                .filter(|stderr| !stderr.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            editor
+                .update_in(cx, |editor, window, cx| {
+                    let edits = outputs
+                        .into_iter()
+                        .map(|output| (output.range, output.stdout))
+                        .collect::<Vec<_>>();
+                    editor.transact(window, cx, |editor, _, cx| {
+                        editor.buffer.update(cx, |buffer, cx| {
+                            buffer.edit(edits, None, cx);
+                        });
+                    });
+                })
+                .ok();
+
+            if !stderr.is_empty() {
+                report_filter_failure(&workspace, &command, stderr, cx);
+            }
+        })
+    }
+}
+
+fn report_filter_failure(
+    workspace: &Option<Entity<Workspace>>,
+    command: &str,
+    message: String,
+    cx: &mut AsyncWindowContext,
+) {
+    log::warn!("filter command `{command}` reported: {message}");
+    let Some(workspace) = workspace else {
+        return;
+    };
+    workspace
+        .update_in(cx, |workspace, _, cx| {
+            struct FilterCommandNotification;
+
+            workspace.show_notification(
+                NotificationId::unique::<FilterCommandNotification>(),
+                cx,
+                |cx| {
+                    cx.new(|cx| {
+                        MessageNotification::new(message, cx).with_title("Filter Through Command")
+                    })
+                },
+            );
+        })
+        .ok();
+}
+
+/// A prompt that asks for the shell command used by [`FilterSelectionsThroughCommand`].
+pub struct FilterCommandPrompt {
+    command_editor: Entity<Editor>,
+    target: WeakEntity<Editor>,
+    mode: FilterCommandMode,
+}
+
+impl ModalView for FilterCommandPrompt {}
+
+impl EventEmitter<DismissEvent> for FilterCommandPrompt {}
+
+impl Focusable for FilterCommandPrompt {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.command_editor.focus_handle(cx)
+    }
+}
+
+impl FilterCommandPrompt {
+    fn new(
+        target: WeakEntity<Editor>,
+        mode: FilterCommandMode,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let command_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("sort | uniq", window, cx);
+            editor
+        });
+        cx.subscribe_in(&command_editor, window, |_, _, event, _, cx| {
+            if let crate::EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        })
+        .detach();
+        Self {
+            command_editor,
+            target,
+            mode,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let command = self.command_editor.read(cx).text(cx);
+        let command = command.trim();
+        if command.is_empty() {
+            return;
+        }
+        let mode = self.mode;
+        self.target
+            .update(cx, |editor, cx| {
+                editor.focus_handle(cx).focus(window, cx);
+                editor
+                    .run_filter_command(command.to_string(), mode, window, cx)
+                    .detach();
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for FilterCommandPrompt {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let help_text = match self.mode {
+            FilterCommandMode::Replace => "Replace each selection with the command's output",
+            FilterCommandMode::Insert => "Insert the command's output at each cursor",
+        };
+        v_flex()
+            .w(rems(34.))
+            .elevation_2(cx)
+            .key_context("FilterCommandPrompt")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.command_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .child(Label::new(help_text).color(Color::Muted)),
+            )
+    }
+}
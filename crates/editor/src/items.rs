@@ -1,7 +1,7 @@
 use crate::{
-    Anchor, Autoscroll, BufferSerialization, Capability, Editor, EditorEvent, EditorSettings,
-    ExcerptId, ExcerptRange, FormatTarget, MultiBuffer, MultiBufferSnapshot, NavigationData,
-    ReportEditorEvent, SelectionEffects, ToPoint as _,
+    Anchor, Autoscroll, BufferSerialization, Capability, CurrentLineHighlight, Editor, EditorEvent,
+    EditorSettings, ExcerptId, ExcerptRange, FormatTarget, MultiBuffer, MultiBufferSnapshot,
+    NavigationData, ReportEditorEvent, SelectionEffects, ToPoint as _,
     display_map::HighlightKey,
     editor_settings::SeedQuerySetting,
     persistence::{DB, SerializedEditor},
@@ -14,15 +14,16 @@ use fs::MTime;
 use futures::future::try_join_all;
 use git::status::GitSummary;
 use gpui::{
-    AnyElement, App, AsyncWindowContext, Context, Entity, EntityId, EventEmitter, IntoElement,
-    ParentElement, Pixels, SharedString, Styled, Task, WeakEntity, Window, point,
+    AnyElement, AnyView, App, AsyncWindowContext, Context, Entity, EntityId, EventEmitter,
+    IntoElement, ParentElement, Pixels, SharedString, Size, Styled, Task, WeakEntity, Window,
+    point, size,
 };
 use language::{
     Bias, Buffer, BufferRow, CharKind, CharScopeContext, LocalFile, Point, SelectionGoal,
-    proto::serialize_anchor as serialize_text_anchor,
+    language_settings::SoftWrap, proto::serialize_anchor as serialize_text_anchor,
 };
 use lsp::DiagnosticSeverity;
-use multi_buffer::MultiBufferOffset;
+use multi_buffer::{MultiBufferOffset, MultiBufferRow};
 use project::{
     File, Project, ProjectItem as _, ProjectPath, lsp_store::FormatTrigger,
     project_settings::ProjectSettings, search::SearchQuery,
@@ -39,6 +40,7 @@ use std::{
     sync::Arc,
 };
 use text::{BufferId, BufferSnapshot, Selection};
+use theme::ThemeSettings;
 use ui::{IconDecorationKind, prelude::*};
 use util::{ResultExt, TryFutureExt, paths::PathExt};
 use workspace::{
@@ -968,6 +970,61 @@ impl Item for Editor {
         self.pixel_position_of_newest_cursor
     }
 
+    fn image_export_content(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<(AnyView, Size<Pixels>)> {
+        let display_snapshot = self.display_snapshot(cx);
+        let selection = self.selections.newest::<Point>(&display_snapshot);
+        if selection.is_empty() {
+            return None;
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let start = Point::new(selection.start.row, 0);
+        let mut end_row = selection.end.row;
+        if selection.end.column == 0 && end_row > start.row {
+            end_row -= 1;
+        }
+        let end = Point::new(end_row, snapshot.line_len(MultiBufferRow(end_row)));
+        let text = snapshot.text_for_range(start..end).collect::<String>();
+        let language = snapshot.language_at(start).cloned();
+
+        let settings = ThemeSettings::get_global(cx);
+        let font_size = settings.buffer_font_size(cx);
+        let font_id = window.text_system().resolve_font(&settings.buffer_font);
+        let em_advance = window
+            .text_system()
+            .em_advance(font_id, font_size)
+            .log_err()?;
+        let longest_line = text.lines().map(|line| line.chars().count()).max();
+        let line_count = text.lines().count().max(1);
+        let content_size = size(
+            em_advance * (longest_line.unwrap_or(0) + 2) as f32,
+            font_size * settings.line_height() * (line_count + 1) as f32,
+        );
+
+        let buffer = cx.new(|cx| {
+            let mut buffer = Buffer::local(text, cx);
+            buffer.set_language(language, cx);
+            buffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(buffer, None, window, cx);
+            editor.set_read_only(true);
+            editor.set_show_gutter(false, cx);
+            editor.disable_scrollbars_and_minimap(window, cx);
+            editor.set_show_indent_guides(false, cx);
+            editor.set_show_wrap_guides(false, cx);
+            editor.set_soft_wrap_mode(SoftWrap::None, cx);
+            editor.set_current_line_highlight(Some(CurrentLineHighlight::None));
+            editor.set_show_cursor_when_unfocused(false, cx);
+            editor
+        });
+        Some((editor.into(), content_size))
+    }
+
     fn breadcrumb_location(&self, cx: &App) -> ToolbarItemLocation {
         if self.show_breadcrumbs && self.buffer().read(cx).is_singleton() {
             ToolbarItemLocation::PrimaryLeft
@@ -1,4 +1,4 @@
-use crate::{FakeFs, FakeFsEntry, Fs};
+use crate::{FakeFs, FakeFsEntry, Fs, RemoveOptions};
 use anyhow::{Context as _, Result, bail};
 use collections::{HashMap, HashSet};
 use futures::future::{self, BoxFuture, join_all};
@@ -407,11 +407,158 @@ impl GitRepository for FakeGitRepository {
 
     fn create_worktree(
         &self,
-        _: String,
-        _: PathBuf,
-        _: Option<String>,
+        name: String,
+        directory: PathBuf,
+        from_commit: Option<String>,
     ) -> BoxFuture<'_, Result<()>> {
-        unimplemented!()
+        let path = directory.join(&name);
+        let git_dir = self.common_dir_path.join("worktrees").join(&name);
+        async move {
+            if self.fs.metadata(&path).await?.is_some() {
+                bail!("'{}' already exists", path.display());
+            }
+            let head_contents = self
+                .with_state_async(true, {
+                    let name = name.clone();
+                    move |state| {
+                        if from_commit.is_some() && !state.branches.insert(name.clone()) {
+                            bail!("a branch named '{name}' already exists");
+                        }
+                        Ok(state.head_contents.clone())
+                    }
+                })
+                .await?;
+
+            // Lay the worktree out like git does, with a gitfile pointing to its git directory
+            // in the common one. The fake reads `commondir` as an absolute path.
+            self.fs
+                .write(
+                    &git_dir.join("commondir"),
+                    self.common_dir_path.to_string_lossy().as_bytes(),
+                )
+                .await?;
+            self.fs
+                .write(
+                    &git_dir.join("gitdir"),
+                    path.join(".git").to_string_lossy().as_bytes(),
+                )
+                .await?;
+            self.fs.create_dir(&path).await?;
+            for (repo_path, content) in &head_contents {
+                self.fs
+                    .write(&path.join(repo_path.as_std_path()), content.as_bytes())
+                    .await?;
+            }
+            self.fs
+                .write(
+                    &path.join(".git"),
+                    format!("gitdir: {}", git_dir.display()).as_bytes(),
+                )
+                .await?;
+            self.fs.with_git_state(&path.join(".git"), true, |state| {
+                state.index_contents = head_contents.clone();
+                state.head_contents = head_contents;
+                state.current_branch_name = Some(name);
+            })
+        }
+        .boxed()
+    }
+
+    fn remove_worktree(&self, path: PathBuf) -> BoxFuture<'_, Result<()>> {
+        let worktrees_dir = self.common_dir_path.join("worktrees");
+        async move {
+            let git_dir =
+                self.fs
+                    .with_git_state_and_paths(&path.join(".git"), false, |_, git_dir, _| {
+                        git_dir.to_path_buf()
+                    })?;
+            anyhow::ensure!(
+                git_dir.starts_with(&worktrees_dir),
+                "'{}' is not a working tree",
+                path.display()
+            );
+            let options = RemoveOptions {
+                recursive: true,
+                ignore_if_not_exists: false,
+            };
+            self.fs.remove_dir(&path, options).await?;
+            self.fs.remove_dir(&git_dir, options).await
+        }
+        .boxed()
+    }
+
+    fn squash_merge(&self, branch: String) -> BoxFuture<'_, Result<()>> {
+        let worktrees_dir = self.common_dir_path.join("worktrees");
+        let work_directory = self.dot_git_path.parent().unwrap().to_path_buf();
+        async move {
+            // The fake only knows the commits of branches that are checked out in a worktree.
+            let mut branch_contents = None;
+            for gitdir_path in self.fs.files() {
+                if !gitdir_path.starts_with(&worktrees_dir)
+                    || gitdir_path.file_name() != Some("gitdir".as_ref())
+                {
+                    continue;
+                }
+                let dot_git = PathBuf::from(self.fs.load(&gitdir_path).await?.trim());
+                let (worktree_branch, head_contents) =
+                    self.fs.with_git_state(&dot_git, false, |state| {
+                        (
+                            state.current_branch_name.clone(),
+                            state.head_contents.clone(),
+                        )
+                    })?;
+                if worktree_branch.as_ref() == Some(&branch) {
+                    branch_contents = Some(head_contents);
+                    break;
+                }
+            }
+            let branch_contents = branch_contents
+                .with_context(|| format!("{branch} - not something we can merge"))?;
+
+            let head_contents = self
+                .with_state_async(false, |state| Ok(state.head_contents.clone()))
+                .await?;
+            let mut changes = branch_contents
+                .iter()
+                .filter(|(path, content)| head_contents.get(*path) != Some(*content))
+                .map(|(path, content)| (path.clone(), Some(content.clone())))
+                .collect::<Vec<_>>();
+            changes.extend(
+                head_contents
+                    .keys()
+                    .filter(|path| !branch_contents.contains_key(*path))
+                    .map(|path| (path.clone(), None)),
+            );
+
+            for (repo_path, content) in &changes {
+                let abs_path = work_directory.join(repo_path.as_std_path());
+                match content {
+                    Some(content) => self.fs.write(&abs_path, content.as_bytes()).await?,
+                    None => {
+                        self.fs
+                            .remove_file(
+                                &abs_path,
+                                RemoveOptions {
+                                    recursive: false,
+                                    ignore_if_not_exists: true,
+                                },
+                            )
+                            .await?
+                    }
+                }
+            }
+            self.with_state_async(true, move |state| {
+                for (repo_path, content) in changes {
+                    match content {
+                        Some(content) => state.index_contents.insert(repo_path, content),
+                        None => state.index_contents.remove(&repo_path),
+                    };
+                }
+                Ok(())
+            })
+            .await
+        }
+        .boxed()
     }
 
     fn change_branch(&self, name: String) -> BoxFuture<'_, Result<()>> {
@@ -576,7 +723,10 @@ impl GitRepository for FakeGitRepository {
         _askpass: AskPassDelegate,
         _env: Arc<HashMap<String, String>>,
     ) -> BoxFuture<'_, Result<()>> {
-        async { Ok(()) }.boxed()
+        self.with_state_async(true, |state| {
+            state.head_contents = state.index_contents.clone();
+            Ok(())
+        })
     }
 
     fn run_hook(
@@ -115,7 +115,11 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
-    async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
+    async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()> {
+        self.atomic_write_bytes(path, text.into_bytes()).await
+    }
+    /// Writes to a temporary file and moves it over `path`, so the file is never half-written.
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
     async fn write(&self, path: &Path, content: &[u8]) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
@@ -769,14 +773,14 @@ impl Fs for RealFs {
     }
 
     #[cfg(not(target_os = "windows"))]
-    async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()> {
         smol::unblock(move || {
             // Use the directory of the destination as temp dir to avoid
             // invalid cross-device link error, and XDG_CACHE_DIR for fallback.
             // See https://github.com/zed-industries/zed/pull/8437 for more details.
             let mut tmp_file =
                 tempfile::NamedTempFile::new_in(path.parent().unwrap_or(paths::temp_dir()))?;
-            tmp_file.write_all(data.as_bytes())?;
+            tmp_file.write_all(&data)?;
             tmp_file.persist(path)?;
             anyhow::Ok(())
         })
@@ -786,7 +790,7 @@ impl Fs for RealFs {
     }
 
     #[cfg(target_os = "windows")]
-    async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()> {
         smol::unblock(move || {
             // If temp dir is set to a different drive than the destination,
             // we receive error:
@@ -804,7 +808,7 @@ impl Fs for RealFs {
             let temp_file = {
                 let temp_file_path = temp_dir.path().join("temp_file");
                 let mut file = std::fs::File::create_new(&temp_file_path)?;
-                file.write_all(data.as_bytes())?;
+                file.write_all(&data)?;
                 temp_file_path
             };
             atomic_replace(path.as_path(), temp_file.as_path())?;
@@ -2574,13 +2578,13 @@ impl Fs for FakeFs {
         self.load_internal(path).await
     }
 
-    async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+    async fn atomic_write_bytes(&self, path: PathBuf, data: Vec<u8>) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path.as_path());
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
-        self.write_file_internal(path, data.into_bytes(), true)?;
+        self.write_file_internal(path, data, true)?;
         Ok(())
     }
 
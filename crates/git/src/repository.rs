@@ -629,6 +629,12 @@ pub trait GitRepository: Send + Sync {
         from_commit: Option<String>,
     ) -> BoxFuture<'_, Result<()>>;
 
+    /// Removes the worktree at `path`, discarding its changes.
+    fn remove_worktree(&self, path: PathBuf) -> BoxFuture<'_, Result<()>>;
+
+    /// Merges `branch` into the working tree and index without committing, as `git merge --squash`.
+    fn squash_merge(&self, branch: String) -> BoxFuture<'_, Result<()>>;
+
     fn reset(
         &self,
         commit: String,
@@ -1599,6 +1605,43 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn remove_worktree(&self, path: PathBuf) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                let args = [
+                    OsString::from("--no-optional-locks"),
+                    OsString::from("worktree"),
+                    OsString::from("remove"),
+                    OsString::from("--force"),
+                    OsString::from(path.as_os_str()),
+                ];
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&args)
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
+    fn squash_merge(&self, branch: String) -> BoxFuture<'_, Result<()>> {
+        let git_binary_path = self.any_git_binary_path.clone();
+        let working_directory = self.working_directory();
+        let executor = self.executor.clone();
+
+        self.executor
+            .spawn(async move {
+                GitBinary::new(git_binary_path, working_directory?, executor)
+                    .run(&["merge", "--squash", &branch])
+                    .await?;
+                anyhow::Ok(())
+            })
+            .boxed()
+    }
+
     fn change_branch(&self, name: String) -> BoxFuture<'_, Result<()>> {
         let repo = self.repository.clone();
         let working_directory = self.working_directory();
@@ -0,0 +1,516 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+use collections::{FxHashMap, FxHasher};
+
+use crate::{Bounds, FocusHandle, FocusId, GlobalElementId, Pixels, SharedString};
+
+/// Identifies a node in a window's [`AccessibilityTree`]. Nodes keep their id across frames as
+/// long as the element that added them does, so that assistive technologies can track them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AccessibilityNodeId(pub u64);
+
+impl AccessibilityNodeId {
+    /// The id of the node representing the window itself.
+    pub const ROOT: Self = Self(0);
+
+    /// Derives the id of a node from its position among its parent's children, for nodes whose
+    /// element doesn't have an id.
+    pub(crate) fn child(self, index: usize) -> Self {
+        let mut hasher = FxHasher::default();
+        self.0.hash(&mut hasher);
+        index.hash(&mut hasher);
+        Self(hasher.finish().max(1))
+    }
+}
+
+impl From<&GlobalElementId> for AccessibilityNodeId {
+    fn from(id: &GlobalElementId) -> Self {
+        let mut hasher = FxHasher::default();
+        id.hash(&mut hasher);
+        Self(hasher.finish().max(1))
+    }
+}
+
+/// What an element is, as announced by assistive technologies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// An element without a more specific role, used to group its children.
+    #[default]
+    Group,
+    /// The window itself. This is the role of the root of every tree.
+    Window,
+    /// A modal or non-modal dialog.
+    Dialog,
+    /// A button that performs an action when clicked.
+    Button,
+    /// A button that can be checked and unchecked.
+    CheckBox,
+    /// A button that toggles between two states.
+    Switch,
+    /// A link to another location.
+    Link,
+    /// A piece of static text.
+    Label,
+    /// A heading that introduces a section.
+    Heading,
+    /// An image or icon.
+    Image,
+    /// A single line text field.
+    TextInput,
+    /// A multi-line text field, such as a code editor.
+    MultilineTextInput,
+    /// A list of items.
+    List,
+    /// An item in a [`Role::List`].
+    ListItem,
+    /// A tree of items, such as the project panel.
+    Tree,
+    /// An item in a [`Role::Tree`].
+    TreeItem,
+    /// A list of tabs.
+    TabList,
+    /// A tab in a [`Role::TabList`].
+    Tab,
+    /// The content shown for the selected tab.
+    TabPanel,
+    /// A menu, such as a context menu.
+    Menu,
+    /// An item in a [`Role::Menu`].
+    MenuItem,
+    /// A toolbar containing buttons.
+    Toolbar,
+    /// A region that scrolls its content.
+    ScrollView,
+    /// A bar that shows status information.
+    Status,
+    /// A tooltip.
+    Tooltip,
+}
+
+/// The text of an editable element, with its selection.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessibleText {
+    /// The text, which may only be the part of a larger document that is near the selection.
+    pub content: SharedString,
+    /// The selected range, as byte offsets into `content`. When the range is empty, it is the
+    /// position of the cursor.
+    pub selection: Range<usize>,
+    /// Whether the selection's head, where the cursor is, is at its start.
+    pub reversed: bool,
+}
+
+impl AccessibleText {
+    /// Returns the offset of the cursor.
+    pub fn cursor(&self) -> usize {
+        if self.reversed {
+            self.selection.start
+        } else {
+            self.selection.end
+        }
+    }
+}
+
+/// The properties that an element exposes to assistive technologies.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessibilityProperties {
+    /// What the element is.
+    pub role: Role,
+    /// The name announced for the element, e.g. the label of a button.
+    pub name: Option<SharedString>,
+    /// The current value of the element, e.g. the contents of a single line text field.
+    pub value: Option<SharedString>,
+    /// A longer description of the element, e.g. its tooltip.
+    pub description: Option<SharedString>,
+    /// Whether a check box or switch is checked.
+    pub toggled: Option<bool>,
+    /// Whether the element is selected, e.g. the active tab or the selected list item.
+    pub selected: Option<bool>,
+    /// Whether the element can't be interacted with.
+    pub disabled: bool,
+    /// The text and selection of an editable element.
+    pub text: Option<AccessibleText>,
+}
+
+impl AccessibilityProperties {
+    /// Creates properties for an element with the given role.
+    pub fn new(role: Role) -> Self {
+        Self {
+            role,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the name announced for the element.
+    pub fn name(mut self, name: impl Into<SharedString>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the current value of the element.
+    pub fn value(mut self, value: impl Into<SharedString>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Sets the description of the element.
+    pub fn description(mut self, description: impl Into<SharedString>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the text and selection of an editable element.
+    pub fn text(mut self, text: AccessibleText) -> Self {
+        self.text = Some(text);
+        self
+    }
+}
+
+/// A node in an [`AccessibilityTree`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityNode {
+    /// The id of the node.
+    pub id: AccessibilityNodeId,
+    /// The properties of the element that added the node.
+    pub properties: AccessibilityProperties,
+    /// The bounds of the element in the window.
+    pub bounds: Bounds<Pixels>,
+    /// Whether the element can be focused.
+    pub focusable: bool,
+    /// The ids of the node's children, in paint order.
+    pub children: Vec<AccessibilityNodeId>,
+}
+
+/// An action that an assistive technology asks to perform on a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessibilityAction {
+    /// Focus the element.
+    Focus,
+    /// Click the element, as if with the mouse.
+    Click,
+}
+
+/// A request from an assistive technology to perform an action on a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessibilityActionRequest {
+    /// The node to perform the action on.
+    pub node: AccessibilityNodeId,
+    /// The action to perform.
+    pub action: AccessibilityAction,
+}
+
+/// The elements of a window that are exposed to assistive technologies, such as screen readers.
+///
+/// The tree is rebuilt each frame from the elements that were painted with accessibility
+/// properties, e.g. with [`InteractiveElement::role`](crate::InteractiveElement::role), and
+/// passed to the platform whenever it changes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityTree {
+    nodes: FxHashMap<AccessibilityNodeId, AccessibilityNode>,
+    focus: AccessibilityNodeId,
+}
+
+impl AccessibilityTree {
+    /// Returns the node representing the window.
+    pub fn root(&self) -> &AccessibilityNode {
+        &self.nodes[&AccessibilityNodeId::ROOT]
+    }
+
+    /// Returns the node with the given id.
+    pub fn node(&self, id: AccessibilityNodeId) -> Option<&AccessibilityNode> {
+        self.nodes.get(&id)
+    }
+
+    /// Returns the node of the focused element, or the root if the focused element isn't
+    /// exposed.
+    pub fn focus(&self) -> &AccessibilityNode {
+        &self.nodes[&self.focus]
+    }
+
+    /// Returns all nodes in the tree, in no particular order.
+    pub fn nodes(&self) -> impl Iterator<Item = &AccessibilityNode> {
+        self.nodes.values()
+    }
+
+    /// Returns the first node in depth-first order that matches the given predicate.
+    pub fn find(
+        &self,
+        mut predicate: impl FnMut(&AccessibilityNode) -> bool,
+    ) -> Option<&AccessibilityNode> {
+        let mut stack = vec![AccessibilityNodeId::ROOT];
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[&id];
+            if predicate(node) {
+                return Some(node);
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        None
+    }
+
+    /// Returns the first node in depth-first order with the given role and name.
+    pub fn find_by_name(&self, role: Role, name: &str) -> Option<&AccessibilityNode> {
+        self.find(|node| {
+            node.properties.role == role && node.properties.name.as_deref() == Some(name)
+        })
+    }
+
+    pub(crate) fn build(
+        root: AccessibilityProperties,
+        root_bounds: Bounds<Pixels>,
+        operations: &[AccessibilityOperation],
+        focus: Option<FocusId>,
+    ) -> (Self, FxHashMap<AccessibilityNodeId, FocusHandle>) {
+        let mut nodes = FxHashMap::default();
+        let mut focus_handles = FxHashMap::default();
+        let mut focused_node = AccessibilityNodeId::ROOT;
+        nodes.insert(
+            AccessibilityNodeId::ROOT,
+            AccessibilityNode {
+                id: AccessibilityNodeId::ROOT,
+                properties: root,
+                bounds: root_bounds,
+                focusable: false,
+                children: Vec::new(),
+            },
+        );
+
+        let mut stack = vec![AccessibilityNodeId::ROOT];
+        for operation in operations {
+            match operation {
+                AccessibilityOperation::Push(entry) => {
+                    let parent = *stack.last().unwrap();
+                    let sibling_count = nodes[&parent].children.len();
+                    let mut id = entry.id.unwrap_or_else(|| parent.child(sibling_count));
+                    // An element may be painted more than once, e.g. while being dragged.
+                    while nodes.contains_key(&id) {
+                        id = id.child(sibling_count);
+                    }
+                    if let Some(focus_handle) = &entry.focus_handle {
+                        if Some(focus_handle.id) == focus {
+                            focused_node = id;
+                        }
+                        focus_handles.insert(id, focus_handle.clone());
+                    }
+                    nodes.get_mut(&parent).unwrap().children.push(id);
+                    nodes.insert(
+                        id,
+                        AccessibilityNode {
+                            id,
+                            properties: entry.properties.clone(),
+                            bounds: entry.bounds,
+                            focusable: entry.focus_handle.is_some(),
+                            children: Vec::new(),
+                        },
+                    );
+                    stack.push(id);
+                }
+                AccessibilityOperation::Pop => {
+                    if stack.len() > 1 {
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        (
+            Self {
+                nodes,
+                focus: focused_node,
+            },
+            focus_handles,
+        )
+    }
+
+    fn fmt_node(
+        &self,
+        id: AccessibilityNodeId,
+        depth: usize,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let node = &self.nodes[&id];
+        let properties = &node.properties;
+        write!(f, "{:indent$}{:?}", "", properties.role, indent = depth * 2)?;
+        if let Some(name) = &properties.name {
+            write!(f, " {name:?}")?;
+        }
+        if let Some(value) = &properties.value {
+            write!(f, " value={value:?}")?;
+        }
+        if let Some(toggled) = properties.toggled {
+            write!(f, " toggled={toggled}")?;
+        }
+        if properties.selected == Some(true) {
+            write!(f, " selected")?;
+        }
+        if properties.disabled {
+            write!(f, " disabled")?;
+        }
+        if let Some(text) = &properties.text {
+            write!(f, " text={:?} cursor={}", text.content, text.cursor())?;
+        }
+        if id == self.focus && id != AccessibilityNodeId::ROOT {
+            write!(f, " (focused)")?;
+        }
+        writeln!(f)?;
+        for child in &node.children {
+            self.fmt_node(*child, depth + 1, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the tree as an indented outline, one node per line, which is convenient for
+/// asserting on in tests.
+impl fmt::Display for AccessibilityTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_node(AccessibilityNodeId::ROOT, 0, f)
+    }
+}
+
+/// A node added by an element while painting. Nodes added between a node's push and its pop
+/// become its children.
+#[derive(Clone)]
+pub(crate) enum AccessibilityOperation {
+    Push(AccessibilityEntry),
+    Pop,
+}
+
+#[derive(Clone)]
+pub(crate) struct AccessibilityEntry {
+    pub(crate) id: Option<AccessibilityNodeId>,
+    pub(crate) properties: AccessibilityProperties,
+    pub(crate) bounds: Bounds<Pixels>,
+    pub(crate) focus_handle: Option<FocusHandle>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as gpui, Context, ElementId, InteractiveElement, IntoElement, ParentElement, Render,
+        StatefulInteractiveElement, Styled, TestAppContext, Window, bounds, div, point, px, size,
+    };
+    use std::sync::Arc;
+
+    fn entry(id: Option<&str>, properties: AccessibilityProperties) -> AccessibilityOperation {
+        AccessibilityOperation::Push(AccessibilityEntry {
+            id: id.map(|id| {
+                AccessibilityNodeId::from(&GlobalElementId(Arc::from([ElementId::Name(id.into())])))
+            }),
+            properties,
+            bounds: bounds(point(px(0.), px(0.)), size(px(10.), px(10.))),
+            focus_handle: None,
+        })
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let operations = [
+            entry(None, AccessibilityProperties::new(Role::Toolbar)),
+            entry(
+                Some("save"),
+                AccessibilityProperties::new(Role::Button).name("Save"),
+            ),
+            AccessibilityOperation::Pop,
+            entry(
+                None,
+                AccessibilityProperties::new(Role::CheckBox)
+                    .name("Wrap")
+                    .description("Soft wrap lines"),
+            ),
+            AccessibilityOperation::Pop,
+            AccessibilityOperation::Pop,
+            entry(
+                None,
+                AccessibilityProperties::new(Role::Label).name("Ready"),
+            ),
+            AccessibilityOperation::Pop,
+        ];
+        let (tree, _) = AccessibilityTree::build(
+            AccessibilityProperties::new(Role::Window).name("Untitled"),
+            bounds(point(px(0.), px(0.)), size(px(100.), px(100.))),
+            &operations,
+            None,
+        );
+
+        assert_eq!(
+            tree.to_string(),
+            concat!(
+                "Window \"Untitled\"\n",
+                "  Toolbar\n",
+                "    Button \"Save\"\n",
+                "    CheckBox \"Wrap\"\n",
+                "  Label \"Ready\"\n",
+            )
+        );
+        assert!(tree.find_by_name(Role::Button, "Save").is_some());
+        assert_eq!(tree.root().children.len(), 2);
+        assert_eq!(tree.focus().id, AccessibilityNodeId::ROOT);
+
+        // Ids are stable across frames.
+        let (next_tree, _) = AccessibilityTree::build(
+            AccessibilityProperties::new(Role::Window).name("Untitled"),
+            bounds(point(px(0.), px(0.)), size(px(100.), px(100.))),
+            &operations,
+            None,
+        );
+        assert_eq!(tree, next_tree);
+    }
+
+    #[gpui::test]
+    fn test_accessibility_actions(cx: &mut TestAppContext) {
+        struct TestView {
+            clicks: usize,
+            search: FocusHandle,
+        }
+
+        impl Render for TestView {
+            fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+                div()
+                    .size_full()
+                    .child(
+                        div()
+                            .id("save")
+                            .size(px(20.))
+                            .role(Role::Button)
+                            .accessible_name("Save")
+                            .on_click(cx.listener(|this, _, _, _| this.clicks += 1)),
+                    )
+                    .child(
+                        div()
+                            .id("search")
+                            .size(px(20.))
+                            .track_focus(&self.search)
+                            .role(Role::TextInput)
+                            .accessible_name("Search"),
+                    )
+            }
+        }
+
+        let (view, cx) = cx.add_window_view(|_, cx| TestView {
+            clicks: 0,
+            search: cx.focus_handle(),
+        });
+        cx.run_until_parked();
+        assert!(cx.accessibility_tree().is_none());
+
+        cx.activate_accessibility();
+        let tree = cx.accessibility_tree().unwrap();
+        let save = tree.find_by_name(Role::Button, "Save").unwrap().id;
+        let search = tree.find_by_name(Role::TextInput, "Search").unwrap();
+        assert!(search.focusable);
+        let search = search.id;
+
+        cx.simulate_accessibility_action(save, AccessibilityAction::Click);
+        view.read_with(cx, |view, _| assert_eq!(view.clicks, 1));
+
+        cx.simulate_accessibility_action(search, AccessibilityAction::Focus);
+        let tree = cx.accessibility_tree().unwrap();
+        assert_eq!(tree.focus().id, search);
+        assert!(tree.to_string().contains("TextInput \"Search\" (focused)"));
+    }
+}
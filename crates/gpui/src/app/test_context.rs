@@ -1,4 +1,5 @@
 use crate::{
+    AccessibilityAction, AccessibilityActionRequest, AccessibilityNodeId, AccessibilityTree,
     Action, AnyView, AnyWindowHandle, App, AppCell, AppContext, AsyncApp, AvailableSpace,
     BackgroundExecutor, BorrowAppContext, Bounds, Capslock, ClipboardItem, DrawPhase, Drawable,
     Element, Empty, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke, Modifiers,
@@ -805,6 +806,38 @@ impl VisualTestContext {
         self.update(|window, _| window.rendered_frame.debug_bounds.get(selector).copied())
     }
 
+    /// Simulates an assistive technology starting to use the window, which is needed for its
+    /// accessibility tree to be built. Automatically runs until parked.
+    pub fn activate_accessibility(&mut self) {
+        self.cx
+            .test_window(self.window)
+            .simulate_accessibility_activation(true);
+        self.run_until_parked();
+    }
+
+    /// Returns the accessibility tree that was last passed to the platform for this window.
+    pub fn accessibility_tree(&mut self) -> Option<AccessibilityTree> {
+        self.cx
+            .test_window(self.window)
+            .0
+            .lock()
+            .accessibility_tree
+            .clone()
+    }
+
+    /// Simulates an assistive technology performing an action on a node of the window's
+    /// accessibility tree. Automatically runs until parked.
+    pub fn simulate_accessibility_action(
+        &mut self,
+        node: AccessibilityNodeId,
+        action: AccessibilityAction,
+    ) {
+        self.cx
+            .test_window(self.window)
+            .simulate_accessibility_action(AccessibilityActionRequest { node, action });
+        self.run_until_parked();
+    }
+
     /// Draw an element to the window. Useful for simulating events or actions
     pub fn draw<E>(
         &mut self,
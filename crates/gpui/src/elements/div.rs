@@ -16,14 +16,14 @@
 //! constructed by combining these two systems into an all-in-one element.
 
 use crate::{
-    AbsoluteLength, Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent,
-    DispatchPhase, Display, Element, ElementId, Entity, FocusHandle, Global, GlobalElementId,
-    Hitbox, HitboxBehavior, HitboxId, InspectorElementId, IntoElement, IsZero, KeyContext,
-    KeyDownEvent, KeyUpEvent, KeyboardButton, KeyboardClickEvent, LayoutId, ModifiersChangedEvent,
-    MouseButton, MouseClickEvent, MouseDownEvent, MouseMoveEvent, MousePressureEvent, MouseUpEvent,
-    Overflow, ParentElement, Pixels, Point, Render, ScrollWheelEvent, SharedString, Size, Style,
-    StyleRefinement, Styled, Task, TooltipId, Visibility, Window, WindowControlArea, point, px,
-    size,
+    AbsoluteLength, AccessibilityNodeId, AccessibilityProperties, Action, AnyDrag, AnyElement,
+    AnyTooltip, AnyView, App, Bounds, ClickEvent, DispatchPhase, Display, Element, ElementId,
+    Entity, FocusHandle, Global, GlobalElementId, Hitbox, HitboxBehavior, HitboxId,
+    InspectorElementId, IntoElement, IsZero, KeyContext, KeyDownEvent, KeyUpEvent, KeyboardButton,
+    KeyboardClickEvent, LayoutId, ModifiersChangedEvent, MouseButton, MouseClickEvent,
+    MouseDownEvent, MouseMoveEvent, MousePressureEvent, MouseUpEvent, Overflow, ParentElement,
+    Pixels, Point, Render, Role, ScrollWheelEvent, SharedString, Size, Style, StyleRefinement,
+    Styled, Task, TooltipId, Visibility, Window, WindowControlArea, point, px, size,
 };
 use collections::HashMap;
 use refineable::Refineable;
@@ -748,6 +748,51 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Expose this element to assistive technologies, such as screen readers, with the given role.
+    /// Elements exposed by this element's children become its children in the window's
+    /// [`AccessibilityTree`](crate::AccessibilityTree).
+    fn role(mut self, role: Role) -> Self {
+        self.interactivity().accessibility_mut().role = role;
+        self
+    }
+
+    /// Set the name that assistive technologies announce for this element, e.g. the label of an
+    /// icon button. This exposes the element even if it has no [`Self::role`].
+    fn accessible_name(mut self, name: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_mut().name = Some(name.into());
+        self
+    }
+
+    /// Set the value that assistive technologies announce for this element.
+    fn accessible_value(mut self, value: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_mut().value = Some(value.into());
+        self
+    }
+
+    /// Set the description that assistive technologies announce for this element.
+    fn accessible_description(mut self, description: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_mut().description = Some(description.into());
+        self
+    }
+
+    /// Set whether this check box or switch is checked, for assistive technologies.
+    fn accessible_toggled(mut self, toggled: bool) -> Self {
+        self.interactivity().accessibility_mut().toggled = Some(toggled);
+        self
+    }
+
+    /// Set whether this element is selected, e.g. the active tab, for assistive technologies.
+    fn accessible_selected(mut self, selected: bool) -> Self {
+        self.interactivity().accessibility_mut().selected = Some(selected);
+        self
+    }
+
+    /// Set whether this element is disabled, for assistive technologies.
+    fn accessible_disabled(mut self, disabled: bool) -> Self {
+        self.interactivity().accessibility_mut().disabled = disabled;
+        self
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     /// Set a key that can be used to look up this element's bounds
     /// in the [`crate::VisualTestContext::debug_bounds`] map
@@ -1660,6 +1705,7 @@ pub struct Interactivity {
     pub(crate) tab_index: Option<isize>,
     pub(crate) tab_group: bool,
     pub(crate) tab_stop: bool,
+    pub(crate) accessibility: Option<Box<AccessibilityProperties>>,
 
     #[cfg(any(feature = "inspector", debug_assertions))]
     pub(crate) source_location: Option<&'static core::panic::Location<'static>>,
@@ -1669,6 +1715,10 @@ pub struct Interactivity {
 }
 
 impl Interactivity {
+    fn accessibility_mut(&mut self) -> &mut AccessibilityProperties {
+        self.accessibility.get_or_insert_default()
+    }
+
     /// Layout this element according to this interactivity state's configured styles
     pub fn request_layout(
         &mut self,
@@ -2004,7 +2054,23 @@ impl Interactivity {
                                         }
 
                                         self.paint_keyboard_listeners(window, cx);
+                                        let accessibility = self
+                                            .accessibility
+                                            .as_ref()
+                                            .filter(|_| window.is_accessibility_active());
+                                        if let Some(properties) = accessibility {
+                                            window.push_accessibility_node(
+                                                global_id.map(AccessibilityNodeId::from),
+                                                (**properties).clone(),
+                                                bounds,
+                                                self.tracked_focus_handle.clone(),
+                                            );
+                                        }
+                                        let exposed = accessibility.is_some();
                                         f(&style, window, cx);
+                                        if exposed {
+                                            window.pop_accessibility_node();
+                                        }
 
                                         if let Some(_hitbox) = hitbox {
                                             #[cfg(any(feature = "inspector", debug_assertions))]
@@ -17,10 +17,30 @@ mod mac;
 ))]
 mod blade;
 
+// The software renderer is only used to draw windows on Linux, but renders images offscreen
+// everywhere.
+#[cfg_attr(
+    not(all(
+        any(target_os = "linux", target_os = "freebsd"),
+        any(feature = "x11", feature = "wayland")
+    )),
+    allow(dead_code)
+)]
+mod software;
+
 #[cfg(any(test, feature = "test-support"))]
 mod test;
 
-#[cfg(all(target_os = "macos", any(test, feature = "test-support")))]
+#[cfg(all(
+    any(
+        target_os = "macos",
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "x11", feature = "wayland")
+        )
+    ),
+    any(test, feature = "test-support")
+))]
 mod visual_test;
 
 #[cfg(target_os = "windows")]
@@ -39,12 +59,13 @@ mod windows;
 pub(crate) mod scap_screen_capture;
 
 use crate::{
-    Action, AnyWindowHandle, App, AsyncWindowContext, BackgroundExecutor, Bounds,
-    DEFAULT_WINDOW_SIZE, DevicePixels, DispatchEventResult, Font, FontId, FontMetrics, FontRun,
-    ForegroundExecutor, GlyphId, GpuSpecs, ImageSource, Keymap, LineLayout, Pixels, PlatformInput,
-    Point, Priority, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Scene,
-    ShapedGlyph, ShapedRun, SharedString, Size, SvgRenderer, SystemWindowTab, Task, TaskTiming,
-    ThreadTaskTimings, Window, WindowControlArea, hash, point, px, size,
+    AccessibilityActionRequest, AccessibilityTree, Action, AnyWindowHandle, App,
+    AsyncWindowContext, BackgroundExecutor, Bounds, DEFAULT_WINDOW_SIZE, DevicePixels,
+    DispatchEventResult, Font, FontId, FontMetrics, FontRun, ForegroundExecutor, GlyphId, GpuSpecs,
+    ImageSource, Keymap, LineLayout, Pixels, PlatformInput, Point, Priority, RenderGlyphParams,
+    RenderImage, RenderImageParams, RenderSvgParams, Scene, ShapedGlyph, ShapedRun, SharedString,
+    Size, SvgRenderer, SystemWindowTab, Task, TaskTiming, ThreadTaskTimings, Window,
+    WindowControlArea, hash, point, px, size,
 };
 use anyhow::Result;
 use async_task::Runnable;
@@ -77,6 +98,7 @@ use uuid::Uuid;
 pub use app_menu::*;
 pub use keyboard::*;
 pub use keystroke::*;
+pub(crate) use software::SoftwareRenderer;
 
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
 pub(crate) use linux::*;
@@ -93,7 +115,16 @@ pub use linux::layer_shell;
 #[cfg(any(test, feature = "test-support"))]
 pub use test::{TestDispatcher, TestScreenCaptureSource, TestScreenCaptureStream};
 
-#[cfg(all(target_os = "macos", any(test, feature = "test-support")))]
+#[cfg(all(
+    any(
+        target_os = "macos",
+        all(
+            any(target_os = "linux", target_os = "freebsd"),
+            any(feature = "x11", feature = "wayland")
+        )
+    ),
+    any(test, feature = "test-support")
+))]
 pub use visual_test::VisualTestPlatform;
 
 /// Returns a background executor for the current platform.
@@ -592,6 +623,19 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
 
     fn update_ime_position(&self, _bounds: Bounds<Pixels>);
 
+    /// Exposes the window's elements to assistive technologies. Called whenever the tree changes
+    /// while an assistive technology is active.
+    fn update_accessibility_tree(&self, _tree: &AccessibilityTree) {}
+    /// Registers the callbacks for when an assistive technology starts or stops using the
+    /// window, and for the actions it requests. Platforms that don't call `on_activation` never
+    /// have the window's accessibility tree built.
+    fn on_accessibility(
+        &self,
+        _on_activation: Box<dyn FnMut(bool)>,
+        _on_action: Box<dyn FnMut(AccessibilityActionRequest)>,
+    ) {
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     fn as_test(&mut self) -> Option<&mut TestWindow> {
         None
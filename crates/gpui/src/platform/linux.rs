@@ -1,8 +1,12 @@
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod accessibility;
 mod dispatcher;
 mod headless;
 mod keyboard;
 mod platform;
 #[cfg(any(feature = "wayland", feature = "x11"))]
+mod renderer;
+#[cfg(any(feature = "wayland", feature = "x11"))]
 mod text_system;
 #[cfg(feature = "wayland")]
 mod wayland;
@@ -12,11 +16,15 @@ mod x11;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 mod xdg_desktop_portal;
 
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use accessibility::*;
 pub(crate) use dispatcher::*;
 pub(crate) use headless::*;
 pub(crate) use keyboard::*;
 pub(crate) use platform::*;
 #[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use renderer::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
 pub(crate) use text_system::*;
 #[cfg(feature = "wayland")]
 pub(crate) use wayland::*;
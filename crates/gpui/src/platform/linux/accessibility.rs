@@ -0,0 +1,306 @@
+use std::sync::Arc;
+
+use accesskit::{
+    Action, ActionHandler, ActionRequest, ActivationHandler, Affine, DeactivationHandler, Node,
+    NodeId, Rect, TextPosition, TextSelection, Toggled, Tree, TreeUpdate,
+};
+use accesskit_unix::Adapter;
+use futures::{StreamExt, channel::mpsc};
+use parking_lot::Mutex;
+
+use crate::{
+    AccessibilityAction, AccessibilityActionRequest, AccessibilityNode, AccessibilityNodeId,
+    AccessibilityTree, AccessibleText, Bounds, ForegroundExecutor, Pixels, Role, Task,
+};
+
+/// Exposes the accessibility tree of an X11 or Wayland window over AT-SPI, so that it can be used
+/// with screen readers such as Orca.
+pub(crate) struct LinuxAccessibility {
+    adapter: Adapter,
+    /// The last tree passed to the adapter, which is sent to assistive technologies that start
+    /// after it was updated.
+    latest_tree: Arc<Mutex<Option<TreeUpdate>>>,
+    _forward_events: Task<()>,
+}
+
+enum AccessibilityEvent {
+    Activation(bool),
+    Action(AccessibilityActionRequest),
+}
+
+impl LinuxAccessibility {
+    /// Connects to the accessibility bus. When an assistive technology starts or stops using the
+    /// window, `on_activation` is called, and actions it requests are performed by `on_action`,
+    /// both on the foreground thread.
+    pub fn new(
+        executor: &ForegroundExecutor,
+        mut on_activation: Box<dyn FnMut(bool)>,
+        mut on_action: Box<dyn FnMut(AccessibilityActionRequest)>,
+    ) -> Self {
+        let latest_tree = Arc::new(Mutex::new(None));
+        let (event_tx, mut event_rx) = mpsc::unbounded();
+        let adapter = Adapter::new(
+            Activate {
+                latest_tree: latest_tree.clone(),
+                event_tx: event_tx.clone(),
+            },
+            ForwardActions(event_tx.clone()),
+            Deactivate(event_tx),
+        );
+        let forward_events = executor.spawn(async move {
+            while let Some(event) = event_rx.next().await {
+                match event {
+                    AccessibilityEvent::Activation(active) => on_activation(active),
+                    AccessibilityEvent::Action(request) => on_action(request),
+                }
+            }
+        });
+        Self {
+            adapter,
+            latest_tree,
+            _forward_events: forward_events,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        tree: &AccessibilityTree,
+        window_bounds: Bounds<Pixels>,
+        scale_factor: f32,
+    ) {
+        let update = tree_update(tree, scale_factor);
+        *self.latest_tree.lock() = Some(update.clone());
+        let bounds = device_rect(window_bounds, scale_factor);
+        self.adapter.set_root_window_bounds(bounds, bounds);
+        self.adapter.update_if_active(|| update);
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.adapter.update_window_focus_state(active);
+    }
+}
+
+/// Starts building the window's tree once an assistive technology connects. Until the first
+/// tree is built, the adapter waits for [`LinuxAccessibility::update`].
+struct Activate {
+    latest_tree: Arc<Mutex<Option<TreeUpdate>>>,
+    event_tx: mpsc::UnboundedSender<AccessibilityEvent>,
+}
+
+impl ActivationHandler for Activate {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        self.event_tx
+            .unbounded_send(AccessibilityEvent::Activation(true))
+            .ok();
+        self.latest_tree.lock().clone()
+    }
+}
+
+struct ForwardActions(mpsc::UnboundedSender<AccessibilityEvent>);
+
+impl ActionHandler for ForwardActions {
+    fn do_action(&mut self, request: ActionRequest) {
+        let action = match request.action {
+            Action::Focus => AccessibilityAction::Focus,
+            Action::Click => AccessibilityAction::Click,
+            _ => return,
+        };
+        self.0
+            .unbounded_send(AccessibilityEvent::Action(AccessibilityActionRequest {
+                node: AccessibilityNodeId(request.target.0),
+                action,
+            }))
+            .ok();
+    }
+}
+
+struct Deactivate(mpsc::UnboundedSender<AccessibilityEvent>);
+
+impl DeactivationHandler for Deactivate {
+    fn deactivate_accessibility(&mut self) {
+        self.0
+            .unbounded_send(AccessibilityEvent::Activation(false))
+            .ok();
+    }
+}
+
+fn tree_update(tree: &AccessibilityTree, scale_factor: f32) -> TreeUpdate {
+    let root_id = NodeId(tree.root().id.0);
+    let mut nodes = Vec::new();
+    for node in tree.nodes() {
+        let mut accesskit_node = Node::new(accesskit_role(node.properties.role));
+        let properties = &node.properties;
+        if let Some(name) = &properties.name {
+            accesskit_node.set_label(name.to_string());
+        }
+        if let Some(value) = &properties.value {
+            accesskit_node.set_value(value.to_string());
+        }
+        if let Some(description) = &properties.description {
+            accesskit_node.set_description(description.to_string());
+        }
+        if let Some(toggled) = properties.toggled {
+            accesskit_node.set_toggled(if toggled {
+                Toggled::True
+            } else {
+                Toggled::False
+            });
+        }
+        if let Some(selected) = properties.selected {
+            accesskit_node.set_selected(selected);
+        }
+        if properties.disabled {
+            accesskit_node.set_disabled();
+        }
+        if node.focusable {
+            accesskit_node.add_action(Action::Focus);
+        }
+        if is_clickable(properties.role) {
+            accesskit_node.add_action(Action::Click);
+        }
+        accesskit_node.set_bounds(logical_rect(node.bounds));
+
+        let mut children: Vec<NodeId> = node.children.iter().map(|id| NodeId(id.0)).collect();
+        if let Some(text) = &properties.text {
+            let (runs, selection) = text_runs(node, text);
+            children.extend(runs.iter().map(|(id, _)| *id));
+            accesskit_node.set_text_selection(selection);
+            nodes.extend(runs);
+        }
+        accesskit_node.set_children(children);
+
+        if node.id == tree.root().id {
+            accesskit_node.set_transform(Affine::scale(scale_factor as f64));
+        }
+        nodes.push((NodeId(node.id.0), accesskit_node));
+    }
+
+    TreeUpdate {
+        nodes,
+        tree: Some(Tree::new(root_id)),
+        focus: NodeId(tree.focus().id.0),
+    }
+}
+
+/// Splits the text of an editable node into one text run per line, which is how assistive
+/// technologies expect to read it, and locates the selection within those runs.
+fn text_runs(
+    node: &AccessibilityNode,
+    text: &AccessibleText,
+) -> (Vec<(NodeId, Node)>, TextSelection) {
+    let position_of = |runs: &[(NodeId, usize, &str)], offset: usize| {
+        let (run_id, run_start, line) = runs
+            .iter()
+            .rev()
+            .find(|(_, start, _)| *start <= offset)
+            .copied()
+            .unwrap_or(runs[0]);
+        let offset = (offset - run_start).min(line.len());
+        TextPosition {
+            node: run_id,
+            character_index: line[..offset].chars().count(),
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (index, line) in text.content.split_inclusive('\n').enumerate() {
+        lines.push((NodeId(node.id.child(index).0), start, line));
+        start += line.len();
+    }
+    if lines.is_empty() || text.content.ends_with('\n') {
+        lines.push((NodeId(node.id.child(lines.len()).0), start, ""));
+    }
+
+    let (anchor, focus) = if text.reversed {
+        (text.selection.end, text.selection.start)
+    } else {
+        (text.selection.start, text.selection.end)
+    };
+    let selection = TextSelection {
+        anchor: position_of(&lines, anchor),
+        focus: position_of(&lines, focus),
+    };
+
+    let line_height = node.bounds.size.height / lines.len() as f32;
+    let runs = lines
+        .iter()
+        .enumerate()
+        .map(|(index, (id, _, line))| {
+            let mut run = Node::new(accesskit::Role::TextRun);
+            run.set_value(line.to_string());
+            let character_lengths = line.chars().map(|c| c.len_utf8() as u8);
+            run.set_character_lengths(character_lengths.collect::<Vec<_>>());
+            let mut bounds = node.bounds;
+            bounds.origin.y += line_height * index as f32;
+            bounds.size.height = line_height;
+            run.set_bounds(logical_rect(bounds));
+            (*id, run)
+        })
+        .collect();
+    (runs, selection)
+}
+
+fn is_clickable(role: Role) -> bool {
+    matches!(
+        role,
+        Role::Button
+            | Role::CheckBox
+            | Role::Switch
+            | Role::Link
+            | Role::ListItem
+            | Role::TreeItem
+            | Role::Tab
+            | Role::MenuItem
+    )
+}
+
+fn accesskit_role(role: Role) -> accesskit::Role {
+    match role {
+        Role::Group => accesskit::Role::GenericContainer,
+        Role::Window => accesskit::Role::Window,
+        Role::Dialog => accesskit::Role::Dialog,
+        Role::Button => accesskit::Role::Button,
+        Role::CheckBox => accesskit::Role::CheckBox,
+        Role::Switch => accesskit::Role::Switch,
+        Role::Link => accesskit::Role::Link,
+        Role::Label => accesskit::Role::Label,
+        Role::Heading => accesskit::Role::Heading,
+        Role::Image => accesskit::Role::Image,
+        Role::TextInput => accesskit::Role::TextInput,
+        Role::MultilineTextInput => accesskit::Role::MultilineTextInput,
+        Role::List => accesskit::Role::List,
+        Role::ListItem => accesskit::Role::ListItem,
+        Role::Tree => accesskit::Role::Tree,
+        Role::TreeItem => accesskit::Role::TreeItem,
+        Role::TabList => accesskit::Role::TabList,
+        Role::Tab => accesskit::Role::Tab,
+        Role::TabPanel => accesskit::Role::TabPanel,
+        Role::Menu => accesskit::Role::Menu,
+        Role::MenuItem => accesskit::Role::MenuItem,
+        Role::Toolbar => accesskit::Role::Toolbar,
+        Role::ScrollView => accesskit::Role::ScrollView,
+        Role::Status => accesskit::Role::Status,
+        Role::Tooltip => accesskit::Role::Tooltip,
+    }
+}
+
+fn logical_rect(bounds: Bounds<Pixels>) -> Rect {
+    Rect::new(
+        f64::from(bounds.origin.x),
+        f64::from(bounds.origin.y),
+        f64::from(bounds.origin.x + bounds.size.width),
+        f64::from(bounds.origin.y + bounds.size.height),
+    )
+}
+
+fn device_rect(bounds: Bounds<Pixels>, scale_factor: f32) -> Rect {
+    let scale_factor = scale_factor as f64;
+    let rect = logical_rect(bounds);
+    Rect::new(
+        rect.x0 * scale_factor,
+        rect.y0 * scale_factor,
+        rect.x1 * scale_factor,
+        rect.y1 * scale_factor,
+    )
+}
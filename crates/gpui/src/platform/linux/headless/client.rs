@@ -5,6 +5,7 @@ use calloop::{EventLoop, LoopHandle};
 use util::ResultExt;
 
 use crate::platform::linux::LinuxClient;
+use crate::platform::software::OffscreenWindow;
 use crate::platform::{LinuxCommon, PlatformWindow};
 use crate::{
     AnyWindowHandle, CursorStyle, DisplayId, LinuxKeyboardLayout, PlatformDisplay,
@@ -91,12 +92,14 @@ impl LinuxClient for HeadlessClient {
         None
     }
 
+    // Without a display server, windows are never shown, but they can still be rendered to
+    // images by the software renderer.
     fn open_window(
         &self,
-        _handle: AnyWindowHandle,
-        _params: WindowParams,
+        handle: AnyWindowHandle,
+        params: WindowParams,
     ) -> anyhow::Result<Box<dyn PlatformWindow>> {
-        anyhow::bail!("neither DISPLAY nor WAYLAND_DISPLAY is set. You can run in headless mode");
+        Ok(Box::new(OffscreenWindow::new(handle, params, None)))
     }
 
     fn compositor_name(&self) -> &'static str {
@@ -0,0 +1,130 @@
+use crate::{
+    DevicePixels, GpuSpecs, PlatformAtlas, Scene, Size,
+    platform::{
+        blade::{BladeContext, BladeRenderer, BladeSurfaceConfig},
+        software::SoftwareRenderer,
+    },
+    size,
+};
+use std::sync::Arc;
+
+/// Creates the GPU context that X11 and Wayland windows are rendered with.
+///
+/// Returns `None` when windows should be rendered on the CPU instead, either because
+/// `ZED_SOFTWARE_RENDERER` is set or because no usable GPU could be found.
+pub(crate) fn create_gpu_context() -> Option<BladeContext> {
+    if std::env::var_os("ZED_SOFTWARE_RENDERER").is_some() {
+        log::info!("ZED_SOFTWARE_RENDERER is set, rendering windows on the CPU");
+        return None;
+    }
+    match BladeContext::new() {
+        Ok(context) => Some(context),
+        Err(error) => {
+            log::warn!("Unable to init GPU context, falling back to software rendering: {error:?}");
+            None
+        }
+    }
+}
+
+/// The renderer of an X11 or Wayland window.
+///
+/// Frames drawn by the software renderer aren't presented by the renderer itself: the window
+/// copies [`LinuxRenderer::software_frame`] to the display server after each draw.
+pub(crate) enum LinuxRenderer {
+    Gpu(BladeRenderer),
+    Software(SoftwareRenderer),
+}
+
+impl LinuxRenderer {
+    pub fn new<I: raw_window_handle::HasWindowHandle + raw_window_handle::HasDisplayHandle>(
+        gpu_context: Option<&BladeContext>,
+        window: &I,
+        config: BladeSurfaceConfig,
+    ) -> anyhow::Result<Self> {
+        Ok(match gpu_context {
+            Some(gpu_context) => Self::Gpu(BladeRenderer::new(gpu_context, window, config)?),
+            None => Self::Software(SoftwareRenderer::new(
+                size(
+                    DevicePixels(config.size.width as i32),
+                    DevicePixels(config.size.height as i32),
+                ),
+                config.transparent,
+            )),
+        })
+    }
+
+    pub fn viewport_size(&self) -> Size<DevicePixels> {
+        match self {
+            Self::Gpu(renderer) => {
+                let extent = renderer.viewport_size();
+                size(
+                    DevicePixels(extent.width as i32),
+                    DevicePixels(extent.height as i32),
+                )
+            }
+            Self::Software(renderer) => renderer.viewport_size(),
+        }
+    }
+
+    pub fn update_drawable_size(&mut self, size: Size<DevicePixels>) {
+        match self {
+            Self::Gpu(renderer) => renderer.update_drawable_size(size),
+            Self::Software(renderer) => renderer.update_drawable_size(size),
+        }
+    }
+
+    pub fn update_transparency(&mut self, transparent: bool) {
+        match self {
+            Self::Gpu(renderer) => renderer.update_transparency(transparent),
+            Self::Software(renderer) => renderer.update_transparency(transparent),
+        }
+    }
+
+    pub fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
+        match self {
+            Self::Gpu(renderer) => renderer.sprite_atlas().clone(),
+            Self::Software(renderer) => renderer.sprite_atlas().clone(),
+        }
+    }
+
+    pub fn gpu_specs(&self) -> GpuSpecs {
+        match self {
+            Self::Gpu(renderer) => renderer.gpu_specs(),
+            Self::Software(renderer) => renderer.gpu_specs(),
+        }
+    }
+
+    pub fn draw(&mut self, scene: &Scene) {
+        match self {
+            Self::Gpu(renderer) => renderer.draw(scene),
+            Self::Software(renderer) => renderer.draw(scene),
+        }
+    }
+
+    /// Returns the last frame drawn by the software renderer, or `None` when rendering on the GPU.
+    pub fn software_frame(&self) -> Option<SoftwareFrame<'_>> {
+        match self {
+            Self::Gpu(_) => None,
+            Self::Software(renderer) => Some(SoftwareFrame {
+                size: renderer.viewport_size(),
+                transparent: renderer.is_transparent(),
+                pixels: renderer.pixels(),
+            }),
+        }
+    }
+
+    pub fn destroy(&mut self) {
+        match self {
+            Self::Gpu(renderer) => renderer.destroy(),
+            Self::Software(_) => {}
+        }
+    }
+}
+
+/// A frame rendered on the CPU, ready to be copied to the display server.
+pub(crate) struct SoftwareFrame<'a> {
+    pub size: Size<DevicePixels>,
+    pub transparent: bool,
+    /// Premultiplied BGRA pixels, row by row with no padding.
+    pub pixels: &'a [u8],
+}
@@ -1,6 +1,9 @@
 use std::{
     cell::{Ref, RefCell, RefMut},
     ffi::c_void,
+    fs::File,
+    io::Write as _,
+    os::fd::{AsFd as _, FromRawFd as _},
     ptr::NonNull,
     rc::Rc,
     sync::Arc,
@@ -11,9 +14,13 @@ use collections::{FxHashSet, HashMap};
 use futures::channel::oneshot::Receiver;
 
 use raw_window_handle as rwh;
+use util::ResultExt as _;
 use wayland_backend::client::ObjectId;
 use wayland_client::WEnum;
-use wayland_client::{Proxy, protocol::wl_surface};
+use wayland_client::{
+    Proxy,
+    protocol::{wl_shm, wl_surface},
+};
 use wayland_protocols::wp::viewporter::client::wp_viewport;
 use wayland_protocols::xdg::decoration::zv1::client::zxdg_toplevel_decoration_v1;
 use wayland_protocols::xdg::shell::client::xdg_surface;
@@ -26,18 +33,22 @@ use wayland_protocols_plasma::blur::client::org_kde_kwin_blur;
 use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1;
 
 use crate::{
-    AnyWindowHandle, Bounds, Decorations, Globals, GpuSpecs, Modifiers, Output, Pixels,
-    PlatformDisplay, PlatformInput, Point, PromptButton, PromptLevel, RequestFrameOptions,
-    ResizeEdge, Size, Tiling, WaylandClientStatePtr, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControlArea, WindowControls, WindowDecorations, WindowParams, get_window,
-    layer_shell::LayerShellNotSupportedError, px, size,
+    AccessibilityActionRequest, AccessibilityTree, AnyWindowHandle, Bounds, Decorations, Globals,
+    GpuSpecs, Modifiers, Output, Pixels, PlatformDisplay, PlatformInput, Point, PromptButton,
+    PromptLevel, RequestFrameOptions, ResizeEdge, Size, Tiling, WaylandClientStatePtr,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowControls,
+    WindowDecorations, WindowParams, get_window, layer_shell::LayerShellNotSupportedError, px,
+    size,
 };
 use crate::{
     Capslock,
     platform::{
         PlatformAtlas, PlatformInputHandler, PlatformWindow,
-        blade::{BladeContext, BladeRenderer, BladeSurfaceConfig},
-        linux::wayland::{display::WaylandDisplay, serial::SerialKind},
+        blade::{BladeContext, BladeSurfaceConfig},
+        linux::{
+            LinuxAccessibility, LinuxRenderer, SoftwareFrame,
+            wayland::{display::WaylandDisplay, serial::SerialKind},
+        },
     },
 };
 use crate::{WindowKind, scene::Scene};
@@ -97,7 +108,7 @@ pub struct WaylandWindowState {
     outputs: HashMap<ObjectId, Output>,
     display: Option<(ObjectId, Output)>,
     globals: Globals,
-    renderer: BladeRenderer,
+    renderer: LinuxRenderer,
     bounds: Bounds<Pixels>,
     scale: f32,
     input_handler: Option<PlatformInputHandler>,
@@ -116,6 +127,7 @@ pub struct WaylandWindowState {
     in_progress_window_controls: Option<WindowControls>,
     window_controls: WindowControls,
     client_inset: Option<Pixels>,
+    accessibility: Option<LinuxAccessibility>,
 }
 
 pub enum WaylandSurfaceState {
@@ -314,7 +326,7 @@ impl WaylandWindowState {
         viewport: Option<wp_viewport::WpViewport>,
         client: WaylandClientStatePtr,
         globals: Globals,
-        gpu_context: &BladeContext,
+        gpu_context: Option<&BladeContext>,
         options: WindowParams,
         parent: Option<WaylandWindowStatePtr>,
     ) -> anyhow::Result<Self> {
@@ -336,7 +348,7 @@ impl WaylandWindowState {
                 },
                 transparent: true,
             };
-            BladeRenderer::new(gpu_context, &raw_window, config)?
+            LinuxRenderer::new(gpu_context, &raw_window, config)?
         };
 
         if let WaylandSurfaceState::Xdg(ref xdg_state) = surface_state {
@@ -377,6 +389,7 @@ impl WaylandWindowState {
             in_progress_window_controls: None,
             window_controls: WindowControls::default(),
             client_inset: None,
+            accessibility: None,
         })
     }
 
@@ -479,7 +492,7 @@ impl WaylandWindow {
     pub fn new(
         handle: AnyWindowHandle,
         globals: Globals,
-        gpu_context: &BladeContext,
+        gpu_context: Option<&BladeContext>,
         client: WaylandClientStatePtr,
         params: WindowParams,
         appearance: WindowAppearance,
@@ -987,7 +1000,12 @@ impl WaylandWindowStatePtr {
     }
 
     pub fn set_focused(&self, focus: bool) {
-        self.state.borrow_mut().active = focus;
+        let mut state = self.state.borrow_mut();
+        state.active = focus;
+        if let Some(accessibility) = state.accessibility.as_mut() {
+            accessibility.set_active(focus);
+        }
+        drop(state);
         if let Some(ref mut fun) = self.callbacks.borrow_mut().active_status_change {
             fun(focus);
         }
@@ -1227,7 +1245,10 @@ impl PlatformWindow for WaylandWindow {
     fn is_subpixel_rendering_supported(&self) -> bool {
         let client = self.borrow().client.get_client();
         let state = client.borrow();
-        state.gpu_context.supports_dual_source_blending()
+        state
+            .gpu_context
+            .as_ref()
+            .is_some_and(|gpu_context| gpu_context.supports_dual_source_blending())
     }
 
     fn minimize(&self) {
@@ -1301,9 +1322,31 @@ impl PlatformWindow for WaylandWindow {
         self.0.callbacks.borrow_mut().appearance_changed = Some(callback);
     }
 
+    fn update_accessibility_tree(&self, tree: &AccessibilityTree) {
+        let mut state = self.borrow_mut();
+        let (bounds, scale) = (state.bounds, state.scale);
+        if let Some(accessibility) = state.accessibility.as_mut() {
+            accessibility.update(tree, bounds, scale);
+        }
+    }
+
+    fn on_accessibility(
+        &self,
+        on_activation: Box<dyn FnMut(bool)>,
+        on_action: Box<dyn FnMut(AccessibilityActionRequest)>,
+    ) {
+        let mut state = self.borrow_mut();
+        let accessibility =
+            LinuxAccessibility::new(&state.globals.executor, on_activation, on_action);
+        state.accessibility = Some(accessibility);
+    }
+
     fn draw(&self, scene: &Scene) {
         let mut state = self.borrow_mut();
         state.renderer.draw(scene);
+        if let Some(frame) = state.renderer.software_frame() {
+            attach_software_frame(&state.surface, &state.globals, frame).log_err();
+        }
     }
 
     fn completed_frame(&self) {
@@ -1313,7 +1356,7 @@ impl PlatformWindow for WaylandWindow {
 
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
         let state = self.borrow();
-        state.renderer.sprite_atlas().clone()
+        state.renderer.sprite_atlas()
     }
 
     fn show_window_menu(&self, position: Point<Pixels>) {
@@ -1400,6 +1443,50 @@ impl PlatformWindow for WaylandWindow {
     }
 }
 
+/// Attaches a frame rendered on the CPU to the surface, to be presented on the next commit.
+///
+/// Every frame gets its own shared memory buffer, which is destroyed right after it is attached:
+/// the compositor keeps its contents alive for as long as it needs them, and since the memory
+/// is never written to again, there is no need to wait for the buffer to be released.
+fn attach_software_frame(
+    surface: &wl_surface::WlSurface,
+    globals: &Globals,
+    frame: SoftwareFrame,
+) -> anyhow::Result<()> {
+    let width = frame.size.width.0;
+    let height = frame.size.height.0;
+    if width <= 0 || height <= 0 {
+        return Ok(());
+    }
+    let stride = width * 4;
+
+    // SAFETY: the name is a valid C string, and the returned descriptor is checked before it is
+    // given to the file that owns it.
+    let fd = unsafe { libc::memfd_create(c"gpui-software-frame".as_ptr(), libc::MFD_CLOEXEC) };
+    anyhow::ensure!(
+        fd >= 0,
+        "memfd_create failed: {}",
+        std::io::Error::last_os_error()
+    );
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.write_all(frame.pixels)?;
+
+    let pool = globals
+        .shm
+        .create_pool(file.as_fd(), stride * height, &globals.qh, ());
+    let format = if frame.transparent {
+        wl_shm::Format::Argb8888
+    } else {
+        wl_shm::Format::Xrgb8888
+    };
+    let buffer = pool.create_buffer(0, width, height, stride, format, &globals.qh, ());
+    surface.attach(Some(&buffer), 0, 0);
+    surface.damage_buffer(0, 0, width, height);
+    buffer.destroy();
+    pool.destroy();
+    Ok(())
+}
+
 fn update_window(mut state: RefMut<WaylandWindowState>) {
     let opaque = !state.is_transparent();
 
@@ -1,13 +1,17 @@
 use anyhow::{Context as _, anyhow};
 use x11rb::connection::RequestConnection;
 
-use crate::platform::blade::{BladeContext, BladeRenderer, BladeSurfaceConfig};
+use crate::platform::{
+    blade::{BladeContext, BladeSurfaceConfig},
+    linux::{LinuxAccessibility, LinuxRenderer, SoftwareFrame},
+};
 use crate::{
-    AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GpuSpecs, Modifiers,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size,
-    Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
-    WindowDecorations, WindowKind, WindowParams, X11ClientStatePtr, px, size,
+    AccessibilityActionRequest, AccessibilityTree, AnyWindowHandle, Bounds, Decorations,
+    DevicePixels, ForegroundExecutor, GpuSpecs, Modifiers, Pixels, PlatformAtlas, PlatformDisplay,
+    PlatformInput, PlatformInputHandler, PlatformWindow, Point, PromptButton, PromptLevel,
+    RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size, Tiling, WindowAppearance,
+    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowDecorations, WindowKind,
+    WindowParams, X11ClientStatePtr, px, size,
 };
 
 use blade_graphics as gpu;
@@ -98,6 +102,49 @@ fn query_render_extent(
     })
 }
 
+/// Copies a frame rendered on the CPU to the window, splitting it into bands of rows that each
+/// fit in a single request. The frame's premultiplied BGRA pixels match the layout of 24 and 32
+/// bit deep TrueColor visuals on little-endian machines.
+fn put_software_frame(
+    xcb: &XCBConnection,
+    x_window: xproto::Window,
+    gc: xproto::Gcontext,
+    depth: u8,
+    frame: SoftwareFrame,
+) -> anyhow::Result<()> {
+    const PUT_IMAGE_HEADER_BYTES: usize = 24;
+
+    let width = frame.size.width.0 as usize;
+    let height = frame.size.height.0 as usize;
+    if width == 0 || height == 0 {
+        return Ok(());
+    }
+    let row_bytes = width * 4;
+    let rows_per_request =
+        ((xcb.maximum_request_bytes() - PUT_IMAGE_HEADER_BYTES) / row_bytes).clamp(1, height);
+    for (band_ix, band) in frame
+        .pixels
+        .chunks(rows_per_request * row_bytes)
+        .enumerate()
+    {
+        xcb.put_image(
+            xproto::ImageFormat::Z_PIXMAP,
+            x_window,
+            gc,
+            width as u16,
+            (band.len() / row_bytes) as u16,
+            0,
+            (band_ix * rows_per_request) as i16,
+            0,
+            depth,
+            band,
+        )
+        .context("X11 PutImage failed.")?;
+    }
+    xcb_flush(xcb);
+    Ok(())
+}
+
 impl ResizeEdge {
     fn to_moveresize(self) -> u32 {
         match self {
@@ -261,7 +308,10 @@ pub struct X11WindowState {
     pub(crate) last_sync_counter: Option<sync::Int64>,
     bounds: Bounds<Pixels>,
     scale_factor: f32,
-    renderer: BladeRenderer,
+    renderer: LinuxRenderer,
+    /// The graphics context that frames rendered on the CPU are copied to the window with.
+    software_gc: Option<xproto::Gcontext>,
+    depth: u8,
     display: Rc<dyn PlatformDisplay>,
     input_handler: Option<PlatformInputHandler>,
     appearance: WindowAppearance,
@@ -277,6 +327,7 @@ pub struct X11WindowState {
     edge_constraints: Option<EdgeConstraints>,
     pub handle: AnyWindowHandle,
     last_insets: [u32; 4],
+    accessibility: Option<LinuxAccessibility>,
 }
 
 impl X11WindowState {
@@ -389,7 +440,7 @@ impl X11WindowState {
         handle: AnyWindowHandle,
         client: X11ClientStatePtr,
         executor: ForegroundExecutor,
-        gpu_context: &BladeContext,
+        gpu_context: Option<&BladeContext>,
         params: WindowParams,
         xcb: &Rc<XCBConnection>,
         client_side_decorations_supported: bool,
@@ -692,7 +743,18 @@ impl X11WindowState {
                     // too
                     transparent: false,
                 };
-                BladeRenderer::new(gpu_context, &raw_window, config)?
+                LinuxRenderer::new(gpu_context, &raw_window, config)?
+            };
+            let software_gc = match renderer {
+                LinuxRenderer::Gpu(_) => None,
+                LinuxRenderer::Software(_) => {
+                    let gc = xcb.generate_id()?;
+                    check_reply(
+                        || "X11 CreateGC failed.",
+                        xcb.create_gc(gc, x_window, &xproto::CreateGCAux::new()),
+                    )?;
+                    Some(gc)
+                }
             };
 
             let display = Rc::new(X11Display::new(xcb, scale_factor, x_screen_index)?);
@@ -707,6 +769,8 @@ impl X11WindowState {
                 bounds: bounds.to_pixels(scale_factor),
                 scale_factor,
                 renderer,
+                software_gc,
+                depth: visual.depth,
                 atoms: *atoms,
                 input_handler: None,
                 active: false,
@@ -725,6 +789,7 @@ impl X11WindowState {
                 edge_constraints: None,
                 counter_id: sync_request_counter,
                 last_sync_counter: None,
+                accessibility: None,
             })
         });
 
@@ -742,8 +807,8 @@ impl X11WindowState {
     fn content_size(&self) -> Size<Pixels> {
         let size = self.renderer.viewport_size();
         Size {
-            width: size.width.into(),
-            height: size.height.into(),
+            width: px(size.width.0 as f32),
+            height: px(size.height.0 as f32),
         }
     }
 }
@@ -759,6 +824,9 @@ impl Drop for X11Window {
         }
 
         state.renderer.destroy();
+        if let Some(gc) = state.software_gc.take() {
+            check_reply(|| "X11 FreeGC failed.", self.0.xcb.free_gc(gc)).log_err();
+        }
 
         let destroy_x_window = maybe!({
             check_reply(
@@ -800,7 +868,7 @@ impl X11Window {
         handle: AnyWindowHandle,
         client: X11ClientStatePtr,
         executor: ForegroundExecutor,
-        gpu_context: &BladeContext,
+        gpu_context: Option<&BladeContext>,
         params: WindowParams,
         xcb: &Rc<XCBConnection>,
         client_side_decorations_supported: bool,
@@ -1196,6 +1264,9 @@ impl X11WindowStatePtr {
     }
 
     pub fn set_active(&self, focus: bool) {
+        if let Some(accessibility) = self.state.borrow_mut().accessibility.as_mut() {
+            accessibility.set_active(focus);
+        }
         if let Some(ref mut fun) = self.callbacks.borrow_mut().active_status_change {
             fun(focus);
         }
@@ -1472,7 +1543,10 @@ impl PlatformWindow for X11Window {
             .upgrade()
             .map(|ref_cell| {
                 let state = ref_cell.borrow();
-                state.gpu_context.supports_dual_source_blending()
+                state
+                    .gpu_context
+                    .as_ref()
+                    .is_some_and(|gpu_context| gpu_context.supports_dual_source_blending())
             })
             .unwrap_or_default()
     }
@@ -1563,14 +1637,40 @@ impl PlatformWindow for X11Window {
         self.0.callbacks.borrow_mut().appearance_changed = Some(callback);
     }
 
+    fn update_accessibility_tree(&self, tree: &AccessibilityTree) {
+        let mut state = self.0.state.borrow_mut();
+        let (bounds, scale_factor) = (state.bounds, state.scale_factor);
+        if let Some(accessibility) = state.accessibility.as_mut() {
+            accessibility.update(tree, bounds, scale_factor);
+        }
+    }
+
+    fn on_accessibility(
+        &self,
+        on_activation: Box<dyn FnMut(bool)>,
+        on_action: Box<dyn FnMut(AccessibilityActionRequest)>,
+    ) {
+        let mut state = self.0.state.borrow_mut();
+        state.accessibility = Some(LinuxAccessibility::new(
+            &state.executor,
+            on_activation,
+            on_action,
+        ));
+    }
+
     fn draw(&self, scene: &Scene) {
         let mut inner = self.0.state.borrow_mut();
         inner.renderer.draw(scene);
+        if let Some(gc) = inner.software_gc
+            && let Some(frame) = inner.renderer.software_frame()
+        {
+            put_software_frame(&self.0.xcb, self.0.x_window, gc, inner.depth, frame).log_err();
+        }
     }
 
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
         let inner = self.0.state.borrow();
-        inner.renderer.sprite_atlas().clone()
+        inner.renderer.sprite_atlas()
     }
 
     fn show_window_menu(&self, position: Point<Pixels>) {
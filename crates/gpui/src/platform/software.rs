@@ -0,0 +1,9 @@
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+mod offscreen_window;
+mod software_atlas;
+mod software_renderer;
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub(crate) use offscreen_window::*;
+pub(crate) use software_atlas::*;
+pub(crate) use software_renderer::*;
@@ -0,0 +1,226 @@
+use super::SoftwareRenderer;
+use crate::{
+    AnyWindowHandle, Bounds, DevicePixels, DispatchEventResult, GpuSpecs, Modifiers, Pixels,
+    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
+    PromptButton, PromptLevel, RequestFrameOptions, Scene, Size, WindowAppearance,
+    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowParams, size,
+};
+use anyhow::Result;
+use futures::channel::oneshot;
+use image::RgbaImage;
+use parking_lot::Mutex;
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+use std::{rc::Rc, sync::Arc};
+
+/// The scale factor of offscreen windows. Screenshots are rendered at one image pixel per logical
+/// pixel, which keeps them small and independent of the machine running the tests.
+const OFFSCREEN_SCALE_FACTOR: f32 = 1.;
+
+pub(crate) struct OffscreenWindowState {
+    handle: AnyWindowHandle,
+    bounds: Bounds<Pixels>,
+    display: Option<Rc<dyn PlatformDisplay>>,
+    renderer: SoftwareRenderer,
+    background_appearance: WindowBackgroundAppearance,
+    input_handler: Option<PlatformInputHandler>,
+    is_fullscreen: bool,
+}
+
+/// A window that is never shown, whose frames are drawn by the [`SoftwareRenderer`]. It is used by
+/// the visual test platform on Linux, so that screenshots can be taken without a display server
+/// or a GPU, and by the headless platform, so that windows can still be rendered to images when
+/// there is no display.
+#[derive(Clone)]
+pub(crate) struct OffscreenWindow(Rc<Mutex<OffscreenWindowState>>);
+
+impl HasWindowHandle for OffscreenWindow {
+    fn window_handle(
+        &self,
+    ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+        Err(raw_window_handle::HandleError::NotSupported)
+    }
+}
+
+impl HasDisplayHandle for OffscreenWindow {
+    fn display_handle(
+        &self,
+    ) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+        Err(raw_window_handle::HandleError::NotSupported)
+    }
+}
+
+impl OffscreenWindow {
+    pub fn new(
+        handle: AnyWindowHandle,
+        params: WindowParams,
+        display: Option<Rc<dyn PlatformDisplay>>,
+    ) -> Self {
+        Self(Rc::new(Mutex::new(OffscreenWindowState {
+            handle,
+            bounds: params.bounds,
+            display,
+            renderer: SoftwareRenderer::new(device_size(params.bounds.size), false),
+            background_appearance: WindowBackgroundAppearance::Opaque,
+            input_handler: None,
+            is_fullscreen: false,
+        })))
+    }
+
+    pub fn handle(&self) -> AnyWindowHandle {
+        self.0.lock().handle
+    }
+}
+
+fn device_size(logical_size: Size<Pixels>) -> Size<DevicePixels> {
+    size(
+        DevicePixels((logical_size.width.0 * OFFSCREEN_SCALE_FACTOR).ceil() as i32),
+        DevicePixels((logical_size.height.0 * OFFSCREEN_SCALE_FACTOR).ceil() as i32),
+    )
+}
+
+impl PlatformWindow for OffscreenWindow {
+    fn bounds(&self) -> Bounds<Pixels> {
+        self.0.lock().bounds
+    }
+
+    fn is_maximized(&self) -> bool {
+        false
+    }
+
+    fn window_bounds(&self) -> WindowBounds {
+        WindowBounds::Windowed(self.bounds())
+    }
+
+    fn content_size(&self) -> Size<Pixels> {
+        self.bounds().size
+    }
+
+    fn resize(&mut self, size: Size<Pixels>) {
+        let mut lock = self.0.lock();
+        lock.bounds.size = size;
+        lock.renderer.update_drawable_size(device_size(size));
+    }
+
+    fn scale_factor(&self) -> f32 {
+        OFFSCREEN_SCALE_FACTOR
+    }
+
+    fn appearance(&self) -> WindowAppearance {
+        WindowAppearance::Light
+    }
+
+    fn display(&self) -> Option<Rc<dyn PlatformDisplay>> {
+        self.0.lock().display.clone()
+    }
+
+    fn mouse_position(&self) -> Point<Pixels> {
+        Point::default()
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        Modifiers::default()
+    }
+
+    fn capslock(&self) -> crate::Capslock {
+        crate::Capslock::default()
+    }
+
+    fn set_input_handler(&mut self, input_handler: PlatformInputHandler) {
+        self.0.lock().input_handler = Some(input_handler);
+    }
+
+    fn take_input_handler(&mut self) -> Option<PlatformInputHandler> {
+        self.0.lock().input_handler.take()
+    }
+
+    fn prompt(
+        &self,
+        _level: PromptLevel,
+        _msg: &str,
+        _detail: Option<&str>,
+        _answers: &[PromptButton],
+    ) -> Option<oneshot::Receiver<usize>> {
+        None
+    }
+
+    fn activate(&self) {}
+
+    fn is_active(&self) -> bool {
+        false
+    }
+
+    fn is_hovered(&self) -> bool {
+        false
+    }
+
+    fn background_appearance(&self) -> WindowBackgroundAppearance {
+        self.0.lock().background_appearance
+    }
+
+    fn set_title(&mut self, _title: &str) {}
+
+    fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
+        let mut lock = self.0.lock();
+        lock.background_appearance = background_appearance;
+        lock.renderer
+            .update_transparency(background_appearance != WindowBackgroundAppearance::Opaque);
+    }
+
+    fn minimize(&self) {}
+
+    fn zoom(&self) {}
+
+    fn toggle_fullscreen(&self) {
+        let mut lock = self.0.lock();
+        lock.is_fullscreen = !lock.is_fullscreen;
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.0.lock().is_fullscreen
+    }
+
+    // Frames are drawn synchronously when effects are flushed in tests, and events are dispatched
+    // to the window directly, so none of these callbacks are ever invoked.
+    fn on_request_frame(&self, _callback: Box<dyn FnMut(RequestFrameOptions)>) {}
+
+    fn on_input(&self, _callback: Box<dyn FnMut(PlatformInput) -> DispatchEventResult>) {}
+
+    fn on_active_status_change(&self, _callback: Box<dyn FnMut(bool)>) {}
+
+    fn on_hover_status_change(&self, _callback: Box<dyn FnMut(bool)>) {}
+
+    fn on_resize(&self, _callback: Box<dyn FnMut(Size<Pixels>, f32)>) {}
+
+    fn on_moved(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn on_should_close(&self, _callback: Box<dyn FnMut() -> bool>) {}
+
+    fn on_hit_test_window_control(&self, _callback: Box<dyn FnMut() -> Option<WindowControlArea>>) {
+    }
+
+    fn on_close(&self, _callback: Box<dyn FnOnce()>) {}
+
+    fn on_appearance_changed(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn draw(&self, scene: &Scene) {
+        self.0.lock().renderer.draw(scene);
+    }
+
+    fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
+        self.0.lock().renderer.sprite_atlas().clone()
+    }
+
+    fn is_subpixel_rendering_supported(&self) -> bool {
+        false
+    }
+
+    fn gpu_specs(&self) -> Option<GpuSpecs> {
+        Some(self.0.lock().renderer.gpu_specs())
+    }
+
+    fn update_ime_position(&self, _bounds: Bounds<Pixels>) {}
+
+    fn render_to_image(&self, scene: &Scene) -> Result<RgbaImage> {
+        self.0.lock().renderer.render_to_image(scene)
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{
+    AtlasKey, AtlasTextureId, AtlasTextureKind, AtlasTile, Bounds, DevicePixels, PlatformAtlas,
+    Point, Size, platform::AtlasTextureList,
+};
+use anyhow::Result;
+use collections::FxHashMap;
+use etagere::BucketedAtlasAllocator;
+use parking_lot::Mutex;
+use std::{borrow::Cow, ops, sync::Arc};
+
+/// An atlas whose textures live in main memory, for use by the [`SoftwareRenderer`].
+///
+/// [`SoftwareRenderer`]: super::SoftwareRenderer
+pub(crate) struct SoftwareAtlas(Mutex<SoftwareAtlasState>);
+
+struct SoftwareAtlasState {
+    storage: SoftwareAtlasStorage,
+    tiles_by_key: FxHashMap<AtlasKey, AtlasTile>,
+}
+
+/// The pixels of an atlas texture. Monochrome textures store one coverage byte per pixel, and
+/// polychrome and subpixel textures store four bytes per pixel in BGRA order.
+#[derive(Clone)]
+pub(crate) struct SoftwareTexture {
+    pub size: Size<DevicePixels>,
+    pub bytes_per_pixel: usize,
+    pub pixels: Arc<Vec<u8>>,
+}
+
+impl SoftwareTexture {
+    fn new(size: Size<DevicePixels>, bytes_per_pixel: usize) -> Self {
+        let len = size.width.0 as usize * size.height.0 as usize * bytes_per_pixel;
+        Self {
+            size,
+            bytes_per_pixel,
+            pixels: Arc::new(vec![0; len]),
+        }
+    }
+
+    fn write(&mut self, bounds: Bounds<DevicePixels>, bytes: &[u8]) {
+        let row_len = bounds.size.width.0 as usize * self.bytes_per_pixel;
+        let stride = self.size.width.0 as usize * self.bytes_per_pixel;
+        let pixels = Arc::make_mut(&mut self.pixels);
+        for (row, source) in bytes
+            .chunks_exact(row_len)
+            .take(bounds.size.height.0 as usize)
+            .enumerate()
+        {
+            let start = (bounds.origin.y.0 as usize + row) * stride
+                + bounds.origin.x.0 as usize * self.bytes_per_pixel;
+            pixels[start..start + row_len].copy_from_slice(source);
+        }
+    }
+}
+
+impl SoftwareAtlas {
+    pub(crate) fn new() -> Self {
+        SoftwareAtlas(Mutex::new(SoftwareAtlasState {
+            storage: SoftwareAtlasStorage::default(),
+            tiles_by_key: Default::default(),
+        }))
+    }
+
+    /// Returns the pixels of the texture with the given id. The pixels are shared with the atlas
+    /// until it next writes to the texture, so this is cheap to call once per batch.
+    pub(crate) fn texture(&self, id: AtlasTextureId) -> Option<SoftwareTexture> {
+        let lock = self.0.lock();
+        let textures = &lock.storage[id.kind].textures;
+        let texture = textures.get(id.index as usize)?.as_ref()?;
+        Some(texture.texture.clone())
+    }
+}
+
+impl PlatformAtlas for SoftwareAtlas {
+    fn get_or_insert_with<'a>(
+        &self,
+        key: &AtlasKey,
+        build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
+    ) -> Result<Option<AtlasTile>> {
+        let mut lock = self.0.lock();
+        if let Some(tile) = lock.tiles_by_key.get(key) {
+            Ok(Some(tile.clone()))
+        } else {
+            profiling::scope!("new tile");
+            let Some((size, bytes)) = build()? else {
+                return Ok(None);
+            };
+            let tile = lock.allocate(size, key.texture_kind());
+            lock.storage[tile.texture_id]
+                .texture
+                .write(tile.bounds, &bytes);
+            lock.tiles_by_key.insert(key.clone(), tile.clone());
+            Ok(Some(tile))
+        }
+    }
+
+    fn remove(&self, key: &AtlasKey) {
+        let mut lock = self.0.lock();
+
+        let Some(id) = lock.tiles_by_key.remove(key).map(|tile| tile.texture_id) else {
+            return;
+        };
+
+        let Some(texture_slot) = lock.storage[id.kind].textures.get_mut(id.index as usize) else {
+            return;
+        };
+
+        if let Some(mut texture) = texture_slot.take() {
+            texture.live_atlas_keys -= 1;
+            if texture.live_atlas_keys == 0 {
+                lock.storage[id.kind]
+                    .free_list
+                    .push(texture.id.index as usize);
+            } else {
+                *texture_slot = Some(texture);
+            }
+        }
+    }
+}
+
+impl SoftwareAtlasState {
+    fn allocate(&mut self, size: Size<DevicePixels>, texture_kind: AtlasTextureKind) -> AtlasTile {
+        {
+            let textures = &mut self.storage[texture_kind];
+
+            if let Some(tile) = textures
+                .iter_mut()
+                .rev()
+                .find_map(|texture| texture.allocate(size))
+            {
+                return tile;
+            }
+        }
+
+        let texture = self.push_texture(size, texture_kind);
+        texture.allocate(size).unwrap()
+    }
+
+    fn push_texture(
+        &mut self,
+        min_size: Size<DevicePixels>,
+        kind: AtlasTextureKind,
+    ) -> &mut SoftwareAtlasTexture {
+        const DEFAULT_ATLAS_SIZE: Size<DevicePixels> = Size {
+            width: DevicePixels(1024),
+            height: DevicePixels(1024),
+        };
+
+        let size = min_size.max(&DEFAULT_ATLAS_SIZE);
+        let bytes_per_pixel = match kind {
+            AtlasTextureKind::Monochrome => 1,
+            AtlasTextureKind::Subpixel | AtlasTextureKind::Polychrome => 4,
+        };
+
+        let texture_list = &mut self.storage[kind];
+        let index = texture_list.free_list.pop();
+
+        let atlas_texture = SoftwareAtlasTexture {
+            id: AtlasTextureId {
+                index: index.unwrap_or(texture_list.textures.len()) as u32,
+                kind,
+            },
+            allocator: BucketedAtlasAllocator::new(etagere::size2(
+                size.width.into(),
+                size.height.into(),
+            )),
+            texture: SoftwareTexture::new(size, bytes_per_pixel),
+            live_atlas_keys: 0,
+        };
+
+        if let Some(ix) = index {
+            texture_list.textures[ix] = Some(atlas_texture);
+            texture_list.textures.get_mut(ix).unwrap().as_mut().unwrap()
+        } else {
+            texture_list.textures.push(Some(atlas_texture));
+            texture_list.textures.last_mut().unwrap().as_mut().unwrap()
+        }
+    }
+}
+
+#[derive(Default)]
+struct SoftwareAtlasStorage {
+    monochrome_textures: AtlasTextureList<SoftwareAtlasTexture>,
+    subpixel_textures: AtlasTextureList<SoftwareAtlasTexture>,
+    polychrome_textures: AtlasTextureList<SoftwareAtlasTexture>,
+}
+
+impl ops::Index<AtlasTextureKind> for SoftwareAtlasStorage {
+    type Output = AtlasTextureList<SoftwareAtlasTexture>;
+    fn index(&self, kind: AtlasTextureKind) -> &Self::Output {
+        match kind {
+            AtlasTextureKind::Monochrome => &self.monochrome_textures,
+            AtlasTextureKind::Subpixel => &self.subpixel_textures,
+            AtlasTextureKind::Polychrome => &self.polychrome_textures,
+        }
+    }
+}
+
+impl ops::IndexMut<AtlasTextureKind> for SoftwareAtlasStorage {
+    fn index_mut(&mut self, kind: AtlasTextureKind) -> &mut Self::Output {
+        match kind {
+            AtlasTextureKind::Monochrome => &mut self.monochrome_textures,
+            AtlasTextureKind::Subpixel => &mut self.subpixel_textures,
+            AtlasTextureKind::Polychrome => &mut self.polychrome_textures,
+        }
+    }
+}
+
+impl ops::Index<AtlasTextureId> for SoftwareAtlasStorage {
+    type Output = SoftwareAtlasTexture;
+    fn index(&self, id: AtlasTextureId) -> &Self::Output {
+        self[id.kind][id.index as usize].as_ref().unwrap()
+    }
+}
+
+impl ops::IndexMut<AtlasTextureId> for SoftwareAtlasStorage {
+    fn index_mut(&mut self, id: AtlasTextureId) -> &mut Self::Output {
+        self[id.kind].textures[id.index as usize].as_mut().unwrap()
+    }
+}
+
+struct SoftwareAtlasTexture {
+    id: AtlasTextureId,
+    allocator: BucketedAtlasAllocator,
+    texture: SoftwareTexture,
+    live_atlas_keys: u32,
+}
+
+impl SoftwareAtlasTexture {
+    fn allocate(&mut self, size: Size<DevicePixels>) -> Option<AtlasTile> {
+        let allocation = self
+            .allocator
+            .allocate(etagere::size2(size.width.into(), size.height.into()))?;
+        let tile = AtlasTile {
+            texture_id: self.id,
+            tile_id: allocation.id.into(),
+            padding: 0,
+            bounds: Bounds {
+                origin: Point {
+                    x: DevicePixels(allocation.rectangle.min.x),
+                    y: DevicePixels(allocation.rectangle.min.y),
+                },
+                size,
+            },
+        };
+        self.live_atlas_keys += 1;
+        Some(tile)
+    }
+}
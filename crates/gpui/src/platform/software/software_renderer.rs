@@ -0,0 +1,1435 @@
+//! A renderer that rasterizes scenes on the CPU, for machines without a usable GPU and for
+//! offscreen rendering in tests.
+//!
+//! The per-pixel math mirrors the fragment shaders in `blade/shaders.wgsl`, so that frames look
+//! the same regardless of the backend that drew them. Pixels are shaded at their centers, and
+//! colors are blended in sRGB space into a premultiplied BGRA frame.
+
+use super::{SoftwareAtlas, SoftwareTexture};
+use crate::{
+    AtlasTile, Background, BackgroundTag, BorderStyle, Bounds, ColorSpace, Corners, DevicePixels,
+    GpuSpecs, Hsla, MonochromeSprite, Path, PolychromeSprite, PrimitiveBatch, Quad, Rgba,
+    ScaledPixels, Scene, Shadow, Size, SubpixelSprite, TransformationMatrix, Underline,
+    get_gamma_correction_ratios,
+};
+use anyhow::Result;
+use image::RgbaImage;
+use std::{
+    f32::consts::{FRAC_PI_2, FRAC_PI_4, PI},
+    ops::Range,
+    sync::Arc,
+};
+
+/// Signed distance field threshold for inclusion of pixels. 0.5 is the minimum distance between
+/// the center of the pixel and the edge.
+const ANTIALIAS_THRESHOLD: f32 = 0.5;
+
+/// Sample positions within a pixel used when rasterizing paths, matching the standard 4x MSAA
+/// pattern.
+const PATH_SAMPLES: [[f32; 2]; 4] = [
+    [0.375, 0.125],
+    [0.875, 0.375],
+    [0.125, 0.625],
+    [0.625, 0.875],
+];
+
+const GRAYSCALE_FACTORS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// A color in sRGB space with straight alpha.
+type Color = [f32; 4];
+
+pub(crate) struct SoftwareRenderer {
+    atlas: Arc<SoftwareAtlas>,
+    size: Size<DevicePixels>,
+    transparent: bool,
+    /// The last drawn frame, as premultiplied BGRA rows without padding.
+    pixels: Vec<u8>,
+    rendering_parameters: RenderingParameters,
+}
+
+impl SoftwareRenderer {
+    pub fn new(size: Size<DevicePixels>, transparent: bool) -> Self {
+        let mut this = Self {
+            atlas: Arc::new(SoftwareAtlas::new()),
+            size: Size::default(),
+            transparent,
+            pixels: Vec::new(),
+            rendering_parameters: RenderingParameters::from_env(),
+        };
+        this.update_drawable_size(size);
+        this
+    }
+
+    pub fn update_drawable_size(&mut self, size: Size<DevicePixels>) {
+        let size = Size {
+            width: size.width.max(DevicePixels(1)),
+            height: size.height.max(DevicePixels(1)),
+        };
+        if size != self.size {
+            self.size = size;
+            self.pixels = vec![0; self.width() * self.height() * 4];
+        }
+    }
+
+    pub fn update_transparency(&mut self, transparent: bool) {
+        self.transparent = transparent;
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.transparent
+    }
+
+    pub fn viewport_size(&self) -> Size<DevicePixels> {
+        self.size
+    }
+
+    pub fn sprite_atlas(&self) -> &Arc<SoftwareAtlas> {
+        &self.atlas
+    }
+
+    pub fn gpu_specs(&self) -> GpuSpecs {
+        GpuSpecs {
+            is_software_emulated: true,
+            device_name: "CPU".into(),
+            driver_name: "gpui software renderer".into(),
+            driver_info: String::new(),
+        }
+    }
+
+    /// The last drawn frame, as rows of premultiplied BGRA pixels. This is the layout of both
+    /// `ZPixmap` images on little-endian X11 servers and `ARGB8888` Wayland buffers.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn draw(&mut self, scene: &Scene) {
+        self.pixels.fill(0);
+
+        for batch in scene.batches() {
+            match batch {
+                PrimitiveBatch::Shadows(range) => {
+                    for shadow in &scene.shadows[range] {
+                        self.draw_shadow(shadow);
+                    }
+                }
+                PrimitiveBatch::Quads(range) => {
+                    for quad in &scene.quads[range] {
+                        self.draw_quad(quad);
+                    }
+                }
+                PrimitiveBatch::Paths(range) => {
+                    for path in &scene.paths[range] {
+                        self.draw_path(path);
+                    }
+                }
+                PrimitiveBatch::Underlines(range) => {
+                    for underline in &scene.underlines[range] {
+                        self.draw_underline(underline);
+                    }
+                }
+                PrimitiveBatch::MonochromeSprites { texture_id, range } => {
+                    let Some(texture) = self.atlas.texture(texture_id) else {
+                        continue;
+                    };
+                    for sprite in &scene.monochrome_sprites[range] {
+                        self.draw_monochrome_sprite(sprite, &texture);
+                    }
+                }
+                PrimitiveBatch::SubpixelSprites { texture_id, range } => {
+                    let Some(texture) = self.atlas.texture(texture_id) else {
+                        continue;
+                    };
+                    for sprite in &scene.subpixel_sprites[range] {
+                        self.draw_subpixel_sprite(sprite, &texture);
+                    }
+                }
+                PrimitiveBatch::PolychromeSprites { texture_id, range } => {
+                    let Some(texture) = self.atlas.texture(texture_id) else {
+                        continue;
+                    };
+                    for sprite in &scene.polychrome_sprites[range] {
+                        self.draw_polychrome_sprite(sprite, &texture);
+                    }
+                }
+                // Surfaces only exist on macOS.
+                PrimitiveBatch::Surfaces(_) => {}
+            }
+        }
+    }
+
+    /// Draws the scene and returns the frame as an image with straight alpha.
+    pub fn render_to_image(&mut self, scene: &Scene) -> Result<RgbaImage> {
+        self.draw(scene);
+        Ok(self.frame_image())
+    }
+
+    /// Returns the last drawn frame as an image with straight alpha. Opaque renderers produce
+    /// opaque images, as if the frame had been drawn over black.
+    pub fn frame_image(&self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width() as u32, self.height() as u32);
+        for (source, target) in self.pixels.chunks_exact(4).zip(image.pixels_mut()) {
+            let alpha = if self.transparent { source[3] } else { 255 };
+            let unpremultiply = |channel: u8| {
+                if alpha == 0 {
+                    0
+                } else {
+                    ((channel as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8
+                }
+            };
+            target.0 = [
+                unpremultiply(source[2]),
+                unpremultiply(source[1]),
+                unpremultiply(source[0]),
+                alpha,
+            ];
+        }
+        image
+    }
+
+    fn width(&self) -> usize {
+        self.size.width.0 as usize
+    }
+
+    fn height(&self) -> usize {
+        self.size.height.0 as usize
+    }
+
+    /// Returns the pixels whose centers lie within the given rectangle.
+    fn pixel_ranges(&self, rect: Rect) -> (Range<i32>, Range<i32>) {
+        let start_x = ((rect.left - 0.5).ceil() as i32).max(0);
+        let end_x = ((rect.right - 0.5).ceil() as i32).min(self.width() as i32);
+        let start_y = ((rect.top - 0.5).ceil() as i32).max(0);
+        let end_y = ((rect.bottom - 0.5).ceil() as i32).min(self.height() as i32);
+        (start_x..end_x.max(start_x), start_y..end_y.max(start_y))
+    }
+
+    fn blend(&mut self, x: i32, y: i32, color: Color, alpha: f32) {
+        let alpha = saturate(color[3] * alpha);
+        if alpha <= 0. {
+            return;
+        }
+        let ix = (y as usize * self.width() + x as usize) * 4;
+        let pixel = &mut self.pixels[ix..ix + 4];
+        for (channel, source) in [color[2], color[1], color[0]].into_iter().enumerate() {
+            pixel[channel] = to_byte(source * alpha + to_unit(pixel[channel]) * (1. - alpha));
+        }
+        pixel[3] = to_byte(alpha + to_unit(pixel[3]) * (1. - alpha));
+    }
+
+    /// Like `blend`, but with a separate coverage for each of the red, green and blue channels.
+    fn blend_subpixel(&mut self, x: i32, y: i32, color: Color, coverage: [f32; 3]) {
+        let alphas = coverage.map(|coverage| saturate(color[3] * coverage));
+        if alphas.iter().all(|alpha| *alpha <= 0.) {
+            return;
+        }
+        let ix = (y as usize * self.width() + x as usize) * 4;
+        let pixel = &mut self.pixels[ix..ix + 4];
+        for (channel, (source, alpha)) in [
+            (color[2], alphas[2]),
+            (color[1], alphas[1]),
+            (color[0], alphas[0]),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            pixel[channel] = to_byte(source * alpha + to_unit(pixel[channel]) * (1. - alpha));
+        }
+        let alpha = alphas.into_iter().fold(0., f32::max);
+        pixel[3] = to_byte(alpha + to_unit(pixel[3]) * (1. - alpha));
+    }
+
+    fn draw_quad(&mut self, quad: &Quad) {
+        let bounds = Rect::from(quad.bounds);
+        let (xs, ys) = self.pixel_ranges(bounds.intersect(quad.content_mask.bounds.into()));
+        let background = BackgroundShader::new(&quad.background);
+        let border_color = hsla_to_rgba(quad.border_color);
+        let unrounded = quad.corner_radii.top_left.0 == 0.
+            && quad.corner_radii.bottom_left.0 == 0.
+            && quad.corner_radii.top_right.0 == 0.
+            && quad.corner_radii.bottom_right.0 == 0.;
+        let borderless = quad.border_widths.top.0 == 0.
+            && quad.border_widths.left.0 == 0.
+            && quad.border_widths.right.0 == 0.
+            && quad.border_widths.bottom.0 == 0.;
+
+        for y in ys {
+            for x in xs.clone() {
+                let position = [x as f32 + 0.5, y as f32 + 0.5];
+                let background_color = background.color_at(position, bounds);
+                if borderless && unrounded {
+                    self.blend(x, y, background_color, 1.);
+                } else {
+                    let (color, alpha) =
+                        shade_quad(quad, background_color, border_color, unrounded, position);
+                    self.blend(x, y, color, alpha);
+                }
+            }
+        }
+    }
+
+    fn draw_shadow(&mut self, shadow: &Shadow) {
+        let blur_radius = shadow.blur_radius.0;
+        let margin = 3. * blur_radius;
+        let bounds = Rect::from(shadow.bounds);
+        let (xs, ys) = self.pixel_ranges(
+            bounds
+                .dilate(margin)
+                .intersect(shadow.content_mask.bounds.into()),
+        );
+        let color = hsla_to_rgba(shadow.color);
+        let half_size = [bounds.width() / 2., bounds.height() / 2.];
+        let center = [bounds.left + half_size[0], bounds.top + half_size[1]];
+
+        for y in ys {
+            for x in xs.clone() {
+                let position = [x as f32 + 0.5, y as f32 + 0.5];
+                let alpha = if blur_radius <= 0. {
+                    saturate(ANTIALIAS_THRESHOLD - quad_sdf(position, bounds, &shadow.corner_radii))
+                } else {
+                    let center_to_point = [position[0] - center[0], position[1] - center[1]];
+                    let corner_radius = pick_corner_radius(center_to_point, &shadow.corner_radii);
+
+                    // The signal is only non-zero in a limited range, so don't waste samples
+                    let low = center_to_point[1] - half_size[1];
+                    let high = center_to_point[1] + half_size[1];
+                    let start = (-margin).clamp(low, high);
+                    let end = margin.clamp(low, high);
+
+                    // Accumulate samples (we can get away with surprisingly few samples)
+                    let step = (end - start) / 4.;
+                    let mut sample_y = start + step * 0.5;
+                    let mut alpha = 0.;
+                    for _ in 0..4 {
+                        let blur = blur_along_x(
+                            center_to_point[0],
+                            center_to_point[1] - sample_y,
+                            blur_radius,
+                            corner_radius,
+                            half_size,
+                        );
+                        alpha += blur * gaussian(sample_y, blur_radius) * step;
+                        sample_y += step;
+                    }
+                    alpha
+                };
+                self.blend(x, y, color, alpha);
+            }
+        }
+    }
+
+    fn draw_path(&mut self, path: &Path<ScaledPixels>) {
+        let bounds = Rect::from(path.clipped_bounds());
+        let (xs, ys) = self.pixel_ranges(bounds);
+        if xs.is_empty() || ys.is_empty() {
+            return;
+        }
+
+        // Accumulate which samples of each pixel are covered by any of the path's triangles.
+        let mask_width = xs.len();
+        let mut mask = vec![0u8; mask_width * ys.len()];
+        for triangle in path.vertices.chunks_exact(3) {
+            let xy = [0, 1, 2].map(|ix| {
+                let position = triangle[ix].xy_position;
+                [position.x.0, position.y.0]
+            });
+            let st = [0, 1, 2].map(|ix| {
+                let position = triangle[ix].st_position;
+                [position.x, position.y]
+            });
+            let area = edge_function(xy[0], xy[1], xy[2]);
+            if area == 0. {
+                continue;
+            }
+
+            let min_x = (xy[0][0].min(xy[1][0]).min(xy[2][0]).floor() as i32).max(xs.start);
+            let max_x = (xy[0][0].max(xy[1][0]).max(xy[2][0]).ceil() as i32).min(xs.end);
+            let min_y = (xy[0][1].min(xy[1][1]).min(xy[2][1]).floor() as i32).max(ys.start);
+            let max_y = (xy[0][1].max(xy[1][1]).max(xy[2][1]).ceil() as i32).min(ys.end);
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let mask_ix = (y - ys.start) as usize * mask_width + (x - xs.start) as usize;
+                    for (sample_ix, offset) in PATH_SAMPLES.iter().enumerate() {
+                        let point = [x as f32 + offset[0], y as f32 + offset[1]];
+                        let weights = [
+                            edge_function(xy[1], xy[2], point) / area,
+                            edge_function(xy[2], xy[0], point) / area,
+                            edge_function(xy[0], xy[1], point) / area,
+                        ];
+                        if weights.iter().any(|weight| *weight < 0.) {
+                            continue;
+                        }
+                        let s =
+                            weights[0] * st[0][0] + weights[1] * st[1][0] + weights[2] * st[2][0];
+                        let t =
+                            weights[0] * st[0][1] + weights[1] * st[1][1] + weights[2] * st[2][1];
+                        // Curves are drawn as triangles whose inside is where `s² - t <= 0`.
+                        // Straight edges use coordinates for which this always holds.
+                        if s * s - t <= 0. {
+                            mask[mask_ix] |= 1 << sample_ix;
+                        }
+                    }
+                }
+            }
+        }
+
+        let background = BackgroundShader::new(&path.color);
+        for y in ys.clone() {
+            for x in xs.clone() {
+                let covered = mask[(y - ys.start) as usize * mask_width + (x - xs.start) as usize];
+                if covered == 0 {
+                    continue;
+                }
+                let coverage = covered.count_ones() as f32 / PATH_SAMPLES.len() as f32;
+                let color = background.color_at([x as f32 + 0.5, y as f32 + 0.5], bounds);
+                self.blend(x, y, color, coverage);
+            }
+        }
+    }
+
+    fn draw_underline(&mut self, underline: &Underline) {
+        const WAVE_FREQUENCY: f32 = 2.0;
+        const WAVE_HEIGHT_RATIO: f32 = 0.8;
+
+        let bounds = Rect::from(underline.bounds);
+        let (xs, ys) = self.pixel_ranges(bounds.intersect(underline.content_mask.bounds.into()));
+        let color = hsla_to_rgba(underline.color);
+        let wavy = underline.wavy & 0xFF != 0;
+        let height = bounds.height();
+        let thickness = underline.thickness.0;
+
+        for y in ys {
+            for x in xs.clone() {
+                if !wavy {
+                    self.blend(x, y, color, 1.);
+                    continue;
+                }
+
+                let half_thickness = thickness * 0.5;
+                let st = [
+                    (x as f32 + 0.5 - bounds.left) / height,
+                    (y as f32 + 0.5 - bounds.top) / height - 0.5,
+                ];
+                let frequency = PI * WAVE_FREQUENCY * thickness / height;
+                let amplitude = (thickness * WAVE_HEIGHT_RATIO) / height;
+
+                let sine = (st[0] * frequency).sin() * amplitude;
+                let d_sine = (st[0] * frequency).cos() * amplitude * frequency;
+                let distance = (st[1] - sine) / (1. + d_sine * d_sine).sqrt();
+                let distance_in_pixels = distance * height;
+                let distance_from_top_border = distance_in_pixels - half_thickness;
+                let distance_from_bottom_border = distance_in_pixels + half_thickness;
+                let alpha =
+                    saturate(0.5 - (-distance_from_bottom_border).max(distance_from_top_border));
+                self.blend(x, y, color, alpha);
+            }
+        }
+    }
+
+    fn draw_monochrome_sprite(&mut self, sprite: &MonochromeSprite, texture: &SoftwareTexture) {
+        let Some(mapping) = SpriteMapping::new(sprite.bounds, &sprite.transformation) else {
+            return;
+        };
+        let (xs, ys) = self.pixel_ranges(
+            mapping
+                .device_bounds()
+                .intersect(sprite.content_mask.bounds.into()),
+        );
+        let color = hsla_to_rgba(sprite.color);
+        let parameters = self.rendering_parameters;
+
+        for y in ys {
+            for x in xs.clone() {
+                let Some(unit) = mapping.unit_position([x as f32 + 0.5, y as f32 + 0.5]) else {
+                    continue;
+                };
+                let sample = sample_tile(texture, &sprite.tile, unit)[0];
+                let alpha = apply_contrast_and_gamma_correction(
+                    sample,
+                    [color[0], color[1], color[2]],
+                    parameters.grayscale_enhanced_contrast,
+                    parameters.gamma_ratios,
+                );
+                self.blend(x, y, color, alpha);
+            }
+        }
+    }
+
+    fn draw_subpixel_sprite(&mut self, sprite: &SubpixelSprite, texture: &SoftwareTexture) {
+        let Some(mapping) = SpriteMapping::new(sprite.bounds, &sprite.transformation) else {
+            return;
+        };
+        let (xs, ys) = self.pixel_ranges(
+            mapping
+                .device_bounds()
+                .intersect(sprite.content_mask.bounds.into()),
+        );
+        let color = hsla_to_rgba(sprite.color);
+        let parameters = self.rendering_parameters;
+
+        for y in ys {
+            for x in xs.clone() {
+                let Some(unit) = mapping.unit_position([x as f32 + 0.5, y as f32 + 0.5]) else {
+                    continue;
+                };
+                let [b, g, r, _] = sample_tile(texture, &sprite.tile, unit);
+                let coverage = [r, g, b].map(|sample| {
+                    apply_contrast_and_gamma_correction(
+                        sample,
+                        [color[0], color[1], color[2]],
+                        parameters.subpixel_enhanced_contrast,
+                        parameters.gamma_ratios,
+                    )
+                });
+                self.blend_subpixel(x, y, color, coverage);
+            }
+        }
+    }
+
+    fn draw_polychrome_sprite(&mut self, sprite: &PolychromeSprite, texture: &SoftwareTexture) {
+        let bounds = Rect::from(sprite.bounds);
+        let (xs, ys) = self.pixel_ranges(bounds.intersect(sprite.content_mask.bounds.into()));
+
+        for y in ys {
+            for x in xs.clone() {
+                let position = [x as f32 + 0.5, y as f32 + 0.5];
+                let unit = [
+                    (position[0] - bounds.left) / bounds.width(),
+                    (position[1] - bounds.top) / bounds.height(),
+                ];
+                let [b, g, r, a] = sample_tile(texture, &sprite.tile, unit);
+                let mut color = [r, g, b, a];
+                if sprite.grayscale {
+                    let grayscale = r * GRAYSCALE_FACTORS[0]
+                        + g * GRAYSCALE_FACTORS[1]
+                        + b * GRAYSCALE_FACTORS[2];
+                    color = [grayscale, grayscale, grayscale, a];
+                }
+                let distance = quad_sdf(position, bounds, &sprite.corner_radii);
+                self.blend(
+                    x,
+                    y,
+                    color,
+                    sprite.opacity * saturate(ANTIALIAS_THRESHOLD - distance),
+                );
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct RenderingParameters {
+    // Env var: ZED_FONTS_GAMMA
+    // Allowed range [1.0, 2.2], other values are clipped
+    // Default: 1.8
+    gamma_ratios: [f32; 4],
+    // Env var: ZED_FONTS_GRAYSCALE_ENHANCED_CONTRAST
+    // Allowed range: [0.0, ..), other values are clipped
+    // Default: 1.0
+    grayscale_enhanced_contrast: f32,
+    // Env var: ZED_FONTS_SUBPIXEL_ENHANCED_CONTRAST
+    // Allowed range: [0.0, ..), other values are clipped
+    // Default: 0.5
+    subpixel_enhanced_contrast: f32,
+}
+
+impl RenderingParameters {
+    fn from_env() -> Self {
+        use std::env;
+
+        let gamma = env::var("ZED_FONTS_GAMMA")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.8_f32)
+            .clamp(1.0, 2.2);
+        let gamma_ratios = get_gamma_correction_ratios(gamma);
+        let grayscale_enhanced_contrast = env::var("ZED_FONTS_GRAYSCALE_ENHANCED_CONTRAST")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0_f32)
+            .max(0.0);
+        let subpixel_enhanced_contrast = env::var("ZED_FONTS_SUBPIXEL_ENHANCED_CONTRAST")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.5_f32)
+            .max(0.0);
+
+        Self {
+            gamma_ratios,
+            grayscale_enhanced_contrast,
+            subpixel_enhanced_contrast,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rect {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+impl Rect {
+    fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    fn height(&self) -> f32 {
+        self.bottom - self.top
+    }
+
+    fn intersect(self, other: Rect) -> Rect {
+        Rect {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        }
+    }
+
+    fn dilate(self, amount: f32) -> Rect {
+        Rect {
+            left: self.left - amount,
+            top: self.top - amount,
+            right: self.right + amount,
+            bottom: self.bottom + amount,
+        }
+    }
+}
+
+impl From<Bounds<ScaledPixels>> for Rect {
+    fn from(bounds: Bounds<ScaledPixels>) -> Self {
+        Rect {
+            left: bounds.origin.x.0,
+            top: bounds.origin.y.0,
+            right: bounds.origin.x.0 + bounds.size.width.0,
+            bottom: bounds.origin.y.0 + bounds.size.height.0,
+        }
+    }
+}
+
+/// Maps device pixels back onto the unit square of a sprite, undoing its transformation.
+struct SpriteMapping {
+    bounds: Rect,
+    transformation: TransformationMatrix,
+    inverse: [[f32; 2]; 2],
+}
+
+impl SpriteMapping {
+    fn new(bounds: Bounds<ScaledPixels>, transformation: &TransformationMatrix) -> Option<Self> {
+        let [[a, b], [c, d]] = transformation.rotation_scale;
+        let determinant = a * d - b * c;
+        if determinant == 0. {
+            return None;
+        }
+        Some(Self {
+            bounds: bounds.into(),
+            transformation: *transformation,
+            inverse: [
+                [d / determinant, -b / determinant],
+                [-c / determinant, a / determinant],
+            ],
+        })
+    }
+
+    fn transform(&self, point: [f32; 2]) -> [f32; 2] {
+        let matrix = self.transformation.rotation_scale;
+        let translation = self.transformation.translation;
+        [
+            matrix[0][0] * point[0] + matrix[0][1] * point[1] + translation[0],
+            matrix[1][0] * point[0] + matrix[1][1] * point[1] + translation[1],
+        ]
+    }
+
+    /// The bounds of the transformed sprite in device pixels.
+    fn device_bounds(&self) -> Rect {
+        let corners = [
+            [self.bounds.left, self.bounds.top],
+            [self.bounds.right, self.bounds.top],
+            [self.bounds.left, self.bounds.bottom],
+            [self.bounds.right, self.bounds.bottom],
+        ]
+        .map(|corner| self.transform(corner));
+        let mut rect = Rect {
+            left: f32::INFINITY,
+            top: f32::INFINITY,
+            right: f32::NEG_INFINITY,
+            bottom: f32::NEG_INFINITY,
+        };
+        for [x, y] in corners {
+            rect.left = rect.left.min(x);
+            rect.top = rect.top.min(y);
+            rect.right = rect.right.max(x);
+            rect.bottom = rect.bottom.max(y);
+        }
+        rect
+    }
+
+    /// Returns where the given device position falls within the sprite, with both coordinates in
+    /// `0..1`, or `None` if it falls outside.
+    fn unit_position(&self, position: [f32; 2]) -> Option<[f32; 2]> {
+        let translation = self.transformation.translation;
+        let offset = [position[0] - translation[0], position[1] - translation[1]];
+        let point = [
+            self.inverse[0][0] * offset[0] + self.inverse[0][1] * offset[1],
+            self.inverse[1][0] * offset[0] + self.inverse[1][1] * offset[1],
+        ];
+        let unit = [
+            (point[0] - self.bounds.left) / self.bounds.width(),
+            (point[1] - self.bounds.top) / self.bounds.height(),
+        ];
+        (unit[0] >= 0. && unit[0] < 1. && unit[1] >= 0. && unit[1] < 1.).then_some(unit)
+    }
+}
+
+/// Bilinearly samples the tile at the given position within it, clamping to the tile's edges.
+/// Returns the texture's channels in storage order, so monochrome tiles only fill the first one.
+fn sample_tile(texture: &SoftwareTexture, tile: &AtlasTile, unit: [f32; 2]) -> [f32; 4] {
+    let origin = [tile.bounds.origin.x.0 as f32, tile.bounds.origin.y.0 as f32];
+    let size = [
+        tile.bounds.size.width.0 as f32,
+        tile.bounds.size.height.0 as f32,
+    ];
+    let u = (unit[0] * size[0] - 0.5).clamp(0., size[0] - 1.);
+    let v = (unit[1] * size[1] - 0.5).clamp(0., size[1] - 1.);
+    let x0 = u.floor();
+    let y0 = v.floor();
+    let x1 = (x0 + 1.).min(size[0] - 1.);
+    let y1 = (y0 + 1.).min(size[1] - 1.);
+    let fx = u - x0;
+    let fy = v - y0;
+
+    let stride = texture.size.width.0 as usize * texture.bytes_per_pixel;
+    let texel = |x: f32, y: f32| {
+        let ix =
+            (origin[1] + y) as usize * stride + (origin[0] + x) as usize * texture.bytes_per_pixel;
+        let mut channels = [0.; 4];
+        for (channel, value) in channels
+            .iter_mut()
+            .zip(&texture.pixels[ix..ix + texture.bytes_per_pixel])
+        {
+            *channel = to_unit(*value);
+        }
+        channels
+    };
+    let top = mix(texel(x0, y0), texel(x1, y0), fx);
+    let bottom = mix(texel(x0, y1), texel(x1, y1), fx);
+    mix(top, bottom, fy)
+}
+
+/// The colors of a background, converted once per primitive.
+struct BackgroundShader<'a> {
+    background: &'a Background,
+    solid: Color,
+    color0: Color,
+    color1: Color,
+}
+
+impl<'a> BackgroundShader<'a> {
+    fn new(background: &'a Background) -> Self {
+        let mut color0 = hsla_to_rgba(background.colors[0].color);
+        let mut color1 = hsla_to_rgba(background.colors[1].color);
+        if background.color_space == ColorSpace::Oklab {
+            color0 = srgb_to_oklab(color0);
+            color1 = srgb_to_oklab(color1);
+        }
+        Self {
+            background,
+            solid: hsla_to_rgba(background.solid),
+            color0,
+            color1,
+        }
+    }
+
+    fn color_at(&self, position: [f32; 2], bounds: Rect) -> Color {
+        match self.background.tag {
+            BackgroundTag::Solid => self.solid,
+            BackgroundTag::LinearGradient => {
+                // -90 degrees to match the CSS gradient angle.
+                let angle = self.background.gradient_angle_or_pattern_height;
+                let radians = (angle % 360. - 90.).to_radians();
+                let mut direction = [radians.cos(), radians.sin()];
+                let size = [bounds.width(), bounds.height()];
+
+                // Expand the short side to be the same as the long side
+                if size[0] > size[1] {
+                    direction[1] *= size[1] / size[0];
+                } else {
+                    direction[0] *= size[0] / size[1];
+                }
+
+                // Get the t value for the linear gradient with the color stop percentages.
+                let half_size = [size[0] / 2., size[1] / 2.];
+                let center_to_point = [
+                    position[0] - bounds.left - half_size[0],
+                    position[1] - bounds.top - half_size[1],
+                ];
+                let mut t = (center_to_point[0] * direction[0] + center_to_point[1] * direction[1])
+                    / (direction[0] * direction[0] + direction[1] * direction[1]).sqrt();
+                if direction[0].abs() > direction[1].abs() {
+                    t = (t + half_size[0]) / size[0];
+                } else {
+                    t = (t + half_size[1]) / size[1];
+                }
+
+                // Adjust t based on the stop percentages
+                let stops = &self.background.colors;
+                t = (t - stops[0].percentage) / (stops[1].percentage - stops[0].percentage);
+                let t = t.clamp(0., 1.);
+
+                let color = mix(self.color0, self.color1, t);
+                match self.background.color_space {
+                    ColorSpace::Srgb => color,
+                    ColorSpace::Oklab => oklab_to_srgb(color),
+                }
+            }
+            BackgroundTag::PatternSlash => {
+                let height = self.background.gradient_angle_or_pattern_height;
+                let pattern_width = (height / 65535.) / 255.;
+                let pattern_interval = (height % 65535.) / 255.;
+                let pattern_height = pattern_width + pattern_interval;
+                let pattern_period = pattern_height * FRAC_PI_4.sin();
+                let relative_position = [position[0] - bounds.left, position[1] - bounds.top];
+                let rotated_x =
+                    FRAC_PI_4.cos() * relative_position[0] + FRAC_PI_4.sin() * relative_position[1];
+                let pattern = rotated_x % pattern_period;
+                let distance = pattern.min(pattern_period - pattern)
+                    - pattern_period * (pattern_width / pattern_height) / 2.;
+                let mut color = self.solid;
+                color[3] *= saturate(0.5 - distance);
+                color
+            }
+            BackgroundTag::Checkerboard => {
+                let size = self.background.gradient_angle_or_pattern_height;
+                let x_index = ((position[0] - bounds.left) / size).floor();
+                let y_index = ((position[1] - bounds.top) / size).floor();
+                let mut color = self.solid;
+                color[3] *= saturate((x_index + y_index) % 2.);
+                color
+            }
+        }
+    }
+}
+
+/// Shades a pixel of a quad with borders or rounded corners, returning its color and coverage.
+fn shade_quad(
+    quad: &Quad,
+    background_color: Color,
+    border_color: Color,
+    unrounded: bool,
+    position: [f32; 2],
+) -> (Color, f32) {
+    let size = [quad.bounds.size.width.0, quad.bounds.size.height.0];
+    let half_size = [size[0] / 2., size[1] / 2.];
+    let point = [
+        position[0] - quad.bounds.origin.x.0,
+        position[1] - quad.bounds.origin.y.0,
+    ];
+    let center_to_point = [point[0] - half_size[0], point[1] - half_size[1]];
+
+    // Radius of the nearest corner
+    let corner_radius = pick_corner_radius(center_to_point, &quad.corner_radii);
+
+    // Width of the nearest borders
+    let widths = &quad.border_widths;
+    let border = [
+        if center_to_point[0] < 0. {
+            widths.left.0
+        } else {
+            widths.right.0
+        },
+        if center_to_point[1] < 0. {
+            widths.top.0
+        } else {
+            widths.bottom.0
+        },
+    ];
+
+    // 0-width borders are reduced so that `inner_sdf >= antialias_threshold`.
+    // The purpose of this is to not draw antialiasing pixels in this case.
+    let reduced_border = border.map(|width| {
+        if width == 0. {
+            -ANTIALIAS_THRESHOLD
+        } else {
+            width
+        }
+    });
+
+    // Vector from the corner of the quad bounds to the point, after mirroring
+    // the point into the bottom right quadrant. Both components are <= 0.
+    let corner_to_point = [
+        center_to_point[0].abs() - half_size[0],
+        center_to_point[1].abs() - half_size[1],
+    ];
+
+    // Vector from the point to the center of the rounded corner's circle, also
+    // mirrored into bottom right quadrant.
+    let corner_center_to_point = corner_to_point.map(|distance| distance + corner_radius);
+
+    // Whether the nearest point on the border is rounded
+    let is_near_rounded_corner = corner_center_to_point[0] >= 0. && corner_center_to_point[1] >= 0.;
+
+    // Vector from straight border inner corner to point.
+    let straight_border_inner_corner_to_point = [
+        corner_to_point[0] + reduced_border[0],
+        corner_to_point[1] + reduced_border[1],
+    ];
+
+    // Whether the point is beyond the inner edge of the straight border.
+    let is_beyond_inner_straight_border = straight_border_inner_corner_to_point[0] > 0.
+        || straight_border_inner_corner_to_point[1] > 0.;
+
+    // Whether the point is far enough inside the quad, such that the pixels are
+    // not affected by the straight border.
+    let is_within_inner_straight_border = straight_border_inner_corner_to_point[0]
+        < -ANTIALIAS_THRESHOLD
+        && straight_border_inner_corner_to_point[1] < -ANTIALIAS_THRESHOLD;
+
+    // Fast path for points that must be part of the background.
+    if is_within_inner_straight_border && !is_near_rounded_corner {
+        return (background_color, 1.);
+    }
+
+    // Signed distance of the point to the outside edge of the quad's border. It
+    // is positive outside this edge, and negative inside.
+    let outer_sdf = quad_sdf_impl(corner_center_to_point, corner_radius);
+
+    // Approximate signed distance of the point to the inside edge of the quad's
+    // border. It is negative outside this edge (within the border), and
+    // positive inside.
+    let inner_sdf = if corner_center_to_point[0] <= 0. || corner_center_to_point[1] <= 0. {
+        // Fast paths for straight borders.
+        -straight_border_inner_corner_to_point[0].max(straight_border_inner_corner_to_point[1])
+    } else if is_beyond_inner_straight_border {
+        // Fast path for points that must be outside the inner edge.
+        -1.
+    } else if reduced_border[0] == reduced_border[1] {
+        // Fast path for circular inner edge.
+        -(outer_sdf + reduced_border[0])
+    } else {
+        let ellipse_radii = [
+            (corner_radius - reduced_border[0]).max(0.),
+            (corner_radius - reduced_border[1]).max(0.),
+        ];
+        quarter_ellipse_sdf(corner_center_to_point, ellipse_radii)
+    };
+
+    // Negative when inside the border
+    let border_sdf = inner_sdf.max(outer_sdf);
+
+    let mut color = background_color;
+    if border_sdf < ANTIALIAS_THRESHOLD {
+        let mut border_color = border_color;
+        if quad.border_style == BorderStyle::Dashed {
+            border_color[3] *= dashed_border_alpha(
+                quad,
+                unrounded,
+                point,
+                center_to_point,
+                corner_center_to_point,
+                corner_radius,
+                is_near_rounded_corner,
+            );
+        }
+
+        // Blend the border on top of the background and then linearly interpolate
+        // between the two as we slide inside the background.
+        let blended_border = over(background_color, border_color);
+        color = mix(
+            background_color,
+            blended_border,
+            saturate(ANTIALIAS_THRESHOLD - inner_sdf),
+        );
+    }
+
+    (color, saturate(ANTIALIAS_THRESHOLD - outer_sdf))
+}
+
+/// Returns the alpha of a dashed border at the given point. See `fs_quad` in `shaders.wgsl` for
+/// how dashes are laid out.
+fn dashed_border_alpha(
+    quad: &Quad,
+    unrounded: bool,
+    point: [f32; 2],
+    center_to_point: [f32; 2],
+    corner_center_to_point: [f32; 2],
+    corner_radius: f32,
+    is_near_rounded_corner: bool,
+) -> f32 {
+    // Dash pattern: (2 * border width) dash, (1 * border width) gap
+    const DASH_LENGTH_PER_WIDTH: f32 = 2.;
+    const DASH_GAP_PER_WIDTH: f32 = 1.;
+    const DASH_PERIOD_PER_WIDTH: f32 = DASH_LENGTH_PER_WIDTH + DASH_GAP_PER_WIDTH;
+
+    let size = [quad.bounds.size.width.0, quad.bounds.size.height.0];
+    let widths = &quad.border_widths;
+    // Dividing this by the border width gives the dash velocity
+    let dv_numerator = 1. / DASH_PERIOD_PER_WIDTH;
+
+    let (t, mut max_t, dash_velocity) = if unrounded {
+        // When corners aren't rounded, the dashes are separately laid out on
+        // each straight line, rather than around the whole perimeter.
+        let is_horizontal = corner_center_to_point[0] < corner_center_to_point[1];
+        let border_width = if is_horizontal {
+            widths.bottom.0.max(widths.top.0)
+        } else {
+            widths.right.0.max(widths.left.0)
+        };
+        let dash_velocity = dv_numerator / border_width;
+        let (along, length) = if is_horizontal {
+            (point[0], size[0])
+        } else {
+            (point[1], size[1])
+        };
+        (along * dash_velocity, length * dash_velocity, dash_velocity)
+    } else {
+        // When corners are rounded, the dashes are laid out clockwise around
+        // the whole perimeter.
+        let radii = &quad.corner_radii;
+        let r_tr = radii.top_right.0;
+        let r_br = radii.bottom_right.0;
+        let r_bl = radii.bottom_left.0;
+        let r_tl = radii.top_left.0;
+
+        // Straight side dash velocities
+        let side_dash_velocity = |width: f32| {
+            if width <= 0. {
+                0.
+            } else {
+                dv_numerator / width
+            }
+        };
+        let dv_t = side_dash_velocity(widths.top.0);
+        let dv_r = side_dash_velocity(widths.right.0);
+        let dv_b = side_dash_velocity(widths.bottom.0);
+        let dv_l = side_dash_velocity(widths.left.0);
+
+        // Straight side lengths in dash space
+        let s_t = (size[0] - r_tl - r_tr) * dv_t;
+        let s_r = (size[1] - r_tr - r_br) * dv_r;
+        let s_b = (size[0] - r_br - r_bl) * dv_b;
+        let s_l = (size[1] - r_bl - r_tl) * dv_l;
+
+        let corner_dash_velocity_tr = corner_dash_velocity(dv_t, dv_r);
+        let corner_dash_velocity_br = corner_dash_velocity(dv_b, dv_r);
+        let corner_dash_velocity_bl = corner_dash_velocity(dv_b, dv_l);
+        let corner_dash_velocity_tl = corner_dash_velocity(dv_t, dv_l);
+
+        // Corner lengths in dash space
+        let c_tr = r_tr * FRAC_PI_2 * corner_dash_velocity_tr;
+        let c_br = r_br * FRAC_PI_2 * corner_dash_velocity_br;
+        let c_bl = r_bl * FRAC_PI_2 * corner_dash_velocity_bl;
+        let c_tl = r_tl * FRAC_PI_2 * corner_dash_velocity_tl;
+
+        // Cumulative dash space upto each segment
+        let upto_tr = s_t;
+        let upto_r = upto_tr + c_tr;
+        let upto_br = upto_r + s_r;
+        let upto_b = upto_br + c_br;
+        let upto_bl = upto_b + s_b;
+        let upto_l = upto_bl + c_bl;
+        let upto_tl = upto_l + s_l;
+        let max_t = upto_tl + c_tl;
+
+        let (t, dash_velocity) = if is_near_rounded_corner {
+            let radians = corner_center_to_point[1].atan2(corner_center_to_point[0]);
+            let corner_t = radians * corner_radius;
+
+            if center_to_point[0] >= 0. {
+                if center_to_point[1] < 0. {
+                    let dash_velocity = corner_dash_velocity_tr;
+                    (upto_r - corner_t * dash_velocity, dash_velocity)
+                } else {
+                    let dash_velocity = corner_dash_velocity_br;
+                    (upto_br + corner_t * dash_velocity, dash_velocity)
+                }
+            } else if center_to_point[1] >= 0. {
+                let dash_velocity = corner_dash_velocity_bl;
+                (upto_l - corner_t * dash_velocity, dash_velocity)
+            } else {
+                let dash_velocity = corner_dash_velocity_tl;
+                (upto_tl + corner_t * dash_velocity, dash_velocity)
+            }
+        } else {
+            // Straight borders
+            let is_horizontal = corner_center_to_point[0] < corner_center_to_point[1];
+            if is_horizontal {
+                if center_to_point[1] < 0. {
+                    ((point[0] - r_tl) * dv_t, dv_t)
+                } else {
+                    (upto_bl - (point[0] - r_bl) * dv_b, dv_b)
+                }
+            } else if center_to_point[0] < 0. {
+                (upto_tl - (point[1] - r_tl) * dv_l, dv_l)
+            } else {
+                (upto_r + (point[1] - r_tr) * dv_r, dv_r)
+            }
+        };
+        (t, max_t, dash_velocity)
+    };
+
+    let dash_length = DASH_LENGTH_PER_WIDTH / DASH_PERIOD_PER_WIDTH;
+
+    // Straight borders should start and end with a dash, so max_t is
+    // reduced to cause this.
+    if unrounded {
+        max_t -= dash_length;
+    }
+    if max_t >= 1. {
+        // Adjust dash gap to evenly divide max_t.
+        let dash_count = max_t.floor();
+        let dash_period = max_t / dash_count;
+        dash_alpha(t, dash_period, dash_length, dash_velocity)
+    } else if unrounded {
+        // When there isn't enough space for the full gap between the two
+        // start / end dashes of a straight border, reduce gap to make them fit.
+        let dash_gap = max_t - dash_length;
+        if dash_gap > 0. {
+            dash_alpha(t, dash_length + dash_gap, dash_length, dash_velocity)
+        } else {
+            1.
+        }
+    } else {
+        1.
+    }
+}
+
+// Returns the dash velocity of a corner given the dash velocity of the two
+// sides, by returning the slower velocity (larger dashes).
+fn corner_dash_velocity(dv1: f32, dv2: f32) -> f32 {
+    if dv1 == 0. {
+        dv2
+    } else if dv2 == 0. {
+        dv1
+    } else {
+        dv1.min(dv2)
+    }
+}
+
+// Returns alpha used to render antialiased dashes.
+// `t` is within the dash when `t % period < length`.
+fn dash_alpha(t: f32, period: f32, length: f32, dash_velocity: f32) -> f32 {
+    let half_period = period / 2.;
+    let half_length = length / 2.;
+    // Value in [-half_period, half_period].
+    // The dash is in [-half_length, half_length].
+    let centered = (t + half_period - half_length) % period - half_period;
+    // Signed distance for the dash, negative values are inside the dash.
+    let signed_distance = centered.abs() - half_length;
+    // Antialiased alpha based on the signed distance.
+    saturate(ANTIALIAS_THRESHOLD - signed_distance / dash_velocity)
+}
+
+// This approximates distance to the nearest point to a quarter ellipse in a way
+// that is sufficient for anti-aliasing when the ellipse is not very eccentric.
+// The components of `point` are expected to be positive.
+//
+// Negative on the outside and positive on the inside.
+fn quarter_ellipse_sdf(point: [f32; 2], radii: [f32; 2]) -> f32 {
+    // Scale the space to treat the ellipse like a unit circle.
+    let circle_vec = [point[0] / radii[0], point[1] / radii[1]];
+    let unit_circle_sdf =
+        (circle_vec[0] * circle_vec[0] + circle_vec[1] * circle_vec[1]).sqrt() - 1.;
+    // Approximate up-scaling of the length by using the average of the radii.
+    unit_circle_sdf * (radii[0] + radii[1]) * -0.5
+}
+
+// Selects corner radius based on quadrant.
+fn pick_corner_radius(center_to_point: [f32; 2], radii: &Corners<ScaledPixels>) -> f32 {
+    if center_to_point[0] < 0. {
+        if center_to_point[1] < 0. {
+            radii.top_left.0
+        } else {
+            radii.bottom_left.0
+        }
+    } else if center_to_point[1] < 0. {
+        radii.top_right.0
+    } else {
+        radii.bottom_right.0
+    }
+}
+
+// Signed distance of the point to the quad's border - positive outside the
+// border, and negative inside.
+fn quad_sdf(point: [f32; 2], bounds: Rect, corner_radii: &Corners<ScaledPixels>) -> f32 {
+    let half_size = [bounds.width() / 2., bounds.height() / 2.];
+    let center_to_point = [
+        point[0] - bounds.left - half_size[0],
+        point[1] - bounds.top - half_size[1],
+    ];
+    let corner_radius = pick_corner_radius(center_to_point, corner_radii);
+    let corner_center_to_point = [
+        center_to_point[0].abs() - half_size[0] + corner_radius,
+        center_to_point[1].abs() - half_size[1] + corner_radius,
+    ];
+    quad_sdf_impl(corner_center_to_point, corner_radius)
+}
+
+fn quad_sdf_impl(corner_center_to_point: [f32; 2], corner_radius: f32) -> f32 {
+    if corner_radius == 0. {
+        // Fast path for unrounded corners.
+        corner_center_to_point[0].max(corner_center_to_point[1])
+    } else {
+        // Signed distance of the point from a quad that is inset by corner_radius.
+        // It is negative inside this quad, and positive outside.
+        let outside = [
+            corner_center_to_point[0].max(0.),
+            corner_center_to_point[1].max(0.),
+        ];
+        let signed_distance_to_inset_quad = (outside[0] * outside[0] + outside[1] * outside[1])
+            .sqrt()
+            + corner_center_to_point[0]
+                .max(corner_center_to_point[1])
+                .min(0.);
+        signed_distance_to_inset_quad - corner_radius
+    }
+}
+
+// A standard gaussian function, used for weighting samples
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    (-(x * x) / (2. * sigma * sigma)).exp() / ((2. * PI).sqrt() * sigma)
+}
+
+// This approximates the error function, needed for the gaussian integral
+fn erf(v: f32) -> f32 {
+    let s = v.signum();
+    let a = v.abs();
+    let r1 = 1. + (0.278393 + (0.230389 + (0.000972 + 0.078108 * a) * a) * a) * a;
+    let r2 = r1 * r1;
+    s - s / (r2 * r2)
+}
+
+fn blur_along_x(x: f32, y: f32, sigma: f32, corner: f32, half_size: [f32; 2]) -> f32 {
+    let delta = (half_size[1] - corner - y.abs()).min(0.);
+    let curved = half_size[0] - corner + (corner * corner - delta * delta).max(0.).sqrt();
+    let scale = 0.5_f32.sqrt() / sigma;
+    let start = 0.5 + 0.5 * erf((x - curved) * scale);
+    let end = 0.5 + 0.5 * erf((x + curved) * scale);
+    end - start
+}
+
+// Contrast and gamma correction adapted from https://github.com/microsoft/terminal/blob/1283c0f5b99a2961673249fa77c6b986efb5086c/src/renderer/atlas/dwrite.hlsl
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+fn apply_contrast_and_gamma_correction(
+    sample: f32,
+    color: [f32; 3],
+    enhanced_contrast_factor: f32,
+    gamma_ratios: [f32; 4],
+) -> f32 {
+    // REC. 601 luminance coefficients for perceived brightness
+    let brightness = color[0] * 0.30 + color[1] * 0.59 + color[2] * 0.11;
+    let enhanced_contrast = enhanced_contrast_factor * saturate(4. * (0.75 - brightness));
+    let contrasted = sample * (enhanced_contrast + 1.) / (sample * enhanced_contrast + 1.);
+
+    let brightness_adjustment = gamma_ratios[0] * brightness + gamma_ratios[1];
+    let correction =
+        brightness_adjustment * contrasted + (gamma_ratios[2] * brightness + gamma_ratios[3]);
+    contrasted + contrasted * (1. - contrasted) * correction
+}
+
+fn edge_function(a: [f32; 2], b: [f32; 2], point: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (point[1] - a[1]) - (b[1] - a[1]) * (point[0] - a[0])
+}
+
+fn hsla_to_rgba(color: Hsla) -> Color {
+    let rgba = Rgba::from(color);
+    [rgba.r, rgba.g, rgba.b, rgba.a]
+}
+
+fn over(below: Color, above: Color) -> Color {
+    let alpha = above[3] + below[3] * (1. - above[3]);
+    if alpha <= 0. {
+        return [0.; 4];
+    }
+    let blend = |ix: usize| (above[ix] * above[3] + below[ix] * below[3] * (1. - above[3])) / alpha;
+    [blend(0), blend(1), blend(2), alpha]
+}
+
+fn mix(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|ix| a[ix] + (b[ix] - a[ix]) * t)
+}
+
+fn saturate(value: f32) -> f32 {
+    value.clamp(0., 1.)
+}
+
+fn to_unit(value: u8) -> f32 {
+    value as f32 / 255.
+}
+
+fn to_byte(value: f32) -> u8 {
+    (saturate(value) * 255. + 0.5) as u8
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value < 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value < 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Reference: https://bottosson.github.io/posts/oklab/#converting-from-linear-srgb-to-oklab
+fn srgb_to_oklab(color: Color) -> Color {
+    let [r, g, b] = [color[0], color[1], color[2]].map(srgb_to_linear);
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        color[3],
+    ]
+}
+
+fn oklab_to_srgb(color: Color) -> Color {
+    let l = color[0] + 0.3963377774 * color[1] + 0.2158037573 * color[2];
+    let m = color[0] - 0.1055613458 * color[1] - 0.0638541728 * color[2];
+    let s = color[0] - 0.0894841775 * color[1] - 1.2914855480 * color[2];
+    let [l, m, s] = [l * l * l, m * m * m, s * s * s];
+    let rgb = [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ]
+    .map(|channel| linear_to_srgb(saturate(channel)));
+    [rgb[0], rgb[1], rgb[2], color[3]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AtlasKey, ContentMask, Edges, PlatformAtlas, RenderSvgParams, black, point, px, red, size,
+        transparent_black,
+    };
+    use std::borrow::Cow;
+
+    fn bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<ScaledPixels> {
+        Bounds {
+            origin: point(ScaledPixels(x), ScaledPixels(y)),
+            size: size(ScaledPixels(width), ScaledPixels(height)),
+        }
+    }
+
+    fn quad(bounds: Bounds<ScaledPixels>, background: Hsla) -> Quad {
+        Quad {
+            bounds,
+            content_mask: ContentMask { bounds },
+            background: background.into(),
+            border_color: transparent_black(),
+            ..Default::default()
+        }
+    }
+
+    fn render(width: i32, height: i32, build: impl FnOnce(&mut Scene)) -> RgbaImage {
+        let mut renderer =
+            SoftwareRenderer::new(size(DevicePixels(width), DevicePixels(height)), true);
+        let mut scene = Scene::default();
+        build(&mut scene);
+        scene.finish();
+        renderer.render_to_image(&scene).unwrap()
+    }
+
+    #[test]
+    fn test_quads() {
+        let image = render(8, 8, |scene| {
+            scene.insert_primitive(quad(bounds(0., 0., 8., 8.), black()));
+            let mut inner = quad(bounds(2., 2., 4., 4.), red());
+            inner.content_mask.bounds = bounds(2., 2., 2., 4.);
+            scene.insert_primitive(inner);
+        });
+
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(2, 2).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 5).0, [255, 0, 0, 255]);
+        // Clipped by the content mask.
+        assert_eq!(image.get_pixel(4, 2).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(6, 6).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_rounded_quad_with_border() {
+        let image = render(20, 20, |scene| {
+            let mut quad = quad(bounds(0., 0., 20., 20.), red());
+            quad.corner_radii = Corners::all(ScaledPixels(10.));
+            quad.border_widths = Edges::all(ScaledPixels(2.));
+            quad.border_color = black();
+            scene.insert_primitive(quad);
+        });
+
+        // The corners are cut off, the edges are covered by the border, and the middle is filled.
+        assert_eq!(image.get_pixel(0, 0).0[3], 0);
+        assert_eq!(image.get_pixel(10, 0).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(10, 10).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_monochrome_sprites() {
+        let mut renderer = SoftwareRenderer::new(size(DevicePixels(4), DevicePixels(4)), true);
+        let key = AtlasKey::Svg(RenderSvgParams {
+            path: "icon.svg".into(),
+            size: size(DevicePixels(2), DevicePixels(1)),
+        });
+        let tile = renderer
+            .sprite_atlas()
+            .get_or_insert_with(&key, &mut || {
+                Ok(Some((
+                    size(DevicePixels(2), DevicePixels(1)),
+                    Cow::Borrowed(&[255, 0]),
+                )))
+            })
+            .unwrap()
+            .unwrap();
+
+        let mut scene = Scene::default();
+        let sprite_bounds = bounds(1., 1., 2., 1.);
+        scene.insert_primitive(MonochromeSprite {
+            order: 0,
+            pad: 0,
+            bounds: sprite_bounds,
+            content_mask: ContentMask {
+                bounds: sprite_bounds,
+            },
+            color: red(),
+            tile,
+            transformation: TransformationMatrix::unit(),
+        });
+        scene.finish();
+        let image = renderer.render_to_image(&scene).unwrap();
+
+        assert_eq!(image.get_pixel(1, 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(2, 1).0[3], 0);
+        assert_eq!(image.get_pixel(0, 0).0[3], 0);
+    }
+
+    #[test]
+    fn test_path() {
+        let image = render(10, 10, |scene| {
+            let mut path = Path::new(point(px(0.), px(0.)));
+            path.line_to(point(px(10.), px(0.)));
+            path.line_to(point(px(0.), px(10.)));
+            path.line_to(point(px(0.), px(0.)));
+            let mut path = path.scale(1.);
+            path.content_mask.bounds = bounds(0., 0., 10., 10.);
+            path.color = red().into();
+            scene.insert_primitive(path);
+        });
+
+        // The triangle covers the top-left half of the frame.
+        assert_eq!(image.get_pixel(1, 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(8, 8).0[3], 0);
+        let diagonal = image.get_pixel(4, 5).0[3];
+        assert!(diagonal > 0 && diagonal < 255, "{diagonal}");
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GpuSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, RequestFrameOptions, Size, TestPlatform, TileId, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowParams,
+    AccessibilityActionRequest, AccessibilityTree, AnyWindowHandle, AtlasKey, AtlasTextureId,
+    AtlasTile, Bounds, DispatchEventResult, GpuSpecs, Pixels, PlatformAtlas, PlatformDisplay,
+    PlatformInput, PlatformInputHandler, PlatformWindow, Point, PromptButton, RequestFrameOptions,
+    Size, TestPlatform, TileId, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowControlArea, WindowParams,
 };
 use collections::HashMap;
 use parking_lot::Mutex;
@@ -29,6 +30,9 @@ pub(crate) struct TestWindowState {
     moved_callback: Option<Box<dyn FnMut()>>,
     input_handler: Option<PlatformInputHandler>,
     is_fullscreen: bool,
+    pub(crate) accessibility_tree: Option<AccessibilityTree>,
+    accessibility_activation_callback: Option<Box<dyn FnMut(bool)>>,
+    accessibility_action_callback: Option<Box<dyn FnMut(AccessibilityActionRequest)>>,
 }
 
 #[derive(Clone)]
@@ -74,6 +78,9 @@ impl TestWindow {
             moved_callback: None,
             input_handler: None,
             is_fullscreen: false,
+            accessibility_tree: None,
+            accessibility_activation_callback: None,
+            accessibility_action_callback: None,
         })))
     }
 
@@ -109,6 +116,26 @@ impl TestWindow {
         self.0.lock().input_callback = Some(callback);
         !result.propagate
     }
+
+    pub(crate) fn simulate_accessibility_activation(&self, active: bool) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.accessibility_activation_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback(active);
+        self.0.lock().accessibility_activation_callback = Some(callback);
+    }
+
+    pub(crate) fn simulate_accessibility_action(&self, request: AccessibilityActionRequest) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.accessibility_action_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback(request);
+        self.0.lock().accessibility_action_callback = Some(callback);
+    }
 }
 
 impl PlatformWindow for TestWindow {
@@ -299,6 +326,20 @@ impl PlatformWindow for TestWindow {
 
     fn update_ime_position(&self, _bounds: Bounds<Pixels>) {}
 
+    fn update_accessibility_tree(&self, tree: &AccessibilityTree) {
+        self.0.lock().accessibility_tree = Some(tree.clone());
+    }
+
+    fn on_accessibility(
+        &self,
+        on_activation: Box<dyn FnMut(bool)>,
+        on_action: Box<dyn FnMut(AccessibilityActionRequest)>,
+    ) {
+        let mut lock = self.0.lock();
+        lock.accessibility_activation_callback = Some(on_activation);
+        lock.accessibility_action_callback = Some(on_action);
+    }
+
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         None
     }
@@ -1,40 +1,61 @@
-//! Visual test platform that combines real rendering (macOs-only for now) with controllable TestDispatcher.
+//! Visual test platform that combines real rendering with controllable TestDispatcher.
+//!
+//! On macOS, windows are rendered by Metal. On Linux, they are offscreen windows rendered by the
+//! software renderer, so visual tests don't need a display server or a GPU.
 //!
 //! This platform is used for visual tests that need:
-//! - Real rendering (e.g. Metal/compositor) for accurate screenshots
+//! - Real rendering (e.g. Metal/compositor or the software renderer) for accurate screenshots
 //! - Deterministic task scheduling via TestDispatcher
 //! - Controllable time via `advance_clock`
 
+#[cfg(target_os = "macos")]
+use crate::MacPlatform;
 #[cfg(feature = "screen-capture")]
 use crate::ScreenCaptureSource;
 use crate::{
     AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, ForegroundExecutor, Keymap,
-    MacPlatform, Menu, MenuItem, OwnedMenu, PathPromptOptions, Platform, PlatformDisplay,
+    Menu, MenuItem, OwnedMenu, PathPromptOptions, Platform, PlatformDisplay,
     PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem, PlatformWindow, Task,
     TestDispatcher, WindowAppearance, WindowParams,
 };
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+use crate::{
+    DummyKeyboardMapper, LinuxKeyboardLayout, TestDisplay, platform::software::OffscreenWindow,
+};
 use anyhow::Result;
 use futures::channel::oneshot;
 use parking_lot::Mutex;
 
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+use std::cell::RefCell;
 use std::{
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
 };
 
-/// A platform that combines real Mac rendering with controllable TestDispatcher.
+/// A platform that combines real rendering with controllable TestDispatcher.
 ///
 /// This allows visual tests to:
-/// - Render real UI via Metal for accurate screenshots
+/// - Render real UI via Metal (macOS) or the software renderer (Linux) for accurate screenshots
 /// - Control task scheduling deterministically via TestDispatcher
 /// - Advance simulated time for testing time-based behaviors (tooltips, animations, etc.)
 pub struct VisualTestPlatform {
     dispatcher: TestDispatcher,
     background_executor: BackgroundExecutor,
     foreground_executor: ForegroundExecutor,
+    #[cfg(target_os = "macos")]
     mac_platform: MacPlatform,
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    text_system: Arc<dyn PlatformTextSystem>,
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    display: Rc<dyn PlatformDisplay>,
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    active_window: RefCell<Option<AnyWindowHandle>>,
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    primary: Mutex<Option<ClipboardItem>>,
     clipboard: Mutex<Option<ClipboardItem>>,
+    #[cfg(target_os = "macos")]
     find_pasteboard: Mutex<Option<ClipboardItem>>,
 }
 
@@ -49,14 +70,22 @@ impl VisualTestPlatform {
         let background_executor = BackgroundExecutor::new(arc_dispatcher.clone());
         let foreground_executor = ForegroundExecutor::new(arc_dispatcher);
 
-        let mac_platform = MacPlatform::new(false);
-
         Self {
             dispatcher,
             background_executor,
             foreground_executor,
-            mac_platform,
+            #[cfg(target_os = "macos")]
+            mac_platform: MacPlatform::new(false),
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+            text_system: Arc::new(crate::CosmicTextSystem::new()),
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+            display: Rc::new(TestDisplay::new()),
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+            active_window: RefCell::new(None),
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+            primary: Mutex::new(None),
             clipboard: Mutex::new(None),
+            #[cfg(target_os = "macos")]
             find_pasteboard: Mutex::new(None),
         }
     }
@@ -77,7 +106,14 @@ impl Platform for VisualTestPlatform {
     }
 
     fn text_system(&self) -> Arc<dyn PlatformTextSystem> {
-        self.mac_platform.text_system()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.text_system()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            self.text_system.clone()
+        }
     }
 
     fn run(&self, _on_finish_launching: Box<dyn 'static + FnOnce()>) {
@@ -97,31 +133,78 @@ impl Platform for VisualTestPlatform {
     fn unhide_other_apps(&self) {}
 
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
-        self.mac_platform.displays()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.displays()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            vec![self.display.clone()]
+        }
     }
 
     fn primary_display(&self) -> Option<Rc<dyn PlatformDisplay>> {
-        self.mac_platform.primary_display()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.primary_display()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            Some(self.display.clone())
+        }
     }
 
     fn active_window(&self) -> Option<AnyWindowHandle> {
-        self.mac_platform.active_window()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.active_window()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            *self.active_window.borrow()
+        }
     }
 
     fn window_stack(&self) -> Option<Vec<AnyWindowHandle>> {
-        self.mac_platform.window_stack()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.window_stack()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            None
+        }
     }
 
     #[cfg(feature = "screen-capture")]
     fn is_screen_capture_supported(&self) -> bool {
-        self.mac_platform.is_screen_capture_supported()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.is_screen_capture_supported()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            false
+        }
     }
 
     #[cfg(feature = "screen-capture")]
     fn screen_capture_sources(
         &self,
     ) -> oneshot::Receiver<Result<Vec<Rc<dyn ScreenCaptureSource>>>> {
-        self.mac_platform.screen_capture_sources()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.screen_capture_sources()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            let (tx, rx) = oneshot::channel();
+            tx.send(Err(anyhow::anyhow!(
+                "screen capture is not supported in visual tests"
+            )))
+            .ok();
+            rx
+        }
     }
 
     fn open_window(
@@ -129,15 +212,37 @@ impl Platform for VisualTestPlatform {
         handle: AnyWindowHandle,
         options: WindowParams,
     ) -> Result<Box<dyn PlatformWindow>> {
-        self.mac_platform.open_window(handle, options)
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.open_window(handle, options)
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            *self.active_window.borrow_mut() = Some(handle);
+            Ok(Box::new(OffscreenWindow::new(
+                handle,
+                options,
+                Some(self.display.clone()),
+            )))
+        }
     }
 
     fn window_appearance(&self) -> WindowAppearance {
-        self.mac_platform.window_appearance()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.window_appearance()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            WindowAppearance::Light
+        }
     }
 
     fn open_url(&self, url: &str) {
-        self.mac_platform.open_url(url)
+        #[cfg(target_os = "macos")]
+        self.mac_platform.open_url(url);
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        let _ = url;
     }
 
     fn on_open_urls(&self, _callback: Box<dyn FnMut(Vec<String>)>) {}
@@ -170,11 +275,17 @@ impl Platform for VisualTestPlatform {
     }
 
     fn reveal_path(&self, path: &Path) {
-        self.mac_platform.reveal_path(path)
+        #[cfg(target_os = "macos")]
+        self.mac_platform.reveal_path(path);
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        let _ = path;
     }
 
     fn open_with_system(&self, path: &Path) {
-        self.mac_platform.open_with_system(path)
+        #[cfg(target_os = "macos")]
+        self.mac_platform.open_with_system(path);
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        let _ = path;
     }
 
     fn on_quit(&self, _callback: Box<dyn FnMut()>) {}
@@ -196,19 +307,43 @@ impl Platform for VisualTestPlatform {
     fn on_validate_app_menu_command(&self, _callback: Box<dyn FnMut(&dyn crate::Action) -> bool>) {}
 
     fn app_path(&self) -> Result<PathBuf> {
-        self.mac_platform.app_path()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.app_path()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            Ok(std::env::current_exe()?)
+        }
     }
 
     fn path_for_auxiliary_executable(&self, name: &str) -> Result<PathBuf> {
-        self.mac_platform.path_for_auxiliary_executable(name)
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.path_for_auxiliary_executable(name)
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            anyhow::bail!("auxiliary executable {name} is not available in visual tests")
+        }
     }
 
     fn set_cursor_style(&self, style: CursorStyle) {
-        self.mac_platform.set_cursor_style(style)
+        #[cfg(target_os = "macos")]
+        self.mac_platform.set_cursor_style(style);
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        let _ = style;
     }
 
     fn should_auto_hide_scrollbars(&self) -> bool {
-        self.mac_platform.should_auto_hide_scrollbars()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.should_auto_hide_scrollbars()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            false
+        }
     }
 
     fn read_from_clipboard(&self) -> Option<ClipboardItem> {
@@ -219,6 +354,16 @@ impl Platform for VisualTestPlatform {
         *self.clipboard.lock() = Some(item);
     }
 
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    fn read_from_primary(&self) -> Option<ClipboardItem> {
+        self.primary.lock().clone()
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    fn write_to_primary(&self, item: ClipboardItem) {
+        *self.primary.lock() = Some(item);
+    }
+
     #[cfg(target_os = "macos")]
     fn read_from_find_pasteboard(&self) -> Option<ClipboardItem> {
         self.find_pasteboard.lock().clone()
@@ -242,11 +387,25 @@ impl Platform for VisualTestPlatform {
     }
 
     fn keyboard_layout(&self) -> Box<dyn PlatformKeyboardLayout> {
-        self.mac_platform.keyboard_layout()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.keyboard_layout()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            Box::new(LinuxKeyboardLayout::new("us".into()))
+        }
     }
 
     fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper> {
-        self.mac_platform.keyboard_mapper()
+        #[cfg(target_os = "macos")]
+        {
+            self.mac_platform.keyboard_mapper()
+        }
+        #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+        {
+            Rc::new(DummyKeyboardMapper)
+        }
     }
 
     fn on_keyboard_layout_change(&self, _callback: Box<dyn FnMut()>) {}
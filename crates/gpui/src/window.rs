@@ -1,23 +1,26 @@
 #[cfg(any(feature = "inspector", debug_assertions))]
 use crate::Inspector;
 use crate::{
-    Action, AnyDrag, AnyElement, AnyImageCache, AnyTooltip, AnyView, App, AppContext, Arena, Asset,
+    AccessibilityAction, AccessibilityActionRequest, AccessibilityEntry, AccessibilityNodeId,
+    AccessibilityOperation, AccessibilityProperties, AccessibilityTree, Action, AnyDrag,
+    AnyElement, AnyImageCache, AnyTooltip, AnyView, App, AppContext, Arena, Asset,
     AsyncWindowContext, AvailableSpace, Background, BorderStyle, Bounds, BoxShadow, Capslock,
     Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
     DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
     FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla, InputHandler, IsZero,
     KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, LayoutId,
-    LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent,
-    MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
-    PlatformInputHandler, PlatformWindow, Point, PolychromeSprite, Priority, PromptButton,
-    PromptLevel, Quad, Render, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams,
-    Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y,
-    ScaledPixels, Scene, Shadow, SharedString, Size, StrikethroughStyle, Style, SubpixelSprite,
-    SubscriberSet, Subscription, SystemWindowTab, SystemWindowTabController, TabStopMap,
-    TaffyLayoutEngine, Task, TextRenderingMode, TextStyle, TextStyleRefinement, ThermalState,
-    TransformationMatrix, Underline, UnderlineStyle, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
-    point, prelude::*, px, rems, size, transparent_black,
+    LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton,
+    MouseDownEvent, MouseEvent, MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas,
+    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, PolychromeSprite,
+    Priority, PromptButton, PromptLevel, Quad, Render, RenderGlyphParams, RenderImage,
+    RenderImageParams, RenderSvgParams, Replay, ResizeEdge, Role, SMOOTH_SVG_SCALE_FACTOR,
+    SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, Shadow, SharedString, Size,
+    StrikethroughStyle, Style, SubpixelSprite, SubscriberSet, Subscription, SystemWindowTab,
+    SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task, TextRenderingMode, TextStyle,
+    TextStyleRefinement, ThermalState, TransformationMatrix, Underline, UnderlineStyle,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations,
+    WindowOptions, WindowParams, WindowTextSystem, platform::SoftwareRenderer, point, prelude::*,
+    px, rems, size, transparent_black,
 };
 use anyhow::{Context as _, Result, anyhow};
 use collections::{FxHashMap, FxHashSet};
@@ -752,6 +755,7 @@ pub(crate) struct Frame {
     #[cfg(any(feature = "inspector", debug_assertions))]
     pub(crate) inspector_hitboxes: FxHashMap<HitboxId, crate::InspectorElementId>,
     pub(crate) tab_stops: TabStopMap,
+    pub(crate) accessibility_operations: Vec<AccessibilityOperation>,
 }
 
 #[derive(Clone, Default)]
@@ -772,6 +776,7 @@ pub(crate) struct PaintIndex {
     cursor_styles_index: usize,
     accessed_element_states_index: usize,
     tab_handle_index: usize,
+    accessibility_operations_index: usize,
     line_layout_index: LineLayoutIndex,
 }
 
@@ -801,6 +806,7 @@ impl Frame {
             #[cfg(any(feature = "inspector", debug_assertions))]
             inspector_hitboxes: FxHashMap::default(),
             tab_stops: TabStopMap::default(),
+            accessibility_operations: Vec::new(),
         }
     }
 
@@ -817,6 +823,7 @@ impl Frame {
         self.window_control_hitboxes.clear();
         self.deferred_draws.clear();
         self.tab_stops.clear();
+        self.accessibility_operations.clear();
         self.focus = None;
 
         #[cfg(any(feature = "inspector", debug_assertions))]
@@ -949,6 +956,12 @@ pub struct Window {
     pub(crate) pending_input_observers: SubscriberSet<(), AnyObserver>,
     prompt: Option<RenderablePromptHandle>,
     pub(crate) client_inset: Option<Pixels>,
+    title: Option<SharedString>,
+    /// Whether an assistive technology is using the window. The accessibility tree is only built
+    /// while one is.
+    accessibility_active: bool,
+    accessibility_tree: Option<AccessibilityTree>,
+    accessibility_focus_handles: FxHashMap<AccessibilityNodeId, FocusHandle>,
     #[cfg(any(feature = "inspector", debug_assertions))]
     inspector: Option<Entity<Inspector>>,
 }
@@ -1116,6 +1129,9 @@ impl Window {
         } = options;
 
         let window_bounds = window_bounds.unwrap_or_else(|| default_bounds(display_id, cx));
+        let title = titlebar
+            .as_ref()
+            .and_then(|titlebar| titlebar.title.clone());
         let mut platform_window = cx.platform.open_window(
             handle,
             WindowParams {
@@ -1305,6 +1321,28 @@ impl Window {
                     .unwrap_or(DispatchEventResult::default())
             })
         });
+        platform_window.on_accessibility(
+            {
+                let mut cx = cx.to_async();
+                Box::new(move |active| {
+                    handle
+                        .update(&mut cx, |_, window, _| {
+                            window.set_accessibility_active(active)
+                        })
+                        .log_err();
+                })
+            },
+            {
+                let mut cx = cx.to_async();
+                Box::new(move |request| {
+                    handle
+                        .update(&mut cx, |_, window, cx| {
+                            window.perform_accessibility_action(request, cx)
+                        })
+                        .log_err();
+                })
+            },
+        );
         platform_window.on_hit_test_window_control({
             let mut cx = cx.to_async();
             Box::new(move || {
@@ -1432,6 +1470,10 @@ impl Window {
             pending_input_observers: SubscriberSet::new(),
             prompt: None,
             client_inset: None,
+            title,
+            accessibility_active: false,
+            accessibility_tree: None,
+            accessibility_focus_handles: FxHashMap::default(),
             image_cache_stack: Vec::new(),
             #[cfg(any(feature = "inspector", debug_assertions))]
             inspector: None,
@@ -1907,6 +1949,76 @@ impl Window {
             .render_to_image(&self.rendered_frame.scene)
     }
 
+    /// Lays out and paints the element returned by `build` offscreen, and rasterizes it on the
+    /// CPU into an image of `size` logical pixels scaled by `scale_factor`. The element is drawn
+    /// over a transparent background with the window's rem size and text style, and doesn't
+    /// receive any input. This is used to export parts of the window as pictures.
+    ///
+    /// Returns an error when called while the window is being drawn.
+    pub fn render_element_to_image<E: IntoElement>(
+        &mut self,
+        size: Size<Pixels>,
+        scale_factor: f32,
+        cx: &mut App,
+        build: impl FnOnce(&mut Window, &mut App) -> E,
+    ) -> Result<image::RgbaImage> {
+        anyhow::ensure!(
+            self.invalidator.not_drawing(),
+            "cannot render an element to an image while the window is being drawn"
+        );
+        let device_size =
+            size.map(|length| DevicePixels((f32::from(length) * scale_factor).ceil() as i32));
+        anyhow::ensure!(
+            device_size.width.0 > 0 && device_size.height.0 > 0,
+            "cannot render an element to an empty image"
+        );
+
+        // Paint into a frame of our own, so that the window's next frame isn't affected, and
+        // rasterize glyphs and images into the software renderer's atlas.
+        let mut renderer = SoftwareRenderer::new(device_size, true);
+        let window_frame = mem::replace(
+            &mut self.next_frame,
+            Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
+        );
+        let window_sprite_atlas = mem::replace(
+            &mut self.sprite_atlas,
+            renderer.sprite_atlas().clone() as Arc<dyn PlatformAtlas>,
+        );
+        let window_viewport_size = mem::replace(&mut self.viewport_size, size);
+        let window_scale_factor = mem::replace(&mut self.scale_factor, scale_factor);
+        // Views must not reuse what they painted in the window's frame.
+        self.refreshing = true;
+
+        {
+            let _arena_scope = ElementArenaScope::enter(&cx.element_arena);
+            let mut element = build(self, cx).into_any_element();
+            self.invalidator.set_phase(DrawPhase::Prepaint);
+            element.prepaint_as_root(Point::default(), size.into(), self, cx);
+            self.invalidator.set_phase(DrawPhase::Paint);
+            element.paint(self, cx);
+            self.invalidator.set_phase(DrawPhase::None);
+        }
+        ArenaClearNeeded::new(&cx.element_arena).clear();
+        self.layout_engine.as_mut().unwrap().clear();
+
+        let mut offscreen_frame = mem::replace(&mut self.next_frame, window_frame);
+        self.sprite_atlas = window_sprite_atlas;
+        self.viewport_size = window_viewport_size;
+        self.scale_factor = window_scale_factor;
+        // Hand the state of elements that were painted offscreen back to the window, and draw it
+        // again from scratch.
+        for (key, state) in offscreen_frame.element_states.drain() {
+            self.rendered_frame
+                .element_states
+                .entry(key)
+                .or_insert(state);
+        }
+        self.refresh();
+
+        offscreen_frame.scene.finish();
+        renderer.render_to_image(&offscreen_frame.scene)
+    }
+
     /// Set the content size of the window.
     pub fn resize(&mut self, size: Size<Pixels>) {
         self.platform_window.resize(size);
@@ -1997,6 +2109,7 @@ impl Window {
     /// Updates the window's title at the platform level.
     pub fn set_window_title(&mut self, title: &str) {
         self.platform_window.set_title(title);
+        self.title = Some(SharedString::new(title));
     }
 
     /// Sets the application identifier.
@@ -2228,6 +2341,7 @@ impl Window {
         debug_assert!(self.rendered_entity_stack.is_empty());
         self.record_entities_accessed(cx);
         self.reset_cursor_style(cx);
+        self.update_accessibility_tree();
         self.refreshing = false;
         self.invalidator.set_phase(DrawPhase::None);
         self.needs_present.set(true);
@@ -2235,6 +2349,96 @@ impl Window {
         ArenaClearNeeded::new(&cx.element_arena)
     }
 
+    fn update_accessibility_tree(&mut self) {
+        if !self.accessibility_active {
+            return;
+        }
+        let mut root = AccessibilityProperties::new(Role::Window);
+        root.name = self.title.clone();
+        let (tree, focus_handles) = AccessibilityTree::build(
+            root,
+            Bounds::new(Point::default(), self.viewport_size),
+            &self.rendered_frame.accessibility_operations,
+            self.focus,
+        );
+        self.accessibility_focus_handles = focus_handles;
+        if self.accessibility_tree.as_ref() != Some(&tree) {
+            self.platform_window.update_accessibility_tree(&tree);
+            self.accessibility_tree = Some(tree);
+        }
+    }
+
+    /// Returns whether an assistive technology, such as a screen reader, is using the window.
+    /// Elements only need to compute what they expose to assistive technologies while one is.
+    pub fn is_accessibility_active(&self) -> bool {
+        self.accessibility_active
+    }
+
+    fn set_accessibility_active(&mut self, active: bool) {
+        if self.accessibility_active == active {
+            return;
+        }
+        self.accessibility_active = active;
+        if active {
+            // Repaint every element, including cached views, so that they all add their nodes.
+            self.refresh();
+        } else {
+            self.accessibility_tree = None;
+            self.accessibility_focus_handles.clear();
+        }
+    }
+
+    /// Returns the elements of the window that were exposed to assistive technologies in the
+    /// last frame.
+    pub fn accessibility_tree(&self) -> Option<&AccessibilityTree> {
+        self.accessibility_tree.as_ref()
+    }
+
+    /// Performs an action requested by an assistive technology, such as a screen reader.
+    pub fn perform_accessibility_action(
+        &mut self,
+        request: AccessibilityActionRequest,
+        cx: &mut App,
+    ) {
+        let Some(bounds) = self
+            .accessibility_tree
+            .as_ref()
+            .and_then(|tree| tree.node(request.node))
+            .map(|node| node.bounds)
+        else {
+            return;
+        };
+        match request.action {
+            AccessibilityAction::Focus => {
+                if let Some(focus_handle) = self.accessibility_focus_handles.get(&request.node) {
+                    self.focus(&focus_handle.clone(), cx);
+                }
+            }
+            AccessibilityAction::Click => {
+                let position = bounds.center();
+                self.dispatch_event(
+                    PlatformInput::MouseDown(MouseDownEvent {
+                        button: MouseButton::Left,
+                        position,
+                        modifiers: Modifiers::default(),
+                        click_count: 1,
+                        first_mouse: false,
+                    }),
+                    cx,
+                );
+                self.dispatch_event(
+                    PlatformInput::MouseUp(MouseUpEvent {
+                        button: MouseButton::Left,
+                        position,
+                        modifiers: Modifiers::default(),
+                        click_count: 1,
+                    }),
+                    cx,
+                );
+            }
+        }
+    }
+
     fn record_entities_accessed(&mut self, cx: &mut App) {
         let mut entities_ref = cx.entities.accessed_entities.borrow_mut();
         let mut entities = mem::take(entities_ref.deref_mut());
@@ -2549,6 +2753,7 @@ impl Window {
             cursor_styles_index: self.next_frame.cursor_styles.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
             tab_handle_index: self.next_frame.tab_stops.paint_index(),
+            accessibility_operations_index: self.next_frame.accessibility_operations.len(),
             line_layout_index: self.text_system.layout_index(),
         }
     }
@@ -2582,6 +2787,10 @@ impl Window {
             &self.rendered_frame.tab_stops.insertion_history
                 [range.start.tab_handle_index..range.end.tab_handle_index],
         );
+        self.next_frame.accessibility_operations.extend_from_slice(
+            &self.rendered_frame.accessibility_operations[range.start.accessibility_operations_index
+                ..range.end.accessibility_operations_index],
+        );
 
         self.text_system
             .reuse_layouts(range.start.line_layout_index..range.end.line_layout_index);
@@ -3010,6 +3219,46 @@ impl Window {
         }
     }
 
+    /// Adds a node to the window's accessibility tree. Nodes pushed before the matching call to
+    /// [`Self::pop_accessibility_node`] become its children. Elements built on `div` should use
+    /// [`InteractiveElement::role`](crate::InteractiveElement::role) instead. Does nothing unless
+    /// [`Self::is_accessibility_active`].
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn push_accessibility_node(
+        &mut self,
+        id: Option<AccessibilityNodeId>,
+        properties: AccessibilityProperties,
+        bounds: Bounds<Pixels>,
+        focus_handle: Option<FocusHandle>,
+    ) {
+        self.invalidator.debug_assert_paint();
+        if !self.accessibility_active {
+            return;
+        }
+        self.next_frame
+            .accessibility_operations
+            .push(AccessibilityOperation::Push(AccessibilityEntry {
+                id,
+                properties,
+                bounds,
+                focus_handle,
+            }));
+    }
+
+    /// Ends the node added by the last call to [`Self::push_accessibility_node`].
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn pop_accessibility_node(&mut self) {
+        self.invalidator.debug_assert_paint();
+        if !self.accessibility_active {
+            return;
+        }
+        self.next_frame
+            .accessibility_operations
+            .push(AccessibilityOperation::Pop);
+    }
+
     /// Defers the drawing of the given element, scheduling it to be painted on top of the currently-drawn tree
     /// at a later time. The `priority` parameter determines the drawing order relative to other deferred elements,
     /// with higher values being drawn on top.
@@ -5494,3 +5743,28 @@ pub fn outline(
         border_style,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as gpui, ParentElement, Styled, TestAppContext, div, px, rgb, size};
+
+    #[gpui::test]
+    fn test_render_element_to_image(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let image = cx
+            .update(|window, cx| {
+                window.render_element_to_image(size(px(4.), px(2.)), 2., cx, |_, _| {
+                    div()
+                        .size_full()
+                        .flex()
+                        .child(div().w(px(2.)).h_full().bg(rgb(0xff0000)))
+                        .child(div().w(px(2.)).h_full().bg(rgb(0x0000ff)))
+                })
+            })
+            .unwrap();
+
+        assert_eq!(image.dimensions(), (8, 4));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(7, 3).0, [0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,315 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use language::File as _;
+use project::{File, Project, ProjectEntryId, ProjectItem, ProjectPath};
+
+/// File extensions that are opened in the hex editor instead of as text.
+const BINARY_FILE_EXTENSIONS: &[&str] = &[
+    "a", "bin", "class", "dat", "dll", "dylib", "exe", "ko", "lib", "o", "obj", "pyc", "so", "wasm",
+];
+
+pub enum BinaryFileEvent {
+    Edited,
+    Saved,
+    Reloaded,
+}
+
+/// A single byte overwrite, recorded so that it can be undone.
+#[derive(Clone, Copy, Debug)]
+struct ByteEdit {
+    offset: usize,
+    old: u8,
+    new: u8,
+}
+
+/// The contents of a file opened in the hex editor, along with its edit history.
+pub struct BinaryFile {
+    file: Arc<File>,
+    data: Vec<u8>,
+    undo_stack: Vec<ByteEdit>,
+    redo_stack: Vec<ByteEdit>,
+    /// The depth of the undo stack when the file was last saved or loaded, or `None` if that
+    /// state can no longer be reached by undoing or redoing.
+    saved_depth: Option<usize>,
+}
+
+impl EventEmitter<BinaryFileEvent> for BinaryFile {}
+
+impl BinaryFile {
+    pub fn open(
+        project: &Entity<Project>,
+        path: ProjectPath,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let Some(worktree) = project.read(cx).worktree_for_id(path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow::anyhow!("no worktree for path {path:?}")));
+        };
+        let load = worktree.update(cx, |worktree, cx| worktree.load_binary_file(&path.path, cx));
+        cx.spawn(async move |cx| {
+            let loaded = load.await?;
+            Ok(cx.new(|_| Self::new(loaded.file, loaded.content)))
+        })
+    }
+
+    pub fn new(file: Arc<File>, data: Vec<u8>) -> Self {
+        Self {
+            file,
+            data,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            saved_depth: Some(0),
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn file(&self) -> &Arc<File> {
+        &self.file
+    }
+
+    pub fn abs_path(&self, cx: &App) -> Option<PathBuf> {
+        Some(self.file.as_local()?.abs_path(cx))
+    }
+
+    pub fn project_path(&self, cx: &App) -> ProjectPath {
+        ProjectPath {
+            worktree_id: self.file.worktree_id(cx),
+            path: self.file.path().clone(),
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.saved_depth != Some(self.undo_stack.len())
+    }
+
+    /// Overwrites the byte at `offset`. Offsets past the end of the file are ignored, as the
+    /// hex editor never changes the length of a file.
+    pub fn overwrite(&mut self, offset: usize, byte: u8, cx: &mut Context<Self>) {
+        let Some(old) = self.data.get(offset).copied() else {
+            return;
+        };
+        if old == byte {
+            return;
+        }
+        if self
+            .saved_depth
+            .is_some_and(|depth| depth > self.undo_stack.len())
+        {
+            self.saved_depth = None;
+        }
+        self.redo_stack.clear();
+        self.data[offset] = byte;
+        self.undo_stack.push(ByteEdit {
+            offset,
+            old,
+            new: byte,
+        });
+        cx.emit(BinaryFileEvent::Edited);
+        cx.notify();
+    }
+
+    /// Reverts the most recent edit, returning its offset.
+    pub fn undo(&mut self, cx: &mut Context<Self>) -> Option<usize> {
+        let edit = self.undo_stack.pop()?;
+        self.data[edit.offset] = edit.old;
+        self.redo_stack.push(edit);
+        cx.emit(BinaryFileEvent::Edited);
+        cx.notify();
+        Some(edit.offset)
+    }
+
+    /// Reapplies the most recently undone edit, returning its offset.
+    pub fn redo(&mut self, cx: &mut Context<Self>) -> Option<usize> {
+        let edit = self.redo_stack.pop()?;
+        self.data[edit.offset] = edit.new;
+        self.undo_stack.push(edit);
+        cx.emit(BinaryFileEvent::Edited);
+        cx.notify();
+        Some(edit.offset)
+    }
+
+    pub fn save(&mut self, project: &Entity<Project>, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(abs_path) = self.abs_path(cx) else {
+            return Task::ready(Err(anyhow::anyhow!("cannot save a remote binary file")));
+        };
+        let fs = project.read(cx).fs().clone();
+        let data = self.data.clone();
+        let depth = self.undo_stack.len();
+        cx.spawn(async move |this, cx| {
+            fs.atomic_write_bytes(abs_path.clone(), data)
+                .await
+                .with_context(|| format!("saving {abs_path:?}"))?;
+            this.update(cx, |this, cx| {
+                this.saved_depth = Some(depth);
+                cx.emit(BinaryFileEvent::Saved);
+                cx.notify();
+            })
+        })
+    }
+
+    /// Replaces the contents with those on disk, discarding the edit history.
+    pub fn reload(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(local_file) = self.file.as_local() else {
+            return Task::ready(Ok(()));
+        };
+        let load = local_file.load_bytes(cx);
+        cx.spawn(async move |this, cx| {
+            let data = load.await?;
+            this.update(cx, |this, cx| {
+                this.data = data;
+                this.undo_stack.clear();
+                this.redo_stack.clear();
+                this.saved_depth = Some(0);
+                cx.emit(BinaryFileEvent::Reloaded);
+                cx.notify();
+            })
+        })
+    }
+}
+
+pub fn is_binary_file_path(path: &ProjectPath) -> bool {
+    path.path.extension().is_some_and(|extension| {
+        BINARY_FILE_EXTENSIONS
+            .iter()
+            .any(|binary| extension.eq_ignore_ascii_case(binary))
+    })
+}
+
+impl ProjectItem for BinaryFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        is_binary_file_path(path).then(|| Self::open(project, path.clone(), cx))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.file.entry_id
+    }
+
+    fn project_path(&self, cx: &App) -> Option<ProjectPath> {
+        Some(self.project_path(cx))
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.is_dirty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::{path, rel_path::rel_path};
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+    }
+
+    async fn open_test_file(
+        cx: &mut TestAppContext,
+    ) -> (Arc<FakeFs>, Entity<Project>, Entity<BinaryFile>) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), json!({})).await;
+        fs.insert_file(path!("/root/data.bin"), vec![0x00, 0x01, 0x02, 0x03])
+            .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let binary_file = cx
+            .update(|cx| {
+                BinaryFile::open(
+                    &project,
+                    ProjectPath::from((worktree_id, rel_path("data.bin"))),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        (fs, project, binary_file)
+    }
+
+    #[gpui::test]
+    async fn test_overwrite_undo_and_redo(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (_fs, _project, binary_file) = open_test_file(cx).await;
+
+        binary_file.update(cx, |file, cx| {
+            assert!(!file.is_dirty());
+
+            file.overwrite(1, 0xff, cx);
+            file.overwrite(2, 0xee, cx);
+            // Writing the same byte or past the end of the file is a no-op.
+            file.overwrite(3, 0x03, cx);
+            file.overwrite(4, 0xdd, cx);
+            assert_eq!(file.data(), &[0x00, 0xff, 0xee, 0x03]);
+            assert!(file.is_dirty());
+
+            assert_eq!(file.undo(cx), Some(2));
+            assert_eq!(file.data(), &[0x00, 0xff, 0x02, 0x03]);
+            assert_eq!(file.undo(cx), Some(1));
+            assert_eq!(file.data(), &[0x00, 0x01, 0x02, 0x03]);
+            assert!(
+                !file.is_dirty(),
+                "Undoing every edit restores the clean state"
+            );
+            assert_eq!(file.undo(cx), None);
+
+            assert_eq!(file.redo(cx), Some(1));
+            assert_eq!(file.data(), &[0x00, 0xff, 0x02, 0x03]);
+            assert!(file.is_dirty());
+
+            // A new edit discards the redo stack.
+            file.overwrite(0, 0xaa, cx);
+            assert_eq!(file.redo(cx), None);
+            assert_eq!(file.data(), &[0xaa, 0xff, 0x02, 0x03]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_save(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (fs, project, binary_file) = open_test_file(cx).await;
+
+        binary_file.update(cx, |file, cx| {
+            file.overwrite(0, 0xca, cx);
+            file.overwrite(1, 0xfe, cx);
+        });
+        binary_file
+            .update(cx, |file, cx| file.save(&project, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.read_file_sync(path!("/root/data.bin")).unwrap(),
+            vec![0xca, 0xfe, 0x02, 0x03]
+        );
+
+        binary_file.update(cx, |file, cx| {
+            assert!(!file.is_dirty());
+
+            // Undoing past the save makes the file dirty, and redoing back to it clean again.
+            file.undo(cx);
+            assert!(file.is_dirty());
+            file.redo(cx);
+            assert!(!file.is_dirty());
+
+            // Once the saved state is discarded from the redo stack, it can't be reached again.
+            file.undo(cx);
+            file.overwrite(1, 0xfe, cx);
+            assert_eq!(file.data(), &[0xca, 0xfe, 0x02, 0x03]);
+            assert!(file.is_dirty());
+        });
+    }
+}
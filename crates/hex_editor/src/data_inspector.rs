@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+use anyhow::{Context as _, Result};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn toggle(self) -> Self {
+        match self {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Endianness::Little => "Little Endian",
+            Endianness::Big => "Big Endian",
+        }
+    }
+}
+
+/// Interprets the bytes starting at `offset` as the common integer and float types.
+///
+/// Types that don't fit in the remaining bytes are reported without a value.
+pub fn inspect(
+    data: &[u8],
+    offset: usize,
+    endianness: Endianness,
+) -> Vec<(&'static str, Option<String>)> {
+    let bytes = data.get(offset..).unwrap_or_default();
+
+    macro_rules! read {
+        ($ty:ty) => {
+            bytes
+                .get(..size_of::<$ty>())
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(|bytes| match endianness {
+                    Endianness::Little => <$ty>::from_le_bytes(bytes),
+                    Endianness::Big => <$ty>::from_be_bytes(bytes),
+                })
+        };
+    }
+
+    vec![
+        ("binary", bytes.first().map(|byte| format!("{byte:08b}"))),
+        ("u8", read!(u8).map(|value| value.to_string())),
+        ("i8", read!(i8).map(|value| value.to_string())),
+        ("u16", read!(u16).map(|value| value.to_string())),
+        ("i16", read!(i16).map(|value| value.to_string())),
+        ("u32", read!(u32).map(|value| value.to_string())),
+        ("i32", read!(i32).map(|value| value.to_string())),
+        ("u64", read!(u64).map(|value| value.to_string())),
+        ("i64", read!(i64).map(|value| value.to_string())),
+        ("f32", read!(f32).map(|value| value.to_string())),
+        ("f64", read!(f64).map(|value| value.to_string())),
+    ]
+}
+
+/// Parses a search query into a byte pattern, where `None` matches any byte.
+///
+/// Queries wrapped in double quotes match their contents as text. Anything else is read as
+/// hex bytes, optionally separated by whitespace, with `??` as a wildcard: `DE AD ?? EF`.
+pub fn parse_byte_pattern(query: &str) -> Result<Vec<Option<u8>>> {
+    let query = query.trim();
+    if let Some(text) = query
+        .strip_prefix('"')
+        .and_then(|query| query.strip_suffix('"'))
+    {
+        return Ok(text.bytes().map(Some).collect());
+    }
+
+    let digits = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    anyhow::ensure!(!digits.is_empty(), "empty byte pattern");
+    anyhow::ensure!(
+        digits.len() % 2 == 0,
+        "byte pattern has an odd number of hex digits"
+    );
+    digits
+        .chunks(2)
+        .map(|pair| match pair {
+            ['?', '?'] => Ok(None),
+            [high, low] => {
+                let byte = format!("{high}{low}");
+                u8::from_str_radix(&byte, 16)
+                    .map(Some)
+                    .with_context(|| format!("invalid hex byte {byte:?}"))
+            }
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Returns every non-overlapping occurrence of `pattern` in `data`.
+pub fn find_pattern(data: &[u8], pattern: &[Option<u8>]) -> Vec<Range<usize>> {
+    let mut matches = Vec::new();
+    if pattern.is_empty() || pattern.len() > data.len() {
+        return matches;
+    }
+    let mut offset = 0;
+    while offset + pattern.len() <= data.len() {
+        let candidate = &data[offset..offset + pattern.len()];
+        let is_match = candidate
+            .iter()
+            .zip(pattern)
+            .all(|(byte, expected)| expected.is_none_or(|expected| *byte == expected));
+        if is_match {
+            matches.push(offset..offset + pattern.len());
+            offset += pattern.len();
+        } else {
+            offset += 1;
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspect() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let values = inspect(&data, 0, Endianness::Little);
+        let value = |name| {
+            values
+                .iter()
+                .find(|(label, _)| *label == name)
+                .and_then(|(_, value)| value.clone())
+        };
+        assert_eq!(value("u8").as_deref(), Some("1"));
+        assert_eq!(value("u16").as_deref(), Some("513"));
+        assert_eq!(value("u32").as_deref(), Some("67305985"));
+        assert_eq!(value("u64"), None);
+
+        let values = inspect(&data, 2, Endianness::Big);
+        let value = |name| {
+            values
+                .iter()
+                .find(|(label, _)| *label == name)
+                .and_then(|(_, value)| value.clone())
+        };
+        assert_eq!(value("u16").as_deref(), Some("772"));
+        assert_eq!(value("binary").as_deref(), Some("00000011"));
+        assert_eq!(value("u32"), None);
+    }
+
+    #[test]
+    fn test_find_byte_pattern() {
+        let data = b"\xde\xad\xbe\xef zed \xde\xad\x00\xef";
+
+        let pattern = parse_byte_pattern("DE AD ?? EF").unwrap();
+        assert_eq!(find_pattern(data, &pattern), vec![0..4, 9..13]);
+
+        let pattern = parse_byte_pattern("\"zed\"").unwrap();
+        assert_eq!(find_pattern(data, &pattern), vec![5..8]);
+
+        assert!(parse_byte_pattern("DEA").is_err());
+        assert!(parse_byte_pattern("ZZ").is_err());
+        assert!(parse_byte_pattern("  ").is_err());
+    }
+}
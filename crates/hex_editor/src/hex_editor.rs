@@ -0,0 +1,757 @@
+mod binary_file;
+mod data_inspector;
+
+use std::{ops::Range, path::Path, sync::Arc};
+
+use gpui::{
+    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, KeyDownEvent, ScrollStrategy,
+    Task, UniformListScrollHandle, Window, actions, uniform_list,
+};
+use language::File as _;
+use project::{Project, search::SearchQuery};
+use ui::{Divider, WithScrollbar, prelude::*};
+use workspace::{
+    Pane, Workspace, WorkspaceId,
+    invalid_item_view::InvalidItemView,
+    item::{Item, ItemEvent, ProjectItem, SaveOptions},
+    searchable::{Direction, SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
+};
+use zed_actions::hex_editor::OpenInHexEditor;
+
+pub use crate::binary_file::*;
+pub use crate::data_inspector::*;
+
+actions!(
+    hex_editor,
+    [
+        /// Moves the cursor one byte to the left.
+        MoveLeft,
+        /// Moves the cursor one byte to the right.
+        MoveRight,
+        /// Moves the cursor one row up.
+        MoveUp,
+        /// Moves the cursor one row down.
+        MoveDown,
+        /// Moves the cursor one page up.
+        MovePageUp,
+        /// Moves the cursor one page down.
+        MovePageDown,
+        /// Moves the cursor to the first byte of its row.
+        MoveToRowStart,
+        /// Moves the cursor to the last byte of its row.
+        MoveToRowEnd,
+        /// Moves the cursor to the first byte of the file.
+        MoveToBeginning,
+        /// Moves the cursor to the last byte of the file.
+        MoveToEnd,
+        /// Switches the cursor between the hex and the text column.
+        SwitchColumn,
+        /// Toggles the data inspector between little and big endian.
+        ToggleEndianness,
+        /// Undoes the last byte edit.
+        Undo,
+        /// Redoes the last undone byte edit.
+        Redo,
+    ]
+);
+
+const BYTES_PER_ROW: usize = 16;
+const ROWS_PER_PAGE: usize = 32;
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<HexEditor>(cx);
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &OpenInHexEditor, window, cx| {
+            HexEditor::open_for_active_item(workspace, window, cx);
+        });
+    })
+    .detach();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Text,
+}
+
+/// A hex editor for binary files, showing offset, hex and text columns alongside a data
+/// inspector for the bytes at the cursor. Edits overwrite bytes in place.
+pub struct HexEditor {
+    binary_file: Entity<BinaryFile>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    cursor: usize,
+    column: Column,
+    /// Whether the next hex digit typed goes into the low nibble of the byte at the cursor.
+    low_nibble: bool,
+    endianness: Endianness,
+    search_matches: Vec<Range<usize>>,
+    active_match_index: Option<usize>,
+}
+
+impl HexEditor {
+    pub fn new(
+        binary_file: Entity<BinaryFile>,
+        project: Entity<Project>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        cx.subscribe(&binary_file, |this, _, event, cx| {
+            match event {
+                BinaryFileEvent::Edited | BinaryFileEvent::Reloaded => {
+                    let len = this.binary_file.read(cx).data().len();
+                    this.cursor = this.cursor.min(len.saturating_sub(1));
+                    cx.emit(SearchEvent::MatchesInvalidated);
+                }
+                BinaryFileEvent::Saved => {}
+            }
+            cx.emit(ItemEvent::UpdateTab);
+            cx.notify();
+        })
+        .detach();
+
+        Self {
+            binary_file,
+            project,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            cursor: 0,
+            column: Column::Hex,
+            low_nibble: false,
+            endianness: Endianness::default(),
+            search_matches: Vec::new(),
+            active_match_index: None,
+        }
+    }
+
+    fn open_for_active_item(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(item) = workspace.active_item(cx) else {
+            return;
+        };
+        let project = workspace.project().clone();
+        let project_path = item.project_path(cx).or_else(|| {
+            let invalid_item = item.downcast::<InvalidItemView>()?;
+            let abs_path = invalid_item.read(cx).abs_path.clone();
+            project.read(cx).find_project_path(&abs_path, cx)
+        });
+        let Some(project_path) = project_path else {
+            return;
+        };
+
+        let pane = workspace.active_pane().clone();
+        let existing = pane.read(cx).items().position(|item| {
+            item.downcast::<HexEditor>().is_some_and(|editor| {
+                editor.read(cx).binary_file.read(cx).project_path(cx) == project_path
+            })
+        });
+        if let Some(ix) = existing {
+            pane.update(cx, |pane, cx| {
+                pane.activate_item(ix, true, true, window, cx)
+            });
+            return;
+        }
+
+        let open = BinaryFile::open(&project, project_path, cx);
+        cx.spawn_in(window, async move |workspace, cx| {
+            let binary_file = open.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let editor = cx.new(|cx| HexEditor::new(binary_file, project, window, cx));
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn len(&self, cx: &App) -> usize {
+        self.binary_file.read(cx).data().len()
+    }
+
+    fn move_cursor_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        let len = self.len(cx);
+        self.cursor = offset.min(len.saturating_sub(1));
+        self.low_nibble = false;
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Nearest);
+        cx.notify();
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(1), cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + 1, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cursor >= BYTES_PER_ROW {
+            self.move_cursor_to(self.cursor - BYTES_PER_ROW, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cursor + BYTES_PER_ROW < self.len(cx) {
+            self.move_cursor_to(self.cursor + BYTES_PER_ROW, cx);
+        }
+    }
+
+    fn move_page_up(&mut self, _: &MovePageUp, _: &mut Window, cx: &mut Context<Self>) {
+        let offset = self
+            .cursor
+            .checked_sub(BYTES_PER_ROW * ROWS_PER_PAGE)
+            .unwrap_or(self.cursor % BYTES_PER_ROW);
+        self.move_cursor_to(offset, cx);
+    }
+
+    fn move_page_down(&mut self, _: &MovePageDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + BYTES_PER_ROW * ROWS_PER_PAGE, cx);
+    }
+
+    fn move_to_row_start(&mut self, _: &MoveToRowStart, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor - self.cursor % BYTES_PER_ROW, cx);
+    }
+
+    fn move_to_row_end(&mut self, _: &MoveToRowEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(
+            self.cursor - self.cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1,
+            cx,
+        );
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(0, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(usize::MAX, cx);
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, _: &mut Window, cx: &mut Context<Self>) {
+        self.column = match self.column {
+            Column::Hex => Column::Text,
+            Column::Text => Column::Hex,
+        };
+        self.low_nibble = false;
+        cx.notify();
+    }
+
+    fn toggle_endianness(&mut self, _: &ToggleEndianness, _: &mut Window, cx: &mut Context<Self>) {
+        self.endianness = self.endianness.toggle();
+        cx.notify();
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(offset) = self.binary_file.update(cx, |file, cx| file.undo(cx)) {
+            self.move_cursor_to(offset, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(offset) = self.binary_file.update(cx, |file, cx| file.redo(cx)) {
+            self.move_cursor_to(offset, cx);
+        }
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.control
+            || keystroke.modifiers.alt
+            || keystroke.modifiers.platform
+            || keystroke.modifiers.function
+        {
+            return;
+        }
+        let Some(character) = keystroke
+            .key_char
+            .as_deref()
+            .and_then(|key_char| key_char.chars().next())
+        else {
+            return;
+        };
+        let Some(current) = self.binary_file.read(cx).data().get(self.cursor).copied() else {
+            return;
+        };
+
+        match self.column {
+            Column::Hex => {
+                let Some(digit) = character.to_digit(16) else {
+                    return;
+                };
+                let digit = digit as u8;
+                let byte = if self.low_nibble {
+                    (current & 0xF0) | digit
+                } else {
+                    (current & 0x0F) | (digit << 4)
+                };
+                let offset = self.cursor;
+                self.binary_file
+                    .update(cx, |file, cx| file.overwrite(offset, byte, cx));
+                if self.low_nibble {
+                    self.move_cursor_to(offset + 1, cx);
+                } else {
+                    self.low_nibble = true;
+                    cx.notify();
+                }
+            }
+            Column::Text => {
+                if !character.is_ascii() || character.is_ascii_control() {
+                    return;
+                }
+                let offset = self.cursor;
+                self.binary_file
+                    .update(cx, |file, cx| file.overwrite(offset, character as u8, cx));
+                self.move_cursor_to(offset + 1, cx);
+            }
+        }
+        cx.stop_propagation();
+    }
+
+    fn cell_background(&self, offset: usize, column: Column, cx: &App) -> Option<Hsla> {
+        let colors = cx.theme().colors();
+        if offset == self.cursor {
+            return Some(if column == self.column {
+                colors.element_selected
+            } else {
+                colors.element_hover
+            });
+        }
+        let match_ix = self
+            .search_matches
+            .binary_search_by(|range| {
+                if range.end <= offset {
+                    std::cmp::Ordering::Less
+                } else if range.start > offset {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .ok()?;
+        Some(if Some(match_ix) == self.active_match_index {
+            colors.search_active_match_background
+        } else {
+            colors.search_match_background
+        })
+    }
+
+    fn render_rows(
+        &mut self,
+        range: Range<usize>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Vec<AnyElement> {
+        let data = self.binary_file.read(cx).data();
+        range
+            .map(|row| {
+                let row_start = row * BYTES_PER_ROW;
+                let row_bytes = data
+                    .get(row_start..(row_start + BYTES_PER_ROW).min(data.len()))
+                    .unwrap_or_default();
+
+                let hex_cells = (0..BYTES_PER_ROW).map(|ix| {
+                    let offset = row_start + ix;
+                    let text = row_bytes
+                        .get(ix)
+                        .map(|byte| format!("{byte:02X}"))
+                        .unwrap_or_else(|| "  ".to_string());
+                    div()
+                        .id(("hex-cell", offset))
+                        .px_0p5()
+                        .when(ix == BYTES_PER_ROW / 2, |cell| cell.ml_1())
+                        .when_some(self.cell_background(offset, Column::Hex, cx), |cell, bg| {
+                            cell.bg(bg)
+                        })
+                        .child(Label::new(text).buffer_font(cx).size(LabelSize::Small))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.column = Column::Hex;
+                            this.move_cursor_to(offset, cx);
+                            window.focus(&this.focus_handle, cx);
+                        }))
+                });
+
+                let text_cells = row_bytes.iter().enumerate().map(|(ix, byte)| {
+                    let offset = row_start + ix;
+                    let character = char::from(*byte);
+                    let text = if character.is_ascii_graphic() || character == ' ' {
+                        character
+                    } else {
+                        '·'
+                    };
+                    div()
+                        .id(("text-cell", offset))
+                        .when_some(
+                            self.cell_background(offset, Column::Text, cx),
+                            |cell, bg| cell.bg(bg),
+                        )
+                        .child(
+                            Label::new(text.to_string())
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .when(text == '·', |label| label.color(Color::Muted)),
+                        )
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.column = Column::Text;
+                            this.move_cursor_to(offset, cx);
+                            window.focus(&this.focus_handle, cx);
+                        }))
+                });
+
+                h_flex()
+                    .id(("hex-row", row))
+                    .gap_3()
+                    .px_2()
+                    .child(
+                        Label::new(format!("{row_start:08X}"))
+                            .buffer_font(cx)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(h_flex().children(hex_cells))
+                    .child(h_flex().children(text_cells))
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_inspector(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let data = self.binary_file.read(cx).data();
+        let values = inspect(data, self.cursor, self.endianness);
+
+        v_flex()
+            .w(rems(18.))
+            .h_full()
+            .p_2()
+            .gap_1()
+            .border_l_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(Label::new("Data Inspector").size(LabelSize::Small))
+                    .child(
+                        Button::new("toggle-endianness", self.endianness.label())
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_endianness(&ToggleEndianness, window, cx);
+                            })),
+                    ),
+            )
+            .child(Divider::horizontal())
+            .children(values.into_iter().map(|(label, value)| {
+                h_flex()
+                    .justify_between()
+                    .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+                    .child(
+                        Label::new(value.unwrap_or_else(|| "—".to_string()))
+                            .buffer_font(cx)
+                            .size(LabelSize::Small),
+                    )
+            }))
+    }
+}
+
+impl EventEmitter<ItemEvent> for HexEditor {}
+impl EventEmitter<SearchEvent> for HexEditor {}
+
+impl Focusable for HexEditor {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let len = self.len(cx);
+        let row_count = len.div_ceil(BYTES_PER_ROW).max(1);
+
+        v_flex()
+            .key_context("HexEditor")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::move_page_up))
+            .on_action(cx.listener(Self::move_page_down))
+            .on_action(cx.listener(Self::move_to_row_start))
+            .on_action(cx.listener(Self::move_to_row_end))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::switch_column))
+            .on_action(cx.listener(Self::toggle_endianness))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .on_key_down(cx.listener(Self::key_down))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .flex_1()
+                    .size_full()
+                    .child(
+                        v_flex()
+                            .flex_1()
+                            .h_full()
+                            .py_1()
+                            .child(
+                                uniform_list(
+                                    "hex-editor-rows",
+                                    row_count,
+                                    cx.processor(Self::render_rows),
+                                )
+                                .size_full()
+                                .track_scroll(&self.scroll_handle),
+                            )
+                            .vertical_scrollbar_for(&self.scroll_handle, window, cx),
+                    )
+                    .child(self.render_inspector(cx)),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_0p5()
+                    .gap_2()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(format!("Offset 0x{:08X} ({})", self.cursor, self.cursor))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(Divider::vertical())
+                    .child(
+                        Label::new(format!("{len} bytes"))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+    }
+}
+
+impl Item for HexEditor {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.binary_file.entity_id(), self.binary_file.read(cx))
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        self.binary_file
+            .read(cx)
+            .file()
+            .file_name(cx)
+            .to_string()
+            .into()
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.binary_file.read(cx).abs_path(cx)?;
+        Some(abs_path.to_string_lossy().into_owned().into())
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Binary))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("hex editor")
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.binary_file.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, _cx: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _options: SaveOptions,
+        project: Entity<Project>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.binary_file
+            .update(cx, |file, cx| file.save(&project, cx))
+    }
+
+    fn reload(
+        &mut self,
+        _project: Entity<Project>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.binary_file.update(cx, |file, cx| file.reload(cx))
+    }
+
+    fn as_searchable(
+        &self,
+        handle: &Entity<Self>,
+        _: &App,
+    ) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(handle.clone()))
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>> {
+        let binary_file = self.binary_file.clone();
+        let project = self.project.clone();
+        Task::ready(Some(
+            cx.new(|cx| HexEditor::new(binary_file, project, window, cx)),
+        ))
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = BinaryFile;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::new(item, project, window, cx)
+    }
+
+    fn for_broken_project_item(
+        abs_path: &Path,
+        is_local: bool,
+        e: &anyhow::Error,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<InvalidItemView> {
+        Some(InvalidItemView::new(abs_path, is_local, e, window, cx))
+    }
+}
+
+impl SearchableItem for HexEditor {
+    type Match = Range<usize>;
+
+    fn supported_options(&self) -> SearchOptions {
+        SearchOptions {
+            case: false,
+            word: false,
+            regex: false,
+            replacement: false,
+            selection: false,
+            find_in_results: false,
+        }
+    }
+
+    fn clear_matches(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        self.search_matches.clear();
+        self.active_match_index = None;
+        cx.notify();
+    }
+
+    fn update_matches(
+        &mut self,
+        matches: &[Self::Match],
+        active_match_index: Option<usize>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.search_matches = matches.to_vec();
+        self.active_match_index = active_match_index;
+        cx.notify();
+    }
+
+    fn query_suggestion(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> String {
+        String::new()
+    }
+
+    fn activate_match(
+        &mut self,
+        index: usize,
+        matches: &[Self::Match],
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(range) = matches.get(index) {
+            self.active_match_index = Some(index);
+            self.move_cursor_to(range.start, cx);
+        }
+    }
+
+    fn select_matches(
+        &mut self,
+        matches: &[Self::Match],
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(range) = matches.first() {
+            self.move_cursor_to(range.start, cx);
+        }
+    }
+
+    fn replace(
+        &mut self,
+        _: &Self::Match,
+        _: &SearchQuery,
+        _window: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+        // Replacing would change the length of the file, which the hex editor doesn't support.
+    }
+
+    fn find_matches(
+        &mut self,
+        query: Arc<SearchQuery>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<Self::Match>> {
+        let Ok(pattern) = parse_byte_pattern(query.as_str()) else {
+            return Task::ready(Vec::new());
+        };
+        let data = self.binary_file.read(cx).data().to_vec();
+        cx.background_spawn(async move { find_pattern(&data, &pattern) })
+    }
+
+    fn active_match_index(
+        &mut self,
+        direction: Direction,
+        matches: &[Self::Match],
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<usize> {
+        if matches.is_empty() {
+            return None;
+        }
+        let cursor = self.cursor;
+        let index = match direction {
+            Direction::Next => matches
+                .iter()
+                .position(|range| range.end > cursor)
+                .unwrap_or(0),
+            Direction::Prev => matches
+                .iter()
+                .rposition(|range| range.start <= cursor)
+                .unwrap_or(matches.len() - 1),
+        };
+        Some(index)
+    }
+}
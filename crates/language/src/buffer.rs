@@ -1034,6 +1034,8 @@ impl Buffer {
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
             saved_version: proto::serialize_version(&self.saved_version),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
+            // Set by the project's buffer store, which tracks large buffers.
+            is_large: false,
         }
     }
 
@@ -0,0 +1,626 @@
+//! Local history keeps snapshots of file contents independently of version control,
+//! so that work survives a bad checkout or an unexpected overwrite.
+
+mod local_history_view;
+
+use anyhow::Result;
+use fs::{Fs, RemoveOptions};
+use futures::StreamExt as _;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Global, Task, actions};
+use language::{Buffer, BufferEvent};
+use project::{
+    PathChange, Project, ProjectPath, UpdatedEntriesSet, WorktreeId,
+    buffer_store::{BufferStore, BufferStoreEvent},
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
+};
+use settings::{RegisterSetting, Settings};
+use sha2::{Digest as _, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use time::OffsetDateTime;
+use util::ResultExt as _;
+use workspace::{AppState, Workspace};
+
+pub use local_history_view::LocalHistoryView;
+
+actions!(
+    local_history,
+    [
+        /// Opens the local history timeline for the active file.
+        OpenTimeline,
+        /// Replaces the buffer contents with the selected local history snapshot.
+        RestoreSnapshot,
+    ]
+);
+
+/// Settings for the local file history.
+#[derive(Clone, Debug, RegisterSetting)]
+pub struct LocalHistorySettings {
+    /// Whether to record snapshots of files on save and on external changes.
+    pub enabled: bool,
+    /// The maximum number of snapshots kept for each file.
+    pub max_entries_per_file: usize,
+    /// Snapshots older than this are deleted, except for the most recent one.
+    pub max_age: Duration,
+    /// Files larger than this many bytes are not recorded.
+    pub max_file_size: u64,
+}
+
+impl Settings for LocalHistorySettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let local_history = content.local_history.clone().unwrap();
+        Self {
+            enabled: local_history.enabled.unwrap(),
+            max_entries_per_file: local_history.max_entries_per_file.unwrap(),
+            max_age: Duration::from_secs(local_history.max_age_days.unwrap() * 24 * 60 * 60),
+            max_file_size: local_history.max_file_size.unwrap(),
+        }
+    }
+}
+
+pub fn init(app_state: Arc<AppState>, cx: &mut App) {
+    let local_history = cx.new(|_| {
+        LocalHistory::new(
+            app_state.fs.clone(),
+            paths::data_dir().join("local_history"),
+        )
+    });
+    cx.set_global(GlobalLocalHistory(local_history.clone()));
+
+    cx.observe_new(move |project: &mut Project, _, cx| {
+        if !project.is_local() {
+            return;
+        }
+        let buffer_store = project.buffer_store().clone();
+        let worktree_store = project.worktree_store();
+        local_history.update(cx, |local_history, cx| {
+            local_history.watch_project(&buffer_store, &worktree_store, cx);
+        });
+    })
+    .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &OpenTimeline, window, cx| {
+            LocalHistoryView::open_for_active_item(workspace, window, cx);
+        });
+    })
+    .detach();
+}
+
+struct GlobalLocalHistory(Entity<LocalHistory>);
+
+impl Global for GlobalLocalHistory {}
+
+/// Why a snapshot was recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotSource {
+    /// The file was opened and differed from its latest snapshot.
+    Opened,
+    /// The file was saved from the editor.
+    Saved,
+    /// The file was changed on disk by another program.
+    ExternalChange,
+}
+
+impl SnapshotSource {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Opened => "open",
+            Self::Saved => "save",
+            Self::ExternalChange => "external",
+        }
+    }
+
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "open" => Some(Self::Opened),
+            "save" => Some(Self::Saved),
+            "external" => Some(Self::ExternalChange),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Opened => "Opened",
+            Self::Saved => "Saved",
+            Self::ExternalChange => "Changed on disk",
+        }
+    }
+}
+
+/// A single recorded version of a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalHistoryEntry {
+    /// Where the snapshot contents are stored.
+    pub snapshot_path: PathBuf,
+    pub timestamp: OffsetDateTime,
+    pub source: SnapshotSource,
+}
+
+impl LocalHistoryEntry {
+    fn from_snapshot_path(snapshot_path: PathBuf) -> Option<Self> {
+        let file_name = snapshot_path.file_name()?.to_str()?;
+        let (millis, extension) = file_name.split_once('.')?;
+        let millis = millis.parse::<i128>().ok()?;
+        let timestamp = OffsetDateTime::from_unix_timestamp_nanos(millis * 1_000_000).ok()?;
+        let source = SnapshotSource::from_extension(extension)?;
+        Some(Self {
+            snapshot_path,
+            timestamp,
+            source,
+        })
+    }
+}
+
+pub enum LocalHistoryEvent {
+    /// A snapshot was recorded for the file at this path.
+    Recorded(PathBuf),
+}
+
+/// Stores snapshots of files under a directory per file, keyed by a hash of the
+/// file's absolute path. Each snapshot is a file named after the time it was
+/// taken and the reason it was recorded.
+pub struct LocalHistory {
+    fs: Arc<dyn Fs>,
+    root: PathBuf,
+}
+
+impl EventEmitter<LocalHistoryEvent> for LocalHistory {}
+
+impl LocalHistory {
+    pub fn new(fs: Arc<dyn Fs>, root: PathBuf) -> Self {
+        Self { fs, root }
+    }
+
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalLocalHistory>()
+            .map(|local_history| local_history.0.clone())
+    }
+
+    fn history_dir(&self, abs_path: &Path) -> PathBuf {
+        let digest = Sha256::digest(abs_path.to_string_lossy().as_bytes());
+        self.root.join(format!("{digest:x}"))
+    }
+
+    /// Returns the snapshots recorded for `abs_path`, newest first.
+    pub fn entries(&self, abs_path: &Path, cx: &App) -> Task<Result<Vec<LocalHistoryEntry>>> {
+        let fs = self.fs.clone();
+        let history_dir = self.history_dir(abs_path);
+        cx.background_spawn(async move { read_entries(fs.as_ref(), &history_dir).await })
+    }
+
+    pub fn load(&self, entry: &LocalHistoryEntry, cx: &App) -> Task<Result<String>> {
+        let fs = self.fs.clone();
+        let snapshot_path = entry.snapshot_path.clone();
+        cx.background_spawn(async move { fs.load(&snapshot_path).await })
+    }
+
+    /// Records `text` as the latest version of `abs_path`, unless it matches the
+    /// most recent snapshot, and then applies the retention limits.
+    pub fn record(
+        &self,
+        abs_path: &Path,
+        text: String,
+        source: SnapshotSource,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let settings = LocalHistorySettings::get_global(cx).clone();
+        if !settings.enabled || text.len() as u64 > settings.max_file_size {
+            return Task::ready(Ok(()));
+        }
+        let fs = self.fs.clone();
+        let history_dir = self.history_dir(abs_path);
+        let write = cx.background_spawn(async move {
+            let entries = read_entries(fs.as_ref(), &history_dir).await?;
+            if let Some(latest) = entries.first()
+                && fs.load(&latest.snapshot_path).await.ok().as_deref() == Some(text.as_str())
+            {
+                return Ok(false);
+            }
+
+            // Keep snapshot names unique and ordered, even when several are
+            // recorded within the same millisecond.
+            let mut millis = OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000;
+            if let Some(latest) = entries.first() {
+                millis = millis.max(latest.timestamp.unix_timestamp_nanos() / 1_000_000 + 1);
+            }
+            let now = OffsetDateTime::from_unix_timestamp_nanos(millis * 1_000_000)?;
+            fs.create_dir(&history_dir).await?;
+            let snapshot_path = history_dir.join(format!("{millis}.{}", source.extension()));
+            fs.atomic_write(snapshot_path.clone(), text).await?;
+
+            let mut entries = entries;
+            entries.insert(
+                0,
+                LocalHistoryEntry {
+                    snapshot_path,
+                    timestamp: now,
+                    source,
+                },
+            );
+            let oldest_allowed = now - settings.max_age;
+            let max_entries = settings.max_entries_per_file.max(1);
+            for (ix, entry) in entries.iter().enumerate().skip(1) {
+                if ix >= max_entries || entry.timestamp < oldest_allowed {
+                    fs.remove_file(
+                        &entry.snapshot_path,
+                        RemoveOptions {
+                            recursive: false,
+                            ignore_if_not_exists: true,
+                        },
+                    )
+                    .await
+                    .log_err();
+                }
+            }
+            Ok(true)
+        });
+
+        let abs_path = abs_path.to_path_buf();
+        cx.spawn(async move |this, cx| {
+            if write.await? {
+                this.update(cx, |_, cx| cx.emit(LocalHistoryEvent::Recorded(abs_path)))?;
+            }
+            Ok(())
+        })
+    }
+
+    fn watch_project(
+        &mut self,
+        buffer_store: &Entity<BufferStore>,
+        worktree_store: &Entity<WorktreeStore>,
+        cx: &mut Context<Self>,
+    ) {
+        cx.subscribe(buffer_store, |this, _, event, cx| {
+            if let BufferStoreEvent::BufferAdded(buffer) = event {
+                this.watch_buffer(buffer, cx);
+            }
+        })
+        .detach();
+
+        let buffer_store = buffer_store.downgrade();
+        cx.subscribe(worktree_store, move |this, worktree_store, event, cx| {
+            if let WorktreeStoreEvent::WorktreeUpdatedEntries(worktree_id, changes) = event
+                && let Some(buffer_store) = buffer_store.upgrade()
+            {
+                this.snapshot_changed_entries(
+                    &buffer_store,
+                    &worktree_store,
+                    *worktree_id,
+                    changes,
+                    cx,
+                );
+            }
+        })
+        .detach();
+    }
+
+    /// Records files that changed on disk while they were not open. Open buffers are recorded
+    /// when they are saved or reloaded instead.
+    fn snapshot_changed_entries(
+        &mut self,
+        buffer_store: &Entity<BufferStore>,
+        worktree_store: &Entity<WorktreeStore>,
+        worktree_id: WorktreeId,
+        changes: &UpdatedEntriesSet,
+        cx: &mut Context<Self>,
+    ) {
+        let settings = LocalHistorySettings::get_global(cx);
+        if !settings.enabled {
+            return;
+        }
+        let max_file_size = settings.max_file_size;
+        let Some(worktree) = worktree_store.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let buffer_store = buffer_store.read(cx);
+        let worktree = worktree.read(cx);
+        let abs_paths = changes
+            .iter()
+            .filter(|(_, _, change)| {
+                matches!(
+                    change,
+                    PathChange::Added | PathChange::Updated | PathChange::AddedOrUpdated
+                )
+            })
+            .filter(|(path, _, _)| {
+                buffer_store
+                    .get_by_path(&ProjectPath {
+                        worktree_id,
+                        path: path.clone(),
+                    })
+                    .is_none()
+            })
+            .filter(|(_, entry_id, _)| {
+                worktree.entry_for_id(*entry_id).is_some_and(|entry| {
+                    entry.is_file() && !entry.is_ignored && entry.size <= max_file_size
+                })
+            })
+            .map(|(path, _, _)| worktree.absolutize(path))
+            .collect::<Vec<_>>();
+
+        for abs_path in abs_paths {
+            self.snapshot_path_on_disk(abs_path, cx);
+        }
+    }
+
+    fn watch_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        self.snapshot_buffer(buffer, SnapshotSource::Opened, cx);
+        cx.subscribe(buffer, |this, buffer, event, cx| match event {
+            BufferEvent::Saved => this.snapshot_buffer(&buffer, SnapshotSource::Saved, cx),
+            BufferEvent::Reloaded => {
+                this.snapshot_buffer(&buffer, SnapshotSource::ExternalChange, cx)
+            }
+            // A dirty buffer is not reloaded when its file changes, so read the
+            // new contents from disk instead.
+            BufferEvent::FileHandleChanged if buffer.read(cx).is_dirty() => {
+                this.snapshot_file_on_disk(&buffer, cx)
+            }
+            _ => {}
+        })
+        .detach();
+    }
+
+    fn snapshot_buffer(
+        &self,
+        buffer: &Entity<Buffer>,
+        source: SnapshotSource,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer = buffer.read(cx);
+        if source == SnapshotSource::Opened && buffer.is_dirty() {
+            return;
+        }
+        let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+            return;
+        };
+        let abs_path = file.abs_path(cx);
+        let text = buffer.text();
+        self.record(&abs_path, text, source, cx)
+            .detach_and_log_err(cx);
+    }
+
+    fn snapshot_file_on_disk(&self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(file) = buffer.read(cx).file().and_then(|file| file.as_local()) else {
+            return;
+        };
+        if !file.disk_state().exists() {
+            return;
+        }
+        let abs_path = file.abs_path(cx);
+        self.snapshot_path_on_disk(abs_path, cx);
+    }
+
+    fn snapshot_path_on_disk(&self, abs_path: PathBuf, cx: &mut Context<Self>) {
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let text = fs.load(&abs_path).await?;
+            this.update(cx, |this, cx| {
+                this.record(&abs_path, text, SnapshotSource::ExternalChange, cx)
+            })?
+            .await
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+/// Replaces the contents of `buffer` with `text` as a single undoable transaction.
+pub fn restore_snapshot(buffer: Entity<Buffer>, text: String, cx: &mut App) -> Task<()> {
+    let diff = buffer.read(cx).diff(text, cx);
+    cx.spawn(async move |cx| {
+        let diff = diff.await;
+        buffer.update(cx, |buffer, cx| {
+            buffer.finalize_last_transaction();
+            buffer.apply_diff(diff, cx);
+            buffer.finalize_last_transaction();
+        });
+    })
+}
+
+async fn read_entries(fs: &dyn Fs, history_dir: &Path) -> Result<Vec<LocalHistoryEntry>> {
+    if !fs.is_dir(history_dir).await {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    let mut paths = fs.read_dir(history_dir).await?;
+    while let Some(path) = paths.next().await {
+        if let Some(entry) = path
+            .log_err()
+            .and_then(LocalHistoryEntry::from_snapshot_path)
+        {
+            entries.push(entry);
+        }
+    }
+    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_record_deduplicates_and_prunes(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings
+                        .local_history
+                        .get_or_insert_default()
+                        .max_entries_per_file = Some(2);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        let local_history = cx.new(|_| LocalHistory::new(fs.clone(), path!("/history").into()));
+        let file = Path::new(path!("/project/file.txt"));
+
+        for text in ["one", "one", "two", "three"] {
+            local_history
+                .update(cx, |local_history, cx| {
+                    local_history.record(file, text.to_string(), SnapshotSource::Saved, cx)
+                })
+                .await
+                .unwrap();
+        }
+
+        let entries = local_history
+            .update(cx, |local_history, cx| local_history.entries(file, cx))
+            .await
+            .unwrap();
+        let contents = futures::future::join_all(entries.iter().map(|entry| {
+            local_history.update(cx, |local_history, cx| local_history.load(entry, cx))
+        }))
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+        assert_eq!(contents, ["three", "two"]);
+        assert!(
+            entries
+                .iter()
+                .all(|entry| entry.source == SnapshotSource::Saved)
+        );
+    }
+
+    async fn recorded_contents(
+        local_history: &Entity<LocalHistory>,
+        abs_path: &Path,
+        cx: &mut TestAppContext,
+    ) -> Vec<(SnapshotSource, String)> {
+        let entries = local_history
+            .update(cx, |local_history, cx| local_history.entries(abs_path, cx))
+            .await
+            .unwrap();
+        let mut contents = Vec::new();
+        for entry in entries {
+            let text = local_history
+                .update(cx, |local_history, cx| local_history.load(&entry, cx))
+                .await
+                .unwrap();
+            contents.push((entry.source, text));
+        }
+        contents
+    }
+
+    #[gpui::test]
+    async fn test_record_prunes_by_age(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.local_history.get_or_insert_default().max_age_days = Some(1);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        let local_history = cx.new(|_| LocalHistory::new(fs.clone(), path!("/history").into()));
+        let file = Path::new(path!("/project/file.txt"));
+
+        let history_dir =
+            local_history.read_with(cx, |local_history, _| local_history.history_dir(file));
+        let now = OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000;
+        let day = 24 * 60 * 60 * 1000;
+        for (age, text) in [(3 * day, "three days old"), (2 * day, "two days old")] {
+            fs.insert_file(
+                history_dir.join(format!("{}.save", now - age)),
+                text.as_bytes().to_vec(),
+            )
+            .await;
+        }
+
+        local_history
+            .update(cx, |local_history, cx| {
+                local_history.record(file, "new".to_string(), SnapshotSource::Saved, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            recorded_contents(&local_history, file, cx).await,
+            [(SnapshotSource::Saved, "new".to_string())]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_restore_snapshot(cx: &mut TestAppContext) {
+        init_test(cx);
+        let buffer = cx.new(|cx| Buffer::local("one\ntwo\nthree\n", cx));
+
+        cx.update(|cx| restore_snapshot(buffer.clone(), "one\n2\nthree\nfour\n".into(), cx))
+            .await;
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "one\n2\nthree\nfour\n");
+        });
+
+        // The restore is undone as a whole.
+        buffer.update(cx, |buffer, cx| buffer.undo(cx));
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "one\ntwo\nthree\n");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_snapshots_on_save_and_external_change(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(path!("/project/open.txt"), b"one".to_vec())
+            .await;
+        fs.insert_file(path!("/project/closed.txt"), b"two".to_vec())
+            .await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let local_history = cx.new(|_| LocalHistory::new(fs.clone(), path!("/history").into()));
+        local_history.update(cx, |local_history, cx| {
+            let project = project.read(cx);
+            let buffer_store = project.buffer_store().clone();
+            let worktree_store = project.worktree_store();
+            local_history.watch_project(&buffer_store, &worktree_store, cx);
+        });
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/project/open.txt"), cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| buffer.edit([(3..3, "!")], None, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            recorded_contents(&local_history, Path::new(path!("/project/open.txt")), cx).await,
+            [
+                (SnapshotSource::Saved, "one!".to_string()),
+                (SnapshotSource::Opened, "one".to_string()),
+            ]
+        );
+
+        // Files that aren't open are recorded when they change on disk.
+        fs.insert_file(path!("/project/closed.txt"), b"overwritten".to_vec())
+            .await;
+        cx.run_until_parked();
+        assert_eq!(
+            recorded_contents(&local_history, Path::new(path!("/project/closed.txt")), cx).await,
+            [(SnapshotSource::ExternalChange, "overwritten".to_string())]
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use std::{path::PathBuf, sync::Arc};
+
+use buffer_diff::BufferDiff;
+use editor::{Editor, MultiBuffer};
+use gpui::{
+    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ScrollStrategy, Subscription,
+    Task, UniformListScrollHandle, Window, uniform_list,
+};
+use language::{Buffer, BufferEvent};
+use time::OffsetDateTime;
+use ui::{Divider, ListItem, WithScrollbar, prelude::*};
+use util::ResultExt as _;
+use workspace::{Item, Workspace, item::ItemEvent};
+
+use crate::{LocalHistory, LocalHistoryEntry, LocalHistoryEvent, RestoreSnapshot};
+
+/// A timeline of the local history snapshots of a file, showing the selected
+/// snapshot as a diff against the current buffer contents.
+pub struct LocalHistoryView {
+    abs_path: PathBuf,
+    buffer: Entity<Buffer>,
+    entries: Vec<LocalHistoryEntry>,
+    selected_entry: Option<usize>,
+    selected_text: Option<Arc<str>>,
+    diff: Entity<BufferDiff>,
+    editor: Entity<Editor>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    load_snapshot_task: Task<()>,
+    load_entries_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LocalHistoryView {
+    pub fn open_for_active_item(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(local_history) = LocalHistory::global(cx) else {
+            return;
+        };
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let Some(abs_path) = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| file.as_local())
+            .map(|file| file.abs_path(cx))
+        else {
+            return;
+        };
+
+        let entries = local_history.read(cx).entries(&abs_path, cx);
+        cx.spawn_in(window, async move |workspace, cx| {
+            let entries = entries.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let pane = workspace.active_pane().clone();
+                let existing = pane.read(cx).items().position(|item| {
+                    item.downcast::<LocalHistoryView>()
+                        .is_some_and(|view| view.read(cx).abs_path == abs_path)
+                });
+                pane.update(cx, |pane, cx| match existing {
+                    Some(ix) => pane.activate_item(ix, true, true, window, cx),
+                    None => {
+                        let view = cx
+                            .new(|cx| LocalHistoryView::new(abs_path, buffer, entries, window, cx));
+                        pane.add_item(Box::new(view), true, true, None, window, cx);
+                    }
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn new(
+        abs_path: PathBuf,
+        buffer: Entity<Buffer>,
+        entries: Vec<LocalHistoryEntry>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let diff = cx.new(|cx| BufferDiff::new(&buffer.read(cx).text_snapshot(), cx));
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+            editor.set_read_only(true);
+            editor.start_temporary_diff_override();
+            editor.disable_diagnostics(cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+        let mut subscriptions = vec![cx.subscribe(&buffer, |this, _, event, cx| {
+            if let BufferEvent::Edited | BufferEvent::Reloaded = event {
+                this.update_diff(cx);
+            }
+        })];
+        if let Some(local_history) = LocalHistory::global(cx) {
+            subscriptions.push(cx.subscribe(&local_history, |this, _, event, cx| {
+                let LocalHistoryEvent::Recorded(abs_path) = event;
+                if *abs_path == this.abs_path {
+                    this.reload_entries(cx);
+                }
+            }));
+        }
+
+        let mut this = Self {
+            abs_path,
+            buffer,
+            entries,
+            selected_entry: None,
+            selected_text: None,
+            diff,
+            editor,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            load_snapshot_task: Task::ready(()),
+            load_entries_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        if !this.entries.is_empty() {
+            this.select_entry(0, cx);
+        }
+        this
+    }
+
+    /// Reloads the snapshots after a new one was recorded, keeping the selected one selected.
+    fn reload_entries(&mut self, cx: &mut Context<Self>) {
+        let Some(local_history) = LocalHistory::global(cx) else {
+            return;
+        };
+        let entries = local_history.read(cx).entries(&self.abs_path, cx);
+        self.load_entries_task = cx.spawn(async move |this, cx| {
+            let Some(entries) = entries.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                let selected_path = this
+                    .selected_entry
+                    .and_then(|ix| this.entries.get(ix))
+                    .map(|entry| entry.snapshot_path.clone());
+                this.entries = entries;
+                match selected_path
+                    .and_then(|path| this.entries.iter().position(|e| e.snapshot_path == path))
+                {
+                    Some(ix) => this.selected_entry = Some(ix),
+                    None if this.entries.is_empty() => {
+                        this.selected_entry = None;
+                        this.selected_text = None;
+                    }
+                    None => this.select_entry(0, cx),
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn select_entry(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        let Some(local_history) = LocalHistory::global(cx) else {
+            return;
+        };
+        self.selected_entry = Some(ix);
+        self.scroll_handle.scroll_to_item(ix, ScrollStrategy::Top);
+        let load = local_history.read(cx).load(entry, cx);
+        self.load_snapshot_task = cx.spawn(async move |this, cx| {
+            let Some(text) = load.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.selected_text = Some(text.into());
+                this.update_diff(cx);
+            })
+            .ok();
+        });
+        cx.notify();
+    }
+
+    fn update_diff(&mut self, cx: &mut Context<Self>) {
+        let Some(base_text) = self.selected_text.clone() else {
+            return;
+        };
+        let buffer = self.buffer.read(cx);
+        let language = buffer.language().cloned();
+        let snapshot = buffer.text_snapshot();
+        self.diff.update(cx, |diff, cx| {
+            diff.set_base_text(Some(base_text), language, snapshot, cx);
+        });
+    }
+
+    fn restore(&mut self, _: &RestoreSnapshot, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = self.selected_text.clone() else {
+            return;
+        };
+        crate::restore_snapshot(self.buffer.clone(), text.to_string(), cx).detach();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let ix = self
+            .selected_entry
+            .map_or(0, |ix| (ix + 1) % self.entries.len());
+        self.select_entry(ix, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let ix = match self.selected_entry {
+            None | Some(0) => self.entries.len() - 1,
+            Some(ix) => ix - 1,
+        };
+        self.select_entry(ix, cx);
+    }
+
+    fn render_entry(&self, ix: usize, entry: &LocalHistoryEntry, cx: &Context<Self>) -> AnyElement {
+        let timestamp = time_format::format_localized_timestamp(
+            entry.timestamp,
+            OffsetDateTime::now_utc(),
+            time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC),
+            time_format::TimestampFormat::Relative,
+        );
+
+        ListItem::new(("local-history-entry", ix))
+            .toggle_state(Some(ix) == self.selected_entry)
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_1()
+                    .gap_2()
+                    .justify_between()
+                    .child(Label::new(timestamp).size(LabelSize::Small))
+                    .child(
+                        Label::new(entry.source.label())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, _, cx| this.select_entry(ix, cx)))
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<ItemEvent> for LocalHistoryView {}
+
+impl Focusable for LocalHistoryView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LocalHistoryView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entry_count = self.entries.len();
+
+        v_flex()
+            .key_context("LocalHistoryView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::restore))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .h(rems_from_px(41.))
+                    .pl_3()
+                    .pr_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new(self.abs_path.to_string_lossy().to_string())
+                            .color(Color::Muted)
+                            .buffer_font(cx),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_1p5()
+                            .child(
+                                Label::new(format!("{entry_count} snapshots"))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(Divider::vertical())
+                            .child(
+                                Button::new("restore-snapshot", "Restore")
+                                    .disabled(self.selected_text.is_none())
+                                    .label_size(LabelSize::Small)
+                                    .icon(IconName::Undo)
+                                    .icon_size(IconSize::Small)
+                                    .icon_color(Color::Muted)
+                                    .icon_position(IconPosition::Start)
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.restore(&RestoreSnapshot, window, cx);
+                                    })),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .size_full()
+                    .child(
+                        v_flex()
+                            .w(rems(18.))
+                            .h_full()
+                            .border_r_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .child({
+                                let view = cx.weak_entity();
+                                uniform_list(
+                                    "local-history-entries",
+                                    entry_count,
+                                    move |range, _, cx| {
+                                        let Some(view) = view.upgrade() else {
+                                            return Vec::new();
+                                        };
+                                        view.update(cx, |this, cx| {
+                                            range
+                                                .filter_map(|ix| {
+                                                    let entry = this.entries.get(ix)?;
+                                                    Some(this.render_entry(ix, entry, cx))
+                                                })
+                                                .collect()
+                                        })
+                                    },
+                                )
+                                .size_full()
+                                .track_scroll(&self.scroll_handle)
+                            })
+                            .vertical_scrollbar_for(&self.scroll_handle, window, cx),
+                    )
+                    .child(div().flex_1().h_full().child(self.editor.clone())),
+            )
+    }
+}
+
+impl Item for LocalHistoryView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        let file_name = self
+            .abs_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "File".to_string());
+        format!("Local History: {file_name}").into()
+    }
+
+    fn tab_tooltip_text(&self, _cx: &App) -> Option<SharedString> {
+        Some(format!("Local history for {}", self.abs_path.display()).into())
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("local history")
+    }
+}
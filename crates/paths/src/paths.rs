@@ -426,6 +426,21 @@ pub fn external_agents_dir() -> &'static PathBuf {
     EXTERNAL_AGENTS_DIR.get_or_init(|| data_dir().join("external_agents"))
 }
 
+/// Returns the path to the agent worktrees directory.
+///
+/// This is where the git worktrees that queued agent tasks run in are created.
+pub fn agent_worktrees_dir() -> &'static PathBuf {
+    static AGENT_WORKTREES_DIR: OnceLock<PathBuf> = OnceLock::new();
+    AGENT_WORKTREES_DIR.get_or_init(|| data_dir().join("agent_worktrees"))
+}
+
+/// Returns the path to the socket of the terminal session host, which keeps terminals running
+/// across restarts.
+pub fn terminal_sessions_socket() -> &'static PathBuf {
+    static TERMINAL_SESSIONS_SOCKET: OnceLock<PathBuf> = OnceLock::new();
+    TERMINAL_SESSIONS_SOCKET.get_or_init(|| data_dir().join("terminal_sessions.sock"))
+}
+
 /// Returns the path to the Copilot directory.
 pub fn copilot_dir() -> &'static PathBuf {
     static COPILOT_DIR: OnceLock<PathBuf> = OnceLock::new();
@@ -39,6 +39,7 @@ pub struct BufferStore {
     downstream_client: Option<(AnyProtoClient, u64)>,
     shared_buffers: HashMap<proto::PeerId, HashMap<BufferId, SharedBuffer>>,
     non_searchable_buffers: HashSet<BufferId>,
+    large_buffers: HashSet<BufferId>,
     project_search: RemoteProjectSearchState,
 }
 
@@ -505,6 +506,7 @@ impl LocalBufferStore {
         } else {
             this.opened_buffers.remove(&buffer_id);
             this.non_searchable_buffers.remove(&buffer_id);
+            this.large_buffers.remove(&buffer_id);
             None
         };
 
@@ -645,13 +647,25 @@ impl LocalBufferStore {
         let load_file = worktree.update(cx, |worktree, cx| worktree.load_file(path.as_ref(), cx));
         cx.spawn(async move |this, cx| {
             let path = path.clone();
+            let mut is_large = false;
             let buffer = match load_file.await {
                 Ok(loaded) => {
+                    is_large = loaded.is_large();
                     let reservation = cx.reserve_entity::<Buffer>();
                     let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
+                    let text = loaded.text;
+                    let large_text = loaded.large_text;
                     let text_buffer = cx
                         .background_spawn(async move {
-                            text::Buffer::new(ReplicaId::LOCAL, buffer_id, loaded.text)
+                            match large_text {
+                                Some((rope, line_ending)) => text::Buffer::new_normalized(
+                                    ReplicaId::LOCAL,
+                                    buffer_id,
+                                    line_ending,
+                                    rope,
+                                ),
+                                None => text::Buffer::new(ReplicaId::LOCAL, buffer_id, text),
+                            }
                         })
                         .await;
                     cx.insert_entity(reservation, |_| {
@@ -681,8 +695,13 @@ impl LocalBufferStore {
                 Err(e) => return Err(e),
             };
             this.update(cx, |this, cx| {
-                this.add_buffer(buffer.clone(), cx)?;
                 let buffer_id = buffer.read(cx).remote_id();
+                // Mark large buffers before announcing them, so that language detection,
+                // language servers and git diffs are skipped from the start.
+                if is_large {
+                    this.large_buffers.insert(buffer_id);
+                }
+                this.add_buffer(buffer.clone(), cx)?;
                 if let Some(file) = File::from_dyn(buffer.read(cx).file()) {
                     let project_path = ProjectPath {
                         worktree_id: file.worktree_id(cx),
@@ -697,7 +716,7 @@ impl LocalBufferStore {
                     } else {
                         settings.is_path_read_only(&project_path.path)
                     };
-                    if is_read_only {
+                    if is_read_only || is_large {
                         buffer.update(cx, |buffer, cx| {
                             buffer.set_capability(Capability::Read, cx);
                         });
@@ -794,6 +813,7 @@ impl BufferStore {
             shared_buffers: Default::default(),
             loading_buffers: Default::default(),
             non_searchable_buffers: Default::default(),
+            large_buffers: Default::default(),
             worktree_store,
             project_search: Default::default(),
         }
@@ -820,6 +840,7 @@ impl BufferStore {
             loading_buffers: Default::default(),
             shared_buffers: Default::default(),
             non_searchable_buffers: Default::default(),
+            large_buffers: Default::default(),
             worktree_store,
             project_search: Default::default(),
         }
@@ -1027,6 +1048,11 @@ impl BufferStore {
         !self.non_searchable_buffers.contains(&id)
     }
 
+    /// Whether the buffer was opened in large file mode, see the `large_file_threshold` setting.
+    pub fn is_large_buffer(&self, id: BufferId) -> bool {
+        self.large_buffers.contains(&id)
+    }
+
     pub fn loading_buffers(
         &self,
     ) -> impl Iterator<Item = (&ProjectPath, impl Future<Output = Result<Entity<Buffer>>>)> {
@@ -1321,6 +1347,17 @@ impl BufferStore {
         capability: Capability,
         cx: &mut Context<Self>,
     ) -> Result<()> {
+        let mut capability = capability;
+        if let Some(proto::create_buffer_for_peer::Variant::State(state)) =
+            &envelope.payload.variant
+            && state.is_large
+        {
+            // Large buffers are read-only on the host, and skip language servers and git diffs
+            // there; keep them that way for the guests too.
+            self.large_buffers.insert(BufferId::new(state.id)?);
+            capability = Capability::Read;
+        }
+
         let remote = self
             .as_remote_mut()
             .context("buffer store is not a remote")?;
@@ -1580,7 +1617,8 @@ impl BufferStore {
 
             let operations = buffer.update(cx, |b, cx| b.serialize_ops(None, cx));
             let operations = operations.await;
-            let state = buffer.update(cx, |buffer, cx| buffer.to_proto(cx));
+            let mut state = buffer.update(cx, |buffer, cx| buffer.to_proto(cx));
+            state.is_large = this.read_with(cx, |this, _| this.is_large_buffer(buffer_id))?;
 
             let initial_state = proto::CreateBufferForPeer {
                 project_id,
@@ -7,13 +7,22 @@ use std::time::Duration;
 
 use anyhow::{Context as _, Result};
 use collections::{HashMap, HashSet};
-use context_server::{ContextServer, ContextServerCommand, ContextServerId};
+use context_server::{
+    AuthorizationRequired, ContextServer, ContextServerCommand, ContextServerId,
+    client::NotificationSubscription,
+    protocol::InitializedContextServerProtocol,
+    types::{
+        Notification as _, ResourcesSubscribeParams, ResourcesUnsubscribeParams,
+        ResourcesUpdatedParams, notifications, requests,
+    },
+};
 use futures::{FutureExt as _, future::join_all};
 use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity, actions};
 use registry::ContextServerDescriptorRegistry;
 use remote::RemoteClient;
 use rpc::{AnyProtoClient, TypedEnvelope, proto};
 use settings::{Settings as _, SettingsStore};
+use url::Url;
 use util::{ResultExt as _, rel_path::RelPath};
 
 use crate::{
@@ -44,6 +53,8 @@ pub enum ContextServerStatus {
     Running,
     Stopped,
     Error(Arc<str>),
+    /// The server requires the user to authorize Zed, see [`ContextServerStore::authenticate_server`].
+    AuthorizationRequired,
 }
 
 impl ContextServerStatus {
@@ -53,6 +64,9 @@ impl ContextServerStatus {
             ContextServerState::Running { .. } => ContextServerStatus::Running,
             ContextServerState::Stopped { .. } => ContextServerStatus::Stopped,
             ContextServerState::Error { error, .. } => ContextServerStatus::Error(error.clone()),
+            ContextServerState::AuthorizationRequired { .. } => {
+                ContextServerStatus::AuthorizationRequired
+            }
         }
     }
 }
@@ -76,6 +90,10 @@ enum ContextServerState {
         configuration: Arc<ContextServerConfiguration>,
         error: Arc<str>,
     },
+    AuthorizationRequired {
+        server: Arc<ContextServer>,
+        configuration: Arc<ContextServerConfiguration>,
+    },
 }
 
 impl ContextServerState {
@@ -85,6 +103,7 @@ impl ContextServerState {
             ContextServerState::Running { server, .. } => server.clone(),
             ContextServerState::Stopped { server, .. } => server.clone(),
             ContextServerState::Error { server, .. } => server.clone(),
+            ContextServerState::AuthorizationRequired { server, .. } => server.clone(),
         }
     }
 
@@ -94,6 +113,9 @@ impl ContextServerState {
             ContextServerState::Running { configuration, .. } => configuration.clone(),
             ContextServerState::Stopped { configuration, .. } => configuration.clone(),
             ContextServerState::Error { configuration, .. } => configuration.clone(),
+            ContextServerState::AuthorizationRequired { configuration, .. } => {
+                configuration.clone()
+            }
         }
     }
 }
@@ -209,14 +231,29 @@ pub struct ContextServerStore {
     update_servers_task: Option<Task<Result<()>>>,
     context_server_factory: Option<ContextServerFactory>,
     needs_server_update: bool,
+    resource_subscriptions: HashMap<ContextServerId, ResourceSubscriptions>,
     _subscriptions: Vec<Subscription>,
 }
 
+/// The resources of a context server that are subscribed to, see
+/// [`ContextServerStore::subscribe_to_resource`].
+#[derive(Default)]
+struct ResourceSubscriptions {
+    subscriber_counts: HashMap<Url, usize>,
+    /// The handler of the running server's `resources/updated` notifications.
+    resources_updated: Option<NotificationSubscription>,
+}
+
 pub enum Event {
     ServerStatusChanged {
         server_id: ContextServerId,
         status: ContextServerStatus,
     },
+    /// A subscribed resource of a server changed.
+    ResourceUpdated {
+        server_id: ContextServerId,
+        uri: String,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerStore {}
@@ -394,6 +431,7 @@ impl ContextServerStore {
             registry,
             needs_server_update: false,
             servers: HashMap::default(),
+            resource_subscriptions: HashMap::default(),
             update_servers_task: None,
             context_server_factory,
         };
@@ -520,6 +558,37 @@ impl ContextServerStore {
         result
     }
 
+    /// Authorizes Zed with a server that requires it, opening the authorization page in the
+    /// browser, and starts the server once the user has approved.
+    pub fn authenticate_server(
+        &mut self,
+        id: &ContextServerId,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let state = self.servers.get(id).context("Context server not found")?;
+        anyhow::ensure!(
+            matches!(state, ContextServerState::AuthorizationRequired { .. }),
+            "Context server {id} doesn't require authorization"
+        );
+        let (server, configuration) = (state.server(), state.configuration());
+        self.spawn_server(server, configuration, true, cx);
+        Ok(())
+    }
+
+    /// Forgets the credentials of a server that uses OAuth, and restarts it so that it requires
+    /// authorization again.
+    pub fn sign_out_server(&mut self, id: &ContextServerId, cx: &mut Context<Self>) -> Result<()> {
+        let state = self.servers.get(id).context("Context server not found")?;
+        let (server, configuration) = (state.server(), state.configuration());
+        anyhow::ensure!(server.uses_oauth(), "Context server {id} doesn't use OAuth");
+        cx.spawn(async move |this, cx| {
+            server.sign_out(cx).await?;
+            this.update(cx, |this, cx| this.run_server(server, configuration, cx))
+        })
+        .detach_and_log_err(cx);
+        Ok(())
+    }
+
     fn run_server(
         &mut self,
         server: Arc<ContextServer>,
@@ -533,13 +602,29 @@ impl ContextServerStore {
         ) {
             self.stop_server(&id, cx).log_err();
         }
+        self.spawn_server(server, configuration, false, cx);
+    }
+
+    fn spawn_server(
+        &mut self,
+        server: Arc<ContextServer>,
+        configuration: Arc<ContextServerConfiguration>,
+        authenticate: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let id = server.id();
         let task = cx.spawn({
             let id = server.id();
             let server = server.clone();
             let configuration = configuration.clone();
 
             async move |this, cx| {
-                match server.clone().start(cx).await {
+                let result = if authenticate {
+                    server.authenticate(cx).await
+                } else {
+                    server.start(cx).await
+                };
+                match result {
                     Ok(_) => {
                         debug_assert!(server.client().is_some());
 
@@ -555,6 +640,18 @@ impl ContextServerStore {
                         })
                         .log_err()
                     }
+                    Err(err) if err.is::<AuthorizationRequired>() => this
+                        .update(cx, |this, cx| {
+                            this.update_server_state(
+                                id.clone(),
+                                ContextServerState::AuthorizationRequired {
+                                    configuration,
+                                    server,
+                                },
+                                cx,
+                            )
+                        })
+                        .log_err(),
                     Err(err) => {
                         log::error!("{} context server failed to start: {}", id, err);
                         this.update(cx, |this, cx| {
@@ -591,6 +688,7 @@ impl ContextServerStore {
             .remove(id)
             .context("Context server not found")?;
         drop(state);
+        self.update_resource_subscriptions(id, cx);
         cx.emit(Event::ServerStatusChanged {
             server_id: id.clone(),
             status: ContextServerStatus::Stopped,
@@ -598,6 +696,151 @@ impl ContextServerStore {
         Ok(())
     }
 
+    /// Asks the server to notify about changes to the resource, which are emitted as
+    /// [`Event::ResourceUpdated`], also after the server restarts.
+    ///
+    /// The subscriptions are counted per resource, so that the server is asked only once however
+    /// many subscribers there are. Each call must be balanced by a call to
+    /// [`Self::unsubscribe_from_resource`].
+    pub fn subscribe_to_resource(
+        &mut self,
+        server_id: ContextServerId,
+        uri: Url,
+        cx: &mut Context<Self>,
+    ) {
+        let is_new_server = !self.resource_subscriptions.contains_key(&server_id);
+        let subscriptions = self
+            .resource_subscriptions
+            .entry(server_id.clone())
+            .or_default();
+        let subscriber_count = subscriptions
+            .subscriber_counts
+            .entry(uri.clone())
+            .or_default();
+        *subscriber_count += 1;
+        let is_new_resource = *subscriber_count == 1;
+
+        if is_new_server {
+            self.update_resource_subscriptions(&server_id, cx);
+        } else if is_new_resource && let Some(client) = self.subscribable_client(&server_id) {
+            Self::send_resource_subscription(client, uri, true, cx);
+        }
+    }
+
+    pub fn unsubscribe_from_resource(
+        &mut self,
+        server_id: &ContextServerId,
+        uri: &Url,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(subscriptions) = self.resource_subscriptions.get_mut(server_id) else {
+            return;
+        };
+        let Some(subscriber_count) = subscriptions.subscriber_counts.get_mut(uri) else {
+            return;
+        };
+        *subscriber_count -= 1;
+        if *subscriber_count > 0 {
+            return;
+        }
+
+        subscriptions.subscriber_counts.remove(uri);
+        if subscriptions.subscriber_counts.is_empty() {
+            self.resource_subscriptions.remove(server_id);
+        }
+        if let Some(client) = self.subscribable_client(server_id) {
+            Self::send_resource_subscription(client, uri.clone(), false, cx);
+        }
+    }
+
+    /// Handles the `resources/updated` notifications of the server if it's running, and asks it
+    /// to notify about the subscribed resources.
+    fn update_resource_subscriptions(&mut self, id: &ContextServerId, cx: &mut Context<Self>) {
+        let client = self
+            .get_running_server(id)
+            .and_then(|server| server.client());
+        let subscribable_client = self.subscribable_client(id);
+        let Some(subscriptions) = self.resource_subscriptions.get_mut(id) else {
+            return;
+        };
+        let Some(client) = client else {
+            subscriptions.resources_updated = None;
+            return;
+        };
+
+        let this = cx.entity().downgrade();
+        let server_id = id.clone();
+        subscriptions.resources_updated = Some(client.on_notification(
+            notifications::ResourcesUpdated::METHOD,
+            Box::new(move |params, cx: AsyncApp| {
+                let Some(params) =
+                    serde_json::from_value::<ResourcesUpdatedParams>(params).log_err()
+                else {
+                    return;
+                };
+                let this = this.clone();
+                let server_id = server_id.clone();
+                cx.spawn(async move |cx| {
+                    this.update(cx, |_, cx| {
+                        cx.emit(Event::ResourceUpdated {
+                            server_id,
+                            uri: params.uri,
+                        })
+                    })
+                })
+                .detach();
+            }),
+        ));
+
+        if let Some(client) = subscribable_client {
+            for uri in subscriptions.subscriber_counts.keys() {
+                Self::send_resource_subscription(client.clone(), uri.clone(), true, cx);
+            }
+        }
+    }
+
+    /// Returns the client of the server if it's running and supports resource subscriptions.
+    fn subscribable_client(
+        &self,
+        id: &ContextServerId,
+    ) -> Option<Arc<InitializedContextServerProtocol>> {
+        let client = self.get_running_server(id)?.client()?;
+        client
+            .initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+            .then_some(client)
+    }
+
+    fn send_resource_subscription(
+        client: Arc<InitializedContextServerProtocol>,
+        uri: Url,
+        subscribe: bool,
+        cx: &mut Context<Self>,
+    ) {
+        cx.background_spawn(async move {
+            if subscribe {
+                client
+                    .request::<requests::ResourcesSubscribe>(ResourcesSubscribeParams {
+                        uri,
+                        meta: None,
+                    })
+                    .await
+            } else {
+                client
+                    .request::<requests::ResourcesUnsubscribe>(ResourcesUnsubscribeParams {
+                        uri,
+                        meta: None,
+                    })
+                    .await
+            }
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub async fn create_context_server(
         this: WeakEntity<Self>,
         id: ContextServerId,
@@ -808,6 +1051,7 @@ impl ContextServerStore {
     ) {
         let status = ContextServerStatus::from_state(&state);
         self.servers.insert(id.clone(), state);
+        self.update_resource_subscriptions(&id, cx);
         cx.emit(Event::ServerStatusChanged {
             server_id: id,
             status,
@@ -671,6 +671,9 @@ impl GitStore {
         cx: &mut Context<Self>,
     ) -> Task<Result<Entity<BufferDiff>>> {
         let buffer_id = buffer.read(cx).remote_id();
+        if self.buffer_store.read(cx).is_large_buffer(buffer_id) {
+            return Task::ready(Err(anyhow!("git diffs are disabled for large files")));
+        }
         if let Some(diff_state) = self.diffs.get(&buffer_id)
             && let Some(unstaged_diff) = diff_state
                 .read(cx)
@@ -828,6 +831,9 @@ impl GitStore {
         cx: &mut Context<Self>,
     ) -> Task<Result<Entity<BufferDiff>>> {
         let buffer_id = buffer.read(cx).remote_id();
+        if self.buffer_store.read(cx).is_large_buffer(buffer_id) {
+            return Task::ready(Err(anyhow!("git diffs are disabled for large files")));
+        }
 
         if let Some(diff_state) = self.diffs.get(&buffer_id)
             && let Some(uncommitted_diff) = diff_state
@@ -5562,6 +5568,34 @@ impl Repository {
         )
     }
 
+    pub fn remove_worktree(&mut self, path: PathBuf) -> oneshot::Receiver<Result<()>> {
+        self.send_job(
+            Some("git worktree remove".into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
+                        backend.remove_worktree(path).await
+                    }
+                    RepositoryState::Remote(..) => anyhow::bail!("not implemented yet"),
+                }
+            },
+        )
+    }
+
+    pub fn squash_merge(&mut self, branch: String) -> oneshot::Receiver<Result<()>> {
+        self.send_job(
+            Some(format!("git merge --squash {branch}").into()),
+            move |repo, _cx| async move {
+                match repo {
+                    RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
+                        backend.squash_merge(branch).await
+                    }
+                    RepositoryState::Remote(..) => anyhow::bail!("not implemented yet"),
+                }
+            },
+        )
+    }
+
     pub fn default_branch(
         &mut self,
         include_remote_name: bool,
@@ -15,6 +15,7 @@ pub mod log_store;
 pub mod lsp_ext_command;
 pub mod rust_analyzer_ext;
 mod semantic_tokens;
+pub mod task_diagnostics;
 pub mod vue_language_server_ext;
 
 mod inlay_hint_cache;
@@ -126,6 +127,7 @@ use std::{
     vec,
 };
 use sum_tree::Dimensions;
+use task::TaskId;
 use text::{Anchor, BufferId, LineEnding, OffsetRangeExt, ToPoint as _};
 
 use util::{
@@ -296,6 +298,7 @@ pub struct LocalLspStore {
         HashMap<LanguageServerId, (LanguageServerName, Arc<LanguageServer>)>,
     prettier_store: Entity<PrettierStore>,
     next_diagnostic_group_id: usize,
+    task_diagnostic_paths: HashMap<TaskId, Vec<PathBuf>>,
     diagnostics: HashMap<
         WorktreeId,
         HashMap<
@@ -4152,6 +4155,7 @@ impl LspStore {
                 fs,
                 yarn,
                 next_diagnostic_group_id: Default::default(),
+                task_diagnostic_paths: HashMap::default(),
                 diagnostics: Default::default(),
                 _subscription: cx.on_app_quit(|this, cx| {
                     this.as_local_mut()
@@ -4449,6 +4453,7 @@ impl LspStore {
     ) -> OpenLspBufferHandle {
         let buffer_id = buffer.read(cx).remote_id();
         let handle = OpenLspBufferHandle(cx.new(|_| OpenLspBuffer(buffer.clone())));
+        let is_large = self.buffer_store.read(cx).is_large_buffer(buffer_id);
         if let Some(local) = self.as_local_mut() {
             let refcount = local.registered_buffers.entry(buffer_id).or_insert(0);
             if !ignore_refcounts {
@@ -4462,7 +4467,7 @@ impl LspStore {
             let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
                 return handle;
             };
-            if !file.is_local() {
+            if !file.is_local() || is_large {
                 return handle;
             }
 
@@ -4670,6 +4675,14 @@ impl LspStore {
         // If the buffer has a language, set it and start the language server if we haven't already.
         let buffer = buffer_handle.read(cx);
         let file = buffer.file()?;
+        // Large files stay plain text, parsing them would make them unusable.
+        if self
+            .buffer_store
+            .read(cx)
+            .is_large_buffer(buffer.remote_id())
+        {
+            return None;
+        }
 
         let content = buffer.as_rope();
         let available_language = self.languages.language_for_file(file, Some(content), cx);
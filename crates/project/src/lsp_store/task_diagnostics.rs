@@ -0,0 +1,129 @@
+use std::{borrow::Cow, path::PathBuf};
+
+use gpui::Context;
+use language::{Diagnostic, DiagnosticEntry, DiagnosticSourceKind, PointUtf16, Unclipped};
+use lsp::{DiagnosticSeverity, LanguageServerId, NumberOrString};
+use task::{Problem, ProblemSeverity, TaskId};
+use util::post_inc;
+
+use crate::lsp_store::{DocumentDiagnostics, DocumentDiagnosticsUpdate, LspStore};
+
+/// The id that diagnostics found in task output are reported under, as they have no language
+/// server.
+pub const TASK_DIAGNOSTICS_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX - 1);
+pub const TASK_DIAGNOSTICS_SOURCE: &str = "task";
+
+impl LspStore {
+    /// Replaces the diagnostics found in the output of the task with the given id.
+    pub fn update_task_diagnostics(
+        &mut self,
+        task_id: TaskId,
+        problems: Vec<Problem>,
+        cx: &mut Context<Self>,
+    ) -> anyhow::Result<()> {
+        let Some(local) = self.as_local_mut() else {
+            return Ok(());
+        };
+
+        let mut diagnostics_by_path = Vec::<(PathBuf, Vec<_>)>::new();
+        // Update the paths that had diagnostics from the task's previous output too, so that they
+        // get cleared.
+        for path in local
+            .task_diagnostic_paths
+            .remove(&task_id)
+            .unwrap_or_default()
+        {
+            diagnostics_by_path.push((path, Vec::new()));
+        }
+        for problem in problems {
+            let entry = DiagnosticEntry {
+                range: problem_range(&problem),
+                diagnostic: Diagnostic {
+                    source: Some(TASK_DIAGNOSTICS_SOURCE.to_string()),
+                    source_kind: DiagnosticSourceKind::Other,
+                    code: problem.code.map(NumberOrString::String),
+                    severity: match problem.severity {
+                        ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                        ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                        ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                        ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+                    },
+                    message: problem.message,
+                    group_id: post_inc(&mut local.next_diagnostic_group_id),
+                    is_primary: true,
+                    data: Some(serde_json::json!({ "task_id": &task_id.0 })),
+                    ..Diagnostic::default()
+                },
+            };
+            match diagnostics_by_path
+                .iter_mut()
+                .find(|(path, _)| *path == problem.path)
+            {
+                Some((_, diagnostics)) => diagnostics.push(entry),
+                None => diagnostics_by_path.push((problem.path, vec![entry])),
+            }
+        }
+
+        let worktree_store = self.worktree_store.read(cx);
+        diagnostics_by_path.retain(|(path, _)| worktree_store.find_worktree(path, cx).is_some());
+        if let Some(local) = self.as_local_mut() {
+            let paths = diagnostics_by_path
+                .iter()
+                .filter(|(_, diagnostics)| !diagnostics.is_empty())
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>();
+            if !paths.is_empty() {
+                local.task_diagnostic_paths.insert(task_id.clone(), paths);
+            }
+        }
+
+        let updates = diagnostics_by_path
+            .into_iter()
+            .map(|(path, diagnostics)| DocumentDiagnosticsUpdate {
+                diagnostics: DocumentDiagnostics {
+                    diagnostics,
+                    document_abs_path: path,
+                    version: None,
+                },
+                result_id: None,
+                registration_id: None,
+                server_id: TASK_DIAGNOSTICS_SERVER_ID,
+                disk_based_sources: Cow::Borrowed(&[]),
+            })
+            .collect::<Vec<_>>();
+        if updates.is_empty() {
+            return Ok(());
+        }
+        // Keep the diagnostics of other tasks.
+        self.merge_diagnostic_entries(
+            updates,
+            move |_, diagnostic, _| {
+                diagnostic
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.get("task_id"))
+                    .and_then(|id| id.as_str())
+                    != Some(task_id.0.as_str())
+            },
+            cx,
+        )
+    }
+}
+
+/// Problems without a column cover their whole line, and ones without an end cover a character.
+fn problem_range(problem: &Problem) -> std::ops::Range<Unclipped<PointUtf16>> {
+    let row = problem.line.saturating_sub(1);
+    let Some(column) = problem.column else {
+        return Unclipped(PointUtf16::new(row, 0))..Unclipped(PointUtf16::new(row, u32::MAX));
+    };
+    let start = PointUtf16::new(row, column.saturating_sub(1));
+    let end = match (problem.end_line, problem.end_column) {
+        (end_line, Some(end_column)) => PointUtf16::new(
+            end_line.map_or(row, |line| line.saturating_sub(1)),
+            end_column.saturating_sub(1),
+        ),
+        (Some(end_line), None) => PointUtf16::new(end_line.saturating_sub(1), u32::MAX),
+        (None, None) => PointUtf16::new(row, start.column + 1),
+    };
+    Unclipped(start)..Unclipped(end.max(start))
+}
@@ -1,5 +1,5 @@
-use anyhow::Result;
-use collections::HashMap;
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
 use gpui::{App, AppContext as _, Context, Entity, Task, WeakEntity};
 
 use futures::{FutureExt, future::Shared};
@@ -9,20 +9,34 @@ use remote::RemoteClient;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
+    mem,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
+};
+use task::{
+    ProblemMatchState, ProblemMatcher, Shell, ShellBuilder, ShellKind, SpawnInTerminal, TaskId,
 };
-use task::{Shell, ShellBuilder, ShellKind, SpawnInTerminal};
 use terminal::{
-    TaskState, TaskStatus, Terminal, TerminalBuilder, insert_zed_terminal_env,
+    OutputCursor, TaskState, TaskStatus, Terminal, TerminalBuilder, insert_zed_terminal_env,
+    sessions::{SESSION_COMMAND_ENV, SessionCommand},
     terminal_settings::TerminalSettings,
 };
-use util::{command::new_std_command, get_default_system_shell, maybe, rel_path::RelPath};
+use util::{
+    ResultExt as _, command::new_std_command, get_default_system_shell, maybe, paths::PathStyle,
+    rel_path::RelPath,
+};
+use uuid::Uuid;
 
 use crate::{Project, ProjectPath};
 
+/// How long to wait for more output before scanning a task's output for problems.
+const PROBLEM_SCAN_DEBOUNCE: Duration = Duration::from_millis(500);
+
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
+    /// Scans of the output of tasks with problem matchers, by the id of the task.
+    pub(crate) problem_scans: HashMap<TaskId, Task<()>>,
 }
 
 impl Project {
@@ -92,12 +106,21 @@ impl Project {
         let (completion_tx, completion_rx) = bounded(1);
 
         let local_path = if is_via_remote { None } else { path.clone() };
+        let problem_scan = (!is_via_remote && !spawn_task.problem_matchers.is_empty()).then(|| {
+            let matchers = spawn_task
+                .problem_matchers
+                .iter()
+                .filter_map(|matcher| ProblemMatcher::new(matcher).log_err())
+                .collect::<Vec<_>>();
+            (spawn_task.id.clone(), matchers, local_path.clone())
+        });
         let task_state = Some(TaskState {
             spawned_task: spawn_task.clone(),
             status: TaskStatus::Running,
             completion_rx,
         });
         let remote_client = self.remote_client.clone();
+        let session_id = new_session_id(&settings, remote_client.as_ref(), cx);
         let shell = match &remote_client {
             Some(remote_client) => remote_client
                 .read(cx)
@@ -192,6 +215,7 @@ impl Project {
                                         env,
                                         path,
                                         remote_client,
+                                        session_id.as_deref(),
                                         cx,
                                     )?
                                 }
@@ -203,6 +227,7 @@ impl Project {
                                     env,
                                     path,
                                     remote_client,
+                                    session_id.as_deref(),
                                     cx,
                                 )?,
                             },
@@ -256,6 +281,7 @@ impl Project {
                         cx,
                         activation_script,
                         path_style,
+                        session_id,
                     ))
                 })??
                 .await?;
@@ -280,17 +306,132 @@ impl Project {
                 })
                 .detach();
 
+                if let Some((task_id, matchers, cwd)) = problem_scan {
+                    this.scan_task_output_for_problems(
+                        task_id,
+                        matchers,
+                        cwd,
+                        &terminal_handle,
+                        cx,
+                    );
+                }
+
                 terminal_handle
             })
         })
     }
 
+    /// Publishes the problems found in a task's output as diagnostics, replacing the ones found
+    /// in the output of its previous run.
+    fn scan_task_output_for_problems(
+        &mut self,
+        task_id: TaskId,
+        matchers: Vec<ProblemMatcher>,
+        cwd: Option<Arc<Path>>,
+        terminal: &Entity<Terminal>,
+        cx: &mut Context<Self>,
+    ) {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_task_diagnostics(task_id.clone(), Vec::new(), cx)
+                .log_err();
+        });
+        if matchers.is_empty() {
+            self.terminals.problem_scans.remove(&task_id);
+            return;
+        }
+
+        let (output_tx, output_rx) = smol::channel::unbounded::<()>();
+        cx.subscribe(terminal, move |_, _, event, _| {
+            if matches!(event, terminal::Event::Wakeup) {
+                output_tx.try_send(()).ok();
+            }
+        })
+        .detach();
+
+        let matchers = Arc::new(matchers);
+        let terminal = terminal.downgrade();
+        let scan = cx.spawn(async move |project, cx| {
+            // Only the output that was added since the previous pass is matched, carrying the
+            // problems found so far over. Output can be read again, e.g. after the scrollback
+            // was cleared, so problems that were already found are skipped.
+            let mut output_cursor = OutputCursor::default();
+            let mut match_states = vec![ProblemMatchState::default(); matchers.len()];
+            let mut problems = Vec::new();
+            let mut found_problems = HashSet::default();
+            while output_rx.recv().await.is_ok() {
+                cx.background_executor().timer(PROBLEM_SCAN_DEBOUNCE).await;
+                while output_rx.try_recv().is_ok() {}
+
+                let Ok(output) = terminal.read_with(cx, |terminal, _| {
+                    terminal.read_completed_output(&mut output_cursor)
+                }) else {
+                    break;
+                };
+                if output.is_empty() {
+                    continue;
+                }
+                let (new_problems, states) = cx
+                    .background_spawn({
+                        let matchers = matchers.clone();
+                        let cwd = cwd.clone();
+                        let mut states = mem::take(&mut match_states);
+                        async move {
+                            let problems = matchers
+                                .iter()
+                                .zip(&mut states)
+                                .flat_map(|(matcher, state)| {
+                                    matcher.match_lines(output.lines(), cwd.as_deref(), state)
+                                })
+                                .collect::<Vec<_>>();
+                            (problems, states)
+                        }
+                    })
+                    .await;
+                match_states = states;
+                let new_problems = new_problems
+                    .into_iter()
+                    .filter(|problem| found_problems.insert(problem.clone()))
+                    .collect::<Vec<_>>();
+                if new_problems.is_empty() {
+                    continue;
+                }
+                problems.extend(new_problems);
+
+                let updated = project.update(cx, |project, cx| {
+                    project.lsp_store.update(cx, |lsp_store, cx| {
+                        lsp_store
+                            .update_task_diagnostics(task_id.clone(), problems.clone(), cx)
+                            .log_err();
+                    })
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        });
+        // Replacing the scan of the task's previous run stops it from publishing stale problems.
+        self.terminals.problem_scans.insert(task_id, scan);
+    }
+
     pub fn create_terminal_shell(
         &mut self,
         cwd: Option<PathBuf>,
         cx: &mut Context<Self>,
     ) -> Task<Result<Entity<Terminal>>> {
-        self.create_terminal_shell_internal(cwd, false, cx)
+        self.create_terminal_shell_internal(cwd, false, None, cx)
+    }
+
+    /// Creates a terminal attached to an existing persistent session, such as one whose terminal
+    /// was restored with the workspace. If the session ended in the meantime, a new one with the
+    /// same id is started.
+    pub fn reattach_terminal_session(
+        &mut self,
+        cwd: Option<PathBuf>,
+        session_id: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Terminal>>> {
+        self.create_terminal_shell_internal(cwd, false, Some(session_id), cx)
     }
 
     /// Creates a local terminal even if the project is remote.
@@ -307,16 +448,18 @@ impl Project {
             // Local project: use project directory like normal terminals
             self.active_project_directory(cx).map(|p| p.to_path_buf())
         };
-        self.create_terminal_shell_internal(working_directory, true, cx)
+        self.create_terminal_shell_internal(working_directory, true, None, cx)
     }
 
     /// Internal method for creating terminal shells.
     /// If force_local is true, creates a local terminal even if the project has a remote client.
     /// This allows "breaking out" to a local shell in remote projects.
+    /// Without a session id, the terminal gets a new session if persistent sessions are enabled.
     fn create_terminal_shell_internal(
         &mut self,
         cwd: Option<PathBuf>,
         force_local: bool,
+        session_id: Option<String>,
         cx: &mut Context<Self>,
     ) -> Task<Result<Entity<Terminal>>> {
         let path = cwd.map(|p| Arc::from(&*p));
@@ -332,7 +475,9 @@ impl Project {
             });
         }
         let settings = TerminalSettings::get(settings_location, cx).clone();
-        let detect_venv = settings.detect_venv.as_option().is_some();
+        // A reattached session's shell was already activated, and activating it again would clear
+        // the output it replays.
+        let detect_venv = settings.detect_venv.as_option().is_some() && session_id.is_none();
         let local_path = if is_via_remote { None } else { path.clone() };
 
         let project_path_contexts = self
@@ -356,6 +501,12 @@ impl Project {
         } else {
             self.remote_client.clone()
         };
+        let session_id = match session_id {
+            Some(session_id) => {
+                supports_terminal_sessions(remote_client.as_ref(), cx).then_some(session_id)
+            }
+            None => new_session_id(&settings, remote_client.as_ref(), cx),
+        };
         let shell = match &remote_client {
             Some(remote_client) => remote_client
                 .read(cx)
@@ -399,9 +550,14 @@ impl Project {
                 .update(cx, move |_, cx| {
                     let (shell, env) = {
                         match remote_client {
-                            Some(remote_client) => {
-                                create_remote_shell(None, env, path, remote_client, cx)?
-                            }
+                            Some(remote_client) => create_remote_shell(
+                                None,
+                                env,
+                                path,
+                                remote_client,
+                                session_id.as_deref(),
+                                cx,
+                            )?,
                             None => (settings.shell, env),
                         }
                     };
@@ -421,6 +577,7 @@ impl Project {
                         cx,
                         activation_script,
                         path_style,
+                        session_id,
                     ))
                 })??
                 .await?;
@@ -458,7 +615,9 @@ impl Project {
     ) -> Task<Result<Entity<Terminal>>> {
         // We cannot clone the task's terminal, as it will effectively re-spawn the task, which might not be desirable.
         // For now, create a new shell instead.
-        if terminal.read(cx).task().is_some() {
+        // A terminal with a session is attached to it by its command, so its clone gets a new shell
+        // with its own session too.
+        if terminal.read(cx).task().is_some() || terminal.read(cx).session_id().is_some() {
             return self.create_terminal_shell(cwd, cx);
         }
         let local_path = if self.is_via_remote_server() {
@@ -604,20 +763,73 @@ impl Project {
     }
 }
 
+/// Returns the id of a new persistent session for a terminal, if the settings ask for one and the
+/// machine the terminal runs on supports them.
+fn new_session_id(
+    settings: &TerminalSettings,
+    remote_client: Option<&Entity<RemoteClient>>,
+    cx: &App,
+) -> Option<String> {
+    (settings.persistent_sessions && supports_terminal_sessions(remote_client, cx))
+        .then(|| Uuid::new_v4().to_string())
+}
+
+fn supports_terminal_sessions(remote_client: Option<&Entity<RemoteClient>>, cx: &App) -> bool {
+    match remote_client {
+        // Remote sessions are hosted by the remote server.
+        Some(remote_client) => {
+            let remote_client = remote_client.read(cx);
+            !remote_client.path_style().is_windows() && remote_client.remote_binary_path().is_some()
+        }
+        None => cfg!(unix),
+    }
+}
+
 fn create_remote_shell(
     spawn_command: Option<(&String, &Vec<String>)>,
     mut env: HashMap<String, String>,
     working_directory: Option<Arc<Path>>,
     remote_client: Entity<RemoteClient>,
+    session_id: Option<&str>,
     cx: &mut App,
 ) -> Result<(Shell, HashMap<String, String>)> {
     insert_zed_terminal_env(&mut env, &release_channel::AppVersion::global(cx));
 
-    let (program, args) = match spawn_command {
-        Some((program, args)) => (Some(program.clone()), args),
-        None => (None, &Vec::new()),
+    let (mut program, mut args) = match spawn_command {
+        Some((program, args)) => (Some(program.clone()), args.clone()),
+        None => (None, Vec::new()),
     };
 
+    // The remote server attaches to the session, which runs the command in its place.
+    if let Some(session_id) = session_id {
+        let remote_client = remote_client.read(cx);
+        let binary_path = remote_client
+            .remote_binary_path()
+            .context("remote server binary not found")?;
+        let command = match program.take() {
+            Some(program) => SessionCommand {
+                program: Some(program),
+                args: std::mem::take(&mut args),
+            },
+            None => SessionCommand {
+                program: remote_client.shell(),
+                args: vec!["-l".to_string()],
+            },
+        };
+        env.insert(
+            SESSION_COMMAND_ENV.to_string(),
+            serde_json::to_string(&command)?,
+        );
+        program = Some("sh".to_string());
+        args = vec![
+            "-c".to_string(),
+            format!(
+                "exec \"$HOME/{}\" attach-terminal-session {session_id}",
+                binary_path.display(PathStyle::Posix)
+            ),
+        ];
+    }
+
     let command = remote_client.read(cx).build_command(
         program,
         args.as_slice(),
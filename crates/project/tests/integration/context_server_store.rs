@@ -647,6 +647,146 @@ impl Drop for ServerEvents {
     }
 }
 
+#[gpui::test]
+async fn test_context_server_resource_subscriptions(cx: &mut TestAppContext) {
+    use context_server::types::{
+        Implementation, InitializeResponse, ProtocolVersion, ResourcesCapabilities,
+        ServerCapabilities, requests,
+    };
+
+    const SERVER_ID: &str = "docs";
+
+    let (_fs, project) = setup_context_server_test(cx, json!({"code.rs": ""}), vec![]).await;
+
+    let registry = cx.new(|_| ContextServerDescriptorRegistry::new());
+    let store = cx.new(|cx| {
+        ContextServerStore::test(
+            registry.clone(),
+            project.read(cx).worktree_store(),
+            Some(project.downgrade()),
+            cx,
+        )
+    });
+
+    let requests = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let transport = create_fake_transport(SERVER_ID, cx.executor())
+        .on_request::<requests::Initialize, _>(|_| async {
+            InitializeResponse {
+                protocol_version: ProtocolVersion(
+                    context_server::types::LATEST_PROTOCOL_VERSION.to_string(),
+                ),
+                server_info: Implementation {
+                    name: SERVER_ID.into(),
+                    version: "1.0.0".into(),
+                },
+                capabilities: ServerCapabilities {
+                    resources: Some(ResourcesCapabilities {
+                        subscribe: Some(true),
+                        list_changed: None,
+                    }),
+                    ..Default::default()
+                },
+                meta: None,
+            }
+        })
+        .on_request::<requests::ResourcesSubscribe, _>({
+            let requests = requests.clone();
+            move |params| {
+                requests.lock().push(format!("subscribe {}", params.uri));
+                async {}
+            }
+        })
+        .on_request::<requests::ResourcesUnsubscribe, _>({
+            let requests = requests.clone();
+            move |params| {
+                requests.lock().push(format!("unsubscribe {}", params.uri));
+                async {}
+            }
+        });
+    let server_messages = transport.sender();
+
+    let server_id = ContextServerId(SERVER_ID.into());
+    let notes_uri = url::Url::parse("docs://notes").unwrap();
+    let todo_uri = url::Url::parse("docs://todo").unwrap();
+
+    let updated_uris = Rc::new(RefCell::new(Vec::new()));
+    let _subscription = cx.update(|cx| {
+        let updated_uris = updated_uris.clone();
+        cx.subscribe(&store, move |_, event, _| {
+            if let Event::ResourceUpdated { uri, .. } = event {
+                updated_uris.borrow_mut().push(uri.clone());
+            }
+        })
+    });
+
+    // Subscriptions made before the server runs are sent once it starts.
+    store.update(cx, |store, cx| {
+        store.subscribe_to_resource(server_id.clone(), notes_uri.clone(), cx);
+        store.subscribe_to_resource(server_id.clone(), notes_uri.clone(), cx);
+    });
+    store.update(cx, |store, cx| {
+        store.test_start_server(
+            Arc::new(ContextServer::new(server_id.clone(), Arc::new(transport))),
+            cx,
+        )
+    });
+    cx.run_until_parked();
+    assert_eq!(*requests.lock(), ["subscribe docs://notes"]);
+
+    store.update(cx, |store, cx| {
+        store.subscribe_to_resource(server_id.clone(), todo_uri.clone(), cx);
+        store.subscribe_to_resource(server_id.clone(), notes_uri.clone(), cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        *requests.lock(),
+        ["subscribe docs://notes", "subscribe docs://todo"]
+    );
+
+    server_messages
+        .unbounded_send(
+            json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/resources/updated",
+                "params": { "uri": "docs://notes" },
+            })
+            .to_string(),
+        )
+        .unwrap();
+    cx.run_until_parked();
+    assert_eq!(*updated_uris.borrow(), ["docs://notes"]);
+
+    // The server is asked to stop notifying about a resource once it has no subscribers.
+    store.update(cx, |store, cx| {
+        store.unsubscribe_from_resource(&server_id, &notes_uri, cx);
+        store.unsubscribe_from_resource(&server_id, &notes_uri, cx);
+        store.unsubscribe_from_resource(&server_id, &todo_uri, cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        *requests.lock(),
+        [
+            "subscribe docs://notes",
+            "subscribe docs://todo",
+            "unsubscribe docs://todo"
+        ]
+    );
+
+    store.update(cx, |store, cx| {
+        store.unsubscribe_from_resource(&server_id, &notes_uri, cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        *requests.lock(),
+        [
+            "subscribe docs://notes",
+            "subscribe docs://todo",
+            "unsubscribe docs://todo",
+            "unsubscribe docs://notes"
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_context_server_global_timeout(cx: &mut TestAppContext) {
     cx.update(|cx| {
@@ -816,6 +956,7 @@ fn assert_server_events(
                     ix += 1;
                     *received_event_count.borrow_mut() += 1;
                 }
+                Event::ResourceUpdated { .. } => {}
             }
         });
         ServerEvents {
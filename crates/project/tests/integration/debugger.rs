@@ -22,6 +22,7 @@ mod go_locator {
             tags: vec![],
             show_summary: true,
             show_command: true,
+            problem_matchers: Vec::new(),
         };
 
         let scenario = locator
@@ -49,6 +50,7 @@ mod go_locator {
             tags: vec![],
             show_summary: true,
             show_command: true,
+            problem_matchers: Vec::new(),
         };
 
         let scenario = locator
@@ -187,6 +189,7 @@ mod go_locator {
             tags: vec![],
             show_summary: true,
             show_command: true,
+            problem_matchers: Vec::new(),
         };
 
         let scenario = locator
@@ -221,6 +224,7 @@ mod python_locator {
             shell: task::Shell::System,
             show_summary: false,
             show_command: false,
+            problem_matchers: Vec::new(),
         };
 
         let expected_scenario = DebugScenario {
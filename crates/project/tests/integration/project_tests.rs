@@ -81,7 +81,7 @@ use std::{
     time::Duration,
 };
 use sum_tree::SumTree;
-use task::{ResolvedTask, ShellKind, TaskContext};
+use task::{ProblemMatcherTemplate, ResolvedTask, ShellKind, SpawnInTerminal, TaskContext, TaskId};
 use text::{Anchor, PointUtf16, ReplicaId, ToOffset, Unclipped};
 use unindent::Unindent as _;
 use util::{
@@ -12013,6 +12013,194 @@ async fn test_read_only_files_empty_setting(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_large_file_mode(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings.project.worktree.large_file_threshold = Some(64);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        path!("/root"),
+        json!({
+            "small.rs": "fn main() {}",
+            "large.rs": "fn main() {}\n".repeat(16),
+            "large_crlf.txt": "line\r\n".repeat(16),
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let small_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/root/small.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let large_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/root/large.rs"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    project.read_with(cx, |project, cx| {
+        let buffer_store = project.buffer_store().read(cx);
+        assert!(!buffer_store.is_large_buffer(small_buffer.read(cx).remote_id()));
+        assert!(buffer_store.is_large_buffer(large_buffer.read(cx).remote_id()));
+    });
+    small_buffer.read_with(cx, |buffer, _| {
+        assert!(!buffer.read_only(), "Small files should stay editable");
+    });
+    large_buffer.read_with(cx, |buffer, _| {
+        assert!(buffer.read_only(), "Large files should be read-only");
+        assert!(
+            buffer.language().is_none(),
+            "Large files should not be parsed"
+        );
+        assert_eq!(buffer.text(), "fn main() {}\n".repeat(16));
+    });
+
+    let large_crlf_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/root/large_crlf.txt"), cx)
+        })
+        .await
+        .unwrap();
+    large_crlf_buffer.read_with(cx, |buffer, _| {
+        assert!(buffer.read_only());
+        assert_eq!(buffer.text(), "line\n".repeat(16));
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+    });
+
+    let diff = project
+        .update(cx, |project, cx| {
+            project.open_uncommitted_diff(large_buffer.clone(), cx)
+        })
+        .await;
+    assert!(diff.is_err(), "Large files should not be diffed");
+}
+
+#[cfg(unix)]
+#[gpui::test]
+async fn test_task_problem_matchers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let dir = TempTree::new(json!({
+        "src": {
+            "main.rs": "fn main() {\n    let x: u32 = \"a\";\n}\n",
+        },
+    }));
+    let root = dir.path().canonicalize().unwrap();
+    let project = Project::test(
+        Arc::new(RealFs::new(None, cx.executor())),
+        [root.as_ref()],
+        cx,
+    )
+    .await;
+    cx.executor().run_until_parked();
+
+    let wait_for_error_count = |expected: usize, cx: &mut gpui::TestAppContext| {
+        for _ in 0..100 {
+            cx.executor().advance_clock(Duration::from_secs(1));
+            cx.executor().run_until_parked();
+            let summary =
+                project.read_with(cx, |project, cx| project.diagnostic_summary(false, cx));
+            if summary.error_count == expected {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("expected {expected} task diagnostics");
+    };
+
+    let _terminal = run_check_task(
+        &project,
+        &root,
+        "echo 'error[E0308]: mismatched types'; echo ' --> src/main.rs:2:18'",
+        cx,
+    )
+    .await;
+    wait_for_error_count(1, cx);
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(root.join("src/main.rs"), cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .collect::<Vec<_>>();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, Point::new(1, 17));
+        assert_eq!(diagnostics[0].diagnostic.message, "mismatched types");
+    });
+
+    // Running the task again replaces the problems found in its previous output.
+    let _terminal = run_check_task(&project, &root, "echo 'Finished'", cx).await;
+    wait_for_error_count(0, cx);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .count(),
+            0
+        );
+    });
+
+    // A problem printed twice in a run is reported once.
+    let error = "echo 'error[E0308]: mismatched types'; echo ' --> src/main.rs:2:18'";
+    let _terminal = run_check_task(&project, &root, &format!("{error}; {error}"), cx).await;
+    wait_for_error_count(1, cx);
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    let summary = project.read_with(cx, |project, cx| project.diagnostic_summary(false, cx));
+    assert_eq!(summary.error_count, 1);
+}
+
+#[cfg(unix)]
+async fn run_check_task(
+    project: &Entity<Project>,
+    cwd: &Path,
+    command: &str,
+    cx: &mut gpui::TestAppContext,
+) -> Entity<terminal::Terminal> {
+    let terminal = project
+        .update(cx, |project, cx| {
+            project.create_terminal_task(
+                SpawnInTerminal {
+                    id: TaskId("check".to_string()),
+                    label: "check".to_string(),
+                    command: Some(command.to_string()),
+                    cwd: Some(cwd.to_path_buf()),
+                    problem_matchers: vec![ProblemMatcherTemplate::BuiltIn("$rustc".to_string())],
+                    ..SpawnInTerminal::default()
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    terminal
+        .update(cx, |terminal, cx| terminal.wait_for_completed_task(cx))
+        .await;
+    terminal
+}
+
 #[gpui::test]
 async fn test_read_only_files_with_lock_files(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -49,6 +49,7 @@ use std::{
 use util::{
     ResultExt,
     paths::{PathStyle, RemotePathBuf},
+    rel_path::RelPath,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -898,6 +899,10 @@ impl RemoteClient {
         Some(self.remote_connection()?.default_system_shell())
     }
 
+    pub fn remote_binary_path(&self) -> Option<Arc<RelPath>> {
+        self.remote_connection()?.remote_binary_path()
+    }
+
     pub fn shares_network_interface(&self) -> bool {
         self.remote_connection()
             .map_or(false, |connection| connection.shares_network_interface())
@@ -1346,6 +1351,10 @@ pub trait RemoteConnection: Send + Sync {
     fn shell(&self) -> String;
     fn default_system_shell(&self) -> String;
     fn has_wsl_interop(&self) -> bool;
+    /// The path of the remote server binary on the remote host, relative to the home directory.
+    fn remote_binary_path(&self) -> Option<Arc<RelPath>> {
+        None
+    }
 
     #[cfg(any(test, feature = "test-support"))]
     fn simulate_disconnect(&self, _: &AsyncApp) {}
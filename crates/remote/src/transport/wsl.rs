@@ -526,6 +526,10 @@ impl RemoteConnection for WslRemoteConnection {
     fn has_wsl_interop(&self) -> bool {
         self.has_wsl_interop
     }
+
+    fn remote_binary_path(&self) -> Option<Arc<RelPath>> {
+        self.remote_binary_path.clone()
+    }
 }
 
 /// `wslpath` is a executable available in WSL, it's a linux binary.
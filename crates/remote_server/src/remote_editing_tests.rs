@@ -961,6 +961,62 @@ async fn test_remote_reload(cx: &mut TestAppContext, server_cx: &mut TestAppCont
     });
 }
 
+#[gpui::test]
+async fn test_remote_large_file_mode(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        path!("/code"),
+        json!({
+            "project1": {
+                "small.txt": "small\r\n",
+                "large.txt": "large\r\n".repeat(16),
+            },
+        }),
+    )
+    .await;
+
+    let (project, _headless) = init_test(&fs, cx, server_cx).await;
+    server_cx
+        .update_global(|settings_store: &mut SettingsStore, cx| {
+            settings_store.set_server_settings(r#"{"large_file_threshold": 64}"#, cx)
+        })
+        .unwrap();
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = cx.update(|cx| worktree.read(cx).id());
+    cx.run_until_parked();
+
+    let small_buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("small.txt")), cx)
+        })
+        .await
+        .unwrap();
+    let large_buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("large.txt")), cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    cx.update(|cx| {
+        let buffer_store = project.read(cx).buffer_store().read(cx);
+        assert!(!buffer_store.is_large_buffer(small_buffer.read(cx).remote_id()));
+        assert!(buffer_store.is_large_buffer(large_buffer.read(cx).remote_id()));
+        assert!(!small_buffer.read(cx).read_only());
+
+        let large_buffer = large_buffer.read(cx);
+        assert!(large_buffer.read_only());
+        assert_eq!(large_buffer.text(), "large\n".repeat(16));
+        assert_eq!(large_buffer.line_ending(), LineEnding::Windows);
+    });
+}
+
 #[gpui::test]
 async fn test_remote_resolve_path_in_buffer(
     cx: &mut TestAppContext,
@@ -79,6 +79,16 @@ pub enum Commands {
         identifier: String,
     },
     Version,
+    /// Attaches the terminal of the standard streams to a persistent terminal session, starting
+    /// the session host if needed.
+    AttachTerminalSession {
+        session_id: String,
+    },
+    #[command(hide = true)]
+    TerminalSessionHost {
+        #[arg(long)]
+        socket: PathBuf,
+    },
 }
 
 pub fn run(command: Commands) -> anyhow::Result<()> {
@@ -122,6 +132,24 @@ pub fn run(command: Commands) -> anyhow::Result<()> {
             };
             Ok(())
         }
+        #[cfg(unix)]
+        Commands::AttachTerminalSession { session_id } => {
+            let binary = env::current_exe().context("locating the remote server binary")?;
+            terminal::sessions::attach(&session_id, |socket| {
+                let mut command = std::process::Command::new(&binary);
+                command
+                    .arg("terminal-session-host")
+                    .arg("--socket")
+                    .arg(socket);
+                command
+            })
+        }
+        #[cfg(unix)]
+        Commands::TerminalSessionHost { socket } => terminal::sessions::run_host(&socket),
+        #[cfg(not(unix))]
+        Commands::AttachTerminalSession { .. } | Commands::TerminalSessionHost { .. } => Err(
+            anyhow!("persistent terminal sessions are not supported on this platform"),
+        ),
     }
 }
 
@@ -196,6 +196,7 @@ impl VsCodeSettings {
             journal: None,
             language_models: None,
             line_indicator_format: None,
+            local_history: self.local_history_settings_content(),
             log: None,
             message_editor: None,
             node: self.node_binary_settings(),
@@ -209,6 +210,7 @@ impl VsCodeSettings {
             repl: None,
             server_url: None,
             session: None,
+            spell_check: None,
             status_bar: self.status_bar_settings_content(),
             tab_bar: self.tab_bar_settings_content(),
             tabs: self.item_settings_content(),
@@ -492,6 +494,17 @@ impl VsCodeSettings {
         })
     }
 
+    fn local_history_settings_content(&self) -> Option<LocalHistorySettingsContent> {
+        skip_default(LocalHistorySettingsContent {
+            enabled: self.read_bool("workbench.localHistory.enabled"),
+            max_entries_per_file: self.read_usize("workbench.localHistory.maxFileEntries"),
+            max_file_size: self
+                .read_u64("workbench.localHistory.maxFileSize")
+                .map(|kilobytes| kilobytes * 1024),
+            ..Default::default()
+        })
+    }
+
     fn project_settings_content(&self) -> ProjectSettingsContent {
         ProjectSettingsContent {
             all_languages: AllLanguageSettingsContent {
@@ -865,9 +878,12 @@ impl VsCodeSettings {
             max_scroll_history_lines: self.read_usize("terminal.integrated.scrollback"),
             minimum_contrast: None,
             option_as_meta: self.read_bool("terminal.integrated.macOptionIsMeta"),
+            persist_scrollback: self.read_bool("terminal.integrated.enablePersistentSessions"),
+            persistent_sessions: None,
             project: self.project_terminal_settings_content(),
             scrollbar: None,
             scroll_multiplier: None,
+            shell_integration: self.read_bool("terminal.integrated.shellIntegration.enabled"),
             toolbar: None,
         })
     }
@@ -1045,6 +1061,7 @@ impl VsCodeSettings {
                         .collect::<Vec<_>>()
                 })
                 .filter(|r| !r.is_empty()),
+            large_file_threshold: None,
         }
     }
 }
@@ -136,6 +136,20 @@ pub struct AgentSettingsContent {
     /// This setting only applies to the native Zed agent. External agent servers (Claude Code, Gemini CLI, etc.)
     /// have their own permission systems and are not affected by these settings.
     pub tool_permissions: Option<ToolPermissionsContent>,
+    /// Shell commands that run at points of a thread's lifecycle, such as before and after
+    /// tool calls. Each receives the event as JSON on stdin.
+    ///
+    /// This setting only applies to the native Zed agent.
+    pub hooks: Option<AgentHooksContent>,
+    /// Prices of language models in US dollars per million tokens, used to estimate what the
+    /// agent's requests cost. Keyed by language model provider ID, then by model ID.
+    ///
+    /// Default: {}
+    pub token_prices: Option<HashMap<Arc<str>, HashMap<Arc<str>, TokenPriceContent>>>,
+    /// Limits on the estimated cost of the agent's requests, in US dollars. The agent pauses
+    /// before a request that would exceed a soft limit, and stops before one that would exceed
+    /// a hard limit.
+    pub usage_limits: Option<UsageLimitsContent>,
 }
 
 impl AgentSettingsContent {
@@ -221,6 +235,29 @@ pub struct AgentProfileContent {
     pub context_servers: IndexMap<Arc<str>, ContextServerPresetContent>,
     /// The default language model selected when using this profile.
     pub default_model: Option<LanguageModelSelection>,
+    /// Runs the commands of the terminal tool in an OS-level sandbox.
+    ///
+    /// Only supported for local projects on Linux, with bubblewrap installed.
+    pub terminal_sandbox: Option<TerminalSandboxContent>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct TerminalSandboxContent {
+    /// Whether to run the commands of the terminal tool in a sandbox, in which they can only
+    /// write to the project's worktrees, a private temporary directory and `writable_paths`.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Whether sandboxed commands can access the network.
+    ///
+    /// Default: true
+    pub allow_network: Option<bool>,
+    /// Absolute paths that sandboxed commands can write to, in addition to the project's
+    /// worktrees, such as `~/.cargo`. Paths starting with `~` are relative to the home directory.
+    ///
+    /// Default: []
+    pub writable_paths: Option<Vec<String>>,
 }
 
 #[with_fallible_options]
@@ -570,6 +607,71 @@ pub struct ToolRegexRule {
     pub case_sensitive: Option<bool>,
 }
 
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHooksContent {
+    /// Hooks that run before a tool call. They can block the call, rewrite its input,
+    /// or add context to its result.
+    /// Default: []
+    pub pre_tool_use: Option<Vec<AgentHookContent>>,
+    /// Hooks that run after a tool call. They can add context to its result.
+    /// Default: []
+    pub post_tool_use: Option<Vec<AgentHookContent>>,
+    /// Hooks that run when a prompt is submitted. They can block the prompt, or add context to it.
+    /// Default: []
+    pub user_prompt_submit: Option<Vec<AgentHookContent>>,
+    /// Hooks that run when the agent ends its turn. Their output is ignored.
+    /// Default: []
+    pub stop: Option<Vec<AgentHookContent>>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHookContent {
+    /// The shell command to run.
+    #[serde(default)]
+    pub command: String,
+    /// For tool hooks, a regex that the whole tool name must match, such as `edit_file|terminal`.
+    /// Default: every tool
+    pub tools: Option<String>,
+    /// How many seconds the command may run before it's stopped.
+    /// Default: 60
+    pub timeout: Option<u64>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct TokenPriceContent {
+    /// The price of a million input tokens.
+    pub input: Option<f64>,
+    /// The price of a million output tokens.
+    pub output: Option<f64>,
+    /// The price of a million input tokens written to the prompt cache.
+    /// Default: the input price
+    pub cache_write: Option<f64>,
+    /// The price of a million input tokens read from the prompt cache.
+    /// Default: the input price
+    pub cache_read: Option<f64>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct UsageLimitsContent {
+    /// Limits on the estimated cost of each thread.
+    pub thread: Option<UsageLimitContent>,
+    /// Limits on the estimated cost of all threads, per day.
+    pub daily: Option<UsageLimitContent>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct UsageLimitContent {
+    /// The cost, in US dollars, at which the agent pauses until you send another message.
+    pub soft: Option<f64>,
+    /// The cost, in US dollars, past which the agent doesn't make any more requests.
+    pub hard: Option<f64>,
+}
+
 #[derive(
     Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, MergeFrom,
 )]
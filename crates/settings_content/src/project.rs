@@ -133,6 +133,13 @@ pub struct WorktreeSettingsContent {
     /// external dependencies that should not be modified directly.
     /// Default: []
     pub read_only_files: Option<Vec<String>>,
+
+    /// Files larger than this many bytes are opened in large file mode: read-only, without
+    /// syntax highlighting, language servers or git diffs, so that big logs and data dumps
+    /// stay responsive. Search and go to line keep working.
+    ///
+    /// Default: 104857600 (100 MiB)
+    pub large_file_threshold: Option<u64>,
 }
 
 #[with_fallible_options]
@@ -120,6 +120,12 @@ pub struct SettingsContent {
 
     pub journal: Option<JournalSettingsContent>,
 
+    /// Configuration for the local file history.
+    pub local_history: Option<LocalHistorySettingsContent>,
+
+    /// Configuration for spell checking comments, strings and prose.
+    pub spell_check: Option<SpellCheckSettingsContent>,
+
     /// A map of log scopes to the desired log level.
     /// Useful for filtering out noisy logs or enabling more verbose logging.
     ///
@@ -819,6 +825,49 @@ pub struct JournalSettingsContent {
     pub hour_format: Option<HourFormat>,
 }
 
+/// Settings for the local file history.
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct LocalHistorySettingsContent {
+    /// Whether to record snapshots of files on save and when they change on disk.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum number of snapshots kept for each file.
+    ///
+    /// Default: 50
+    pub max_entries_per_file: Option<usize>,
+    /// The number of days after which snapshots are deleted.
+    /// The most recent snapshot of a file is always kept.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u64>,
+    /// Files larger than this many bytes are not recorded.
+    ///
+    /// Default: 1048576
+    pub max_file_size: Option<u64>,
+}
+
+/// Settings for spell checking.
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct SpellCheckSettingsContent {
+    /// Whether to underline misspelled words in comments, strings and prose files.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The names of the Hunspell dictionaries to check against, such as "en_US".
+    /// A word is accepted if any of the dictionaries contains it.
+    ///
+    /// Default: ["en_US"]
+    pub dictionaries: Option<Vec<String>>,
+    /// Additional directories to search for `.aff` and `.dic` files, before the
+    /// system's Hunspell directories.
+    ///
+    /// Default: []
+    pub dictionary_directories: Option<Vec<String>>,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum HourFormat {
@@ -171,6 +171,23 @@ pub struct TerminalSettingsContent {
     /// Default: 45
     #[serde(serialize_with = "crate::serialize_optional_f32_with_two_decimal_places")]
     pub minimum_contrast: Option<f32>,
+    /// Whether to load Zed's shell integration into bash, zsh and fish, which marks prompts
+    /// and commands so that you can jump between them and select their output.
+    /// Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: true
+    pub shell_integration: Option<bool>,
+    /// Whether to save each terminal's scrollback with the workspace and show it again, above
+    /// a new shell prompt, when the workspace is restored.
+    ///
+    /// Default: false
+    pub persist_scrollback: Option<bool>,
+    /// Whether to run terminals and tasks in persistent sessions, which keep running when Zed
+    /// quits or the connection to a remote project drops, and are reattached to when the
+    /// workspace is restored.
+    ///
+    /// Default: false
+    pub persistent_sessions: Option<bool>,
 }
 
 /// Shell configuration to open the terminal with.
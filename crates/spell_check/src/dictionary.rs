@@ -0,0 +1,426 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context as _, Result};
+
+/// A spelling dictionary in the Hunspell `.aff`/`.dic` format.
+///
+/// Only the subset of Hunspell needed for checking and suggesting plain words is supported:
+/// word lists, prefix and suffix rules (including cross products), `TRY` and `FORBIDDENWORD`.
+/// Compounding and morphological analysis are ignored.
+pub struct Dictionary {
+    words: HashMap<String, Vec<Flag>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    forbidden: Option<Flag>,
+    try_chars: Vec<char>,
+}
+
+type Flag = u32;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum FlagFormat {
+    #[default]
+    Char,
+    Long,
+    Numeric,
+}
+
+impl FlagFormat {
+    fn parse(self, flags: &str) -> Vec<Flag> {
+        match self {
+            FlagFormat::Char => flags.chars().map(|c| c as Flag).collect(),
+            FlagFormat::Long => flags
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| pair.iter().fold(0, |flag, c| (flag << 16) | *c as Flag))
+                .collect(),
+            FlagFormat::Numeric => flags
+                .split(',')
+                .filter_map(|flag| flag.trim().parse().ok())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Vec<CharClass>,
+}
+
+#[derive(Debug)]
+enum CharClass {
+    Any,
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+
+    fn parse_condition(condition: &str) -> Vec<CharClass> {
+        let mut classes = Vec::new();
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => classes.push(CharClass::Any),
+                '[' => {
+                    let mut set = Vec::new();
+                    let mut negated = false;
+                    for c in chars.by_ref() {
+                        match c {
+                            ']' => break,
+                            '^' if set.is_empty() && !negated => negated = true,
+                            c => set.push(c),
+                        }
+                    }
+                    classes.push(CharClass::Set {
+                        chars: set,
+                        negated,
+                    });
+                }
+                c => classes.push(CharClass::Set {
+                    chars: vec![c],
+                    negated: false,
+                }),
+            }
+        }
+        classes
+    }
+}
+
+impl Affix {
+    /// Whether the condition matches the start (for prefixes) or end (for suffixes) of `stem`.
+    fn condition_matches(&self, stem: &str, is_suffix: bool) -> bool {
+        let chars = stem.chars().collect::<Vec<_>>();
+        if chars.len() < self.condition.len() {
+            return false;
+        }
+        let offset = if is_suffix {
+            chars.len() - self.condition.len()
+        } else {
+            0
+        };
+        self.condition
+            .iter()
+            .zip(&chars[offset..])
+            .all(|(class, c)| class.matches(*c))
+    }
+}
+
+impl Dictionary {
+    pub fn parse(aff: &str, dic: &str) -> Result<Self> {
+        let mut flag_format = FlagFormat::default();
+        let mut prefixes = Vec::new();
+        let mut suffixes = Vec::new();
+        let mut forbidden = None;
+        let mut try_chars = Vec::new();
+        let mut forbidden_flag = None;
+        let mut cross_products = HashMap::new();
+
+        for line in aff.lines() {
+            let mut fields = line.split_whitespace();
+            let Some(keyword) = fields.next() else {
+                continue;
+            };
+            match keyword {
+                "FLAG" => {
+                    flag_format = match fields.next() {
+                        Some("long") => FlagFormat::Long,
+                        Some("num") => FlagFormat::Numeric,
+                        _ => FlagFormat::Char,
+                    };
+                }
+                "TRY" => try_chars = fields.next().unwrap_or_default().chars().collect(),
+                "FORBIDDENWORD" => forbidden_flag = fields.next().map(str::to_string),
+                "PFX" | "SFX" => {
+                    let fields = fields.collect::<Vec<_>>();
+                    let Some(flag) = fields
+                        .first()
+                        .and_then(|flag| flag_format.parse(flag).first().copied())
+                    else {
+                        continue;
+                    };
+                    // The header of an affix class is `SFX <flag> <cross product> <count>`,
+                    // followed by `SFX <flag> <strip> <add> <condition>` rules.
+                    if fields.len() == 3 && !cross_products.contains_key(&(keyword, flag)) {
+                        cross_products.insert((keyword, flag), fields[1] == "Y");
+                        continue;
+                    }
+                    let [_, strip, add, rest @ ..] = fields.as_slice() else {
+                        continue;
+                    };
+                    let add = add.split('/').next().unwrap_or_default();
+                    let affix = Affix {
+                        flag,
+                        cross_product: cross_products
+                            .get(&(keyword, flag))
+                            .copied()
+                            .unwrap_or(false),
+                        strip: if *strip == "0" {
+                            String::new()
+                        } else {
+                            strip.to_string()
+                        },
+                        add: if add == "0" {
+                            String::new()
+                        } else {
+                            add.to_string()
+                        },
+                        condition: CharClass::parse_condition(rest.first().copied().unwrap_or(".")),
+                    };
+                    if keyword == "PFX" {
+                        prefixes.push(affix);
+                    } else {
+                        suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(flag) = forbidden_flag {
+            forbidden = flag_format.parse(&flag).first().copied();
+        }
+
+        let mut lines = dic.lines();
+        lines
+            .next()
+            .context("dictionary is empty")?
+            .trim()
+            .parse::<usize>()
+            .context("dictionary does not start with a word count")?;
+        let mut words = HashMap::<String, Vec<Flag>>::default();
+        for line in lines {
+            let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+            if entry.is_empty() {
+                continue;
+            }
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => (word, flag_format.parse(flags)),
+                None => (entry, Vec::new()),
+            };
+            words.entry(word.to_string()).or_default().extend(flags);
+        }
+
+        Ok(Self {
+            words,
+            prefixes,
+            suffixes,
+            forbidden,
+            try_chars,
+        })
+    }
+
+    pub fn check(&self, word: &str) -> bool {
+        if self.check_exact(word) {
+            return true;
+        }
+        let lowercase = word.to_lowercase();
+        if lowercase != word && self.check_exact(&lowercase) {
+            return true;
+        }
+        // Words written in all caps can be the uppercase form of a capitalized word.
+        let mut chars = lowercase.chars();
+        if word.chars().all(|c| !c.is_lowercase())
+            && let Some(first) = chars.next()
+        {
+            let capitalized = first.to_uppercase().chain(chars).collect::<String>();
+            return self.check_exact(&capitalized);
+        }
+        false
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            return !self.is_forbidden(flags);
+        }
+        if self.check_suffixed(word, None) {
+            return true;
+        }
+        for prefix in &self.prefixes {
+            let Some(rest) = word.strip_prefix(prefix.add.as_str()) else {
+                continue;
+            };
+            if rest.is_empty() {
+                continue;
+            }
+            let stem = format!("{}{rest}", prefix.strip);
+            if !prefix.condition_matches(&stem, false) {
+                continue;
+            }
+            if self.has_flag(&stem, prefix.flag) {
+                return true;
+            }
+            if prefix.cross_product && self.check_suffixed(&stem, Some(prefix.flag)) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether `word` is a stem with one of the suffix rules applied. When `prefix_flag` is
+    /// given, the stem must also accept that prefix.
+    fn check_suffixed(&self, word: &str, prefix_flag: Option<Flag>) -> bool {
+        self.suffixes.iter().any(|suffix| {
+            if prefix_flag.is_some() && !suffix.cross_product {
+                return false;
+            }
+            let Some(rest) = word.strip_suffix(suffix.add.as_str()) else {
+                return false;
+            };
+            if rest.is_empty() {
+                return false;
+            }
+            let stem = format!("{rest}{}", suffix.strip);
+            suffix.condition_matches(&stem, true)
+                && self.has_flag(&stem, suffix.flag)
+                && prefix_flag.is_none_or(|flag| self.has_flag(&stem, flag))
+        })
+    }
+
+    fn has_flag(&self, stem: &str, flag: Flag) -> bool {
+        self.words
+            .get(stem)
+            .is_some_and(|flags| flags.contains(&flag) && !self.is_forbidden(flags))
+    }
+
+    fn is_forbidden(&self, flags: &[Flag]) -> bool {
+        self.forbidden.is_some_and(|flag| flags.contains(&flag))
+    }
+
+    /// Returns correctly spelled words within one edit of `word`, best candidates first.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let chars = word.chars().collect::<Vec<_>>();
+        // Only suggest capitals where the word already has them, so that `playd` doesn't become
+        // `playEd`.
+        let has_uppercase = chars.iter().any(|c| c.is_uppercase());
+        let try_chars = if self.try_chars.is_empty() {
+            ('a'..='z').collect::<Vec<_>>()
+        } else {
+            self.try_chars
+                .iter()
+                .copied()
+                .filter(|c| has_uppercase || !c.is_uppercase())
+                .collect()
+        };
+
+        let mut candidates = Vec::new();
+        // Swapped adjacent characters and wrong characters are the most common typos.
+        for ix in 1..chars.len() {
+            let mut swapped = chars.clone();
+            swapped.swap(ix - 1, ix);
+            candidates.push(swapped.into_iter().collect::<String>());
+        }
+        for ix in 0..chars.len() {
+            for c in &try_chars {
+                if *c != chars[ix] {
+                    let mut replaced = chars.clone();
+                    replaced[ix] = *c;
+                    candidates.push(replaced.into_iter().collect());
+                }
+            }
+        }
+        for ix in 0..chars.len() {
+            let mut deleted = chars.clone();
+            deleted.remove(ix);
+            candidates.push(deleted.into_iter().collect());
+        }
+        for ix in 0..=chars.len() {
+            for c in &try_chars {
+                let mut inserted = chars.clone();
+                inserted.insert(ix, *c);
+                candidates.push(inserted.into_iter().collect());
+            }
+        }
+        for ix in 1..chars.len() {
+            let (first, second) = chars.split_at(ix);
+            let first = first.iter().collect::<String>();
+            let second = second.iter().collect::<String>();
+            if first.chars().count() > 1
+                && second.chars().count() > 1
+                && self.check(&first)
+                && self.check(&second)
+            {
+                candidates.push(format!("{first} {second}"));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        candidates
+            .into_iter()
+            .filter(|candidate| {
+                candidate != word
+                    && (candidate.contains(' ') || self.check(candidate))
+                    && seen.insert(candidate.clone())
+            })
+            .take(limit)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "
+SET UTF-8
+TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+FORBIDDENWORD !
+
+PFX U Y 1
+PFX U   0     un         .
+
+SFX S Y 2
+SFX S   y     ies        [^aeiou]y
+SFX S   0     s          [^y]
+
+SFX D N 1
+SFX D   0     ed         .
+";
+
+    const DIC: &str = "6
+word/S
+try/SU
+do/U
+play/D
+Zed
+wrongly/!
+";
+
+    #[test]
+    fn test_check() {
+        let dictionary = Dictionary::parse(AFF, DIC).unwrap();
+        assert!(dictionary.check("word"));
+        assert!(dictionary.check("words"));
+        assert!(dictionary.check("Words"));
+        assert!(dictionary.check("WORDS"));
+        assert!(dictionary.check("tries"));
+        assert!(!dictionary.check("trys"));
+        assert!(dictionary.check("untries"));
+        assert!(dictionary.check("undo"));
+        assert!(dictionary.check("played"));
+        assert!(!dictionary.check("unplayed"));
+        assert!(dictionary.check("Zed"));
+        assert!(dictionary.check("ZED"));
+        assert!(!dictionary.check("zed"));
+        assert!(!dictionary.check("wrongly"));
+        assert!(!dictionary.check("wrod"));
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = Dictionary::parse(AFF, DIC).unwrap();
+        assert_eq!(dictionary.suggest("wrod", 3), vec!["word"]);
+        assert_eq!(dictionary.suggest("wordds", 3), vec!["words"]);
+        assert_eq!(dictionary.suggest("playd", 3), vec!["play", "played"]);
+        assert_eq!(dictionary.suggest("wordplay", 3), vec!["word play"]);
+    }
+}
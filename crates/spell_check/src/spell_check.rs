@@ -0,0 +1,917 @@
+//! Spell checking for comments, strings and prose, using offline Hunspell dictionaries.
+
+mod dictionary;
+mod words;
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use editor::{
+    Addon, Bias, CodeActionProvider, DisplayPoint, Editor, EditorEvent, ExcerptId, HighlightKey,
+    display_map::DisplayRow,
+};
+use fs::Fs;
+use gpui::{
+    App, AppContext as _, Context, Entity, Global, HighlightStyle, Subscription, Task,
+    UnderlineStyle, Window, px,
+};
+use language::{Buffer, BufferSnapshot, Point, ToOffset as _, ToPoint as _};
+use project::{CodeAction, LspAction, ProjectTransaction};
+use serde::{Deserialize, Serialize};
+use settings::{RegisterSetting, Settings, SettingsStore};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
+use theme::{ActiveTheme as _, SyntaxTheme};
+use util::ResultExt as _;
+
+pub use dictionary::Dictionary;
+
+/// How long to wait after an edit before checking the buffer again.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// The file, inside the project's `.zed` folder or the config directory, that holds words
+/// added to the project or user dictionary.
+const WORD_LIST_FILE_NAME: &str = "dictionary.txt";
+
+const MAX_SUGGESTIONS: usize = 5;
+
+/// How many rows around the visible ones are checked, so that scrolling a little doesn't reveal
+/// unchecked text.
+const MARGIN_ROWS: u32 = 50;
+
+/// How many rows are assumed to be visible before the editor has been laid out.
+const DEFAULT_VISIBLE_ROWS: u32 = 100;
+
+/// Languages whose plain text is prose and should be checked in full, rather than only in
+/// comments and strings.
+const PROSE_LANGUAGES: &[&str] = &["Git Commit", "Markdown", "Plain Text"];
+
+/// Settings for spell checking.
+#[derive(Clone, Debug, RegisterSetting)]
+pub struct SpellCheckSettings {
+    /// Whether to underline misspelled words.
+    pub enabled: bool,
+    /// The names of the dictionaries to check against.
+    pub dictionaries: Vec<String>,
+    /// Additional directories to search for dictionaries.
+    pub dictionary_directories: Vec<String>,
+}
+
+impl Settings for SpellCheckSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let spell_check = content.spell_check.clone().unwrap();
+        Self {
+            enabled: spell_check.enabled.unwrap(),
+            dictionaries: spell_check.dictionaries.unwrap(),
+            dictionary_directories: spell_check.dictionary_directories.unwrap(),
+        }
+    }
+}
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut App) {
+    let store = cx.new(|cx| SpellCheckStore::new(fs, cx));
+    cx.set_global(GlobalSpellCheckStore(store));
+
+    cx.observe_new(register_editor).detach();
+}
+
+struct GlobalSpellCheckStore(Entity<SpellCheckStore>);
+
+impl Global for GlobalSpellCheckStore {}
+
+/// Which word list a word is added to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WordListScope {
+    /// The `.zed/dictionary.txt` file in the worktree with the given root.
+    Project(Arc<Path>),
+    /// The `dictionary.txt` file in the config directory.
+    User,
+}
+
+impl WordListScope {
+    fn path(&self) -> PathBuf {
+        match self {
+            WordListScope::Project(root) => root
+                .join(paths::local_settings_folder_name())
+                .join(WORD_LIST_FILE_NAME),
+            WordListScope::User => paths::config_dir().join(WORD_LIST_FILE_NAME),
+        }
+    }
+}
+
+/// Holds the loaded dictionaries and the user and project word lists.
+pub struct SpellCheckStore {
+    fs: Arc<dyn Fs>,
+    dictionaries: Arc<Vec<Dictionary>>,
+    /// The dictionary settings the current dictionaries were loaded for.
+    loaded_settings: Option<(Vec<String>, Vec<String>)>,
+    user_words: Arc<HashSet<String>>,
+    project_words: HashMap<Arc<Path>, Arc<HashSet<String>>>,
+    _load_dictionaries_task: Task<()>,
+    _settings_subscription: Subscription,
+}
+
+impl SpellCheckStore {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalSpellCheckStore>()
+            .map(|global| global.0.clone())
+    }
+
+    fn new(fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Self {
+        let settings_subscription =
+            cx.observe_global::<SettingsStore>(|this, cx| this.load_dictionaries(cx));
+        let mut this = Self {
+            fs,
+            dictionaries: Arc::default(),
+            loaded_settings: None,
+            user_words: Arc::default(),
+            project_words: HashMap::default(),
+            _load_dictionaries_task: Task::ready(()),
+            _settings_subscription: settings_subscription,
+        };
+        this.load_dictionaries(cx);
+        this.load_word_list(WordListScope::User, cx);
+        this
+    }
+
+    /// Loads the configured dictionaries, unless spell checking is disabled or they are
+    /// already loaded.
+    fn load_dictionaries(&mut self, cx: &mut Context<Self>) {
+        let settings = SpellCheckSettings::get_global(cx);
+        let dictionary_settings = (
+            settings.dictionaries.clone(),
+            settings.dictionary_directories.clone(),
+        );
+        if !settings.enabled || self.loaded_settings.as_ref() == Some(&dictionary_settings) {
+            return;
+        }
+        self.loaded_settings = Some(dictionary_settings);
+
+        let fs = self.fs.clone();
+        let names = settings.dictionaries.clone();
+        let directories = dictionary_directories(&settings.dictionary_directories);
+        self._load_dictionaries_task = cx.spawn(async move |this, cx| {
+            let dictionaries = cx
+                .background_spawn(async move {
+                    let mut dictionaries = Vec::new();
+                    for name in names {
+                        match load_dictionary(fs.as_ref(), &directories, &name).await {
+                            Ok(dictionary) => dictionaries.push(dictionary),
+                            Err(error) => {
+                                log::warn!("failed to load dictionary {name:?}: {error:#}")
+                            }
+                        }
+                    }
+                    dictionaries
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.dictionaries = Arc::new(dictionaries);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn load_word_list(&mut self, scope: WordListScope, cx: &mut Context<Self>) {
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let words = read_word_list(fs.as_ref(), &scope.path()).await;
+            this.update(cx, |this, cx| {
+                this.set_word_list(scope, words);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn set_word_list(&mut self, scope: WordListScope, words: HashSet<String>) {
+        match scope {
+            WordListScope::Project(root) => {
+                self.project_words.insert(root, Arc::new(words));
+            }
+            WordListScope::User => self.user_words = Arc::new(words),
+        }
+    }
+
+    /// Returns a checker for files in the worktree with the given root, or `None` if no
+    /// dictionaries are loaded.
+    pub fn checker(
+        &mut self,
+        worktree_root: Option<Arc<Path>>,
+        cx: &mut Context<Self>,
+    ) -> Option<WordChecker> {
+        if self.dictionaries.is_empty() {
+            return None;
+        }
+        let project_words = worktree_root.map(|root| {
+            if !self.project_words.contains_key(&root) {
+                self.project_words.insert(root.clone(), Arc::default());
+                self.load_word_list(WordListScope::Project(root.clone()), cx);
+            }
+            self.project_words[&root].clone()
+        });
+        Some(WordChecker {
+            dictionaries: self.dictionaries.clone(),
+            user_words: self.user_words.clone(),
+            project_words,
+        })
+    }
+
+    /// Adds a word to the project or user word list and writes it to disk.
+    pub fn add_word(
+        &mut self,
+        word: String,
+        scope: WordListScope,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            // Re-read the file so that words added by other windows or by hand are kept.
+            let path = scope.path();
+            let mut words = read_word_list(fs.as_ref(), &path).await;
+            words.insert(word);
+
+            let mut sorted_words = words.iter().map(String::as_str).collect::<Vec<_>>();
+            sorted_words.sort_unstable();
+            let mut contents = sorted_words.join("\n");
+            contents.push('\n');
+            if let Some(parent) = path.parent() {
+                fs.create_dir(parent).await?;
+            }
+            fs.atomic_write(path.clone(), contents)
+                .await
+                .with_context(|| format!("writing {path:?}"))?;
+
+            this.update(cx, |this, cx| {
+                this.set_word_list(scope, words);
+                cx.notify();
+            })
+        })
+    }
+}
+
+/// A snapshot of the dictionaries and word lists that apply to a file.
+#[derive(Clone)]
+pub struct WordChecker {
+    dictionaries: Arc<Vec<Dictionary>>,
+    user_words: Arc<HashSet<String>>,
+    project_words: Option<Arc<HashSet<String>>>,
+}
+
+impl WordChecker {
+    pub fn check(&self, word: &str) -> bool {
+        let lowercase = word.to_lowercase();
+        let in_word_list =
+            |words: &HashSet<String>| words.contains(word) || words.contains(&lowercase);
+        in_word_list(&self.user_words)
+            || self.project_words.as_deref().is_some_and(in_word_list)
+            || self
+                .dictionaries
+                .iter()
+                .any(|dictionary| dictionary.check(word))
+    }
+
+    /// Returns spelling suggestions for `word`, capitalized like `word`.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let is_capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        let mut suggestions = Vec::new();
+        for dictionary in self.dictionaries.iter() {
+            for suggestion in dictionary.suggest(word, limit) {
+                let suggestion = if is_capitalized {
+                    capitalize(&suggestion)
+                } else {
+                    suggestion
+                };
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// The directories searched for `<name>.aff` and `<name>.dic`, in order of precedence.
+fn dictionary_directories(configured: &[String]) -> Vec<PathBuf> {
+    let mut directories = configured
+        .iter()
+        .map(|directory| PathBuf::from(shellexpand::tilde(directory).as_ref()))
+        .collect::<Vec<_>>();
+    directories.push(paths::config_dir().join("dictionaries"));
+    if cfg!(target_os = "macos") {
+        directories.push(paths::home_dir().join("Library/Spelling"));
+        directories.push(PathBuf::from("/Library/Spelling"));
+        directories.push(PathBuf::from("/opt/homebrew/share/hunspell"));
+    }
+    if cfg!(any(
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "freebsd"
+    )) {
+        directories.push(PathBuf::from("/usr/local/share/hunspell"));
+        directories.push(PathBuf::from("/usr/share/hunspell"));
+        directories.push(PathBuf::from("/usr/share/myspell"));
+        directories.push(PathBuf::from("/usr/share/myspell/dicts"));
+    }
+    directories
+}
+
+async fn load_dictionary(fs: &dyn Fs, directories: &[PathBuf], name: &str) -> Result<Dictionary> {
+    for directory in directories {
+        let aff_path = directory.join(format!("{name}.aff"));
+        let dic_path = directory.join(format!("{name}.dic"));
+        if !fs.is_file(&aff_path).await || !fs.is_file(&dic_path).await {
+            continue;
+        }
+        let aff = fs.load_bytes(&aff_path).await?;
+        let dic = fs.load_bytes(&dic_path).await?;
+        // Older dictionaries are often encoded as Latin-1, whose bytes map directly to chars.
+        let is_latin1 = String::from_utf8_lossy(&aff)
+            .lines()
+            .any(|line| line.trim() == "SET ISO8859-1");
+        let decode = |bytes: Vec<u8>| {
+            if is_latin1 {
+                bytes.into_iter().map(char::from).collect::<String>()
+            } else {
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+        };
+        return Dictionary::parse(&decode(aff), &decode(dic))
+            .with_context(|| format!("parsing {dic_path:?}"));
+    }
+    anyhow::bail!("{name}.aff and {name}.dic not found in {directories:?}")
+}
+
+async fn read_word_list(fs: &dyn Fs, path: &Path) -> HashSet<String> {
+    let Ok(contents) = fs.load(path).await else {
+        return HashSet::default();
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The root of the local worktree containing a file, which locates its project word list.
+fn worktree_root(buffer: &BufferSnapshot, cx: &App) -> Option<Arc<Path>> {
+    let file = project::File::from_dyn(buffer.file())?;
+    let worktree = file.worktree.read(cx);
+    worktree.is_local().then(|| worktree.abs_path())
+}
+
+/// Returns the ranges of misspelled words within `range` of the buffer.
+///
+/// Only comments and strings are checked, except in prose languages like Markdown, where all
+/// text other than code is checked. Buffers without a language, like large files, have neither.
+pub fn misspelled_words(
+    buffer: &BufferSnapshot,
+    range: Range<usize>,
+    checker: &WordChecker,
+    syntax_theme: &SyntaxTheme,
+) -> Vec<Range<usize>> {
+    let is_prose = buffer
+        .language()
+        .is_some_and(|language| PROSE_LANGUAGES.contains(&language.name().as_ref()));
+    let mut misspelled = Vec::new();
+    let mut results = HashMap::<String, bool>::default();
+    let mut check_text = |start: usize, text: &str| {
+        for word in words::words(text) {
+            let is_correct = *results
+                .entry(text[word.clone()].to_string())
+                .or_insert_with_key(|word| checker.check(word));
+            if !is_correct {
+                misspelled.push(start + word.start..start + word.end);
+            }
+        }
+    };
+
+    // Adjacent chunks that are checked are joined, so that words aren't split where the
+    // highlighting changes, e.g. at the start of a doc comment.
+    let mut pending: Option<(usize, String)> = None;
+    let mut offset = range.start;
+    for chunk in buffer.chunks(range, true) {
+        let capture = chunk
+            .syntax_highlight_id
+            .and_then(|id| id.name(syntax_theme));
+        if is_checked_capture(capture, is_prose) {
+            pending
+                .get_or_insert_with(|| (offset, String::new()))
+                .1
+                .push_str(chunk.text);
+        } else if let Some((start, text)) = pending.take() {
+            check_text(start, &text);
+        }
+        offset += chunk.text.len();
+    }
+    if let Some((start, text)) = pending.take() {
+        check_text(start, &text);
+    }
+    misspelled
+}
+
+fn is_checked_capture(capture: Option<&str>, is_prose: bool) -> bool {
+    let Some(capture) = capture else {
+        return is_prose;
+    };
+    let matches = |prefix: &str| {
+        capture == prefix
+            || capture
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('.'))
+    };
+    if matches("comment") {
+        return true;
+    }
+    if matches("string") {
+        return !matches("string.escape") && !matches("string.regex") && !matches("string.special");
+    }
+    is_prose
+        && (matches("text") || matches("title") || matches("emphasis"))
+        && !matches("text.literal")
+}
+
+/// Keeps the spell check highlights of an editor up to date.
+struct SpellCheckAddon {
+    _refresh_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl Addon for SpellCheckAddon {
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn to_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
+    }
+}
+
+fn register_editor(editor: &mut Editor, window: Option<&mut Window>, cx: &mut Context<Editor>) {
+    if !editor.mode().is_full() {
+        return;
+    }
+    let Some(window) = window else {
+        return;
+    };
+    let Some(store) = SpellCheckStore::global(cx) else {
+        return;
+    };
+
+    editor.add_code_action_provider(
+        Rc::new(SpellCheckCodeActionProvider {
+            store: store.clone(),
+        }),
+        window,
+        cx,
+    );
+    let subscriptions = vec![
+        cx.subscribe_self(|editor, event: &EditorEvent, cx| match event {
+            EditorEvent::BufferEdited
+            | EditorEvent::ExcerptsAdded { .. }
+            | EditorEvent::Reparsed(_)
+            | EditorEvent::ScrollPositionChanged { .. } => {
+                schedule_refresh(editor, REFRESH_DEBOUNCE, cx)
+            }
+            _ => {}
+        }),
+        cx.observe(&store, |editor, _, cx| {
+            schedule_refresh(editor, Duration::ZERO, cx)
+        }),
+        cx.observe_global::<SettingsStore>(|editor, cx| {
+            schedule_refresh(editor, Duration::ZERO, cx)
+        }),
+    ];
+    editor.register_addon(SpellCheckAddon {
+        _refresh_task: Task::ready(()),
+        _subscriptions: subscriptions,
+    });
+    schedule_refresh(editor, Duration::ZERO, cx);
+}
+
+/// An excerpt to check, with the checker for the file it comes from.
+struct ExcerptToCheck {
+    excerpt_id: ExcerptId,
+    buffer: BufferSnapshot,
+    range: Range<usize>,
+    checker: WordChecker,
+}
+
+fn schedule_refresh(editor: &mut Editor, delay: Duration, cx: &mut Context<Editor>) {
+    let Some(addon) = editor.addon_mut::<SpellCheckAddon>() else {
+        return;
+    };
+    addon._refresh_task = cx.spawn(async move |editor, cx| {
+        if !delay.is_zero() {
+            cx.background_executor().timer(delay).await;
+        }
+        let Some(excerpts) = editor.update(cx, excerpts_to_check).ok().flatten() else {
+            return;
+        };
+        let syntax_theme = cx.update(|cx| cx.theme().syntax().clone());
+        let misspelled = cx
+            .background_spawn(async move {
+                excerpts
+                    .into_iter()
+                    .map(|excerpt| {
+                        let ranges = misspelled_words(
+                            &excerpt.buffer,
+                            excerpt.range,
+                            &excerpt.checker,
+                            &syntax_theme,
+                        )
+                        .into_iter()
+                        .map(|range| {
+                            excerpt.buffer.anchor_after(range.start)
+                                ..excerpt.buffer.anchor_before(range.end)
+                        })
+                        .collect::<Vec<_>>();
+                        (excerpt.excerpt_id, ranges)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+        editor
+            .update(cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let ranges = misspelled
+                    .into_iter()
+                    .flat_map(|(excerpt_id, ranges)| {
+                        let snapshot = &snapshot;
+                        ranges.into_iter().filter_map(move |range| {
+                            snapshot.anchor_range_in_excerpt(excerpt_id, range)
+                        })
+                    })
+                    .collect();
+                editor.highlight_text(
+                    HighlightKey::SpellCheck,
+                    ranges,
+                    HighlightStyle {
+                        underline: Some(UnderlineStyle {
+                            thickness: px(1.),
+                            color: Some(cx.theme().status().info),
+                            wavy: true,
+                        }),
+                        ..HighlightStyle::default()
+                    },
+                    cx,
+                );
+            })
+            .log_err();
+    });
+}
+
+/// Collects the parts of the excerpts around the visible rows to check, clearing the highlights
+/// instead if spell checking is disabled. Buffers opened in large file mode are skipped.
+fn excerpts_to_check(editor: &mut Editor, cx: &mut Context<Editor>) -> Option<Vec<ExcerptToCheck>> {
+    let store = SpellCheckStore::global(cx)?;
+    if !SpellCheckSettings::get_global(cx).enabled {
+        editor.clear_highlights(HighlightKey::SpellCheck, cx);
+        return None;
+    }
+
+    let rows = rows_to_check(editor, cx);
+    let buffer_store = editor
+        .project()
+        .map(|project| project.read(cx).buffer_store().clone());
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let excerpts = snapshot
+        .range_to_buffer_ranges(rows)
+        .into_iter()
+        .filter(|(buffer, range, _)| {
+            !range.is_empty()
+                && !buffer_store
+                    .as_ref()
+                    .is_some_and(|store| store.read(cx).is_large_buffer(buffer.remote_id()))
+        })
+        .map(|(buffer, range, excerpt_id)| {
+            (
+                excerpt_id,
+                buffer.clone(),
+                range.start.0..range.end.0,
+                worktree_root(buffer, cx),
+            )
+        })
+        .collect::<Vec<_>>();
+    store.update(cx, |store, cx| {
+        excerpts
+            .into_iter()
+            .map(|(excerpt_id, buffer, range, worktree_root)| {
+                Some(ExcerptToCheck {
+                    excerpt_id,
+                    buffer,
+                    range,
+                    checker: store.checker(worktree_root, cx)?,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Returns the rows of the multi-buffer that are visible, plus a margin around them.
+fn rows_to_check(editor: &mut Editor, cx: &mut Context<Editor>) -> Range<Point> {
+    let display_snapshot = editor.display_snapshot(cx);
+    let top_row = editor.scroll_position(cx).y.max(0.) as u32;
+    let visible_rows = editor
+        .visible_line_count()
+        .map_or(DEFAULT_VISIBLE_ROWS, |line_count| line_count.ceil() as u32);
+    let max_row = display_snapshot.max_point().row().0;
+    let start_row = top_row.saturating_sub(MARGIN_ROWS).min(max_row);
+    let end_row = (top_row + visible_rows + MARGIN_ROWS).min(max_row);
+
+    let start = display_snapshot
+        .display_point_to_point(DisplayPoint::new(DisplayRow(start_row), 0), Bias::Left);
+    let end = display_snapshot
+        .display_point_to_point(DisplayPoint::new(DisplayRow(end_row), 0), Bias::Left);
+    let max_point = display_snapshot.buffer_snapshot().max_point();
+    Point::new(start.row, 0)..Point::new(end.row + 1, 0).min(max_point)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SpellCheckAction {
+    Replace { replacement: String },
+    AddToProjectDictionary { word: String },
+    AddToUserDictionary { word: String },
+}
+
+struct SpellCheckCodeActionProvider {
+    store: Entity<SpellCheckStore>,
+}
+
+const SPELL_CHECK_CODE_ACTION_PROVIDER_ID: &str = "spell_check";
+
+impl CodeActionProvider for SpellCheckCodeActionProvider {
+    fn id(&self) -> Arc<str> {
+        SPELL_CHECK_CODE_ACTION_PROVIDER_ID.into()
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Entity<Buffer>,
+        range: Range<text::Anchor>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        if !SpellCheckSettings::get_global(cx).enabled {
+            return Task::ready(Ok(Vec::new()));
+        }
+        let snapshot = buffer.read(cx).snapshot();
+        let worktree_root = worktree_root(&snapshot, cx);
+        let Some(checker) = self
+            .store
+            .update(cx, |store, cx| store.checker(worktree_root.clone(), cx))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let syntax_theme = cx.theme().syntax().clone();
+
+        // Check whole lines, as the range is usually an empty selection inside a word.
+        let range = range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot);
+        let start = range.start.to_point(&snapshot);
+        let end = range.end.to_point(&snapshot);
+        let line_range = Point::new(start.row, 0).to_offset(&snapshot)
+            ..Point::new(end.row, snapshot.line_len(end.row)).to_offset(&snapshot);
+
+        cx.background_spawn(async move {
+            let mut actions = Vec::new();
+            for word_range in misspelled_words(&snapshot, line_range, &checker, &syntax_theme) {
+                if word_range.end < range.start || word_range.start > range.end {
+                    continue;
+                }
+                let word = snapshot
+                    .text_for_range(word_range.clone())
+                    .collect::<String>();
+                let anchor_range =
+                    snapshot.anchor_before(word_range.start)..snapshot.anchor_after(word_range.end);
+                let code_action = |title: String, action: SpellCheckAction| -> Result<CodeAction> {
+                    Ok(CodeAction {
+                        server_id: language::LanguageServerId(0),
+                        range: anchor_range.clone(),
+                        lsp_action: LspAction::Action(Box::new(lsp::CodeAction {
+                            title,
+                            kind: Some(lsp::CodeActionKind::QUICKFIX),
+                            data: Some(serde_json::to_value(action)?),
+                            ..Default::default()
+                        })),
+                        resolved: true,
+                    })
+                };
+
+                for replacement in checker.suggest(&word, MAX_SUGGESTIONS) {
+                    actions.push(code_action(
+                        format!("Change to \"{replacement}\""),
+                        SpellCheckAction::Replace { replacement },
+                    )?);
+                }
+                if worktree_root.is_some() {
+                    actions.push(code_action(
+                        format!("Add \"{word}\" to project dictionary"),
+                        SpellCheckAction::AddToProjectDictionary { word: word.clone() },
+                    )?);
+                }
+                actions.push(code_action(
+                    format!("Add \"{word}\" to user dictionary"),
+                    SpellCheckAction::AddToUserDictionary { word },
+                )?);
+            }
+            Ok(actions)
+        })
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Entity<Buffer>,
+        action: CodeAction,
+        _excerpt_id: ExcerptId,
+        push_to_history: bool,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<ProjectTransaction>> {
+        let spell_check_action = match &action.lsp_action {
+            LspAction::Action(lsp_action) => lsp_action
+                .data
+                .clone()
+                .context("spell check action has no data")
+                .and_then(|data| Ok(serde_json::from_value::<SpellCheckAction>(data)?)),
+            _ => Err(anyhow::anyhow!("not a spell check action")),
+        };
+        let spell_check_action = match spell_check_action {
+            Ok(spell_check_action) => spell_check_action,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let (word, scope) = match spell_check_action {
+            SpellCheckAction::Replace { replacement } => {
+                let transaction = buffer.update(cx, |buffer, cx| {
+                    buffer.finalize_last_transaction();
+                    buffer.start_transaction();
+                    buffer.edit([(action.range, replacement)], None, cx);
+                    buffer.end_transaction(cx)?;
+                    let transaction = buffer.finalize_last_transaction()?.clone();
+                    if !push_to_history {
+                        buffer.forget_transaction(transaction.id);
+                    }
+                    Some(transaction)
+                });
+                let mut project_transaction = ProjectTransaction::default();
+                if let Some(transaction) = transaction {
+                    project_transaction.0.insert(buffer, transaction);
+                }
+                return Task::ready(Ok(project_transaction));
+            }
+            SpellCheckAction::AddToProjectDictionary { word } => {
+                let Some(root) = worktree_root(&buffer.read(cx).snapshot(), cx) else {
+                    return Task::ready(Err(anyhow::anyhow!("file is not in a local project")));
+                };
+                (word, WordListScope::Project(root))
+            }
+            SpellCheckAction::AddToUserDictionary { word } => (word, WordListScope::User),
+        };
+        let add_word = self
+            .store
+            .update(cx, |store, cx| store.add_word(word, scope, cx));
+        cx.background_spawn(async move {
+            add_word.await?;
+            Ok(ProjectTransaction::default())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::ToPoint as _;
+    use fs::FakeFs;
+    use gpui::{TestAppContext, point};
+    use serde_json::json;
+    use util::path;
+
+    #[test]
+    fn test_checked_captures() {
+        assert!(is_checked_capture(Some("comment"), false));
+        assert!(is_checked_capture(Some("comment.doc"), false));
+        assert!(is_checked_capture(Some("string"), false));
+        assert!(!is_checked_capture(Some("string.escape"), false));
+        assert!(!is_checked_capture(Some("string.special.symbol"), false));
+        assert!(!is_checked_capture(Some("stringly"), false));
+        assert!(!is_checked_capture(Some("variable"), false));
+        assert!(!is_checked_capture(None, false));
+
+        assert!(is_checked_capture(None, true));
+        assert!(is_checked_capture(Some("title"), true));
+        assert!(is_checked_capture(Some("emphasis.strong"), true));
+        assert!(!is_checked_capture(Some("text.literal"), true));
+        assert!(!is_checked_capture(Some("link_uri"), true));
+    }
+
+    #[gpui::test]
+    async fn test_spell_check_visible_rows(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.project.worktree.large_file_threshold = Some(4096);
+                    let spell_check = settings.spell_check.get_or_insert_default();
+                    spell_check.enabled = Some(true);
+                    spell_check.dictionaries = Some(vec!["en_US".into()]);
+                    spell_check.dictionary_directories = Some(vec![path!("/dictionaries").into()]);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dictionaries"),
+            json!({
+                "en_US.aff": "SET UTF-8\n",
+                "en_US.dic": "2\nword\nwords\n",
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "notes.txt": "word wrod\n".repeat(400),
+                "large.txt": "wrod\n".repeat(1000),
+            }),
+        )
+        .await;
+        cx.update(|cx| init(fs.clone(), cx));
+        let project = project::Project::test(fs, [path!("/root").as_ref()], cx).await;
+
+        let open_editor = async |path: &str, cx: &mut TestAppContext| {
+            let buffer = project
+                .update(cx, |project, cx| project.open_local_buffer(path, cx))
+                .await
+                .unwrap();
+            cx.run_until_parked();
+            buffer.update(cx, |buffer, cx| {
+                buffer.set_language(Some(language::PLAIN_TEXT.clone()), cx)
+            });
+            let project = project.clone();
+            let (editor, cx) = cx.add_window_view(|window, cx| {
+                Editor::for_buffer(buffer, Some(project), window, cx)
+            });
+            cx.run_until_parked();
+            (editor, cx.clone())
+        };
+        let highlighted_rows = |editor: &Entity<Editor>, cx: &mut gpui::VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                editor
+                    .text_highlights(HighlightKey::SpellCheck, cx)
+                    .map(|(_, ranges)| {
+                        ranges
+                            .iter()
+                            .map(|range| range.start.to_point(&snapshot).row)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+            })
+        };
+
+        // Only the rows around the visible ones are checked.
+        let (editor, mut cx) = open_editor(path!("/root/notes.txt"), cx).await;
+        let expected_rows = (0..=DEFAULT_VISIBLE_ROWS + MARGIN_ROWS).collect::<Vec<_>>();
+        assert_eq!(highlighted_rows(&editor, &mut cx), expected_rows);
+
+        editor.update_in(&mut cx, |editor, window, cx| {
+            editor.set_scroll_position(point(0., 300.), window, cx);
+        });
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        let expected_rows = (300 - MARGIN_ROWS..400).collect::<Vec<_>>();
+        assert_eq!(highlighted_rows(&editor, &mut cx), expected_rows);
+
+        // Files opened in large file mode aren't checked, even with a language.
+        let (editor, mut cx) = open_editor(path!("/root/large.txt"), &mut cx).await;
+        assert_eq!(highlighted_rows(&editor, &mut cx), Vec::<u32>::new());
+
+        // Neither are buffers without a language.
+        let buffer = cx.new(|cx| Buffer::local("wrod", cx));
+        let (editor, mut cx) =
+            cx.add_window_view(|window, cx| Editor::for_buffer(buffer, None, window, cx));
+        cx.run_until_parked();
+        assert_eq!(highlighted_rows(&editor, &mut cx), Vec::<u32>::new());
+    }
+}
@@ -0,0 +1,115 @@
+use std::ops::Range;
+
+/// Words shorter than this are never checked.
+const MIN_WORD_LEN: usize = 2;
+
+/// Returns the byte ranges of the words in `text` that should be spell checked.
+///
+/// Identifiers are split into their parts, so `parseHTTPResponse` and `parse_http_response`
+/// are checked as `parse`, `HTTP` and `Response`. Parts written entirely in capitals are
+/// assumed to be acronyms and skipped, as are parts containing digits, URLs and email
+/// addresses.
+pub fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    for (run_start, run) in runs(text) {
+        if run.contains("://") || run.contains('@') {
+            continue;
+        }
+        for (token_start, token) in tokens(run) {
+            if token.chars().any(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            for part in split_identifier(token) {
+                let word = &token[part.clone()];
+                if word.chars().count() < MIN_WORD_LEN
+                    || (word.chars().count() > 1 && word.chars().all(|c| !c.is_lowercase()))
+                {
+                    continue;
+                }
+                let start = run_start + token_start;
+                words.push(start + part.start..start + part.end);
+            }
+        }
+    }
+    words
+}
+
+/// Splits `text` on whitespace, returning each run with its byte offset.
+fn runs(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace()
+        .map(move |run| (run.as_ptr() as usize - text.as_ptr() as usize, run))
+}
+
+/// Splits a whitespace-free run into alphanumeric tokens, keeping apostrophes that join two
+/// letters so that contractions like `don't` are checked as a single word.
+fn tokens(run: &str) -> Vec<(usize, &str)> {
+    let chars = run.char_indices().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (ix, &(offset, c)) in chars.iter().enumerate() {
+        let is_word_char = c.is_alphanumeric()
+            || (c == '\''
+                && ix > 0
+                && chars[ix - 1].1.is_alphabetic()
+                && chars.get(ix + 1).is_some_and(|(_, c)| c.is_alphabetic()));
+        match (is_word_char, start) {
+            (true, None) => start = Some(offset),
+            (false, Some(token_start)) => {
+                tokens.push((token_start, &run[token_start..offset]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(token_start) = start {
+        tokens.push((token_start, &run[token_start..]));
+    }
+    tokens
+}
+
+/// Splits a camelCase or PascalCase identifier at its case boundaries.
+fn split_identifier(token: &str) -> Vec<Range<usize>> {
+    let chars = token.char_indices().collect::<Vec<_>>();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for ix in 1..chars.len() {
+        let (offset, c) = chars[ix];
+        let previous = chars[ix - 1].1;
+        let next = chars.get(ix + 1).map(|(_, c)| *c);
+        let is_boundary = c.is_uppercase()
+            && (previous.is_lowercase()
+                || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase())));
+        if is_boundary {
+            parts.push(start..offset);
+            start = offset;
+        }
+    }
+    parts.push(start..token.len());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word_strings(text: &str) -> Vec<&str> {
+        words(text).into_iter().map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            word_strings("// Don't parse the HTTPServer response twice."),
+            vec!["Don't", "parse", "the", "Server", "response", "twice"]
+        );
+        assert_eq!(
+            word_strings("let parse_http_response = parseJsonValue;"),
+            vec!["let", "parse", "http", "response", "parse", "Json", "Value"]
+        );
+        assert_eq!(
+            word_strings("see https://zed.dev or mail hi@zed.dev about utf8 and a TODO"),
+            vec!["see", "or", "mail", "about", "and"]
+        );
+        assert_eq!(word_strings("naïve café"), vec!["naïve", "café"]);
+    }
+}
@@ -0,0 +1,553 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, bail};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A way of finding problems, such as compiler errors, in the output of a task.
+///
+/// Problems found are shown as diagnostics in the editor and the project diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcherTemplate {
+    /// The name of a built-in problem matcher: `$rustc` (or `$cargo`), `$tsc`, `$gcc`,
+    /// `$eslint` or `$pytest`.
+    BuiltIn(String),
+    /// A problem matcher defined with regular expressions.
+    Custom(ProblemMatcherDefinition),
+}
+
+/// A problem matcher defined with regular expressions, matched against each line of a task's
+/// output.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemMatcherDefinition {
+    /// The name of a built-in problem matcher to start from, whose patterns are used unless
+    /// `pattern` is set.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// The pattern matching a problem, or the patterns matching consecutive lines that together
+    /// describe a problem.
+    #[serde(default)]
+    pub pattern: Option<ProblemPatterns>,
+    /// The severity of problems whose pattern doesn't capture one.
+    #[serde(default)]
+    pub severity: Option<ProblemSeverity>,
+    /// The directory that relative file paths are resolved against, itself relative to the
+    /// task's working directory, which is the default. May use task variables.
+    #[serde(default)]
+    pub directory: Option<String>,
+}
+
+/// One pattern, or several matching consecutive lines.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemPatterns {
+    Single(ProblemPattern),
+    Multiple(Vec<ProblemPattern>),
+}
+
+impl ProblemPatterns {
+    fn as_slice(&self) -> &[ProblemPattern] {
+        match self {
+            Self::Single(pattern) => std::slice::from_ref(pattern),
+            Self::Multiple(patterns) => patterns,
+        }
+    }
+}
+
+/// A regular expression matching a line of output, with the indices of the capture groups that
+/// hold each part of the problem.
+///
+/// When a problem is described by several lines, its parts can be captured by any of their
+/// patterns. A problem needs a file and a message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the line with.
+    pub regexp: String,
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// The 1-based line of the problem, which defaults to the first.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// The 1-based column of the problem. Problems without one cover their whole line.
+    #[serde(default)]
+    pub column: Option<usize>,
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// The severity of the problem, such as `error` or `warning`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    #[serde(default)]
+    pub code: Option<usize>,
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether this last pattern of several can match any number of lines, each of which is a
+    /// problem sharing the parts captured by the previous patterns.
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl ProblemSeverity {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.to_ascii_lowercase();
+        if text.starts_with("err") || text.starts_with("fatal") {
+            Some(Self::Error)
+        } else if text.starts_with("warn") {
+            Some(Self::Warning)
+        } else if text.starts_with("info") || text.starts_with("note") {
+            Some(Self::Info)
+        } else if text.starts_with("hint") {
+            Some(Self::Hint)
+        } else {
+            None
+        }
+    }
+}
+
+/// A problem found in a task's output.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Problem {
+    pub path: PathBuf,
+    /// The 1-based line of the problem.
+    pub line: u32,
+    pub column: Option<u32>,
+    pub end_line: Option<u32>,
+    pub end_column: Option<u32>,
+    pub severity: ProblemSeverity,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// A [`ProblemMatcherTemplate`] with its patterns compiled.
+#[derive(Debug)]
+pub struct ProblemMatcher {
+    patterns: Vec<(Regex, ProblemPattern)>,
+    severity: ProblemSeverity,
+    directory: Option<PathBuf>,
+}
+
+impl ProblemMatcher {
+    pub fn new(template: &ProblemMatcherTemplate) -> anyhow::Result<Self> {
+        let definition = match template {
+            ProblemMatcherTemplate::BuiltIn(name) => built_in(name)?,
+            ProblemMatcherTemplate::Custom(definition) => {
+                let mut resolved = match &definition.base {
+                    Some(base) => built_in(base)?,
+                    None => ProblemMatcherDefinition::default(),
+                };
+                if definition.pattern.is_some() {
+                    resolved.pattern = definition.pattern.clone();
+                }
+                if definition.severity.is_some() {
+                    resolved.severity = definition.severity;
+                }
+                resolved.directory = definition.directory.clone();
+                resolved
+            }
+        };
+
+        let patterns = definition
+            .pattern
+            .as_ref()
+            .map(ProblemPatterns::as_slice)
+            .unwrap_or_default();
+        if patterns.is_empty() {
+            bail!("problem matcher has no patterns");
+        }
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let regex = Regex::new(&pattern.regexp)
+                    .with_context(|| format!("invalid problem pattern {:?}", pattern.regexp))?;
+                anyhow::Ok((regex, pattern.clone()))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            patterns,
+            severity: definition.severity.unwrap_or_default(),
+            directory: definition.directory.map(PathBuf::from),
+        })
+    }
+
+    /// Finds the problems in a task's output. Relative paths are resolved against the matcher's
+    /// directory and `cwd`, and problems whose path can't be resolved are skipped.
+    pub fn match_output(&self, output: &str, cwd: Option<&Path>) -> Vec<Problem> {
+        self.match_lines(output.lines(), cwd, &mut ProblemMatchState::default())
+    }
+
+    /// Like [`Self::match_output`], for output that arrives a few lines at a time: a problem
+    /// described by several lines can span calls that share the same `state`.
+    pub fn match_lines<'a>(
+        &self,
+        lines: impl IntoIterator<Item = &'a str>,
+        cwd: Option<&Path>,
+        state: &mut ProblemMatchState,
+    ) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let pending = &mut state.pending;
+        for line in lines {
+            if let Some((ix, captured)) = pending.take() {
+                let (regex, pattern) = &self.patterns[ix];
+                if let Some(captures) = regex.captures(line) {
+                    let mut problem = captured.clone();
+                    problem.capture(pattern, &captures);
+                    if ix + 1 < self.patterns.len() {
+                        *pending = Some((ix + 1, problem));
+                    } else {
+                        problems.extend(self.problem(problem, cwd));
+                        if pattern.repeat {
+                            *pending = Some((ix, captured));
+                        }
+                    }
+                    continue;
+                }
+            }
+
+            let (regex, pattern) = &self.patterns[0];
+            if let Some(captures) = regex.captures(line) {
+                let mut problem = CapturedProblem::default();
+                problem.capture(pattern, &captures);
+                if self.patterns.len() > 1 {
+                    *pending = Some((1, problem));
+                } else {
+                    problems.extend(self.problem(problem, cwd));
+                }
+            }
+        }
+        problems
+    }
+
+    fn problem(&self, captured: CapturedProblem, cwd: Option<&Path>) -> Option<Problem> {
+        let file = PathBuf::from(captured.file?.trim());
+        let path = if file.is_absolute() {
+            file
+        } else {
+            match &self.directory {
+                Some(directory) if directory.is_absolute() => directory.join(file),
+                Some(directory) => cwd?.join(directory).join(file),
+                None => cwd?.join(file),
+            }
+        };
+        Some(Problem {
+            path,
+            line: captured.line.unwrap_or(1).max(1),
+            column: captured.column,
+            end_line: captured.end_line,
+            end_column: captured.end_column,
+            severity: captured.severity.unwrap_or(self.severity),
+            code: captured.code,
+            message: captured.message?,
+        })
+    }
+}
+
+/// The progress of a [`ProblemMatcher`] through a problem described by several lines of output.
+#[derive(Clone, Debug, Default)]
+pub struct ProblemMatchState {
+    /// The index of the pattern expected to match the next line, and the parts of the problem
+    /// captured by the previous ones.
+    pending: Option<(usize, CapturedProblem)>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct CapturedProblem {
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl CapturedProblem {
+    fn capture(&mut self, pattern: &ProblemPattern, captures: &Captures) {
+        let text = |group: Option<usize>| {
+            captures
+                .get(group?)
+                .map(|capture| capture.as_str())
+                .filter(|text| !text.is_empty())
+        };
+        let number = |group| text(group).and_then(|text| text.parse().ok());
+        if let Some(file) = text(pattern.file) {
+            self.file = Some(file.to_string());
+        }
+        if let Some(line) = number(pattern.line) {
+            self.line = Some(line);
+        }
+        if let Some(column) = number(pattern.column) {
+            self.column = Some(column);
+        }
+        if let Some(end_line) = number(pattern.end_line) {
+            self.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(pattern.end_column) {
+            self.end_column = Some(end_column);
+        }
+        if let Some(severity) = text(pattern.severity).and_then(ProblemSeverity::parse) {
+            self.severity = Some(severity);
+        }
+        if let Some(code) = text(pattern.code) {
+            self.code = Some(code.to_string());
+        }
+        if let Some(message) = text(pattern.message) {
+            self.message = Some(message.trim().to_string());
+        }
+    }
+}
+
+fn built_in(name: &str) -> anyhow::Result<ProblemMatcherDefinition> {
+    let patterns = match name {
+        "$rustc" | "$cargo" => vec![
+            ProblemPattern {
+                regexp: r"^(warning|error)(?:\[(\w+)\])?: (.*)$".to_string(),
+                severity: Some(1),
+                code: Some(2),
+                message: Some(3),
+                ..Default::default()
+            },
+            ProblemPattern {
+                regexp: r"^\s*-->\s+(.+?):(\d+):(\d+)\s*$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                ..Default::default()
+            },
+        ],
+        "$tsc" | "$tsc-watch" => vec![ProblemPattern {
+            regexp: r"^([^\s].*?)[(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$"
+                .to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            code: Some(5),
+            message: Some(6),
+            ..Default::default()
+        }],
+        "$gcc" => vec![ProblemPattern {
+            regexp: r"^(.+?):(\d+):(?:(\d+):)?\s+(?:fatal\s+)?(warning|error):\s+(.*)$"
+                .to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            ..Default::default()
+        }],
+        "$eslint" | "$eslint-stylish" => vec![
+            ProblemPattern {
+                regexp: r"^([^\s].*)$".to_string(),
+                file: Some(1),
+                ..Default::default()
+            },
+            ProblemPattern {
+                regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$"
+                    .to_string(),
+                line: Some(1),
+                column: Some(2),
+                severity: Some(3),
+                message: Some(4),
+                code: Some(5),
+                repeat: true,
+                ..Default::default()
+            },
+        ],
+        "$pytest" => vec![ProblemPattern {
+            regexp: r"^(.+?\.py):(\d+): ([A-Z]\w*(?:Error|Exception|Failed)\b.*)$".to_string(),
+            file: Some(1),
+            line: Some(2),
+            message: Some(3),
+            ..Default::default()
+        }],
+        _ => bail!("unknown problem matcher {name:?}"),
+    };
+    Ok(ProblemMatcherDefinition {
+        pattern: Some(ProblemPatterns::Multiple(patterns)),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn match_output(name: &str, output: &str) -> Vec<Problem> {
+        ProblemMatcher::new(&ProblemMatcherTemplate::BuiltIn(name.to_string()))
+            .unwrap()
+            .match_output(output, Some(Path::new("/project")))
+    }
+
+    #[test]
+    fn test_rustc() {
+        let output = "   Compiling foo v0.1.0 (/project)
+error[E0308]: mismatched types
+ --> src/main.rs:4:18
+  |
+4 |     let x: u32 = \"a\";
+  |
+warning: unused variable: `y`
+  --> src/lib.rs:10:9
+error: could not compile `foo` (bin \"foo\") due to 1 previous error
+";
+        assert_eq!(
+            match_output("$cargo", output),
+            vec![
+                Problem {
+                    path: PathBuf::from("/project/src/main.rs"),
+                    line: 4,
+                    column: Some(18),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0308".to_string()),
+                    message: "mismatched types".to_string(),
+                },
+                Problem {
+                    path: PathBuf::from("/project/src/lib.rs"),
+                    line: 10,
+                    column: Some(9),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `y`".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matching_output_line_by_line() {
+        let matcher =
+            ProblemMatcher::new(&ProblemMatcherTemplate::BuiltIn("$rustc".to_string())).unwrap();
+        let cwd = Some(Path::new("/project"));
+        let output = "error[E0308]: mismatched types
+ --> src/main.rs:4:18
+warning: unused variable: `y`
+  --> src/lib.rs:10:9
+";
+        let mut state = ProblemMatchState::default();
+        let problems = output
+            .lines()
+            .flat_map(|line| matcher.match_lines([line], cwd, &mut state))
+            .collect::<Vec<_>>();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems, matcher.match_output(output, cwd));
+    }
+
+    #[test]
+    fn test_single_line_matchers() {
+        let problems = match_output(
+            "$tsc",
+            "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+             src/other.ts:5:1 - warning TS6133: 'x' is declared but its value is never read.",
+        );
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].path, PathBuf::from("/project/src/index.ts"));
+        assert_eq!((problems[0].line, problems[0].column), (3, Some(7)));
+        assert_eq!(problems[0].code.as_deref(), Some("TS2322"));
+        assert_eq!(problems[1].severity, ProblemSeverity::Warning);
+
+        let problems = match_output(
+            "$gcc",
+            "/abs/main.c:12:5: error: 'x' undeclared (first use in this function)\n\
+             main.c: In function 'main':\n\
+             util.c:3: warning: implicit declaration of function 'f'",
+        );
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].path, PathBuf::from("/abs/main.c"));
+        assert_eq!(problems[1].path, PathBuf::from("/project/util.c"));
+        assert_eq!((problems[1].line, problems[1].column), (3, None));
+
+        let problems = match_output(
+            "$pytest",
+            "tests/test_math.py:8: in test_add\n\
+             tests/test_math.py:12: AssertionError: assert 3 == 4",
+        );
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, 12);
+        assert_eq!(problems[0].message, "AssertionError: assert 3 == 4");
+    }
+
+    #[test]
+    fn test_looping_matcher() {
+        let output = "
+/project/src/a.js
+  1:10  error    'x' is defined but never used  no-unused-vars
+  2:1   warning  Unexpected console statement   no-console
+
+/project/src/b.js
+  7:3  error  Missing semicolon  semi
+
+✖ 3 problems (2 errors, 1 warning)
+";
+        let problems = match_output("$eslint", output);
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (
+                    problem.path.to_str().unwrap(),
+                    problem.line,
+                    problem.message.as_str(),
+                    problem.code.as_deref()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "/project/src/a.js",
+                    1,
+                    "'x' is defined but never used",
+                    Some("no-unused-vars")
+                ),
+                (
+                    "/project/src/a.js",
+                    2,
+                    "Unexpected console statement",
+                    Some("no-console")
+                ),
+                ("/project/src/b.js", 7, "Missing semicolon", Some("semi")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_matcher() {
+        let template: ProblemMatcherTemplate = serde_json::from_value(serde_json::json!({
+            "pattern": {
+                "regexp": "^(.*):(\\d+): (.*)$",
+                "file": 1,
+                "line": 2,
+                "message": 3
+            },
+            "severity": "warning",
+            "directory": "/elsewhere"
+        }))
+        .unwrap();
+        let matcher = ProblemMatcher::new(&template).unwrap();
+        let problems = matcher.match_output("lib/x.rb:3: something odd", Some(Path::new("/p")));
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path, PathBuf::from("/elsewhere/lib/x.rb"));
+        assert_eq!(problems[0].severity, ProblemSeverity::Warning);
+
+        let template: ProblemMatcherTemplate =
+            serde_json::from_value(serde_json::json!({ "base": "$nope" })).unwrap();
+        assert!(ProblemMatcher::new(&template).is_err());
+    }
+}
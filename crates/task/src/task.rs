@@ -2,6 +2,7 @@
 
 mod adapter_schema;
 mod debug_format;
+mod problem_matcher;
 mod serde_helpers;
 pub mod static_source;
 mod task_template;
@@ -22,6 +23,10 @@ pub use debug_format::{
     AttachRequest, BuildTaskDefinition, DebugRequest, DebugScenario, DebugTaskFile, LaunchRequest,
     Request, TcpArgumentsTemplate, ZedDebugConfig,
 };
+pub use problem_matcher::{
+    Problem, ProblemMatchState, ProblemMatcher, ProblemMatcherDefinition, ProblemMatcherTemplate,
+    ProblemPattern, ProblemPatterns, ProblemSeverity,
+};
 pub use task_template::{
     DebugArgsRequest, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates,
     substitute_variables_in_map, substitute_variables_in_str,
@@ -75,6 +80,8 @@ pub struct SpawnInTerminal {
     pub show_command: bool,
     /// Whether to show the rerun button in the terminal tab.
     pub show_rerun: bool,
+    /// Problem matchers to find diagnostics in the task output with, with all task variables resolved.
+    pub problem_matchers: Vec<ProblemMatcherTemplate>,
 }
 
 impl SpawnInTerminal {
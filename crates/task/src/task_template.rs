@@ -9,8 +9,9 @@ use util::serde::default_true;
 use util::{ResultExt, truncate_and_remove_front};
 
 use crate::{
-    AttachRequest, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId,
-    VariableName, ZED_VARIABLE_NAME_PREFIX, serde_helpers::non_empty_string_vec,
+    AttachRequest, ProblemMatcherTemplate, ResolvedTask, RevealTarget, Shell, SpawnInTerminal,
+    TaskContext, TaskId, VariableName, ZED_VARIABLE_NAME_PREFIX,
+    serde_helpers::non_empty_string_vec,
 };
 
 /// A template definition of a Zed task to run.
@@ -72,6 +73,11 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Problem matchers that turn the task's output into diagnostics, which are cleared when the task reruns.
+    /// Either the names of built-in matchers (`$rustc`, `$tsc`, `$gcc`, `$eslint`, `$pytest`),
+    /// or matchers defined with regular expressions.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcherTemplate>,
 }
 
 #[derive(Deserialize, Eq, PartialEq, Clone, Debug)]
@@ -212,6 +218,26 @@ impl TaskTemplate {
             &mut substituted_variables,
         )?;
 
+        let problem_matchers = self
+            .problem_matchers
+            .iter()
+            .map(|matcher| match matcher {
+                ProblemMatcherTemplate::Custom(definition) => {
+                    let mut definition = definition.clone();
+                    if let Some(directory) = &definition.directory {
+                        definition.directory = Some(substitute_all_template_variables_in_str(
+                            directory,
+                            &task_variables,
+                            &variable_names,
+                            &mut substituted_variables,
+                        )?);
+                    }
+                    Some(ProblemMatcherTemplate::Custom(definition))
+                }
+                built_in => Some(built_in.clone()),
+            })
+            .collect::<Option<Vec<_>>>()?;
+
         let task_hash = to_hex_hash(self)
             .context("hashing task template")
             .log_err()?;
@@ -270,6 +296,7 @@ impl TaskTemplate {
                 show_summary: self.show_summary,
                 show_command: self.show_command,
                 show_rerun: true,
+                problem_matchers,
             },
         })
     }
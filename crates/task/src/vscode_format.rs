@@ -3,7 +3,10 @@ use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{EnvVariableReplacer, TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    EnvVariableReplacer, ProblemMatcherDefinition, ProblemMatcherTemplate, ProblemPattern,
+    ProblemPatterns, ProblemSeverity, TaskTemplate, TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -69,10 +72,16 @@ impl VsCodeTaskDefinition {
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
         let args = args.into_iter().map(|arg| replacer.replace(&arg)).collect();
+        let problem_matchers = self
+            .other_attributes
+            .get("problemMatcher")
+            .map(|matchers| problem_matchers(matchers, replacer))
+            .unwrap_or_default();
         let mut template = TaskTemplate {
             label: self.label,
             command,
             args,
+            problem_matchers,
             ..TaskTemplate::default()
         };
         if let Some(options) = self.options {
@@ -83,6 +92,82 @@ impl VsCodeTaskDefinition {
     }
 }
 
+/// Converts Code's `problemMatcher`, which may be the name of a matcher, a matcher definition or
+/// an array of either. Unsupported parts of a definition, such as background patterns, are ignored.
+fn problem_matchers(
+    value: &serde_json_lenient::Value,
+    replacer: &EnvVariableReplacer,
+) -> Vec<ProblemMatcherTemplate> {
+    use serde_json_lenient::Value;
+
+    match value {
+        Value::String(name) => vec![ProblemMatcherTemplate::BuiltIn(name.clone())],
+        Value::Array(matchers) => matchers
+            .iter()
+            .flat_map(|matcher| problem_matchers(matcher, replacer))
+            .collect(),
+        Value::Object(definition) => {
+            let string = |key: &str| definition.get(key).and_then(Value::as_str);
+            let pattern = |value: &Value| {
+                let regexp = value.get("regexp")?.as_str()?.to_string();
+                let group = |key: &str| {
+                    value
+                        .get(key)
+                        .and_then(Value::as_u64)
+                        .map(|group| group as usize)
+                };
+                Some(ProblemPattern {
+                    regexp,
+                    file: group("file"),
+                    line: group("line"),
+                    column: group("column"),
+                    end_line: group("endLine"),
+                    end_column: group("endColumn"),
+                    severity: group("severity"),
+                    code: group("code"),
+                    message: group("message"),
+                    repeat: value.get("loop").and_then(Value::as_bool).unwrap_or(false),
+                })
+            };
+            let pattern = match definition.get("pattern") {
+                // Named patterns can't be resolved, so use the base matcher's patterns instead.
+                Some(Value::Array(patterns)) => patterns
+                    .iter()
+                    .map(pattern)
+                    .collect::<Option<Vec<_>>>()
+                    .map(ProblemPatterns::Multiple),
+                Some(value) => pattern(value).map(ProblemPatterns::Single),
+                None => None,
+            };
+            let severity = string("severity").and_then(|severity| match severity {
+                "error" => Some(ProblemSeverity::Error),
+                "warning" => Some(ProblemSeverity::Warning),
+                "info" => Some(ProblemSeverity::Info),
+                _ => None,
+            });
+            let directory = match definition.get("fileLocation") {
+                Some(Value::Array(location)) => location
+                    .get(1)
+                    .and_then(Value::as_str)
+                    .map(|directory| replacer.replace(directory)),
+                _ => None,
+            };
+            let base = string("base").map(ToOwned::to_owned);
+            if base.is_none() && pattern.is_none() {
+                log::warn!("Skipping a problem matcher without a base or supported patterns");
+                return Vec::new();
+            }
+            vec![ProblemMatcherTemplate::Custom(ProblemMatcherDefinition {
+                base,
+                pattern,
+                severity,
+                directory,
+            })]
+        }
+        _ => Vec::new(),
+    }
+}
+
 /// [`VsCodeTaskFile`] is a superset of Code's task definition format.
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
@@ -124,7 +209,8 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        ProblemMatcherDefinition, ProblemMatcherTemplate, TaskTemplate, TaskTemplates,
+        VsCodeTaskFile,
         vscode_format::{Command, VsCodeTaskDefinition},
     };
 
@@ -227,6 +313,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcherTemplate::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -238,18 +325,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcherTemplate::BuiltIn("$tsc-watch".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcherTemplate::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcherTemplate::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -330,28 +420,45 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matchers: vec![ProblemMatcherTemplate::Custom(ProblemMatcherDefinition {
+                    base: Some("$tsc-watch".to_string()),
+                    directory: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..Default::default()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcherTemplate::Custom(ProblemMatcherDefinition {
+                    base: Some("$tsc".to_string()),
+                    directory: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..Default::default()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcherTemplate::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcherTemplate::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcherTemplate::Custom(ProblemMatcherDefinition {
+                    base: Some("$tsc".to_string()),
+                    directory: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..Default::default()
+                })],
                 ..Default::default()
             },
         ];
@@ -0,0 +1,152 @@
+//! Rewriting of PTY output before alacritty parses it.
+//!
+//! Alacritty reads the PTY and parses its output on its own I/O thread, and silently drops the
+//! escape sequences it doesn't implement. [`FilteredPty`] sits between the two so that Zed can
+//! handle some of those sequences itself, turning them into ones alacritty does understand.
+
+use std::{
+    io::{self, Read, Write},
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use collections::VecDeque;
+use polling::{Event, PollMode, Poller};
+
+/// A stage in the rewriting of PTY output.
+///
+/// Escape sequences may be split across reads, so a filter holds back the bytes that could
+/// still turn out to be part of one until the sequence is complete.
+pub(crate) trait PtyOutputFilter: Send {
+    /// Appends the rewritten `input` to `output`, and anything that should be written back to
+    /// the program, such as the answer to a query, to `reply`.
+    fn filter(&mut self, input: &[u8], output: &mut Vec<u8>, reply: &mut Vec<u8>);
+
+    /// Appends the bytes held back for an incomplete sequence to `output` as they are.
+    fn flush(&mut self, output: &mut Vec<u8>);
+
+    fn resize(&mut self, _window_size: WindowSize) {}
+}
+
+/// Writes an `OSC 8` sequence that starts a hyperlink to `uri`, or ends the current one.
+///
+/// Alacritty stores hyperlinks on the cells they cover, so filters use hyperlinks with
+/// internal URIs to attach data to a range of the grid that scrolls and reflows with it.
+pub(crate) fn write_hyperlink(output: &mut Vec<u8>, uri: Option<&str>) {
+    output.extend_from_slice(b"\x1b]8;;");
+    output.extend_from_slice(uri.unwrap_or_default().as_bytes());
+    output.extend_from_slice(b"\x1b\\");
+}
+
+/// A PTY whose output is passed through a chain of [`PtyOutputFilter`]s.
+pub(crate) struct FilteredPty<T> {
+    pty: T,
+    filters: Vec<Box<dyn PtyOutputFilter>>,
+    filtered: VecDeque<u8>,
+    buffer: Vec<u8>,
+}
+
+impl<T> FilteredPty<T> {
+    pub fn new(pty: T, filters: Vec<Box<dyn PtyOutputFilter>>) -> Self {
+        Self {
+            pty,
+            filters,
+            filtered: VecDeque::new(),
+            buffer: vec![0; 0x8000],
+        }
+    }
+}
+
+impl<T: EventedReadWrite> FilteredPty<T> {
+    fn run_filters(&mut self, len: usize) {
+        // A read of zero bytes means the PTY was closed, so no held back sequence will ever be
+        // completed.
+        let closed = len == 0;
+        let mut data = self.buffer[..len].to_vec();
+        let mut reply = Vec::new();
+        for filter in &mut self.filters {
+            let mut output = Vec::with_capacity(data.len());
+            filter.filter(&data, &mut output, &mut reply);
+            if closed {
+                filter.flush(&mut output);
+            }
+            data = output;
+        }
+        self.filtered.extend(data);
+
+        if !reply.is_empty()
+            && !closed
+            && let Err(error) = self.pty.writer().write_all(&reply)
+        {
+            log::warn!("failed to reply to terminal program: {error}");
+        }
+    }
+}
+
+impl<T: EventedReadWrite> Read for FilteredPty<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.filtered.is_empty() {
+            // Rewriting a sequence can make it longer, so read less than alacritty asks for.
+            let len = (buf.len() / 2).clamp(1, self.buffer.len());
+            let read = self.pty.reader().read(&mut self.buffer[..len])?;
+            self.run_filters(read);
+            if read == 0 {
+                break;
+            }
+        }
+        self.filtered.read(buf)
+    }
+}
+
+impl<T: EventedReadWrite> EventedReadWrite for FilteredPty<T> {
+    type Reader = Self;
+    type Writer = T::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.pty.register(poll, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<T: EventedPty> EventedPty for FilteredPty<T> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<T: OnResize> OnResize for FilteredPty<T> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        for filter in &mut self.filters {
+            filter.resize(window_size);
+        }
+        self.pty.on_resize(window_size)
+    }
+}
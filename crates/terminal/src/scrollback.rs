@@ -0,0 +1,249 @@
+//! Serialization of the terminal's contents as text with escape sequences, which a terminal can
+//! replay to show them again with their styling.
+
+use std::fmt::Write as _;
+
+use alacritty_terminal::{
+    grid::{Dimensions, Grid},
+    index::{Column, Point as AlacPoint},
+    term::cell::{Cell, Flags},
+    vte::ansi::{Color, NamedColor},
+};
+
+use crate::is_internal_hyperlink;
+
+/// Serializes the cells of `grid` from its topmost line up to `end`, exclusive.
+///
+/// Blank cells at the end of a line are left out, except on the line of `end`, so that replaying
+/// the text leaves the cursor at `end`. Wrapped lines stay joined so that they reflow to the
+/// width of the terminal they're replayed in.
+pub(crate) fn serialize(grid: &Grid<Cell>, end: AlacPoint) -> String {
+    let mut output = String::new();
+    let mut style = Style::default();
+    let mut hyperlink = None;
+    let columns = grid.columns();
+    let mut line = grid.topmost_line();
+    while line <= end.line {
+        let row = &grid[line];
+        let is_last = line == end.line;
+        let wrapped = !is_last && row[Column(columns - 1)].flags.contains(Flags::WRAPLINE);
+        let len = if is_last {
+            end.column.0.min(columns)
+        } else if wrapped {
+            columns
+        } else {
+            (0..columns)
+                .rev()
+                .find(|column| !is_blank(&row[Column(*column)]))
+                .map_or(0, |column| column + 1)
+        };
+
+        for column in 0..len {
+            let cell = &row[Column(column)];
+            if cell
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+            let cell_style = Style::of(cell);
+            if cell_style != style {
+                cell_style.write(&mut output);
+                style = cell_style;
+            }
+            let cell_hyperlink = cell
+                .hyperlink()
+                .map(|link| link.uri().to_string())
+                .filter(|uri| !is_internal_hyperlink(uri));
+            if cell_hyperlink != hyperlink {
+                write_hyperlink(&mut output, cell_hyperlink.as_deref());
+                hyperlink = cell_hyperlink;
+            }
+            output.push(cell.c);
+            if let Some(zerowidth) = cell.zerowidth() {
+                output.extend(zerowidth);
+            }
+        }
+
+        if !wrapped && !is_last {
+            // Reset the style before moving to the next line, as a line feed that scrolls fills
+            // the new line with the current background.
+            if style != Style::default() {
+                output.push_str("\x1b[0m");
+                style = Style::default();
+            }
+            if hyperlink.take().is_some() {
+                write_hyperlink(&mut output, None);
+            }
+            output.push_str("\r\n");
+        }
+        line += 1;
+    }
+
+    if style != Style::default() {
+        output.push_str("\x1b[0m");
+    }
+    if hyperlink.is_some() {
+        write_hyperlink(&mut output, None);
+    }
+    output
+}
+
+fn is_blank(cell: &Cell) -> bool {
+    cell.c == ' '
+        && cell.bg == Color::Named(NamedColor::Background)
+        && !cell
+            .flags
+            .intersects(Flags::INVERSE | Flags::ALL_UNDERLINES | Flags::STRIKEOUT)
+}
+
+fn write_hyperlink(output: &mut String, uri: Option<&str>) {
+    write!(output, "\x1b]8;;{}\x1b\\", uri.unwrap_or_default()).ok();
+}
+
+/// The attributes of a cell that are set with `SGR` sequences.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Style {
+    fg: Color,
+    bg: Color,
+    flags: Flags,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fg: Color::Named(NamedColor::Foreground),
+            bg: Color::Named(NamedColor::Background),
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl Style {
+    const FLAGS: [(Flags, &str); 11] = [
+        (Flags::BOLD, "1"),
+        (Flags::DIM, "2"),
+        (Flags::ITALIC, "3"),
+        (Flags::UNDERLINE, "4"),
+        (Flags::DOUBLE_UNDERLINE, "21"),
+        (Flags::UNDERCURL, "4:3"),
+        (Flags::DOTTED_UNDERLINE, "4:4"),
+        (Flags::DASHED_UNDERLINE, "4:5"),
+        (Flags::INVERSE, "7"),
+        (Flags::HIDDEN, "8"),
+        (Flags::STRIKEOUT, "9"),
+    ];
+
+    fn of(cell: &Cell) -> Self {
+        let mut flags = Flags::empty();
+        for (flag, _) in Self::FLAGS {
+            if cell.flags.contains(flag) {
+                flags.insert(flag);
+            }
+        }
+        Self {
+            fg: cell.fg,
+            bg: cell.bg,
+            flags,
+        }
+    }
+
+    /// Writes an `SGR` sequence that resets the attributes, then sets the ones of this style.
+    fn write(&self, output: &mut String) {
+        output.push_str("\x1b[0");
+        for (flag, parameter) in Self::FLAGS {
+            if self.flags.contains(flag) {
+                output.push(';');
+                output.push_str(parameter);
+            }
+        }
+        write_color(output, self.fg, 30);
+        write_color(output, self.bg, 40);
+        output.push('m');
+    }
+}
+
+/// Writes the `SGR` parameters that set a color, where `base` is 30 for the foreground and 40
+/// for the background.
+fn write_color(output: &mut String, color: Color, base: u8) {
+    match color {
+        Color::Named(named) => {
+            let index = named as usize;
+            let dim_black = NamedColor::DimBlack as usize;
+            let parameter = match index {
+                0..=7 => base as usize + index,
+                8..=15 => base as usize + 60 + index - 8,
+                _ if (dim_black..dim_black + 8).contains(&index) => {
+                    base as usize + index - dim_black
+                }
+                // The default colors.
+                _ => return,
+            };
+            write!(output, ";{parameter}").ok();
+        }
+        Color::Indexed(index) => {
+            write!(output, ";{};5;{index}", base + 8).ok();
+        }
+        Color::Spec(rgb) => {
+            write!(output, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{
+        Term,
+        event::VoidListener,
+        index::{Column, Line},
+        term::{Config, test::TermSize},
+        vte::ansi::{Processor, StdSyncHandler},
+    };
+
+    use super::*;
+
+    fn term_with_output(columns: usize, output: &str) -> Term<VoidListener> {
+        let mut term = Term::new(Config::default(), &TermSize::new(columns, 10), VoidListener);
+        let mut processor = Processor::<StdSyncHandler>::new();
+        processor.advance(&mut term, output.as_bytes());
+        term
+    }
+
+    #[test]
+    fn test_serialize() {
+        let term = term_with_output(
+            14,
+            "plain   \r\n\x1b[1;31mbold red\x1b[0m \x1b[38;2;1;2;3mrgb\x1b[0m\r\n\r\n\
+             \x1b]8;;https://zed.dev\x1b\\link\x1b]8;;\x1b\\ wraps around\r\n$ ",
+        );
+        let end = term.grid().cursor.point;
+        assert_eq!(end, AlacPoint::new(Line(5), Column(2)));
+
+        let serialized = serialize(term.grid(), end);
+        assert_eq!(
+            serialized,
+            "plain\r\n\x1b[0;1;31mbold red\x1b[0m \x1b[0;38;2;1;2;3mrgb\x1b[0m\r\n\r\n\
+             \x1b]8;;https://zed.dev\x1b\\link\x1b]8;;\x1b\\ wraps around\r\n$ "
+        );
+
+        // Replaying the serialized text, even at another width, reproduces the contents.
+        let replayed = term_with_output(20, &serialized);
+        assert_eq!(
+            replayed.grid().cursor.point,
+            AlacPoint::new(Line(4), Column(2))
+        );
+        let line = |line| {
+            crate::row_to_string(&replayed.grid()[Line(line)])
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(line(3), "link wraps around");
+        let cell = &replayed.grid()[Line(1)][Column(0)];
+        assert!(cell.flags.contains(Flags::BOLD));
+        assert_eq!(cell.fg, Color::Named(NamedColor::Red));
+
+        // Stopping at the start of the cursor's line leaves out the prompt.
+        let serialized = serialize(term.grid(), AlacPoint::new(end.line, Column(0)));
+        assert!(serialized.ends_with("wraps around\r\n"));
+    }
+}
@@ -0,0 +1,328 @@
+//! Persistent terminal sessions, whose processes outlive the editor and its remote connections.
+//!
+//! A session host is a small long-lived process that owns the PTYs of persistent terminals. A
+//! terminal with a session doesn't run its shell directly: it runs an attach client, which relays
+//! input, output and window size changes between the terminal and the session. When the client
+//! goes away, because the editor quit or the SSH connection dropped, the host keeps the session
+//! running, and replays its recent output to the next client that attaches to it.
+//!
+//! Clients and the host talk over a Unix socket. A client first sends an [`AttachRequest`] as a
+//! line of JSON, after which both sides exchange [`Frame`]s.
+
+#[cfg(unix)]
+mod client;
+#[cfg(unix)]
+mod host;
+
+#[cfg(unix)]
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    path::PathBuf,
+};
+
+#[cfg(unix)]
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+#[cfg(unix)]
+pub use client::attach;
+#[cfg(unix)]
+pub use host::run_host;
+
+/// The flag that makes the editor act as an attach client.
+pub const ATTACH_SESSION_FLAG: &str = "--attach-terminal-session";
+/// The environment variable that holds the command a new session runs, as JSON.
+pub const SESSION_COMMAND_ENV: &str = "ZED_TERMINAL_SESSION_COMMAND";
+/// Written to an attach client's input to end its session, rather than detach from it.
+pub const END_SESSION_SEQUENCE: &[u8] = b"\x1b_zed:end-session\x1b\\";
+/// The longest frame payload that is accepted. The longest ones sent are replayed output.
+#[cfg(unix)]
+const MAX_FRAME_LEN: usize = host::REPLAY_LIMIT;
+
+/// The command a new session runs. Without a program, it runs the user's login shell.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCommand {
+    pub program: Option<String>,
+    pub args: Vec<String>,
+}
+
+#[cfg(unix)]
+#[derive(Debug, Serialize, Deserialize)]
+struct AttachRequest {
+    session_id: String,
+    command: SessionCommand,
+    working_directory: Option<PathBuf>,
+    env: HashMap<String, String>,
+    rows: u16,
+    columns: u16,
+}
+
+#[cfg(unix)]
+#[derive(Debug, PartialEq)]
+enum Frame {
+    /// Input for the session, sent by the client.
+    Input(Vec<u8>),
+    /// A new window size for the session, sent by the client.
+    Resize { rows: u16, columns: u16 },
+    /// Ends the session, sent by the client.
+    End,
+    /// Output of the session, sent by the host.
+    Output(Vec<u8>),
+    /// The exit status of the session's process, sent by the host after its last output.
+    Exit(i32),
+}
+
+#[cfg(unix)]
+impl Frame {
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let (kind, payload) = match self {
+            Frame::Input(input) => (0, input.clone()),
+            Frame::Resize { rows, columns } => {
+                let mut payload = rows.to_be_bytes().to_vec();
+                payload.extend(columns.to_be_bytes());
+                (1, payload)
+            }
+            Frame::End => (2, Vec::new()),
+            Frame::Output(output) => (3, output.clone()),
+            Frame::Exit(status) => (4, status.to_be_bytes().to_vec()),
+        };
+        writer.write_all(&[kind])?;
+        writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+        writer.write_all(&payload)?;
+        writer.flush()
+    }
+
+    /// Reads the next frame, or `None` when the other side disconnected.
+    fn read(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut header = [0; 5];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(error) => return Err(error),
+        }
+        let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("frame of {len} bytes is too long"),
+            ));
+        }
+        let mut payload = vec![0; len];
+        reader.read_exact(&mut payload)?;
+        Ok(Some(match header[0] {
+            0 => Frame::Input(payload),
+            1 if len == 4 => Frame::Resize {
+                rows: u16::from_be_bytes([payload[0], payload[1]]),
+                columns: u16::from_be_bytes([payload[2], payload[3]]),
+            },
+            2 => Frame::End,
+            3 => Frame::Output(payload),
+            4 if len == 4 => Frame::Exit(i32::from_be_bytes([
+                payload[0], payload[1], payload[2], payload[3],
+            ])),
+            kind => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown frame kind {kind}"),
+                ));
+            }
+        }))
+    }
+}
+
+/// The socket of the session host of the current user.
+#[cfg(unix)]
+pub fn socket_path() -> PathBuf {
+    paths::terminal_sessions_socket().clone()
+}
+
+/// Returns the program and arguments that attach a terminal to the session with the given id,
+/// creating it if needed, when run by the editor binary.
+#[cfg(unix)]
+pub fn attach_command(session_id: &str) -> Result<(String, Vec<String>)> {
+    let program = std::env::current_exe().context("locating the editor binary")?;
+    Ok((
+        program.to_string_lossy().into_owned(),
+        vec![ATTACH_SESSION_FLAG.to_string(), session_id.to_string()],
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::{
+        io::BufReader,
+        net::Shutdown,
+        os::unix::net::UnixStream,
+        path::Path,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frames = [
+            Frame::Input(b"ls -la\r".to_vec()),
+            Frame::Resize {
+                rows: 40,
+                columns: 120,
+            },
+            Frame::End,
+            Frame::Output(b"\x1b[1mtotal 0\x1b[0m\r\n".to_vec()),
+            Frame::Exit(130),
+        ];
+        let mut bytes = Vec::new();
+        for frame in &frames {
+            frame.write(&mut bytes).unwrap();
+        }
+
+        let mut reader = bytes.as_slice();
+        for frame in frames {
+            assert_eq!(Frame::read(&mut reader).unwrap(), Some(frame));
+        }
+        assert_eq!(Frame::read(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_frame_too_long() {
+        let mut bytes = vec![0];
+        bytes.extend(u32::MAX.to_be_bytes());
+        let error = Frame::read(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_input_frame() {
+        assert_eq!(client::input_frame(b"ls\r"), Frame::Input(b"ls\r".to_vec()));
+        let mut input = b"exit".to_vec();
+        input.extend(END_SESSION_SEQUENCE);
+        assert_eq!(client::input_frame(&input), Frame::End);
+    }
+
+    #[test]
+    fn test_host_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("sessions.sock");
+        let host = thread::spawn({
+            let socket_path = socket_path.clone();
+            move || run_host(&socket_path)
+        });
+
+        let persistent = SessionCommand {
+            program: Some("/bin/sh".into()),
+            args: vec![
+                "-c".into(),
+                "echo ready; read line; echo \"got $line\"; read line; exit 3".into(),
+            ],
+        };
+        let (first, mut first_frames) = attach_to(&socket_path, "persistent", persistent.clone());
+        read_output_until(&mut first_frames, "ready");
+        // Detaching leaves the session running.
+        first.shutdown(Shutdown::Both).unwrap();
+
+        // Ending another session closes its client's connection, but keeps the host running.
+        let sleep = SessionCommand {
+            program: Some("/bin/sh".into()),
+            args: vec!["-c".into(), "sleep 60".into()],
+        };
+        let (mut other, mut other_frames) = attach_to(&socket_path, "ended", sleep);
+        client::input_frame(END_SESSION_SEQUENCE)
+            .write(&mut other)
+            .unwrap();
+        while let Some(frame) = Frame::read(&mut other_frames).unwrap() {
+            assert!(matches!(frame, Frame::Output(_)), "unexpected {frame:?}");
+        }
+
+        // Attaching again replays the session's output.
+        let (mut second, mut second_frames) = attach_to(&socket_path, "persistent", persistent);
+        match Frame::read(&mut second_frames).unwrap() {
+            Some(Frame::Output(replay)) => {
+                assert!(String::from_utf8_lossy(&replay).contains("ready"))
+            }
+            frame => panic!("expected the replayed output, got {frame:?}"),
+        }
+        Frame::Input(b"hello\n".to_vec())
+            .write(&mut second)
+            .unwrap();
+        read_output_until(&mut second_frames, "got hello");
+
+        // When the last session exits, its client gets the exit status and the host stops.
+        Frame::Input(b"bye\n".to_vec()).write(&mut second).unwrap();
+        assert_eq!(read_exit_status(&mut second_frames), 3);
+        host.join().unwrap().unwrap();
+        assert!(!socket_path.exists());
+    }
+
+    #[test]
+    fn test_host_stops_after_detached_sessions_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("sessions.sock");
+        let host = thread::spawn({
+            let socket_path = socket_path.clone();
+            move || host::run_host_with_timeout(&socket_path, Duration::from_millis(100))
+        });
+
+        let command = SessionCommand {
+            program: Some("/bin/sh".into()),
+            args: vec!["-c".into(), "echo ready; sleep 1; exit 3".into()],
+        };
+        let (client, mut frames) = attach_to(&socket_path, "detached", command);
+        read_output_until(&mut frames, "ready");
+        // The process exits after the client detached, so there's no one to report it to.
+        client.shutdown(Shutdown::Both).unwrap();
+
+        host.join().unwrap().unwrap();
+        assert!(!socket_path.exists());
+    }
+
+    fn attach_to(
+        socket_path: &Path,
+        session_id: &str,
+        command: SessionCommand,
+    ) -> (UnixStream, BufReader<UnixStream>) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut stream = loop {
+            match UnixStream::connect(socket_path) {
+                Ok(stream) => break stream,
+                Err(error) if Instant::now() > deadline => panic!("{error}"),
+                Err(_) => thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let request = AttachRequest {
+            session_id: session_id.into(),
+            command,
+            working_directory: None,
+            env: HashMap::default(),
+            rows: 24,
+            columns: 80,
+        };
+        serde_json::to_writer(&mut stream, &request).unwrap();
+        stream.write_all(b"\n").unwrap();
+        let frames = BufReader::new(stream.try_clone().unwrap());
+        (stream, frames)
+    }
+
+    fn read_output_until(frames: &mut BufReader<UnixStream>, needle: &str) {
+        let mut output = String::new();
+        while !output.contains(needle) {
+            match Frame::read(frames).unwrap() {
+                Some(Frame::Output(bytes)) => output.push_str(&String::from_utf8_lossy(&bytes)),
+                frame => panic!("expected {needle:?} in {output:?}, got {frame:?}"),
+            }
+        }
+    }
+
+    fn read_exit_status(frames: &mut BufReader<UnixStream>) -> i32 {
+        loop {
+            match Frame::read(frames).unwrap() {
+                Some(Frame::Output(_)) => {}
+                Some(Frame::Exit(status)) => return status,
+                frame => panic!("expected the exit status, got {frame:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    io::{self, BufReader, Read, Write},
+    os::{
+        fd::AsRawFd,
+        unix::{net::UnixStream, process::CommandExt as _},
+    },
+    path::Path,
+    process::{self, Command, Stdio},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+
+use super::{
+    AttachRequest, END_SESSION_SEQUENCE, Frame, SESSION_COMMAND_ENV, SessionCommand, socket_path,
+};
+
+/// How often the attach client checks whether its terminal was resized.
+const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Runs the attach client for `session_id`, on the terminal of the standard streams, until the
+/// session ends or the terminal goes away. If no session host is running, one is started with
+/// `host_command`.
+///
+/// Exits the process with the status of the session's process when the session ends.
+pub fn attach(session_id: &str, host_command: impl FnOnce(&Path) -> Command) -> Result<()> {
+    let socket_path = socket_path();
+    let mut stream = match UnixStream::connect(&socket_path) {
+        Ok(stream) => stream,
+        Err(_) => {
+            if let Some(parent) = socket_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut command = host_command(&socket_path);
+            // Leave the session of the terminal that started the host, so that it isn't hung up
+            // with it.
+            unsafe {
+                command.pre_exec(|| {
+                    libc::setsid();
+                    Ok(())
+                })
+            };
+            command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .context("starting the terminal session host")?;
+            connect_with_retries(&socket_path)?
+        }
+    };
+
+    let command = match std::env::var(SESSION_COMMAND_ENV) {
+        Ok(command) => serde_json::from_str(&command).context("parsing the session command")?,
+        Err(_) => SessionCommand::default(),
+    };
+    let (rows, columns) = window_size();
+    let request = AttachRequest {
+        session_id: session_id.to_string(),
+        command,
+        working_directory: std::env::current_dir().ok(),
+        env: std::env::vars()
+            .filter(|(name, _)| name != SESSION_COMMAND_ENV)
+            .collect(),
+        rows,
+        columns,
+    };
+    serde_json::to_writer(&mut stream, &request)?;
+    stream.write_all(b"\n")?;
+
+    let raw_mode = RawMode::enable();
+    let mut frames = BufReader::new(stream.try_clone()?);
+    thread::spawn(move || {
+        let mut stdout = io::stdout().lock();
+        // The host also closes the connection when another client attaches to the session.
+        let mut status = 0;
+        while let Ok(Some(frame)) = Frame::read(&mut frames) {
+            match frame {
+                Frame::Output(output) => {
+                    if stdout
+                        .write_all(&output)
+                        .and_then(|_| stdout.flush())
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Frame::Exit(exit_status) => {
+                    status = exit_status;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        drop(raw_mode);
+        process::exit(status);
+    });
+
+    // Input and resizes are sent from different threads, so their frames must not interleave.
+    let stream = Arc::new(Mutex::new(stream));
+    thread::spawn({
+        let stream = stream.clone();
+        move || {
+            let mut size = (rows, columns);
+            loop {
+                thread::sleep(RESIZE_POLL_INTERVAL);
+                let new_size = window_size();
+                if new_size != size {
+                    size = new_size;
+                    let (rows, columns) = size;
+                    let frame = Frame::Resize { rows, columns };
+                    if frame.write(&mut *stream.lock()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    let mut stdin = io::stdin().lock();
+    let mut buffer = [0; 8192];
+    loop {
+        let len = stdin.read(&mut buffer)?;
+        if len == 0 {
+            // The terminal was closed without ending the session, so leave it running.
+            return Ok(());
+        }
+        let frame = input_frame(&buffer[..len]);
+        frame.write(&mut *stream.lock())?;
+        if frame == Frame::End {
+            return Ok(());
+        }
+    }
+}
+
+/// Returns the frame to send for input read from the terminal.
+pub(super) fn input_frame(input: &[u8]) -> Frame {
+    if input
+        .windows(END_SESSION_SEQUENCE.len())
+        .any(|window| window == END_SESSION_SEQUENCE)
+    {
+        Frame::End
+    } else {
+        Frame::Input(input.to_vec())
+    }
+}
+
+fn connect_with_retries(socket_path: &Path) -> Result<UnixStream> {
+    let mut attempts = 0;
+    loop {
+        match UnixStream::connect(socket_path) {
+            Ok(stream) => return Ok(stream),
+            Err(error) if attempts >= 50 => {
+                return Err(error).context("connecting to the terminal session host");
+            }
+            Err(_) => {
+                attempts += 1;
+                thread::sleep(Duration::from_millis(20));
+            }
+        }
+    }
+}
+
+fn window_size() -> (u16, u16) {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(io::stdin().as_raw_fd(), libc::TIOCGWINSZ, &mut size) } == 0
+        && size.ws_row > 0
+        && size.ws_col > 0
+    {
+        (size.ws_row, size.ws_col)
+    } else {
+        (24, 80)
+    }
+}
+
+/// Passes all input through to the session untouched while alive, including the keys that the
+/// terminal would otherwise turn into signals or edit itself.
+struct RawMode(Option<libc::termios>);
+
+impl RawMode {
+    fn enable() -> Self {
+        let fd = io::stdin().as_raw_fd();
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+            return Self(None);
+        }
+        let mut raw = original;
+        unsafe {
+            libc::cfmakeraw(&mut raw);
+            libc::tcsetattr(fd, libc::TCSANOW, &raw);
+        }
+        Self(Some(original))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(original) = &self.0 {
+            unsafe { libc::tcsetattr(io::stdin().as_raw_fd(), libc::TCSANOW, original) };
+        }
+    }
+}
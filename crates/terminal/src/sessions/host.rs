@@ -0,0 +1,321 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{BufRead, BufReader, Read, Write},
+    os::{
+        fd::AsRawFd,
+        unix::net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    tty::{self, Pty},
+};
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+
+use super::{AttachRequest, Frame};
+
+/// How much of a session's most recent output is replayed when attaching to it.
+pub(super) const REPLAY_LIMIT: usize = 1024 * 1024;
+/// How long a session whose process exited while no client was attached waits for one to report
+/// the exit to, before it ends anyway.
+const EXITED_SESSION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Runs the session host on `socket_path` until its last session ends. Returns immediately if
+/// another host already serves that socket.
+pub fn run_host(socket_path: &Path) -> Result<()> {
+    run_host_with_timeout(socket_path, EXITED_SESSION_TIMEOUT)
+}
+
+pub(super) fn run_host_with_timeout(
+    socket_path: &Path,
+    exited_session_timeout: Duration,
+) -> Result<()> {
+    if UnixStream::connect(socket_path).is_ok() {
+        return Ok(());
+    }
+    std::fs::remove_file(socket_path).ok();
+    let listener = UnixListener::bind(socket_path)
+        .with_context(|| format!("binding {}", socket_path.display()))?;
+
+    let host = Arc::new(Host {
+        socket_path: socket_path.to_path_buf(),
+        state: Mutex::default(),
+        next_client_id: AtomicUsize::new(0),
+        exited_session_timeout,
+    });
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        {
+            let mut state = host.state.lock();
+            if state.shutting_down {
+                // The last session ended. Stop serving the socket before releasing the lock, so
+                // that new clients start a new host rather than attach to this one.
+                std::fs::remove_file(socket_path).ok();
+                break;
+            }
+            state.attaching += 1;
+        }
+        let host = host.clone();
+        thread::spawn(move || {
+            if let Err(error) = host.serve(stream) {
+                log::error!("terminal session client failed: {error:#}");
+            }
+        });
+    }
+    Ok(())
+}
+
+struct Host {
+    socket_path: PathBuf,
+    state: Mutex<HostState>,
+    next_client_id: AtomicUsize,
+    exited_session_timeout: Duration,
+}
+
+#[derive(Default)]
+struct HostState {
+    sessions: HashMap<String, Arc<Session>>,
+    /// The number of clients that connected but whose session isn't running yet. The host keeps
+    /// running while there are any, even if it has no sessions.
+    attaching: usize,
+    shutting_down: bool,
+}
+
+struct Session {
+    pty: Mutex<Pty>,
+    writer: Mutex<File>,
+    output: Mutex<SessionOutput>,
+}
+
+#[derive(Default)]
+struct SessionOutput {
+    replay: VecDeque<u8>,
+    client: Option<(usize, UnixStream)>,
+    /// The exit status of the session's process, once it exited.
+    exit_status: Option<i32>,
+}
+
+impl Host {
+    fn serve(self: &Arc<Self>, stream: UnixStream) -> Result<()> {
+        let attaching = Attaching(self);
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        let request: AttachRequest = serde_json::from_str(&request)?;
+        let client_id = self.next_client_id.fetch_add(1, Ordering::SeqCst);
+
+        let existing = self.state.lock().sessions.get(&request.session_id).cloned();
+        let session = match existing {
+            Some(session) => session,
+            None => self.spawn_session(&request)?,
+        };
+        drop(attaching);
+        session.resize(request.rows, request.columns);
+
+        {
+            let mut output = session.output.lock();
+            if let Some((_, previous_client)) = output.client.take() {
+                previous_client.shutdown(std::net::Shutdown::Both).ok();
+            }
+            let mut client = stream.try_clone()?;
+            Frame::Output(output.replay.iter().copied().collect()).write(&mut client)?;
+            if let Some(exit_status) = output.exit_status {
+                // The session ended while detached, so this was the last of its output.
+                drop(output);
+                Frame::Exit(exit_status).write(&mut client).ok();
+                self.remove_exited_session(&request.session_id, &session);
+                return Ok(());
+            }
+            output.client = Some((client_id, client));
+        }
+
+        loop {
+            match Frame::read(&mut reader) {
+                Ok(Some(Frame::Input(input))) => {
+                    session.writer.lock().write_all(&input).ok();
+                }
+                Ok(Some(Frame::Resize { rows, columns })) => session.resize(rows, columns),
+                Ok(Some(Frame::End)) => {
+                    self.end_session(&request.session_id);
+                    break;
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        let mut output = session.output.lock();
+        if output
+            .client
+            .as_ref()
+            .is_some_and(|(id, _)| *id == client_id)
+        {
+            output.client = None;
+        }
+        Ok(())
+    }
+
+    fn spawn_session(self: &Arc<Self>, request: &AttachRequest) -> Result<Arc<Session>> {
+        let options = tty::Options {
+            shell: request
+                .command
+                .program
+                .clone()
+                .map(|program| tty::Shell::new(program, request.command.args.clone())),
+            working_directory: request.working_directory.clone(),
+            drain_on_exit: true,
+            env: request.env.clone(),
+        };
+        let pty = tty::new(&options, window_size_of(request.rows, request.columns), 0)
+            .context("spawning the session's process")?;
+        let mut reader = pty.file().try_clone()?;
+        set_blocking(&reader);
+        let writer = pty.file().try_clone()?;
+        let session = Arc::new(Session {
+            pty: Mutex::new(pty),
+            writer: Mutex::new(writer),
+            output: Mutex::default(),
+        });
+        self.state
+            .lock()
+            .sessions
+            .insert(request.session_id.clone(), session.clone());
+
+        let host = self.clone();
+        let session_id = request.session_id.clone();
+        let output_session = session.clone();
+        thread::spawn(move || {
+            let mut buffer = [0; 8192];
+            loop {
+                let len = match reader.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(len) => len,
+                };
+                output_session.push_output(&buffer[..len]);
+            }
+
+            let exit_status = wait_for_exit(output_session.pty.lock().child().id());
+            let mut output = output_session.output.lock();
+            output.exit_status = Some(exit_status);
+            if let Some((_, mut client)) = output.client.take() {
+                drop(output);
+                Frame::Exit(exit_status).write(&mut client).ok();
+            } else {
+                // Keep the exit status for the next client, but not forever, so that the host
+                // stops once all of its sessions are gone.
+                drop(output);
+                thread::sleep(host.exited_session_timeout);
+            }
+            host.remove_exited_session(&session_id, &output_session);
+        });
+        Ok(session)
+    }
+
+    fn end_session(&self, session_id: &str) {
+        let mut state = self.state.lock();
+        if let Some(session) = state.sessions.remove(session_id) {
+            // The session's process leads its own process group, which is hung up as a whole.
+            let pid = session.pty.lock().child().id() as libc::pid_t;
+            unsafe { libc::kill(-pid, libc::SIGHUP) };
+        }
+        self.shut_down_if_idle(&mut state);
+    }
+
+    /// Removes a session whose process exited, unless it was removed already and its id reused.
+    fn remove_exited_session(&self, session_id: &str, session: &Arc<Session>) {
+        let mut state = self.state.lock();
+        if state
+            .sessions
+            .get(session_id)
+            .is_some_and(|existing| Arc::ptr_eq(existing, session))
+        {
+            state.sessions.remove(session_id);
+        }
+        self.shut_down_if_idle(&mut state);
+    }
+
+    fn finish_attach(&self) {
+        let mut state = self.state.lock();
+        state.attaching = state.attaching.saturating_sub(1);
+        self.shut_down_if_idle(&mut state);
+    }
+
+    fn shut_down_if_idle(&self, state: &mut HostState) {
+        if state.sessions.is_empty() && state.attaching == 0 && !state.shutting_down {
+            state.shutting_down = true;
+            // Wake up the accept loop, so that it notices.
+            UnixStream::connect(&self.socket_path).ok();
+        }
+    }
+}
+
+/// Counts a client as attaching until its session is running, or attaching to it failed.
+struct Attaching<'a>(&'a Host);
+
+impl Drop for Attaching<'_> {
+    fn drop(&mut self) {
+        self.0.finish_attach();
+    }
+}
+
+impl Session {
+    fn push_output(&self, bytes: &[u8]) {
+        let mut output = self.output.lock();
+        output.replay.extend(bytes);
+        let excess = output.replay.len().saturating_sub(REPLAY_LIMIT);
+        output.replay.drain(..excess);
+        if let Some((_, client)) = &mut output.client
+            && Frame::Output(bytes.to_vec()).write(client).is_err()
+        {
+            output.client = None;
+        }
+    }
+
+    fn resize(&self, rows: u16, columns: u16) {
+        self.pty.lock().on_resize(window_size_of(rows, columns));
+    }
+}
+
+/// Reaps the session's process, returning its exit status the way shells report it.
+fn wait_for_exit(pid: u32) -> i32 {
+    let mut status = 0;
+    if unsafe { libc::waitpid(pid as libc::pid_t, &mut status, 0) } < 0 {
+        return 0;
+    }
+    if libc::WIFEXITED(status) {
+        libc::WEXITSTATUS(status)
+    } else if libc::WIFSIGNALED(status) {
+        128 + libc::WTERMSIG(status)
+    } else {
+        0
+    }
+}
+
+fn window_size_of(rows: u16, columns: u16) -> WindowSize {
+    WindowSize {
+        num_lines: rows,
+        num_cols: columns,
+        cell_width: 1,
+        cell_height: 1,
+    }
+}
+
+/// The PTY is non-blocking for alacritty's event loop, while the host reads it on its own thread.
+fn set_blocking(file: &File) {
+    let fd = file.as_raw_fd();
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK);
+    }
+}
@@ -0,0 +1,445 @@
+//! Shell integration based on the `OSC 133` semantic prompt sequences.
+//!
+//! Alacritty parses the PTY output on its own I/O thread and ignores `OSC 133`, so the
+//! sequences are rewritten before they reach it. Each prompt and command line becomes an
+//! `OSC 8` hyperlink with a `zed-shell-integration:` URI, which alacritty stores on the grid
+//! cells it covers. The marks therefore scroll, reflow and get trimmed from the scrollback
+//! together with the text they belong to. Exit statuses reported by `OSC 133;D` don't belong
+//! to any cell and are kept in [`CommandHistory`] instead.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    grid::{Dimensions, Grid},
+    index::{Column, Line, Point as AlacPoint},
+    term::cell::Cell,
+};
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use parking_lot::Mutex;
+use task::Shell;
+
+use crate::{
+    IndexedCell,
+    pty_filter::{PtyOutputFilter, write_hyperlink},
+};
+
+const URI_SCHEME: &str = "zed-shell-integration:";
+const PROMPT_URI_PREFIX: &str = "zed-shell-integration:prompt/";
+const COMMAND_URI_PREFIX: &str = "zed-shell-integration:command/";
+
+/// The prefix shared by every `OSC 133` sequence.
+const SEQUENCE_PREFIX: &[u8] = b"\x1b]133;";
+/// Sequences with longer parameters than this are passed through untouched.
+const MAX_PARAMS_LEN: usize = 256;
+/// Statuses of commands older than this many prompts are forgotten.
+const MAX_TRACKED_COMMANDS: usize = 10_000;
+
+const BASH_SCRIPT: &str = include_str!("shell_integration/zed.bash");
+const ZSH_SCRIPT: &str = include_str!("shell_integration/zed.zsh");
+const FISH_SCRIPT: &str = include_str!("shell_integration/zed.fish");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    Running,
+    Finished { exit_code: Option<i32> },
+}
+
+impl CommandStatus {
+    pub fn succeeded(&self) -> Option<bool> {
+        match self {
+            CommandStatus::Running => None,
+            CommandStatus::Finished { exit_code } => Some(exit_code.is_none_or(|code| code == 0)),
+        }
+    }
+}
+
+/// The kind of text covered by a shell integration hyperlink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MarkKind {
+    Prompt,
+    Command,
+}
+
+/// Returns whether a hyperlink URI marks up a prompt or command.
+pub(crate) fn is_shell_integration_hyperlink(uri: &str) -> bool {
+    uri.starts_with(URI_SCHEME)
+}
+
+/// Parses a shell integration hyperlink URI into its kind and prompt id.
+pub(crate) fn parse_hyperlink(uri: &str) -> Option<(MarkKind, usize)> {
+    if let Some(id) = uri.strip_prefix(PROMPT_URI_PREFIX) {
+        Some((MarkKind::Prompt, id.parse().ok()?))
+    } else if let Some(id) = uri.strip_prefix(COMMAND_URI_PREFIX) {
+        Some((MarkKind::Command, id.parse().ok()?))
+    } else {
+        None
+    }
+}
+
+/// A prompt that is visible in the terminal, with the status of the command run from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PromptMark {
+    pub point: AlacPoint,
+    pub status: CommandStatus,
+}
+
+/// A prompt in the terminal's grid and the command typed at it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct CommandBlock {
+    pub prompt_id: usize,
+    pub prompt_start: AlacPoint,
+    /// The last cell of the command, or of the prompt if no command was typed.
+    pub input_end: AlacPoint,
+}
+
+/// Returns the prompts that are still in the grid's scrollback, from oldest to newest.
+pub(crate) fn command_blocks(grid: &Grid<Cell>) -> Vec<CommandBlock> {
+    let mut blocks = Vec::<CommandBlock>::new();
+    for line in grid.topmost_line().0..=grid.bottommost_line().0 {
+        let row = &grid[Line(line)];
+        for column in 0..grid.columns() {
+            let Some(link) = row[Column(column)].hyperlink() else {
+                continue;
+            };
+            let Some((kind, prompt_id)) = parse_hyperlink(link.uri()) else {
+                continue;
+            };
+            let point = AlacPoint::new(Line(line), Column(column));
+            match blocks.last_mut() {
+                Some(block) if block.prompt_id == prompt_id => block.input_end = point,
+                _ if kind == MarkKind::Prompt => blocks.push(CommandBlock {
+                    prompt_id,
+                    prompt_start: point,
+                    input_end: point,
+                }),
+                _ => {}
+            }
+        }
+    }
+    blocks
+}
+
+/// Returns a mark for each prompt among `cells` whose command has been run.
+pub(crate) fn prompt_marks(cells: &[IndexedCell], history: &CommandHistory) -> Vec<PromptMark> {
+    let mut marks = Vec::new();
+    let mut last_prompt_id = None;
+    for cell in cells {
+        let Some((MarkKind::Prompt, prompt_id)) = cell
+            .hyperlink()
+            .and_then(|link| parse_hyperlink(link.uri()))
+        else {
+            continue;
+        };
+        if last_prompt_id == Some(prompt_id) {
+            continue;
+        }
+        last_prompt_id = Some(prompt_id);
+        if let Some(status) = history.status(prompt_id) {
+            marks.push(PromptMark {
+                point: cell.point,
+                status,
+            });
+        }
+    }
+    marks
+}
+
+/// The statuses of the commands run from each prompt, keyed by prompt id.
+#[derive(Debug, Default)]
+pub struct CommandHistory {
+    statuses: HashMap<usize, CommandStatus>,
+}
+
+impl CommandHistory {
+    pub fn status(&self, prompt_id: usize) -> Option<CommandStatus> {
+        self.statuses.get(&prompt_id).copied()
+    }
+
+    fn set_status(&mut self, prompt_id: usize, status: CommandStatus) {
+        self.statuses.insert(prompt_id, status);
+        if prompt_id >= MAX_TRACKED_COMMANDS {
+            let oldest = prompt_id - MAX_TRACKED_COMMANDS;
+            self.statuses.retain(|id, _| *id > oldest);
+        }
+    }
+}
+
+/// Rewrites `OSC 133` sequences in a stream of PTY output. Sequences may be split across
+/// reads, so bytes that could still turn out to be one are held back until it's complete.
+pub(crate) struct SemanticPromptFilter {
+    /// Bytes of a sequence that has started but hasn't been terminated yet.
+    pending: Vec<u8>,
+    next_prompt_id: usize,
+    current_prompt_id: Option<usize>,
+    history: Arc<Mutex<CommandHistory>>,
+}
+
+impl SemanticPromptFilter {
+    pub fn new(history: Arc<Mutex<CommandHistory>>) -> Self {
+        Self {
+            pending: Vec::new(),
+            next_prompt_id: 0,
+            current_prompt_id: None,
+            history,
+        }
+    }
+
+    fn push(&mut self, byte: u8, output: &mut Vec<u8>) {
+        if self.pending.len() < SEQUENCE_PREFIX.len() {
+            if byte == SEQUENCE_PREFIX[self.pending.len()] {
+                self.pending.push(byte);
+            } else {
+                output.append(&mut self.pending);
+                if byte == SEQUENCE_PREFIX[0] {
+                    self.pending.push(byte);
+                } else {
+                    output.push(byte);
+                }
+            }
+            return;
+        }
+
+        let params = &self.pending[SEQUENCE_PREFIX.len()..];
+        if byte == b'\x07' {
+            let params = params.to_vec();
+            self.pending.clear();
+            self.handle_sequence(&params, output);
+        } else if byte == b'\\' && params.last() == Some(&b'\x1b') {
+            let params = params[..params.len() - 1].to_vec();
+            self.pending.clear();
+            self.handle_sequence(&params, output);
+        } else if params.len() >= MAX_PARAMS_LEN {
+            output.append(&mut self.pending);
+            output.push(byte);
+        } else {
+            self.pending.push(byte);
+        }
+    }
+
+    fn handle_sequence(&mut self, params: &[u8], output: &mut Vec<u8>) {
+        let mut params = params.split(|byte| *byte == b';');
+        let kind = params.next().unwrap_or_default();
+        match kind {
+            b"A" => {
+                let prompt_id = self.next_prompt_id;
+                self.next_prompt_id += 1;
+                self.current_prompt_id = Some(prompt_id);
+                write_hyperlink(output, Some(&format!("{PROMPT_URI_PREFIX}{prompt_id}")));
+            }
+            b"B" => {
+                if let Some(prompt_id) = self.current_prompt_id {
+                    write_hyperlink(output, Some(&format!("{COMMAND_URI_PREFIX}{prompt_id}")));
+                }
+            }
+            b"C" => {
+                write_hyperlink(output, None);
+                if let Some(prompt_id) = self.current_prompt_id {
+                    self.history
+                        .lock()
+                        .set_status(prompt_id, CommandStatus::Running);
+                }
+            }
+            b"D" => {
+                write_hyperlink(output, None);
+                if let Some(prompt_id) = self.current_prompt_id.take() {
+                    let exit_code = params
+                        .next()
+                        .and_then(|code| std::str::from_utf8(code).ok()?.parse().ok());
+                    self.history
+                        .lock()
+                        .set_status(prompt_id, CommandStatus::Finished { exit_code });
+                }
+            }
+            // Other marks, such as continuation prompts, carry nothing we use.
+            _ => {}
+        }
+    }
+}
+
+impl PtyOutputFilter for SemanticPromptFilter {
+    fn filter(&mut self, input: &[u8], output: &mut Vec<u8>, _reply: &mut Vec<u8>) {
+        for &byte in input {
+            self.push(byte, output);
+        }
+    }
+
+    fn flush(&mut self, output: &mut Vec<u8>) {
+        output.append(&mut self.pending);
+    }
+}
+
+/// Prepares `env` so that the shell loads Zed's integration script, returning the shell to
+/// spawn instead of `shell` when that requires different arguments.
+///
+/// Returns `Ok(None)` without touching anything when the shell isn't supported or was given
+/// arguments that might conflict with the integration.
+pub(crate) fn inject(shell: &Shell, env: &mut HashMap<String, String>) -> Result<Option<Shell>> {
+    if cfg!(windows) {
+        return Ok(None);
+    }
+
+    let (program, args) = match shell {
+        Shell::System => (util::shell::get_system_shell(), Vec::new()),
+        Shell::Program(program) => (program.clone(), Vec::new()),
+        Shell::WithArguments { program, args, .. } => (program.clone(), args.clone()),
+    };
+    let login = args.iter().any(|arg| arg == "-l" || arg == "--login")
+        || (cfg!(target_os = "macos") && matches!(shell, Shell::System));
+    if args
+        .iter()
+        .any(|arg| !matches!(arg.as_str(), "-l" | "--login" | "-i"))
+    {
+        return Ok(None);
+    }
+
+    let name = Path::new(&program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let dir = paths::temp_dir().join("shell_integration");
+    match name {
+        "bash" => {
+            let script = write_script(&dir.join("bash"), "zed.bash", BASH_SCRIPT)?;
+            if login {
+                env.insert("ZED_SHELL_INTEGRATION_LOGIN".into(), "1".into());
+            }
+            Ok(Some(Shell::WithArguments {
+                program,
+                args: vec!["--init-file".into(), script.to_string_lossy().into_owned()],
+                title_override: None,
+            }))
+        }
+        "zsh" => {
+            let zdotdir = dir.join("zsh");
+            write_script(&zdotdir, ".zshenv", ZSH_SCRIPT)?;
+            if let Some(user_zdotdir) = env
+                .get("ZDOTDIR")
+                .cloned()
+                .or_else(|| std::env::var("ZDOTDIR").ok())
+            {
+                env.insert("ZED_USER_ZDOTDIR".into(), user_zdotdir);
+            }
+            env.insert("ZDOTDIR".into(), zdotdir.to_string_lossy().into_owned());
+            Ok(None)
+        }
+        "fish" => {
+            let data_dir = dir.join("fish");
+            write_script(
+                &data_dir.join("fish/vendor_conf.d"),
+                "zed.fish",
+                FISH_SCRIPT,
+            )?;
+            let user_data_dirs = env
+                .get("XDG_DATA_DIRS")
+                .cloned()
+                .or_else(|| std::env::var("XDG_DATA_DIRS").ok());
+            let data_dirs = match &user_data_dirs {
+                Some(user_data_dirs) => format!("{}:{user_data_dirs}", data_dir.display()),
+                None => data_dir.to_string_lossy().into_owned(),
+            };
+            env.insert(
+                "ZED_USER_XDG_DATA_DIRS".into(),
+                user_data_dirs.unwrap_or_default(),
+            );
+            env.insert("XDG_DATA_DIRS".into(), data_dirs);
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn write_script(dir: &Path, file_name: &str, contents: &str) -> Result<PathBuf> {
+    let path = dir.join(file_name);
+    if std::fs::read_to_string(&path).is_ok_and(|existing| existing == contents) {
+        return Ok(path);
+    }
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("creating shell integration directory {dir:?}"))?;
+    let mut file = std::fs::File::create(&path)
+        .with_context(|| format!("creating shell integration script {path:?}"))?;
+    file.write_all(contents.as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter_chunks(chunks: &[&[u8]]) -> (String, Arc<Mutex<CommandHistory>>) {
+        let history = Arc::new(Mutex::new(CommandHistory::default()));
+        let mut filter = SemanticPromptFilter::new(history.clone());
+        let mut output = Vec::new();
+        for chunk in chunks {
+            filter.filter(chunk, &mut output, &mut Vec::new());
+        }
+        let output = String::from_utf8(output).unwrap();
+        (output, history)
+    }
+
+    #[test]
+    fn test_semantic_prompt_filter() {
+        let (output, history) = filter_chunks(&[
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07",
+            b"Cargo.toml\r\n\x1b]133;D;0\x07",
+            b"\x1b]133;A\x1b\\$ \x1b]133;B\x1b\\false\r\n\x1b]133;C\x1b\\\x1b]133;D;1\x1b\\",
+            b"\x1b]133;A\x07$ \x1b]133;B\x07sleep 5\r\n\x1b]133;C\x07",
+        ]);
+        assert_eq!(
+            output,
+            concat!(
+                "\x1b]8;;zed-shell-integration:prompt/0\x1b\\$ ",
+                "\x1b]8;;zed-shell-integration:command/0\x1b\\ls\r\n\x1b]8;;\x1b\\",
+                "Cargo.toml\r\n\x1b]8;;\x1b\\",
+                "\x1b]8;;zed-shell-integration:prompt/1\x1b\\$ ",
+                "\x1b]8;;zed-shell-integration:command/1\x1b\\false\r\n",
+                "\x1b]8;;\x1b\\\x1b]8;;\x1b\\",
+                "\x1b]8;;zed-shell-integration:prompt/2\x1b\\$ ",
+                "\x1b]8;;zed-shell-integration:command/2\x1b\\sleep 5\r\n\x1b]8;;\x1b\\",
+            )
+        );
+
+        let history = history.lock();
+        assert_eq!(
+            history.status(0),
+            Some(CommandStatus::Finished { exit_code: Some(0) })
+        );
+        assert_eq!(
+            history.status(1),
+            Some(CommandStatus::Finished { exit_code: Some(1) })
+        );
+        assert_eq!(history.status(2), Some(CommandStatus::Running));
+        assert_eq!(history.status(3), None);
+    }
+
+    #[test]
+    fn test_semantic_prompt_filter_split_sequences() {
+        let (output, _) = filter_chunks(&[b"a\x1b", b"]13", b"3;A", b"\x07b\x1b]0;title\x07"]);
+        assert_eq!(
+            output,
+            "a\x1b]8;;zed-shell-integration:prompt/0\x1b\\b\x1b]0;title\x07"
+        );
+
+        let (output, _) = filter_chunks(&[b"\x1b\x1b]133;P;k=i\x07\x1b[0m"]);
+        assert_eq!(output, "\x1b\x1b[0m");
+    }
+
+    #[test]
+    fn test_parse_hyperlink() {
+        assert_eq!(
+            parse_hyperlink("zed-shell-integration:prompt/12"),
+            Some((MarkKind::Prompt, 12))
+        );
+        assert_eq!(
+            parse_hyperlink("zed-shell-integration:command/3"),
+            Some((MarkKind::Command, 3))
+        );
+        assert_eq!(parse_hyperlink("zed-shell-integration:prompt/"), None);
+        assert_eq!(parse_hyperlink("https://zed.dev"), None);
+        assert!(!is_shell_integration_hyperlink("https://zed.dev"));
+    }
+}
@@ -0,0 +1,47 @@
+# Zed shell integration for bash, loaded with `--init-file` in place of ~/.bashrc.
+
+if [[ -n "$ZED_SHELL_INTEGRATION_LOGIN" ]]; then
+    unset ZED_SHELL_INTEGRATION_LOGIN
+    [[ -r /etc/profile ]] && builtin source /etc/profile
+    for __zed_file in ~/.bash_profile ~/.bash_login ~/.profile; do
+        if [[ -r "$__zed_file" ]]; then
+            builtin source "$__zed_file"
+            break
+        fi
+    done
+    unset __zed_file
+else
+    [[ -r /etc/bash.bashrc ]] && builtin source /etc/bash.bashrc
+    [[ -r ~/.bashrc ]] && builtin source ~/.bashrc
+fi
+
+if [[ -n "$__zed_shell_integration" || $- != *i* ]]; then
+    builtin return
+fi
+__zed_shell_integration=1
+__zed_command_running=
+
+__zed_precmd() {
+    local status=$?
+    if [[ -n "$__zed_command_running" ]]; then
+        builtin printf '\e]133;D;%s\a' "$status"
+        __zed_command_running=
+    fi
+    builtin return $status
+}
+
+__zed_update_prompt() {
+    local status=$?
+    if [[ "$PS1" != *'\e]133;A\a'* ]]; then
+        PS1='\[\e]133;A\a\]'"$PS1"'\[\e]133;B\a\]'
+    fi
+    # PS0 is expanded once a command has been read and before it runs. Evaluating the
+    # subscript of an unset array marks the command as running without printing anything.
+    if [[ "$PS0" != *'\e]133;C\a'* ]]; then
+        PS0='${__zed_unset[__zed_command_running=1]}\e]133;C\a'"$PS0"
+    fi
+    builtin return $status
+}
+
+# Newlines rather than semicolons separate the commands, as PROMPT_COMMAND may end with one.
+PROMPT_COMMAND="__zed_precmd"$'\n'"$PROMPT_COMMAND"$'\n'"__zed_update_prompt"
@@ -0,0 +1,40 @@
+# Zed shell integration for fish, loaded from vendor_conf.d by prepending its directory to
+# XDG_DATA_DIRS.
+
+# Restore the user's XDG_DATA_DIRS so that programs started from fish don't see ours.
+if set -q ZED_USER_XDG_DATA_DIRS
+    if test -n "$ZED_USER_XDG_DATA_DIRS"
+        set -gx XDG_DATA_DIRS (string split : -- $ZED_USER_XDG_DATA_DIRS)
+    else
+        set -e XDG_DATA_DIRS
+    end
+    set -e ZED_USER_XDG_DATA_DIRS
+end
+
+if not status is-interactive; or set -q __zed_shell_integration
+    exit
+end
+set -g __zed_shell_integration 1
+
+function __zed_prompt_start --on-event fish_prompt
+    printf '\e]133;A\a'
+end
+
+function __zed_preexec --on-event fish_preexec
+    printf '\e]133;C\a'
+end
+
+function __zed_postexec --on-event fish_postexec
+    printf '\e]133;D;%s\a' $status
+end
+
+# Mark the end of the prompt once the user's config has defined it.
+function __zed_wrap_prompt --on-event fish_prompt
+    functions -e __zed_wrap_prompt
+    functions -q fish_prompt; or return
+    functions -c fish_prompt __zed_user_fish_prompt
+    function fish_prompt
+        __zed_user_fish_prompt
+        printf '\e]133;B\a'
+    end
+end
@@ -0,0 +1,287 @@
+//! A decoder for DEC Sixel graphics.
+
+use anyhow::{Result, anyhow, bail};
+use image::RgbaImage;
+
+/// The colors of the VT340, which are the initial color registers, in percent.
+const DEFAULT_PALETTE: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [20, 20, 80],
+    [80, 13, 13],
+    [20, 80, 20],
+    [80, 20, 80],
+    [20, 80, 80],
+    [80, 80, 20],
+    [53, 53, 53],
+    [26, 26, 26],
+    [33, 33, 60],
+    [60, 26, 26],
+    [33, 60, 33],
+    [60, 33, 60],
+    [33, 60, 60],
+    [60, 60, 33],
+    [80, 80, 80],
+];
+
+/// Decodes the data of a Sixel `DCS` sequence, from after its `q` up to its terminator.
+///
+/// Pixels that no sixel sets are left transparent, whatever the sequence's background mode.
+pub(crate) fn decode(data: &[u8], max_dimension: u32) -> Result<RgbaImage> {
+    let mut palette = [[0, 0, 0, 255]; 256];
+    for (register, color) in palette.iter_mut().zip(DEFAULT_PALETTE) {
+        *register = percent_to_rgba(color[0], color[1], color[2]);
+    }
+
+    let mut canvas = Canvas {
+        max_dimension,
+        ..Canvas::default()
+    };
+    let mut color = palette[0];
+    let mut x = 0;
+    let mut y = 0;
+    let mut ix = 0;
+    while ix < data.len() {
+        let byte = data[ix];
+        ix += 1;
+        match byte {
+            b'"' => {
+                let params = parse_params(data, &mut ix);
+                if let [_, _, width, height] = params[..] {
+                    canvas.declare_size(width.min(max_dimension), height.min(max_dimension));
+                }
+            }
+            b'#' => {
+                let params = parse_params(data, &mut ix);
+                let Some(&register) = params.first() else {
+                    continue;
+                };
+                let register = register as usize % palette.len();
+                match params[1..] {
+                    [1, hue, lightness, saturation] => {
+                        palette[register] = hls_to_rgba(hue, lightness, saturation);
+                    }
+                    [2, red, green, blue] => {
+                        palette[register] = percent_to_rgba(
+                            red.min(100) as u8,
+                            green.min(100) as u8,
+                            blue.min(100) as u8,
+                        );
+                    }
+                    _ => {}
+                }
+                color = palette[register];
+            }
+            b'!' => {
+                let count = parse_params(data, &mut ix).first().copied().unwrap_or(1);
+                if let Some(&sixel @ 0x3f..=0x7e) = data.get(ix) {
+                    ix += 1;
+                    let count = count.max(1);
+                    if x + count > max_dimension {
+                        bail!("sixel image is wider than {max_dimension} pixels");
+                    }
+                    canvas.paint(x, y, count, sixel - 0x3f, color);
+                    x += count;
+                }
+            }
+            b'$' => x = 0,
+            b'-' => {
+                x = 0;
+                y += 6;
+                if y >= max_dimension {
+                    bail!("sixel image is taller than {max_dimension} pixels");
+                }
+            }
+            0x3f..=0x7e => {
+                if x >= max_dimension {
+                    bail!("sixel image is wider than {max_dimension} pixels");
+                }
+                canvas.paint(x, y, 1, byte - 0x3f, color);
+                x += 1;
+            }
+            _ => {}
+        }
+    }
+
+    canvas.into_image()
+}
+
+/// Parses the `;` separated numbers at `ix`, leaving `ix` after them.
+fn parse_params(data: &[u8], ix: &mut usize) -> Vec<u32> {
+    let mut params = Vec::new();
+    let mut current = None::<u32>;
+    while let Some(&byte) = data.get(*ix) {
+        match byte {
+            b'0'..=b'9' => {
+                let digit = (byte - b'0') as u32;
+                current = Some(
+                    current
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(digit),
+                );
+            }
+            b';' => params.push(current.take().unwrap_or(0)),
+            _ => break,
+        }
+        *ix += 1;
+    }
+    if let Some(current) = current {
+        params.push(current);
+    }
+    params
+}
+
+fn percent_to_rgba(red: u8, green: u8, blue: u8) -> [u8; 4] {
+    let scale = |percent: u8| ((percent as u32 * 255 + 50) / 100) as u8;
+    [scale(red), scale(green), scale(blue), 255]
+}
+
+/// Converts a DEC HLS color, whose hue angle starts at blue rather than red, to RGBA.
+fn hls_to_rgba(hue: u32, lightness: u32, saturation: u32) -> [u8; 4] {
+    let hue = ((hue + 240) % 360) as f32 / 360.;
+    let lightness = lightness.min(100) as f32 / 100.;
+    let saturation = saturation.min(100) as f32 / 100.;
+    if saturation == 0. {
+        let value = (lightness * 255.).round() as u8;
+        return [value, value, value, 255];
+    }
+
+    let q = if lightness < 0.5 {
+        lightness * (1. + saturation)
+    } else {
+        lightness + saturation - lightness * saturation
+    };
+    let p = 2. * lightness - q;
+    let channel = |t: f32| {
+        let t = t.rem_euclid(1.);
+        let value = if t < 1. / 6. {
+            p + (q - p) * 6. * t
+        } else if t < 0.5 {
+            q
+        } else if t < 2. / 3. {
+            p + (q - p) * (2. / 3. - t) * 6.
+        } else {
+            p
+        };
+        (value * 255.).round() as u8
+    };
+    [
+        channel(hue + 1. / 3.),
+        channel(hue),
+        channel(hue - 1. / 3.),
+        255,
+    ]
+}
+
+/// The pixels painted so far, growing as sixels are painted outside of it.
+#[derive(Default)]
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+    /// The extent of the painted pixels, which is the image's size unless the sequence's raster
+    /// attributes declared a larger one.
+    painted_width: u32,
+    painted_height: u32,
+    max_dimension: u32,
+}
+
+impl Canvas {
+    fn reserve(&mut self, width: u32, height: u32) {
+        if width <= self.width && height <= self.height {
+            return;
+        }
+        let new_width = width.max(self.width);
+        let new_height = height.max(self.height);
+        let mut pixels = vec![[0; 4]; new_width as usize * new_height as usize];
+        for row in 0..self.height as usize {
+            let old_row = row * self.width as usize..(row + 1) * self.width as usize;
+            let new_start = row * new_width as usize;
+            pixels[new_start..new_start + self.width as usize]
+                .copy_from_slice(&self.pixels[old_row]);
+        }
+        self.pixels = pixels;
+        self.width = new_width;
+        self.height = new_height;
+    }
+
+    fn declare_size(&mut self, width: u32, height: u32) {
+        self.reserve(width, height);
+        self.painted_width = self.painted_width.max(width);
+        self.painted_height = self.painted_height.max(height);
+    }
+
+    /// Paints `count` columns of the six pixel tall `sixel` starting at `x` and `y`.
+    fn paint(&mut self, x: u32, y: u32, count: u32, sixel: u8, color: [u8; 4]) {
+        if sixel == 0 {
+            return;
+        }
+        let right = x + count;
+        let bottom = y + 8 - sixel.leading_zeros();
+        // Grow geometrically, as images without raster attributes are painted a sixel at a time.
+        if right > self.width || bottom > self.height {
+            let width = if right > self.width {
+                right.max((self.width * 2).min(self.max_dimension))
+            } else {
+                self.width
+            };
+            let height = if bottom > self.height {
+                bottom.max((self.height * 2).min(self.max_dimension))
+            } else {
+                self.height
+            };
+            self.reserve(width, height);
+        }
+        self.painted_width = self.painted_width.max(right);
+        self.painted_height = self.painted_height.max(bottom);
+        for bit in 0..6 {
+            if sixel & (1 << bit) == 0 {
+                continue;
+            }
+            let row = (y + bit) as usize * self.width as usize;
+            self.pixels[row + x as usize..row + right as usize].fill(color);
+        }
+    }
+
+    fn into_image(self) -> Result<RgbaImage> {
+        if self.painted_width == 0 || self.painted_height == 0 {
+            bail!("sixel image is empty");
+        }
+        let mut data =
+            Vec::with_capacity(self.painted_width as usize * self.painted_height as usize * 4);
+        for row in 0..self.painted_height as usize {
+            let start = row * self.width as usize;
+            for pixel in &self.pixels[start..start + self.painted_width as usize] {
+                data.extend_from_slice(pixel);
+            }
+        }
+        RgbaImage::from_raw(self.painted_width, self.painted_height, data)
+            .ok_or_else(|| anyhow!("invalid sixel image size"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        // A red 2x6 block next to a blue 1x2 one, on a 4x8 raster.
+        let image = decode(b"\"1;1;4;8#1;2;100;0;0#2;2;0;0;100#1!2~#2B", 100).unwrap();
+        assert_eq!(image.dimensions(), (4, 8));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 5).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(2, 1).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(2, 2).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(3, 7).0, [0, 0, 0, 0]);
+
+        // Without raster attributes, the image is as large as the painted pixels.
+        let image = decode(b"#0;2;0;100;0~-?@", 100).unwrap();
+        assert_eq!(image.dimensions(), (2, 7));
+        assert_eq!(image.get_pixel(0, 5).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(1, 6).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(0, 6).0, [0, 0, 0, 0]);
+
+        assert!(decode(b"!200~", 100).is_err());
+    }
+}
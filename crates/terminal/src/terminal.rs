@@ -2,8 +2,14 @@ pub mod mappings;
 
 pub use alacritty_terminal;
 
+mod pty_filter;
 mod pty_info;
+mod scrollback;
+pub mod sessions;
+mod shell_integration;
+mod sixel;
 mod terminal_hyperlinks;
+mod terminal_images;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -41,12 +47,18 @@ use mappings::mouse::{
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
+use parking_lot::Mutex;
+use pty_filter::{FilteredPty, PtyOutputFilter};
 use pty_info::{ProcessIdGetter, PtyProcessInfo};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{CommandHistory, SemanticPromptFilter, command_blocks};
+pub use shell_integration::{CommandStatus, PromptMark};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, SpawnInTerminal};
 use terminal_hyperlinks::RegexSearches;
+pub use terminal_images::ImagePlacement;
+use terminal_images::{ImageFilter, ImageStore};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use urlencoding;
@@ -107,6 +119,14 @@ actions!(
         ToggleViMode,
         /// Selects all text in the terminal.
         SelectAll,
+        /// Scrolls to the previous shell prompt.
+        ScrollToPreviousPrompt,
+        /// Scrolls to the next shell prompt.
+        ScrollToNextPrompt,
+        /// Selects the output of the command containing the selection, or of the last command.
+        SelectCommandOutput,
+        /// Copies the output of the last finished command to the clipboard.
+        CopyLastCommandOutput,
     ]
 );
 
@@ -393,8 +413,11 @@ impl TerminalBuilder {
             next_link_id: 0,
             selection_phase: SelectionPhase::Ended,
             hyperlink_regex_searches: RegexSearches::default(),
+            command_history: None,
+            images: Arc::default(),
             vi_mode_enabled: false,
             is_remote_terminal: false,
+            session_id: None,
             last_mouse_move_time: Instant::now(),
             last_hyperlink_search_position: None,
             mouse_down_hyperlink: None,
@@ -439,9 +462,11 @@ impl TerminalBuilder {
         cx: &App,
         activation_script: Vec<String>,
         path_style: PathStyle,
+        session_id: Option<String>,
     ) -> Task<Result<TerminalBuilder>> {
         let version = release_channel::AppVersion::global(cx);
         let background_executor = cx.background_executor().clone();
+        let shell_integration = TerminalSettings::get_global(cx).shell_integration;
         let fut = async move {
             // Remove SHLVL so the spawned shell initializes it to 1, matching
             // the behavior of standalone terminal emulators like iTerm2/Kitty/Alacritty.
@@ -480,7 +505,28 @@ impl TerminalBuilder {
                 }
             }
 
-            let shell_params = match shell.clone() {
+            // The shell integration variables only apply to the spawned shell, so they are kept
+            // out of `env`, which is reused when this terminal is cloned.
+            let mut pty_env = env.clone();
+            let mut pty_shell = shell.clone();
+            let command_history = if shell_integration && task.is_none() && !is_remote_terminal {
+                match shell_integration::inject(&shell, &mut pty_env) {
+                    Ok(injected_shell) => {
+                        if let Some(injected_shell) = injected_shell {
+                            pty_shell = injected_shell;
+                        }
+                        Some(Arc::new(Mutex::new(CommandHistory::default())))
+                    }
+                    Err(error) => {
+                        log::error!("failed to set up shell integration: {error:#}");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            let shell_params = match pty_shell {
                 Shell::System => {
                     if cfg!(windows) {
                         Some(ShellParams::new(
@@ -502,6 +548,32 @@ impl TerminalBuilder {
             let terminal_title_override =
                 shell_params.as_ref().and_then(|e| e.title_override.clone());
 
+            // A local terminal with a session runs an attach client, which hands the shell over to
+            // the session host. Remote sessions are attached to by the remote server instead.
+            #[cfg(unix)]
+            let shell_params = match &session_id {
+                Some(session_id) if !is_remote_terminal => {
+                    let command = sessions::SessionCommand {
+                        program: shell_params.as_ref().map(|params| params.program.clone()),
+                        args: shell_params
+                            .as_ref()
+                            .and_then(|params| params.args.clone())
+                            .unwrap_or_default(),
+                    };
+                    pty_env.insert(
+                        sessions::SESSION_COMMAND_ENV.to_string(),
+                        serde_json::to_string(&command)?,
+                    );
+                    let (program, args) = sessions::attach_command(session_id)?;
+                    Some(ShellParams::new(
+                        program,
+                        Some(args),
+                        terminal_title_override.clone(),
+                    ))
+                }
+                _ => shell_params,
+            };
+
             #[cfg(windows)]
             let shell_program = shell_params.as_ref().map(|params| {
                 use util::ResultExt;
@@ -530,7 +602,7 @@ impl TerminalBuilder {
                     shell: alac_shell,
                     working_directory: working_directory.clone(),
                     drain_on_exit: true,
-                    env: env.clone().into_iter().collect(),
+                    env: pty_env.into_iter().collect(),
                     #[cfg(windows)]
                     escape_args: shell_kind.tty_escape_args(),
                 }
@@ -587,23 +659,28 @@ impl TerminalBuilder {
             let pty_info = PtyProcessInfo::new(&pty);
 
             //And connect them together
+            let images = Arc::new(Mutex::new(ImageStore::default()));
+            let mut filters: Vec<Box<dyn PtyOutputFilter>> =
+                vec![Box::new(ImageFilter::new(images.clone()))];
+            if let Some(command_history) = &command_history {
+                filters.push(Box::new(SemanticPromptFilter::new(command_history.clone())));
+            }
             let event_loop = EventLoop::new(
                 term.clone(),
                 ZedListener(events_tx),
-                pty,
+                FilteredPty::new(pty, filters),
                 pty_options.drain_on_exit,
                 false,
             )
             .context("failed to create event loop")?;
-
-            let pty_tx = event_loop.channel();
+            let pty_tx = Notifier(event_loop.channel());
             let _io_thread = event_loop.spawn(); // DANGER
 
             let no_task = task.is_none();
             let terminal = Terminal {
                 task,
                 terminal_type: TerminalType::Pty {
-                    pty_tx: Notifier(pty_tx),
+                    pty_tx,
                     info: Arc::new(pty_info),
                 },
                 completion_tx,
@@ -624,8 +701,11 @@ impl TerminalBuilder {
                     &path_hyperlink_regexes,
                     path_hyperlink_timeout_ms,
                 ),
+                command_history,
+                images,
                 vi_mode_enabled: false,
                 is_remote_terminal,
+                session_id,
                 last_mouse_move_time: Instant::now(),
                 last_hyperlink_search_position: None,
                 mouse_down_hyperlink: None,
@@ -794,6 +874,21 @@ pub struct TerminalContent {
     pub last_hovered_word: Option<HoveredWord>,
     pub scrolled_to_top: bool,
     pub scrolled_to_bottom: bool,
+    /// The visible prompts whose commands have run, when shell integration is loaded.
+    pub prompt_marks: Vec<PromptMark>,
+    /// The inline images that are at least partly visible.
+    pub images: Vec<ImagePlacement>,
+}
+
+/// How far [`Terminal::read_completed_output`] has read a terminal's output.
+#[derive(Clone, Debug, Default)]
+pub struct OutputCursor {
+    /// The first unread line, as an index from the top of the scrollback. The scrollback grows
+    /// with every line scrolled into it, which keeps the indices of the lines it holds stable
+    /// until it's full.
+    next_line: usize,
+    /// The size of the scrollback when the cursor was last advanced.
+    history_size: usize,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -820,6 +915,8 @@ impl Default for TerminalContent {
             last_hovered_word: None,
             scrolled_to_top: false,
             scrolled_to_bottom: false,
+            prompt_marks: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -856,9 +953,12 @@ pub struct Terminal {
     next_link_id: usize,
     selection_phase: SelectionPhase,
     hyperlink_regex_searches: RegexSearches,
+    command_history: Option<Arc<Mutex<CommandHistory>>>,
+    images: Arc<Mutex<ImageStore>>,
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_remote_terminal: bool,
+    session_id: Option<String>,
     last_mouse_move_time: Instant,
     last_hyperlink_search_position: Option<Point<Pixels>>,
     mouse_down_hyperlink: Option<(String, bool, Match)>,
@@ -1314,6 +1414,51 @@ impl Terminal {
         cx.emit(Event::Wakeup);
     }
 
+    /// Returns the scrollback and the screen above the cursor's line, with their styling, for
+    /// [`Self::restore_scrollback`]. The scrollback is already capped to
+    /// `max_scroll_history_lines`. Returns `None` while the alternate screen is shown.
+    pub fn serialize_scrollback(&self) -> Option<String> {
+        let term = self.term.lock_unfair();
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return None;
+        }
+        let cursor = term.grid().cursor.point;
+        Some(scrollback::serialize(
+            term.grid(),
+            AlacPoint::new(cursor.line, Column(0)),
+        ))
+    }
+
+    /// Shows scrollback saved by [`Self::serialize_scrollback`] above the shell's prompt, followed
+    /// by a note that it was restored. The restored text is only output, so nothing in it runs
+    /// again.
+    pub fn restore_scrollback(&mut self, scrollback: &str, cx: &mut Context<Self>) {
+        if scrollback.is_empty() {
+            return;
+        }
+
+        let mut processor = alacritty_terminal::vte::ansi::Processor::<
+            alacritty_terminal::vte::ansi::StdSyncHandler,
+        >::new();
+        {
+            let mut term = self.term.lock();
+            // The shell may have printed its prompt already, in which case it's moved below the
+            // restored scrollback.
+            let cursor = term.grid().cursor.point;
+            let shell_output = (cursor != AlacPoint::new(Line(0), Column(0)))
+                .then(|| scrollback::serialize(term.grid(), cursor));
+            if shell_output.is_some() {
+                processor.advance(&mut *term, b"\x1b[H\x1b[2J\x1b[3J");
+            }
+            processor.advance(&mut *term, scrollback.as_bytes());
+            processor.advance(&mut *term, b"\x1b[0;2mSession contents restored\x1b[0m\r\n");
+            if let Some(shell_output) = shell_output {
+                processor.advance(&mut *term, shell_output.as_bytes());
+            }
+        }
+        cx.emit(Event::Wakeup);
+    }
+
     pub fn total_lines(&self) -> usize {
         self.term.lock_unfair().total_lines()
     }
@@ -1362,6 +1507,88 @@ impl Terminal {
         self.set_selection(Some((make_selection(&(start..=end)), end)));
     }
 
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.scroll_to_prompt(AlacDirection::Left);
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.scroll_to_prompt(AlacDirection::Right);
+    }
+
+    /// Scrolls the nearest prompt above or below the top of the viewport to the top.
+    fn scroll_to_prompt(&mut self, direction: AlacDirection) {
+        let term = self.term.lock();
+        let top = Line(-(term.grid().display_offset() as i32));
+        let blocks = command_blocks(term.grid());
+        drop(term);
+        let prompt = match direction {
+            AlacDirection::Left => blocks
+                .iter()
+                .rev()
+                .find(|block| block.prompt_start.line < top),
+            AlacDirection::Right => blocks.iter().find(|block| block.prompt_start.line > top),
+        };
+        if let Some(prompt) = prompt {
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                    top.0 - prompt.prompt_start.line.0,
+                )));
+        }
+    }
+
+    /// Selects the output of the command whose prompt is above the start of the selection, or
+    /// of the last finished command if nothing is selected.
+    pub fn select_command_output(&mut self) {
+        let term = self.term.lock();
+        let selection_line = term
+            .selection
+            .as_ref()
+            .and_then(|selection| selection.to_range(&*term))
+            .map(|range| range.start.line);
+        drop(term);
+        if let Some(output) = self.command_output_range(selection_line) {
+            self.set_selection(Some((make_selection(&output), *output.end())));
+        }
+    }
+
+    /// Returns the output of the last finished command, if shell integration is loaded and the
+    /// command's prompt is still in the scrollback.
+    pub fn last_command_output(&self) -> Option<String> {
+        let output = self.command_output_range(None)?;
+        let term = self.term.lock();
+        Some(term.bounds_to_string(*output.start(), *output.end()))
+    }
+
+    /// Returns the lines printed by the command run from the prompt at or above `line`, or by the
+    /// last finished command when `line` is `None`.
+    fn command_output_range(&self, line: Option<Line>) -> Option<RangeInclusive<AlacPoint>> {
+        let command_history = self.command_history.as_ref()?;
+        let term = self.term.lock();
+        let blocks = command_blocks(term.grid());
+        let ix = match line {
+            Some(line) => blocks
+                .iter()
+                .rposition(|block| block.prompt_start.line <= line)?,
+            None => {
+                let command_history = command_history.lock();
+                blocks.iter().rposition(|block| {
+                    matches!(
+                        command_history.status(block.prompt_id),
+                        Some(CommandStatus::Finished { .. })
+                    )
+                })?
+            }
+        };
+
+        let start = blocks[ix].input_end.line + 1;
+        let end = match blocks.get(ix + 1) {
+            Some(next_block) => next_block.prompt_start.line - 1,
+            None => term.grid().cursor.point.line,
+        };
+        (start <= end)
+            .then(|| AlacPoint::new(start, Column(0))..=AlacPoint::new(end, term.last_column()))
+    }
+
     fn set_selection(&mut self, selection: Option<(Selection, AlacPoint)>) {
         self.events
             .push_back(InternalEvent::SetSelection(selection));
@@ -1603,6 +1830,16 @@ impl Terminal {
         }
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        if let Some(command_history) = &self.command_history {
+            self.last_content.prompt_marks =
+                shell_integration::prompt_marks(&self.last_content.cells, &command_history.lock());
+        }
+
+        let mut images = self.images.lock();
+        self.last_content.images = images.placements(&self.last_content.cells);
+        for image in images.take_evicted() {
+            cx.drop_image(image, Some(window));
+        }
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -1635,6 +1872,8 @@ impl Terminal {
             last_hovered_word: last_content.last_hovered_word.clone(),
             scrolled_to_top: content.display_offset == term.history_size(),
             scrolled_to_bottom: content.display_offset == 0,
+            prompt_marks: Vec::new(),
+            images: Vec::new(),
         }
     }
 
@@ -1670,6 +1909,35 @@ impl Terminal {
         lines
     }
 
+    /// Returns the output that was completed since `cursor` was last advanced, and advances it.
+    /// Output is complete once the terminal's cursor has moved below it: the line the cursor is
+    /// on, and the rows wrapping into it, may still be rewritten.
+    pub fn read_completed_output(&self, cursor: &mut OutputCursor) -> String {
+        let term = self.term.lock_unfair();
+        let grid = term.grid();
+        let topmost_line = grid.topmost_line().0;
+        let end = self.find_logical_line_start(grid, grid.cursor.point.line.0, topmost_line);
+
+        // Viewport lines are negative in the scrollback, which `history_size` lines precede. A
+        // smaller scrollback was cleared, so whatever output remains is read again.
+        let history_size = grid.history_size();
+        let start = if history_size < cursor.history_size {
+            topmost_line
+        } else {
+            (cursor.next_line as i32 - history_size as i32).clamp(topmost_line, end)
+        };
+        cursor.history_size = history_size;
+        if start >= end {
+            return String::new();
+        }
+
+        cursor.next_line = (end + history_size as i32) as usize;
+        term.bounds_to_string(
+            AlacPoint::new(Line(start), Column(0)),
+            AlacPoint::new(Line(end - 1), term.last_column()),
+        )
+    }
+
     fn find_logical_line_start(&self, grid: &Grid<Cell>, current: i32, topmost: i32) -> i32 {
         let mut line_start = current;
         while line_start > topmost {
@@ -1984,7 +2252,10 @@ impl Terminal {
             if self.selection_phase == SelectionPhase::Ended {
                 let mouse_cell_index =
                     content_index_for_mouse(position, &self.last_content.terminal_bounds);
-                if let Some(link) = self.last_content.cells[mouse_cell_index].hyperlink() {
+                if let Some(link) = self.last_content.cells[mouse_cell_index]
+                    .hyperlink()
+                    .filter(|link| !is_internal_hyperlink(link.uri()))
+                {
                     cx.open_url(link.uri());
                 } else if e.modifiers.secondary() {
                     self.events
@@ -2192,6 +2463,19 @@ impl Terminal {
         self.task.as_ref()
     }
 
+    /// The id of the persistent session this terminal is attached to, if any.
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Ends the terminal's persistent session, instead of leaving it running detached once the
+    /// terminal goes away.
+    pub fn end_session(&self) {
+        if self.session_id.is_some() {
+            self.write_to_pty(sessions::END_SESSION_SEQUENCE);
+        }
+    }
+
     pub fn wait_for_completed_task(&self, cx: &App) -> Task<Option<ExitStatus>> {
         if let Some(task) = self.task() {
             if task.status == TaskStatus::Running {
@@ -2295,10 +2579,18 @@ impl Terminal {
             cx,
             self.activation_script.clone(),
             self.path_style,
+            None,
         )
     }
 }
 
+/// Returns whether a hyperlink URI was added by Zed to mark up the grid rather than by a program
+/// running in the terminal. These links are never shown or opened.
+pub fn is_internal_hyperlink(uri: &str) -> bool {
+    shell_integration::is_shell_integration_hyperlink(uri)
+        || terminal_images::is_image_hyperlink(uri)
+}
+
 // Helper function to convert a grid row to a string
 pub fn row_to_string(row: &Row<Cell>) -> String {
     row[..Column(row.len())]
@@ -2582,6 +2874,7 @@ mod tests {
                     cx,
                     vec![],
                     PathStyle::local(),
+                    None,
                 )
             })
             .await
@@ -2728,6 +3021,7 @@ mod tests {
                     cx,
                     Vec::new(),
                     PathStyle::local(),
+                    None,
                 )
             })
             .await
@@ -2804,6 +3098,7 @@ mod tests {
                     cx,
                     Vec::new(),
                     PathStyle::local(),
+                    None,
                 )
             })
             .await
@@ -3029,6 +3324,43 @@ mod tests {
         assert!(line2_col0, "Second line should start at column 0");
     }
 
+    #[gpui::test]
+    async fn test_read_completed_output(cx: &mut TestAppContext) {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+        let mut cursor = OutputCursor::default();
+        let mut write_and_read = |output: &str, cx: &mut TestAppContext| {
+            terminal.update(cx, |terminal, cx| {
+                terminal.write_output(output.as_bytes(), cx);
+                terminal
+                    .read_completed_output(&mut cursor)
+                    .lines()
+                    .map(|line| line.trim_end().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(write_and_read("one\ntwo\nthr", cx), ["one", "two"]);
+        // The line the cursor is on is completed by the next one.
+        assert_eq!(write_and_read("ee\n", cx), ["three"]);
+        assert_eq!(write_and_read("", cx), Vec::<String>::new());
+
+        // Identical lines are read once, including after they have scrolled into the scrollback.
+        assert_eq!(write_and_read(&"same\n".repeat(100), cx), vec!["same"; 100]);
+        assert_eq!(write_and_read("same\n", cx), ["same"]);
+        assert_eq!(write_and_read("", cx), Vec::<String>::new());
+    }
+
     #[gpui::test]
     async fn test_write_output_preserves_existing_crlf(cx: &mut TestAppContext) {
         let terminal = cx.new(|cx| {
@@ -3292,6 +3624,7 @@ mod tests {
                         cx,
                         vec![],
                         PathStyle::local(),
+                        None,
                     )
                 })
                 .await
@@ -0,0 +1,951 @@
+//! Inline images drawn with the Kitty graphics protocol, Sixel or iTerm2's inline images.
+//!
+//! [`ImageFilter`] decodes the images out of the PTY output and replaces each one with a block
+//! of blank cells as large as the image, covered by an `OSC 8` hyperlink whose URI names the
+//! image and the row of the block. The blank cells scroll, get trimmed from the scrollback and
+//! are overwritten like any other text, and the terminal element draws the image over them.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use alacritty_terminal::{
+    event::WindowSize,
+    index::{Line, Point as AlacPoint},
+};
+use anyhow::{Context as _, Result, anyhow, bail};
+use base64::Engine as _;
+use collections::{HashMap, HashSet, VecDeque};
+use gpui::RenderImage;
+use image::{DynamicImage, Frame, ImageFormat, ImageReader, Limits, RgbImage, RgbaImage};
+use parking_lot::Mutex;
+
+use crate::{
+    IndexedCell,
+    pty_filter::{PtyOutputFilter, write_hyperlink},
+    sixel,
+};
+
+const IMAGE_URI_PREFIX: &str = "zed-terminal-image:";
+
+/// The prefix of iTerm2's inline image `OSC`, after `ESC ]`.
+const ITERM_FILE_PREFIX: &[u8] = b"1337;File=";
+/// Longer image sequences are discarded.
+const MAX_SEQUENCE_LEN: usize = 64 << 20;
+/// Images wider or taller than this many pixels are rejected.
+const MAX_IMAGE_DIMENSION: u32 = 8192;
+/// Images are displayed at most this many lines tall.
+const MAX_IMAGE_LINES: usize = 1000;
+/// Once the decoded images of a terminal take more memory than this, the oldest are evicted.
+const MAX_IMAGE_MEMORY: usize = 320 << 20;
+/// Kitty images transmitted for later display are forgotten once there are more than this.
+const MAX_KITTY_IMAGES: usize = 64;
+
+/// Returns whether a hyperlink URI marks the cells covered by an inline image.
+pub(crate) fn is_image_hyperlink(uri: &str) -> bool {
+    uri.starts_with(IMAGE_URI_PREFIX)
+}
+
+/// Parses an image hyperlink URI into the image's id and the row of the image it covers.
+fn parse_hyperlink(uri: &str) -> Option<(usize, usize)> {
+    let (id, row) = uri.strip_prefix(IMAGE_URI_PREFIX)?.split_once('/')?;
+    Some((id.parse().ok()?, row.parse().ok()?))
+}
+
+/// An image that is at least partly visible in the terminal.
+#[derive(Clone, Debug)]
+pub struct ImagePlacement {
+    /// The cell of the image's top left corner, which may be above the viewport.
+    pub point: AlacPoint,
+    pub columns: usize,
+    pub lines: usize,
+    pub image: Arc<RenderImage>,
+}
+
+struct StoredImage {
+    image: Arc<RenderImage>,
+    columns: usize,
+    lines: usize,
+    size_in_bytes: usize,
+}
+
+/// The images placed in a terminal, keyed by the id in their hyperlinks.
+#[derive(Default)]
+pub(crate) struct ImageStore {
+    images: HashMap<usize, StoredImage>,
+    insertion_order: VecDeque<usize>,
+    next_id: usize,
+    size_in_bytes: usize,
+    evicted: Vec<Arc<RenderImage>>,
+}
+
+impl ImageStore {
+    fn insert(&mut self, image: Arc<RenderImage>, columns: usize, lines: usize) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        let size = image.size(0);
+        let size_in_bytes = size.width.0 as usize * size.height.0 as usize * 4;
+        self.size_in_bytes += size_in_bytes;
+        self.images.insert(
+            id,
+            StoredImage {
+                image,
+                columns,
+                lines,
+                size_in_bytes,
+            },
+        );
+        self.insertion_order.push_back(id);
+
+        while self.size_in_bytes > MAX_IMAGE_MEMORY && self.insertion_order.len() > 1 {
+            let Some(oldest) = self.insertion_order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = self.images.remove(&oldest) {
+                self.size_in_bytes -= evicted.size_in_bytes;
+                self.evicted.push(evicted.image);
+            }
+        }
+        id
+    }
+
+    /// Returns the images evicted since the last call, whose textures can be released.
+    pub fn take_evicted(&mut self) -> Vec<Arc<RenderImage>> {
+        std::mem::take(&mut self.evicted)
+    }
+
+    /// Returns the images covering any of `cells`.
+    pub(crate) fn placements(&self, cells: &[IndexedCell]) -> Vec<ImagePlacement> {
+        let mut placements = Vec::new();
+        let mut seen = HashSet::default();
+        for cell in cells {
+            let Some((id, row)) = cell
+                .hyperlink()
+                .and_then(|link| parse_hyperlink(link.uri()))
+            else {
+                continue;
+            };
+            // Cells are in reading order, so the first cell of an image is the leftmost one on
+            // its topmost visible row.
+            if !seen.insert(id) {
+                continue;
+            }
+            if let Some(stored) = self.images.get(&id) {
+                placements.push(ImagePlacement {
+                    point: AlacPoint::new(Line(cell.point.line.0 - row as i32), cell.point.column),
+                    columns: stored.columns,
+                    lines: stored.lines,
+                    image: stored.image.clone(),
+                });
+            }
+        }
+        placements
+    }
+}
+
+/// Where the cursor is left once an image has been placed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CursorMovement {
+    /// After the image's last column, on its last line.
+    AfterImage,
+    /// At the start of the line below the image.
+    NextLine,
+    /// Where the image started.
+    None,
+}
+
+enum Protocol {
+    Kitty,
+    Sixel,
+    Iterm,
+}
+
+enum State {
+    Ground,
+    /// After an `ESC`.
+    Escape,
+    /// After `ESC _`, which starts a Kitty graphics command if followed by `G`.
+    ApcStart,
+    /// After `ESC P` and the given parameters, which start a Sixel image if followed by `q`.
+    DcsParams(Vec<u8>),
+    /// After `ESC ]` and the given number of bytes of [`ITERM_FILE_PREFIX`].
+    OscPrefix(usize),
+    Capture {
+        protocol: Protocol,
+        data: Vec<u8>,
+        overflowed: bool,
+    },
+}
+
+/// Keys of a Kitty graphics command.
+#[derive(Clone, Debug, Default)]
+struct KittyCommand {
+    action: Option<u8>,
+    format: Option<u32>,
+    medium: Option<u8>,
+    compression: Option<u8>,
+    width: u32,
+    height: u32,
+    image_id: u32,
+    more: bool,
+    quiet: u32,
+    columns: Option<usize>,
+    lines: Option<usize>,
+    keep_cursor: bool,
+}
+
+impl KittyCommand {
+    fn parse(control: &[u8]) -> Self {
+        let mut command = Self::default();
+        for pair in control.split(|byte| *byte == b',') {
+            let [key, b'=', value @ ..] = pair else {
+                continue;
+            };
+            let number = || {
+                std::str::from_utf8(value)
+                    .ok()
+                    .and_then(|value| value.parse::<u32>().ok())
+            };
+            match key {
+                b'a' => command.action = value.first().copied(),
+                b'f' => command.format = number(),
+                b't' => command.medium = value.first().copied(),
+                b'o' => command.compression = value.first().copied(),
+                b's' => command.width = number().unwrap_or(0),
+                b'v' => command.height = number().unwrap_or(0),
+                b'i' => command.image_id = number().unwrap_or(0),
+                b'm' => command.more = number() == Some(1),
+                b'q' => command.quiet = number().unwrap_or(0),
+                b'c' => command.columns = number().filter(|n| *n > 0).map(|n| n as usize),
+                b'r' => command.lines = number().filter(|n| *n > 0).map(|n| n as usize),
+                b'C' => command.keep_cursor = number() == Some(1),
+                _ => {}
+            }
+        }
+        command
+    }
+}
+
+/// Decodes inline images from PTY output and places them on the grid.
+pub(crate) struct ImageFilter {
+    state: State,
+    store: Arc<Mutex<ImageStore>>,
+    window_size: Option<WindowSize>,
+    /// A Kitty transmission split into several chunks, with the payload received so far.
+    kitty_transmission: Option<(KittyCommand, Vec<u8>)>,
+    /// Images transmitted with Kitty's `a=t`, which can be displayed later with `a=p`.
+    kitty_images: HashMap<u32, Arc<RenderImage>>,
+}
+
+impl ImageFilter {
+    pub fn new(store: Arc<Mutex<ImageStore>>) -> Self {
+        Self {
+            state: State::Ground,
+            store,
+            window_size: None,
+            kitty_transmission: None,
+            kitty_images: HashMap::default(),
+        }
+    }
+
+    fn push(&mut self, byte: u8, output: &mut Vec<u8>, reply: &mut Vec<u8>) {
+        match std::mem::replace(&mut self.state, State::Ground) {
+            State::Ground => {
+                if byte == 0x1b {
+                    self.state = State::Escape;
+                } else {
+                    output.push(byte);
+                }
+            }
+            State::Escape => match byte {
+                b'_' => self.state = State::ApcStart,
+                b'P' => self.state = State::DcsParams(Vec::new()),
+                b']' => self.state = State::OscPrefix(0),
+                _ => self.abandon(b"\x1b", byte, output),
+            },
+            State::ApcStart => {
+                if byte == b'G' {
+                    self.state = State::Capture {
+                        protocol: Protocol::Kitty,
+                        data: Vec::new(),
+                        overflowed: false,
+                    };
+                } else {
+                    self.abandon(b"\x1b_", byte, output);
+                }
+            }
+            State::DcsParams(mut params) => match byte {
+                b'0'..=b'9' | b';' if params.len() < 32 => {
+                    params.push(byte);
+                    self.state = State::DcsParams(params);
+                }
+                b'q' => {
+                    self.state = State::Capture {
+                        protocol: Protocol::Sixel,
+                        data: Vec::new(),
+                        overflowed: false,
+                    };
+                }
+                _ => {
+                    let mut held = b"\x1bP".to_vec();
+                    held.extend(params);
+                    self.abandon(&held, byte, output);
+                }
+            },
+            State::OscPrefix(matched) => {
+                if byte != ITERM_FILE_PREFIX[matched] {
+                    let mut held = b"\x1b]".to_vec();
+                    held.extend_from_slice(&ITERM_FILE_PREFIX[..matched]);
+                    self.abandon(&held, byte, output);
+                } else if matched + 1 == ITERM_FILE_PREFIX.len() {
+                    self.state = State::Capture {
+                        protocol: Protocol::Iterm,
+                        data: Vec::new(),
+                        overflowed: false,
+                    };
+                } else {
+                    self.state = State::OscPrefix(matched + 1);
+                }
+            }
+            State::Capture {
+                protocol,
+                mut data,
+                mut overflowed,
+            } => {
+                let terminated = if byte == b'\\' && data.last() == Some(&0x1b) {
+                    data.pop();
+                    true
+                } else {
+                    byte == 0x07 && matches!(protocol, Protocol::Iterm)
+                };
+                if terminated {
+                    if overflowed {
+                        log::warn!(
+                            "discarded an inline image larger than {MAX_SEQUENCE_LEN} bytes"
+                        );
+                    } else {
+                        self.handle_image(protocol, data, output, reply);
+                    }
+                    return;
+                }
+
+                if data.len() >= MAX_SEQUENCE_LEN {
+                    overflowed = true;
+                    data.clear();
+                }
+                data.push(byte);
+                self.state = State::Capture {
+                    protocol,
+                    data,
+                    overflowed,
+                };
+            }
+        }
+    }
+
+    /// Outputs the bytes held back for a sequence that turned out not to be an image, and
+    /// handles the byte that ended it from the ground state.
+    fn abandon(&mut self, held: &[u8], byte: u8, output: &mut Vec<u8>) {
+        output.extend_from_slice(held);
+        if byte == 0x1b {
+            self.state = State::Escape;
+        } else {
+            output.push(byte);
+        }
+    }
+
+    fn handle_image(
+        &mut self,
+        protocol: Protocol,
+        data: Vec<u8>,
+        output: &mut Vec<u8>,
+        reply: &mut Vec<u8>,
+    ) {
+        let result = match protocol {
+            Protocol::Kitty => {
+                self.handle_kitty_command(&data, output, reply);
+                return;
+            }
+            Protocol::Sixel => sixel::decode(&data, MAX_IMAGE_DIMENSION).map(|image| {
+                self.place(image, None, None, CursorMovement::NextLine, output);
+            }),
+            Protocol::Iterm => self.handle_iterm_file(&data, output),
+        };
+        if let Err(error) = result {
+            log::warn!("failed to display inline image: {error:#}");
+        }
+    }
+
+    fn handle_kitty_command(&mut self, data: &[u8], output: &mut Vec<u8>, reply: &mut Vec<u8>) {
+        let (control, payload) = match data.iter().position(|byte| *byte == b';') {
+            Some(ix) => (&data[..ix], &data[ix + 1..]),
+            None => (data, &[][..]),
+        };
+        let chunk = KittyCommand::parse(control);
+
+        // Only the first chunk of a transmission carries its keys.
+        let (command, payload) = match self.kitty_transmission.take() {
+            Some((command, mut received)) => {
+                received.extend_from_slice(payload);
+                if received.len() > MAX_SEQUENCE_LEN {
+                    log::warn!("discarded an inline image larger than {MAX_SEQUENCE_LEN} bytes");
+                    return;
+                }
+                (command, received)
+            }
+            None => (chunk.clone(), payload.to_vec()),
+        };
+        if chunk.more {
+            self.kitty_transmission = Some((command, payload));
+            return;
+        }
+
+        let result = match command.action.unwrap_or(b't') {
+            b'q' => decode_kitty_image(&command, &payload).map(|_| ()),
+            action @ (b't' | b'T') => decode_kitty_image(&command, &payload).map(|image| {
+                if command.image_id != 0 {
+                    if self.kitty_images.len() >= MAX_KITTY_IMAGES
+                        && let Some(&oldest) = self.kitty_images.keys().min()
+                    {
+                        self.kitty_images.remove(&oldest);
+                    }
+                    self.kitty_images.insert(command.image_id, image.clone());
+                }
+                if action == b'T' {
+                    self.place_kitty_image(&command, image, output);
+                }
+            }),
+            b'p' => match self.kitty_images.get(&command.image_id).cloned() {
+                Some(image) => {
+                    self.place_kitty_image(&command, image, output);
+                    Ok(())
+                }
+                None => Err(anyhow!("ENOENT:no image with id {}", command.image_id)),
+            },
+            b'd' => {
+                // Deleting placements would mean erasing cells, so only transmitted images
+                // are forgotten.
+                if command.image_id != 0 {
+                    self.kitty_images.remove(&command.image_id);
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        // Kitty only answers commands that name an image, and `q` silences the answers.
+        if command.image_id == 0 {
+            return;
+        }
+        let message = match &result {
+            Ok(()) if command.quiet == 0 => "OK".to_string(),
+            Err(error) if command.quiet < 2 => {
+                let message = error.to_string();
+                if message.starts_with('E') && message.contains(':') {
+                    message
+                } else {
+                    format!("EINVAL:{message}")
+                }
+            }
+            _ => return,
+        };
+        reply.extend_from_slice(
+            format!(
+                "\x1b_Gi={};{}\x1b\\",
+                command.image_id,
+                message.replace('\x1b', "")
+            )
+            .as_bytes(),
+        );
+    }
+
+    fn place_kitty_image(
+        &mut self,
+        command: &KittyCommand,
+        image: Arc<RenderImage>,
+        output: &mut Vec<u8>,
+    ) {
+        let cursor = if command.keep_cursor {
+            CursorMovement::None
+        } else {
+            CursorMovement::AfterImage
+        };
+        self.place_render_image(image, command.columns, command.lines, cursor, output);
+    }
+
+    fn handle_iterm_file(&mut self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let separator = data
+            .iter()
+            .position(|byte| *byte == b':')
+            .context("missing file contents")?;
+        let args = std::str::from_utf8(&data[..separator]).context("invalid arguments")?;
+        let mut inline = false;
+        let mut width = None;
+        let mut height = None;
+        let mut preserve_aspect_ratio = true;
+        for arg in args.split(';') {
+            match arg.split_once('=') {
+                Some(("inline", value)) => inline = value == "1",
+                Some(("width", value)) => width = Some(value),
+                Some(("height", value)) => height = Some(value),
+                Some(("preserveAspectRatio", value)) => preserve_aspect_ratio = value != "0",
+                _ => {}
+            }
+        }
+        // Files that aren't inline are downloads, which aren't supported.
+        if !inline {
+            return Ok(());
+        }
+
+        let contents = base64::engine::general_purpose::STANDARD
+            .decode(&data[separator + 1..])
+            .context("invalid base64")?;
+        let image = decode_image(&contents, None)?;
+
+        let (cell_width, cell_height) = self.cell_size();
+        let (screen_columns, screen_lines) = self.screen_size();
+        let mut columns = width.and_then(|width| {
+            parse_iterm_dimension(width, cell_width, screen_columns, image.width())
+        });
+        let mut lines = height.and_then(|height| {
+            parse_iterm_dimension(height, cell_height, screen_lines, image.height())
+        });
+        // Fit the image inside the requested box by only keeping its limiting side.
+        if preserve_aspect_ratio && let (Some(box_columns), Some(box_lines)) = (columns, lines) {
+            let width_scale = box_columns as f32 * cell_width / image.width() as f32;
+            let height_scale = box_lines as f32 * cell_height / image.height() as f32;
+            if width_scale < height_scale {
+                lines = None;
+            } else {
+                columns = None;
+            }
+        }
+        self.place(image, columns, lines, CursorMovement::NextLine, output);
+        Ok(())
+    }
+
+    fn place(
+        &mut self,
+        image: RgbaImage,
+        columns: Option<usize>,
+        lines: Option<usize>,
+        cursor: CursorMovement,
+        output: &mut Vec<u8>,
+    ) {
+        let image = Arc::new(render_image(image));
+        self.place_render_image(image, columns, lines, cursor, output);
+    }
+
+    /// Stores `image` and writes the blank cells it's drawn over, sized to cover the image at
+    /// its natural size unless `columns` or `lines` are given.
+    fn place_render_image(
+        &mut self,
+        image: Arc<RenderImage>,
+        columns: Option<usize>,
+        lines: Option<usize>,
+        cursor: CursorMovement,
+        output: &mut Vec<u8>,
+    ) {
+        let size = image.size(0);
+        let (width, height) = (size.width.0.max(1) as f32, size.height.0.max(1) as f32);
+        let (cell_width, cell_height) = self.cell_size();
+        let (screen_columns, _) = self.screen_size();
+        let (mut columns, mut lines) = match (columns, lines) {
+            (Some(columns), Some(lines)) => (columns, lines),
+            (Some(columns), None) => {
+                let lines = columns as f32 * cell_width * height / width / cell_height;
+                (columns, lines.ceil() as usize)
+            }
+            (None, Some(lines)) => {
+                let columns = lines as f32 * cell_height * width / height / cell_width;
+                (columns.ceil() as usize, lines)
+            }
+            (None, None) => (
+                (width / cell_width).ceil() as usize,
+                (height / cell_height).ceil() as usize,
+            ),
+        };
+        if columns > screen_columns {
+            lines = (lines * screen_columns).div_ceil(columns);
+            columns = screen_columns;
+        }
+        let columns = columns.max(1);
+        let lines = lines.clamp(1, MAX_IMAGE_LINES);
+
+        let id = self.store.lock().insert(image, columns, lines);
+        let blank = " ".repeat(columns);
+        for row in 0..lines {
+            if row > 0 {
+                // A line feed moves down without returning to the first column, scrolling
+                // when the image reaches the bottom of the screen.
+                output.push(b'\n');
+            }
+            output.extend_from_slice(b"\x1b7");
+            write_hyperlink(output, Some(&format!("{IMAGE_URI_PREFIX}{id}/{row}")));
+            output.extend_from_slice(blank.as_bytes());
+            write_hyperlink(output, None);
+            if row + 1 < lines || cursor != CursorMovement::AfterImage {
+                output.extend_from_slice(b"\x1b8");
+            }
+        }
+        match cursor {
+            CursorMovement::AfterImage => {}
+            CursorMovement::NextLine => output.extend_from_slice(b"\r\n"),
+            CursorMovement::None => {
+                if lines > 1 {
+                    output.extend_from_slice(format!("\x1b[{}A", lines - 1).as_bytes());
+                }
+            }
+        }
+    }
+
+    /// The size of a cell in pixels, as last reported to the program.
+    fn cell_size(&self) -> (f32, f32) {
+        match self.window_size {
+            Some(size) if size.cell_width > 0 && size.cell_height > 0 => {
+                (size.cell_width as f32, size.cell_height as f32)
+            }
+            _ => (8., 16.),
+        }
+    }
+
+    fn screen_size(&self) -> (usize, usize) {
+        match self.window_size {
+            Some(size) if size.num_cols > 0 && size.num_lines > 0 => {
+                (size.num_cols as usize, size.num_lines as usize)
+            }
+            _ => (80, 24),
+        }
+    }
+}
+
+impl PtyOutputFilter for ImageFilter {
+    fn filter(&mut self, mut input: &[u8], output: &mut Vec<u8>, reply: &mut Vec<u8>) {
+        while !input.is_empty() {
+            // Copy plain output and image data in bulk rather than a byte at a time.
+            let bulk_len = match &mut self.state {
+                State::Ground => {
+                    let len = input
+                        .iter()
+                        .position(|byte| *byte == 0x1b)
+                        .unwrap_or(input.len());
+                    output.extend_from_slice(&input[..len]);
+                    len
+                }
+                State::Capture { data, .. } if data.len() < MAX_SEQUENCE_LEN => {
+                    let len = input
+                        .iter()
+                        .position(|byte| matches!(byte, 0x1b | 0x07 | b'\\'))
+                        .unwrap_or(input.len())
+                        .min(MAX_SEQUENCE_LEN - data.len());
+                    data.extend_from_slice(&input[..len]);
+                    len
+                }
+                _ => 0,
+            };
+            input = &input[bulk_len..];
+            if let Some((&byte, rest)) = input.split_first() {
+                self.push(byte, output, reply);
+                input = rest;
+            }
+        }
+    }
+
+    fn flush(&mut self, output: &mut Vec<u8>) {
+        match std::mem::replace(&mut self.state, State::Ground) {
+            State::Ground | State::Capture { .. } => {}
+            State::Escape => output.push(0x1b),
+            State::ApcStart => output.extend_from_slice(b"\x1b_"),
+            State::DcsParams(params) => {
+                output.extend_from_slice(b"\x1bP");
+                output.extend(params);
+            }
+            State::OscPrefix(matched) => {
+                output.extend_from_slice(b"\x1b]");
+                output.extend_from_slice(&ITERM_FILE_PREFIX[..matched]);
+            }
+        }
+    }
+
+    fn resize(&mut self, window_size: WindowSize) {
+        self.window_size = Some(window_size);
+    }
+}
+
+fn check_dimensions(width: u32, height: u32) -> Result<()> {
+    if width == 0 || height == 0 || width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        bail!("image size {width}x{height} is not supported");
+    }
+    Ok(())
+}
+
+/// Decodes an image file, rejecting images that are too large from their header, before their
+/// pixels are decompressed, so that a small payload can't make the terminal run out of memory.
+fn decode_image(data: &[u8], format: Option<ImageFormat>) -> Result<RgbaImage> {
+    let mut reader = ImageReader::new(Cursor::new(data));
+    match format {
+        Some(format) => reader.set_format(format),
+        None => reader = reader.with_guessed_format()?,
+    }
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    limits.max_alloc = Some(MAX_IMAGE_MEMORY as u64);
+    reader.limits(limits);
+    let image = reader.decode()?.into_rgba8();
+    check_dimensions(image.width(), image.height())?;
+    Ok(image)
+}
+
+fn decode_kitty_image(command: &KittyCommand, payload: &[u8]) -> Result<Arc<RenderImage>> {
+    if command.medium.is_some_and(|medium| medium != b'd') {
+        bail!("only direct transmission is supported");
+    }
+    if command.compression.is_some() {
+        bail!("compression is not supported");
+    }
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(payload)
+        .context("invalid base64")?;
+    let image = match command.format.unwrap_or(32) {
+        100 => decode_image(&data, Some(ImageFormat::Png))?,
+        format @ (24 | 32) => {
+            check_dimensions(command.width, command.height)?;
+            let (width, height) = (command.width, command.height);
+            let expected_len = width as usize * height as usize * (format as usize / 8);
+            if data.len() != expected_len {
+                bail!("expected {expected_len} bytes of pixel data");
+            }
+            if format == 32 {
+                RgbaImage::from_raw(width, height, data).context("invalid pixel data")?
+            } else {
+                let image =
+                    RgbImage::from_raw(width, height, data).context("invalid pixel data")?;
+                DynamicImage::ImageRgb8(image).into_rgba8()
+            }
+        }
+        format => bail!("unsupported format {format}"),
+    };
+    check_dimensions(image.width(), image.height())?;
+    Ok(Arc::new(render_image(image)))
+}
+
+/// Parses an iTerm2 image dimension, which is a number of cells, pixels or a percentage of the
+/// screen, into cells. Returns `None` for `auto`.
+fn parse_iterm_dimension(
+    value: &str,
+    cell_size: f32,
+    screen_size: usize,
+    image_size: u32,
+) -> Option<usize> {
+    let cells = if value == "auto" {
+        return None;
+    } else if let Some(pixels) = value.strip_suffix("px") {
+        pixels.parse::<f32>().ok()? / cell_size
+    } else if let Some(percent) = value.strip_suffix('%') {
+        percent.parse::<f32>().ok()? * screen_size as f32 / 100.
+    } else {
+        value.parse::<f32>().ok()?
+    };
+    if cells <= 0. {
+        return Some((image_size as f32 / cell_size).ceil() as usize);
+    }
+    Some(cells.ceil() as usize)
+}
+
+fn render_image(mut image: RgbaImage) -> RenderImage {
+    // Convert from RGBA to BGRA.
+    for pixel in image.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    RenderImage::new([Frame::new(image)])
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{index::Column, term::cell::Hyperlink};
+
+    use super::*;
+
+    fn filter_chunks(filter: &mut ImageFilter, chunks: &[&[u8]]) -> (String, String) {
+        let mut output = Vec::new();
+        let mut reply = Vec::new();
+        for chunk in chunks {
+            filter.filter(chunk, &mut output, &mut reply);
+        }
+        (
+            String::from_utf8(output).unwrap(),
+            String::from_utf8(reply).unwrap(),
+        )
+    }
+
+    fn image_filter() -> (ImageFilter, Arc<Mutex<ImageStore>>) {
+        let store = Arc::new(Mutex::new(ImageStore::default()));
+        let mut filter = ImageFilter::new(store.clone());
+        filter.resize(WindowSize {
+            num_lines: 24,
+            num_cols: 80,
+            cell_width: 2,
+            cell_height: 4,
+        });
+        (filter, store)
+    }
+
+    #[test]
+    fn test_kitty_image() {
+        let (mut filter, store) = image_filter();
+        // A 3x5 pixel RGB image split into two chunks, which covers 2x2 cells.
+        let pixels = base64::engine::general_purpose::STANDARD.encode([255; 45]);
+        let (first, second) = pixels.split_at(20);
+        let (output, reply) = filter_chunks(
+            &mut filter,
+            &[
+                b"before\x1b_Ga=T,f=24,s=3,v=5,i=7,m=1;",
+                first.as_bytes(),
+                b"\x1b\\\x1b_Gm=0;",
+                second.as_bytes(),
+                b"\x1b\\after",
+            ],
+        );
+        assert_eq!(
+            output,
+            concat!(
+                "before",
+                "\x1b7\x1b]8;;zed-terminal-image:0/0\x1b\\  \x1b]8;;\x1b\\\x1b8\n",
+                "\x1b7\x1b]8;;zed-terminal-image:0/1\x1b\\  \x1b]8;;\x1b\\",
+                "after"
+            )
+        );
+        assert_eq!(reply, "\x1b_Gi=7;OK\x1b\\");
+        assert_eq!(store.lock().images[&0].columns, 2);
+        assert_eq!(store.lock().images[&0].lines, 2);
+
+        // Display the transmitted image again, stretched and without moving the cursor.
+        let (output, reply) = filter_chunks(&mut filter, &[b"\x1b_Ga=p,i=7,c=1,r=2,C=1,q=1\x1b\\"]);
+        assert_eq!(
+            output,
+            concat!(
+                "\x1b7\x1b]8;;zed-terminal-image:1/0\x1b\\ \x1b]8;;\x1b\\\x1b8\n",
+                "\x1b7\x1b]8;;zed-terminal-image:1/1\x1b\\ \x1b]8;;\x1b\\\x1b8\x1b[1A",
+            )
+        );
+        assert_eq!(reply, "");
+
+        let (output, reply) = filter_chunks(&mut filter, &[b"\x1b_Ga=p,i=8\x1b\\"]);
+        assert_eq!(output, "");
+        assert_eq!(reply, "\x1b_Gi=8;ENOENT:no image with id 8\x1b\\");
+
+        let (output, reply) = filter_chunks(&mut filter, &[b"\x1b_Ga=q,i=9,t=f;AAAA\x1b\\"]);
+        assert_eq!(output, "");
+        assert_eq!(
+            reply,
+            "\x1b_Gi=9;EINVAL:only direct transmission is supported\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_oversized_png() {
+        // A PNG whose header claims a 100000x100000 pixels image, that would take tens of
+        // gigabytes to decode.
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut header = Vec::new();
+        header.extend(100_000u32.to_be_bytes());
+        header.extend(100_000u32.to_be_bytes());
+        header.extend([8, 0, 0, 0, 0]);
+        push_png_chunk(&mut png, b"IHDR", &header);
+        push_png_chunk(
+            &mut png,
+            b"IDAT",
+            &[0x78, 0x9c, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01],
+        );
+        push_png_chunk(&mut png, b"IEND", &[]);
+        assert!(decode_image(&png, Some(ImageFormat::Png)).is_err());
+
+        let (mut filter, store) = image_filter();
+        let payload = base64::engine::general_purpose::STANDARD.encode(&png);
+        let (output, reply) = filter_chunks(
+            &mut filter,
+            &[
+                b"\x1b_Ga=T,f=100,i=1;",
+                payload.as_bytes(),
+                b"\x1b\\\x1b]1337;File=inline=1:",
+                payload.as_bytes(),
+                b"\x07",
+            ],
+        );
+        assert_eq!(output, "");
+        assert!(reply.starts_with("\x1b_Gi=1;EINVAL:"), "{reply:?}");
+        assert!(store.lock().images.is_empty());
+    }
+
+    fn push_png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        png.extend((data.len() as u32).to_be_bytes());
+        png.extend(kind);
+        png.extend(data);
+        let mut crc = !0u32;
+        for byte in kind.iter().chain(data) {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        png.extend((!crc).to_be_bytes());
+    }
+
+    #[test]
+    fn test_sixel_image() {
+        let (mut filter, store) = image_filter();
+        let (output, _) = filter_chunks(&mut filter, &[b"\x1bPq#0;2;100;0;0!4~\x1b\\$ "]);
+        assert_eq!(
+            output,
+            concat!(
+                "\x1b7\x1b]8;;zed-terminal-image:0/0\x1b\\  \x1b]8;;\x1b\\\x1b8\n",
+                "\x1b7\x1b]8;;zed-terminal-image:0/1\x1b\\  \x1b]8;;\x1b\\\x1b8\r\n",
+                "$ "
+            )
+        );
+        assert_eq!(store.lock().images[&0].image.size(0).width.0, 4);
+    }
+
+    #[test]
+    fn test_other_sequences_pass_through() {
+        let (mut filter, _) = image_filter();
+        let input: &[&[u8]] = &[
+            b"\x1b[1m\x1b]0;title\x07\x1b]1337;SetMark\x07",
+            b"\x1bP=1s\x1b\\\x1b_Xignored\x1b\\\x1b",
+            b"\x1b\x1b]8;;https://zed.dev\x1b\\",
+        ];
+        let (output, _) = filter_chunks(&mut filter, input);
+        assert_eq!(output, String::from_utf8(input.concat()).unwrap());
+    }
+
+    #[test]
+    fn test_placements() {
+        let mut store = ImageStore::default();
+        let image = Arc::new(render_image(RgbaImage::new(1, 1)));
+        let id = store.insert(image, 3, 4);
+        let cell = |line, column, uri: Option<&str>| {
+            let mut cell = IndexedCell {
+                point: AlacPoint::new(Line(line), Column(column)),
+                cell: Default::default(),
+            };
+            if let Some(uri) = uri {
+                cell.cell.set_hyperlink(Some(Hyperlink::new(None, uri)));
+            }
+            cell
+        };
+        let placements = store.placements(&[
+            cell(-1, 0, None),
+            cell(-1, 2, Some(&format!("zed-terminal-image:{id}/2"))),
+            cell(-1, 3, Some(&format!("zed-terminal-image:{id}/2"))),
+            cell(0, 2, Some(&format!("zed-terminal-image:{id}/3"))),
+            cell(0, 5, Some("zed-terminal-image:99/0")),
+        ]);
+        assert_eq!(placements.len(), 1);
+        assert_eq!(placements[0].point, AlacPoint::new(Line(-3), Column(2)));
+        assert_eq!((placements[0].columns, placements[0].lines), (3, 4));
+    }
+}
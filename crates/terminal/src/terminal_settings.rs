@@ -50,6 +50,9 @@ pub struct TerminalSettings {
     pub minimum_contrast: f32,
     pub path_hyperlink_regexes: Vec<String>,
     pub path_hyperlink_timeout_ms: u64,
+    pub shell_integration: bool,
+    pub persist_scrollback: bool,
+    pub persistent_sessions: bool,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
@@ -129,6 +132,9 @@ impl settings::Settings for TerminalSettings {
                 })
                 .collect(),
             path_hyperlink_timeout_ms: project_content.path_hyperlink_timeout_ms.unwrap(),
+            shell_integration: user_content.shell_integration.unwrap(),
+            persist_scrollback: user_content.persist_scrollback.unwrap(),
+            persistent_sessions: user_content.persistent_sessions.unwrap(),
         }
     }
 }
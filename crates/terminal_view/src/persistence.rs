@@ -64,7 +64,7 @@ fn serialize_pane(pane: &Entity<Pane>, active: bool, cx: &mut App) -> Serialized
         .items()
         .filter_map(|item| {
             let terminal_view = item.act_as::<TerminalView>(cx)?;
-            if terminal_view.read(cx).terminal().read(cx).task().is_some() {
+            if !terminal_view.read(cx).is_restorable(cx) {
                 None
             } else {
                 let id = item.item_id().as_u64();
@@ -427,6 +427,12 @@ impl Domain for TerminalDb {
         sql! (
             ALTER TABLE terminals ADD COLUMN custom_title TEXT;
         ),
+        sql! (
+            ALTER TABLE terminals ADD COLUMN scrollback TEXT;
+        ),
+        sql! (
+            ALTER TABLE terminals ADD COLUMN session_id TEXT;
+        ),
     ];
 }
 
@@ -511,6 +517,74 @@ impl TerminalDb {
         .await
     }
 
+    pub async fn save_scrollback(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        scrollback: String,
+    ) -> Result<()> {
+        log::debug!(
+            "Saving {} bytes of scrollback for item {} in workspace {:?}",
+            scrollback.len(),
+            item_id,
+            workspace_id
+        );
+        self.write(move |conn| {
+            let query = "INSERT INTO terminals (item_id, workspace_id, scrollback)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (workspace_id, item_id) DO UPDATE SET
+                    scrollback = excluded.scrollback";
+            let mut statement = Statement::prepare(conn, query)?;
+            let mut next_index = statement.bind(&item_id, 1)?;
+            next_index = statement.bind(&workspace_id, next_index)?;
+            statement.bind(&scrollback, next_index)?;
+            statement.exec()
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_scrollback(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<String>> {
+            SELECT scrollback
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    pub async fn save_session_id(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        session_id: Option<String>,
+    ) -> Result<()> {
+        log::debug!(
+            "Saving session id {:?} for item {} in workspace {:?}",
+            session_id,
+            item_id,
+            workspace_id
+        );
+        self.write(move |conn| {
+            let query = "INSERT INTO terminals (item_id, workspace_id, session_id)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (workspace_id, item_id) DO UPDATE SET
+                    session_id = excluded.session_id";
+            let mut statement = Statement::prepare(conn, query)?;
+            let mut next_index = statement.bind(&item_id, 1)?;
+            next_index = statement.bind(&workspace_id, next_index)?;
+            statement.bind(&session_id, next_index)?;
+            statement.exec()
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_session_id(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<String>> {
+            SELECT session_id
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
     query! {
         pub fn get_custom_title(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<String>> {
             SELECT custom_title
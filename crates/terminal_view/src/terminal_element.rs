@@ -1,19 +1,19 @@
 use editor::{CursorLayout, EditorSettings, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    AbsoluteLength, AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, DispatchPhase,
-    Element, ElementId, Entity, FocusHandle, Font, FontFeatures, FontStyle, FontWeight,
-    GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity,
-    IntoElement, LayoutId, Length, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels,
-    Point, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle,
-    UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window, div, fill, point, px, relative,
-    size,
+    AbsoluteLength, AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, Corners,
+    DispatchPhase, Element, ElementId, Entity, FocusHandle, Font, FontFeatures, FontStyle,
+    FontWeight, GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement,
+    Interactivity, IntoElement, LayoutId, Length, ModifiersChangedEvent, MouseButton,
+    MouseMoveEvent, Pixels, Point, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun,
+    TextStyle, UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window, div, fill, point,
+    px, relative, size,
 };
 use itertools::Itertools;
 use language::CursorShape;
 use settings::Settings;
 use std::time::Instant;
 use terminal::{
-    IndexedCell, Terminal, TerminalBounds, TerminalContent,
+    ImagePlacement, IndexedCell, Terminal, TerminalBounds, TerminalContent,
     alacritty_terminal::{
         grid::Dimensions,
         index::Point as AlacPoint,
@@ -23,6 +23,7 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    is_internal_hyperlink,
     terminal_settings::TerminalSettings,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
@@ -50,6 +51,10 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    /// The display lines of the prompts whose commands have finished, with the color that
+    /// shows whether they succeeded.
+    prompt_marks: Vec<(i32, Hsla)>,
+    images: Vec<ImagePlacement>,
     block_below_cursor_element: Option<AnyElement>,
     base_text_style: TextStyle,
     content_mode: ContentMode,
@@ -564,7 +569,10 @@ impl TerminalElement {
         }
 
         let underline = (flags.intersects(Flags::ALL_UNDERLINES)
-            || indexed.cell.hyperlink().is_some())
+            || indexed
+                .cell
+                .hyperlink()
+                .is_some_and(|link| !is_internal_hyperlink(link.uri())))
         .then(|| UnderlineStyle {
             color: Some(fg),
             thickness: Pixels::from(1.0),
@@ -1030,10 +1038,24 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    prompt_marks,
+                    images,
                     ..
                 } = &self.terminal.read(cx).last_content;
+                let images = images.clone();
                 let mode = *mode;
                 let display_offset = *display_offset;
+                let prompt_marks = prompt_marks
+                    .iter()
+                    .filter_map(|mark| {
+                        let color = if mark.status.succeeded()? {
+                            theme.status().success
+                        } else {
+                            theme.status().error
+                        };
+                        Some((mark.point.line.0 + display_offset as i32, color))
+                    })
+                    .collect();
 
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
@@ -1219,6 +1241,8 @@ impl Element for TerminalElement {
                     display_offset,
                     hyperlink_tooltip,
                     gutter,
+                    prompt_marks,
+                    images,
                     block_below_cursor_element,
                     base_text_style: text_style,
                     content_mode,
@@ -1302,6 +1326,42 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, window);
                     }
 
+                    for image in &layout.images {
+                        let dimensions = &layout.dimensions;
+                        let line = image.point.line.0 + layout.display_offset as i32;
+                        let image_bounds = Bounds::new(
+                            point(
+                                origin.x + dimensions.cell_width * image.point.column.0 as f32,
+                                origin.y + dimensions.line_height * line as f32,
+                            ),
+                            size(
+                                dimensions.cell_width * image.columns as f32,
+                                dimensions.line_height * image.lines as f32,
+                            ),
+                        );
+                        window
+                            .paint_image(
+                                image_bounds,
+                                Corners::default(),
+                                image.image.clone(),
+                                0,
+                                false,
+                            )
+                            .log_err();
+                    }
+
+                    for (line, color) in &layout.prompt_marks {
+                        let line_height = layout.dimensions.line_height;
+                        let mark_bounds = Bounds::new(
+                            point(
+                                bounds.origin.x + layout.gutter / 4.,
+                                origin.y + line_height * *line as f32,
+                            ),
+                            size(layout.gutter / 2., line_height),
+                        );
+                        window.paint_quad(fill(mark_bounds, *color));
+                    }
+
                     for (relative_highlighted_range, color) in &layout.relative_highlighted_ranges {
                         if let Some((start_y, highlighted_range_lines)) =
                             to_highlighted_range_lines(relative_highlighted_range, layout, origin)
@@ -1538,7 +1598,10 @@ pub fn is_blank(cell: &IndexedCell) -> bool {
         return false;
     }
 
-    if cell.hyperlink().is_some() {
+    if cell
+        .hyperlink()
+        .is_some_and(|link| !is_internal_hyperlink(link.uri()))
+    {
         return false;
     }
 
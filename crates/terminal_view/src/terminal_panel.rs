@@ -1,7 +1,7 @@
 use std::{cmp, ops::ControlFlow, path::PathBuf, process::ExitStatus, sync::Arc, time::Duration};
 
 use crate::{
-    TerminalView, default_working_directory,
+    TerminalView, ToggleBroadcastInput, default_working_directory,
     persistence::{
         SerializedItems, SerializedTerminalPanel, deserialize_terminal_panel, serialize_pane_group,
     },
@@ -33,7 +33,7 @@ use workspace::{
     PaneGroup, SplitDirection, SplitDown, SplitLeft, SplitMode, SplitRight, SplitUp, SwapPaneDown,
     SwapPaneLeft, SwapPaneRight, SwapPaneUp, ToggleZoom, Workspace,
     dock::{DockPosition, Panel, PanelEvent, PanelHandle},
-    item::SerializableItem,
+    item::{ItemEvent, SerializableItem},
     move_active_item, move_item, pane,
 };
 
@@ -57,6 +57,7 @@ pub fn init(cx: &mut App) {
         |workspace: &mut Workspace, _window, _: &mut Context<Workspace>| {
             workspace.register_action(TerminalPanel::new_terminal);
             workspace.register_action(TerminalPanel::open_terminal);
+            workspace.register_action(TerminalPanel::toggle_broadcast_input);
             workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
                 if is_enabled_in_workspace(workspace, cx) {
                     workspace.toggle_panel_focus::<TerminalPanel>(window, cx);
@@ -87,6 +88,7 @@ pub struct TerminalPanel {
     assistant_enabled: bool,
     assistant_tab_bar_button: Option<AnyView>,
     active: bool,
+    broadcast_input: bool,
 }
 
 impl TerminalPanel {
@@ -107,6 +109,7 @@ impl TerminalPanel {
             assistant_enabled: false,
             assistant_tab_bar_button: None,
             active: false,
+            broadcast_input: false,
         };
         terminal_panel.apply_tab_bar_buttons(&terminal_panel.active_pane, cx);
         terminal_panel
@@ -675,6 +678,47 @@ impl TerminalPanel {
             .detach_and_log_err(cx);
     }
 
+    /// Whether input to any terminal of the workspace is also sent to all other terminals that
+    /// aren't excluded from broadcasting.
+    pub fn broadcast_input(&self) -> bool {
+        self.broadcast_input
+    }
+
+    fn toggle_broadcast_input(
+        workspace: &mut Workspace,
+        _: &ToggleBroadcastInput,
+        _: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(terminal_panel) = workspace.panel::<Self>(cx) else {
+            return;
+        };
+        let terminal_views = terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.broadcast_input = !terminal_panel.broadcast_input;
+            terminal_panel.terminal_views(workspace, cx)
+        });
+        for terminal_view in terminal_views {
+            terminal_view.update(cx, |_, cx| {
+                cx.emit(ItemEvent::UpdateTab);
+                cx.notify();
+            });
+        }
+    }
+
+    /// Returns all terminals of the workspace, both in the panel and in the center.
+    pub(crate) fn terminal_views(
+        &self,
+        workspace: &Workspace,
+        cx: &App,
+    ) -> Vec<Entity<TerminalView>> {
+        self.center
+            .panes()
+            .into_iter()
+            .chain(workspace.panes())
+            .flat_map(|pane| pane.read(cx).items_of_type::<TerminalView>())
+            .collect()
+    }
+
     fn terminals_for_task(
         &self,
         label: &str,
@@ -1918,6 +1962,79 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_broadcast_input(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let workspace = cx.add_window(|window, cx| Workspace::test_new(project, window, cx));
+
+        let (window_handle, terminal_panel) = workspace
+            .update(cx, |workspace, window, cx| {
+                let window_handle = window.window_handle();
+                let terminal_panel = cx.new(|cx| TerminalPanel::new(workspace, window, cx));
+                workspace.add_panel(terminal_panel.clone(), window, cx);
+                (window_handle, terminal_panel)
+            })
+            .unwrap();
+
+        for _ in 0..3 {
+            let task = window_handle
+                .update(cx, |_, window, cx| {
+                    terminal_panel.update(cx, |panel, cx| {
+                        panel.add_terminal_shell(None, RevealStrategy::Always, window, cx)
+                    })
+                })
+                .unwrap();
+            task.await.unwrap();
+        }
+        cx.run_until_parked();
+
+        let terminal_views = workspace
+            .read_with(cx, |workspace, cx| {
+                terminal_panel.read(cx).terminal_views(workspace, cx)
+            })
+            .unwrap();
+        assert_eq!(terminal_views.len(), 3);
+        let input_terminal_counts = |cx: &mut TestAppContext| {
+            terminal_views
+                .iter()
+                .map(|terminal_view| {
+                    terminal_view.read_with(cx, |terminal_view, cx| {
+                        terminal_view.input_terminals(cx).len()
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(input_terminal_counts(cx), [1, 1, 1]);
+
+        workspace
+            .update(cx, |workspace, window, cx| {
+                TerminalPanel::toggle_broadcast_input(workspace, &ToggleBroadcastInput, window, cx)
+            })
+            .unwrap();
+        assert_eq!(input_terminal_counts(cx), [3, 3, 3]);
+
+        window_handle
+            .update(cx, |_, window, cx| {
+                terminal_views[2].update(cx, |terminal_view, cx| {
+                    terminal_view.toggle_broadcast_exclusion(
+                        &crate::ToggleBroadcastExclusion,
+                        window,
+                        cx,
+                    )
+                })
+            })
+            .unwrap();
+        assert_eq!(
+            input_terminal_counts(cx),
+            [2, 2, 1],
+            "An excluded terminal neither sends nor receives broadcast input"
+        );
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_prepare_script_like_task() {
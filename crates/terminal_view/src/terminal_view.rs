@@ -8,10 +8,10 @@ mod terminal_slash_command;
 use assistant_slash_command::SlashCommandRegistry;
 use editor::{Editor, EditorSettings, actions::SelectAll, blink_manager::BlinkManager};
 use gpui::{
-    Action, AnyElement, App, ClipboardEntry, DismissEvent, Entity, EventEmitter, FocusHandle,
-    Focusable, KeyContext, KeyDownEvent, Keystroke, MouseButton, MouseDownEvent, Pixels, Point,
-    Render, ScrollWheelEvent, Styled, Subscription, Task, WeakEntity, actions, anchored, deferred,
-    div,
+    Action, AnyElement, App, ClipboardEntry, ClipboardItem, DismissEvent, Entity, EventEmitter,
+    FocusHandle, Focusable, KeyContext, KeyDownEvent, Keystroke, MouseButton, MouseDownEvent,
+    Pixels, Point, PromptLevel, Render, ScrollWheelEvent, Styled, Subscription, Task, WeakEntity,
+    actions, anchored, deferred, div,
 };
 use menu;
 use persistence::TERMINAL_DB;
@@ -29,8 +29,9 @@ use std::{
 };
 use task::TaskId;
 use terminal::{
-    Clear, Copy, Event, HoveredWord, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp,
-    ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskState,
+    Clear, Copy, CopyLastCommandOutput, Event, HoveredWord, MaybeNavigationTarget, Paste,
+    ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ScrollToTop, SelectCommandOutput, ShowCharacterPalette, TaskState,
     TaskStatus, Terminal, TerminalBounds, ToggleViMode,
     alacritty_terminal::{
         index::Point as AlacPoint,
@@ -65,6 +66,8 @@ struct ImeState {
 }
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+/// How long after output the scrollback is saved, when `terminal.persist_scrollback` is enabled.
+const SCROLLBACK_SERIALIZATION_DELAY: Duration = Duration::from_secs(2);
 
 /// Event to transmit the scroll from the element to the view
 #[derive(Clone, Debug, PartialEq)]
@@ -85,6 +88,10 @@ actions!(
     [
         /// Reruns the last executed task in the terminal.
         RerunTask,
+        /// Toggles sending input typed into a terminal to all other terminals of the workspace.
+        ToggleBroadcastInput,
+        /// Toggles whether the terminal is excluded from broadcast input.
+        ToggleBroadcastExclusion,
     ]
 );
 
@@ -131,6 +138,8 @@ pub struct TerminalView {
     mode: TerminalMode,
     blinking_terminal_enabled: bool,
     needs_serialize: bool,
+    needs_scrollback_serialize: bool,
+    scrollback_serialization: Option<Task<()>>,
     custom_title: Option<String>,
     hover: Option<HoverTarget>,
     hover_tooltip_update: Task<()>,
@@ -143,6 +152,7 @@ pub struct TerminalView {
     self_handle: WeakEntity<Self>,
     rename_editor: Option<Entity<Editor>>,
     rename_editor_subscription: Option<Subscription>,
+    exclude_from_broadcast: bool,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -284,11 +294,14 @@ impl TerminalView {
             scroll_top: Pixels::ZERO,
             scroll_handle,
             needs_serialize: false,
+            needs_scrollback_serialize: false,
+            scrollback_serialization: None,
             custom_title: None,
             ime_state: None,
             self_handle: cx.entity().downgrade(),
             rename_editor: None,
             rename_editor_subscription: None,
+            exclude_from_broadcast: false,
             _subscriptions: subscriptions,
             _terminal_subscriptions: terminal_subscriptions,
         }
@@ -366,12 +379,65 @@ impl TerminalView {
     /// Commits (sends) the given text to the PTY. Called by InputHandler::replace_text_in_range.
     pub(crate) fn commit_text(&mut self, text: &str, cx: &mut Context<Self>) {
         if !text.is_empty() {
-            self.terminal.update(cx, |term, _| {
-                term.input(text.to_string().into_bytes());
-            });
+            for terminal in self.input_terminals(cx) {
+                terminal.update(cx, |term, _| {
+                    term.input(text.to_string().into_bytes());
+                });
+            }
         }
     }
 
+    /// Whether input to this terminal is currently broadcast to the other terminals of the
+    /// workspace.
+    pub fn is_broadcasting(&self, cx: &App) -> bool {
+        !self.exclude_from_broadcast
+            && matches!(self.mode, TerminalMode::Standalone)
+            && self
+                .workspace
+                .upgrade()
+                .and_then(|workspace| workspace.read(cx).panel::<TerminalPanel>(cx))
+                .is_some_and(|terminal_panel| terminal_panel.read(cx).broadcast_input())
+    }
+
+    /// Returns the terminals that input to this view is sent to: its own terminal, followed by
+    /// those of the other non-excluded terminal views when broadcasting.
+    pub(crate) fn input_terminals(&self, cx: &App) -> Vec<Entity<Terminal>> {
+        let mut terminals = vec![self.terminal.clone()];
+        if !self.is_broadcasting(cx) {
+            return terminals;
+        }
+        let Some(workspace) = self.workspace.upgrade() else {
+            return terminals;
+        };
+        let workspace = workspace.read(cx);
+        let Some(terminal_panel) = workspace.panel::<TerminalPanel>(cx) else {
+            return terminals;
+        };
+        let own_id = self.self_handle.entity_id();
+        terminals.extend(
+            terminal_panel
+                .read(cx)
+                .terminal_views(workspace, cx)
+                .into_iter()
+                .filter(|terminal_view| terminal_view.entity_id() != own_id)
+                .map(|terminal_view| terminal_view.read(cx))
+                .filter(|terminal_view| !terminal_view.exclude_from_broadcast)
+                .map(|terminal_view| terminal_view.terminal.clone()),
+        );
+        terminals
+    }
+
+    pub(crate) fn toggle_broadcast_exclusion(
+        &mut self,
+        _: &ToggleBroadcastExclusion,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.exclude_from_broadcast = !self.exclude_from_broadcast;
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+    }
+
     pub(crate) fn terminal_bounds(&self, cx: &App) -> TerminalBounds {
         self.terminal.read(cx).last_content().terminal_bounds
     }
@@ -388,6 +454,37 @@ impl TerminalView {
         self.custom_title.as_deref()
     }
 
+    /// Whether the terminal is restored with its workspace. A task is only restored when it runs
+    /// in a persistent session, as restoring it otherwise would run it again.
+    pub(crate) fn is_restorable(&self, cx: &App) -> bool {
+        let terminal = self.terminal.read(cx);
+        terminal.task().is_none() || terminal.session_id().is_some()
+    }
+
+    /// Saves the scrollback a while after the terminal's output changes, so that a burst of
+    /// output is only saved once.
+    fn schedule_scrollback_serialization(&mut self, cx: &mut Context<Self>) {
+        if self.scrollback_serialization.is_some()
+            || self.workspace_id.is_none()
+            || self.terminal.read(cx).task().is_some()
+            || !TerminalSettings::get_global(cx).persist_scrollback
+        {
+            return;
+        }
+        self.scrollback_serialization = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(SCROLLBACK_SERIALIZATION_DELAY)
+                .await;
+            this.update(cx, |this, cx| {
+                this.scrollback_serialization = None;
+                this.needs_serialize = true;
+                this.needs_scrollback_serialize = true;
+                cx.emit(ItemEvent::UpdateTab);
+            })
+            .ok();
+        }));
+    }
+
     pub fn set_custom_title(&mut self, label: Option<String>, cx: &mut Context<Self>) {
         let label = label.filter(|l| !l.trim().is_empty());
         if self.custom_title != label {
@@ -502,6 +599,12 @@ impl TerminalView {
             .selection_text
             .as_ref()
             .is_some_and(|text| !text.is_empty());
+        let broadcast_input = self
+            .workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).panel::<TerminalPanel>(cx))
+            .is_some_and(|terminal_panel| terminal_panel.read(cx).broadcast_input());
+        let exclude_from_broadcast = self.exclude_from_broadcast;
         let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal::default()))
@@ -518,6 +621,19 @@ impl TerminalView {
                         })
                 })
                 .separator()
+                .action_checked(
+                    "Broadcast Input",
+                    Box::new(ToggleBroadcastInput),
+                    broadcast_input,
+                )
+                .when(broadcast_input, |menu| {
+                    menu.action_checked(
+                        "Exclude from Broadcast",
+                        Box::new(ToggleBroadcastExclusion),
+                        exclude_from_broadcast,
+                    )
+                })
+                .separator()
                 .action(
                     "Close Terminal Tab",
                     Box::new(CloseActiveItem {
@@ -736,6 +852,50 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(
+        &mut self,
+        _: &ScrollToNextPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn select_command_output(
+        &mut self,
+        _: &SelectCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.select_command_output());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(
+        &mut self,
+        _: &CopyLastCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(output) = self.terminal.read(cx).last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
     fn scroll_to_bottom(&mut self, _: &ScrollToBottom, _: &mut Window, cx: &mut Context<Self>) {
         self.terminal.update(cx, |term, _| term.scroll_to_bottom());
         if self.block_below_cursor.is_some() {
@@ -804,7 +964,7 @@ impl TerminalView {
     }
 
     ///Attempt to paste the clipboard into the terminal
-    fn paste(&mut self, _: &Paste, _: &mut Window, cx: &mut Context<Self>) {
+    fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
         let Some(clipboard) = cx.read_from_clipboard() else {
             return;
         };
@@ -817,25 +977,58 @@ impl TerminalView {
             return;
         }
 
-        if let Some(text) = clipboard.text() {
-            self.terminal
-                .update(cx, |terminal, _cx| terminal.paste(&text));
+        let Some(text) = clipboard.text() else {
+            return;
+        };
+        let terminals = self.input_terminals(cx);
+        if terminals.len() == 1 || !text.contains('\n') {
+            Self::paste_into(&terminals, &text, cx);
+            return;
+        }
+
+        // A newline runs whatever was pasted before it, so confirm before doing that everywhere.
+        let answer = window.prompt(
+            PromptLevel::Warning,
+            &format!(
+                "Paste {} lines into {} terminals?",
+                text.lines().count(),
+                terminals.len()
+            ),
+            Some("Input is being broadcast, so the pasted commands will run in every terminal."),
+            &["Paste", "Cancel"],
+            cx,
+        );
+        cx.spawn(async move |_, cx| {
+            if answer.await == Ok(0) {
+                cx.update(|cx| Self::paste_into(&terminals, &text, cx));
+            }
+        })
+        .detach();
+    }
+
+    fn paste_into(terminals: &[Entity<Terminal>], text: &str, cx: &mut App) {
+        for terminal in terminals {
+            terminal.update(cx, |terminal, _cx| terminal.paste(text));
         }
     }
 
     /// Emits a raw Ctrl+V so TUI agents can read the OS clipboard directly
     /// and attach images using their native workflows.
     fn forward_ctrl_v(&self, cx: &mut Context<Self>) {
-        self.terminal.update(cx, |term, _| {
-            term.input(vec![0x16]);
-        });
+        for terminal in self.input_terminals(cx) {
+            terminal.update(cx, |term, _| {
+                term.input(vec![0x16]);
+            });
+        }
     }
 
     fn send_text(&mut self, text: &SendText, _: &mut Window, cx: &mut Context<Self>) {
         self.clear_bell(cx);
-        self.terminal.update(cx, |term, _| {
-            term.input(text.0.to_string().into_bytes());
-        });
+        for terminal in self.input_terminals(cx) {
+            terminal.update(cx, |term, _| {
+                term.input(text.0.to_string().into_bytes());
+            });
+        }
     }
 
     fn send_keystroke(&mut self, text: &SendKeystroke, _: &mut Window, cx: &mut Context<Self>) {
@@ -990,6 +1183,7 @@ fn subscribe_for_terminal_events(
             match event {
                 Event::Wakeup => {
                     cx.notify();
+                    terminal_view.schedule_scrollback_serialization(cx);
                     cx.emit(Event::Wakeup);
                     cx.emit(ItemEvent::UpdateTab);
                     cx.emit(SearchEvent::MatchesInvalidated);
@@ -1138,6 +1332,13 @@ impl TerminalView {
             cx.notify();
         }
 
+        if handled {
+            let option_as_meta = TerminalSettings::get_global(cx).option_as_meta;
+            for terminal in self.input_terminals(cx).into_iter().skip(1) {
+                terminal.update(cx, |term, _| term.try_keystroke(keystroke, option_as_meta));
+            }
+        }
+
         handled
     }
 
@@ -1218,11 +1419,16 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::select_command_output))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
             .on_action(cx.listener(TerminalView::rerun_task))
             .on_action(cx.listener(TerminalView::rename_terminal))
+            .on_action(cx.listener(TerminalView::toggle_broadcast_exclusion))
             .on_key_down(cx.listener(Self::key_down))
             .on_mouse_down(
                 MouseButton::Right,
@@ -1268,6 +1474,15 @@ impl Render for TerminalView {
                         )
                     }),
             )
+            .when(self.is_broadcasting(cx), |this| {
+                this.child(
+                    div()
+                        .absolute()
+                        .inset_0()
+                        .border_1()
+                        .border_color(cx.theme().status().warning),
+                )
+            })
             .children(self.context_menu.as_ref().map(|(menu, position, _)| {
                 deferred(
                     anchored()
@@ -1395,6 +1610,13 @@ impl Item for TerminalView {
                         )
                     }),
             )
+            .when(self.is_broadcasting(cx), |this| {
+                this.child(
+                    Icon::new(IconName::Keyboard)
+                        .size(IconSize::Small)
+                        .color(Color::Warning),
+                )
+            })
             .into_any()
     }
 
@@ -1410,6 +1632,10 @@ impl Item for TerminalView {
         None
     }
 
+    fn on_closed(&self, cx: &mut Context<Self>) {
+        self.terminal.read(cx).end_session();
+    }
+
     fn tab_extra_context_menu_actions(
         &self,
         _window: &mut Window,
@@ -1508,7 +1734,7 @@ impl Item for TerminalView {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if self.terminal().read(cx).task().is_none() {
+        if self.is_restorable(cx) {
             if let Some((new_id, old_id)) = workspace.database_id().zip(self.workspace_id) {
                 log::debug!(
                     "Updating workspace id for the terminal, old: {old_id:?}, new: {new_id:?}",
@@ -1551,19 +1777,28 @@ impl SerializableItem for TerminalView {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) -> Option<Task<anyhow::Result<()>>> {
-        let terminal = self.terminal().read(cx);
-        if terminal.task().is_some() {
-            return None;
-        }
-
-        if !self.needs_serialize {
+        if !self.is_restorable(cx) || !self.needs_serialize {
             return None;
         }
 
         let workspace_id = self.workspace_id?;
+        let terminal = self.terminal().read(cx);
         let cwd = terminal.working_directory();
-        let custom_title = self.custom_title.clone();
+        // A restored task runs as a plain terminal, which keeps the task's label as its title.
+        let custom_title = self
+            .custom_title
+            .clone()
+            .or_else(|| terminal.task().map(|task| task.spawned_task.label.clone()));
+        let session_id = terminal.session_id().map(ToOwned::to_owned);
+        let scrollback = if self.needs_scrollback_serialize
+            && TerminalSettings::get_global(cx).persist_scrollback
+        {
+            terminal.serialize_scrollback()
+        } else {
+            None
+        };
         self.needs_serialize = false;
+        self.needs_scrollback_serialize = false;
 
         Some(cx.background_spawn(async move {
             if let Some(cwd) = cwd {
@@ -1574,6 +1809,14 @@ impl SerializableItem for TerminalView {
             TERMINAL_DB
                 .save_custom_title(item_id, workspace_id, custom_title)
                 .await?;
+            TERMINAL_DB
+                .save_session_id(item_id, workspace_id, session_id)
+                .await?;
+            if let Some(scrollback) = scrollback {
+                TERMINAL_DB
+                    .save_scrollback(item_id, workspace_id, scrollback)
+                    .await?;
+            }
             Ok(())
         }))
     }
@@ -1591,7 +1834,7 @@ impl SerializableItem for TerminalView {
         cx: &mut App,
     ) -> Task<anyhow::Result<Entity<Self>>> {
         window.spawn(cx, async move |cx| {
-            let (cwd, custom_title) = cx
+            let (cwd, custom_title, scrollback, session_id) = cx
                 .update(|_window, cx| {
                     let from_db = TERMINAL_DB
                         .get_working_directory(item_id, workspace_id)
@@ -1612,15 +1855,38 @@ impl SerializableItem for TerminalView {
                         .log_err()
                         .flatten()
                         .filter(|title| !title.trim().is_empty());
-                    (cwd, custom_title)
+                    let scrollback = if TerminalSettings::get_global(cx).persist_scrollback {
+                        TERMINAL_DB
+                            .get_scrollback(item_id, workspace_id)
+                            .log_err()
+                            .flatten()
+                    } else {
+                        None
+                    };
+                    let session_id = TERMINAL_DB
+                        .get_session_id(item_id, workspace_id)
+                        .log_err()
+                        .flatten();
+                    (cwd, custom_title, scrollback, session_id)
                 })
                 .ok()
-                .unwrap_or((None, None));
+                .unwrap_or((None, None, None, None));
 
             let terminal = project
-                .update(cx, |project, cx| project.create_terminal_shell(cwd, cx))
+                .update(cx, |project, cx| match session_id {
+                    Some(session_id) => project.reattach_terminal_session(cwd, session_id, cx),
+                    None => project.create_terminal_shell(cwd, cx),
+                })
                 .await?;
             cx.update(|window, cx| {
+                // A reattached session replays its own recent output.
+                if let Some(scrollback) = scrollback
+                    && terminal.read(cx).session_id().is_none()
+                {
+                    terminal.update(cx, |terminal, cx| {
+                        terminal.restore_scrollback(&scrollback, cx)
+                    });
+                }
                 cx.new(|cx| {
                     let mut view = TerminalView::new(
                         terminal,
@@ -1,18 +1,19 @@
 use crate::{
-    Appearance, DEFAULT_ICON_THEME_NAME, SyntaxTheme, Theme, status_colors_refinement,
+    Appearance, DEFAULT_ICON_THEME_NAME, GlobalTheme, SyntaxTheme, Theme, status_colors_refinement,
     syntax_overrides, theme_colors_refinement,
 };
 use collections::HashMap;
 use derive_more::{Deref, DerefMut};
 use gpui::{
-    App, Context, Font, FontFallbacks, FontStyle, Global, Pixels, Subscription, Window, px,
+    App, Context, Font, FontFallbacks, FontStyle, Global, Pixels, SharedString, Subscription,
+    Window, px,
 };
 use refineable::Refineable;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 pub use settings::{FontFamilyName, IconThemeName, ThemeAppearanceMode, ThemeName};
-use settings::{IntoGpui, RegisterSetting, Settings, SettingsContent};
-use std::sync::Arc;
+use settings::{IntoGpui, RegisterSetting, Settings, SettingsContent, SettingsStore};
+use std::{mem, sync::Arc};
 
 const MIN_FONT_SIZE: Pixels = px(6.0);
 const MAX_FONT_SIZE: Pixels = px(100.0);
@@ -614,6 +615,52 @@ pub fn reset_buffer_font_size(cx: &mut App) {
     }
 }
 
+/// Renders with the given theme and buffer font instead of the configured ones while `f` runs,
+/// e.g. to export an image whose look doesn't depend on the user's settings.
+pub fn with_theme_overrides<R>(
+    theme: Option<Arc<Theme>>,
+    buffer_font_family: Option<SharedString>,
+    buffer_font_size: Option<Pixels>,
+    cx: &mut App,
+    f: impl FnOnce(&mut App) -> R,
+) -> R {
+    let previous_theme =
+        theme.map(|theme| mem::replace(&mut cx.global_mut::<GlobalTheme>().theme, theme));
+    let previous_settings =
+        (buffer_font_family.is_some() || buffer_font_size.is_some()).then(|| {
+            let settings = ThemeSettings::get_global(cx).clone();
+            let mut overridden_settings = settings.clone();
+            if let Some(family) = buffer_font_family {
+                overridden_settings.buffer_font.family = family;
+            }
+            if let Some(size) = buffer_font_size {
+                overridden_settings.buffer_font_size = clamp_font_size(size);
+            }
+            SettingsStore::update_global(cx, |store, _| store.override_global(overridden_settings));
+            settings
+        });
+    // An adjusted buffer font size would take precedence over the overridden one.
+    let previous_font_size_adjustment = if buffer_font_size.is_some() {
+        cx.has_global::<BufferFontSize>()
+            .then(|| cx.remove_global::<BufferFontSize>())
+    } else {
+        None
+    };
+
+    let result = f(cx);
+
+    if let Some(adjustment) = previous_font_size_adjustment {
+        cx.set_global(adjustment);
+    }
+    if let Some(settings) = previous_settings {
+        SettingsStore::update_global(cx, |store, _| store.override_global(settings));
+    }
+    if let Some(theme) = previous_theme {
+        cx.global_mut::<GlobalTheme>().theme = theme;
+    }
+    result
+}
+
 // TODO: Make private, change usages to use `get_ui_font_size` instead.
 #[allow(missing_docs)]
 pub fn setup_ui_font(window: &mut Window, cx: &mut App) -> gpui::Font {
@@ -743,7 +743,10 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
                         &task
                             .await
                             .log_err()
-                            .map(|loaded_file| loaded_file.text)
+                            .map(|loaded_file| match loaded_file.large_text {
+                                Some((rope, _)) => rope.to_string(),
+                                None => loaded_file.text,
+                            })
                             .unwrap_or_default(),
                     );
                 }
@@ -2479,6 +2482,7 @@ impl ShellExec {
                     show_summary: false,
                     show_command: false,
                     show_rerun: false,
+                    problem_matchers: Vec::new(),
                 };
 
                 let task_status = workspace.spawn_in_terminal(spawn_in_terminal, window, cx);
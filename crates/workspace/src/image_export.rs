@@ -0,0 +1,185 @@
+use std::{io::Cursor, path::Path};
+
+use anyhow::{Context as _, Result};
+use base64::Engine as _;
+use gpui::{
+    Action, App, Context, IntoElement, ParentElement, Pixels, Size, Styled, Window, div, px,
+};
+use image::RgbaImage;
+use project::DirectoryLister;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use theme::{ActiveTheme, ThemeRegistry};
+use util::ResultExt;
+
+use crate::{ItemHandle, Workspace, notifications::NotifyTaskExt};
+
+/// Exports the active item as a PNG or SVG image, rendered offscreen. When the active item is
+/// an editor with a selection, only the selected lines are exported. SVG images embed the
+/// rendered pixels rather than vector graphics.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, JsonSchema, Action)]
+#[action(namespace = workspace)]
+#[serde(deny_unknown_fields)]
+pub struct ExportAsImage {
+    /// The name of the theme to render with. Defaults to the current theme.
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// The buffer font family to render with. Defaults to the configured one.
+    #[serde(default)]
+    pub buffer_font_family: Option<String>,
+    /// The buffer font size to render with, in pixels. Defaults to the configured one.
+    #[serde(default)]
+    pub buffer_font_size: Option<f32>,
+    /// How many image pixels to render for each logical pixel. Defaults to the window's
+    /// scale factor.
+    #[serde(default)]
+    pub scale_factor: Option<f32>,
+}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(
+        |workspace: &mut Workspace, _window, _cx: &mut Context<Workspace>| {
+            workspace.register_action(|workspace, action: &ExportAsImage, window, cx| {
+                workspace.export_active_item_as_image(action, window, cx);
+            });
+        },
+    )
+    .detach();
+}
+
+impl Workspace {
+    fn export_active_item_as_image(
+        &mut self,
+        action: &ExportAsImage,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(item) = self.active_item(cx) else {
+            return;
+        };
+        let item_size = self
+            .bounding_box_for_pane(&self.active_pane)
+            .map_or_else(|| window.viewport_size(), |bounds| bounds.size);
+        let image = match render_item_to_image(item.as_ref(), item_size, action, window, cx) {
+            Ok(image) => image,
+            Err(error) => {
+                self.show_error(&error, cx);
+                return;
+            }
+        };
+        let scale_factor = action.scale_factor.unwrap_or(window.scale_factor());
+
+        let fs = self.app_state.fs.clone();
+        let lister = DirectoryLister::Local(self.project.clone(), fs.clone());
+        let suggested_name = format!("{}.png", item.tab_content_text(0, cx));
+        let path = self.prompt_for_new_path(lister, Some(suggested_name), window, cx);
+        cx.spawn(async move |_, _| {
+            let Some(path) = path.await.ok().flatten().into_iter().flatten().next() else {
+                return Ok(());
+            };
+            let bytes = encode_image(&image, scale_factor, &path)?;
+            fs.write(&path, &bytes).await
+        })
+        .detach_and_notify_err(window, cx);
+    }
+}
+
+/// Renders an item offscreen with the theme and fonts of the given export options. Items that
+/// don't provide [`Item::image_export_content`](crate::Item::image_export_content) are rendered
+/// as a whole, at `item_size`.
+pub fn render_item_to_image(
+    item: &dyn ItemHandle,
+    item_size: Size<Pixels>,
+    options: &ExportAsImage,
+    window: &mut Window,
+    cx: &mut App,
+) -> Result<RgbaImage> {
+    let theme = options
+        .theme
+        .as_deref()
+        .map(|name| ThemeRegistry::global(cx).get(name))
+        .transpose()?;
+    let scale_factor = options.scale_factor.unwrap_or(window.scale_factor());
+    anyhow::ensure!(
+        scale_factor > 0.,
+        "the scale factor of an exported image must be positive"
+    );
+
+    theme::with_theme_overrides(
+        theme,
+        options.buffer_font_family.clone().map(Into::into),
+        options.buffer_font_size.map(px),
+        cx,
+        |cx| {
+            let (view, size) = item
+                .image_export_content(window, cx)
+                .unwrap_or_else(|| (item.to_any_view(), item_size));
+            let background = cx.theme().colors().editor_background;
+            window.render_element_to_image(size, scale_factor, cx, |_, _| {
+                div()
+                    .size_full()
+                    .bg(background)
+                    .child(view)
+                    .into_any_element()
+            })
+        },
+    )
+}
+
+/// Encodes an image in the format given by the extension of `path`. SVG images aren't vector
+/// graphics: they wrap the rendered pixels as an embedded PNG, sized so that they show at
+/// `scale_factor` image pixels per logical pixel, for tools that only accept SVG.
+pub fn encode_image(image: &RgbaImage, scale_factor: f32, path: &Path) -> Result<Vec<u8>> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    let mut bytes = Vec::new();
+    if is_svg {
+        image.write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+        let width = image.width() as f32 / scale_factor;
+        let height = image.height() as f32 / scale_factor;
+        let data = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        return Ok(format!(
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" ",
+                "viewBox=\"0 0 {width} {height}\">",
+                "<image width=\"{width}\" height=\"{height}\" href=\"data:image/png;base64,{data}\"/>",
+                "</svg>\n"
+            ),
+            width = width,
+            height = height,
+            data = data,
+        )
+        .into_bytes());
+    }
+
+    let format = image::ImageFormat::from_path(path)
+        .log_err()
+        .unwrap_or(image::ImageFormat::Png);
+    image
+        .write_to(&mut Cursor::new(&mut bytes), format)
+        .with_context(|| format!("encoding {}", path.display()))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_image() {
+        let mut rendered = RgbaImage::new(4, 2);
+        rendered.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+
+        let png = encode_image(&rendered, 2., Path::new("snippet.png")).unwrap();
+        let decoded = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(decoded, rendered);
+
+        let svg = encode_image(&rendered, 2., Path::new("snippet.SVG")).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"2\" height=\"1\"")
+        );
+        assert!(svg.contains("href=\"data:image/png;base64,"));
+    }
+}
@@ -6,7 +6,7 @@ use ui::{
     KeyBinding, Label, LabelCommon, LabelSize, ParentElement, Render, SharedString, Styled as _,
     Window, h_flex, v_flex,
 };
-use zed_actions::workspace::OpenWithSystem;
+use zed_actions::{hex_editor::OpenInHexEditor, workspace::OpenWithSystem};
 
 use crate::Item;
 
@@ -76,8 +76,9 @@ impl Focusable for InvalidItemView {
 }
 
 impl Render for InvalidItemView {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl gpui::IntoElement {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl gpui::IntoElement {
         let abs_path = self.abs_path.clone();
+        let can_open_in_hex_editor = window.is_action_available(&OpenInHexEditor, cx);
         v_flex()
             .size_full()
             .track_focus(&self.focus_handle(cx))
@@ -107,6 +108,18 @@ impl Render for InvalidItemView {
                                         .key_binding(KeyBinding::for_action(&OpenWithSystem, cx)),
                                 ),
                             )
+                        })
+                        .when(can_open_in_hex_editor, |contents| {
+                            contents.child(
+                                h_flex().justify_center().child(
+                                    Button::new("open-in-hex-editor", "Open in Hex Editor")
+                                        .on_click(|_, window, cx| {
+                                            window.dispatch_action(Box::new(OpenInHexEditor), cx);
+                                        })
+                                        .style(ButtonStyle::Outlined)
+                                        .key_binding(KeyBinding::for_action(&OpenInHexEditor, cx)),
+                                ),
+                            )
                         }),
                 ),
             )
@@ -13,7 +13,7 @@ use futures::{StreamExt, channel::mpsc};
 use gpui::{
     Action, AnyElement, AnyEntity, AnyView, App, AppContext, Context, Entity, EntityId,
     EventEmitter, FocusHandle, Focusable, Font, HighlightStyle, Pixels, Point, Render,
-    SharedString, Task, WeakEntity, Window,
+    SharedString, Size, Task, WeakEntity, Window,
 };
 use language::Capability;
 use project::{Project, ProjectEntryId, ProjectPath};
@@ -217,6 +217,9 @@ pub trait Item: Focusable + EventEmitter<Self::Event> + Render + Sized {
     fn deactivated(&mut self, _window: &mut Window, _: &mut Context<Self>) {}
     fn discarded(&self, _project: Entity<Project>, _window: &mut Window, _cx: &mut Context<Self>) {}
     fn on_removed(&self, _cx: &mut Context<Self>) {}
+    /// Called when the item is closed, unlike [`Self::on_removed`], which is also called when the
+    /// item is moved to another pane.
+    fn on_closed(&self, _cx: &mut Context<Self>) {}
     fn workspace_deactivated(&mut self, _window: &mut Window, _: &mut Context<Self>) {}
     fn navigate(
         &mut self,
@@ -356,6 +359,16 @@ pub trait Item: Focusable + EventEmitter<Self::Event> + Render + Sized {
         None
     }
 
+    /// Returns a view to render when exporting this item as an image, along with its size, for
+    /// items that export something other than their whole contents (e.g. just the selection).
+    fn image_export_content(
+        &self,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<(AnyView, Size<Pixels>)> {
+        None
+    }
+
     fn preserve_preview(&self, _cx: &App) -> bool {
         false
     }
@@ -493,6 +506,7 @@ pub trait ItemHandle: 'static + Send {
     );
     fn deactivated(&self, window: &mut Window, cx: &mut App);
     fn on_removed(&self, cx: &mut App);
+    fn on_closed(&self, cx: &mut App);
     fn workspace_deactivated(&self, window: &mut Window, cx: &mut App);
     fn navigate(&self, data: Arc<dyn Any + Send>, window: &mut Window, cx: &mut App) -> bool;
     fn item_id(&self) -> EntityId;
@@ -538,6 +552,11 @@ pub trait ItemHandle: 'static + Send {
     fn breadcrumb_prefix(&self, window: &mut Window, cx: &mut App) -> Option<gpui::AnyElement>;
     fn show_toolbar(&self, cx: &App) -> bool;
     fn pixel_position_of_cursor(&self, cx: &App) -> Option<Point<Pixels>>;
+    fn image_export_content(
+        &self,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<(AnyView, Size<Pixels>)>;
     fn downgrade_item(&self) -> Box<dyn WeakItemHandle>;
     fn workspace_settings<'a>(&self, cx: &'a App) -> &'a WorkspaceSettings;
     fn preserve_preview(&self, cx: &App) -> bool;
@@ -959,6 +978,10 @@ impl<T: Item> ItemHandle for Entity<T> {
         self.update(cx, |item, cx| item.on_removed(cx));
     }
 
+    fn on_closed(&self, cx: &mut App) {
+        self.update(cx, |item, cx| item.on_closed(cx));
+    }
+
     fn workspace_deactivated(&self, window: &mut Window, cx: &mut App) {
         self.update(cx, |this, cx| this.workspace_deactivated(window, cx));
     }
@@ -1074,6 +1097,14 @@ impl<T: Item> ItemHandle for Entity<T> {
         self.read(cx).pixel_position_of_cursor(cx)
     }
 
+    fn image_export_content(
+        &self,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<(AnyView, Size<Pixels>)> {
+        self.update(cx, |item, cx| item.image_export_content(window, cx))
+    }
+
     fn downgrade_item(&self) -> Box<dyn WeakItemHandle> {
         Box::new(self.downgrade())
     }
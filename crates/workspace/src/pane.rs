@@ -2670,6 +2670,7 @@ impl Pane {
                 // Remove the item from the pane.
                 if should_close {
                     pane.update_in(cx, |pane, window, cx| {
+                        item_to_close.on_closed(cx);
                         pane.remove_item(
                             item_to_close.item_id(),
                             false,
@@ -1,5 +1,6 @@
 pub mod dock;
 pub mod history_manager;
+mod image_export;
 pub mod invalid_item_view;
 pub mod item;
 mod modal_layer;
@@ -52,6 +53,7 @@ use gpui::{
     WindowId, WindowOptions, actions, canvas, point, relative, size, transparent_black,
 };
 pub use history_manager::*;
+pub use image_export::{ExportAsImage, encode_image, render_item_to_image};
 pub use item::{
     FollowableItem, FollowableItemHandle, Item, ItemHandle, ItemSettings, PreviewTabsSettings,
     ProjectItem, SerializableItem, SerializableItemHandle, WeakItemHandle,
@@ -917,6 +919,7 @@ pub fn init(app_state: Arc<AppState>, cx: &mut App) {
     toast_layer::init(cx);
     history_manager::init(cx);
     watch_folder::init(cx);
+    image_export::init(cx);
 
     cx.on_action(|_: &CloseWindow, cx| Workspace::close_global(cx))
         .on_action(|_: &Reload, cx| reload(cx))
@@ -104,9 +104,21 @@ pub enum CreatedEntry {
 #[derive(Debug)]
 pub struct LoadedFile {
     pub file: Arc<File>,
+    /// The file's contents. Empty when the file was loaded as [`LoadedFile::large_text`].
     pub text: String,
     pub encoding: &'static Encoding,
     pub has_bom: bool,
+    /// Set when the file exceeds the `large_file_threshold` setting: its contents, with
+    /// normalized line endings, read in chunks straight into a rope so that the file is
+    /// never held in a single string. Such files should be opened read-only and without
+    /// the features that don't scale to large files.
+    pub large_text: Option<(Rope, LineEnding)>,
+}
+
+impl LoadedFile {
+    pub fn is_large(&self) -> bool {
+        self.large_text.is_some()
+    }
 }
 
 pub struct LoadedBinaryFile {
@@ -1340,24 +1352,33 @@ impl LocalWorktree {
         let fs = self.fs.clone();
         let entry = self.refresh_entry(path.clone(), None, cx);
         let is_private = self.is_path_private(path.as_ref());
+        let settings = self.settings.clone();
 
         let this = cx.weak_entity();
         cx.background_spawn(async move {
-            // WARN: Temporary workaround for #27283.
-            //       We are not efficient with our memory usage per file, and use in excess of 64GB for a 10GB file
-            //       Therefore, as a temporary workaround to prevent system freezes, we just bail before opening a file
-            //       if it is too large
-            //       5GB seems to be more reasonable, peaking at ~16GB, while 6GB jumps up to >24GB which seems like a
-            //       reasonable limit
-            {
+            let metadata = fs.metadata(&abs_path).await.ok().flatten();
+            let is_large = metadata
+                .as_ref()
+                .is_some_and(|metadata| settings.is_large_file(metadata.len));
+            let (text, encoding, has_bom, large_text) = if is_large {
+                let (rope, line_ending, encoding, has_bom) =
+                    decode_large_file_text(fs.as_ref(), &abs_path).await?;
+                (String::new(), encoding, has_bom, Some((rope, line_ending)))
+            } else {
+                // WARN: Temporary workaround for #27283.
+                //       We are not efficient with our memory usage per file, and use in excess of 64GB for a 10GB file
+                //       Therefore, as a temporary workaround to prevent system freezes, we just bail before opening a file
+                //       if it is too large
+                //       5GB seems to be more reasonable, peaking at ~16GB, while 6GB jumps up to >24GB which seems like a
+                //       reasonable limit
+                //       Files in large file mode are streamed into a rope instead, and are not subject to this limit.
                 const FILE_SIZE_MAX: u64 = 6 * 1024 * 1024 * 1024; // 6GB
-                if let Ok(Some(metadata)) = fs.metadata(&abs_path).await
-                    && metadata.len >= FILE_SIZE_MAX
-                {
+                if metadata.is_some_and(|metadata| metadata.len >= FILE_SIZE_MAX) {
                     anyhow::bail!("File is too large to load");
                 }
-            }
-            let (text, encoding, has_bom) = decode_file_text(fs.as_ref(), &abs_path).await?;
+                let (text, encoding, has_bom) = decode_file_text(fs.as_ref(), &abs_path).await?;
+                (text, encoding, has_bom, None)
+            };
 
             let worktree = this.upgrade().context("worktree was dropped")?;
             let file = match entry.await? {
@@ -1390,6 +1411,7 @@ impl LocalWorktree {
                 text,
                 encoding,
                 has_bom,
+                large_text,
             })
         })
     }
@@ -5983,6 +6005,88 @@ async fn decode_file_text(
     decode_byte_full(content, bom_encoding, byte_content)
 }
 
+/// Decodes a file in chunks straight into a rope with normalized line endings, so that
+/// large files are never held in memory as a single string. Unlike [`decode_file_text`],
+/// the encoding is guessed from the first chunk of the file only.
+async fn decode_large_file_text(
+    fs: &dyn Fs,
+    abs_path: &Path,
+) -> Result<(Rope, LineEnding, &'static Encoding, bool)> {
+    const CHUNK_BYTES: usize = 1024 * 1024;
+
+    let mut file = fs
+        .open_sync(&abs_path)
+        .await
+        .with_context(|| format!("opening file {abs_path:?}"))?;
+
+    let mut bytes = vec![0u8; CHUNK_BYTES];
+    let mut len = 0;
+    while len < bytes.len() {
+        let n = file
+            .read(&mut bytes[len..])
+            .with_context(|| format!("reading bytes of the file {abs_path:?}"))?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+    }
+
+    let (bom_encoding, byte_content) = decode_byte_header(&bytes[..len]);
+    let (encoding, has_bom) = match (bom_encoding, byte_content) {
+        (Some(encoding), _) => (encoding, true),
+        (None, ByteContent::Utf16Le) => (encoding_rs::UTF_16LE, false),
+        (None, ByteContent::Utf16Be) => (encoding_rs::UTF_16BE, false),
+        (None, ByteContent::Binary) => anyhow::bail!("Binary files are not supported"),
+        (None, ByteContent::Unknown) => {
+            let mut detector = EncodingDetector::new();
+            detector.feed(&bytes[..len], len < bytes.len());
+            (detector.guess(None, true), false)
+        }
+    };
+    let mut decoder = if has_bom {
+        encoding.new_decoder_with_bom_removal()
+    } else {
+        encoding.new_decoder_without_bom_handling()
+    };
+
+    let mut rope = Rope::new();
+    let mut line_ending = None;
+    let mut text = String::new();
+    let mut pending_carriage_return = false;
+    loop {
+        let is_last = len == 0;
+        text.clear();
+        if pending_carriage_return {
+            text.push('\r');
+        }
+        text.reserve(
+            decoder
+                .max_utf8_buffer_length(len)
+                .context("file chunk is too large to decode")?,
+        );
+        decoder.decode_to_string(&bytes[..len], &mut text, is_last);
+
+        // Hold back a trailing carriage return, as it may be the start of a CRLF line ending
+        // that continues in the next chunk.
+        pending_carriage_return = !is_last && text.ends_with('\r');
+        if pending_carriage_return {
+            text.pop();
+        }
+        line_ending.get_or_insert_with(|| LineEnding::detect(&text));
+        LineEnding::normalize(&mut text);
+        rope.push(&text);
+
+        if is_last {
+            break;
+        }
+        len = file
+            .read(&mut bytes)
+            .with_context(|| format!("reading remaining bytes of the file {abs_path:?}"))?;
+    }
+
+    Ok((rope, line_ending.unwrap_or_default(), encoding, has_bom))
+}
+
 fn decode_byte_header(prefix: &[u8]) -> (Option<&'static Encoding>, ByteContent) {
     if let Some((encoding, _bom_len)) = Encoding::for_bom(prefix) {
         return (Some(encoding), ByteContent::Unknown);
@@ -21,6 +21,8 @@ pub struct WorktreeSettings {
     pub private_files: PathMatcher,
     pub hidden_files: PathMatcher,
     pub read_only_files: PathMatcher,
+    /// Files at least this many bytes long are opened in large file mode.
+    pub large_file_threshold: u64,
 }
 
 impl WorktreeSettings {
@@ -54,6 +56,10 @@ impl WorktreeSettings {
     pub fn is_std_path_read_only(&self, path: &Path) -> bool {
         self.read_only_files.is_match_std_path(path)
     }
+
+    pub fn is_large_file(&self, len: u64) -> bool {
+        len >= self.large_file_threshold
+    }
 }
 
 impl Settings for WorktreeSettings {
@@ -97,6 +103,7 @@ impl Settings for WorktreeSettings {
             read_only_files: path_matchers(read_only_files, "read_only_files")
                 .log_err()
                 .unwrap_or_default(),
+            large_file_threshold: worktree.large_file_threshold.unwrap(),
         }
     }
 }
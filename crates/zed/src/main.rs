@@ -224,6 +224,35 @@ fn main() {
         }
     }
 
+    // `zublime --attach-terminal-session` Makes Zublime relay a terminal to a persistent session
+    #[cfg(unix)]
+    if let Some(session_id) = &args.attach_terminal_session {
+        let result = std::env::current_exe()
+            .map_err(anyhow::Error::from)
+            .and_then(|binary| {
+                terminal::sessions::attach(session_id, |socket| {
+                    let mut command = process::Command::new(&binary);
+                    command.arg("--terminal-session-host").arg(socket);
+                    command
+                })
+            });
+        if let Err(err) = result {
+            eprintln!("Error: {err:#}");
+            process::exit(1);
+        }
+        return;
+    }
+
+    // `zublime --terminal-session-host` Makes Zublime host persistent terminal sessions
+    #[cfg(unix)]
+    if let Some(socket) = &args.terminal_session_host {
+        if let Err(err) = terminal::sessions::run_host(socket) {
+            eprintln!("Error: {err:#}");
+            process::exit(1);
+        }
+        return;
+    }
+
     #[cfg(all(not(debug_assertions), target_os = "windows"))]
     unsafe {
         use windows::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
@@ -658,6 +687,7 @@ fn main() {
 
         editor::init(cx);
         image_viewer::init(cx);
+        hex_editor::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
@@ -679,6 +709,8 @@ fn main() {
         vim::init(cx);
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
+        local_history::init(app_state.clone(), cx);
+        spell_check::init(app_state.fs.clone(), cx);
         encoding_selector::init(cx);
         language_selector::init(cx);
         line_ending_selector::init(cx);
@@ -1569,6 +1601,17 @@ struct Args {
     #[arg(hide = true)]
     askpass: Option<String>,
 
+    /// Used for persistent terminal sessions, by having Zublime relay the terminal it runs in
+    /// to the session with the given id.
+    #[arg(long, hide = true)]
+    #[cfg(unix)]
+    attach_terminal_session: Option<String>,
+
+    /// Used for persistent terminal sessions, by having Zublime host them on the given socket.
+    #[arg(long, hide = true)]
+    #[cfg(unix)]
+    terminal_session_host: Option<PathBuf>,
+
     #[arg(long, hide = true)]
     dump_all_actions: bool,
 
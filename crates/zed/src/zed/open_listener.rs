@@ -458,7 +458,39 @@ pub async fn handle_cli_connection(
                 reuse,
                 env,
                 user_data_dir: _,
+                render_to_image,
             } => {
+                if let Some(image_path) = render_to_image {
+                    let status = match render_paths_to_image(
+                        paths,
+                        PathBuf::from(image_path),
+                        app_state.clone(),
+                        env,
+                        cx,
+                    )
+                    .await
+                    {
+                        Ok(()) => 0,
+                        Err(error) => {
+                            responses
+                                .send(CliResponse::Stderr {
+                                    message: format!("{error:#}"),
+                                })
+                                .log_err();
+                            1
+                        }
+                    };
+                    responses.send(CliResponse::Exit { status }).log_err();
+                    // Without a display, Zed was only started to render the image, and the user
+                    // has no way of quitting it.
+                    cx.update(|cx| {
+                        if cx.displays().is_empty() && cx.windows().is_empty() {
+                            cx.quit();
+                        }
+                    });
+                    return;
+                }
+
                 if !urls.is_empty() {
                     cx.update(|cx| {
                         match OpenRequest::parse(
@@ -508,6 +540,71 @@ pub async fn handle_cli_connection(
     }
 }
 
+/// Opens `paths` in a new window, renders its first item to an image at `image_path`, and
+/// closes the window again. When there is no display, the window is opened offscreen and drawn
+/// by the software renderer.
+async fn render_paths_to_image(
+    paths: Vec<String>,
+    image_path: PathBuf,
+    app_state: Arc<AppState>,
+    env: Option<collections::HashMap<String, String>>,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let paths_with_position = derive_paths_with_position(app_state.fs.as_ref(), paths).await;
+    let (workspace, items) = open_paths_with_positions(
+        &paths_with_position,
+        &[],
+        false,
+        app_state.clone(),
+        OpenOptions {
+            open_new_workspace: Some(true),
+            focus: Some(false),
+            env,
+            ..Default::default()
+        },
+        cx,
+    )
+    .await?;
+    let item = items
+        .into_iter()
+        .flatten()
+        .next()
+        .context("no file to render was given")??;
+
+    // Wait for syntax highlighting, so that it shows up in the image.
+    let parsing_idle = cx.update(|cx| {
+        let buffer = item
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .as_singleton()?;
+        Some(buffer.read(cx).parsing_idle())
+    });
+    if let Some(parsing_idle) = parsing_idle {
+        parsing_idle.await;
+    }
+
+    let result = workspace.update(cx, |workspace, window, cx| {
+        let item_size = workspace
+            .bounding_box_for_pane(workspace.active_pane())
+            .map_or_else(|| window.viewport_size(), |bounds| bounds.size);
+        let options = workspace::ExportAsImage::default();
+        let image =
+            workspace::render_item_to_image(item.as_ref(), item_size, &options, window, cx)?;
+        workspace::encode_image(&image, window.scale_factor(), &image_path)
+    });
+    workspace
+        .update(cx, |_, window, _| window.remove_window())
+        .log_err();
+    let bytes = result??;
+    app_state
+        .fs
+        .write(&image_path, &bytes)
+        .await
+        .with_context(|| format!("writing {}", image_path.display()))
+}
+
 async fn open_workspaces(
     paths: Vec<String>,
     diff_paths: Vec<[String; 2]>,
@@ -163,6 +163,7 @@ fn send_args_to_instance(args: &Args) -> anyhow::Result<()> {
             reuse: false,
             env: None,
             user_data_dir: args.user_data_dir.clone(),
+            render_to_image: None,
         }
     };
 
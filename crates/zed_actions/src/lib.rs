@@ -243,6 +243,18 @@ pub mod workspace {
     );
 }
 
+pub mod hex_editor {
+    use gpui::actions;
+
+    actions!(
+        hex_editor,
+        [
+            /// Opens the current file in the hex editor.
+            OpenInHexEditor
+        ]
+    );
+}
+
 pub mod git {
     use gpui::actions;
 